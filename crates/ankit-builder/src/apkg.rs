@@ -1,8 +1,13 @@
 //! .apkg file generation.
 //!
 //! Creates Anki package files that can be imported directly into Anki.
+//!
+//! By default note and card IDs come from the current time, so every build
+//! differs. [`ApkgBuilder::stable_ids`] and [`ApkgBuilder::timestamp`] make
+//! the output byte-identical across builds of the same definition, for
+//! reproducible releases and golden-file tests.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashSet};
 use std::io::Write;
 use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -13,13 +18,16 @@ use zip::ZipWriter;
 use zip::write::SimpleFileOptions;
 
 use crate::error::Result;
-use crate::schema::DeckDefinition;
+use crate::model_version;
+use crate::schema::{DeckDef, DeckDefinition, DeckOptions};
 use crate::sql::{DEFAULT_CONF, DEFAULT_DCONF, FIELD_SEPARATOR, SCHEMA};
 
 /// Builder for creating .apkg files from deck definitions.
 pub struct ApkgBuilder {
     definition: DeckDefinition,
     media_base_path: Option<std::path::PathBuf>,
+    stable_ids: bool,
+    timestamp: Option<i64>,
 }
 
 impl ApkgBuilder {
@@ -28,6 +36,8 @@ impl ApkgBuilder {
         Self {
             definition,
             media_base_path: None,
+            stable_ids: false,
+            timestamp: None,
         }
     }
 
@@ -37,8 +47,32 @@ impl ApkgBuilder {
         self
     }
 
+    /// Derive note and card IDs (and GUIDs of notes without one) from note
+    /// content instead of the current time.
+    ///
+    /// A note's ID is a hash of its GUID if set, otherwise of its model and
+    /// field values, so IDs survive reordering notes in the definition.
+    pub fn stable_ids(mut self, enabled: bool) -> Self {
+        self.stable_ids = enabled;
+        self
+    }
+
+    /// Use a fixed creation and modification time (Unix seconds) instead of
+    /// the current time.
+    ///
+    /// Together with [`stable_ids`](Self::stable_ids) this makes the package
+    /// byte-identical for the same definition and media.
+    pub fn timestamp(mut self, secs: i64) -> Self {
+        self.timestamp = Some(secs);
+        self
+    }
+
     /// Build the .apkg file and write it to the specified path.
+    ///
+    /// If the package has a description, a README.html for shared deck
+    /// listings is written to the same directory.
     pub fn write_to_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
         let temp_dir = TempDir::new()?;
         let db_path = temp_dir.path().join("collection.anki2");
 
@@ -51,8 +85,12 @@ impl ApkgBuilder {
         let mut zip = ZipWriter::new(file);
 
         // Add the database file
-        let options =
+        let mut options =
             SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        if self.timestamp.is_some() {
+            // Entry times would otherwise depend on when the package was built
+            options = options.last_modified_time(zip::DateTime::default());
+        }
         zip.start_file("collection.anki2", options)?;
         let db_bytes = std::fs::read(&db_path)?;
         zip.write_all(&db_bytes)?;
@@ -64,13 +102,20 @@ impl ApkgBuilder {
 
         // Add media files with numeric names
         for (index, media) in self.definition.media.iter().enumerate() {
-            let source_path = self.resolve_media_path(&media.path)?;
-            let content = std::fs::read(&source_path)?;
+            let content = match media.data {
+                Some(ref data) => data.as_bytes().to_vec(),
+                None => std::fs::read(self.resolve_media_path(&media.path)?)?,
+            };
             zip.start_file(index.to_string(), options)?;
             zip.write_all(&content)?;
         }
 
         zip.finish()?;
+
+        if let Some(readme) = self.definition.package.readme_html() {
+            let dir = path.parent().unwrap_or(Path::new(""));
+            std::fs::write(dir.join("README.html"), readme)?;
+        }
         Ok(())
     }
 
@@ -80,39 +125,33 @@ impl ApkgBuilder {
         conn.execute_batch(SCHEMA)?;
 
         // Generate timestamps and IDs
-        let now = current_timestamp();
+        let now = self.timestamp.unwrap_or_else(current_timestamp);
         let now_ms = now * 1000;
 
         // Build model and deck JSON
-        let models_json = self.build_models_json(now);
+        let models_json = self.build_models_json(now)?;
         let decks_json = self.build_decks_json(now);
+        let dconf_json = self.build_dconf_json(now);
 
         // Insert collection row
         conn.execute(
             "INSERT INTO col (id, crt, mod, scm, ver, dty, usn, ls, conf, models, decks, dconf, tags)
              VALUES (1, ?, ?, ?, 11, 0, -1, 0, ?, ?, ?, ?, '{}')",
-            rusqlite::params![now, now_ms, now_ms, DEFAULT_CONF, models_json, decks_json, DEFAULT_DCONF],
+            rusqlite::params![now, now_ms, now_ms, DEFAULT_CONF, models_json, decks_json, dconf_json],
         )?;
 
         // Insert notes and cards
-        let mut note_id_gen = now_ms;
-        let mut card_id_gen = now_ms;
+        let mut position = 0;
+        let due_base = if self.stable_ids { 0 } else { now_ms };
+        let mut used_note_ids = HashSet::new();
+        let mut used_card_ids = HashSet::new();
 
-        for note_def in &self.definition.notes {
+        for (index, note_def) in self.definition.notes.iter().enumerate() {
             let model = self.definition.get_model(&note_def.model).unwrap();
             let deck = self.definition.get_deck(&note_def.deck).unwrap();
             let deck_id = deck.id.unwrap_or_else(|| generate_id(&deck.name));
             let model_id = model.id.unwrap_or_else(|| generate_id(&model.name));
 
-            // Insert note
-            let note_id = note_id_gen;
-            note_id_gen += 1;
-
-            let guid = note_def
-                .guid
-                .clone()
-                .unwrap_or_else(|| generate_guid(note_id));
-
             // Convert markdown fields to HTML before storing
             let html_fields = note_def.fields_as_html(&model.markdown_fields);
             let fields_str = model
@@ -121,6 +160,21 @@ impl ApkgBuilder {
                 .map(|f| html_fields.get(f).cloned().unwrap_or_default())
                 .collect::<Vec<_>>()
                 .join(&FIELD_SEPARATOR.to_string());
+
+            // Insert note
+            let note_id = if self.stable_ids {
+                let content = match note_def.guid {
+                    Some(ref guid) => content_id(&["guid", guid]),
+                    None => content_id(&["note", &model.name, &fields_str]),
+                };
+                unique_id(&mut used_note_ids, content)
+            } else {
+                now_ms + index as i64
+            };
+            let guid = note_def
+                .guid
+                .clone()
+                .unwrap_or_else(|| generate_guid(note_id));
             let sort_field = note_def
                 .fields_ordered(model)
                 .get(model.sort_field_index())
@@ -143,15 +197,28 @@ impl ApkgBuilder {
                 ],
             )?;
 
-            // Insert cards (one per template)
-            for (ord, _template) in model.templates.iter().enumerate() {
-                let card_id = card_id_gen;
-                card_id_gen += 1;
+            // Insert cards (one per selected template)
+            for (ord, template) in model.templates.iter().enumerate() {
+                if !note_def.generates_card(&template.name) {
+                    continue;
+                }
+                let queue = if note_def.suspends_card(&template.name) {
+                    -1
+                } else {
+                    0
+                };
+                let card_id = if self.stable_ids {
+                    let content = content_id(&["card", &note_id.to_string(), &ord.to_string()]);
+                    unique_id(&mut used_card_ids, content)
+                } else {
+                    now_ms + position
+                };
+                position += 1;
 
                 conn.execute(
                     "INSERT INTO cards (id, nid, did, ord, mod, usn, type, queue, due, ivl, factor, reps, lapses, left, odue, odid, flags, data)
-                     VALUES (?, ?, ?, ?, ?, -1, 0, 0, ?, 0, 0, 0, 0, 0, 0, 0, 0, '')",
-                    rusqlite::params![card_id, note_id, deck_id, ord as i64, now, card_id_gen],
+                     VALUES (?, ?, ?, ?, ?, -1, 0, ?, ?, 0, 0, 0, 0, 0, 0, 0, 0, '')",
+                    rusqlite::params![card_id, note_id, deck_id, ord as i64, now, queue, due_base + position],
                 )?;
             }
         }
@@ -160,11 +227,17 @@ impl ApkgBuilder {
     }
 
     /// Build the models JSON for the col table.
-    fn build_models_json(&self, now: i64) -> String {
-        let mut models: HashMap<String, serde_json::Value> = HashMap::new();
+    fn build_models_json(&self, now: i64) -> Result<String> {
+        let mut models: BTreeMap<String, serde_json::Value> = BTreeMap::new();
 
         for model in &self.definition.models {
             let model_id = model.id.unwrap_or_else(|| generate_id(&model.name));
+            let css = self
+                .definition
+                .model_css(model)?
+                .unwrap_or_else(default_css);
+            // Record the version, so syncing a later version can update it
+            let css = model_version::stamp_model(model, css);
 
             let fields: Vec<serde_json::Value> = model
                 .fields
@@ -212,7 +285,7 @@ impl ApkgBuilder {
                 "did": null,
                 "tmpls": templates,
                 "flds": fields,
-                "css": model.css.clone().unwrap_or_else(default_css),
+                "css": css,
                 "latexPre": "\\documentclass[12pt]{article}\n\\special{papersize=3in,5in}\n\\usepackage{amssymb,amsmath}\n\\pagestyle{empty}\n\\setlength{\\parindent}{0in}\n\\begin{document}\n",
                 "latexPost": "\\end{document}",
                 "latexsvg": false,
@@ -222,12 +295,12 @@ impl ApkgBuilder {
             models.insert(model_id.to_string(), model_obj);
         }
 
-        serde_json::to_string(&models).unwrap()
+        Ok(serde_json::to_string(&models).unwrap())
     }
 
     /// Build the decks JSON for the col table.
     fn build_decks_json(&self, now: i64) -> String {
-        let mut decks: HashMap<String, serde_json::Value> = HashMap::new();
+        let mut decks: BTreeMap<String, serde_json::Value> = BTreeMap::new();
 
         // Always include the default deck
         decks.insert(
@@ -251,8 +324,16 @@ impl ApkgBuilder {
             }),
         );
 
+        let package_description = self.definition.package.description_html();
         for deck in &self.definition.decks {
             let deck_id = deck.id.unwrap_or_else(|| generate_id(&deck.name));
+            // Top-level decks show the package description unless they have
+            // their own, so shared decks explain themselves after import
+            let description = match (&deck.description, &package_description) {
+                (Some(own), _) => own.clone(),
+                (None, Some(package)) if !deck.name.contains("::") => package.clone(),
+                _ => String::new(),
+            };
             let deck_obj = serde_json::json!({
                 "id": deck_id,
                 "mod": now,
@@ -264,9 +345,9 @@ impl ApkgBuilder {
                 "timeToday": [0, 0],
                 "collapsed": false,
                 "browserCollapsed": false,
-                "desc": deck.description.clone().unwrap_or_default(),
+                "desc": description,
                 "dyn": 0,
-                "conf": 1,
+                "conf": deck_conf_id(deck),
                 "extendNew": 10,
                 "extendRev": 50
             });
@@ -277,9 +358,35 @@ impl ApkgBuilder {
         serde_json::to_string(&decks).unwrap()
     }
 
+    /// Build the deck options JSON for the col table.
+    ///
+    /// Decks with `[decks.options]` get their own options group (shared by
+    /// decks naming the same group); all others use the default group.
+    fn build_dconf_json(&self, now: i64) -> String {
+        let mut dconf: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(DEFAULT_DCONF).unwrap();
+        let default = dconf["1"].clone();
+
+        for deck in &self.definition.decks {
+            if let Some(ref options) = deck.options {
+                let conf_id = deck_conf_id(deck);
+                dconf.entry(conf_id.to_string()).or_insert_with(|| {
+                    let mut conf = default.clone();
+                    conf["id"] = conf_id.into();
+                    conf["name"] = options.group_name(&deck.name).into();
+                    conf["mod"] = now.into();
+                    apply_deck_options(&mut conf, options);
+                    conf
+                });
+            }
+        }
+
+        serde_json::to_string(&dconf).unwrap()
+    }
+
     /// Build the media manifest JSON.
     fn build_media_manifest(&self) -> Result<String> {
-        let manifest: HashMap<String, &str> = self
+        let manifest: BTreeMap<String, &str> = self
             .definition
             .media
             .iter()
@@ -303,6 +410,48 @@ impl ApkgBuilder {
     }
 }
 
+/// ID of the options group used by a deck.
+fn deck_conf_id(deck: &DeckDef) -> i64 {
+    match deck.options {
+        Some(ref options) => generate_id(options.group_name(&deck.name)),
+        None => 1,
+    }
+}
+
+/// Overwrite the values set in `options` on a deck options JSON object.
+fn apply_deck_options(conf: &mut serde_json::Value, options: &DeckOptions) {
+    if let Some(n) = options.new_per_day {
+        conf["new"]["perDay"] = n.into();
+    }
+    if let Some(ref steps) = options.learning_steps {
+        conf["new"]["delays"] = steps.clone().into();
+    }
+    if let Some(n) = options.graduating_interval {
+        conf["new"]["ints"][0] = n.into();
+    }
+    if let Some(n) = options.easy_interval {
+        conf["new"]["ints"][1] = n.into();
+    }
+    if let Some(n) = options.reviews_per_day {
+        conf["rev"]["perDay"] = n.into();
+    }
+    if let Some(n) = options.maximum_interval {
+        conf["rev"]["maxIvl"] = n.into();
+    }
+    if let Some(ref steps) = options.relearning_steps {
+        conf["lapse"]["delays"] = steps.clone().into();
+    }
+    if let Some(n) = options.lapse_minimum_interval {
+        conf["lapse"]["minInt"] = n.into();
+    }
+    if let Some(n) = options.leech_threshold {
+        conf["lapse"]["leechFails"] = n.into();
+    }
+    if let Some(action) = options.leech_action_code() {
+        conf["lapse"]["leechAction"] = action.into();
+    }
+}
+
 /// Get current Unix timestamp in seconds.
 fn current_timestamp() -> i64 {
     SystemTime::now()
@@ -322,6 +471,28 @@ fn generate_id(name: &str) -> i64 {
     (hasher.finish() & 0x7FFF_FFFF_FFFF) as i64
 }
 
+/// Hash content into a positive ID that doesn't depend on the toolchain.
+fn content_id(parts: &[&str]) -> i64 {
+    // 64-bit FNV-1a, with a separator so ["ab", "c"] and ["a", "bc"] differ
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        for byte in part.bytes().chain([0]) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    // Same range as generate_id, and never 0
+    ((hash & 0x7FFF_FFFF_FFFF) as i64).max(1)
+}
+
+/// Take the first ID from `id` upward that isn't in `used`.
+fn unique_id(used: &mut HashSet<i64>, mut id: i64) -> i64 {
+    while !used.insert(id) {
+        id += 1;
+    }
+    id
+}
+
 /// Generate a GUID for a note.
 fn generate_guid(note_id: i64) -> String {
     // Base91 encoding similar to Anki
@@ -427,6 +598,18 @@ mod tests {
         assert_ne!(id, generate_id("Other Model"));
     }
 
+    #[test]
+    fn test_content_id() {
+        let id = content_id(&["note", "Basic", "Question"]);
+        assert!(id > 0);
+        assert_eq!(id, content_id(&["note", "Basic", "Question"]));
+        assert_ne!(content_id(&["ab", "c"]), content_id(&["a", "bc"]));
+
+        let mut used = HashSet::new();
+        assert_eq!(unique_id(&mut used, 5), 5);
+        assert_eq!(unique_id(&mut used, 5), 6);
+    }
+
     #[test]
     fn test_strip_html() {
         assert_eq!(strip_html("<b>Hello</b> World"), "Hello World");
@@ -478,5 +661,69 @@ Back = "Answer"
         let file_names: Vec<_> = archive.file_names().collect();
         assert!(file_names.contains(&"collection.anki2"));
         assert!(file_names.contains(&"media"));
+        // No package description, no README
+        assert!(!dir.path().join("README.html").exists());
+    }
+
+    #[test]
+    fn test_package_description_embedded() {
+        let toml = r#"
+[package]
+name = "Spanish"
+version = "2.1.0"
+author = "Ana"
+description = "Core **Spanish** vocabulary."
+license = "CC BY-SA 4.0"
+tags = ["language", "spanish"]
+support_url = "https://example.com/issues"
+
+[[models]]
+name = "Basic"
+fields = ["Front", "Back"]
+
+[[models.templates]]
+name = "Card 1"
+front = "{{Front}}"
+back = "{{Back}}"
+
+[[decks]]
+name = "Spanish"
+
+[[decks]]
+name = "Spanish::Verbs"
+
+[[decks]]
+name = "Extra"
+description = "Bonus cards"
+"#;
+
+        let def = DeckDefinition::parse(toml).unwrap();
+        let builder = ApkgBuilder::new(def);
+        let decks: BTreeMap<String, serde_json::Value> =
+            serde_json::from_str(&builder.build_decks_json(0)).unwrap();
+        let desc = |name: &str| {
+            decks
+                .values()
+                .find(|d| d["name"] == name)
+                .map(|d| d["desc"].as_str().unwrap().to_string())
+                .unwrap()
+        };
+
+        let top = desc("Spanish");
+        assert!(top.contains("<strong>Spanish</strong>"));
+        assert!(top.contains("Version 2.1.0"));
+        assert!(top.contains("License: CC BY-SA 4.0"));
+        assert!(top.contains("href=\"https://example.com/issues\""));
+        assert!(top.contains("Tags: language, spanish"));
+        assert_eq!(desc("Spanish::Verbs"), "");
+        assert_eq!(desc("Extra"), "Bonus cards");
+
+        let dir = tempdir().unwrap();
+        builder
+            .write_to_file(dir.path().join("spanish.apkg"))
+            .unwrap();
+        let readme = std::fs::read_to_string(dir.path().join("README.html")).unwrap();
+        assert!(readme.contains("<title>Spanish</title>"));
+        assert!(readme.contains(&top));
     }
 }
@@ -0,0 +1,324 @@
+//! Last-synced snapshots for three-way merge.
+//!
+//! A [`SyncBase`] records every synced note as it was on both sides at the
+//! end of a sync. On the next sync it serves as the common ancestor: a field
+//! changed only in TOML is pushed, a field changed only in Anki is pulled,
+//! and only fields changed on both sides are reported as conflicts. Tags
+//! are merged the same way and never conflict.
+//!
+//! The snapshot lives next to the TOML file, at
+//! [`SyncBase::path_for`]`("deck.toml")` = `deck.sync-base.toml`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ankit_builder::{DeckBuilder, SyncBase, SyncStrategy};
+//!
+//! # async fn example() -> ankit_builder::Result<()> {
+//! let base_path = SyncBase::path_for("deck.toml");
+//! let mut builder = DeckBuilder::from_file("deck.toml")?;
+//! if base_path.exists() {
+//!     builder = builder.sync_base(SyncBase::from_file(&base_path)?);
+//! }
+//!
+//! let result = builder.sync(SyncStrategy::bidirectional()).await?;
+//! if let Some(updated) = &result.updated_definition {
+//!     updated.write_toml("deck.toml")?;
+//! }
+//! if let Some(base) = &result.base {
+//!     base.write(&base_path)?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use ankit::AnkiClient;
+use serde::{Deserialize, Serialize};
+
+use crate::diff::{FieldChange, MatchedNote, ModifiedNote, TagChanges};
+use crate::error::{Error, Result};
+use crate::guid;
+use crate::schema::DeckDefinition;
+
+/// Snapshot of every synced note at the end of the last sync.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncBase {
+    /// Synced notes.
+    #[serde(default)]
+    pub notes: Vec<BaseNote>,
+}
+
+/// A note as it was in TOML and in Anki after the last sync.
+///
+/// Both sides are kept because they can legitimately differ, such as
+/// Markdown fields that are stored as HTML in Anki.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaseNote {
+    /// The Anki note ID.
+    pub note_id: i64,
+    /// Field values in TOML.
+    #[serde(default)]
+    pub fields: HashMap<String, String>,
+    /// Field values in Anki.
+    #[serde(default)]
+    pub anki_fields: HashMap<String, String>,
+    /// Tags in TOML.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Tags in Anki, without the GUID tag.
+    #[serde(default)]
+    pub anki_tags: Vec<String>,
+}
+
+impl SyncBase {
+    /// Where the snapshot for a TOML file is stored: `deck.toml` becomes
+    /// `deck.sync-base.toml` in the same directory.
+    pub fn path_for(toml_path: impl AsRef<Path>) -> PathBuf {
+        let path = toml_path.as_ref();
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        path.with_file_name(format!("{}.sync-base.toml", stem))
+    }
+
+    /// Load a snapshot from a TOML file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(toml::from_str(&content)?)
+    }
+
+    /// Write the snapshot to a TOML file.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let content =
+            toml::to_string_pretty(self).map_err(|e| Error::TomlSerialize(e.to_string()))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// The snapshot of an Anki note, if it was synced before.
+    pub fn get(&self, note_id: i64) -> Option<&BaseNote> {
+        self.notes.iter().find(|n| n.note_id == note_id)
+    }
+
+    /// Snapshot the given pairs of definition notes and Anki notes.
+    pub(crate) async fn capture(
+        client: &AnkiClient,
+        definition: &DeckDefinition,
+        synced: &[MatchedNote],
+    ) -> Result<Self> {
+        let note_ids: Vec<i64> = synced.iter().map(|m| m.note_id).collect();
+        if note_ids.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let infos: HashMap<i64, ankit::NoteInfo> = client
+            .notes()
+            .info(&note_ids)
+            .await?
+            .into_iter()
+            .map(|info| (info.note_id, info))
+            .collect();
+
+        let mut notes = Vec::with_capacity(synced.len());
+        for pair in synced {
+            let (Some(note), Some(info)) =
+                (definition.notes.get(pair.index), infos.get(&pair.note_id))
+            else {
+                continue;
+            };
+            let (_, anki_tags) = guid::split_tags(info.tags.clone());
+            notes.push(BaseNote {
+                note_id: pair.note_id,
+                fields: note.fields.clone(),
+                anki_fields: info
+                    .fields
+                    .iter()
+                    .map(|(name, field)| (name.clone(), field.value.clone()))
+                    .collect(),
+                tags: note.tags.clone(),
+                anki_tags,
+            });
+        }
+        Ok(Self { notes })
+    }
+}
+
+/// A modified note split into the parts that merge cleanly and the fields
+/// that changed on both sides.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Merge {
+    /// Fields changed only in TOML.
+    pub push_fields: Vec<FieldChange>,
+    /// Fields changed only in Anki.
+    pub pull_fields: Vec<FieldChange>,
+    /// Tags to add to and remove from Anki.
+    pub push_tags: TagChanges,
+    /// Tags to add to and remove from TOML.
+    pub pull_tags: TagChanges,
+    /// Fields changed on both sides.
+    pub conflicts: Vec<FieldChange>,
+}
+
+impl Merge {
+    /// Whether anything merges cleanly.
+    pub fn has_changes(&self) -> bool {
+        !self.push_fields.is_empty()
+            || !self.pull_fields.is_empty()
+            || !self.push_tags.is_empty()
+            || !self.pull_tags.is_empty()
+    }
+}
+
+/// Three-way merge a modified note against its last-synced snapshot.
+pub(crate) fn merge(modified: &ModifiedNote, base: &BaseNote) -> Merge {
+    let mut merge = Merge::default();
+
+    for change in &modified.field_changes {
+        let toml_changed = base.fields.get(&change.field) != Some(&change.toml_value);
+        let anki_changed = base.anki_fields.get(&change.field) != Some(&change.anki_value);
+        match (toml_changed, anki_changed) {
+            (true, false) => merge.push_fields.push(change.clone()),
+            (false, true) => merge.pull_fields.push(change.clone()),
+            (true, true) => merge.conflicts.push(change.clone()),
+            // Differed at the last sync too, e.g. a Markdown field
+            (false, false) => {}
+        }
+    }
+
+    // Tags in TOML but not Anki were either added in TOML or removed in Anki
+    for tag in &modified.tag_changes.added {
+        if base.anki_tags.contains(tag) {
+            merge.pull_tags.removed.push(tag.clone());
+        } else {
+            merge.push_tags.added.push(tag.clone());
+        }
+    }
+    // Tags in Anki but not TOML were either added in Anki or removed in TOML
+    for tag in &modified.tag_changes.removed {
+        if base.tags.contains(tag) {
+            merge.push_tags.removed.push(tag.clone());
+        } else {
+            merge.pull_tags.added.push(tag.clone());
+        }
+    }
+
+    merge
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(field: &str, toml_value: &str, anki_value: &str) -> FieldChange {
+        FieldChange {
+            field: field.to_string(),
+            toml_value: toml_value.to_string(),
+            anki_value: anki_value.to_string(),
+        }
+    }
+
+    fn base() -> BaseNote {
+        let fields = HashMap::from([
+            ("Front".to_string(), "perro".to_string()),
+            ("Back".to_string(), "dog".to_string()),
+            ("Notes".to_string(), "**noun**".to_string()),
+        ]);
+        let mut anki_fields = fields.clone();
+        anki_fields.insert("Notes".to_string(), "<b>noun</b>".to_string());
+        BaseNote {
+            note_id: 1,
+            fields,
+            anki_fields,
+            tags: vec!["animals".to_string(), "old".to_string()],
+            anki_tags: vec!["animals".to_string(), "old".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_merge_fields() {
+        let modified = ModifiedNote {
+            note_id: 1,
+            first_field: "perro".to_string(),
+            model: "Basic".to_string(),
+            field_changes: vec![
+                // Edited in TOML only
+                change("Front", "el perro", "perro"),
+                // Edited in Anki only
+                change("Back", "dog", "the dog"),
+                // Markdown vs HTML, unchanged since the last sync
+                change("Notes", "**noun**", "<b>noun</b>"),
+            ],
+            tag_changes: TagChanges::default(),
+            guid: None,
+        };
+
+        let merge = merge(&modified, &base());
+        assert_eq!(merge.push_fields.len(), 1);
+        assert_eq!(merge.push_fields[0].field, "Front");
+        assert_eq!(merge.pull_fields.len(), 1);
+        assert_eq!(merge.pull_fields[0].field, "Back");
+        assert!(merge.conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_merge_conflicting_field() {
+        let modified = ModifiedNote {
+            note_id: 1,
+            first_field: "perro".to_string(),
+            model: "Basic".to_string(),
+            field_changes: vec![change("Back", "hound", "the dog")],
+            tag_changes: TagChanges::default(),
+            guid: None,
+        };
+
+        let merge = merge(&modified, &base());
+        assert_eq!(merge.conflicts.len(), 1);
+        assert!(!merge.has_changes());
+    }
+
+    #[test]
+    fn test_merge_tags() {
+        let modified = ModifiedNote {
+            note_id: 1,
+            first_field: "perro".to_string(),
+            model: "Basic".to_string(),
+            field_changes: vec![],
+            tag_changes: TagChanges {
+                // "new" added in TOML, "old" removed in Anki
+                added: vec!["new".to_string(), "old".to_string()],
+                // "anki" added in Anki, "animals" removed in TOML
+                removed: vec!["anki".to_string(), "animals".to_string()],
+            },
+            guid: None,
+        };
+
+        let merge = merge(&modified, &base());
+        assert_eq!(merge.push_tags.added, vec!["new"]);
+        assert_eq!(merge.push_tags.removed, vec!["animals"]);
+        assert_eq!(merge.pull_tags.added, vec!["anki"]);
+        assert_eq!(merge.pull_tags.removed, vec!["old"]);
+    }
+
+    #[test]
+    fn test_path_for_and_round_trip() {
+        assert_eq!(
+            SyncBase::path_for("decks/spanish.toml"),
+            PathBuf::from("decks/spanish.sync-base.toml")
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("deck.sync-base.toml");
+        let snapshot = SyncBase {
+            notes: vec![base()],
+        };
+        snapshot.write(&path).unwrap();
+
+        let loaded = SyncBase::from_file(&path).unwrap();
+        assert_eq!(loaded.get(1).unwrap().anki_fields["Notes"], "<b>noun</b>");
+        assert!(loaded.get(2).is_none());
+    }
+}
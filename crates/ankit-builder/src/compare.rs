@@ -0,0 +1,255 @@
+//! Structural comparison of .apkg files.
+//!
+//! Compares the decks, note types, notes, and media of two packages while
+//! ignoring IDs, timestamps, and ZIP layout, so tests and CI can check that
+//! a shared deck's content hasn't changed unexpectedly.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ankit_builder::ApkgReader;
+//!
+//! # fn main() -> ankit_builder::Result<()> {
+//! let expected = ApkgReader::open("golden/deck.apkg")?;
+//! let actual = ApkgReader::open("target/deck.apkg")?;
+//! let diff = expected.compare(&actual)?;
+//! assert!(diff.is_empty(), "package changed: {:?}", diff);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::reader::ApkgReader;
+use crate::schema::{DeckDefinition, ModelDef, NoteDef};
+
+/// Differences between two packages, from the first to the second.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PackageDiff {
+    /// Decks only in the second package.
+    pub decks_added: Vec<String>,
+    /// Decks only in the first package.
+    pub decks_removed: Vec<String>,
+    /// Note types only in the second package.
+    pub models_added: Vec<String>,
+    /// Note types only in the first package.
+    pub models_removed: Vec<String>,
+    /// Note types in both whose fields, templates, or styling differ.
+    pub models_changed: Vec<String>,
+    /// Notes only in the second package.
+    pub notes_added: Vec<PackageNote>,
+    /// Notes only in the first package.
+    pub notes_removed: Vec<PackageNote>,
+    /// Notes in both with different content.
+    pub notes_changed: Vec<ChangedNote>,
+    /// Number of notes identical in both packages.
+    pub notes_unchanged: usize,
+    /// Media files only in the second package.
+    pub media_added: Vec<String>,
+    /// Media files only in the first package.
+    pub media_removed: Vec<String>,
+    /// Media files in both with different contents.
+    pub media_changed: Vec<String>,
+}
+
+impl PackageDiff {
+    /// Check if the packages have the same content.
+    pub fn is_empty(&self) -> bool {
+        self.decks_added.is_empty()
+            && self.decks_removed.is_empty()
+            && self.models_added.is_empty()
+            && self.models_removed.is_empty()
+            && self.models_changed.is_empty()
+            && self.notes_added.is_empty()
+            && self.notes_removed.is_empty()
+            && self.notes_changed.is_empty()
+            && self.media_added.is_empty()
+            && self.media_removed.is_empty()
+            && self.media_changed.is_empty()
+    }
+}
+
+/// A note that exists in only one package.
+#[derive(Debug, Clone, Serialize)]
+pub struct PackageNote {
+    /// The note's GUID.
+    pub guid: String,
+    /// Model (note type) name.
+    pub model: String,
+    /// Value of the first field.
+    pub first_field: String,
+}
+
+/// A note in both packages with different content.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangedNote {
+    /// The note in the first package.
+    pub note: PackageNote,
+    /// Names of fields whose values differ.
+    pub fields: Vec<String>,
+    /// Whether the tags differ.
+    pub tags_changed: bool,
+    /// Whether the note moved to another deck.
+    pub deck_changed: bool,
+}
+
+impl ApkgReader {
+    /// Compare this package's content against `other`.
+    ///
+    /// Notes are matched by GUID, then by model and first field, so a note
+    /// whose generated GUID changed with its content is reported as changed
+    /// rather than removed and re-added.
+    pub fn compare(&self, other: &ApkgReader) -> Result<PackageDiff> {
+        let (old, new) = (self.read()?, other.read()?);
+        let mut diff = PackageDiff::default();
+
+        let (added, removed) = added_removed(
+            old.decks.iter().map(|d| &d.name),
+            new.decks.iter().map(|d| &d.name),
+        );
+        diff.decks_added = added;
+        diff.decks_removed = removed;
+
+        let (added, removed) = added_removed(
+            old.models.iter().map(|m| &m.name),
+            new.models.iter().map(|m| &m.name),
+        );
+        diff.models_added = added;
+        diff.models_removed = removed;
+        diff.models_changed = old
+            .models
+            .iter()
+            .filter(|model| {
+                new.get_model(&model.name)
+                    .is_some_and(|other| !same_model(model, other))
+            })
+            .map(|model| model.name.clone())
+            .collect();
+
+        compare_notes(&old, &new, &mut diff);
+
+        let old_media: BTreeMap<&str, &[u8]> = self.media().collect();
+        let new_media: BTreeMap<&str, &[u8]> = other.media().collect();
+        let (added, removed) = added_removed(old_media.keys(), new_media.keys());
+        diff.media_added = added;
+        diff.media_removed = removed;
+        diff.media_changed = old_media
+            .iter()
+            .filter(|(name, bytes)| new_media.get(*name).is_some_and(|other| other != *bytes))
+            .map(|(name, _)| name.to_string())
+            .collect();
+
+        Ok(diff)
+    }
+}
+
+/// Names only in `new` and names only in `old`, each sorted.
+fn added_removed<'a, T>(
+    old: impl Iterator<Item = &'a T>,
+    new: impl Iterator<Item = &'a T>,
+) -> (Vec<String>, Vec<String>)
+where
+    T: ToString + Ord + ?Sized + 'a,
+{
+    let old: std::collections::BTreeSet<&T> = old.collect();
+    let new: std::collections::BTreeSet<&T> = new.collect();
+    (
+        new.difference(&old).map(|n| n.to_string()).collect(),
+        old.difference(&new).map(|n| n.to_string()).collect(),
+    )
+}
+
+fn same_model(a: &ModelDef, b: &ModelDef) -> bool {
+    a.fields == b.fields
+        && a.css == b.css
+        && a.sort_field_index() == b.sort_field_index()
+        && a.templates.len() == b.templates.len()
+        && a.templates
+            .iter()
+            .zip(&b.templates)
+            .all(|(x, y)| x.name == y.name && x.front == y.front && x.back == y.back)
+}
+
+fn compare_notes(old: &DeckDefinition, new: &DeckDefinition, diff: &mut PackageDiff) {
+    let by_guid: BTreeMap<&str, usize> = new
+        .notes
+        .iter()
+        .enumerate()
+        .filter_map(|(i, note)| Some((note.guid.as_deref()?, i)))
+        .collect();
+    let by_first_field: BTreeMap<(&str, String), usize> = new
+        .notes
+        .iter()
+        .enumerate()
+        .map(|(i, note)| ((note.model.as_str(), first_field(new, note)), i))
+        .collect();
+
+    let mut matched = HashSet::new();
+    for note in &old.notes {
+        let key = (note.model.as_str(), first_field(old, note));
+        let found = note
+            .guid
+            .as_deref()
+            .and_then(|guid| by_guid.get(guid))
+            .or_else(|| by_first_field.get(&key))
+            .filter(|i| !matched.contains(*i))
+            .copied();
+        let Some(index) = found else {
+            diff.notes_removed.push(package_note(old, note));
+            continue;
+        };
+        matched.insert(index);
+
+        let other = &new.notes[index];
+        let mut names: Vec<&String> = note.fields.keys().chain(other.fields.keys()).collect();
+        names.sort();
+        names.dedup();
+        let fields: Vec<String> = names
+            .into_iter()
+            .filter(|name| note.fields.get(*name) != other.fields.get(*name))
+            .cloned()
+            .collect();
+        let mut tags = (note.tags.clone(), other.tags.clone());
+        tags.0.sort();
+        tags.1.sort();
+        let tags_changed = tags.0 != tags.1;
+        let deck_changed = note.deck != other.deck;
+
+        if fields.is_empty() && !tags_changed && !deck_changed && note.model == other.model {
+            diff.notes_unchanged += 1;
+        } else {
+            diff.notes_changed.push(ChangedNote {
+                note: package_note(old, note),
+                fields,
+                tags_changed,
+                deck_changed,
+            });
+        }
+    }
+
+    diff.notes_added = new
+        .notes
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !matched.contains(i))
+        .map(|(_, note)| package_note(new, note))
+        .collect();
+}
+
+fn first_field(definition: &DeckDefinition, note: &NoteDef) -> String {
+    definition
+        .get_model(&note.model)
+        .and_then(|model| note.fields_ordered(model).into_iter().next())
+        .unwrap_or_default()
+}
+
+fn package_note(definition: &DeckDefinition, note: &NoteDef) -> PackageNote {
+    PackageNote {
+        guid: note.guid.clone().unwrap_or_default(),
+        model: note.model.clone(),
+        first_field: first_field(definition, note),
+    }
+}
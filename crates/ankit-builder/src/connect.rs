@@ -7,7 +7,8 @@
 //!
 //! - Anki must be running
 //! - The [AnkiConnect](https://foosoft.net/projects/anki-connect/) add-on must be installed
-//! - Note types (models) referenced in the definition must already exist in Anki
+//! - Note types (models) referenced in the definition must already exist in Anki,
+//!   except [image occlusion](crate::occlusion) models, which are created if missing
 //!
 //! # Example
 //!
@@ -32,11 +33,15 @@
 //! ```
 
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-use ankit::{AnkiClient, NoteBuilder};
+use ankit::{AnkiClient, CreateModelParams, DeckConfig, NoteBuilder, StoreMediaParams};
+use base64::Engine as _;
 
 use crate::error::{Error, Result};
-use crate::schema::DeckDefinition;
+use crate::guid;
+use crate::model_version;
+use crate::schema::{DeckDefinition, DeckOptions, NoteDef};
 
 /// Imports deck definitions into Anki via AnkiConnect.
 ///
@@ -56,6 +61,7 @@ use crate::schema::DeckDefinition;
 pub struct ConnectImporter {
     definition: DeckDefinition,
     client: AnkiClient,
+    media_base_path: Option<PathBuf>,
 }
 
 /// Result of an import operation.
@@ -63,10 +69,19 @@ pub struct ConnectImporter {
 pub struct ImportResult {
     /// Number of decks created.
     pub decks_created: usize,
+    /// Number of decks whose options group was configured.
+    pub deck_options_applied: usize,
+    /// Number of image occlusion models created.
+    pub models_created: usize,
+    /// Number of media files stored.
+    pub media_stored: usize,
     /// Number of notes created.
     pub notes_created: usize,
     /// Number of notes skipped (duplicates or errors).
     pub notes_skipped: usize,
+    /// Number of cards suspended because a note's `cards` excluded their
+    /// template or its `suspend` listed it.
+    pub cards_suspended: usize,
     /// Errors encountered (note index -> error message).
     pub errors: HashMap<usize, String>,
 }
@@ -74,40 +89,48 @@ pub struct ImportResult {
 impl ConnectImporter {
     /// Create a new importer from a deck definition.
     pub fn new(definition: DeckDefinition) -> Self {
+        Self::with_client(definition, AnkiClient::new())
+    }
+
+    /// Create a new importer with a custom AnkiConnect client.
+    pub fn with_client(definition: DeckDefinition, client: AnkiClient) -> Self {
         Self {
             definition,
-            client: AnkiClient::new(),
+            client,
+            media_base_path: None,
         }
     }
 
-    /// Create a new importer with a custom AnkiConnect client.
-    pub fn with_client(definition: DeckDefinition, client: AnkiClient) -> Self {
-        Self { definition, client }
+    /// Set the base path for resolving media file paths.
+    pub fn media_base_path(mut self, path: impl AsRef<Path>) -> Self {
+        self.media_base_path = Some(path.as_ref().to_path_buf());
+        self
     }
 
     /// Import the deck definition into Anki.
     ///
     /// This will:
-    /// 1. Create any missing decks
-    /// 2. Add all notes (using existing models)
+    /// 1. Create any missing decks and apply their `[decks.options]`
+    /// 2. Create any missing image occlusion models
+    /// 3. Store media files
+    /// 4. Add all notes (using existing models), suspending cards left out
+    ///    by a note's `cards` or listed in its `suspend`
     ///
-    /// Note: Models must already exist in Anki. This method does not create models.
+    /// Note: Other models must already exist in Anki. This method does not create them.
     pub async fn import(&self) -> Result<ImportResult> {
         let mut result = ImportResult {
             decks_created: 0,
+            deck_options_applied: 0,
+            models_created: 0,
+            media_stored: 0,
             notes_created: 0,
             notes_skipped: 0,
+            cards_suspended: 0,
             errors: HashMap::new(),
         };
 
-        // Create decks if they don't exist
-        let existing_decks = self.client.decks().names().await?;
-        for deck in &self.definition.decks {
-            if !existing_decks.contains(&deck.name) {
-                self.client.decks().create(&deck.name).await?;
-                result.decks_created += 1;
-            }
-        }
+        self.create_decks(&mut result).await?;
+        self.create_occlusion_models(&mut result).await?;
 
         // Verify models exist
         let existing_models = self.client.models().names().await?;
@@ -120,6 +143,8 @@ impl ConnectImporter {
             }
         }
 
+        self.store_media(&mut result).await?;
+
         // Add notes
         for (i, note_def) in self.definition.notes.iter().enumerate() {
             let mut builder = NoteBuilder::new(&note_def.deck, &note_def.model);
@@ -141,12 +166,17 @@ impl ConnectImporter {
             for tag in &note_def.tags {
                 builder = builder.tag(tag);
             }
+            if let Some(guid) = &note_def.guid {
+                builder = builder.tag(guid::tag(guid));
+            }
 
             let note = builder.build();
 
             match self.client.notes().add(note).await {
-                Ok(_) => {
+                Ok(note_id) => {
                     result.notes_created += 1;
+                    self.suspend_unselected_cards(note_def, note_id, &mut result)
+                        .await?;
                 }
                 Err(e) => {
                     result.notes_skipped += 1;
@@ -160,23 +190,22 @@ impl ConnectImporter {
 
     /// Import notes in batches for better performance.
     ///
-    /// Note: Models must already exist in Anki. This method does not create models.
+    /// Note: Models other than image occlusion models must already exist in
+    /// Anki. This method does not create them.
     pub async fn import_batch(&self) -> Result<ImportResult> {
         let mut result = ImportResult {
             decks_created: 0,
+            deck_options_applied: 0,
+            models_created: 0,
+            media_stored: 0,
             notes_created: 0,
             notes_skipped: 0,
+            cards_suspended: 0,
             errors: HashMap::new(),
         };
 
-        // Create decks if they don't exist
-        let existing_decks = self.client.decks().names().await?;
-        for deck in &self.definition.decks {
-            if !existing_decks.contains(&deck.name) {
-                self.client.decks().create(&deck.name).await?;
-                result.decks_created += 1;
-            }
-        }
+        self.create_decks(&mut result).await?;
+        self.create_occlusion_models(&mut result).await?;
 
         // Verify models exist
         let existing_models = self.client.models().names().await?;
@@ -189,6 +218,8 @@ impl ConnectImporter {
             }
         }
 
+        self.store_media(&mut result).await?;
+
         // Build notes for batch add
         let notes: Vec<_> = self
             .definition
@@ -213,6 +244,9 @@ impl ConnectImporter {
                 for tag in &note_def.tags {
                     builder = builder.tag(tag);
                 }
+                if let Some(guid) = &note_def.guid {
+                    builder = builder.tag(guid::tag(guid));
+                }
                 builder.build()
             })
             .collect();
@@ -220,9 +254,13 @@ impl ConnectImporter {
         // Add notes in batch
         let results = self.client.notes().add_many(&notes).await?;
 
-        for (i, note_result) in results.iter().enumerate() {
+        for (i, (note_def, note_result)) in self.definition.notes.iter().zip(&results).enumerate() {
             match note_result {
-                Some(_) => result.notes_created += 1,
+                Some(note_id) => {
+                    result.notes_created += 1;
+                    self.suspend_unselected_cards(note_def, *note_id, &mut result)
+                        .await?;
+                }
                 None => {
                     result.notes_skipped += 1;
                     result.errors.insert(i, "Failed to add note".to_string());
@@ -233,6 +271,144 @@ impl ConnectImporter {
         Ok(result)
     }
 
+    /// Suspend the cards of a new note that its card selection leaves out.
+    ///
+    /// AnkiConnect generates a card for every template and can't delete
+    /// single cards, so cards for templates missing from `cards` are
+    /// suspended along with those listed in `suspend`.
+    async fn suspend_unselected_cards(
+        &self,
+        note_def: &NoteDef,
+        note_id: i64,
+        result: &mut ImportResult,
+    ) -> Result<()> {
+        if !note_def.selects_cards() {
+            return Ok(());
+        }
+        let Some(model) = self.definition.get_model(&note_def.model) else {
+            return Ok(());
+        };
+
+        let card_ids = self
+            .client
+            .cards()
+            .find(&format!("nid:{}", note_id))
+            .await?;
+        let unwanted: Vec<i64> = self
+            .client
+            .cards()
+            .info(&card_ids)
+            .await?
+            .into_iter()
+            .filter(|card| {
+                model
+                    .templates
+                    .get(card.ord as usize)
+                    .is_some_and(|template| {
+                        !note_def.generates_card(&template.name)
+                            || note_def.suspends_card(&template.name)
+                    })
+            })
+            .map(|card| card.card_id)
+            .collect();
+
+        if !unwanted.is_empty() {
+            self.client.cards().suspend(&unwanted).await?;
+            result.cards_suspended += unwanted.len();
+        }
+        Ok(())
+    }
+
+    /// Create missing decks and apply deck options.
+    async fn create_decks(&self, result: &mut ImportResult) -> Result<()> {
+        let existing_decks = self.client.decks().names().await?;
+        for deck in &self.definition.decks {
+            if !existing_decks.contains(&deck.name) {
+                self.client.decks().create(&deck.name).await?;
+                result.decks_created += 1;
+            }
+
+            if let Some(ref options) = deck.options {
+                self.apply_deck_options(&deck.name, options).await?;
+                result.deck_options_applied += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Create image occlusion models that don't exist in Anki yet.
+    async fn create_occlusion_models(&self, result: &mut ImportResult) -> Result<()> {
+        let existing_models = self.client.models().names().await?;
+        for model in &self.definition.models {
+            if !model.is_image_occlusion() || existing_models.contains(&model.name) {
+                continue;
+            }
+
+            let css = self.definition.model_css(model)?.unwrap_or_default();
+            let css = model_version::stamp_model(model, css);
+            let mut params = CreateModelParams::new(&model.name).css(css);
+            for field in &model.fields {
+                params = params.field(field);
+            }
+            for template in &model.templates {
+                params = params.template(&template.name, &template.front, &template.back);
+            }
+            self.client.models().create(params).await?;
+            result.models_created += 1;
+        }
+        Ok(())
+    }
+
+    /// Store the definition's media files in Anki's media folder.
+    async fn store_media(&self, result: &mut ImportResult) -> Result<()> {
+        for media in &self.definition.media {
+            let params = match media.data {
+                Some(ref data) => StoreMediaParams::from_base64(
+                    &media.name,
+                    base64::engine::general_purpose::STANDARD.encode(data),
+                ),
+                None => {
+                    // AnkiConnect reads the file itself, so the path must be absolute
+                    let path = match self.media_base_path {
+                        Some(ref base) => base.join(&media.path),
+                        None => PathBuf::from(&media.path),
+                    };
+                    let path = std::path::absolute(path)?;
+                    StoreMediaParams::from_path(&media.name, path.to_string_lossy())
+                }
+            };
+            self.client.media().store(params).await?;
+            result.media_stored += 1;
+        }
+        Ok(())
+    }
+
+    /// Point a deck at its named options group and update the group.
+    ///
+    /// If a group with the configured name already exists, the deck is
+    /// moved to it and it is updated in place; otherwise a new group is
+    /// cloned from the deck's current one.
+    async fn apply_deck_options(&self, deck: &str, options: &DeckOptions) -> Result<()> {
+        let group = options.group_name(deck);
+        let mut config = self.client.decks().config(deck).await?;
+
+        if config.name != group {
+            let config_id = match self.client.decks().config_by_name(group).await? {
+                Some(existing) => existing.id,
+                None => self.client.decks().clone_config(group, config.id).await?,
+            };
+            self.client
+                .decks()
+                .set_config_id(&[deck], config_id)
+                .await?;
+            config = self.client.decks().config(deck).await?;
+        }
+
+        apply_to_config(&mut config, options);
+        self.client.decks().save_config(&config).await?;
+        Ok(())
+    }
+
     /// Check if all required models exist in Anki.
     ///
     /// Returns a list of model names that are defined in the TOML but do not
@@ -309,6 +485,49 @@ impl ConnectImporter {
     }
 }
 
+/// Overwrite the values set in `options` on an AnkiConnect deck config.
+fn apply_to_config(config: &mut DeckConfig, options: &DeckOptions) {
+    if let Some(n) = options.new_per_day {
+        config.new.per_day = n;
+    }
+    if let Some(ref steps) = options.learning_steps {
+        config.new.delays = steps.clone();
+    }
+    if let Some(n) = options.graduating_interval {
+        set_interval(&mut config.new.ints, 0, n);
+    }
+    if let Some(n) = options.easy_interval {
+        set_interval(&mut config.new.ints, 1, n);
+    }
+    if let Some(n) = options.reviews_per_day {
+        config.rev.per_day = n;
+    }
+    if let Some(n) = options.maximum_interval {
+        config.rev.max_ivl = n;
+    }
+    if let Some(ref steps) = options.relearning_steps {
+        config.lapse.delays = steps.clone();
+    }
+    if let Some(n) = options.lapse_minimum_interval {
+        config.lapse.min_int = n;
+    }
+    if let Some(n) = options.leech_threshold {
+        config.lapse.leech_fails = n;
+    }
+    if let Some(action) = options.leech_action_code() {
+        config.lapse.leech_action = action;
+    }
+}
+
+/// Set one entry of Anki's `[graduating, easy, unused]` interval list.
+fn set_interval(ints: &mut Vec<i64>, index: usize, value: i64) {
+    const DEFAULT_INTS: [i64; 3] = [1, 4, 7];
+    while ints.len() <= index {
+        ints.push(DEFAULT_INTS[ints.len()]);
+    }
+    ints[index] = value;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -317,10 +536,42 @@ mod tests {
     fn test_import_result_default() {
         let result = ImportResult {
             decks_created: 0,
+            deck_options_applied: 0,
+            models_created: 0,
+            media_stored: 0,
             notes_created: 0,
             notes_skipped: 0,
+            cards_suspended: 0,
             errors: HashMap::new(),
         };
         assert_eq!(result.decks_created, 0);
     }
+
+    #[test]
+    fn test_apply_to_config() {
+        let mut config: DeckConfig = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "name": "Default",
+            "new": {"delays": [1.0, 10.0], "ints": [], "perDay": 20},
+            "rev": {"perDay": 200, "maxIvl": 36500},
+            "lapse": {"delays": [10.0], "leechFails": 8, "leechAction": 0}
+        }))
+        .unwrap();
+        let options = DeckOptions {
+            new_per_day: Some(5),
+            easy_interval: Some(3),
+            relearning_steps: Some(vec![5.0, 30.0]),
+            leech_action: Some("tag".to_string()),
+            ..Default::default()
+        };
+
+        apply_to_config(&mut config, &options);
+
+        assert_eq!(config.new.per_day, 5);
+        assert_eq!(config.new.delays, vec![1.0, 10.0]);
+        assert_eq!(config.new.ints, vec![1, 3]);
+        assert_eq!(config.rev.per_day, 200);
+        assert_eq!(config.lapse.delays, vec![5.0, 30.0]);
+        assert_eq!(config.lapse.leech_action, 1);
+    }
 }
@@ -23,9 +23,11 @@
 use std::collections::HashMap;
 
 use ankit::AnkiClient;
+use ankit::key::KeyNormalization;
 use serde::Serialize;
 
 use crate::error::Result;
+use crate::guid;
 use crate::schema::{DeckDefinition, NoteDef};
 
 /// Result of comparing a TOML definition against Anki state.
@@ -39,6 +41,11 @@ pub struct DeckDiff {
     pub modified: Vec<ModifiedNote>,
     /// Number of notes that are identical.
     pub unchanged: usize,
+    /// Notes matched by first field whose Anki copy doesn't have the TOML
+    /// note's GUID tag yet.
+    pub untagged: Vec<UntaggedNote>,
+    /// Every note found in both TOML and Anki, modified or not.
+    pub matched: Vec<MatchedNote>,
 }
 
 /// A note that exists in only one place.
@@ -54,6 +61,8 @@ pub struct NoteDiff {
     pub first_field: String,
     /// Tags on the note.
     pub tags: Vec<String>,
+    /// Stable note identity, if the note has one.
+    pub guid: Option<String>,
 }
 
 /// A note that exists in both TOML and Anki but has differences.
@@ -69,6 +78,26 @@ pub struct ModifiedNote {
     pub field_changes: Vec<FieldChange>,
     /// Tag changes between TOML and Anki.
     pub tag_changes: TagChanges,
+    /// Stable identity of the TOML note, if it has one.
+    pub guid: Option<String>,
+}
+
+/// A note found in both TOML and Anki.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct MatchedNote {
+    /// Index of the note in the definition's `notes`.
+    pub index: usize,
+    /// The Anki note ID.
+    pub note_id: i64,
+}
+
+/// A matched Anki note that needs its GUID tag added.
+#[derive(Debug, Clone, Serialize)]
+pub struct UntaggedNote {
+    /// The Anki note ID.
+    pub note_id: i64,
+    /// GUID of the matching TOML note.
+    pub guid: String,
 }
 
 /// A field that differs between TOML and Anki.
@@ -102,146 +131,152 @@ impl TagChanges {
 pub struct DeckDiffer<'a> {
     client: &'a AnkiClient,
     definition: &'a DeckDefinition,
+    normalization: KeyNormalization,
 }
 
 impl<'a> DeckDiffer<'a> {
     /// Create a new differ with the given client and definition.
     pub fn new(client: &'a AnkiClient, definition: &'a DeckDefinition) -> Self {
-        Self { client, definition }
+        Self {
+            client,
+            definition,
+            normalization: KeyNormalization::default(),
+        }
+    }
+
+    /// Set how first field values are normalized before matching.
+    pub fn normalization(mut self, normalization: KeyNormalization) -> Self {
+        self.normalization = normalization;
+        self
     }
 
     /// Compute the diff between TOML and Anki.
     ///
-    /// Uses the first field value (normalized) as the key for matching
-    /// notes between TOML and Anki.
+    /// Notes are matched on their [`guid`](NoteDef::guid) first, which Anki
+    /// stores as an `ankit-guid::<guid>` tag. Notes without a match fall
+    /// back to the normalized first field value.
     pub async fn diff(&self) -> Result<DeckDiff> {
         let mut result = DeckDiff::default();
 
-        // Get all decks in the definition
-        let deck_names: Vec<&str> = self
-            .definition
-            .decks
-            .iter()
-            .map(|d| d.name.as_str())
-            .collect();
-
-        // Build a map of TOML notes by (deck, model, first_field_normalized)
-        let mut toml_notes: HashMap<NoteKey, &NoteDef> = HashMap::new();
-        for note in &self.definition.notes {
-            let model = self.definition.get_model(&note.model);
-            if let Some(model) = model {
-                if let Some(first_field_name) = model.fields.first() {
-                    let first_field_value = note
-                        .fields
-                        .get(first_field_name)
-                        .cloned()
-                        .unwrap_or_default();
-                    let key = NoteKey {
-                        deck: note.deck.clone(),
-                        model: note.model.clone(),
-                        first_field: normalize_key(&first_field_value),
-                    };
-                    toml_notes.insert(key, note);
-                }
-            }
-        }
-
-        // Fetch notes from Anki for each deck
-        let mut anki_notes: HashMap<NoteKey, AnkiNote> = HashMap::new();
-
-        for deck_name in &deck_names {
-            let query = format!("deck:\"{}\"", deck_name);
+        // Fetch notes from Anki for each deck in the definition
+        let mut anki_notes: Vec<AnkiNote> = Vec::new();
+        for deck in &self.definition.decks {
+            let query = format!("deck:\"{}\"", deck.name);
             let note_ids = self.client.notes().find(&query).await?;
 
             if note_ids.is_empty() {
                 continue;
             }
 
-            let note_infos = self.client.notes().info(&note_ids).await?;
-
-            for note in note_infos {
-                // Get first field value
+            for note in self.client.notes().info(&note_ids).await? {
                 let first_field_value = get_first_field_value(&note.fields);
-                let key = NoteKey {
-                    deck: deck_name.to_string(),
-                    model: note.model_name.clone(),
-                    first_field: normalize_key(&first_field_value),
-                };
+                let (guid, tags) = guid::split_tags(note.tags);
 
                 // Convert fields to simple HashMap
                 let fields: HashMap<String, String> = note
                     .fields
-                    .iter()
-                    .map(|(name, field)| (name.clone(), field.value.clone()))
+                    .into_iter()
+                    .map(|(name, field)| (name, field.value))
                     .collect();
 
-                anki_notes.insert(
-                    key,
-                    AnkiNote {
-                        note_id: note.note_id,
-                        model_name: note.model_name,
-                        fields,
-                        tags: note.tags,
-                        first_field_value,
+                anki_notes.push(AnkiNote {
+                    key: NoteKey {
+                        deck: deck.name.clone(),
+                        model: note.model_name.clone(),
+                        first_field: self.normalization.apply(&first_field_value),
                     },
-                );
+                    note_id: note.note_id,
+                    model_name: note.model_name,
+                    fields,
+                    tags,
+                    guid,
+                    first_field_value,
+                });
             }
         }
 
-        // Compare TOML notes against Anki
-        for (key, toml_note) in &toml_notes {
-            if let Some(anki_note) = anki_notes.get(key) {
-                // Note exists in both - check for modifications
-                let (field_changes, tag_changes) = self.compare_note(toml_note, anki_note);
-
-                if field_changes.is_empty() && tag_changes.is_empty() {
-                    result.unchanged += 1;
-                } else {
-                    result.modified.push(ModifiedNote {
-                        note_id: anki_note.note_id,
-                        first_field: anki_note.first_field_value.clone(),
-                        model: anki_note.model_name.clone(),
-                        field_changes,
-                        tag_changes,
-                    });
-                }
-            } else {
-                // Note only in TOML
-                let model = self.definition.get_model(&toml_note.model);
-                let first_field = if let Some(model) = model {
-                    model
-                        .fields
-                        .first()
-                        .and_then(|f| toml_note.fields.get(f))
-                        .cloned()
-                        .unwrap_or_default()
-                } else {
-                    String::new()
+        // TOML notes that can be compared, with their first field and key
+        let (toml_indices, toml_notes): (Vec<usize>, Vec<(&NoteDef, String, NoteKey)>) = self
+            .definition
+            .notes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, note)| {
+                let model = self.definition.get_model(&note.model)?;
+                let first_field_name = model.fields.first()?;
+                let first_field = note
+                    .fields
+                    .get(first_field_name)
+                    .cloned()
+                    .unwrap_or_default();
+                let key = NoteKey {
+                    deck: note.deck.clone(),
+                    model: note.model.clone(),
+                    first_field: self.normalization.apply(&first_field),
                 };
+                Some((i, (note, first_field, key)))
+            })
+            .unzip();
+
+        let (matches, matched) = match_notes(&toml_notes, &anki_notes);
 
+        // Compare TOML notes against Anki, in definition order
+        for (((toml_note, first_field, _), found), toml_index) in
+            toml_notes.into_iter().zip(matches).zip(toml_indices)
+        {
+            let Some(index) = found else {
+                // Note only in TOML
                 result.toml_only.push(NoteDiff {
                     note_id: None,
                     model: toml_note.model.clone(),
                     deck: toml_note.deck.clone(),
                     first_field,
                     tags: toml_note.tags.clone(),
+                    guid: toml_note.guid.clone(),
+                });
+                continue;
+            };
+            let anki_note = &anki_notes[index];
+            result.matched.push(MatchedNote {
+                index: toml_index,
+                note_id: anki_note.note_id,
+            });
+
+            if let (Some(guid), None) = (&toml_note.guid, &anki_note.guid) {
+                result.untagged.push(UntaggedNote {
+                    note_id: anki_note.note_id,
+                    guid: guid.clone(),
                 });
             }
-        }
 
-        // Find notes only in Anki
-        for (key, anki_note) in &anki_notes {
-            if !toml_notes.contains_key(key) {
-                result.anki_only.push(NoteDiff {
-                    note_id: Some(anki_note.note_id),
-                    model: anki_note.model_name.clone(),
-                    deck: key.deck.clone(),
+            // Note exists in both - check for modifications
+            let (field_changes, tag_changes) = self.compare_note(toml_note, anki_note);
+            if field_changes.is_empty() && tag_changes.is_empty() {
+                result.unchanged += 1;
+            } else {
+                result.modified.push(ModifiedNote {
+                    note_id: anki_note.note_id,
                     first_field: anki_note.first_field_value.clone(),
-                    tags: anki_note.tags.clone(),
+                    model: anki_note.model_name.clone(),
+                    field_changes,
+                    tag_changes,
+                    guid: toml_note.guid.clone(),
                 });
             }
         }
 
+        // Find notes only in Anki
+        for (anki_note, _) in anki_notes.iter().zip(&matched).filter(|(_, m)| !**m) {
+            result.anki_only.push(NoteDiff {
+                note_id: Some(anki_note.note_id),
+                model: anki_note.model_name.clone(),
+                deck: anki_note.key.deck.clone(),
+                first_field: anki_note.first_field_value.clone(),
+                tags: anki_note.tags.clone(),
+                guid: anki_note.guid.clone(),
+            });
+        }
+
         Ok(result)
     }
 
@@ -307,39 +342,59 @@ struct NoteKey {
 
 /// Temporary struct for Anki note data.
 struct AnkiNote {
+    key: NoteKey,
     note_id: i64,
     model_name: String,
     fields: HashMap<String, String>,
+    /// Tags without the GUID tag.
     tags: Vec<String>,
+    guid: Option<String>,
     first_field_value: String,
 }
 
-/// Normalize a key value for comparison.
+/// Match TOML notes to Anki notes, returning the Anki index for each TOML
+/// note and whether each Anki note was matched.
 ///
-/// - Trims whitespace
-/// - Converts to lowercase
-/// - Strips HTML tags
-fn normalize_key(value: &str) -> String {
-    let trimmed = value.trim();
-    let lower = trimmed.to_lowercase();
-    strip_html(&lower)
-}
-
-/// Strip HTML tags from a string.
-fn strip_html(s: &str) -> String {
-    let mut result = String::with_capacity(s.len());
-    let mut in_tag = false;
-
-    for c in s.chars() {
-        match c {
-            '<' => in_tag = true,
-            '>' => in_tag = false,
-            _ if !in_tag => result.push(c),
-            _ => {}
+/// GUIDs are matched first, so a first-field match can't take a note that
+/// belongs to another GUID. The first-field fallback also skips Anki notes
+/// tagged with a different GUID, since those are different notes.
+fn match_notes(
+    toml_notes: &[(&NoteDef, String, NoteKey)],
+    anki_notes: &[AnkiNote],
+) -> (Vec<Option<usize>>, Vec<bool>) {
+    let by_guid: HashMap<&str, usize> = anki_notes
+        .iter()
+        .enumerate()
+        .filter_map(|(i, note)| note.guid.as_deref().map(|guid| (guid, i)))
+        .collect();
+    let mut matched = vec![false; anki_notes.len()];
+    let mut matches: Vec<Option<usize>> = toml_notes
+        .iter()
+        .map(|(note, _, _)| {
+            let index = *by_guid.get(note.guid.as_deref()?)?;
+            matched[index] = true;
+            Some(index)
+        })
+        .collect();
+
+    let by_key: HashMap<&NoteKey, usize> = anki_notes
+        .iter()
+        .enumerate()
+        .map(|(i, note)| (&note.key, i))
+        .collect();
+    for ((note, _, key), found) in toml_notes.iter().zip(&mut matches) {
+        if found.is_some() {
+            continue;
+        }
+        if let Some(&index) = by_key.get(key) {
+            if !matched[index] && (anki_notes[index].guid.is_none() || note.guid.is_none()) {
+                matched[index] = true;
+                *found = Some(index);
+            }
         }
     }
 
-    result
+    (matches, matched)
 }
 
 /// Get the first field value from a note's fields map.
@@ -356,6 +411,18 @@ fn get_first_field_value(fields: &HashMap<String, ankit::NoteField>) -> String {
 mod tests {
     use super::*;
 
+    fn normalize_key(value: &str) -> String {
+        KeyNormalization::default().apply(value)
+    }
+
+    fn strip_html(value: &str) -> String {
+        KeyNormalization {
+            strip_html: true,
+            ..KeyNormalization::none()
+        }
+        .apply(value)
+    }
+
     #[test]
     fn test_normalize_key() {
         assert_eq!(normalize_key("  Hello World  "), "hello world");
@@ -442,6 +509,7 @@ mod tests {
             deck: "Test".to_string(),
             first_field: "Question".to_string(),
             tags: vec!["tag1".to_string(), "tag2".to_string()],
+            guid: None,
         };
 
         assert_eq!(note.note_id, Some(12345));
@@ -479,6 +547,7 @@ mod tests {
                 added: vec!["updated".to_string()],
                 removed: vec![],
             },
+            guid: Some("abc123".to_string()),
         };
 
         assert_eq!(modified.note_id, 67890);
@@ -532,4 +601,81 @@ mod tests {
         set.insert(key1);
         assert!(set.contains(&key2));
     }
+
+    fn key(first_field: &str) -> NoteKey {
+        NoteKey {
+            deck: "Test".to_string(),
+            model: "Basic".to_string(),
+            first_field: normalize_key(first_field),
+        }
+    }
+
+    fn toml_note(first_field: &str, guid: Option<&str>) -> (NoteDef, String, NoteKey) {
+        let note = NoteDef {
+            deck: "Test".to_string(),
+            model: "Basic".to_string(),
+            fields: HashMap::from([("Front".to_string(), first_field.to_string())]),
+            tags: vec![],
+            guid: guid.map(String::from),
+            note_id: None,
+            cards: None,
+            suspend: Vec::new(),
+        };
+        (note, first_field.to_string(), key(first_field))
+    }
+
+    fn anki_note(note_id: i64, first_field: &str, guid: Option<&str>) -> AnkiNote {
+        AnkiNote {
+            key: key(first_field),
+            note_id,
+            model_name: "Basic".to_string(),
+            fields: HashMap::new(),
+            tags: vec![],
+            guid: guid.map(String::from),
+            first_field_value: first_field.to_string(),
+        }
+    }
+
+    fn matches(toml: &[(NoteDef, String, NoteKey)], anki: &[AnkiNote]) -> Vec<Option<usize>> {
+        let toml: Vec<_> = toml
+            .iter()
+            .map(|(note, first, key)| (note, first.clone(), key.clone()))
+            .collect();
+        match_notes(&toml, anki).0
+    }
+
+    #[test]
+    fn test_match_follows_guid_through_edits() {
+        // The front was edited in TOML, but the GUID still identifies the note
+        let toml = [toml_note("la casa", Some("g1"))];
+        let anki = [anki_note(1, "casa", Some("g1"))];
+        assert_eq!(matches(&toml, &anki), vec![Some(0)]);
+    }
+
+    #[test]
+    fn test_match_falls_back_to_first_field() {
+        let toml = [
+            toml_note("perro", Some("g1")),
+            toml_note("gato", None),
+            toml_note("casa", Some("g3")),
+        ];
+        let anki = [
+            anki_note(1, "<b>Perro</b>", None),
+            anki_note(2, "gato", Some("g2")),
+            anki_note(3, "casa", Some("other")),
+        ];
+
+        // An untagged note matches by first field, a note without a GUID
+        // matches a tagged one, and a different GUID never matches
+        assert_eq!(matches(&toml, &anki), vec![Some(0), Some(1), None]);
+    }
+
+    #[test]
+    fn test_match_prefers_guid_over_earlier_first_field() {
+        // The first TOML note shares a front with the tagged Anki note, but
+        // the GUID match claims it
+        let toml = [toml_note("casa", None), toml_note("la casa", Some("g1"))];
+        let anki = [anki_note(1, "casa", Some("g1"))];
+        assert_eq!(matches(&toml, &anki), vec![None, Some(0)]);
+    }
 }
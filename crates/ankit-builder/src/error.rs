@@ -16,6 +16,20 @@ pub enum Error {
     #[error("TOML serialize error: {0}")]
     TomlSerialize(String),
 
+    /// YAML parsing or serialization error (yaml feature).
+    #[cfg(feature = "yaml")]
+    #[error("YAML error: {0}")]
+    Yaml(#[from] serde_norway::Error),
+
+    /// JSON parsing or serialization error (json feature).
+    #[cfg(feature = "json")]
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// Definition format whose feature isn't enabled.
+    #[error("unsupported format: {0}")]
+    UnsupportedFormat(String),
+
     /// IO error.
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
@@ -59,6 +73,11 @@ pub enum Error {
     #[error("SQLite error: {0}")]
     Sqlite(#[from] rusqlite::Error),
 
+    /// Malformed or unsupported .apkg file (apkg feature).
+    #[cfg(feature = "apkg")]
+    #[error("invalid package: {0}")]
+    InvalidPackage(String),
+
     /// ZIP error (apkg feature).
     #[cfg(feature = "apkg")]
     #[error("ZIP error: {0}")]
@@ -7,7 +7,7 @@
 //!
 //! ```no_run
 //! use ankit::AnkiClient;
-//! use ankit_builder::DeckExporter;
+//! use ankit_builder::{DeckExporter, NoteOrder};
 //!
 //! # async fn example() -> ankit_builder::Result<()> {
 //! let client = AnkiClient::new();
@@ -18,30 +18,76 @@
 //!
 //! // Write to TOML file
 //! definition.write_toml("japanese.toml")?;
+//!
+//! // Order notes by their first field instead of creation time
+//! let exporter = DeckExporter::new(&client).order(NoteOrder::FirstField);
 //! # Ok(())
 //! # }
 //! ```
 
-use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use std::collections::{BTreeSet, HashMap};
 
 use ankit::AnkiClient;
 
-use crate::error::{Error, Result};
-use crate::schema::{DeckDef, DeckDefinition, ModelDef, NoteDef, PackageInfo, TemplateDef};
+use crate::error::Result;
+use crate::guid;
+use crate::model_version;
+use crate::schema::{
+    DeckDef, DeckDefinition, DeckOptions, ModelDef, NoteDef, PackageInfo, TemplateDef,
+};
+
+/// Order of notes in an exported definition.
+///
+/// Every order falls back to the note ID, so repeated exports of an
+/// unchanged deck list notes identically.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NoteOrder {
+    /// Oldest first, by note ID (the note's creation time).
+    #[default]
+    Created,
+    /// By the value of the first field in model order.
+    FirstField,
+    /// By GUID.
+    Guid,
+}
 
 /// Exports decks from Anki to TOML format.
 ///
 /// Uses AnkiConnect to fetch deck contents and convert them to a
-/// [`DeckDefinition`] that can be serialized to TOML.
+/// [`DeckDefinition`] that can be serialized to TOML. Models are sorted by
+/// name and notes by [`NoteOrder`], so exports are stable under version
+/// control.
 pub struct DeckExporter<'a> {
     client: &'a AnkiClient,
+    order: NoteOrder,
+    deck_options: bool,
 }
 
 impl<'a> DeckExporter<'a> {
     /// Create a new exporter with the given AnkiConnect client.
     pub fn new(client: &'a AnkiClient) -> Self {
-        Self { client }
+        Self {
+            client,
+            order: NoteOrder::default(),
+            deck_options: false,
+        }
+    }
+
+    /// Set the order of exported notes (default: [`NoteOrder::Created`]).
+    pub fn order(mut self, order: NoteOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Include each deck's options group as `[decks.options]` (default:
+    /// false).
+    ///
+    /// Every setting of the group is written, so importing the definition
+    /// elsewhere reproduces it. Decks that share a group name it in
+    /// `group`, and import them into one group.
+    pub fn deck_options(mut self, include: bool) -> Self {
+        self.deck_options = include;
+        self
     }
 
     /// Export a deck to a [`DeckDefinition`].
@@ -76,20 +122,15 @@ impl<'a> DeckExporter<'a> {
         if note_ids.is_empty() {
             // Return empty definition with just the deck
             return Ok(DeckDefinition {
-                package: PackageInfo {
-                    name: deck_name.to_string(),
-                    version: "1.0.0".to_string(),
-                    author: None,
-                    description: None,
-                },
+                include: Vec::new(),
+                package: PackageInfo::new(deck_name),
                 models: Vec::new(),
-                decks: vec![DeckDef {
-                    name: deck_name.to_string(),
-                    description: None,
-                    id: None,
-                }],
+                decks: vec![self.deck_def(deck_name).await?],
                 notes: Vec::new(),
                 media: Vec::new(),
+                generators: Vec::new(),
+                occlusions: Vec::new(),
+                base_dir: None,
             });
         }
 
@@ -97,7 +138,7 @@ impl<'a> DeckExporter<'a> {
         let note_infos = self.client.notes().info(&note_ids).await?;
 
         // Collect unique model names
-        let model_names: HashSet<String> =
+        let model_names: BTreeSet<String> =
             note_infos.iter().map(|n| n.model_name.clone()).collect();
 
         // Fetch model details
@@ -108,7 +149,7 @@ impl<'a> DeckExporter<'a> {
         }
 
         // Convert notes to NoteDef
-        let notes: Vec<NoteDef> = note_infos
+        let mut notes: Vec<NoteDef> = note_infos
             .iter()
             .map(|note| {
                 let fields: HashMap<String, String> = note
@@ -117,32 +158,32 @@ impl<'a> DeckExporter<'a> {
                     .map(|(name, field)| (name.clone(), field.value.clone()))
                     .collect();
 
+                let (guid, tags) = guid::split_tags(note.tags.clone());
+
                 NoteDef {
                     deck: deck_name.to_string(),
                     model: note.model_name.clone(),
                     fields,
-                    tags: note.tags.clone(),
-                    guid: None,
+                    tags,
+                    guid: Some(guid.unwrap_or_else(guid::generate)),
                     note_id: Some(note.note_id),
+                    cards: None,
+                    suspend: Vec::new(),
                 }
             })
             .collect();
+        self.sort_notes(&mut notes, &models);
 
         Ok(DeckDefinition {
-            package: PackageInfo {
-                name: deck_name.to_string(),
-                version: "1.0.0".to_string(),
-                author: None,
-                description: None,
-            },
+            include: Vec::new(),
+            package: PackageInfo::new(deck_name),
             models,
-            decks: vec![DeckDef {
-                name: deck_name.to_string(),
-                description: None,
-                id: None,
-            }],
+            decks: vec![self.deck_def(deck_name).await?],
             notes,
             media: Vec::new(),
+            generators: Vec::new(),
+            occlusions: Vec::new(),
+            base_dir: None,
         })
     }
 
@@ -177,7 +218,7 @@ impl<'a> DeckExporter<'a> {
         package_name: &str,
     ) -> Result<DeckDefinition> {
         let mut all_notes = Vec::new();
-        let mut model_names: HashSet<String> = HashSet::new();
+        let mut model_names: BTreeSet<String> = BTreeSet::new();
         let mut decks = Vec::new();
 
         for deck_name in deck_names {
@@ -186,11 +227,7 @@ impl<'a> DeckExporter<'a> {
             let note_ids = self.client.notes().find(&query).await?;
 
             // Add deck to list
-            decks.push(DeckDef {
-                name: deck_name.to_string(),
-                description: None,
-                id: None,
-            });
+            decks.push(self.deck_def(deck_name).await?);
 
             if note_ids.is_empty() {
                 continue;
@@ -212,13 +249,17 @@ impl<'a> DeckExporter<'a> {
                     .map(|(name, field)| (name.clone(), field.value.clone()))
                     .collect();
 
+                let (guid, tags) = guid::split_tags(note.tags);
+
                 all_notes.push(NoteDef {
                     deck: deck_name.to_string(),
                     model: note.model_name.clone(),
                     fields,
-                    tags: note.tags,
-                    guid: None,
+                    tags,
+                    guid: Some(guid.unwrap_or_else(guid::generate)),
                     note_id: Some(note.note_id),
+                    cards: None,
+                    suspend: Vec::new(),
                 });
             }
         }
@@ -230,17 +271,54 @@ impl<'a> DeckExporter<'a> {
             models.push(model_def);
         }
 
+        // Sort within each deck, keeping decks in the order given
+        let deck_index = |note: &NoteDef| deck_names.iter().position(|d| *d == note.deck);
+        self.sort_notes(&mut all_notes, &models);
+        all_notes.sort_by_key(deck_index);
+
         Ok(DeckDefinition {
-            package: PackageInfo {
-                name: package_name.to_string(),
-                version: "1.0.0".to_string(),
-                author: None,
-                description: None,
-            },
+            include: Vec::new(),
+            package: PackageInfo::new(package_name),
             models,
             decks,
             notes: all_notes,
             media: Vec::new(),
+            generators: Vec::new(),
+            occlusions: Vec::new(),
+            base_dir: None,
+        })
+    }
+
+    /// Sort notes by the configured [`NoteOrder`], then note ID.
+    fn sort_notes(&self, notes: &mut [NoteDef], models: &[ModelDef]) {
+        let order = self.order;
+        notes.sort_by_cached_key(|note| {
+            let key = match order {
+                NoteOrder::Created => String::new(),
+                NoteOrder::FirstField => models
+                    .iter()
+                    .find(|m| m.name == note.model)
+                    .and_then(|model| note.fields_ordered(model).into_iter().next())
+                    .unwrap_or_default(),
+                NoteOrder::Guid => note.guid.clone().unwrap_or_default(),
+            };
+            (key, note.note_id)
+        });
+    }
+
+    /// The definition of a deck, with its options group if requested.
+    async fn deck_def(&self, deck_name: &str) -> Result<DeckDef> {
+        let options = if self.deck_options {
+            let config = self.client.decks().config(deck_name).await?;
+            Some(DeckOptions::from_config(&config, deck_name))
+        } else {
+            None
+        };
+        Ok(DeckDef {
+            name: deck_name.to_string(),
+            description: None,
+            id: None,
+            options,
         })
     }
 
@@ -249,9 +327,10 @@ impl<'a> DeckExporter<'a> {
         // Get field names
         let fields = self.client.models().field_names(model_name).await?;
 
-        // Get templates
+        // Get templates, in card ordinal order
         let templates_map = self.client.models().templates(model_name).await?;
-        let templates: Vec<TemplateDef> = templates_map
+        let ordinals = self.template_ordinals(model_name).await?;
+        let mut templates: Vec<TemplateDef> = templates_map
             .into_iter()
             .map(|(name, template)| TemplateDef {
                 name,
@@ -259,13 +338,18 @@ impl<'a> DeckExporter<'a> {
                 back: template.back,
             })
             .collect();
+        templates.sort_by_cached_key(|t| {
+            let ord = ordinals.iter().position(|name| *name == t.name);
+            (ord.unwrap_or(usize::MAX), t.name.clone())
+        });
 
-        // Get CSS styling
+        // Get CSS styling, minus the version recorded by sync
         let styling = self.client.models().styling(model_name).await?;
-        let css = if styling.css.is_empty() {
+        let (applied, css) = model_version::split_css(&styling.css);
+        let css = if css.is_empty() {
             None
         } else {
-            Some(styling.css)
+            Some(css.to_string())
         };
 
         Ok(ModelDef {
@@ -277,47 +361,21 @@ impl<'a> DeckExporter<'a> {
             id: None,
             markdown_fields: vec![],
             model_type: None,
+            version: applied.map(|applied| applied.version),
+            include: vec![],
         })
     }
-}
-
-impl DeckDefinition {
-    /// Write the deck definition to a TOML file.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// use ankit_builder::DeckDefinition;
-    ///
-    /// # fn example() -> ankit_builder::Result<()> {
-    /// let definition = DeckDefinition::from_file("input.toml")?;
-    /// // ... modify definition ...
-    /// definition.write_toml("output.toml")?;
-    /// # Ok(())
-    /// # }
-    /// ```
-    pub fn write_toml(&self, path: impl AsRef<Path>) -> Result<()> {
-        let content = self.to_toml()?;
-        std::fs::write(path, content)?;
-        Ok(())
-    }
 
-    /// Serialize the deck definition to a TOML string.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// use ankit_builder::DeckDefinition;
-    ///
-    /// # fn example() -> ankit_builder::Result<()> {
-    /// let definition = DeckDefinition::from_file("input.toml")?;
-    /// let toml_string = definition.to_toml()?;
-    /// println!("{}", toml_string);
-    /// # Ok(())
-    /// # }
-    /// ```
-    pub fn to_toml(&self) -> Result<String> {
-        toml::to_string_pretty(self).map_err(|e| Error::TomlSerialize(e.to_string()))
+    /// Template names of a model in card ordinal order.
+    async fn template_ordinals(&self, model_name: &str) -> Result<Vec<String>> {
+        let models = self.client.models().find_by_name(&[model_name]).await?;
+        Ok(models
+            .first()
+            .and_then(|model| model["tmpls"].as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|template| template["name"].as_str().map(str::to_string))
+            .collect())
     }
 }
 
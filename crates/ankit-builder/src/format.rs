@@ -0,0 +1,112 @@
+//! File formats for deck definitions.
+//!
+//! TOML is always available. YAML and JSON are behind the `yaml` and `json`
+//! features. [`DeckDefinition::from_file`](crate::DeckDefinition::from_file)
+//! and [`DeckDefinition::write_file`](crate::DeckDefinition::write_file) pick
+//! the format from the file extension.
+
+use std::path::Path;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::error::{Error, Result};
+
+/// A deck definition file format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    /// TOML (`.toml`).
+    #[default]
+    Toml,
+    /// YAML (`.yaml` or `.yml`, requires the `yaml` feature).
+    Yaml,
+    /// JSON (`.json`, requires the `json` feature).
+    Json,
+}
+
+impl Format {
+    /// Detect the format from a path's extension.
+    ///
+    /// Unknown or missing extensions are treated as TOML.
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        let extension = path
+            .as_ref()
+            .extension()
+            .map(|e| e.to_string_lossy().to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("yaml" | "yml") => Self::Yaml,
+            Some("json") => Self::Json,
+            _ => Self::Toml,
+        }
+    }
+
+    /// The usual file extension for this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Toml => "toml",
+            Self::Yaml => "yaml",
+            Self::Json => "json",
+        }
+    }
+
+    /// Deserialize `content` in this format.
+    pub(crate) fn parse<T: DeserializeOwned>(self, content: &str) -> Result<T> {
+        match self {
+            Self::Toml => Ok(toml::from_str(content)?),
+            #[cfg(feature = "yaml")]
+            Self::Yaml => Ok(serde_norway::from_str(content)?),
+            #[cfg(feature = "json")]
+            Self::Json => Ok(serde_json::from_str(content)?),
+            #[cfg(not(all(feature = "yaml", feature = "json")))]
+            _ => Err(self.unsupported()),
+        }
+    }
+
+    /// Serialize `value` in this format.
+    pub(crate) fn serialize<T: Serialize>(self, value: &T) -> Result<String> {
+        match self {
+            Self::Toml => {
+                toml::to_string_pretty(value).map_err(|e| Error::TomlSerialize(e.to_string()))
+            }
+            #[cfg(feature = "yaml")]
+            Self::Yaml => Ok(serde_norway::to_string(value)?),
+            #[cfg(feature = "json")]
+            Self::Json => Ok(serde_json::to_string_pretty(value)? + "\n"),
+            #[cfg(not(all(feature = "yaml", feature = "json")))]
+            _ => Err(self.unsupported()),
+        }
+    }
+
+    #[cfg(not(all(feature = "yaml", feature = "json")))]
+    fn unsupported(self) -> Error {
+        let feature = self.extension();
+        Error::UnsupportedFormat(format!(
+            "{} definitions require the `{}` feature",
+            self, feature
+        ))
+    }
+}
+
+impl std::fmt::Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Toml => "TOML",
+            Self::Yaml => "YAML",
+            Self::Json => "JSON",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(Format::from_path("deck.toml"), Format::Toml);
+        assert_eq!(Format::from_path("deck.yaml"), Format::Yaml);
+        assert_eq!(Format::from_path("deck.YML"), Format::Yaml);
+        assert_eq!(Format::from_path("decks/deck.json"), Format::Json);
+        assert_eq!(Format::from_path("deck"), Format::Toml);
+    }
+}
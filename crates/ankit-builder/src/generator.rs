@@ -0,0 +1,249 @@
+//! Note generators: expand one template into many notes.
+//!
+//! A `[[generators]]` entry pairs field templates containing `{{placeholder}}`
+//! markers with one or more named data tables. One note is generated for every
+//! combination of rows across the tables (the Cartesian product), with each
+//! placeholder replaced by the matching column value.
+//!
+//! Placeholders may name a column directly (`{{infinitive}}`) or qualify it
+//! with its table (`{{verbs.infinitive}}`) when two tables share a column
+//! name. Markers containing `::`, such as cloze deletions (`{{c1::answer}}`),
+//! are left untouched, though placeholders nested inside them are substituted.
+//!
+//! # Example TOML
+//!
+//! ```toml
+//! [[generators]]
+//! deck = "Spanish::Verbs"
+//! model = "Basic"
+//! tags = ["verbs", "{{tense}}"]
+//!
+//! [generators.fields]
+//! Front = "{{infinitive}} ({{pronoun}}, {{tense}})"
+//! Back = "{{pronoun}} {{stem}}{{ending}}"
+//!
+//! [[generators.tables.verbs]]
+//! infinitive = "hablar"
+//! stem = "habl"
+//!
+//! [[generators.tables.verbs]]
+//! infinitive = "cantar"
+//! stem = "cant"
+//!
+//! [[generators.tables.forms]]
+//! pronoun = "yo"
+//! tense = "present"
+//! ending = "o"
+//!
+//! [[generators.tables.forms]]
+//! pronoun = "tú"
+//! tense = "present"
+//! ending = "as"
+//! ```
+//!
+//! This expands to four notes (2 verbs x 2 forms).
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::error::{Error, Result};
+use crate::schema::NoteDef;
+
+/// A row in a generator data table: column name to value.
+pub type GeneratorRow = HashMap<String, String>;
+
+/// Generator definition that expands into many notes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratorDef {
+    /// Deck name to add generated notes to.
+    pub deck: String,
+
+    /// Model name for generated notes.
+    pub model: String,
+
+    /// Field templates containing `{{placeholder}}` markers.
+    pub fields: HashMap<String, String>,
+
+    /// Tags for generated notes (placeholders are substituted).
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Named data tables. Notes are generated for every combination of rows.
+    pub tables: BTreeMap<String, Vec<GeneratorRow>>,
+}
+
+impl GeneratorDef {
+    /// Expand this generator into notes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidDefinition`] if the generator has no tables, or
+    /// if a template references a placeholder that no table provides.
+    pub fn expand(&self) -> Result<Vec<NoteDef>> {
+        if self.tables.is_empty() {
+            return Err(Error::InvalidDefinition(format!(
+                "generator for deck '{}' has no data tables",
+                self.deck
+            )));
+        }
+
+        let mut notes = Vec::new();
+        for combination in self.combinations() {
+            let fields = self
+                .fields
+                .iter()
+                .map(|(name, template)| Ok((name.clone(), substitute(template, &combination)?)))
+                .collect::<Result<HashMap<_, _>>>()?;
+            let tags = self
+                .tags
+                .iter()
+                .map(|tag| substitute(tag, &combination))
+                .collect::<Result<Vec<_>>>()?;
+
+            notes.push(NoteDef {
+                deck: self.deck.clone(),
+                model: self.model.clone(),
+                fields,
+                tags,
+                guid: None,
+                note_id: None,
+                cards: None,
+                suspend: Vec::new(),
+            });
+        }
+
+        Ok(notes)
+    }
+
+    /// Every combination of rows across the tables, one row per table.
+    fn combinations(&self) -> Vec<Vec<(&str, &GeneratorRow)>> {
+        let mut combinations: Vec<Vec<(&str, &GeneratorRow)>> = vec![Vec::new()];
+        for (table, rows) in &self.tables {
+            combinations = combinations
+                .into_iter()
+                .flat_map(|prefix| {
+                    rows.iter().map(move |row| {
+                        let mut combination = prefix.clone();
+                        combination.push((table.as_str(), row));
+                        combination
+                    })
+                })
+                .collect();
+        }
+        combinations
+    }
+}
+
+/// Replace `{{placeholder}}` markers in a template with values from the rows.
+fn substitute(template: &str, rows: &[(&str, &GeneratorRow)]) -> Result<String> {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    // Resolve innermost markers first so placeholders nested inside cloze
+    // deletions (`{{c1::{{word}}}}`) are substituted
+    while let Some(end) = rest.find("}}") {
+        let Some(start) = rest[..end].rfind("{{") else {
+            output.push_str(&rest[..end + 2]);
+            rest = &rest[end + 2..];
+            continue;
+        };
+        let key = rest[start + 2..end].trim();
+        output.push_str(&rest[..start]);
+
+        if key.contains("::") {
+            // Cloze deletion or similar Anki syntax; keep it verbatim
+            output.push_str(&rest[start..end + 2]);
+        } else {
+            output.push_str(lookup(key, rows).ok_or_else(|| {
+                Error::InvalidDefinition(format!("unknown generator placeholder '{{{{{key}}}}}'"))
+            })?);
+        }
+        rest = &rest[end + 2..];
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Resolve a plain (`column`) or qualified (`table.column`) placeholder.
+fn lookup<'a>(key: &str, rows: &[(&str, &'a GeneratorRow)]) -> Option<&'a str> {
+    if let Some((table, column)) = key.split_once('.') {
+        if let Some((_, row)) = rows.iter().find(|(name, _)| *name == table) {
+            return row.get(column).map(String::as_str);
+        }
+    }
+    rows.iter()
+        .find_map(|(_, row)| row.get(key))
+        .map(String::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(pairs: &[(&str, &str)]) -> GeneratorRow {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn generator(fields: &[(&str, &str)], tables: &[(&str, Vec<GeneratorRow>)]) -> GeneratorDef {
+        GeneratorDef {
+            deck: "Deck".to_string(),
+            model: "Basic".to_string(),
+            fields: row(fields),
+            tags: Vec::new(),
+            tables: tables
+                .iter()
+                .map(|(name, rows)| (name.to_string(), rows.clone()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_expand_cartesian_product() {
+        let def = generator(
+            &[("Front", "{{word}} / {{pronoun}}")],
+            &[
+                ("words", vec![row(&[("word", "a")]), row(&[("word", "b")])]),
+                (
+                    "pronouns",
+                    vec![row(&[("pronoun", "x")]), row(&[("pronoun", "y")])],
+                ),
+            ],
+        );
+
+        let fronts: Vec<String> = def
+            .expand()
+            .unwrap()
+            .into_iter()
+            .map(|n| n.fields["Front"].clone())
+            .collect();
+        assert_eq!(fronts, vec!["a / x", "b / x", "a / y", "b / y"]);
+    }
+
+    #[test]
+    fn test_qualified_placeholder_and_cloze() {
+        let def = generator(
+            &[("Text", "{{c1::{{left.name}}}} and {{right.name}}")],
+            &[
+                ("left", vec![row(&[("name", "L")])]),
+                ("right", vec![row(&[("name", "R")])]),
+            ],
+        );
+
+        let notes = def.expand().unwrap();
+        assert_eq!(notes[0].fields["Text"], "{{c1::L}} and R");
+    }
+
+    #[test]
+    fn test_unknown_placeholder() {
+        let def = generator(
+            &[("Front", "{{missing}}")],
+            &[("words", vec![row(&[("word", "a")])])],
+        );
+
+        assert!(matches!(def.expand(), Err(Error::InvalidDefinition(_))));
+    }
+}
@@ -0,0 +1,85 @@
+//! Stable note identity shared between TOML and Anki.
+//!
+//! AnkiConnect doesn't expose Anki's own note GUIDs, so a note's
+//! [`guid`](crate::NoteDef::guid) is stored in Anki as a tag,
+//! `ankit-guid::<guid>`. Diff and sync match notes on it before falling
+//! back to the first field, and keep it out of tag comparisons.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Prefix of the tag holding a note's GUID in Anki.
+pub(crate) const TAG_PREFIX: &str = "ankit-guid::";
+
+const ALPHABET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Generate a new random GUID.
+///
+/// Only alphanumeric characters are used, so the GUID is always a valid tag.
+pub(crate) fn generate() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default();
+    // RandomState is randomly keyed, so this doesn't need a rand dependency
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(nanos);
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    let mut n = hasher.finish();
+
+    let mut guid = String::with_capacity(11);
+    for _ in 0..11 {
+        guid.push(ALPHABET[(n % 62) as usize] as char);
+        n /= 62;
+    }
+    guid
+}
+
+/// The Anki tag that stores `guid`.
+pub(crate) fn tag(guid: &str) -> String {
+    format!("{}{}", TAG_PREFIX, guid)
+}
+
+/// Split an Anki note's tags into its GUID, if tagged, and the other tags.
+pub(crate) fn split_tags(tags: Vec<String>) -> (Option<String>, Vec<String>) {
+    let mut guid = None;
+    let mut rest = Vec::with_capacity(tags.len());
+    for tag in tags {
+        match tag.strip_prefix(TAG_PREFIX) {
+            Some(value) if guid.is_none() && !value.is_empty() => guid = Some(value.to_string()),
+            Some(_) => {}
+            None => rest.push(tag),
+        }
+    }
+    (guid, rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_is_unique_and_tag_safe() {
+        let a = generate();
+        let b = generate();
+        assert_ne!(a, b);
+        assert_eq!(a.len(), 11);
+        assert!(a.chars().all(|c| c.is_ascii_alphanumeric()));
+    }
+
+    #[test]
+    fn test_split_tags() {
+        let tags = vec!["verbs".to_string(), tag("abc123"), "chapter1".to_string()];
+        let (guid, rest) = split_tags(tags);
+        assert_eq!(guid.as_deref(), Some("abc123"));
+        assert_eq!(rest, vec!["verbs", "chapter1"]);
+
+        let (guid, rest) = split_tags(vec!["verbs".to_string()]);
+        assert!(guid.is_none());
+        assert_eq!(rest, vec!["verbs"]);
+    }
+}
@@ -0,0 +1,400 @@
+//! Multi-file deck definitions.
+//!
+//! A definition can list other files in `include`. Each included file holds
+//! more models, decks, notes, media, generators, and occlusions, but no
+//! `[package]`, and may include further files. Entries are paths relative to
+//! the including file, and may use `*` and `?` in the file name:
+//!
+//! ```toml
+//! include = ["models.toml", "notes/*.toml"]
+//!
+//! [package]
+//! name = "Japanese"
+//! ```
+//!
+//! Everything is merged into the root definition, in include order with
+//! glob matches sorted by name. A model or deck may only be defined in one
+//! file, and the same note (matched by GUID, or by model and first field)
+//! may not appear in two files. Relative media and CSS include paths in an
+//! included file are resolved against that file's directory.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::error::{Error, Result};
+use crate::format::Format;
+use crate::generator::GeneratorDef;
+use crate::occlusion::OcclusionDef;
+use crate::schema::{DeckDef, DeckDefinition, MediaDef, ModelDef, NoteDef};
+
+/// Contents of an included file.
+#[derive(Debug, Deserialize)]
+struct Fragment {
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default)]
+    models: Vec<ModelDef>,
+    #[serde(default)]
+    decks: Vec<DeckDef>,
+    #[serde(default)]
+    notes: Vec<NoteDef>,
+    #[serde(default)]
+    media: Vec<MediaDef>,
+    #[serde(default)]
+    generators: Vec<GeneratorDef>,
+    #[serde(default)]
+    occlusions: Vec<OcclusionDef>,
+}
+
+/// Merges included files into a definition, remembering which file each
+/// model, deck, and note came from.
+struct Merger {
+    base: PathBuf,
+    visited: HashSet<PathBuf>,
+    model_sources: Vec<PathBuf>,
+    deck_sources: Vec<PathBuf>,
+    note_sources: Vec<PathBuf>,
+}
+
+/// Load every file `definition` includes, leaving `include` empty.
+///
+/// `root` is the file the definition was read from, if any. Does nothing
+/// for definitions without includes.
+pub(crate) fn resolve(definition: &mut DeckDefinition, root: Option<&Path>) -> Result<()> {
+    if definition.include.is_empty() {
+        return Ok(());
+    }
+
+    let root_name = root.map_or_else(|| PathBuf::from("<root>"), Path::to_path_buf);
+    let mut merger = Merger {
+        base: definition.base_dir.clone().unwrap_or_default(),
+        visited: root
+            .and_then(|r| r.canonicalize().ok())
+            .into_iter()
+            .collect(),
+        model_sources: vec![root_name.clone(); definition.models.len()],
+        deck_sources: vec![root_name.clone(); definition.decks.len()],
+        note_sources: vec![root_name; definition.notes.len()],
+    };
+
+    let base = merger.base.clone();
+    for pattern in std::mem::take(&mut definition.include) {
+        for path in expand(&base, &pattern)? {
+            merger.load(definition, &path)?;
+        }
+    }
+    merger.check_duplicates(definition)
+}
+
+impl Merger {
+    fn load(&mut self, definition: &mut DeckDefinition, path: &Path) -> Result<()> {
+        let canonical = path.canonicalize().map_err(|e| cannot_read(path, e))?;
+        if !self.visited.insert(canonical) {
+            return Ok(());
+        }
+
+        let content = std::fs::read_to_string(path).map_err(|e| cannot_read(path, e))?;
+        let mut fragment: Fragment = Format::from_path(path)
+            .parse(&content)
+            .map_err(|e| Error::InvalidDefinition(format!("{}: {}", path.display(), e)))?;
+
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let relative_dir = dir.strip_prefix(&self.base).unwrap_or(dir);
+        for model in &mut fragment.models {
+            for include in &mut model.include {
+                *include = rebase(relative_dir, include);
+            }
+        }
+        for media in &mut fragment.media {
+            if !media.path.is_empty() {
+                media.path = rebase(relative_dir, &media.path);
+            }
+        }
+        for generator in std::mem::take(&mut fragment.generators) {
+            fragment.notes.extend(generator.expand()?);
+        }
+
+        let source = path.to_path_buf();
+        self.model_sources
+            .extend(std::iter::repeat_n(source.clone(), fragment.models.len()));
+        self.deck_sources
+            .extend(std::iter::repeat_n(source.clone(), fragment.decks.len()));
+        self.note_sources
+            .extend(std::iter::repeat_n(source, fragment.notes.len()));
+        definition.models.extend(fragment.models);
+        definition.decks.extend(fragment.decks);
+        definition.notes.extend(fragment.notes);
+        definition.occlusions.extend(fragment.occlusions);
+        for media in fragment.media {
+            if !definition.media.iter().any(|m| m.name == media.name) {
+                definition.media.push(media);
+            }
+        }
+
+        for pattern in fragment.include {
+            for included in expand(dir, &pattern)? {
+                self.load(definition, &included)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Fail if a model, deck, or note is defined in more than one file.
+    fn check_duplicates(&self, definition: &DeckDefinition) -> Result<()> {
+        let models = definition.models.iter().map(|m| format!("'{}'", m.name));
+        check_unique("model", models.zip(&self.model_sources))?;
+
+        let decks = definition.decks.iter().map(|d| format!("'{}'", d.name));
+        check_unique("deck", decks.zip(&self.deck_sources))?;
+
+        let notes = definition.notes.iter().map(|note| match note.guid {
+            Some(ref guid) => format!("with guid '{}'", guid),
+            None => {
+                let first_field = definition
+                    .get_model(&note.model)
+                    .and_then(|model| note.fields_ordered(model).into_iter().next())
+                    .unwrap_or_default();
+                format!("'{}' ({})", first_field, note.model)
+            }
+        });
+        check_unique("note", notes.zip(&self.note_sources))
+    }
+}
+
+fn check_unique<'a>(kind: &str, items: impl Iterator<Item = (String, &'a PathBuf)>) -> Result<()> {
+    let mut seen: HashMap<String, &PathBuf> = HashMap::new();
+    for (key, source) in items {
+        match seen.get(&key) {
+            Some(first) if *first != source => {
+                return Err(Error::InvalidDefinition(format!(
+                    "{} {} is defined in both {} and {}",
+                    kind,
+                    key,
+                    first.display(),
+                    source.display()
+                )));
+            }
+            Some(_) => {}
+            None => {
+                seen.insert(key, source);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Files matching an include pattern, relative to `dir`, sorted by name.
+fn expand(dir: &Path, pattern: &str) -> Result<Vec<PathBuf>> {
+    let path = dir.join(pattern);
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    if !name.contains(['*', '?']) {
+        return Ok(vec![path]);
+    }
+
+    let parent = path.parent().unwrap_or(Path::new(""));
+    if parent.to_string_lossy().contains(['*', '?']) {
+        return Err(Error::InvalidDefinition(format!(
+            "include '{}': wildcards are only supported in file names",
+            pattern
+        )));
+    }
+    let listing = if parent.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        parent
+    };
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(listing)
+        .map_err(|e| cannot_read(parent, e))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|file| wildcard_match(name, file))
+        })
+        .map(|entry| parent.join(entry.file_name()))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Match `name` against a pattern where `*` matches any run of characters
+/// and `?` matches one character.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Make a path from an included file relative to the root's directory.
+fn rebase(dir: &Path, path: &str) -> String {
+    if Path::new(path).is_absolute() || dir.as_os_str().is_empty() {
+        path.to_string()
+    } else {
+        dir.join(path).to_string_lossy().into_owned()
+    }
+}
+
+fn cannot_read(path: &Path, e: std::io::Error) -> Error {
+    Error::InvalidDefinition(format!("cannot read include '{}': {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*.toml", "verbs.toml"));
+        assert!(wildcard_match("part?.toml", "part1.toml"));
+        assert!(wildcard_match("*", "anything"));
+        assert!(wildcard_match("a*b*c", "aXXbYc"));
+        assert!(!wildcard_match("*.toml", "verbs.yaml"));
+        assert!(!wildcard_match("part?.toml", "part10.toml"));
+    }
+
+    const ROOT: &str = r#"
+include = ["models.toml", "notes/*.toml"]
+
+[package]
+name = "Split"
+
+[[decks]]
+name = "Test"
+"#;
+
+    const MODELS: &str = r#"
+[[models]]
+name = "Basic"
+fields = ["Front", "Back"]
+
+[[models.templates]]
+name = "Card 1"
+front = "{{Front}}"
+back = "{{Back}}"
+"#;
+
+    fn note(front: &str) -> String {
+        format!(
+            "[[notes]]\ndeck = \"Test\"\nmodel = \"Basic\"\n\n[notes.fields]\nFront = \"{}\"\n",
+            front
+        )
+    }
+
+    fn write_package(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, content) in files {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_include_merges_files() {
+        let verbs = note("taberu") + "\n[[media]]\nname = \"a.mp3\"\npath = \"audio/a.mp3\"\n";
+        let dir = write_package(&[
+            ("deck.toml", ROOT),
+            ("models.toml", MODELS),
+            ("notes/verbs.toml", &verbs),
+            ("notes/nouns.toml", &note("neko")),
+            ("notes/readme.md", "not a definition"),
+        ]);
+
+        let definition = DeckDefinition::from_file(dir.path().join("deck.toml")).unwrap();
+        assert!(definition.include.is_empty());
+        assert_eq!(definition.models.len(), 1);
+        let fronts: Vec<_> = definition
+            .notes
+            .iter()
+            .map(|n| n.fields["Front"].as_str())
+            .collect();
+        assert_eq!(fronts, ["neko", "taberu"]);
+        assert_eq!(
+            Path::new(&definition.media[0].path),
+            Path::new("notes/audio/a.mp3")
+        );
+    }
+
+    #[test]
+    fn test_include_rejects_duplicate_notes() {
+        let dir = write_package(&[
+            ("deck.toml", ROOT),
+            ("models.toml", MODELS),
+            ("notes/a.toml", &note("neko")),
+            ("notes/b.toml", &note("neko")),
+        ]);
+
+        let err = DeckDefinition::from_file(dir.path().join("deck.toml"))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("note 'neko' (Basic) is defined in both"),
+            "{}",
+            err
+        );
+        assert!(err.contains("a.toml") && err.contains("b.toml"), "{}", err);
+    }
+
+    #[test]
+    fn test_include_rejects_duplicate_models() {
+        let dir = write_package(&[
+            ("deck.toml", ROOT),
+            ("models.toml", MODELS),
+            ("notes/models.toml", MODELS),
+        ]);
+
+        let err = DeckDefinition::from_file(dir.path().join("deck.toml"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("model 'Basic' is defined in both"), "{}", err);
+    }
+
+    #[test]
+    fn test_include_missing_file() {
+        let dir = write_package(&[("deck.toml", ROOT)]);
+        let err = DeckDefinition::from_file(dir.path().join("deck.toml"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("cannot read include"), "{}", err);
+    }
+
+    #[test]
+    fn test_rebase() {
+        assert_eq!(rebase(Path::new("notes"), "audio.mp3"), "notes/audio.mp3");
+        assert_eq!(rebase(Path::new(""), "audio.mp3"), "audio.mp3");
+        assert_eq!(
+            rebase(Path::new("notes"), "/abs/audio.mp3"),
+            "/abs/audio.mp3"
+        );
+    }
+}
@@ -0,0 +1,619 @@
+//! Importers for other flashcard apps' export formats.
+//!
+//! Each importer turns an export into a [`DeckDefinition`] with ready-made
+//! note types, so a collection can be built into an `.apkg`, imported with
+//! AnkiConnect, or written out as TOML and edited further:
+//!
+//! - [`from_quizlet`]: Quizlet's "Export" text (term and definition per row)
+//! - [`from_remnote`]: RemNote's flashcard CSV export
+//! - [`from_mochi`]: the `data.json` inside a Mochi `.mochi` export (requires
+//!   the `json` feature)
+//!
+//! Cards become notes of a two-field `Basic` type (`Front` and `Back`).
+//! RemNote and Mochi cards with `{{cloze}}` deletions become notes of a
+//! `Cloze` type instead, numbered `{{c1::...}}`, `{{c2::...}}` in order.
+//! Only the note types that are used are added to the definition.
+//!
+//! # Example
+//!
+//! ```
+//! use ankit_builder::interop::{QuizletOptions, from_quizlet};
+//!
+//! let export = "el gato\tthe cat\nel perro\tthe dog\n";
+//! let definition = from_quizlet(export, &QuizletOptions::new("Spanish")).unwrap();
+//! assert_eq!(definition.notes.len(), 2);
+//! assert_eq!(definition.notes[0].fields["Front"], "el gato");
+//! ```
+
+use std::collections::HashMap;
+
+use crate::error::{Error, Result};
+use crate::schema::{DeckDef, DeckDefinition, ModelDef, NoteDef, PackageInfo, TemplateDef};
+
+/// Name of the note type used for question/answer cards.
+pub const BASIC_MODEL: &str = "Basic";
+
+/// Name of the note type used for cards with cloze deletions.
+pub const CLOZE_MODEL: &str = "Cloze";
+
+/// Options for [`from_quizlet`].
+///
+/// The separators match the choices in Quizlet's export dialog. The
+/// defaults are Quizlet's own: a tab between term and definition, and a new
+/// line between rows.
+#[derive(Debug, Clone)]
+pub struct QuizletOptions {
+    /// Deck that receives the cards.
+    pub deck: String,
+    /// Separator between a term and its definition.
+    pub term_separator: String,
+    /// Separator between rows.
+    pub row_separator: String,
+}
+
+impl QuizletOptions {
+    /// Options for importing into `deck` with Quizlet's default separators.
+    pub fn new(deck: impl Into<String>) -> Self {
+        Self {
+            deck: deck.into(),
+            term_separator: "\t".to_string(),
+            row_separator: "\n".to_string(),
+        }
+    }
+
+    /// Set the separator between a term and its definition.
+    pub fn term_separator(mut self, separator: impl Into<String>) -> Self {
+        self.term_separator = separator.into();
+        self
+    }
+
+    /// Set the separator between rows.
+    pub fn row_separator(mut self, separator: impl Into<String>) -> Self {
+        self.row_separator = separator.into();
+        self
+    }
+}
+
+/// Convert a Quizlet export into a deck definition.
+///
+/// Each row becomes a `Basic` note with the term on the front and the
+/// definition on the back. Blank rows are skipped, and line breaks inside a
+/// definition (possible with a custom row separator) become `<br>`.
+///
+/// # Errors
+///
+/// Returns [`Error::InvalidDefinition`] if a row has no term separator or
+/// the export contains no cards.
+pub fn from_quizlet(text: &str, options: &QuizletOptions) -> Result<DeckDefinition> {
+    if options.term_separator.is_empty() || options.row_separator.is_empty() {
+        return Err(Error::InvalidDefinition(
+            "Quizlet separators must not be empty".to_string(),
+        ));
+    }
+
+    let text = text.trim_start_matches('\u{feff}');
+    let text = if options.row_separator == "\n" {
+        text.replace("\r\n", "\n")
+    } else {
+        text.to_string()
+    };
+
+    let mut notes = Vec::new();
+    for (index, row) in text.split(options.row_separator.as_str()).enumerate() {
+        if row.trim().is_empty() {
+            continue;
+        }
+        let (term, definition) =
+            row.split_once(options.term_separator.as_str())
+                .ok_or_else(|| {
+                    Error::InvalidDefinition(format!(
+                        "Quizlet row {} has no term separator: {}",
+                        index + 1,
+                        row.trim()
+                    ))
+                })?;
+        notes.push(basic_note(
+            &options.deck,
+            &line_breaks(term.trim()),
+            &line_breaks(definition.trim()),
+            Vec::new(),
+        ));
+    }
+
+    build_definition(&options.deck, vec![options.deck.clone()], notes, false)
+}
+
+/// Convert a RemNote flashcard CSV export into a deck definition.
+///
+/// The header row names the columns. `Front` (or `Question`) and `Back` (or
+/// `Answer`) are required. An optional `Tags` column holds tags separated by
+/// commas or spaces, and an optional `Deck` (or `Document`) column places
+/// each card in a subdeck of `deck`, with `/` and ` > ` in document paths
+/// becoming `::`.
+///
+/// Text is kept as Markdown, and the note types convert it to HTML when the
+/// deck is built.
+///
+/// # Errors
+///
+/// Returns [`Error::InvalidDefinition`] if the CSV is malformed, lacks the
+/// front or back column, or contains no cards.
+pub fn from_remnote(csv: &str, deck: &str) -> Result<DeckDefinition> {
+    let mut rows = parse_csv(csv.trim_start_matches('\u{feff}'))?.into_iter();
+    let header = rows
+        .next()
+        .ok_or_else(|| Error::InvalidDefinition("RemNote export is empty".to_string()))?;
+
+    let column = |names: &[&str]| {
+        header
+            .iter()
+            .position(|h| names.iter().any(|name| h.trim().eq_ignore_ascii_case(name)))
+    };
+    let (Some(front), Some(back)) = (column(&["front", "question"]), column(&["back", "answer"]))
+    else {
+        return Err(Error::InvalidDefinition(
+            "RemNote export needs Front and Back columns".to_string(),
+        ));
+    };
+    let tags = column(&["tags"]);
+    let subdeck = column(&["deck", "document"]);
+
+    let mut decks = vec![deck.to_string()];
+    let mut notes = Vec::new();
+    for row in rows {
+        let cell = |index: Option<usize>| {
+            index
+                .and_then(|i| row.get(i))
+                .map(|value| value.trim())
+                .unwrap_or_default()
+        };
+        let (front, back) = (cell(Some(front)), cell(Some(back)));
+        if front.is_empty() && back.is_empty() {
+            continue;
+        }
+
+        let note_deck = match subdeck_name(cell(subdeck)) {
+            Some(path) => format!("{}::{}", deck, path),
+            None => deck.to_string(),
+        };
+        if !decks.contains(&note_deck) {
+            decks.push(note_deck.clone());
+        }
+        let tags = cell(tags)
+            .split([',', ' '])
+            .filter(|tag| !tag.is_empty())
+            .map(|tag| tag.trim_start_matches('#').to_string())
+            .collect();
+        notes.push(card_note(&note_deck, front, back, tags));
+    }
+
+    build_definition(deck, decks, notes, true)
+}
+
+/// Convert a Mochi export into a deck definition.
+///
+/// `json` is the `data.json` file inside a `.mochi` export (a ZIP archive).
+/// Mochi decks keep their nesting as `Parent::Child` decks. A card's
+/// Markdown content is split into front and back at the first line
+/// consisting of `---`. Archived and trashed cards are skipped.
+///
+/// Attachments are referenced as `@media/<name>` in Mochi; references are
+/// rewritten to plain file names, so copy the archive's media files next to
+/// the definition and list them under `media` to include them.
+///
+/// # Errors
+///
+/// Returns [`Error::Json`] if `json` is not valid JSON, or
+/// [`Error::InvalidDefinition`] if it contains no cards.
+#[cfg(feature = "json")]
+pub fn from_mochi(json: &str) -> Result<DeckDefinition> {
+    let export: mochi::Export = serde_json::from_str(json)?;
+
+    let names: HashMap<&str, &mochi::Deck> =
+        export.decks.iter().map(|d| (d.id.as_str(), d)).collect();
+    let full_name = |deck: &mochi::Deck| {
+        let mut parts = vec![deck.name.trim()];
+        let mut parent = deck.parent_id.as_deref();
+        // Stop at cycles rather than looping forever
+        while let Some(p) = parent.and_then(|id| names.get(id)) {
+            if parts.len() > names.len() {
+                break;
+            }
+            parts.push(p.name.trim());
+            parent = p.parent_id.as_deref();
+        }
+        parts.reverse();
+        parts.join("::")
+    };
+
+    let mut decks = Vec::new();
+    let mut notes = Vec::new();
+    for deck in &export.decks {
+        if deck.archived || deck.trashed.is_some() {
+            continue;
+        }
+        let name = full_name(deck);
+        for card in &deck.cards {
+            if card.archived || card.trashed.is_some() {
+                continue;
+            }
+            let content = card.content.replace("@media/", "");
+            let (front, back) = split_mochi_card(&content);
+            if front.is_empty() {
+                continue;
+            }
+            notes.push(card_note(&name, front, back, card.tags.clone()));
+        }
+        decks.push(name);
+    }
+
+    let package = match export.decks.iter().find(|d| d.parent_id.is_none()) {
+        Some(root) if export.decks.len() == 1 => root.name.trim().to_string(),
+        _ => "Mochi".to_string(),
+    };
+    build_definition(&package, decks, notes, true)
+}
+
+#[cfg(feature = "json")]
+mod mochi {
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    pub struct Export {
+        #[serde(default)]
+        pub decks: Vec<Deck>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Deck {
+        pub id: String,
+        pub name: String,
+        #[serde(default, rename = "parent-id")]
+        pub parent_id: Option<String>,
+        #[serde(default, rename = "archived?")]
+        pub archived: bool,
+        #[serde(default, rename = "trashed?")]
+        pub trashed: Option<serde_json::Value>,
+        #[serde(default)]
+        pub cards: Vec<Card>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Card {
+        #[serde(default)]
+        pub content: String,
+        #[serde(default)]
+        pub tags: Vec<String>,
+        #[serde(default, rename = "archived?")]
+        pub archived: bool,
+        #[serde(default, rename = "trashed?")]
+        pub trashed: Option<serde_json::Value>,
+    }
+}
+
+/// Split Mochi Markdown into front and back at the first `---` line.
+#[cfg(feature = "json")]
+fn split_mochi_card(content: &str) -> (&str, &str) {
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        if line.trim() == "---" {
+            return (
+                content[..offset].trim(),
+                content[offset + line.len()..].trim(),
+            );
+        }
+        offset += line.len();
+    }
+    (content.trim(), "")
+}
+
+/// A note for a card that may contain `{{cloze}}` deletions.
+fn card_note(deck: &str, front: &str, back: &str, tags: Vec<String>) -> NoteDef {
+    match numbered_clozes(front) {
+        Some(text) => {
+            let fields = HashMap::from([
+                ("Text".to_string(), text),
+                ("Extra".to_string(), back.to_string()),
+            ]);
+            NoteDef {
+                deck: deck.to_string(),
+                model: CLOZE_MODEL.to_string(),
+                fields,
+                tags,
+                guid: None,
+                note_id: None,
+                cards: None,
+                suspend: Vec::new(),
+            }
+        }
+        None => basic_note(deck, front, back, tags),
+    }
+}
+
+fn basic_note(deck: &str, front: &str, back: &str, tags: Vec<String>) -> NoteDef {
+    NoteDef {
+        deck: deck.to_string(),
+        model: BASIC_MODEL.to_string(),
+        fields: HashMap::from([
+            ("Front".to_string(), front.to_string()),
+            ("Back".to_string(), back.to_string()),
+        ]),
+        tags,
+        guid: None,
+        note_id: None,
+        cards: None,
+        suspend: Vec::new(),
+    }
+}
+
+/// Number `{{text}}` deletions as Anki clozes, or `None` if there are none.
+///
+/// Deletions already in Anki syntax (`{{c1::text}}`) are kept as they are.
+fn numbered_clozes(text: &str) -> Option<String> {
+    let mut out = String::with_capacity(text.len() + 16);
+    let mut number = 0;
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let inner = &rest[start + 2..start + 2 + len];
+        out.push_str(&rest[..start]);
+        if anki_cloze_number(inner) > 0 {
+            out.push_str(&rest[start..start + len + 4]);
+        } else {
+            number += 1;
+            out.push_str(&format!("{{{{c{}::{}}}}}", number, inner));
+        }
+        number = number.max(anki_cloze_number(inner));
+        rest = &rest[start + len + 4..];
+    }
+    out.push_str(rest);
+
+    (number > 0).then_some(out)
+}
+
+/// The number of an Anki cloze body like `c2::text`, or 0.
+fn anki_cloze_number(inner: &str) -> usize {
+    inner
+        .strip_prefix('c')
+        .and_then(|rest| rest.split_once("::"))
+        .and_then(|(number, _)| number.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Turn a RemNote document path into a subdeck name.
+fn subdeck_name(path: &str) -> Option<String> {
+    let parts: Vec<&str> = path
+        .split(['/', '>'])
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("::"))
+}
+
+fn line_breaks(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\n', "<br>")
+}
+
+/// Assemble a definition with the note types the notes use.
+fn build_definition(
+    package: &str,
+    decks: Vec<String>,
+    notes: Vec<NoteDef>,
+    markdown: bool,
+) -> Result<DeckDefinition> {
+    if notes.is_empty() {
+        return Err(Error::InvalidDefinition(format!(
+            "no cards found to import into '{}'",
+            package
+        )));
+    }
+
+    let mut models = Vec::new();
+    if notes.iter().any(|n| n.model == BASIC_MODEL) {
+        let mut model = basic_model();
+        if markdown {
+            model.markdown_fields = model.fields.clone();
+        }
+        models.push(model);
+    }
+    if notes.iter().any(|n| n.model == CLOZE_MODEL) {
+        let mut model = ModelDef::cloze(CLOZE_MODEL, vec!["Text", "Extra"]);
+        if markdown {
+            model.markdown_fields = model.fields.clone();
+        }
+        models.push(model);
+    }
+
+    Ok(DeckDefinition {
+        include: Vec::new(),
+        package: PackageInfo::new(package),
+        models,
+        decks: decks
+            .into_iter()
+            .map(|name| DeckDef {
+                name,
+                description: None,
+                id: None,
+                options: None,
+            })
+            .collect(),
+        notes,
+        media: Vec::new(),
+        generators: Vec::new(),
+        occlusions: Vec::new(),
+        base_dir: None,
+    })
+}
+
+fn basic_model() -> ModelDef {
+    ModelDef {
+        name: BASIC_MODEL.to_string(),
+        fields: vec!["Front".to_string(), "Back".to_string()],
+        templates: vec![TemplateDef {
+            name: "Card 1".to_string(),
+            front: "{{Front}}".to_string(),
+            back: "{{FrontSide}}<hr id=answer>{{Back}}".to_string(),
+        }],
+        css: None,
+        include: vec![],
+        sort_field: None,
+        id: None,
+        markdown_fields: vec![],
+        model_type: None,
+        version: None,
+    }
+}
+
+/// Parse CSV with quoted fields, doubled quotes, and newlines inside quotes.
+fn parse_csv(text: &str) -> Result<Vec<Vec<String>>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => quoted = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(Error::InvalidDefinition(
+            "unterminated quoted field in CSV".to_string(),
+        ));
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quizlet_default_separators() {
+        let export = "\u{feff}el gato\tthe cat\r\n\r\nel perro\tthe dog\r\n";
+        let definition = from_quizlet(export, &QuizletOptions::new("Spanish")).unwrap();
+
+        assert_eq!(definition.package.name, "Spanish");
+        assert_eq!(definition.models.len(), 1);
+        assert_eq!(definition.models[0].name, BASIC_MODEL);
+        assert_eq!(definition.notes.len(), 2);
+        assert_eq!(definition.notes[1].fields["Front"], "el perro");
+        assert_eq!(definition.notes[1].fields["Back"], "the dog");
+        definition.validate().unwrap();
+    }
+
+    #[test]
+    fn test_quizlet_custom_separators() {
+        let options = QuizletOptions::new("Capitals")
+            .term_separator(" - ")
+            .row_separator(";");
+        let export = "France - Paris\nin Europe;Japan - Tokyo;";
+        let definition = from_quizlet(export, &options).unwrap();
+
+        assert_eq!(definition.notes.len(), 2);
+        assert_eq!(definition.notes[0].fields["Back"], "Paris<br>in Europe");
+    }
+
+    #[test]
+    fn test_quizlet_missing_separator() {
+        let err = from_quizlet("no tab here\n", &QuizletOptions::new("Deck")).unwrap_err();
+        assert!(err.to_string().contains("row 1"));
+    }
+
+    #[test]
+    fn test_remnote_columns_and_clozes() {
+        let csv = concat!(
+            "Question,Answer,Tags,Document\n",
+            "\"What is 2 + 2?\",4,\"math, basics\",Math/Arithmetic\n",
+            "\"The {{mitochondria}} is the {{powerhouse}} of the cell\",,biology,Biology\n",
+            "\"A \"\"quoted\"\"\nanswer\",yes,,\n",
+        );
+        let definition = from_remnote(csv, "RemNote").unwrap();
+
+        assert_eq!(definition.notes.len(), 3);
+        assert_eq!(definition.models.len(), 2);
+        assert_eq!(definition.notes[0].deck, "RemNote::Math::Arithmetic");
+        assert_eq!(definition.notes[0].tags, vec!["math", "basics"]);
+        assert_eq!(
+            definition.notes[1].fields["Text"],
+            "The {{c1::mitochondria}} is the {{c2::powerhouse}} of the cell"
+        );
+        assert_eq!(definition.notes[2].deck, "RemNote");
+        assert_eq!(definition.notes[2].fields["Front"], "A \"quoted\"\nanswer");
+        assert!(definition.get_deck("RemNote::Biology").is_some());
+        definition.validate().unwrap();
+    }
+
+    #[test]
+    fn test_remnote_requires_columns() {
+        let err = from_remnote("Name,Value\na,b\n", "RemNote").unwrap_err();
+        assert!(err.to_string().contains("Front and Back"));
+    }
+
+    #[test]
+    fn test_numbered_clozes_keeps_anki_syntax() {
+        assert_eq!(
+            numbered_clozes("{{c1::a}} and {{b}}").as_deref(),
+            Some("{{c1::a}} and {{c2::b}}")
+        );
+        assert_eq!(numbered_clozes("no deletions"), None);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_mochi_decks_and_cards() {
+        let json = r#"{
+            "version": 2,
+            "decks": [
+                {"id": "a", "name": "Languages", "cards": []},
+                {"id": "b", "name": "French", "parent-id": "a", "cards": [
+                    {"content": "bonjour\n---\nhello ![](@media/hi.mp3)", "tags": ["greeting"]},
+                    {"content": "Le {{chat}} est noir", "tags": []},
+                    {"content": "old", "archived?": true}
+                ]},
+                {"id": "c", "name": "Trash", "trashed?": "2024-01-01", "cards": [
+                    {"content": "gone"}
+                ]}
+            ]
+        }"#;
+        let definition = from_mochi(json).unwrap();
+
+        assert_eq!(definition.package.name, "Mochi");
+        assert_eq!(definition.notes.len(), 2);
+        assert_eq!(definition.notes[0].deck, "Languages::French");
+        assert_eq!(definition.notes[0].fields["Back"], "hello ![](hi.mp3)");
+        assert_eq!(definition.notes[0].tags, vec!["greeting"]);
+        assert_eq!(
+            definition.notes[1].fields["Text"],
+            "Le {{c1::chat}} est noir"
+        );
+        assert!(definition.get_deck("Languages").is_some());
+        assert!(definition.get_deck("Trash").is_none());
+        definition.validate().unwrap();
+    }
+}
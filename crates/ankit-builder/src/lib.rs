@@ -7,7 +7,9 @@
 //! # Features
 //!
 //! - `apkg` (default): Enable .apkg file generation
-//! - `connect` (default): Enable AnkiConnect import
+//! - `connect` (default): Enable AnkiConnect import, sync, and watch mode
+//! - `yaml`: Read and write deck definitions as YAML
+//! - `json`: Read and write deck definitions as JSON, and import Mochi exports
 //!
 //! # Example TOML Format
 //!
@@ -72,7 +74,14 @@
 
 pub mod cloze;
 pub mod error;
+pub mod format;
+pub mod generator;
+pub mod include;
+pub mod interop;
+pub mod lint;
 pub mod markdown;
+pub mod occlusion;
+pub mod scaffold;
 pub mod schema;
 
 #[cfg(feature = "apkg")]
@@ -81,6 +90,18 @@ mod sql;
 #[cfg(feature = "apkg")]
 mod apkg;
 
+#[cfg(feature = "apkg")]
+mod compare;
+
+#[cfg(feature = "apkg")]
+mod reader;
+
+#[cfg(feature = "apkg")]
+mod release;
+
+#[cfg(feature = "connect")]
+mod base;
+
 #[cfg(feature = "connect")]
 mod connect;
 
@@ -90,30 +111,63 @@ mod diff;
 #[cfg(feature = "connect")]
 mod export;
 
+#[cfg(feature = "connect")]
+mod guid;
+
+#[cfg(any(feature = "apkg", feature = "connect"))]
+mod model_version;
+
 #[cfg(feature = "connect")]
 mod sync;
 
+#[cfg(feature = "connect")]
+mod watch;
+
 pub use error::{Error, Result};
-pub use schema::{DeckDef, DeckDefinition, MediaDef, ModelDef, NoteDef, PackageInfo, TemplateDef};
+pub use format::Format;
+pub use generator::GeneratorDef;
+pub use lint::{Diagnostic, LintReport, Severity};
+pub use occlusion::{MaskDef, OcclusionDef, OcclusionMode};
+pub use scaffold::ScaffoldKind;
+pub use schema::{
+    DeckDef, DeckDefinition, DeckOptions, MediaDef, ModelDef, NoteDef, PackageInfo, TemplateDef,
+};
 
 #[cfg(feature = "apkg")]
 pub use apkg::ApkgBuilder;
 
+#[cfg(feature = "apkg")]
+pub use compare::{ChangedNote, PackageDiff, PackageNote};
+
+#[cfg(feature = "apkg")]
+pub use reader::ApkgReader;
+
+#[cfg(feature = "apkg")]
+pub use release::{Changelog, Release, ReleaseBuilder, VersionBump};
+
+#[cfg(feature = "connect")]
+pub use base::{BaseNote, SyncBase};
+
 #[cfg(feature = "connect")]
 pub use connect::{ConnectImporter, ImportResult};
 
 #[cfg(feature = "connect")]
-pub use diff::{DeckDiff, FieldChange, ModifiedNote, NoteDiff, TagChanges};
+pub use diff::{
+    DeckDiff, FieldChange, MatchedNote, ModifiedNote, NoteDiff, TagChanges, UntaggedNote,
+};
 
 #[cfg(feature = "connect")]
-pub use export::DeckExporter;
+pub use export::{DeckExporter, NoteOrder};
 
 #[cfg(feature = "connect")]
 pub use sync::{
-    ConflictResolution, ResolvedConflict, SyncConflict, SyncError, SyncNote, SyncPlan, SyncResult,
-    SyncStrategy, SyncedNote,
+    ConflictResolution, MergedNote, ModelUpgrade, ResolvedConflict, SyncConflict, SyncError,
+    SyncNote, SyncPlan, SyncResult, SyncStrategy, SyncTemplate, SyncedNote,
 };
 
+#[cfg(feature = "connect")]
+pub use watch::{DeckWatcher, WatchEvent};
+
 /// Unified builder that can output to either .apkg or AnkiConnect.
 ///
 /// `DeckBuilder` provides a high-level interface for working with deck definitions,
@@ -153,8 +207,12 @@ pub use sync::{
 /// ```
 pub struct DeckBuilder {
     definition: DeckDefinition,
-    #[cfg(feature = "apkg")]
+    #[cfg(any(feature = "apkg", feature = "connect"))]
     media_base_path: Option<std::path::PathBuf>,
+    #[cfg(feature = "connect")]
+    sync_base: Option<SyncBase>,
+    #[cfg(feature = "connect")]
+    key_normalization: ankit::key::KeyNormalization,
 }
 
 impl DeckBuilder {
@@ -183,16 +241,24 @@ impl DeckBuilder {
     pub fn new(definition: DeckDefinition) -> Self {
         Self {
             definition,
-            #[cfg(feature = "apkg")]
+            #[cfg(any(feature = "apkg", feature = "connect"))]
             media_base_path: None,
+            #[cfg(feature = "connect")]
+            sync_base: None,
+            #[cfg(feature = "connect")]
+            key_normalization: Default::default(),
         }
     }
 
-    /// Load a deck definition from a TOML file.
+    /// Load a deck definition from a file.
+    ///
+    /// TOML, YAML, and JSON are detected by extension; see
+    /// [`DeckDefinition::from_file()`].
     ///
     /// # Errors
     ///
-    /// Returns an error if the file cannot be read or contains invalid TOML.
+    /// Returns an error if the file cannot be read, contains an invalid
+    /// definition, or needs a format feature that isn't enabled.
     ///
     /// # Example
     ///
@@ -249,6 +315,53 @@ impl DeckBuilder {
         Ok(Self::new(definition))
     }
 
+    /// Start a new deck from a [starter definition](DeckDefinition::scaffold).
+    ///
+    /// To start from a file with the explanatory comments kept, write it
+    /// with [`ScaffoldKind::write()`] instead.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ankit_builder::{DeckBuilder, ScaffoldKind};
+    ///
+    /// # fn main() -> ankit_builder::Result<()> {
+    /// let builder = DeckBuilder::scaffold(ScaffoldKind::LanguageAudio);
+    /// builder.write_toml("spanish.toml")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn scaffold(kind: ScaffoldKind) -> Self {
+        Self::new(DeckDefinition::scaffold(kind))
+    }
+
+    /// Load a deck definition from an existing `.apkg` file.
+    ///
+    /// Media files in the package are extracted into `media_dir`, which is
+    /// also set as the [media base path](Self::media_base_path) so the
+    /// package can be rebuilt as-is.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ankit_builder::DeckBuilder;
+    ///
+    /// # fn main() -> ankit_builder::Result<()> {
+    /// let builder = DeckBuilder::from_apkg("shared_deck.apkg", "media")?;
+    /// builder.write_toml("shared_deck.toml")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "apkg")]
+    pub fn from_apkg(
+        path: impl AsRef<std::path::Path>,
+        media_dir: impl AsRef<std::path::Path>,
+    ) -> Result<Self> {
+        let reader = ApkgReader::open(path)?;
+        reader.extract_media(&media_dir)?;
+        Ok(Self::new(reader.read()?).media_base_path(media_dir))
+    }
+
     /// Set the base path for resolving media file paths.
     ///
     /// When your TOML definition references media files with relative paths,
@@ -268,12 +381,61 @@ impl DeckBuilder {
     /// # Ok(())
     /// # }
     /// ```
-    #[cfg(feature = "apkg")]
+    #[cfg(any(feature = "apkg", feature = "connect"))]
     pub fn media_base_path(mut self, path: impl AsRef<std::path::Path>) -> Self {
         self.media_base_path = Some(path.as_ref().to_path_buf());
         self
     }
 
+    /// Set the snapshot from the previous sync, enabling three-way merge.
+    ///
+    /// [`plan_sync()`](Self::plan_sync) and [`sync()`](Self::sync) then
+    /// merge fields changed on only one side instead of reporting them as
+    /// conflicts. Store [`SyncResult::base`] after each sync to pass it here
+    /// next time.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ankit_builder::{DeckBuilder, SyncBase};
+    ///
+    /// # fn main() -> ankit_builder::Result<()> {
+    /// let base = SyncBase::from_file(SyncBase::path_for("deck.toml"))?;
+    /// let builder = DeckBuilder::from_file("deck.toml")?.sync_base(base);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "connect")]
+    pub fn sync_base(mut self, base: SyncBase) -> Self {
+        self.sync_base = Some(base);
+        self
+    }
+
+    /// Set how first field values are normalized when matching TOML notes
+    /// to Anki notes that have no GUID tag.
+    ///
+    /// The default ignores HTML, extra whitespace, and case. Decks in
+    /// languages with full-width characters or inconsistent accents may
+    /// need more folding.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ankit::key::KeyNormalization;
+    /// use ankit_builder::DeckBuilder;
+    ///
+    /// # fn main() -> ankit_builder::Result<()> {
+    /// let builder = DeckBuilder::from_file("deck.toml")?
+    ///     .key_normalization(KeyNormalization::cjk());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "connect")]
+    pub fn key_normalization(mut self, normalization: ankit::key::KeyNormalization) -> Self {
+        self.key_normalization = normalization;
+        self
+    }
+
     /// Get the underlying deck definition.
     ///
     /// Use this to inspect the parsed TOML structure, including package metadata,
@@ -335,12 +497,12 @@ impl DeckBuilder {
     /// Import the deck definition via AnkiConnect.
     ///
     /// Imports notes one at a time into a running Anki instance. Creates
-    /// any missing decks automatically.
+    /// any missing decks automatically and stores media files.
     ///
     /// # Requirements
     ///
     /// - Anki must be running with the AnkiConnect add-on installed
-    /// - Note types (models) must already exist in Anki
+    /// - Note types (models) must already exist in Anki, except image occlusion models
     ///
     /// # Errors
     ///
@@ -365,7 +527,10 @@ impl DeckBuilder {
     /// ```
     #[cfg(feature = "connect")]
     pub async fn import_connect(&self) -> Result<ImportResult> {
-        let importer = ConnectImporter::new(self.definition.clone());
+        let mut importer = ConnectImporter::new(self.definition.clone());
+        if let Some(ref media_path) = self.media_base_path {
+            importer = importer.media_base_path(media_path);
+        }
         importer.import().await
     }
 
@@ -377,7 +542,7 @@ impl DeckBuilder {
     /// # Requirements
     ///
     /// - Anki must be running with the AnkiConnect add-on installed
-    /// - Note types (models) must already exist in Anki
+    /// - Note types (models) must already exist in Anki, except image occlusion models
     ///
     /// # Example
     ///
@@ -393,7 +558,10 @@ impl DeckBuilder {
     /// ```
     #[cfg(feature = "connect")]
     pub async fn import_connect_batch(&self) -> Result<ImportResult> {
-        let importer = ConnectImporter::new(self.definition.clone());
+        let mut importer = ConnectImporter::new(self.definition.clone());
+        if let Some(ref media_path) = self.media_base_path {
+            importer = importer.media_base_path(media_path);
+        }
         importer.import_batch().await
     }
 
@@ -443,7 +611,8 @@ impl DeckBuilder {
     /// [`AnkiClient`](ankit::AnkiClient) with non-default settings.
     #[cfg(feature = "connect")]
     pub async fn diff_connect_with_client(&self, client: &ankit::AnkiClient) -> Result<DeckDiff> {
-        let differ = diff::DeckDiffer::new(client, &self.definition);
+        let differ = diff::DeckDiffer::new(client, &self.definition)
+            .normalization(self.key_normalization.clone());
         differ.diff().await
     }
 
@@ -489,8 +658,7 @@ impl DeckBuilder {
     /// [`AnkiClient`](ankit::AnkiClient) with non-default settings.
     #[cfg(feature = "connect")]
     pub async fn plan_sync_with_client(&self, client: &ankit::AnkiClient) -> Result<SyncPlan> {
-        let syncer = sync::DeckSyncer::new(client, self.definition.clone());
-        syncer.plan().await
+        self.syncer(client).plan().await
     }
 
     /// Execute bidirectional sync with Anki.
@@ -547,8 +715,62 @@ impl DeckBuilder {
         client: &ankit::AnkiClient,
         strategy: SyncStrategy,
     ) -> Result<SyncResult> {
-        let syncer = sync::DeckSyncer::new(client, self.definition.clone());
-        syncer.sync(strategy).await
+        self.syncer(client).sync(strategy).await
+    }
+
+    /// A syncer for this definition, with the sync base if one is set.
+    #[cfg(feature = "connect")]
+    fn syncer<'a>(&self, client: &'a ankit::AnkiClient) -> sync::DeckSyncer<'a> {
+        let syncer = sync::DeckSyncer::new(client, self.definition.clone())
+            .with_normalization(self.key_normalization.clone());
+        match &self.sync_base {
+            Some(base) => syncer.with_base(base.clone()),
+            None => syncer,
+        }
+    }
+
+    /// Watch a TOML file or directory and sync changes to Anki.
+    ///
+    /// Every watched file is synced once at startup, then again whenever it
+    /// changes. When `path` is a directory, all `.toml` files directly inside
+    /// it are watched. `on_event` is called with the outcome of each sync;
+    /// return [`ControlFlow::Break`](std::ops::ControlFlow::Break) from it to
+    /// stop watching.
+    ///
+    /// Use [`DeckWatcher`] directly to configure the client, poll interval,
+    /// or debounce period.
+    ///
+    /// # Requirements
+    ///
+    /// - Anki must be running with the AnkiConnect add-on installed
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::ops::ControlFlow;
+    /// use ankit_builder::{DeckBuilder, SyncStrategy, WatchEvent};
+    ///
+    /// # async fn example() -> ankit_builder::Result<()> {
+    /// DeckBuilder::watch("vocabulary.toml", SyncStrategy::push_only(), |event| {
+    ///     if let WatchEvent::Synced { result, .. } = event {
+    ///         println!("Pushed {} notes", result.pushed.len());
+    ///     }
+    ///     ControlFlow::Continue(())
+    /// })
+    /// .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "connect")]
+    pub async fn watch<F>(
+        path: impl AsRef<std::path::Path>,
+        strategy: SyncStrategy,
+        on_event: F,
+    ) -> Result<()>
+    where
+        F: FnMut(WatchEvent) -> std::ops::ControlFlow<()>,
+    {
+        DeckWatcher::new(path, strategy).run(on_event).await
     }
 
     /// Export a deck from Anki to a [`DeckBuilder`].
@@ -604,6 +826,14 @@ impl DeckBuilder {
     pub fn write_toml(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
         self.definition.write_toml(path)
     }
+
+    /// Write the deck definition to a file, picking the format from its
+    /// extension.
+    ///
+    /// Convenience method that calls [`DeckDefinition::write_file()`].
+    pub fn write_file(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        self.definition.write_file(path)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,398 @@
+//! Template linting for deck definitions.
+//!
+//! [`DeckDefinition::lint()`] checks each model's card templates against its
+//! declared fields and reports problems that `validate()` does not catch,
+//! because Anki accepts the templates but renders broken or confusing cards.
+//! Each [`Diagnostic`] carries a [`Severity`], so a CI pipeline can fail on
+//! errors while only printing warnings.
+//!
+//! # Example
+//!
+//! ```
+//! use ankit_builder::DeckDefinition;
+//! use ankit_builder::lint::Rule;
+//!
+//! let def = DeckDefinition::parse(r#"
+//! [package]
+//! name = "Example"
+//!
+//! [[models]]
+//! name = "Basic"
+//! fields = ["Front", "Back"]
+//!
+//! [[models.templates]]
+//! name = "Card 1"
+//! front = "{{Front}}"
+//! back = "{{Back}} {{Notes}}"
+//!
+//! [[decks]]
+//! name = "Example"
+//! "#).unwrap();
+//!
+//! let report = def.lint();
+//! assert!(report.has_errors());
+//! assert!(report.diagnostics.iter().any(|d| d.rule == Rule::UnknownField));
+//! ```
+
+use std::collections::HashSet;
+use std::fmt;
+
+use ankit_template::{self as template, Filter, SPECIAL_FIELDS, TagKind, TemplateErrorKind};
+use serde::Serialize;
+
+use crate::schema::{DeckDefinition, ModelDef, TemplateDef};
+
+/// How serious a diagnostic is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Probably intended, but worth a look.
+    Warning,
+    /// The template is broken and will produce bad or empty cards.
+    Error,
+}
+
+/// The check that produced a diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    /// A template references a field the model does not declare.
+    UnknownField,
+    /// A front template is empty.
+    EmptyFront,
+    /// A front template does not reference any field.
+    NoFieldOnFront,
+    /// A back template of a standard model does not include `{{FrontSide}}`.
+    MissingFrontSide,
+    /// A `cloze:` filter is used on a non-cloze model.
+    ClozeOnStandardModel,
+    /// A cloze model's front template has no `cloze:` filter.
+    MissingCloze,
+    /// Two templates in a model share a name.
+    DuplicateTemplate,
+    /// A standard model has no templates.
+    NoTemplates,
+    /// A `{{#Field}}` or `{{^Field}}` section is not closed, or is closed
+    /// out of order.
+    UnbalancedSection,
+}
+
+/// A problem found in a model's templates.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    /// How serious the problem is.
+    pub severity: Severity,
+    /// The check that found it.
+    pub rule: Rule,
+    /// Model the problem is in.
+    pub model: String,
+    /// Template the problem is in, if it is specific to one template.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Human-readable description.
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}: model '{}'", severity, self.model)?;
+        if let Some(template) = &self.template {
+            write!(f, ", template '{}'", template)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// The result of linting a deck definition.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LintReport {
+    /// All problems found, in model and template order.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl LintReport {
+    /// Whether any diagnostic is an error.
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// Whether nothing was found.
+    pub fn is_clean(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    /// Error diagnostics.
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+    }
+
+    /// Warning diagnostics.
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Warning)
+    }
+}
+
+impl DeckDefinition {
+    /// Check model templates for problems.
+    ///
+    /// Reports unknown field references, empty front templates, back
+    /// templates without `{{FrontSide}}`, cloze filters on non-cloze models,
+    /// duplicated template names, and unbalanced conditional sections.
+    pub fn lint(&self) -> LintReport {
+        let mut diagnostics = Vec::new();
+        for model in &self.models {
+            lint_model(model, &mut diagnostics);
+        }
+        LintReport { diagnostics }
+    }
+}
+
+fn lint_model(model: &ModelDef, diagnostics: &mut Vec<Diagnostic>) {
+    let mut push = |severity, rule, template: Option<&TemplateDef>, message: String| {
+        diagnostics.push(Diagnostic {
+            severity,
+            rule,
+            model: model.name.clone(),
+            template: template.map(|t| t.name.clone()),
+            message,
+        });
+    };
+
+    if model.templates.is_empty() && !model.is_cloze() {
+        push(
+            Severity::Error,
+            Rule::NoTemplates,
+            None,
+            "model has no templates".to_string(),
+        );
+    }
+
+    let mut names = HashSet::new();
+    for template in &model.templates {
+        if !names.insert(template.name.as_str()) {
+            push(
+                Severity::Error,
+                Rule::DuplicateTemplate,
+                Some(template),
+                format!("template name '{}' is used more than once", template.name),
+            );
+        }
+
+        let front = template::tags(&template.front);
+        let back = template::tags(&template.back);
+
+        if template.front.trim().is_empty() {
+            push(
+                Severity::Error,
+                Rule::EmptyFront,
+                Some(template),
+                "front template is empty".to_string(),
+            );
+        } else if !front
+            .iter()
+            .any(|tag| tag.kind == TagKind::Replacement && model.fields.contains(&tag.field))
+        {
+            push(
+                Severity::Warning,
+                Rule::NoFieldOnFront,
+                Some(template),
+                "front template does not show any field".to_string(),
+            );
+        }
+
+        // Cloze backs conventionally repeat the cloze field instead, and
+        // occlusion backs show a different mask over the image
+        if !model.is_cloze()
+            && !model.is_image_occlusion()
+            && !back.iter().any(|tag| tag.field == "FrontSide")
+        {
+            push(
+                Severity::Warning,
+                Rule::MissingFrontSide,
+                Some(template),
+                "back template does not include {{FrontSide}}".to_string(),
+            );
+        }
+
+        let mut reported = HashSet::new();
+        for (side, source, side_tags) in [
+            ("front", &template.front, &front),
+            ("back", &template.back, &back),
+        ] {
+            for tag in side_tags.iter() {
+                if tag.kind == TagKind::Close || tag.field.is_empty() {
+                    continue;
+                }
+                if !model.fields.contains(&tag.field)
+                    && !SPECIAL_FIELDS.contains(&tag.field.as_str())
+                    && reported.insert(tag.field.clone())
+                {
+                    push(
+                        Severity::Error,
+                        Rule::UnknownField,
+                        Some(template),
+                        format!("{} template references unknown field '{}'", side, tag.field),
+                    );
+                }
+            }
+
+            if let Some(problem) = unbalanced(source) {
+                push(
+                    Severity::Error,
+                    Rule::UnbalancedSection,
+                    Some(template),
+                    format!("{} template: {}", side, problem),
+                );
+            }
+        }
+
+        let is_cloze = |tag: &template::Tag| tag.has_filter(&Filter::Cloze);
+        let uses_cloze = front.iter().chain(&back).any(is_cloze);
+        if uses_cloze && !model.is_cloze() {
+            push(
+                Severity::Error,
+                Rule::ClozeOnStandardModel,
+                Some(template),
+                "cloze filter used on a non-cloze model; set model_type = \"cloze\"".to_string(),
+            );
+        }
+        if model.is_cloze() && !front.iter().any(is_cloze) {
+            push(
+                Severity::Error,
+                Rule::MissingCloze,
+                Some(template),
+                "cloze model's front template has no {{cloze:Field}}".to_string(),
+            );
+        }
+    }
+}
+
+/// Describe the template's structural error, if any.
+fn unbalanced(source: &str) -> Option<String> {
+    let error = template::parse(source).err()?;
+    Some(match error.kind {
+        TemplateErrorKind::UnclosedTag(rest) => format!("tag {} is never closed", rest),
+        TemplateErrorKind::MismatchedClose { found, expected } => {
+            format!(
+                "found {{{{/{}}}}} but expected {{{{/{}}}}}",
+                found, expected
+            )
+        }
+        TemplateErrorKind::UnopenedClose(found) => {
+            format!("found {{{{/{}}}}} without an opening tag", found)
+        }
+        TemplateErrorKind::UnclosedSection { field, negated } => format!(
+            "section {{{{{}{}}}}} is never closed",
+            if negated { '^' } else { '#' },
+            field
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(fields: &[&str], templates: &[(&str, &str, &str)]) -> ModelDef {
+        ModelDef {
+            name: "Test".to_string(),
+            fields: fields.iter().map(|f| f.to_string()).collect(),
+            templates: templates
+                .iter()
+                .map(|(name, front, back)| TemplateDef {
+                    name: name.to_string(),
+                    front: front.to_string(),
+                    back: back.to_string(),
+                })
+                .collect(),
+            css: None,
+            sort_field: None,
+            id: None,
+            markdown_fields: vec![],
+            model_type: None,
+            version: None,
+            include: vec![],
+        }
+    }
+
+    fn rules(model: &ModelDef) -> Vec<Rule> {
+        let mut diagnostics = Vec::new();
+        lint_model(model, &mut diagnostics);
+        diagnostics.into_iter().map(|d| d.rule).collect()
+    }
+
+    #[test]
+    fn test_clean_model() {
+        let m = model(
+            &["Front", "Back"],
+            &[(
+                "Card 1",
+                "{{Front}}{{#Tags}} ({{Tags}}){{/Tags}}",
+                "{{FrontSide}}<hr id=answer>{{text:Back}}",
+            )],
+        );
+        assert!(rules(&m).is_empty());
+
+        assert!(rules(&ModelDef::cloze("Cloze", vec!["Text", "Extra"])).is_empty());
+    }
+
+    #[test]
+    fn test_template_problems() {
+        let m = model(
+            &["Front", "Back"],
+            &[
+                ("Card 1", "{{Front}}", "{{FrontSide}}{{Bakc}}{{cloze:Back}}"),
+                ("Card 1", "  ", "{{Back}}"),
+                ("Card 3", "{{#Front}}{{Front}}", "{{FrontSide}}"),
+            ],
+        );
+
+        assert_eq!(
+            rules(&m),
+            vec![
+                Rule::UnknownField,
+                Rule::ClozeOnStandardModel,
+                Rule::DuplicateTemplate,
+                Rule::EmptyFront,
+                Rule::MissingFrontSide,
+                Rule::UnbalancedSection,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cloze_model_without_cloze() {
+        let mut m = model(&["Text"], &[("Cloze", "{{Text}}", "{{FrontSide}}")]);
+        m.model_type = Some("cloze".to_string());
+
+        assert_eq!(rules(&m), vec![Rule::MissingCloze]);
+    }
+
+    #[test]
+    fn test_report_severities() {
+        let report = LintReport {
+            diagnostics: vec![Diagnostic {
+                severity: Severity::Warning,
+                rule: Rule::MissingFrontSide,
+                model: "Basic".to_string(),
+                template: Some("Card 1".to_string()),
+                message: "back template does not include {{FrontSide}}".to_string(),
+            }],
+        };
+
+        assert!(!report.has_errors());
+        assert_eq!(report.warnings().count(), 1);
+        assert_eq!(
+            report.diagnostics[0].to_string(),
+            "warning: model 'Basic', template 'Card 1': back template does not include {{FrontSide}}"
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! Note type versions recorded in Anki.
+//!
+//! AnkiConnect has nowhere to keep custom model metadata, so the applied
+//! [`version`](crate::ModelDef::version) of a model is stored as a comment on
+//! the first line of its CSS:
+//!
+//! ```css
+//! /* ankit-model-version: 3 9f2c4b1e0a7d5c38 */
+//! ```
+//!
+//! The second value is a checksum of the model's templates and CSS as they
+//! were applied, so sync can tell whether they were edited in Anki since.
+
+use crate::schema::ModelDef;
+
+/// Start of the CSS comment holding a model's version.
+const MARKER_PREFIX: &str = "/* ankit-model-version: ";
+
+/// End of the CSS comment holding a model's version.
+const MARKER_SUFFIX: &str = " */";
+
+/// The version recorded in a model's CSS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct AppliedVersion {
+    /// The model version that was applied.
+    pub version: u32,
+    /// Checksum of the templates and CSS that were applied.
+    pub checksum: u64,
+}
+
+/// Split a model's CSS into its recorded version, if any, and the rest.
+pub(crate) fn split_css(css: &str) -> (Option<AppliedVersion>, &str) {
+    let (first, rest) = css.split_once('\n').unwrap_or((css, ""));
+    let applied = first
+        .trim_end()
+        .strip_prefix(MARKER_PREFIX)
+        .and_then(|marker| marker.strip_suffix(MARKER_SUFFIX))
+        .and_then(|marker| {
+            let (version, checksum) = marker.split_once(' ')?;
+            Some(AppliedVersion {
+                version: version.parse().ok()?,
+                checksum: u64::from_str_radix(checksum, 16).ok()?,
+            })
+        });
+    match applied {
+        Some(applied) => (Some(applied), rest),
+        None => (None, css),
+    }
+}
+
+/// The CSS to store in Anki for `version` of a model with these templates
+/// and CSS.
+pub(crate) fn stamp<'a>(
+    version: u32,
+    templates: impl IntoIterator<Item = (&'a str, &'a str, &'a str)>,
+    css: &str,
+) -> String {
+    let checksum = checksum(templates, css);
+    format!(
+        "{}{} {:016x}{}\n{}",
+        MARKER_PREFIX, version, checksum, MARKER_SUFFIX, css
+    )
+}
+
+/// The CSS to store in Anki for a TOML model, stamped with its version if
+/// it has one.
+pub(crate) fn stamp_model(model: &ModelDef, css: String) -> String {
+    let Some(version) = model.version else {
+        return css;
+    };
+    let templates = model
+        .templates
+        .iter()
+        .map(|t| (t.name.as_str(), t.front.as_str(), t.back.as_str()));
+    stamp(version, templates, &css)
+}
+
+/// Checksum of a model's `(name, front, back)` templates and CSS, without
+/// its version marker.
+///
+/// Templates are hashed in name order, and surrounding whitespace in the CSS
+/// is ignored, so the checksum doesn't depend on how Anki returns them.
+pub(crate) fn checksum<'a>(
+    templates: impl IntoIterator<Item = (&'a str, &'a str, &'a str)>,
+    css: &str,
+) -> u64 {
+    let mut templates: Vec<_> = templates.into_iter().collect();
+    templates.sort_unstable();
+
+    // FNV-1a, which is stable across Rust versions unlike DefaultHasher
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut write = |bytes: &[u8]| {
+        for byte in bytes.iter().chain([&0xff]) {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    };
+    for (name, front, back) in templates {
+        write(name.as_bytes());
+        write(front.as_bytes());
+        write(back.as_bytes());
+    }
+    write(css.trim().as_bytes());
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATES: [(&str, &str, &str); 2] = [
+        ("Card 1", "{{Front}}", "{{Back}}"),
+        ("Card 2", "{{Back}}", "{{Front}}"),
+    ];
+
+    #[test]
+    fn test_stamp_roundtrip() {
+        let css = stamp(3, TEMPLATES, ".card { color: red; }");
+        let (applied, rest) = split_css(&css);
+        assert_eq!(rest, ".card { color: red; }");
+        assert_eq!(
+            applied,
+            Some(AppliedVersion {
+                version: 3,
+                checksum: checksum(TEMPLATES, rest),
+            })
+        );
+    }
+
+    #[test]
+    fn test_split_css_without_marker() {
+        let css = "/* theme */\n.card { color: red; }";
+        assert_eq!(split_css(css), (None, css));
+        assert_eq!(split_css(""), (None, ""));
+    }
+
+    #[test]
+    fn test_checksum() {
+        let css = ".card {}";
+        let reversed = [TEMPLATES[1], TEMPLATES[0]];
+        assert_eq!(checksum(TEMPLATES, css), checksum(reversed, css));
+        assert_eq!(
+            checksum(TEMPLATES, css),
+            checksum(TEMPLATES, "\n.card {}\n")
+        );
+
+        let edited = [TEMPLATES[0], ("Card 2", "{{Back}}!", "{{Front}}")];
+        assert_ne!(checksum(TEMPLATES, css), checksum(edited, css));
+        assert_ne!(
+            checksum(TEMPLATES, css),
+            checksum(TEMPLATES, ".card { x: y; }")
+        );
+        // Field boundaries count
+        assert_ne!(
+            checksum([("a", "bc", "")], css),
+            checksum([("ab", "c", "")], css)
+        );
+    }
+}
@@ -0,0 +1,397 @@
+//! Image occlusion notes: hide regions of an image and ask for what's beneath.
+//!
+//! An `[[occlusions]]` entry names an image and a set of rectangular masks.
+//! It expands into one note per mask. Each note gets three SVG overlays,
+//! generated as media files and drawn over the image:
+//!
+//! - **Question mask** - the tested region is highlighted and hidden
+//! - **Answer mask** - the tested region is revealed
+//! - **Original mask** - every mask, for reference
+//!
+//! With the default `hide-all` mode, the question hides every mask, and the
+//! answer only reveals the tested region. With `hide-one`, only the tested
+//! region is ever hidden.
+//!
+//! Notes use the [`ModelDef::image_occlusion`](crate::ModelDef::image_occlusion) note type, which is added to
+//! the definition if no model with that name is defined. The image itself
+//! must be listed under `[[media]]`.
+//!
+//! # Example TOML
+//!
+//! ```toml
+//! [[media]]
+//! name = "heart.png"
+//! path = "images/heart.png"
+//!
+//! [[occlusions]]
+//! deck = "Anatomy"
+//! image = "heart.png"
+//! width = 800
+//! height = 600
+//! header = "Chambers of the heart"
+//! tags = ["cardio"]
+//!
+//! [[occlusions.masks]]
+//! left = 120
+//! top = 80
+//! width = 140
+//! height = 60
+//! label = "Right atrium"
+//!
+//! [[occlusions.masks]]
+//! left = 480
+//! top = 90
+//! width = 140
+//! height = 60
+//! label = "Left atrium"
+//! ```
+//!
+//! This expands to two notes, one asking for each atrium.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::error::{Error, Result};
+use crate::schema::{MediaDef, NoteDef};
+
+/// Name of the note type used when an occlusion doesn't specify one.
+pub const DEFAULT_MODEL: &str = "Image Occlusion (SVG)";
+
+/// Fields of the image occlusion note type, in order.
+pub const FIELDS: [&str; 7] = [
+    "Header",
+    "Image",
+    "Question Mask",
+    "Answer Mask",
+    "Original Mask",
+    "Label",
+    "Footer",
+];
+
+/// Fill color of the mask being tested.
+const TARGET_FILL: &str = "#ff7e7e";
+/// Fill color of the other masks.
+const MASK_FILL: &str = "#ffeba2";
+/// Outline color of every mask.
+const MASK_STROKE: &str = "#2d2d2d";
+
+/// Which masks are hidden on each card.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OcclusionMode {
+    /// Hide every mask; reveal only the tested one on the answer.
+    #[default]
+    HideAll,
+    /// Hide only the tested mask.
+    HideOne,
+}
+
+/// A rectangle to hide, in image pixels.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaskDef {
+    /// Distance from the image's left edge.
+    pub left: u32,
+    /// Distance from the image's top edge.
+    pub top: u32,
+    /// Width of the rectangle.
+    pub width: u32,
+    /// Height of the rectangle.
+    pub height: u32,
+    /// What the mask hides, shown on the answer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl MaskDef {
+    /// Create a mask from its position and size.
+    pub fn new(left: u32, top: u32, width: u32, height: u32) -> Self {
+        Self {
+            left,
+            top,
+            width,
+            height,
+            label: None,
+        }
+    }
+
+    /// Set the label shown on the answer.
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+}
+
+/// Image occlusion definition that expands into one note per mask.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OcclusionDef {
+    /// Deck name to add notes to.
+    pub deck: String,
+
+    /// Model name for the notes.
+    #[serde(default = "default_model")]
+    pub model: String,
+
+    /// Media filename of the image (must be listed under `[[media]]`).
+    pub image: String,
+
+    /// Image width in pixels.
+    pub width: u32,
+
+    /// Image height in pixels.
+    pub height: u32,
+
+    /// Which masks are hidden on each card.
+    #[serde(default)]
+    pub mode: OcclusionMode,
+
+    /// Text shown above the image.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<String>,
+
+    /// Text shown below the answer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub footer: Option<String>,
+
+    /// Tags for every generated note.
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Regions to hide. Each becomes a note.
+    pub masks: Vec<MaskDef>,
+}
+
+fn default_model() -> String {
+    DEFAULT_MODEL.to_string()
+}
+
+impl OcclusionDef {
+    /// Create an occlusion for an image of the given size, using the default model.
+    pub fn new(deck: impl Into<String>, image: impl Into<String>, width: u32, height: u32) -> Self {
+        Self {
+            deck: deck.into(),
+            model: default_model(),
+            image: image.into(),
+            width,
+            height,
+            mode: OcclusionMode::default(),
+            header: None,
+            footer: None,
+            tags: Vec::new(),
+            masks: Vec::new(),
+        }
+    }
+
+    /// Add a mask.
+    pub fn mask(mut self, mask: MaskDef) -> Self {
+        self.masks.push(mask);
+        self
+    }
+
+    /// Expand this occlusion into notes and the SVG media they reference.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidDefinition`] if there are no masks, the image
+    /// has no size, or a mask is empty or extends past the image.
+    pub fn expand(&self) -> Result<(Vec<NoteDef>, Vec<MediaDef>)> {
+        self.check()?;
+
+        let prefix = self.media_prefix();
+        let original_name = format!("{}-o.svg", prefix);
+        let mut media = vec![MediaDef::inline(
+            original_name.clone(),
+            self.svg(self.masks.iter().map(|mask| (mask, MASK_FILL))),
+        )];
+
+        let mut notes = Vec::with_capacity(self.masks.len());
+        for (index, target) in self.masks.iter().enumerate() {
+            let question_name = format!("{}-{}-q.svg", prefix, index + 1);
+            let answer_name = format!("{}-{}-a.svg", prefix, index + 1);
+
+            let others = self
+                .masks
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != index && self.mode == OcclusionMode::HideAll)
+                .map(|(_, mask)| (mask, MASK_FILL));
+            let question = self.svg(others.clone().chain([(target, TARGET_FILL)]));
+            let answer = self.svg(others.chain([(target, "none")]));
+            media.push(MediaDef::inline(question_name.clone(), question));
+            media.push(MediaDef::inline(answer_name.clone(), answer));
+
+            let fields = HashMap::from([
+                (
+                    "Header".to_string(),
+                    self.header.clone().unwrap_or_default(),
+                ),
+                ("Image".to_string(), img_tag(&self.image)),
+                ("Question Mask".to_string(), img_tag(&question_name)),
+                ("Answer Mask".to_string(), img_tag(&answer_name)),
+                ("Original Mask".to_string(), img_tag(&original_name)),
+                (
+                    "Label".to_string(),
+                    target.label.clone().unwrap_or_default(),
+                ),
+                (
+                    "Footer".to_string(),
+                    self.footer.clone().unwrap_or_default(),
+                ),
+            ]);
+            notes.push(NoteDef {
+                deck: self.deck.clone(),
+                model: self.model.clone(),
+                fields,
+                tags: self.tags.clone(),
+                guid: None,
+                note_id: None,
+                cards: None,
+                suspend: Vec::new(),
+            });
+        }
+
+        Ok((notes, media))
+    }
+
+    /// Validate the image size and masks.
+    fn check(&self) -> Result<()> {
+        let invalid = |reason: String| {
+            Err(Error::InvalidDefinition(format!(
+                "occlusion for image '{}': {}",
+                self.image, reason
+            )))
+        };
+
+        if self.width == 0 || self.height == 0 {
+            return invalid("width and height must be greater than zero".to_string());
+        }
+        if self.masks.is_empty() {
+            return invalid("no masks".to_string());
+        }
+        for (index, mask) in self.masks.iter().enumerate() {
+            if mask.width == 0 || mask.height == 0 {
+                return invalid(format!("mask {} is empty", index + 1));
+            }
+            if mask.left.saturating_add(mask.width) > self.width
+                || mask.top.saturating_add(mask.height) > self.height
+            {
+                return invalid(format!("mask {} extends past the image", index + 1));
+            }
+        }
+        Ok(())
+    }
+
+    /// Filename prefix for generated media: the image's stem plus a hash of
+    /// the masks, so two occlusions of one image don't collide.
+    fn media_prefix(&self) -> String {
+        let stem = self
+            .image
+            .rsplit_once('.')
+            .map_or(self.image.as_str(), |(stem, _)| stem);
+        let stem: String = stem
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+
+        let mut key = format!(
+            "{}:{}x{}:{:?}",
+            self.image, self.width, self.height, self.mode
+        );
+        for mask in &self.masks {
+            key.push_str(&format!(
+                ":{},{},{},{}",
+                mask.left, mask.top, mask.width, mask.height
+            ));
+        }
+        // FNV-1a, so the same occlusion always produces the same filenames
+        let hash = key.bytes().fold(0x811c9dc5u32, |hash, byte| {
+            (hash ^ byte as u32).wrapping_mul(0x01000193)
+        });
+        format!("{}-io-{:08x}", stem, hash)
+    }
+
+    /// An SVG the size of the image with the given rectangles drawn on it.
+    fn svg<'a>(&self, rects: impl Iterator<Item = (&'a MaskDef, &'a str)>) -> String {
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = self.width,
+            h = self.height
+        );
+        for (mask, fill) in rects {
+            svg.push_str(&format!(
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" stroke="{}" stroke-width="2"/>"#,
+                mask.left, mask.top, mask.width, mask.height, fill, MASK_STROKE
+            ));
+        }
+        svg.push_str("</svg>");
+        svg
+    }
+}
+
+/// An `<img>` tag referencing a media file.
+fn img_tag(filename: &str) -> String {
+    let escaped = filename
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;");
+    format!(r#"<img src="{}">"#, escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn occlusion() -> OcclusionDef {
+        OcclusionDef::new("Anatomy", "heart diagram.png", 800, 600)
+            .mask(MaskDef::new(10, 20, 100, 50).label("Aorta"))
+            .mask(MaskDef::new(300, 200, 80, 40))
+    }
+
+    #[test]
+    fn test_expand_one_note_per_mask() {
+        let (notes, media) = occlusion().expand().unwrap();
+
+        assert_eq!(notes.len(), 2);
+        assert_eq!(media.len(), 5);
+        assert_eq!(notes[0].fields["Label"], "Aorta");
+        assert_eq!(notes[0].fields["Image"], r#"<img src="heart diagram.png">"#);
+        assert!(media[0].name.starts_with("heart_diagram-io-"));
+        assert!(
+            notes[1].fields["Question Mask"].contains(&media[3].name),
+            "second note references its own question mask"
+        );
+    }
+
+    #[test]
+    fn test_hide_all_and_hide_one_masks() {
+        let (_, media) = occlusion().expand().unwrap();
+        let question = media[1].data.as_deref().unwrap();
+        let answer = media[2].data.as_deref().unwrap();
+        assert_eq!(question.matches("<rect").count(), 2);
+        assert!(question.contains(TARGET_FILL));
+        assert!(!answer.contains(TARGET_FILL));
+        assert!(answer.contains(r#"fill="none""#));
+
+        let mut def = occlusion();
+        def.mode = OcclusionMode::HideOne;
+        let (_, media) = def.expand().unwrap();
+        assert_eq!(
+            media[1].data.as_deref().unwrap().matches("<rect").count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_invalid_masks() {
+        let empty = OcclusionDef::new("Deck", "a.png", 100, 100);
+        assert!(matches!(empty.expand(), Err(Error::InvalidDefinition(_))));
+
+        let outside = empty.clone().mask(MaskDef::new(50, 50, 60, 10));
+        assert!(matches!(outside.expand(), Err(Error::InvalidDefinition(_))));
+    }
+}
@@ -0,0 +1,506 @@
+//! .apkg file reading.
+//!
+//! Opens existing Anki package files and reconstructs a [`DeckDefinition`]
+//! from the collection inside, so packages can be converted to TOML or
+//! compared without a running Anki instance.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::path::Path;
+
+use rusqlite::Connection;
+use serde::Deserialize;
+use tempfile::TempDir;
+use zip::ZipArchive;
+
+use crate::error::{Error, Result};
+use crate::model_version;
+use crate::schema::{
+    DeckDef, DeckDefinition, DeckOptions, MediaDef, ModelDef, NoteDef, PackageInfo, TemplateDef,
+};
+use crate::sql::FIELD_SEPARATOR;
+
+/// Reader for existing .apkg files.
+///
+/// # Example
+///
+/// ```no_run
+/// use ankit_builder::ApkgReader;
+///
+/// # fn main() -> ankit_builder::Result<()> {
+/// let reader = ApkgReader::open("shared_deck.apkg")?;
+/// let definition = reader.read()?;
+/// reader.extract_media("media")?;
+/// definition.write_toml("shared_deck.toml")?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ApkgReader {
+    name: String,
+    collection: Vec<u8>,
+    media: Vec<(String, Vec<u8>)>,
+}
+
+impl ApkgReader {
+    /// Open an .apkg file and load its contents into memory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file is not a ZIP archive or contains no
+    /// readable collection. Packages exported in Anki's newest format
+    /// (`collection.anki21b`, zstd-compressed) are not supported; export
+    /// with "Support older Anki versions" enabled instead.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)?;
+        let mut archive = ZipArchive::new(file)?;
+
+        // Packages with scheduler v2 data carry the real collection in
+        // collection.anki21 next to a placeholder collection.anki2
+        let collection_name = ["collection.anki21", "collection.anki2"]
+            .into_iter()
+            .find(|name| archive.index_for_name(name).is_some())
+            .ok_or_else(|| {
+                Error::InvalidPackage(format!(
+                    "{}: no collection.anki21 or collection.anki2 found",
+                    path.display()
+                ))
+            })?;
+        let collection = read_entry(&mut archive, collection_name)?;
+
+        let manifest: HashMap<String, String> = match archive.index_for_name("media") {
+            Some(_) => {
+                let bytes = read_entry(&mut archive, "media")?;
+                serde_json::from_slice(&bytes)
+                    .map_err(|e| Error::InvalidPackage(format!("invalid media manifest: {}", e)))?
+            }
+            None => HashMap::new(),
+        };
+
+        // Keep media in manifest order (entries are numbered 0, 1, 2, ...)
+        let manifest: BTreeMap<u64, String> = manifest
+            .into_iter()
+            .filter_map(|(index, name)| index.parse().ok().map(|i| (i, name)))
+            .collect();
+        let mut media = Vec::with_capacity(manifest.len());
+        for (index, name) in manifest {
+            let bytes = read_entry(&mut archive, &index.to_string())?;
+            media.push((name, bytes));
+        }
+
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        Ok(Self {
+            name,
+            collection,
+            media,
+        })
+    }
+
+    /// Reconstruct the deck definition stored in the package.
+    ///
+    /// The package name is taken from the file name. Media entries refer to
+    /// files by their name relative to the directory passed to
+    /// [`extract_media()`](Self::extract_media).
+    pub fn read(&self) -> Result<DeckDefinition> {
+        let temp_dir = TempDir::new()?;
+        let db_path = temp_dir.path().join("collection.anki2");
+        std::fs::write(&db_path, &self.collection)?;
+        let conn = Connection::open(&db_path)?;
+
+        let (models_json, decks_json, dconf_json): (String, String, String) =
+            conn.query_row("SELECT models, decks, dconf FROM col", [], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })?;
+        let models: HashMap<String, RawModel> = serde_json::from_str(&models_json)
+            .map_err(|e| Error::InvalidPackage(format!("invalid models JSON: {}", e)))?;
+        let decks: HashMap<String, RawDeck> = serde_json::from_str(&decks_json)
+            .map_err(|e| Error::InvalidPackage(format!("invalid decks JSON: {}", e)))?;
+        // Options groups that don't parse are left out rather than failing
+        let dconf: HashMap<i64, RawDeckConfig> =
+            serde_json::from_str::<HashMap<String, serde_json::Value>>(&dconf_json)
+                .map_err(|e| Error::InvalidPackage(format!("invalid dconf JSON: {}", e)))?
+                .into_values()
+                .filter_map(|conf| serde_json::from_value::<RawDeckConfig>(conf).ok())
+                .map(|conf| (conf.id, conf))
+                .collect();
+
+        let models: HashMap<i64, ModelDef> = models
+            .into_values()
+            .map(|model| (model.id, model.into_model_def()))
+            .collect();
+        let deck_names: HashMap<i64, &str> = decks
+            .values()
+            .map(|deck| (deck.id, deck.name.as_str()))
+            .collect();
+
+        // A note's deck is the deck of its first card
+        let mut note_decks: HashMap<i64, i64> = HashMap::new();
+        let mut stmt = conn.prepare("SELECT nid, did FROM cards ORDER BY nid, ord")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)))?;
+        for row in rows {
+            let (note_id, deck_id) = row?;
+            note_decks.entry(note_id).or_insert(deck_id);
+        }
+
+        let mut notes = Vec::new();
+        let mut stmt = conn.prepare("SELECT id, guid, mid, tags, flds FROM notes ORDER BY id")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })?;
+        for row in rows {
+            let (note_id, guid, model_id, tags, flds) = row?;
+            let model = models.get(&model_id).ok_or_else(|| {
+                Error::InvalidPackage(format!(
+                    "note {} references unknown model {}",
+                    note_id, model_id
+                ))
+            })?;
+            let deck = note_decks
+                .get(&note_id)
+                .and_then(|id| deck_names.get(id))
+                .copied()
+                .unwrap_or("Default");
+
+            notes.push(NoteDef {
+                deck: deck.to_string(),
+                model: model.name.clone(),
+                fields: model
+                    .fields
+                    .iter()
+                    .cloned()
+                    .zip(flds.split(FIELD_SEPARATOR).map(str::to_string))
+                    .collect(),
+                tags: tags.split_whitespace().map(str::to_string).collect(),
+                guid: Some(guid),
+                note_id: None,
+                cards: None,
+                suspend: Vec::new(),
+            });
+        }
+
+        // Keep every real deck, plus Default only if notes live there
+        let mut decks: Vec<DeckDef> = decks
+            .into_values()
+            .filter(|deck| deck.dyn_ == 0)
+            .filter(|deck| deck.id != 1 || notes.iter().any(|n| n.deck == deck.name))
+            .map(|deck| DeckDef {
+                // Only groups other than the default carry settings
+                options: dconf
+                    .get(&deck.conf)
+                    .filter(|conf| conf.id != 1)
+                    .map(|conf| deck_options(conf, &deck.name, dconf.get(&1))),
+                name: deck.name,
+                description: Some(deck.desc).filter(|d| !d.is_empty()),
+                id: Some(deck.id),
+            })
+            .collect();
+        decks.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut models: Vec<ModelDef> = models.into_values().collect();
+        models.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let media = self
+            .media
+            .iter()
+            .map(|(name, _)| MediaDef::file(name, name))
+            .collect();
+
+        Ok(DeckDefinition {
+            include: Vec::new(),
+            package: PackageInfo::new(self.name.as_str()),
+            models,
+            decks,
+            notes,
+            media,
+            generators: Vec::new(),
+            occlusions: Vec::new(),
+            base_dir: None,
+        })
+    }
+
+    /// Media files in the package, as `(filename, contents)` pairs.
+    pub fn media(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        self.media
+            .iter()
+            .map(|(name, bytes)| (name.as_str(), bytes.as_slice()))
+    }
+
+    /// Write all media files into a directory, creating it if needed.
+    pub fn extract_media(&self, dir: impl AsRef<Path>) -> Result<()> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        for (name, bytes) in &self.media {
+            // Media names are flat filenames; refuse anything that would escape `dir`
+            let file_name = Path::new(name)
+                .file_name()
+                .ok_or_else(|| Error::InvalidPackage(format!("invalid media name: {}", name)))?;
+            std::fs::write(dir.join(file_name), bytes)?;
+        }
+        Ok(())
+    }
+}
+
+/// Read a whole ZIP entry into memory.
+fn read_entry(archive: &mut ZipArchive<std::fs::File>, name: &str) -> Result<Vec<u8>> {
+    let mut entry = archive.by_name(name)?;
+    let mut bytes = Vec::new();
+    entry.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Model as stored in the `col.models` JSON.
+#[derive(Deserialize)]
+struct RawModel {
+    id: i64,
+    name: String,
+    #[serde(rename = "type", default)]
+    kind: i64,
+    #[serde(default)]
+    sortf: usize,
+    #[serde(default)]
+    css: String,
+    flds: Vec<RawField>,
+    tmpls: Vec<RawTemplate>,
+}
+
+#[derive(Deserialize)]
+struct RawField {
+    name: String,
+    ord: i64,
+}
+
+#[derive(Deserialize)]
+struct RawTemplate {
+    name: String,
+    ord: i64,
+    qfmt: String,
+    afmt: String,
+}
+
+impl RawModel {
+    fn into_model_def(mut self) -> ModelDef {
+        self.flds.sort_by_key(|f| f.ord);
+        self.tmpls.sort_by_key(|t| t.ord);
+        let fields: Vec<String> = self.flds.into_iter().map(|f| f.name).collect();
+        let (applied, css) = model_version::split_css(&self.css);
+
+        ModelDef {
+            name: self.name,
+            sort_field: fields.get(self.sortf).filter(|_| self.sortf != 0).cloned(),
+            fields,
+            templates: self
+                .tmpls
+                .into_iter()
+                .map(|t| TemplateDef {
+                    name: t.name,
+                    front: t.qfmt,
+                    back: t.afmt,
+                })
+                .collect(),
+            css: Some(css.to_string()).filter(|css| !css.is_empty()),
+            id: Some(self.id),
+            markdown_fields: vec![],
+            model_type: (self.kind == 1).then(|| "cloze".to_string()),
+            version: applied.map(|applied| applied.version),
+            include: vec![],
+        }
+    }
+}
+
+/// Deck as stored in the `col.decks` JSON.
+#[derive(Deserialize)]
+struct RawDeck {
+    id: i64,
+    name: String,
+    #[serde(default)]
+    desc: String,
+    #[serde(rename = "dyn", default)]
+    dyn_: i64,
+    #[serde(default = "default_conf")]
+    conf: i64,
+}
+
+fn default_conf() -> i64 {
+    1
+}
+
+/// Options group as stored in the `col.dconf` JSON.
+#[derive(Deserialize)]
+struct RawDeckConfig {
+    id: i64,
+    name: String,
+    #[serde(default)]
+    new: RawNewConfig,
+    #[serde(default)]
+    rev: RawReviewConfig,
+    #[serde(default)]
+    lapse: RawLapseConfig,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct RawNewConfig {
+    per_day: i64,
+    delays: Vec<f64>,
+    ints: Vec<i64>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct RawReviewConfig {
+    per_day: i64,
+    max_ivl: i64,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct RawLapseConfig {
+    delays: Vec<f64>,
+    min_int: i64,
+    leech_fails: i64,
+    leech_action: i64,
+}
+
+impl RawDeckConfig {
+    /// Every setting of the group, keeping its name only if it differs from
+    /// the deck name.
+    fn to_options(&self, deck_name: &str) -> DeckOptions {
+        DeckOptions {
+            group: Some(self.name.clone()).filter(|name| name != deck_name),
+            new_per_day: Some(self.new.per_day),
+            reviews_per_day: Some(self.rev.per_day),
+            learning_steps: Some(self.new.delays.clone()),
+            graduating_interval: self.new.ints.first().copied(),
+            easy_interval: self.new.ints.get(1).copied(),
+            maximum_interval: Some(self.rev.max_ivl),
+            relearning_steps: Some(self.lapse.delays.clone()),
+            lapse_minimum_interval: Some(self.lapse.min_int),
+            leech_threshold: Some(self.lapse.leech_fails),
+            leech_action: DeckOptions::leech_action_name(self.lapse.leech_action),
+        }
+    }
+}
+
+/// The settings of an options group that differ from the default group.
+fn deck_options(
+    conf: &RawDeckConfig,
+    deck_name: &str,
+    default: Option<&RawDeckConfig>,
+) -> DeckOptions {
+    let options = conf.to_options(deck_name);
+    match default {
+        Some(default) => options.without_defaults(&default.to_options(deck_name)),
+        None => options,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ApkgBuilder;
+    use tempfile::tempdir;
+
+    const TOML: &str = r#"
+[package]
+name = "Test"
+
+[[models]]
+name = "Basic"
+fields = ["Front", "Back"]
+sort_field = "Back"
+
+[[models.templates]]
+name = "Card 1"
+front = "{{Front}}"
+back = "{{Back}}"
+
+[[decks]]
+name = "Languages::Spanish"
+description = "Spanish words"
+
+[decks.options]
+group = "Languages"
+new_per_day = 5
+leech_action = "tag"
+
+[[notes]]
+deck = "Languages::Spanish"
+model = "Basic"
+tags = ["food", "nouns"]
+guid = "abc123"
+
+[notes.fields]
+Front = "el pan"
+Back = "bread"
+
+[[media]]
+name = "pan.mp3"
+path = "pan.mp3"
+"#;
+
+    #[test]
+    fn test_roundtrip() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("pan.mp3"), b"audio").unwrap();
+        let apkg_path = dir.path().join("spanish.apkg");
+        ApkgBuilder::new(DeckDefinition::parse(TOML).unwrap())
+            .media_base_path(dir.path())
+            .write_to_file(&apkg_path)
+            .unwrap();
+
+        let reader = ApkgReader::open(&apkg_path).unwrap();
+        let def = reader.read().unwrap();
+
+        assert_eq!(def.package.name, "spanish");
+        assert_eq!(def.models.len(), 1);
+        assert_eq!(def.models[0].fields, vec!["Front", "Back"]);
+        assert_eq!(def.models[0].sort_field.as_deref(), Some("Back"));
+        assert_eq!(def.decks.len(), 1);
+        assert_eq!(def.decks[0].name, "Languages::Spanish");
+        assert_eq!(def.decks[0].description.as_deref(), Some("Spanish words"));
+        // Only the settings that differ from the default group come back
+        assert_eq!(
+            def.decks[0].options,
+            Some(DeckOptions {
+                group: Some("Languages".to_string()),
+                new_per_day: Some(5),
+                leech_action: Some("tag".to_string()),
+                ..Default::default()
+            })
+        );
+
+        let note = &def.notes[0];
+        assert_eq!(note.deck, "Languages::Spanish");
+        assert_eq!(note.fields["Front"], "el pan");
+        assert_eq!(note.fields["Back"], "bread");
+        assert_eq!(note.tags, vec!["food", "nouns"]);
+        assert_eq!(note.guid.as_deref(), Some("abc123"));
+
+        assert_eq!(def.media[0].name, "pan.mp3");
+        let media_dir = dir.path().join("out");
+        reader.extract_media(&media_dir).unwrap();
+        assert_eq!(std::fs::read(media_dir.join("pan.mp3")).unwrap(), b"audio");
+
+        // The reconstructed definition is valid
+        def.validate().unwrap();
+    }
+
+    #[test]
+    fn test_open_not_a_package() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("bad.apkg");
+        std::fs::write(&path, b"not a zip").unwrap();
+
+        assert!(ApkgReader::open(&path).is_err());
+    }
+}
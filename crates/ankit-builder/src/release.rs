@@ -0,0 +1,574 @@
+//! Versioned releases of deck definitions.
+//!
+//! A release bumps `package.version`, writes a versioned `.apkg`, and keeps a
+//! TOML snapshot of what was released. The next release is compared against
+//! the newest snapshot to produce a changelog of added, changed, and removed
+//! notes, which is prepended to `<stem>.changelog.md`.
+//!
+//! For a definition at `deck.toml` released as 1.1.0, the output directory
+//! (`releases/` next to the definition by default) ends up with:
+//!
+//! - `deck-1.1.0.apkg` - the package to distribute
+//! - `deck-1.1.0.toml` - the snapshot the next release is compared against
+//! - `deck.changelog.md` - every release's changes, newest first
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ankit_builder::{ReleaseBuilder, VersionBump};
+//!
+//! # fn example() -> ankit_builder::Result<()> {
+//! let release = ReleaseBuilder::new("deck.toml")
+//!     .bump(VersionBump::Minor)
+//!     .release()?;
+//! println!("Wrote {}", release.apkg_path.display());
+//! print!("{}", release.changelog.to_markdown());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::apkg::ApkgBuilder;
+use crate::error::{Error, Result};
+use crate::schema::{DeckDefinition, NoteDef};
+
+/// Which part of a `MAJOR.MINOR.PATCH` version to increment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionBump {
+    /// `1.2.3` becomes `2.0.0`.
+    Major,
+    /// `1.2.3` becomes `1.3.0`.
+    Minor,
+    /// `1.2.3` becomes `1.2.4`.
+    Patch,
+}
+
+impl VersionBump {
+    /// Apply the bump to a `MAJOR.MINOR.PATCH` version string.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ankit_builder::VersionBump;
+    ///
+    /// assert_eq!(VersionBump::Minor.apply("1.2.3").unwrap(), "1.3.0");
+    /// ```
+    pub fn apply(self, version: &str) -> Result<String> {
+        let (major, minor, patch) = parse_version(version)?;
+        Ok(match self {
+            Self::Major => format!("{}.0.0", major + 1),
+            Self::Minor => format!("{}.{}.0", major, minor + 1),
+            Self::Patch => format!("{}.{}.{}", major, minor, patch + 1),
+        })
+    }
+}
+
+impl FromStr for VersionBump {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "major" => Ok(Self::Major),
+            "minor" => Ok(Self::Minor),
+            "patch" => Ok(Self::Patch),
+            _ => Err(Error::InvalidDefinition(format!(
+                "unknown version bump '{}', expected major, minor, or patch",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for VersionBump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Major => "major",
+            Self::Minor => "minor",
+            Self::Patch => "patch",
+        })
+    }
+}
+
+/// A parsed `MAJOR.MINOR.PATCH` version, ordered numerically.
+type Version = (u64, u64, u64);
+
+/// Parse a `MAJOR.MINOR.PATCH` version.
+fn parse_version(version: &str) -> Result<Version> {
+    let parts: Vec<_> = version.trim().split('.').map(str::parse::<u64>).collect();
+    match parts.as_slice() {
+        [Ok(major), Ok(minor), Ok(patch)] => Ok((*major, *minor, *patch)),
+        _ => Err(Error::InvalidDefinition(format!(
+            "package version '{}' is not in MAJOR.MINOR.PATCH form",
+            version
+        ))),
+    }
+}
+
+/// Notes added, changed, and removed since the previous release.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Changelog {
+    /// Package name.
+    pub package: String,
+    /// The version being released.
+    pub version: String,
+    /// The previously released version, if any.
+    pub previous_version: Option<String>,
+    /// First fields of notes that are new in this release.
+    pub added: Vec<String>,
+    /// First fields of notes whose fields, tags, or deck changed.
+    pub changed: Vec<String>,
+    /// First fields of notes that were dropped in this release.
+    pub removed: Vec<String>,
+}
+
+impl Changelog {
+    /// Compare a definition against the previous release.
+    ///
+    /// Notes are matched by `guid` when both sides have one, otherwise by
+    /// model and first field. With no previous release every note is added.
+    pub fn between(previous: Option<&DeckDefinition>, current: &DeckDefinition) -> Self {
+        let mut changelog = Self {
+            package: current.package.name.clone(),
+            version: current.package.version.clone(),
+            previous_version: previous.map(|p| p.package.version.clone()),
+            ..Self::default()
+        };
+
+        let previous_notes: Vec<((NoteKey, String), &NoteDef)> = previous
+            .map(|p| p.notes.iter().map(|n| (note_key(p, n), n)).collect())
+            .unwrap_or_default();
+        let mut unmatched: HashMap<&NoteKey, &NoteDef> = previous_notes
+            .iter()
+            .map(|((key, _), note)| (key, *note))
+            .collect();
+
+        for note in &current.notes {
+            let (key, first_field) = note_key(current, note);
+            match unmatched.remove(&key) {
+                None => changelog.added.push(first_field),
+                Some(old) => {
+                    if old.fields != note.fields || old.tags != note.tags || old.deck != note.deck {
+                        changelog.changed.push(first_field);
+                    }
+                }
+            }
+        }
+
+        changelog.removed = previous_notes
+            .iter()
+            .filter(|((key, _), _)| unmatched.contains_key(key))
+            .map(|((_, first_field), _)| first_field.clone())
+            .collect();
+
+        changelog
+    }
+
+    /// Whether no notes were added, changed, or removed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+
+    /// Render the changelog as a Markdown section.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("## {} {}\n\n", self.package, self.version);
+        match self.previous_version {
+            Some(ref previous) => out.push_str(&format!(
+                "Changes since {}: {} added, {} changed, {} removed.\n",
+                previous,
+                self.added.len(),
+                self.changed.len(),
+                self.removed.len()
+            )),
+            None => out.push_str(&format!(
+                "First release, with {} notes.\n",
+                self.added.len()
+            )),
+        }
+
+        for (heading, notes) in [
+            ("Added", &self.added),
+            ("Changed", &self.changed),
+            ("Removed", &self.removed),
+        ] {
+            if notes.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("\n### {}\n\n", heading));
+            for note in notes {
+                out.push_str(&format!("- {}\n", note));
+            }
+        }
+        out
+    }
+}
+
+/// How a note is recognized across releases.
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum NoteKey {
+    Guid(String),
+    FirstField { model: String, value: String },
+}
+
+/// A note's key, and its first field for display.
+fn note_key(definition: &DeckDefinition, note: &NoteDef) -> (NoteKey, String) {
+    let first_field = definition
+        .get_model(&note.model)
+        .and_then(|m| m.fields.first())
+        .and_then(|f| note.fields.get(f))
+        .cloned()
+        .unwrap_or_default();
+    let key = match note.guid {
+        Some(ref guid) => NoteKey::Guid(guid.clone()),
+        None => NoteKey::FirstField {
+            model: note.model.clone(),
+            value: first_field.clone(),
+        },
+    };
+    (key, first_field)
+}
+
+/// The files written by a release.
+#[derive(Debug, Clone, Serialize)]
+pub struct Release {
+    /// The released version.
+    pub version: String,
+    /// The versioned `.apkg` file.
+    pub apkg_path: PathBuf,
+    /// The snapshot the next release is compared against.
+    pub snapshot_path: PathBuf,
+    /// The changelog file the new section was prepended to.
+    pub changelog_path: PathBuf,
+    /// Changes since the previous release.
+    pub changelog: Changelog,
+}
+
+/// Builder for versioned releases of a deck definition file.
+#[derive(Debug, Clone)]
+pub struct ReleaseBuilder {
+    path: PathBuf,
+    bump: Option<VersionBump>,
+    output_dir: Option<PathBuf>,
+    media_base_path: Option<PathBuf>,
+}
+
+impl ReleaseBuilder {
+    /// Release the definition at `path`.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            bump: None,
+            output_dir: None,
+            media_base_path: None,
+        }
+    }
+
+    /// Bump `package.version` before releasing.
+    ///
+    /// Without a bump the current version is released as is, which suits a
+    /// first release.
+    pub fn bump(mut self, bump: VersionBump) -> Self {
+        self.bump = Some(bump);
+        self
+    }
+
+    /// Directory for packages, snapshots, and the changelog (default:
+    /// `releases/` next to the definition).
+    pub fn output_dir(mut self, path: impl AsRef<Path>) -> Self {
+        self.output_dir = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Set the base path for resolving media file paths.
+    pub fn media_base_path(mut self, path: impl AsRef<Path>) -> Self {
+        self.media_base_path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Compute the changelog for the next release without writing anything.
+    pub fn plan(&self) -> Result<Changelog> {
+        let (definition, previous, _) = self.prepare()?;
+        Ok(Changelog::between(previous.as_ref(), &definition))
+    }
+
+    /// Write the package, snapshot, and changelog, then update
+    /// `package.version` in the definition file.
+    pub fn release(&self) -> Result<Release> {
+        let (definition, previous, source_version) = self.prepare()?;
+        let changelog = Changelog::between(previous.as_ref(), &definition);
+
+        let output_dir = self.release_dir();
+        std::fs::create_dir_all(&output_dir)?;
+        let stem = self.stem();
+        let version = definition.package.version.clone();
+
+        let apkg_path = output_dir.join(format!("{}-{}.apkg", stem, version));
+        let mut apkg = ApkgBuilder::new(definition.clone());
+        if let Some(ref base) = self.media_base_path {
+            apkg = apkg.media_base_path(base);
+        }
+        apkg.write_to_file(&apkg_path)?;
+
+        let snapshot_path = output_dir.join(format!("{}-{}.toml", stem, version));
+        definition.write_toml(&snapshot_path)?;
+
+        let changelog_path = output_dir.join(format!("{}.changelog.md", stem));
+        let existing = match std::fs::read_to_string(&changelog_path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let mut content = changelog.to_markdown();
+        if !existing.is_empty() {
+            content.push('\n');
+            content.push_str(&existing);
+        }
+        std::fs::write(&changelog_path, content)?;
+
+        if version != source_version {
+            let source = std::fs::read_to_string(&self.path)?;
+            std::fs::write(
+                &self.path,
+                replace_version(&source, &source_version, &version)?,
+            )?;
+        }
+
+        Ok(Release {
+            version,
+            apkg_path,
+            snapshot_path,
+            changelog_path,
+            changelog,
+        })
+    }
+
+    /// Load the definition at its release version, the newest snapshot, and
+    /// the version currently in the definition file.
+    fn prepare(&self) -> Result<(DeckDefinition, Option<DeckDefinition>, String)> {
+        let mut definition = DeckDefinition::from_file(&self.path)?;
+        let source_version = definition.package.version.clone();
+        if let Some(bump) = self.bump {
+            definition.package.version = bump.apply(&definition.package.version)?;
+        }
+        let version = parse_version(&definition.package.version)?;
+
+        let previous = self.latest_snapshot()?;
+        if let Some((previous_version, _)) = previous {
+            if version <= previous_version {
+                return Err(Error::InvalidDefinition(format!(
+                    "version {} is not newer than the last release, {}.{}.{}",
+                    definition.package.version,
+                    previous_version.0,
+                    previous_version.1,
+                    previous_version.2
+                )));
+            }
+        }
+        let previous = match previous {
+            Some((_, path)) => Some(DeckDefinition::from_file(path)?),
+            None => None,
+        };
+        Ok((definition, previous, source_version))
+    }
+
+    /// The snapshot with the highest version in the output directory.
+    fn latest_snapshot(&self) -> Result<Option<(Version, PathBuf)>> {
+        let output_dir = self.release_dir();
+        if !output_dir.is_dir() {
+            return Ok(None);
+        }
+
+        let prefix = format!("{}-", self.stem());
+        let mut latest = None;
+        for entry in std::fs::read_dir(&output_dir)? {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let Some(version) = name
+                .strip_prefix(&prefix)
+                .and_then(|rest| rest.strip_suffix(".toml"))
+                .and_then(|v| parse_version(v).ok())
+            else {
+                continue;
+            };
+            if latest.as_ref().is_none_or(|(v, _)| version > *v) {
+                latest = Some((version, path));
+            }
+        }
+        Ok(latest)
+    }
+
+    /// Where packages, snapshots, and the changelog are written.
+    fn release_dir(&self) -> PathBuf {
+        match self.output_dir {
+            Some(ref dir) => dir.clone(),
+            None => self.path.parent().unwrap_or(Path::new("")).join("releases"),
+        }
+    }
+
+    /// File name prefix for release files, from the definition's file name.
+    fn stem(&self) -> String {
+        self.path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "deck".to_string())
+    }
+}
+
+/// Replace the package version in a definition file's source text.
+///
+/// Only the first line that sets a `version` key to `from` is changed, so
+/// comments and formatting are kept. This covers TOML (`version = "1.0.0"`),
+/// YAML (`version: 1.0.0`), and JSON (`"version": "1.0.0"`) as long as the
+/// package comes before any note with a `version` field.
+fn replace_version(source: &str, from: &str, to: &str) -> Result<String> {
+    let mut replaced = false;
+    let lines: Vec<String> = source
+        .split_inclusive('\n')
+        .map(|line| {
+            let key = line.trim_start().trim_start_matches('"');
+            if !replaced && key.starts_with("version") && line.contains(from) {
+                replaced = true;
+                line.replacen(from, to, 1)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+
+    if !replaced {
+        return Err(Error::InvalidDefinition(format!(
+            "could not find package version '{}' to update",
+            from
+        )));
+    }
+    Ok(lines.concat())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DECK: &str = r#"# Shared Spanish deck
+[package]
+name = "Spanish"
+version = "1.0.0"
+
+[[models]]
+name = "Basic"
+fields = ["Front", "Back"]
+
+[[models.templates]]
+name = "Card 1"
+front = "{{Front}}"
+back = "{{Back}}"
+
+[[decks]]
+name = "Spanish"
+
+[[notes]]
+deck = "Spanish"
+model = "Basic"
+[notes.fields]
+Front = "el gato"
+Back = "the cat"
+
+[[notes]]
+deck = "Spanish"
+model = "Basic"
+[notes.fields]
+Front = "el perro"
+Back = "the dog"
+"#;
+
+    #[test]
+    fn test_version_bump() {
+        assert_eq!(VersionBump::Major.apply("1.2.3").unwrap(), "2.0.0");
+        assert_eq!(VersionBump::Minor.apply("1.2.3").unwrap(), "1.3.0");
+        assert_eq!(VersionBump::Patch.apply("1.2.3").unwrap(), "1.2.4");
+        assert!(VersionBump::Patch.apply("1.2").is_err());
+        assert_eq!("minor".parse::<VersionBump>().unwrap(), VersionBump::Minor);
+    }
+
+    #[test]
+    fn test_changelog_between() {
+        let previous = DeckDefinition::parse(DECK).unwrap();
+        let mut current = previous.clone();
+        current.notes[0]
+            .fields
+            .insert("Back".to_string(), "the cat (m.)".to_string());
+        current.notes.remove(1);
+        let mut added = current.notes[0].clone();
+        added
+            .fields
+            .insert("Front".to_string(), "el pez".to_string());
+        current.notes.push(added);
+
+        let changelog = Changelog::between(Some(&previous), &current);
+        assert_eq!(changelog.added, vec!["el pez"]);
+        assert_eq!(changelog.changed, vec!["el gato"]);
+        assert_eq!(changelog.removed, vec!["el perro"]);
+
+        let markdown = changelog.to_markdown();
+        assert!(markdown.contains("1 added, 1 changed, 1 removed"));
+        assert!(markdown.contains("### Removed\n\n- el perro\n"));
+    }
+
+    #[test]
+    fn test_changelog_follows_guid() {
+        let mut previous = DeckDefinition::parse(DECK).unwrap();
+        previous.notes[0].guid = Some("abc".to_string());
+        let mut current = previous.clone();
+        current.notes[0]
+            .fields
+            .insert("Front".to_string(), "el gato negro".to_string());
+
+        let changelog = Changelog::between(Some(&previous), &current);
+        assert!(changelog.added.is_empty());
+        assert!(changelog.removed.is_empty());
+        assert_eq!(changelog.changed, vec!["el gato negro"]);
+    }
+
+    #[test]
+    fn test_release() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("spanish.toml");
+        std::fs::write(&path, DECK).unwrap();
+
+        let first = ReleaseBuilder::new(&path).release().unwrap();
+        assert_eq!(first.version, "1.0.0");
+        assert_eq!(first.changelog.added.len(), 2);
+        assert!(dir.path().join("releases/spanish-1.0.0.apkg").exists());
+
+        // Releasing the same version again is refused
+        assert!(ReleaseBuilder::new(&path).release().is_err());
+
+        let source = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, source.replace("the dog", "the hound")).unwrap();
+        let second = ReleaseBuilder::new(&path)
+            .bump(VersionBump::Minor)
+            .release()
+            .unwrap();
+        assert_eq!(second.version, "1.1.0");
+        assert_eq!(second.changelog.previous_version.as_deref(), Some("1.0.0"));
+        assert_eq!(second.changelog.changed, vec!["el perro"]);
+        assert!(second.apkg_path.ends_with("spanish-1.1.0.apkg"));
+
+        // The source keeps its comments and gets the new version
+        let source = std::fs::read_to_string(&path).unwrap();
+        assert!(source.starts_with("# Shared Spanish deck\n"));
+        assert!(source.contains("version = \"1.1.0\""));
+
+        let changelog = std::fs::read_to_string(&second.changelog_path).unwrap();
+        assert!(
+            changelog.find("Spanish 1.1.0").unwrap() < changelog.find("Spanish 1.0.0").unwrap()
+        );
+    }
+}
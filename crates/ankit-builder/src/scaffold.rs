@@ -0,0 +1,362 @@
+//! Starter deck definitions for common kinds of decks.
+//!
+//! Each [`ScaffoldKind`] is a commented TOML file with a note type,
+//! templates, CSS, a deck, and one example note, meant to be written to disk
+//! and edited from there.
+//!
+//! # Example
+//!
+//! ```
+//! use ankit_builder::{DeckDefinition, ScaffoldKind};
+//!
+//! // The TOML to write out, comments included
+//! let toml = ScaffoldKind::ClozeSentences.toml();
+//! assert!(toml.contains("[[notes]]"));
+//!
+//! // Or the parsed definition
+//! let definition = DeckDefinition::scaffold(ScaffoldKind::ClozeSentences);
+//! assert_eq!(definition.notes.len(), 1);
+//! ```
+
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::schema::DeckDefinition;
+
+/// A kind of starter deck.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScaffoldKind {
+    /// Word and meaning, carded both ways.
+    BasicVocab,
+    /// Sentences with cloze deletions.
+    ClozeSentences,
+    /// An image on the front, its answer on the back.
+    ImageBased,
+    /// Words with pronunciation audio, with a listening card.
+    LanguageAudio,
+}
+
+impl ScaffoldKind {
+    /// Every kind, in display order.
+    pub const ALL: [ScaffoldKind; 4] = [
+        ScaffoldKind::BasicVocab,
+        ScaffoldKind::ClozeSentences,
+        ScaffoldKind::ImageBased,
+        ScaffoldKind::LanguageAudio,
+    ];
+
+    /// Kebab-case name, as accepted by [`FromStr`].
+    pub fn name(self) -> &'static str {
+        match self {
+            ScaffoldKind::BasicVocab => "basic-vocab",
+            ScaffoldKind::ClozeSentences => "cloze-sentences",
+            ScaffoldKind::ImageBased => "image-based",
+            ScaffoldKind::LanguageAudio => "language-audio",
+        }
+    }
+
+    /// One-line description of the deck.
+    pub fn description(self) -> &'static str {
+        match self {
+            ScaffoldKind::BasicVocab => "Word and meaning, carded both ways",
+            ScaffoldKind::ClozeSentences => "Sentences with cloze deletions",
+            ScaffoldKind::ImageBased => "An image on the front, its answer on the back",
+            ScaffoldKind::LanguageAudio => "Words with pronunciation audio and a listening card",
+        }
+    }
+
+    /// The starter TOML file, with comments.
+    pub fn toml(self) -> &'static str {
+        match self {
+            ScaffoldKind::BasicVocab => BASIC_VOCAB,
+            ScaffoldKind::ClozeSentences => CLOZE_SENTENCES,
+            ScaffoldKind::ImageBased => IMAGE_BASED,
+            ScaffoldKind::LanguageAudio => LANGUAGE_AUDIO,
+        }
+    }
+
+    /// Write the starter TOML file to `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if `path` already exists, so an existing deck
+    /// is never overwritten, or if the write fails.
+    pub fn write(self, path: impl AsRef<Path>) -> Result<()> {
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)?;
+        file.write_all(self.toml().as_bytes())?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for ScaffoldKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ScaffoldKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(|kind| kind.name()).collect();
+                Error::InvalidDefinition(format!(
+                    "unknown scaffold '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                ))
+            })
+    }
+}
+
+impl DeckDefinition {
+    /// A ready-to-edit starter definition of the given kind.
+    ///
+    /// Use [`ScaffoldKind::toml`] or [`ScaffoldKind::write`] to get the file
+    /// with its explanatory comments.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ankit_builder::{DeckDefinition, ScaffoldKind};
+    ///
+    /// let definition = DeckDefinition::scaffold(ScaffoldKind::BasicVocab);
+    /// assert_eq!(definition.models[0].templates.len(), 2);
+    /// ```
+    pub fn scaffold(kind: ScaffoldKind) -> Self {
+        Self::parse(kind.toml()).expect("scaffold TOML is valid")
+    }
+}
+
+const BASIC_VOCAB: &str = r##"# Vocabulary deck: each word gets a card in both directions.
+# Build with `ankit builder build deck.toml -o deck.apkg`.
+
+[package]
+name = "Vocabulary"
+version = "0.1.0"
+description = "Words and their meanings"
+
+[[models]]
+name = "Vocabulary"
+fields = ["Word", "Meaning", "Example"]
+# Example sentences are written in Markdown
+markdown_fields = ["Example"]
+css = """
+.card { font-family: sans-serif; font-size: 24px; text-align: center; color: #222; background: #fff; }
+.example { margin-top: 1em; font-size: 18px; color: #555; }
+"""
+
+[[models.templates]]
+name = "Word -> Meaning"
+front = "{{Word}}"
+back = "{{FrontSide}}<hr id=answer>{{Meaning}}{{#Example}}<div class=example>{{Example}}</div>{{/Example}}"
+
+[[models.templates]]
+name = "Meaning -> Word"
+front = "{{Meaning}}"
+back = "{{FrontSide}}<hr id=answer>{{Word}}{{#Example}}<div class=example>{{Example}}</div>{{/Example}}"
+
+[[decks]]
+name = "Vocabulary"
+description = "Words and their meanings"
+
+# One note per word. Copy this block for each new word.
+[[notes]]
+deck = "Vocabulary"
+model = "Vocabulary"
+tags = ["example"]
+
+[notes.fields]
+Word = "ephemeral"
+Meaning = "lasting a very short time"
+Example = "*Ephemeral* pleasures fade quickly."
+"##;
+
+const CLOZE_SENTENCES: &str = r##"# Cloze deck: hide parts of a sentence with {{c1::...}}.
+# Each cloze number (c1, c2, ...) becomes its own card.
+# Build with `ankit builder build deck.toml -o deck.apkg`.
+
+[package]
+name = "Cloze Sentences"
+version = "0.1.0"
+description = "Sentences with cloze deletions"
+
+[[models]]
+name = "Cloze Sentences"
+fields = ["Text", "Extra"]
+model_type = "cloze"
+css = """
+.card { font-family: sans-serif; font-size: 22px; text-align: center; color: #222; background: #fff; }
+.cloze { font-weight: bold; color: #0a66c2; }
+.extra { margin-top: 1em; font-size: 16px; color: #555; }
+"""
+
+[[models.templates]]
+name = "Cloze"
+front = "{{cloze:Text}}"
+back = "{{cloze:Text}}{{#Extra}}<div class=extra>{{Extra}}</div>{{/Extra}}"
+
+[[decks]]
+name = "Cloze Sentences"
+description = "Sentences with cloze deletions"
+
+# One note per sentence. Copy this block for each new sentence.
+[[notes]]
+deck = "Cloze Sentences"
+model = "Cloze Sentences"
+tags = ["example"]
+
+[notes.fields]
+Text = "The {{c1::mitochondria}} is the {{c2::powerhouse}} of the cell."
+Extra = "Cell biology"
+"##;
+
+const IMAGE_BASED: &str = r##"# Image deck: an image on the front, what it shows on the back.
+# Build with `ankit builder build deck.toml -o deck.apkg`.
+
+[package]
+name = "Images"
+version = "0.1.0"
+description = "Identify what each image shows"
+
+[[models]]
+name = "Image"
+fields = ["Image", "Answer", "Notes"]
+sort_field = "Answer"
+css = """
+.card { font-family: sans-serif; font-size: 22px; text-align: center; color: #222; background: #fff; }
+.card img { max-width: 100%; max-height: 60vh; }
+.notes { margin-top: 1em; font-size: 16px; color: #555; }
+"""
+
+[[models.templates]]
+name = "Image -> Answer"
+front = "{{Image}}"
+back = "{{FrontSide}}<hr id=answer>{{Answer}}{{#Notes}}<div class=notes>{{Notes}}</div>{{/Notes}}"
+
+[[decks]]
+name = "Images"
+description = "Identify what each image shows"
+
+# List every image the notes use. Relative paths are resolved from the media
+# base path, or the working directory. Uncomment once the file exists.
+# [[media]]
+# name = "example.jpg"
+# path = "images/example.jpg"
+
+# One note per image. Copy this block for each new image.
+[[notes]]
+deck = "Images"
+model = "Image"
+tags = ["example"]
+
+[notes.fields]
+Image = '<img src="example.jpg">'
+Answer = "Eiffel Tower"
+Notes = "Paris, completed in 1889"
+"##;
+
+const LANGUAGE_AUDIO: &str = r##"# Language deck with audio: a reading card and a listening card per word.
+# Build with `ankit builder build deck.toml -o deck.apkg`.
+
+[package]
+name = "Language"
+version = "0.1.0"
+description = "Words with pronunciation audio"
+
+[[models]]
+name = "Language with Audio"
+fields = ["Word", "Translation", "Audio", "Example"]
+css = """
+.card { font-family: sans-serif; font-size: 24px; text-align: center; color: #222; background: #fff; }
+.translation { font-weight: bold; }
+.example { margin-top: 1em; font-size: 18px; font-style: italic; color: #555; }
+"""
+
+[[models.templates]]
+name = "Reading"
+front = "{{Word}}"
+back = "{{FrontSide}}<hr id=answer><div class=translation>{{Translation}}</div>{{Audio}}{{#Example}}<div class=example>{{Example}}</div>{{/Example}}"
+
+[[models.templates]]
+name = "Listening"
+front = "{{Audio}}"
+back = "{{FrontSide}}<hr id=answer>{{Word}}<div class=translation>{{Translation}}</div>"
+
+[[decks]]
+name = "Language"
+description = "Words with pronunciation audio"
+
+# List every audio file the notes use. Relative paths are resolved from the
+# media base path, or the working directory. Uncomment once the file exists.
+# [[media]]
+# name = "hola.mp3"
+# path = "audio/hola.mp3"
+
+# One note per word. Copy this block for each new word.
+[[notes]]
+deck = "Language"
+model = "Language with Audio"
+tags = ["example"]
+
+[notes.fields]
+Word = "hola"
+Translation = "hello"
+Audio = "[sound:hola.mp3]"
+Example = "¡Hola! ¿Qué tal?"
+"##;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scaffolds_parse_and_lint_clean() {
+        for kind in ScaffoldKind::ALL {
+            let definition = DeckDefinition::scaffold(kind);
+            assert_eq!(definition.notes.len(), 1, "{}", kind);
+            assert!(!definition.models[0].css.as_deref().unwrap_or("").is_empty());
+
+            let report = definition.lint();
+            assert!(report.is_clean(), "{}: {:?}", kind, report.diagnostics);
+        }
+    }
+
+    #[test]
+    fn test_from_str_round_trip() {
+        for kind in ScaffoldKind::ALL {
+            assert_eq!(kind.name().parse::<ScaffoldKind>().unwrap(), kind);
+        }
+        assert!(matches!(
+            "flashcards".parse::<ScaffoldKind>(),
+            Err(Error::InvalidDefinition(_))
+        ));
+    }
+
+    #[test]
+    fn test_write_refuses_to_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("deck.toml");
+
+        ScaffoldKind::LanguageAudio.write(&path).unwrap();
+        let definition = DeckDefinition::from_file(&path).unwrap();
+        assert_eq!(definition.models[0].templates.len(), 2);
+
+        assert!(matches!(
+            ScaffoldKind::BasicVocab.write(&path),
+            Err(Error::Io(_))
+        ));
+    }
+}
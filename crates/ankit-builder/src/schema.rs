@@ -41,13 +41,29 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use crate::error::{Error, Result};
+use crate::format::Format;
+use crate::generator::GeneratorDef;
+use crate::occlusion::OcclusionDef;
 
 /// Root structure for a deck definition file.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Serializes canonically so repeated writes of the same content are
+/// identical: note fields follow their model's field order, with any fields
+/// the model doesn't declare sorted by name after them.
+#[derive(Debug, Clone, Deserialize)]
 pub struct DeckDefinition {
+    /// Other definition files to merge into this one, relative to this
+    /// file; the file name may contain `*` and `?` wildcards.
+    ///
+    /// Included files are loaded by [`from_file()`](Self::from_file) and
+    /// [`parse()`](Self::parse), which leave this empty. See the
+    /// [`include`](crate::include) module docs.
+    #[serde(default)]
+    pub include: Vec<String>,
+
     /// Package metadata.
     pub package: PackageInfo,
 
@@ -66,20 +82,93 @@ pub struct DeckDefinition {
     /// Media file definitions.
     #[serde(default)]
     pub media: Vec<MediaDef>,
+
+    /// Note generators, expanded into `notes` when the definition is parsed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub generators: Vec<GeneratorDef>,
+
+    /// Image occlusions, expanded into `notes` and `media` when the
+    /// definition is parsed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub occlusions: Vec<OcclusionDef>,
+
+    /// Directory that relative [`include`](ModelDef::include) paths are
+    /// resolved against.
+    ///
+    /// [`from_file()`](Self::from_file) sets this to the file's directory.
+    /// When unset, the working directory is used.
+    #[serde(skip)]
+    pub base_dir: Option<PathBuf>,
 }
 
 impl DeckDefinition {
-    /// Load a deck definition from a TOML file.
+    /// Load a deck definition from a file.
+    ///
+    /// The format is picked by [`Format::from_path`]: `.yaml`/`.yml` and
+    /// `.json` files need the `yaml` and `json` features, and anything else
+    /// is read as TOML.
     pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
         let content = std::fs::read_to_string(path)?;
-        Self::parse(&content)
+        let mut def: Self = Format::from_path(path).parse(&content)?;
+        def.base_dir = path.parent().map(Path::to_path_buf);
+        def.finish(Some(path))
     }
 
     /// Parse a deck definition from a TOML string.
     pub fn parse(content: &str) -> Result<Self> {
-        let def: DeckDefinition = toml::from_str(content)?;
-        def.validate()?;
-        Ok(def)
+        Self::parse_format(content, Format::Toml)
+    }
+
+    /// Parse a deck definition from a string in the given format.
+    ///
+    /// Includes are resolved against the working directory.
+    pub fn parse_format(content: &str, format: Format) -> Result<Self> {
+        let def: DeckDefinition = format.parse(content)?;
+        def.finish(None)
+    }
+
+    /// Load includes, expand generators and occlusions, and validate.
+    fn finish(mut self, root: Option<&Path>) -> Result<Self> {
+        crate::include::resolve(&mut self, root)?;
+        self.expand_generators()?;
+        self.expand_occlusions()?;
+        self.validate()?;
+        Ok(self)
+    }
+
+    /// Expand all generators into notes, leaving `generators` empty.
+    ///
+    /// Called automatically by [`parse()`](Self::parse) and
+    /// [`from_file()`](Self::from_file).
+    pub fn expand_generators(&mut self) -> Result<()> {
+        for generator in std::mem::take(&mut self.generators) {
+            self.notes.extend(generator.expand()?);
+        }
+        Ok(())
+    }
+
+    /// Expand all image occlusions into notes and generated SVG media,
+    /// leaving `occlusions` empty.
+    ///
+    /// Adds an [image occlusion model](ModelDef::image_occlusion) for each
+    /// model name that isn't already defined. Called automatically by
+    /// [`parse()`](Self::parse) and [`from_file()`](Self::from_file).
+    pub fn expand_occlusions(&mut self) -> Result<()> {
+        for occlusion in std::mem::take(&mut self.occlusions) {
+            if self.get_model(&occlusion.model).is_none() {
+                self.models
+                    .push(ModelDef::image_occlusion(occlusion.model.clone()));
+            }
+            let (notes, media) = occlusion.expand()?;
+            self.notes.extend(notes);
+            for file in media {
+                if !self.media.iter().any(|m| m.name == file.name) {
+                    self.media.push(file);
+                }
+            }
+        }
+        Ok(())
     }
 
     /// Validate the deck definition for consistency.
@@ -103,6 +192,79 @@ impl DeckDefinition {
                     });
                 }
             }
+
+            // Check that card selections name templates of the model
+            let selected = note.cards.iter().flatten().chain(&note.suspend);
+            for template in selected {
+                if !model.templates.iter().any(|t| &t.name == template) {
+                    return Err(Error::InvalidDefinition(format!(
+                        "note in deck '{}': model '{}' has no template named '{}'",
+                        note.deck, note.model, template
+                    )));
+                }
+            }
+            if note.cards.as_ref().is_some_and(|cards| cards.is_empty()) {
+                return Err(Error::InvalidDefinition(format!(
+                    "note in deck '{}': cards must name at least one template",
+                    note.deck
+                )));
+            }
+        }
+
+        // Check deck options
+        for deck in &self.decks {
+            if let Some(action) = deck
+                .options
+                .as_ref()
+                .and_then(|o| o.leech_action.as_deref())
+            {
+                if action != "suspend" && action != "tag" {
+                    return Err(Error::InvalidDefinition(format!(
+                        "deck '{}': leech_action must be \"suspend\" or \"tag\", got \"{}\"",
+                        deck.name, action
+                    )));
+                }
+            }
+        }
+
+        // Decks sharing an options group must agree on its settings, since
+        // the group can only hold one value for each
+        let mut groups: std::collections::HashMap<&str, (&str, &DeckOptions)> =
+            std::collections::HashMap::new();
+        for deck in &self.decks {
+            let Some(options) = &deck.options else {
+                continue;
+            };
+            let group = options.group_name(&deck.name);
+            match groups.get(group) {
+                Some((first, first_options)) if !options.same_settings(first_options) => {
+                    return Err(Error::InvalidDefinition(format!(
+                        "decks '{}' and '{}' share options group '{}' but set different options",
+                        first, deck.name, group
+                    )));
+                }
+                Some(_) => {}
+                None => {
+                    groups.insert(group, (&deck.name, options));
+                }
+            }
+        }
+
+        // Check that GUIDs are usable as tags and identify one note each
+        let mut guids = std::collections::HashSet::new();
+        for guid in self.notes.iter().filter_map(|n| n.guid.as_deref()) {
+            if guid.is_empty() || guid.contains(char::is_whitespace) {
+                return Err(Error::InvalidDefinition(format!(
+                    "note guid '{}' must be non-empty and contain no whitespace",
+                    guid
+                )));
+            }
+            if !guids.insert(guid) {
+                return Err(Error::InvalidDefinition(format!(
+                    "note guid '{}' is used more than once",
+                    guid
+                )));
+            }
         }
 
         // Check that all notes reference valid decks
@@ -128,6 +290,35 @@ impl DeckDefinition {
         self.decks.iter().find(|d| d.name == name)
     }
 
+    /// The CSS for a model: its [`include`](ModelDef::include) files in
+    /// order, followed by its own `css`.
+    ///
+    /// Returns `None` if the model has neither.
+    pub fn model_css(&self, model: &ModelDef) -> Result<Option<String>> {
+        if model.include.is_empty() {
+            return Ok(model.css.clone());
+        }
+
+        let mut parts = Vec::with_capacity(model.include.len() + 1);
+        for include in &model.include {
+            let path = match self.base_dir {
+                Some(ref base) => base.join(include),
+                None => PathBuf::from(include),
+            };
+            let content = std::fs::read_to_string(&path).map_err(|e| {
+                Error::InvalidDefinition(format!(
+                    "model '{}': cannot read include '{}': {}",
+                    model.name,
+                    path.display(),
+                    e
+                ))
+            })?;
+            parts.push(content.trim_end().to_string());
+        }
+        parts.extend(model.css.clone());
+        Ok(Some(parts.join("\n\n")))
+    }
+
     /// Get notes for a specific deck.
     pub fn notes_for_deck(&self, deck_name: &str) -> impl Iterator<Item = &NoteDef> {
         self.notes.iter().filter(move |n| n.deck == deck_name)
@@ -193,6 +384,71 @@ impl DeckDefinition {
         }
     }
 
+    /// Write the deck definition to a TOML file.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ankit_builder::DeckDefinition;
+    ///
+    /// # fn example() -> ankit_builder::Result<()> {
+    /// let definition = DeckDefinition::from_file("input.toml")?;
+    /// // ... modify definition ...
+    /// definition.write_toml("output.toml")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_toml(&self, path: impl AsRef<Path>) -> Result<()> {
+        let content = self.to_toml()?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Serialize the deck definition to a TOML string.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ankit_builder::DeckDefinition;
+    ///
+    /// # fn example() -> ankit_builder::Result<()> {
+    /// let definition = DeckDefinition::from_file("input.toml")?;
+    /// let toml_string = definition.to_toml()?;
+    /// println!("{}", toml_string);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_toml(&self) -> Result<String> {
+        self.to_string_format(Format::Toml)
+    }
+
+    /// Write the deck definition to a file, picking the format from its
+    /// extension like [`from_file()`](Self::from_file).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ankit_builder::DeckDefinition;
+    ///
+    /// # fn example() -> ankit_builder::Result<()> {
+    /// let definition = DeckDefinition::from_file("deck.toml")?;
+    /// // Needs the `yaml` feature
+    /// definition.write_file("deck.yaml")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let content = self.to_string_format(Format::from_path(path))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Serialize the deck definition to a string in the given format.
+    pub fn to_string_format(&self, format: Format) -> Result<String> {
+        format.serialize(self)
+    }
+
     /// Set markdown fields for a model.
     ///
     /// Convenience method to mark which fields should use Markdown format.
@@ -226,18 +482,97 @@ pub struct PackageInfo {
     pub version: String,
 
     /// Package author.
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub author: Option<String>,
 
-    /// Package description.
-    #[serde(default)]
+    /// Package description, in Markdown.
+    ///
+    /// Shown in Anki's deck description panel for top-level decks without
+    /// their own description, and in the README.html written next to the
+    /// .apkg for shared deck listings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+
+    /// License the deck is shared under (e.g. "CC BY-SA 4.0").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+
+    /// Tags describing the package for shared deck listings.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Where users can report problems or ask questions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub support_url: Option<String>,
+}
+
+impl PackageInfo {
+    /// Create package metadata with just a name and the default version.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: default_version(),
+            author: None,
+            description: None,
+            license: None,
+            tags: Vec::new(),
+            support_url: None,
+        }
+    }
+
+    /// The description as HTML, followed by a line with the version,
+    /// author, license, support link and tags.
+    ///
+    /// Returns `None` if the package has no description.
+    pub fn description_html(&self) -> Option<String> {
+        let description = self.description.as_deref()?;
+        let mut html = crate::markdown::markdown_to_html(description);
+
+        let mut details = vec![format!("Version {}", escape(&self.version))];
+        if let Some(author) = &self.author {
+            details.push(format!("by {}", escape(author)));
+        }
+        if let Some(license) = &self.license {
+            details.push(format!("License: {}", escape(license)));
+        }
+        if let Some(url) = &self.support_url {
+            let url = escape(url);
+            details.push(format!("<a href=\"{url}\">Support</a>"));
+        }
+        if !self.tags.is_empty() {
+            let tags: Vec<String> = self.tags.iter().map(|t| escape(t)).collect();
+            details.push(format!("Tags: {}", tags.join(", ")));
+        }
+        html.push_str("<br><br><small>");
+        html.push_str(&details.join(" &middot; "));
+        html.push_str("</small>");
+        Some(html)
+    }
+
+    /// A standalone HTML page describing the package, suitable for an
+    /// AnkiWeb shared deck listing.
+    ///
+    /// Returns `None` if the package has no description.
+    pub fn readme_html(&self) -> Option<String> {
+        let body = self.description_html()?;
+        let title = escape(&self.name);
+        Some(format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n</head>\n<body>\n<h1>{title}</h1>\n{body}\n</body>\n</html>\n"
+        ))
+    }
 }
 
 fn default_version() -> String {
     "1.0.0".to_string()
 }
 
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 /// Model (note type) definition.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelDef {
@@ -251,15 +586,22 @@ pub struct ModelDef {
     pub templates: Vec<TemplateDef>,
 
     /// CSS styling for cards.
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub css: Option<String>,
 
+    /// CSS files to prepend to `css`, such as a shared theme.
+    ///
+    /// Paths are relative to the definition file. The files are read when the
+    /// deck is built or imported; see [`DeckDefinition::model_css()`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+
     /// Which field to sort by (default: first field).
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub sort_field: Option<String>,
 
     /// Model ID (auto-generated if not specified).
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub id: Option<i64>,
 
     /// Fields that use Markdown format (converted to/from HTML).
@@ -272,8 +614,16 @@ pub struct ModelDef {
     /// Model type: "standard" (default) or "cloze".
     ///
     /// When set to "cloze", templates are optional and a default cloze template is used.
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub model_type: Option<String>,
+
+    /// Version of the templates and CSS, bumped when they change.
+    ///
+    /// Sync records the applied version in Anki and updates the model's
+    /// templates and CSS when this is newer. Models without a version are
+    /// never updated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
 }
 
 impl ModelDef {
@@ -302,6 +652,60 @@ impl ModelDef {
             id: None,
             markdown_fields: vec![],
             model_type: Some("cloze".to_string()),
+            version: None,
+            include: vec![],
+        }
+    }
+
+    /// Create the note type used by image occlusion notes.
+    ///
+    /// The mask fields hold SVG overlays that are stretched over the image.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ankit_builder::ModelDef;
+    ///
+    /// let model = ModelDef::image_occlusion("Image Occlusion (SVG)");
+    /// assert!(model.is_image_occlusion());
+    /// assert_eq!(model.fields[1], "Image");
+    /// ```
+    pub fn image_occlusion(name: impl Into<String>) -> Self {
+        let header = r#"{{#Header}}<div class="io-header">{{Header}}</div>{{/Header}}"#;
+        Self {
+            name: name.into(),
+            fields: crate::occlusion::FIELDS.iter().map(|f| f.to_string()).collect(),
+            templates: vec![TemplateDef {
+                name: "Occlusion".to_string(),
+                front: format!(
+                    r#"{}<div class="io-wrap">{{{{Image}}}}{{{{Question Mask}}}}</div>"#,
+                    header
+                ),
+                back: format!(
+                    concat!(
+                        r#"{}<div class="io-wrap">{{{{Image}}}}{{{{Answer Mask}}}}</div>"#,
+                        r#"{{{{#Label}}}}<div class="io-label">{{{{Label}}}}</div>{{{{/Label}}}}"#,
+                        r#"{{{{#Footer}}}}<div class="io-footer">{{{{Footer}}}}</div>{{{{/Footer}}}}"#
+                    ),
+                    header
+                ),
+            }],
+            css: Some(
+                concat!(
+                    ".card { font-family: arial; font-size: 20px; text-align: center; }\n",
+                    ".io-wrap { position: relative; display: inline-block; }\n",
+                    ".io-wrap img { display: block; max-width: 100%; }\n",
+                    ".io-wrap img + img { position: absolute; top: 0; left: 0; width: 100%; height: 100%; }\n",
+                    ".io-label { margin-top: 0.5em; font-weight: bold; }\n",
+                )
+                .to_string(),
+            ),
+            sort_field: Some("Label".to_string()),
+            id: None,
+            markdown_fields: vec![],
+            model_type: Some("image-occlusion".to_string()),
+            version: None,
+            include: vec![],
         }
     }
 
@@ -309,6 +713,11 @@ impl ModelDef {
     pub fn is_cloze(&self) -> bool {
         self.model_type.as_deref() == Some("cloze")
     }
+
+    /// Check if this is an image occlusion model.
+    pub fn is_image_occlusion(&self) -> bool {
+        self.model_type.as_deref() == Some("image-occlusion")
+    }
 }
 
 impl ModelDef {
@@ -342,12 +751,159 @@ pub struct DeckDef {
     pub name: String,
 
     /// Deck description.
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
 
     /// Deck ID (auto-generated if not specified).
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub id: Option<i64>,
+
+    /// Deck options (daily limits, learning steps, lapses).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<DeckOptions>,
+}
+
+/// Deck options group settings.
+///
+/// Unset values keep Anki's defaults (or, when importing via AnkiConnect,
+/// the values of the deck's current options group).
+///
+/// ```toml
+/// [[decks]]
+/// name = "Spanish"
+///
+/// [decks.options]
+/// new_per_day = 10
+/// reviews_per_day = 150
+/// learning_steps = [1.0, 10.0, 60.0]
+/// relearning_steps = [10.0]
+/// leech_threshold = 6
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeckOptions {
+    /// Name of the options group (default: the deck name).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+
+    /// Maximum new cards per day.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_per_day: Option<i64>,
+
+    /// Maximum reviews per day.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reviews_per_day: Option<i64>,
+
+    /// Learning steps in minutes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub learning_steps: Option<Vec<f64>>,
+
+    /// Interval in days after a new card graduates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graduating_interval: Option<i64>,
+
+    /// Interval in days after answering a new card "Easy".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub easy_interval: Option<i64>,
+
+    /// Maximum review interval in days.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maximum_interval: Option<i64>,
+
+    /// Relearning steps in minutes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relearning_steps: Option<Vec<f64>>,
+
+    /// Minimum interval in days after a lapse.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lapse_minimum_interval: Option<i64>,
+
+    /// Number of lapses before a card is marked as a leech.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leech_threshold: Option<i64>,
+
+    /// Action for leeches: "suspend" or "tag".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leech_action: Option<String>,
+}
+
+impl DeckOptions {
+    /// Options group name for a deck with these options.
+    pub fn group_name<'a>(&'a self, deck_name: &'a str) -> &'a str {
+        self.group.as_deref().unwrap_or(deck_name)
+    }
+
+    /// Whether two decks' options set the same values, ignoring the group
+    /// name.
+    fn same_settings(&self, other: &DeckOptions) -> bool {
+        let settings = |options: &DeckOptions| DeckOptions {
+            group: None,
+            ..options.clone()
+        };
+        settings(self) == settings(other)
+    }
+
+    /// Leech action as Anki's numeric code (0 = suspend, 1 = tag only).
+    pub fn leech_action_code(&self) -> Option<i64> {
+        match self.leech_action.as_deref() {
+            Some("suspend") => Some(0),
+            Some("tag") => Some(1),
+            _ => None,
+        }
+    }
+
+    /// Leech action name for Anki's numeric code.
+    #[cfg(any(feature = "apkg", feature = "connect"))]
+    pub(crate) fn leech_action_name(code: i64) -> Option<String> {
+        match code {
+            0 => Some("suspend".to_string()),
+            1 => Some("tag".to_string()),
+            _ => None,
+        }
+    }
+
+    /// Every setting of an options group used by a deck.
+    ///
+    /// The group name is only kept if it differs from the deck name.
+    #[cfg(feature = "connect")]
+    pub fn from_config(config: &ankit::DeckConfig, deck_name: &str) -> Self {
+        Self {
+            group: Some(config.name.clone()).filter(|name| name != deck_name),
+            new_per_day: Some(config.new.per_day),
+            reviews_per_day: Some(config.rev.per_day),
+            learning_steps: Some(config.new.delays.clone()),
+            graduating_interval: config.new.ints.first().copied(),
+            easy_interval: config.new.ints.get(1).copied(),
+            maximum_interval: Some(config.rev.max_ivl),
+            relearning_steps: Some(config.lapse.delays.clone()),
+            lapse_minimum_interval: Some(config.lapse.min_int),
+            leech_threshold: Some(config.lapse.leech_fails),
+            leech_action: Self::leech_action_name(config.lapse.leech_action),
+        }
+    }
+
+    /// These options without the settings that equal `defaults`.
+    #[cfg(feature = "apkg")]
+    pub(crate) fn without_defaults(self, defaults: &DeckOptions) -> Self {
+        fn keep<T: PartialEq>(value: Option<T>, default: &Option<T>) -> Option<T> {
+            value.filter(|value| Some(value) != default.as_ref())
+        }
+        Self {
+            group: self.group,
+            new_per_day: keep(self.new_per_day, &defaults.new_per_day),
+            reviews_per_day: keep(self.reviews_per_day, &defaults.reviews_per_day),
+            learning_steps: keep(self.learning_steps, &defaults.learning_steps),
+            graduating_interval: keep(self.graduating_interval, &defaults.graduating_interval),
+            easy_interval: keep(self.easy_interval, &defaults.easy_interval),
+            maximum_interval: keep(self.maximum_interval, &defaults.maximum_interval),
+            relearning_steps: keep(self.relearning_steps, &defaults.relearning_steps),
+            lapse_minimum_interval: keep(
+                self.lapse_minimum_interval,
+                &defaults.lapse_minimum_interval,
+            ),
+            leech_threshold: keep(self.leech_threshold, &defaults.leech_threshold),
+            leech_action: keep(self.leech_action, &defaults.leech_action),
+        }
+    }
 }
 
 /// Note definition.
@@ -360,19 +916,36 @@ pub struct NoteDef {
     pub model: String,
 
     /// Field values.
+    #[serde(serialize_with = "serialize_sorted")]
     pub fields: HashMap<String, String>,
 
     /// Tags for this note.
     #[serde(default)]
     pub tags: Vec<String>,
 
-    /// Custom GUID (auto-generated if not specified).
-    #[serde(default)]
+    /// Stable note identity (auto-generated if not specified).
+    ///
+    /// Used as the note's GUID in `.apkg` files. With AnkiConnect it is
+    /// stored as an `ankit-guid::<guid>` tag, which lets diff and sync
+    /// follow a note through edits to its first field. Export and sync fill
+    /// it in for notes that don't have one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub guid: Option<String>,
 
     /// Anki note ID (assigned after sync, used for tracking).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub note_id: Option<i64>,
+
+    /// Names of the templates that generate cards for this note.
+    ///
+    /// Every template generates a card if not specified, e.g. use
+    /// `cards = ["Card 1"]` to skip a reverse card.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cards: Option<Vec<String>>,
+
+    /// Names of the templates whose cards start suspended.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suspend: Vec<String>,
 }
 
 impl NoteDef {
@@ -385,6 +958,23 @@ impl NoteDef {
             .collect()
     }
 
+    /// Whether the template named `template` generates a card for this note.
+    pub fn generates_card(&self, template: &str) -> bool {
+        self.cards
+            .as_ref()
+            .is_none_or(|cards| cards.iter().any(|c| c == template))
+    }
+
+    /// Whether the card for the template named `template` starts suspended.
+    pub fn suspends_card(&self, template: &str) -> bool {
+        self.suspend.iter().any(|s| s == template)
+    }
+
+    /// Whether this note restricts or suspends any of its cards.
+    pub fn selects_cards(&self) -> bool {
+        self.cards.is_some() || !self.suspend.is_empty()
+    }
+
     /// Get tags as a space-separated string with surrounding spaces.
     pub fn tags_string(&self) -> String {
         if self.tags.is_empty() {
@@ -425,6 +1015,100 @@ impl NoteDef {
     }
 }
 
+impl Serialize for DeckDefinition {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        CanonicalDefinition {
+            include: &self.include,
+            package: &self.package,
+            models: &self.models,
+            decks: &self.decks,
+            notes: self
+                .notes
+                .iter()
+                .map(|note| CanonicalNote::new(note, self.get_model(&note.model)))
+                .collect(),
+            media: &self.media,
+            generators: &self.generators,
+            occlusions: &self.occlusions,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Serialized form of [`DeckDefinition`].
+#[derive(Serialize)]
+struct CanonicalDefinition<'a> {
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    include: &'a [String],
+    package: &'a PackageInfo,
+    models: &'a [ModelDef],
+    decks: &'a [DeckDef],
+    notes: Vec<CanonicalNote<'a>>,
+    media: &'a [MediaDef],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    generators: &'a [GeneratorDef],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    occlusions: &'a [OcclusionDef],
+}
+
+/// Serialized form of a [`NoteDef`], with fields in model order.
+#[derive(Serialize)]
+struct CanonicalNote<'a> {
+    deck: &'a str,
+    model: &'a str,
+    #[serde(serialize_with = "serialize_pairs")]
+    fields: Vec<(&'a String, &'a String)>,
+    tags: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    guid: &'a Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note_id: &'a Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cards: &'a Option<Vec<String>>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    suspend: &'a [String],
+}
+
+impl<'a> CanonicalNote<'a> {
+    fn new(note: &'a NoteDef, model: Option<&ModelDef>) -> Self {
+        let position = |name: &String| {
+            model
+                .and_then(|m| m.fields.iter().position(|f| f == name))
+                .unwrap_or(usize::MAX)
+        };
+        let mut fields: Vec<_> = note.fields.iter().collect();
+        fields.sort_by(|a, b| (position(a.0), a.0).cmp(&(position(b.0), b.0)));
+
+        Self {
+            deck: &note.deck,
+            model: &note.model,
+            fields,
+            tags: &note.tags,
+            guid: &note.guid,
+            note_id: &note.note_id,
+            cards: &note.cards,
+            suspend: &note.suspend,
+        }
+    }
+}
+
+fn serialize_pairs<S: serde::Serializer>(
+    pairs: &[(&String, &String)],
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_map(pairs.iter().copied())
+}
+
+fn serialize_sorted<S: serde::Serializer>(
+    map: &HashMap<String, String>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_map(map.iter().collect::<std::collections::BTreeMap<_, _>>())
+}
+
 /// Media file definition.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MediaDef {
@@ -432,7 +1116,32 @@ pub struct MediaDef {
     pub name: String,
 
     /// Path to the source file.
+    #[serde(default)]
     pub path: String,
+
+    /// Inline file contents, used instead of `path` (e.g. generated SVG).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+}
+
+impl MediaDef {
+    /// Media read from a file.
+    pub fn file(name: impl Into<String>, path: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            path: path.into(),
+            data: None,
+        }
+    }
+
+    /// Media with inline text contents.
+    pub fn inline(name: impl Into<String>, data: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            path: String::new(),
+            data: Some(data.into()),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -556,6 +1265,80 @@ InvalidField = "X"
         assert!(matches!(result, Err(Error::FieldNotFound { .. })));
     }
 
+    #[test]
+    fn test_duplicate_guid() {
+        let toml = r#"
+[package]
+name = "Test"
+
+[[models]]
+name = "Basic"
+fields = ["Front", "Back"]
+
+[[models.templates]]
+name = "Card 1"
+front = "{{Front}}"
+back = "{{Back}}"
+
+[[decks]]
+name = "Test Deck"
+
+[[notes]]
+deck = "Test Deck"
+model = "Basic"
+guid = "abc123"
+fields = { Front = "Q1" }
+
+[[notes]]
+deck = "Test Deck"
+model = "Basic"
+guid = "abc123"
+fields = { Front = "Q2" }
+"#;
+
+        let result = DeckDefinition::parse(toml);
+        assert!(matches!(result, Err(Error::InvalidDefinition(_))));
+
+        let result = DeckDefinition::parse(&toml.replacen("abc123", "has space", 1));
+        assert!(matches!(result, Err(Error::InvalidDefinition(_))));
+    }
+
+    #[test]
+    fn test_validate_shared_options_group() {
+        let toml = r#"
+[package]
+name = "Languages"
+
+[[decks]]
+name = "Spanish"
+
+[decks.options]
+group = "Languages"
+new_per_day = 10
+
+[[decks]]
+name = "French"
+
+[decks.options]
+group = "Languages"
+new_per_day = 10
+"#;
+
+        assert!(DeckDefinition::parse(toml).is_ok());
+
+        // Only one of the two values could be stored in the group
+        let conflicting = toml.replacen("new_per_day = 10", "new_per_day = 20", 1);
+        let result = DeckDefinition::parse(&conflicting);
+        assert!(matches!(
+            result,
+            Err(Error::InvalidDefinition(message)) if message.contains("'Languages'")
+        ));
+
+        // A deck naming the group without settings would also differ
+        let partial = toml.replacen("new_per_day = 10\n", "", 1);
+        assert!(DeckDefinition::parse(&partial).is_err());
+    }
+
     #[test]
     fn test_fields_ordered() {
         let model = ModelDef {
@@ -567,6 +1350,8 @@ InvalidField = "X"
             id: None,
             markdown_fields: vec![],
             model_type: None,
+            version: None,
+            include: vec![],
         };
 
         let mut fields = HashMap::new();
@@ -581,6 +1366,8 @@ InvalidField = "X"
             tags: vec![],
             guid: None,
             note_id: None,
+            cards: None,
+            suspend: Vec::new(),
         };
 
         let ordered = note.fields_ordered(&model);
@@ -611,6 +1398,8 @@ InvalidField = "X"
             id: None,
             markdown_fields: vec![],
             model_type: None,
+            version: None,
+            include: vec![],
         };
 
         assert!(!model.is_cloze());
@@ -639,4 +1428,193 @@ name = "Cloze Test"
         let def = DeckDefinition::parse(toml).unwrap();
         assert!(def.models[0].is_cloze());
     }
+
+    #[test]
+    fn test_parse_generators() {
+        let toml = r#"
+[package]
+name = "Verbs"
+
+[[models]]
+name = "Basic"
+fields = ["Front", "Back"]
+
+[[models.templates]]
+name = "Card 1"
+front = "{{Front}}"
+back = "{{Back}}"
+
+[[decks]]
+name = "Verbs"
+
+[[notes]]
+deck = "Verbs"
+model = "Basic"
+
+[notes.fields]
+Front = "ser"
+Back = "to be"
+
+[[generators]]
+deck = "Verbs"
+model = "Basic"
+tags = ["{{tense}}"]
+
+[generators.fields]
+Front = "{{infinitive}} ({{pronoun}})"
+Back = "{{pronoun}} {{stem}}{{ending}}"
+
+[[generators.tables.verbs]]
+infinitive = "hablar"
+stem = "habl"
+
+[[generators.tables.verbs]]
+infinitive = "cantar"
+stem = "cant"
+
+[[generators.tables.forms]]
+pronoun = "yo"
+tense = "present"
+ending = "o"
+"#;
+
+        let def = DeckDefinition::parse(toml).unwrap();
+        assert!(def.generators.is_empty());
+        assert_eq!(def.notes.len(), 3);
+        assert_eq!(def.notes[1].fields["Front"], "hablar (yo)");
+        assert_eq!(def.notes[2].fields["Back"], "yo canto");
+        assert_eq!(def.notes[2].tags, vec!["present"]);
+    }
+
+    #[test]
+    fn test_generator_invalid_field_reference() {
+        let toml = r#"
+[package]
+name = "Verbs"
+
+[[models]]
+name = "Basic"
+fields = ["Front", "Back"]
+
+[[models.templates]]
+name = "Card 1"
+front = "{{Front}}"
+back = "{{Back}}"
+
+[[decks]]
+name = "Verbs"
+
+[[generators]]
+deck = "Verbs"
+model = "Basic"
+
+[generators.fields]
+Question = "{{word}}"
+
+[[generators.tables.words]]
+word = "hola"
+"#;
+
+        assert!(matches!(
+            DeckDefinition::parse(toml),
+            Err(Error::FieldNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_expand_occlusions() {
+        let toml = r#"
+[package]
+name = "Anatomy"
+
+[[decks]]
+name = "Anatomy"
+
+[[occlusions]]
+deck = "Anatomy"
+image = "heart.png"
+width = 400
+height = 300
+mode = "hide-one"
+
+[[occlusions.masks]]
+left = 10
+top = 10
+width = 50
+height = 20
+"#;
+
+        let def = DeckDefinition::parse(toml).unwrap();
+        assert!(def.occlusions.is_empty());
+        assert_eq!(def.notes.len(), 1);
+        assert_eq!(def.media.len(), 3);
+        assert!(def.media.iter().all(|m| m.data.is_some()));
+
+        let model = def.get_model(crate::occlusion::DEFAULT_MODEL).unwrap();
+        assert!(model.is_image_occlusion());
+        assert!(def.lint().is_clean());
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml_definition() {
+        let yaml = r#"
+package:
+  name: Test Deck
+models:
+  - name: Basic
+    fields: [Front, Back]
+    templates:
+      - name: Card 1
+        front: "{{Front}}"
+        back: "{{FrontSide}}<hr>{{Back}}"
+decks:
+  - name: Test Deck
+notes:
+  - deck: Test Deck
+    model: Basic
+    tags: [test]
+    fields:
+      Front: Question
+      Back: Answer
+"#;
+
+        let def = DeckDefinition::parse_format(yaml, Format::Yaml).unwrap();
+        assert_eq!(def.notes[0].fields["Front"], "Question");
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("deck.yml");
+        def.write_file(&path).unwrap();
+        let loaded = DeckDefinition::from_file(&path).unwrap();
+        assert_eq!(loaded.notes[0].fields, def.notes[0].fields);
+        assert_eq!(loaded.models[0].templates.len(), 1);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_definition() {
+        let def = DeckDefinition::scaffold(crate::ScaffoldKind::BasicVocab);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("deck.json");
+        def.write_file(&path).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.trim_start().starts_with('{'));
+        // Unset options are left out rather than written as null
+        assert!(!content.contains("null"));
+
+        let loaded = DeckDefinition::from_file(&path).unwrap();
+        assert_eq!(loaded.notes[0].fields, def.notes[0].fields);
+        assert_eq!(loaded.models[0].css, def.models[0].css);
+        assert_eq!(loaded.decks[0].description, def.decks[0].description);
+    }
+
+    #[cfg(not(feature = "json"))]
+    #[test]
+    fn test_json_needs_feature() {
+        assert!(matches!(
+            DeckDefinition::parse_format("{}", Format::Json),
+            Err(Error::UnsupportedFormat(_))
+        ));
+    }
 }
@@ -24,14 +24,18 @@
 //! # }
 //! ```
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use ankit::AnkiClient;
+use ankit::key::KeyNormalization;
 use serde::Serialize;
 
-use crate::diff::{DeckDiff, DeckDiffer, FieldChange, TagChanges};
+use crate::base::{self, Merge, SyncBase};
+use crate::diff::{DeckDiff, DeckDiffer, FieldChange, MatchedNote, ModifiedNote, TagChanges};
 use crate::error::Result;
-use crate::schema::{DeckDefinition, NoteDef};
+use crate::guid;
+use crate::model_version;
+use crate::schema::{DeckDefinition, ModelDef, NoteDef, TemplateDef};
 
 /// Strategy for how to handle sync operations.
 #[derive(Debug, Clone)]
@@ -44,6 +48,14 @@ pub struct SyncStrategy {
     pub push_new_notes: bool,
     /// Sync tag changes.
     pub update_tags: bool,
+    /// Add card templates that exist in TOML but not in the Anki model.
+    pub push_templates: bool,
+    /// Update the templates and CSS of models whose TOML
+    /// [`version`](ModelDef::version) is newer than the one applied in Anki.
+    pub upgrade_models: bool,
+    /// Upgrade models even if their templates or CSS were edited in Anki
+    /// since their version was applied, overwriting the edits.
+    pub force_model_upgrade: bool,
 }
 
 impl Default for SyncStrategy {
@@ -53,6 +65,9 @@ impl Default for SyncStrategy {
             pull_new_notes: false,
             push_new_notes: true,
             update_tags: true,
+            push_templates: true,
+            upgrade_models: true,
+            force_model_upgrade: false,
         }
     }
 }
@@ -65,6 +80,26 @@ impl SyncStrategy {
             pull_new_notes: false,
             push_new_notes: true,
             update_tags: true,
+            push_templates: true,
+            upgrade_models: true,
+            force_model_upgrade: false,
+        }
+    }
+
+    /// Create a bidirectional strategy: push and pull new notes, and skip
+    /// conflicts.
+    ///
+    /// With a [`SyncBase`] from the previous sync, fields changed on only one
+    /// side are merged, so only fields edited on both sides conflict.
+    pub fn bidirectional() -> Self {
+        Self {
+            conflict_resolution: ConflictResolution::Skip,
+            pull_new_notes: true,
+            push_new_notes: true,
+            update_tags: true,
+            push_templates: true,
+            upgrade_models: true,
+            force_model_upgrade: false,
         }
     }
 
@@ -75,6 +110,9 @@ impl SyncStrategy {
             pull_new_notes: true,
             push_new_notes: false,
             update_tags: true,
+            push_templates: false,
+            upgrade_models: false,
+            force_model_upgrade: false,
         }
     }
 }
@@ -101,9 +139,18 @@ pub struct SyncPlan {
     /// Notes that would be pulled from Anki to TOML.
     pub to_pull: Vec<SyncNote>,
     /// Notes that have conflicts (modified in both places).
+    ///
+    /// With a [`SyncBase`], only fields changed on both sides since the last
+    /// sync are listed.
     pub conflicts: Vec<SyncConflict>,
+    /// Notes whose one-sided changes would be merged (requires a [`SyncBase`]).
+    pub to_merge: Vec<MergedNote>,
     /// Number of notes that are identical (no action needed).
     pub unchanged: usize,
+    /// Card templates in TOML that the Anki model doesn't have yet.
+    pub templates_to_add: Vec<SyncTemplate>,
+    /// Models whose TOML version is newer than the one applied in Anki.
+    pub models_to_upgrade: Vec<ModelUpgrade>,
 }
 
 /// A note involved in sync.
@@ -117,6 +164,36 @@ pub struct SyncNote {
     pub deck: String,
     /// First field value (identifier).
     pub first_field: String,
+    /// Stable note identity, if the note has one.
+    pub guid: Option<String>,
+}
+
+/// A card template involved in sync.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SyncTemplate {
+    /// Model name.
+    pub model: String,
+    /// Template name.
+    pub template: String,
+}
+
+/// A model whose TOML [`version`](ModelDef::version) is newer than the one
+/// applied in Anki.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModelUpgrade {
+    /// Model name.
+    pub model: String,
+    /// Version applied in Anki, if one was recorded.
+    pub from_version: Option<u32>,
+    /// Version in TOML.
+    pub to_version: u32,
+    /// Whether the templates or CSS were edited in Anki since the version
+    /// was applied.
+    ///
+    /// Models with no recorded version count as edited, since there is
+    /// nothing to compare them against. Edited models are only upgraded with
+    /// [`SyncStrategy::force_model_upgrade`].
+    pub locally_modified: bool,
 }
 
 /// A conflict where a note differs between TOML and Anki.
@@ -145,10 +222,22 @@ pub struct SyncResult {
     pub resolved_conflicts: Vec<ResolvedConflict>,
     /// Conflicts that were skipped.
     pub skipped_conflicts: Vec<SyncConflict>,
+    /// Notes whose one-sided changes were merged (requires a [`SyncBase`]).
+    pub merged: Vec<MergedNote>,
+    /// Card templates added to existing Anki models.
+    pub templates_added: Vec<SyncTemplate>,
+    /// Models whose templates and CSS were updated to their TOML version.
+    pub models_upgraded: Vec<ModelUpgrade>,
     /// Errors that occurred during sync.
     pub errors: Vec<SyncError>,
     /// Updated TOML definition (if pull_new_notes or conflicts resolved to Anki).
     pub updated_definition: Option<DeckDefinition>,
+    /// Snapshot of the synced notes, to store next to the TOML file and pass
+    /// to the next sync for three-way merge.
+    ///
+    /// Notes with skipped conflicts keep their previous snapshot, so the
+    /// conflict is reported again next time.
+    pub base: Option<SyncBase>,
 }
 
 /// A note that was synced.
@@ -162,6 +251,38 @@ pub struct SyncedNote {
     pub was_new: bool,
 }
 
+/// A note whose changes on each side were merged using the last-synced
+/// snapshot.
+#[derive(Debug, Clone, Serialize)]
+pub struct MergedNote {
+    /// The Anki note ID.
+    pub note_id: i64,
+    /// First field value.
+    pub first_field: String,
+    /// Fields changed only in TOML, pushed to Anki.
+    pub pushed_fields: Vec<String>,
+    /// Fields changed only in Anki, pulled into TOML.
+    pub pulled_fields: Vec<String>,
+    /// Tags added to and removed from Anki.
+    pub pushed_tags: TagChanges,
+    /// Tags added to and removed from TOML.
+    pub pulled_tags: TagChanges,
+}
+
+impl MergedNote {
+    fn new(modified: &ModifiedNote, merge: &Merge) -> Self {
+        let names = |changes: &[FieldChange]| changes.iter().map(|c| c.field.clone()).collect();
+        Self {
+            note_id: modified.note_id,
+            first_field: modified.first_field.clone(),
+            pushed_fields: names(&merge.push_fields),
+            pulled_fields: names(&merge.pull_fields),
+            pushed_tags: merge.push_tags.clone(),
+            pulled_tags: merge.pull_tags.clone(),
+        }
+    }
+}
+
 /// A conflict that was resolved.
 #[derive(Debug, Clone, Serialize)]
 pub struct ResolvedConflict {
@@ -188,20 +309,209 @@ pub struct SyncError {
 pub struct DeckSyncer<'a> {
     client: &'a AnkiClient,
     definition: DeckDefinition,
+    base: Option<SyncBase>,
+    normalization: KeyNormalization,
 }
 
 impl<'a> DeckSyncer<'a> {
     /// Create a new syncer.
     pub fn new(client: &'a AnkiClient, definition: DeckDefinition) -> Self {
-        Self { client, definition }
+        Self {
+            client,
+            definition,
+            base: None,
+            normalization: KeyNormalization::default(),
+        }
+    }
+
+    /// Merge against the snapshot from the previous sync.
+    pub fn with_base(mut self, base: SyncBase) -> Self {
+        self.base = Some(base);
+        self
+    }
+
+    /// Match notes using the given key normalization.
+    pub fn with_normalization(mut self, normalization: KeyNormalization) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    /// A differ for the definition, using this syncer's normalization.
+    fn differ(&self) -> DeckDiffer<'_> {
+        DeckDiffer::new(self.client, &self.definition).normalization(self.normalization.clone())
+    }
+
+    /// Three-way merge a modified note, if it was synced before.
+    fn merge(&self, modified: &ModifiedNote) -> Option<Merge> {
+        let base = self.base.as_ref()?.get(modified.note_id)?;
+        Some(base::merge(modified, base))
     }
 
     /// Plan what sync would do without executing it.
     pub async fn plan(&self) -> Result<SyncPlan> {
-        let differ = DeckDiffer::new(self.client, &self.definition);
-        let diff = differ.diff().await?;
+        let diff = self.differ().diff().await?;
 
-        Ok(self.diff_to_plan(diff))
+        let mut plan = self.diff_to_plan(diff);
+        plan.templates_to_add = self.missing_templates().await?;
+        plan.models_to_upgrade = self.model_upgrades().await?;
+        Ok(plan)
+    }
+
+    /// Card templates defined in TOML that are missing from models that
+    /// already exist in Anki.
+    ///
+    /// Templates are matched by name, so a template renamed in Anki is
+    /// reported as missing.
+    async fn missing_templates(&self) -> Result<Vec<SyncTemplate>> {
+        let existing_models = self.client.models().names().await?;
+        let mut missing = Vec::new();
+        for model in &self.definition.models {
+            if !existing_models.contains(&model.name) {
+                continue;
+            }
+            let existing = self.client.models().templates(&model.name).await?;
+            missing.extend(
+                templates_missing(model, existing.keys().map(String::as_str)).map(|template| {
+                    SyncTemplate {
+                        model: model.name.clone(),
+                        template: template.name.clone(),
+                    }
+                }),
+            );
+        }
+        Ok(missing)
+    }
+
+    /// Add missing card templates to their Anki models.
+    async fn push_templates(&self, result: &mut SyncResult) -> Result<()> {
+        for missing in self.missing_templates().await? {
+            let Some(template) = self
+                .definition
+                .get_model(&missing.model)
+                .and_then(|model| model.templates.iter().find(|t| t.name == missing.template))
+            else {
+                continue;
+            };
+            match self
+                .client
+                .models()
+                .add_template(
+                    &missing.model,
+                    &template.name,
+                    &template.front,
+                    &template.back,
+                )
+                .await
+            {
+                Ok(()) => result.templates_added.push(missing),
+                Err(e) => result.errors.push(SyncError {
+                    description: format!(
+                        "Failed to add template '{}' to model '{}'",
+                        missing.template, missing.model
+                    ),
+                    first_field: None,
+                    error: e.to_string(),
+                }),
+            }
+        }
+        Ok(())
+    }
+
+    /// Models that exist in Anki with an older version than in TOML.
+    async fn model_upgrades(&self) -> Result<Vec<ModelUpgrade>> {
+        let existing_models = self.client.models().names().await?;
+        let mut upgrades = Vec::new();
+        for model in &self.definition.models {
+            if model.version.is_none() || !existing_models.contains(&model.name) {
+                continue;
+            }
+            let templates = self.client.models().templates(&model.name).await?;
+            let styling = self.client.models().styling(&model.name).await?;
+            let templates = templates
+                .iter()
+                .map(|(name, t)| (name.as_str(), t.front.as_str(), t.back.as_str()));
+            upgrades.extend(model_upgrade(model, templates, &styling.css));
+        }
+        Ok(upgrades)
+    }
+
+    /// Update models to their TOML version, unless they were edited in Anki
+    /// and the strategy doesn't force it.
+    async fn upgrade_models(
+        &self,
+        upgrades: Vec<ModelUpgrade>,
+        strategy: &SyncStrategy,
+        result: &mut SyncResult,
+    ) {
+        for upgrade in upgrades {
+            if upgrade.locally_modified && !strategy.force_model_upgrade {
+                let reason = match upgrade.from_version {
+                    Some(version) => format!("it was edited in Anki since version {}", version),
+                    None => "it has no recorded version in Anki".to_string(),
+                };
+                result.errors.push(SyncError {
+                    description: format!(
+                        "Refused to upgrade model '{}' to version {}",
+                        upgrade.model, upgrade.to_version
+                    ),
+                    first_field: None,
+                    error: format!("{}; force the upgrade to overwrite it", reason),
+                });
+                continue;
+            }
+            match self.upgrade_model(&upgrade).await {
+                Ok(()) => result.models_upgraded.push(upgrade),
+                Err(e) => result.errors.push(SyncError {
+                    description: format!(
+                        "Failed to upgrade model '{}' to version {}",
+                        upgrade.model, upgrade.to_version
+                    ),
+                    first_field: None,
+                    error: e.to_string(),
+                }),
+            }
+        }
+    }
+
+    /// Overwrite a model's templates and CSS with its TOML definition, and
+    /// record the new version.
+    ///
+    /// Templates missing from Anki are left to
+    /// [`push_templates`](SyncStrategy::push_templates); templates only in
+    /// Anki are kept.
+    async fn upgrade_model(&self, upgrade: &ModelUpgrade) -> Result<()> {
+        let Some(model) = self.definition.get_model(&upgrade.model) else {
+            return Ok(());
+        };
+        let models = self.client.models();
+        let mut templates = models.templates(&model.name).await?;
+        let updates: HashMap<&str, (&str, &str)> = model
+            .templates
+            .iter()
+            .filter(|t| templates.contains_key(&t.name))
+            .map(|t| (t.name.as_str(), (t.front.as_str(), t.back.as_str())))
+            .collect();
+        if !updates.is_empty() {
+            models.update_templates(&model.name, updates).await?;
+        }
+
+        // The checksum covers the templates as Anki now has them
+        for t in &model.templates {
+            if let Some(template) = templates.get_mut(&t.name) {
+                template.front = t.front.clone();
+                template.back = t.back.clone();
+            }
+        }
+        let css = self.definition.model_css(model)?.unwrap_or_default();
+        let css = model_version::stamp(
+            upgrade.to_version,
+            templates
+                .iter()
+                .map(|(name, t)| (name.as_str(), t.front.as_str(), t.back.as_str())),
+            &css,
+        );
+        models.update_styling(&model.name, &css).await?;
+        Ok(())
     }
 
     /// Convert a diff to a sync plan.
@@ -218,6 +528,7 @@ impl<'a> DeckSyncer<'a> {
                 model: note.model,
                 deck: note.deck,
                 first_field: note.first_field,
+                guid: note.guid,
             });
         }
 
@@ -228,11 +539,25 @@ impl<'a> DeckSyncer<'a> {
                 model: note.model,
                 deck: note.deck,
                 first_field: note.first_field,
+                guid: note.guid,
             });
         }
 
-        // Modified notes -> conflicts
-        for modified in diff.modified {
+        // Modified notes -> merges and conflicts
+        for mut modified in diff.modified {
+            if let Some(merge) = self.merge(&modified) {
+                if merge.has_changes() {
+                    plan.to_merge.push(MergedNote::new(&modified, &merge));
+                } else if merge.conflicts.is_empty() {
+                    // Only differences that were already there last sync
+                    plan.unchanged += 1;
+                }
+                if merge.conflicts.is_empty() {
+                    continue;
+                }
+                modified.field_changes = merge.conflicts;
+                modified.tag_changes = TagChanges::default();
+            }
             plan.conflicts.push(SyncConflict {
                 note_id: modified.note_id,
                 first_field: modified.first_field,
@@ -247,19 +572,52 @@ impl<'a> DeckSyncer<'a> {
 
     /// Execute sync with the given strategy.
     pub async fn sync(mut self, strategy: SyncStrategy) -> Result<SyncResult> {
-        let differ = DeckDiffer::new(self.client, &self.definition);
-        let diff = differ.diff().await?;
+        let mut diff = self.differ().diff().await?;
 
         let mut result = SyncResult::default();
         let mut definition_modified = false;
+        // Notes to snapshot at the end, and notes that keep their old snapshot
+        let mut synced = std::mem::take(&mut diff.matched);
+        let mut keep_base = HashSet::new();
+
+        // Check versions before adding templates, which would otherwise look
+        // like edits made in Anki
+        let upgrades = if strategy.upgrade_models {
+            self.model_upgrades().await?
+        } else {
+            Vec::new()
+        };
+
+        // New templates go first, so pushed notes get cards for them too
+        if strategy.push_templates {
+            self.push_templates(&mut result).await?;
+        }
+        self.upgrade_models(upgrades, &strategy, &mut result).await;
+
+        // Tag notes that were matched by first field with their GUID, so
+        // later syncs can follow them through edits
+        for untagged in &diff.untagged {
+            if let Err(e) = self
+                .client
+                .notes()
+                .add_tags(&[untagged.note_id], &guid::tag(&untagged.guid))
+                .await
+            {
+                result.errors.push(SyncError {
+                    description: format!("Failed to tag note {} with its GUID", untagged.note_id),
+                    first_field: None,
+                    error: e.to_string(),
+                });
+            }
+        }
 
         // Handle notes only in TOML (push to Anki)
         if strategy.push_new_notes {
             for note_diff in &diff.toml_only {
                 match self.push_new_note(note_diff).await {
-                    Ok(note_id) => {
-                        // Update the definition with the assigned note ID
-                        self.update_note_id(&note_diff.first_field, &note_diff.model, note_id);
+                    Ok(pushed) => {
+                        let note_id = pushed.note_id;
+                        synced.push(pushed);
                         definition_modified = true;
 
                         result.pushed.push(SyncedNote {
@@ -285,6 +643,10 @@ impl<'a> DeckSyncer<'a> {
                 if let Some(note_id) = note_diff.note_id {
                     match self.pull_note(note_id, &note_diff.deck).await {
                         Ok(note_def) => {
+                            synced.push(MatchedNote {
+                                index: self.definition.notes.len(),
+                                note_id,
+                            });
                             self.definition.notes.push(note_def);
                             definition_modified = true;
                             result.pulled.push(SyncedNote {
@@ -306,7 +668,37 @@ impl<'a> DeckSyncer<'a> {
         }
 
         // Handle conflicts (modified in both)
-        for modified in diff.modified {
+        for mut modified in diff.modified {
+            // With a snapshot of the last sync, merge what changed on one side
+            if let Some(mut merge) = self.merge(&modified) {
+                if !strategy.update_tags {
+                    merge.push_tags = TagChanges::default();
+                    merge.pull_tags = TagChanges::default();
+                }
+                if merge.has_changes() {
+                    match self.apply_merge(&modified, &merge, &strategy).await {
+                        Ok(()) => {
+                            definition_modified |=
+                                !merge.pull_fields.is_empty() || !merge.pull_tags.is_empty();
+                            result.merged.push(MergedNote::new(&modified, &merge));
+                        }
+                        Err(e) => {
+                            keep_base.insert(modified.note_id);
+                            result.errors.push(SyncError {
+                                description: "Failed to merge note".to_string(),
+                                first_field: Some(modified.first_field.clone()),
+                                error: e.to_string(),
+                            });
+                        }
+                    }
+                }
+                if merge.conflicts.is_empty() {
+                    continue;
+                }
+                modified.field_changes = merge.conflicts;
+                modified.tag_changes = TagChanges::default();
+            }
+
             let conflict = SyncConflict {
                 note_id: modified.note_id,
                 first_field: modified.first_field.clone(),
@@ -323,6 +715,7 @@ impl<'a> DeckSyncer<'a> {
                     )));
                 }
                 ConflictResolution::Skip => {
+                    keep_base.insert(modified.note_id);
                     result.skipped_conflicts.push(conflict);
                 }
                 ConflictResolution::PreferToml => {
@@ -349,6 +742,7 @@ impl<'a> DeckSyncer<'a> {
                                 first_field: Some(conflict.first_field.clone()),
                                 error: e.to_string(),
                             });
+                            keep_base.insert(modified.note_id);
                             result.skipped_conflicts.push(conflict);
                         }
                     }
@@ -356,6 +750,7 @@ impl<'a> DeckSyncer<'a> {
                 ConflictResolution::PreferAnki => {
                     // Update TOML with Anki values
                     self.update_definition_from_anki(
+                        modified.guid.as_deref(),
                         &modified.first_field,
                         &modified.model,
                         &modified.field_changes,
@@ -371,6 +766,31 @@ impl<'a> DeckSyncer<'a> {
             }
         }
 
+        // Snapshot what was synced for the next three-way merge. Notes left
+        // unresolved keep their previous snapshot, or get none.
+        synced.retain(|m| !keep_base.contains(&m.note_id));
+        match SyncBase::capture(self.client, &self.definition, &synced).await {
+            Ok(mut base) => {
+                if let Some(previous) = &self.base {
+                    base.notes.extend(
+                        previous
+                            .notes
+                            .iter()
+                            .filter(|n| keep_base.contains(&n.note_id))
+                            .cloned(),
+                    );
+                }
+                result.base = Some(base);
+            }
+            Err(e) => {
+                result.errors.push(SyncError {
+                    description: "Failed to snapshot synced notes".to_string(),
+                    first_field: None,
+                    error: e.to_string(),
+                });
+            }
+        }
+
         if definition_modified {
             result.updated_definition = Some(self.definition);
         }
@@ -378,30 +798,89 @@ impl<'a> DeckSyncer<'a> {
         Ok(result)
     }
 
-    /// Push a new note from TOML to Anki.
-    async fn push_new_note(&self, note_diff: &crate::diff::NoteDiff) -> Result<i64> {
+    /// Apply the one-sided changes of a three-way merge.
+    async fn apply_merge(
+        &mut self,
+        modified: &ModifiedNote,
+        merge: &Merge,
+        strategy: &SyncStrategy,
+    ) -> Result<()> {
+        if !merge.push_fields.is_empty() {
+            self.push_updates(
+                modified.note_id,
+                &modified.model,
+                &merge.push_fields,
+                strategy,
+            )
+            .await?;
+        }
+        let notes = self.client.notes();
+        if !merge.push_tags.added.is_empty() {
+            notes
+                .add_tags(&[modified.note_id], &merge.push_tags.added.join(" "))
+                .await?;
+        }
+        if !merge.push_tags.removed.is_empty() {
+            notes
+                .remove_tags(&[modified.note_id], &merge.push_tags.removed.join(" "))
+                .await?;
+        }
+
+        let Some(index) = self.find_note(
+            modified.guid.as_deref(),
+            &modified.model,
+            &modified.first_field,
+        ) else {
+            return Ok(());
+        };
+        let note = &mut self.definition.notes[index];
+        for change in &merge.pull_fields {
+            note.fields
+                .insert(change.field.clone(), change.anki_value.clone());
+        }
+        for tag in &merge.pull_tags.added {
+            if !note.tags.contains(tag) {
+                note.tags.push(tag.clone());
+            }
+        }
+        note.tags.retain(|t| !merge.pull_tags.removed.contains(t));
+        Ok(())
+    }
+
+    /// Find a note in the definition by GUID, or by model and first field
+    /// for notes without one.
+    fn find_note(&self, guid: Option<&str>, model_name: &str, first_field: &str) -> Option<usize> {
+        self.definition.notes.iter().position(|n| {
+            if let Some(guid) = guid {
+                return n.guid.as_deref() == Some(guid);
+            }
+            let model = self.definition.get_model(&n.model);
+            if let Some(first_field_name) = model.and_then(|m| m.fields.first()) {
+                let note_first_field = n.fields.get(first_field_name).cloned().unwrap_or_default();
+                return note_first_field == first_field && n.model == model_name;
+            }
+            false
+        })
+    }
+
+    /// Push a new note from TOML to Anki, recording its note ID and GUID in
+    /// the definition.
+    async fn push_new_note(&mut self, note_diff: &crate::diff::NoteDiff) -> Result<MatchedNote> {
         // Find the note in our definition
-        let note_def = self
-            .definition
-            .notes
-            .iter()
-            .find(|n| {
-                let model = self.definition.get_model(&n.model);
-                if let Some(model) = model {
-                    if let Some(first_field_name) = model.fields.first() {
-                        let first_field =
-                            n.fields.get(first_field_name).cloned().unwrap_or_default();
-                        return first_field == note_diff.first_field && n.model == note_diff.model;
-                    }
-                }
-                false
-            })
+        let index = self
+            .find_note(
+                note_diff.guid.as_deref(),
+                &note_diff.model,
+                &note_diff.first_field,
+            )
             .ok_or_else(|| {
                 crate::error::Error::InvalidDefinition(format!(
                     "Note '{}' not found in definition",
                     note_diff.first_field
                 ))
             })?;
+        let note_def = &self.definition.notes[index];
+        let guid = note_def.guid.clone().unwrap_or_else(guid::generate);
 
         // Get markdown fields for this model
         let markdown_fields = self
@@ -414,16 +893,19 @@ impl<'a> DeckSyncer<'a> {
         let fields = note_def.fields_as_html(&markdown_fields);
 
         // Create the note in Anki
-        let note =
-            ankit::NoteBuilder::new(&note_def.deck, &note_def.model).tags(note_def.tags.clone());
-
-        let mut note = note;
+        let mut note = ankit::NoteBuilder::new(&note_def.deck, &note_def.model)
+            .tags(note_def.tags.clone())
+            .tag(guid::tag(&guid));
         for (field, value) in &fields {
             note = note.field(field, value);
         }
 
         let note_id = self.client.notes().add(note.build()).await?;
-        Ok(note_id)
+
+        let note_def = &mut self.definition.notes[index];
+        note_def.note_id = Some(note_id);
+        note_def.guid = Some(guid);
+        Ok(MatchedNote { index, note_id })
     }
 
     /// Pull a note from Anki to TOML definition.
@@ -439,13 +921,30 @@ impl<'a> DeckSyncer<'a> {
             .map(|(name, field)| (name, field.value))
             .collect();
 
+        // Notes pulled without a GUID get one, tagged in Anki so the next
+        // sync matches them
+        let (guid, tags) = guid::split_tags(note_info.tags);
+        let guid = match guid {
+            Some(guid) => guid,
+            None => {
+                let guid = guid::generate();
+                self.client
+                    .notes()
+                    .add_tags(&[note_id], &guid::tag(&guid))
+                    .await?;
+                guid
+            }
+        };
+
         let mut note_def = NoteDef {
             deck: deck.to_string(),
             model: note_info.model_name.clone(),
             fields,
-            tags: note_info.tags,
-            guid: None,
+            tags,
+            guid: Some(guid),
             note_id: Some(note_id),
+            cards: None,
+            suspend: Vec::new(),
         };
 
         // Convert HTML to markdown for markdown fields
@@ -501,70 +1000,76 @@ impl<'a> DeckSyncer<'a> {
         Ok(())
     }
 
-    /// Update the note_id field for a note in the definition.
-    fn update_note_id(&mut self, first_field: &str, model_name: &str, note_id: i64) {
-        for note in &mut self.definition.notes {
-            let model = self.definition.models.iter().find(|m| m.name == note.model);
-            if let Some(model) = model {
-                if let Some(first_field_name) = model.fields.first() {
-                    let note_first_field = note
-                        .fields
-                        .get(first_field_name)
-                        .cloned()
-                        .unwrap_or_default();
-                    if note_first_field == first_field && note.model == model_name {
-                        note.note_id = Some(note_id);
-                        return;
-                    }
-                }
-            }
-        }
-    }
-
     /// Update the TOML definition with Anki values for a note.
     fn update_definition_from_anki(
         &mut self,
+        guid: Option<&str>,
         first_field: &str,
         model_name: &str,
         field_changes: &[FieldChange],
         tag_changes: &TagChanges,
     ) {
         // Find the note in our definition
-        for note in &mut self.definition.notes {
-            let model = self.definition.models.iter().find(|m| m.name == note.model);
-            if let Some(model) = model {
-                if let Some(first_field_name) = model.fields.first() {
-                    let note_first_field = note
-                        .fields
-                        .get(first_field_name)
-                        .cloned()
-                        .unwrap_or_default();
-                    if note_first_field == first_field && note.model == model_name {
-                        // Update fields with Anki values
-                        for fc in field_changes {
-                            note.fields.insert(fc.field.clone(), fc.anki_value.clone());
-                        }
+        let Some(index) = self.find_note(guid, model_name, first_field) else {
+            return;
+        };
+        let note = &mut self.definition.notes[index];
 
-                        // Update tags
-                        for tag in &tag_changes.removed {
-                            // Tags in Anki but not TOML - add to TOML
-                            if !note.tags.contains(tag) {
-                                note.tags.push(tag.clone());
-                            }
-                        }
-                        for tag in &tag_changes.added {
-                            // Tags in TOML but not Anki - remove from TOML
-                            note.tags.retain(|t| t != tag);
-                        }
+        // Update fields with Anki values
+        for fc in field_changes {
+            note.fields.insert(fc.field.clone(), fc.anki_value.clone());
+        }
 
-                        return;
-                    }
-                }
+        // Update tags
+        for tag in &tag_changes.removed {
+            // Tags in Anki but not TOML - add to TOML
+            if !note.tags.contains(tag) {
+                note.tags.push(tag.clone());
             }
         }
+        for tag in &tag_changes.added {
+            // Tags in TOML but not Anki - remove from TOML
+            note.tags.retain(|t| t != tag);
+        }
     }
 }
 
+/// The upgrade a TOML model needs, given the `(name, front, back)` templates
+/// and CSS it has in Anki.
+///
+/// Returns `None` if the model is unversioned or Anki already has its version
+/// or a newer one.
+fn model_upgrade<'a>(
+    model: &ModelDef,
+    templates: impl IntoIterator<Item = (&'a str, &'a str, &'a str)>,
+    css: &str,
+) -> Option<ModelUpgrade> {
+    let to_version = model.version?;
+    let (applied, css) = model_version::split_css(css);
+    if applied.is_some_and(|applied| applied.version >= to_version) {
+        return None;
+    }
+    Some(ModelUpgrade {
+        model: model.name.clone(),
+        from_version: applied.map(|applied| applied.version),
+        to_version,
+        locally_modified: applied
+            .is_none_or(|applied| applied.checksum != model_version::checksum(templates, css)),
+    })
+}
+
+/// Templates of a TOML model whose names are not among `existing`.
+fn templates_missing<'m, 'e>(
+    model: &'m ModelDef,
+    existing: impl IntoIterator<Item = &'e str>,
+) -> impl Iterator<Item = &'m TemplateDef> {
+    let existing: HashSet<&str> = existing.into_iter().collect();
+    model
+        .templates
+        .iter()
+        .filter(move |template| !existing.contains(template.name.as_str()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -593,4 +1098,97 @@ mod tests {
         assert!(strategy.pull_new_notes);
         assert!(!strategy.push_new_notes);
     }
+
+    #[test]
+    fn test_sync_strategy_templates() {
+        assert!(SyncStrategy::default().push_templates);
+        assert!(SyncStrategy::push_only().push_templates);
+        assert!(SyncStrategy::bidirectional().push_templates);
+        assert!(!SyncStrategy::pull_only().push_templates);
+    }
+
+    #[test]
+    fn test_model_upgrade() {
+        let mut model = ModelDef::cloze("Cloze", vec!["Text", "Extra"]);
+        let template = &model.templates[0];
+        let anki = [(
+            template.name.as_str(),
+            template.front.as_str(),
+            template.back.as_str(),
+        )];
+        let css = model_version::stamp(1, anki, ".card {}");
+
+        // Unversioned models are left alone
+        assert_eq!(model_upgrade(&model, anki, &css), None);
+
+        model.version = Some(2);
+        let upgrade = model_upgrade(&model, anki, &css).unwrap();
+        assert_eq!(upgrade.from_version, Some(1));
+        assert_eq!(upgrade.to_version, 2);
+        assert!(!upgrade.locally_modified);
+
+        // Edits in Anki since version 1 was applied
+        let edited = [("Cloze", "{{cloze:Text}}!", "{{cloze:Text}}")];
+        assert!(
+            model_upgrade(&model, edited, &css)
+                .unwrap()
+                .locally_modified
+        );
+        let edited_css = format!("{}\n.extra {{}}", css);
+        assert!(
+            model_upgrade(&model, anki, &edited_css)
+                .unwrap()
+                .locally_modified
+        );
+
+        // Nothing recorded to compare against
+        let upgrade = model_upgrade(&model, anki, ".card {}").unwrap();
+        assert_eq!(upgrade.from_version, None);
+        assert!(upgrade.locally_modified);
+
+        // Anki already has this version or a newer one
+        assert_eq!(
+            model_upgrade(&model, anki, &model_version::stamp(2, anki, "")),
+            None
+        );
+        assert_eq!(
+            model_upgrade(&model, anki, &model_version::stamp(3, anki, "")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_sync_strategy_model_upgrades() {
+        assert!(SyncStrategy::default().upgrade_models);
+        assert!(SyncStrategy::push_only().upgrade_models);
+        assert!(!SyncStrategy::pull_only().upgrade_models);
+        assert!(!SyncStrategy::default().force_model_upgrade);
+    }
+
+    #[test]
+    fn test_templates_missing() {
+        let template = |name: &str| TemplateDef {
+            name: name.to_string(),
+            front: "{{Front}}".to_string(),
+            back: "{{Back}}".to_string(),
+        };
+        let model = ModelDef {
+            name: "Basic".to_string(),
+            fields: vec!["Front".to_string(), "Back".to_string()],
+            templates: vec![template("Card 1"), template("Reverse")],
+            css: None,
+            sort_field: None,
+            id: None,
+            markdown_fields: vec![],
+            model_type: None,
+            version: None,
+            include: vec![],
+        };
+
+        let missing: Vec<&str> = templates_missing(&model, ["Card 1"])
+            .map(|t| t.name.as_str())
+            .collect();
+        assert_eq!(missing, vec!["Reverse"]);
+        assert_eq!(templates_missing(&model, ["Card 1", "Reverse"]).count(), 0);
+    }
 }
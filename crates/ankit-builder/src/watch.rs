@@ -0,0 +1,408 @@
+//! Watch TOML files and sync them to Anki on change.
+//!
+//! [`DeckWatcher`] polls a TOML file (or every `.toml` file in a directory)
+//! for changes to its contents. Once a change has settled for the debounce
+//! period, the changed files are reloaded and synced with [`SyncStrategy`],
+//! and a [`WatchEvent`] describing the outcome is passed to a callback.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::ops::ControlFlow;
+//! use ankit_builder::{DeckBuilder, SyncStrategy, WatchEvent};
+//!
+//! # async fn example() -> ankit_builder::Result<()> {
+//! DeckBuilder::watch("decks/", SyncStrategy::push_only(), |event| {
+//!     match event {
+//!         WatchEvent::Synced { path, result } => {
+//!             println!("{}: pushed {}", path.display(), result.pushed.len());
+//!         }
+//!         WatchEvent::Failed { path, error } => {
+//!             eprintln!("{}: {}", path.display(), error);
+//!         }
+//!     }
+//!     ControlFlow::Continue(())
+//! })
+//! .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use ankit::AnkiClient;
+
+use crate::error::{Error, Result};
+use crate::schema::DeckDefinition;
+use crate::sync::{DeckSyncer, SyncResult, SyncStrategy};
+
+/// Content hash of each watched file.
+type Snapshot = HashMap<PathBuf, u64>;
+
+fn content_hash(contents: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Outcome of syncing one changed file.
+#[derive(Debug)]
+pub enum WatchEvent {
+    /// The file was synced.
+    Synced {
+        /// The file that changed.
+        path: PathBuf,
+        /// What the sync did.
+        result: Box<SyncResult>,
+    },
+    /// The file could not be loaded or synced, or the watched path could
+    /// not be read.
+    Failed {
+        /// The file that changed, or the watched path.
+        path: PathBuf,
+        /// Why loading or syncing failed.
+        error: Error,
+    },
+}
+
+/// Watches TOML deck definitions and syncs them to Anki when they change.
+///
+/// Most users should call [`DeckBuilder::watch()`](crate::DeckBuilder::watch);
+/// use this type directly to customize the client or timing.
+pub struct DeckWatcher {
+    path: PathBuf,
+    strategy: SyncStrategy,
+    client: AnkiClient,
+    poll_interval: Duration,
+    debounce: Duration,
+    sync_on_start: bool,
+}
+
+impl DeckWatcher {
+    /// Create a watcher for a TOML file or a directory of TOML files.
+    pub fn new(path: impl AsRef<Path>, strategy: SyncStrategy) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            strategy,
+            client: AnkiClient::new(),
+            poll_interval: Duration::from_secs(1),
+            debounce: Duration::from_millis(500),
+            sync_on_start: true,
+        }
+    }
+
+    /// Use a custom AnkiConnect client.
+    pub fn client(mut self, client: AnkiClient) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// How often to check files for changes (default: 1 second).
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// How long files must stay unchanged before syncing (default: 500ms).
+    ///
+    /// Editors often write a file several times in quick succession; this
+    /// avoids syncing half-written files.
+    pub fn debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Whether to sync every watched file once at startup (default: true).
+    pub fn sync_on_start(mut self, enabled: bool) -> Self {
+        self.sync_on_start = enabled;
+        self
+    }
+
+    /// Watch until the callback returns [`ControlFlow::Break`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the watched path cannot be read at startup.
+    /// Later failures to read it, such as while an editor replaces a file,
+    /// and failures to load or sync an individual file are reported as
+    /// [`WatchEvent::Failed`] and do not stop the watcher.
+    pub async fn run<F>(self, mut on_event: F) -> Result<()>
+    where
+        F: FnMut(WatchEvent) -> ControlFlow<()>,
+    {
+        let mut known = self.snapshot()?;
+
+        if self.sync_on_start {
+            let mut files: Vec<PathBuf> = known.keys().cloned().collect();
+            files.sort();
+            for file in files {
+                if on_event(self.sync_file(file).await).is_break() {
+                    return Ok(());
+                }
+            }
+        }
+
+        let mut failing = false;
+        'poll: loop {
+            tokio::time::sleep(self.poll_interval).await;
+            let mut current = match self.poll(&mut failing, &mut on_event) {
+                ControlFlow::Continue(Some(current)) => current,
+                ControlFlow::Continue(None) => continue,
+                ControlFlow::Break(()) => return Ok(()),
+            };
+            if current == known {
+                continue;
+            }
+
+            // Wait for writes to settle
+            loop {
+                tokio::time::sleep(self.debounce).await;
+                let next = match self.poll(&mut failing, &mut on_event) {
+                    ControlFlow::Continue(Some(next)) => next,
+                    ControlFlow::Continue(None) => continue 'poll,
+                    ControlFlow::Break(()) => return Ok(()),
+                };
+                if next == current {
+                    break;
+                }
+                current = next;
+            }
+
+            let mut changed: Vec<PathBuf> = current
+                .iter()
+                .filter(|(path, hash)| known.get(*path) != Some(hash))
+                .map(|(path, _)| path.clone())
+                .collect();
+            changed.sort();
+            known = current;
+
+            for file in changed {
+                if on_event(self.sync_file(file).await).is_break() {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Take a snapshot while polling.
+    ///
+    /// A failure is reported as [`WatchEvent::Failed`] for the watched path
+    /// once, until a snapshot succeeds again, and yields None.
+    fn poll<F>(&self, failing: &mut bool, on_event: &mut F) -> ControlFlow<(), Option<Snapshot>>
+    where
+        F: FnMut(WatchEvent) -> ControlFlow<()>,
+    {
+        match self.snapshot() {
+            Ok(snapshot) => {
+                *failing = false;
+                ControlFlow::Continue(Some(snapshot))
+            }
+            Err(_) if *failing => ControlFlow::Continue(None),
+            Err(error) => {
+                *failing = true;
+                on_event(WatchEvent::Failed {
+                    path: self.path.clone(),
+                    error,
+                })?;
+                ControlFlow::Continue(None)
+            }
+        }
+    }
+
+    /// Content hashes of all watched files.
+    ///
+    /// Contents are compared rather than modification times, which may not
+    /// change between two quick writes.
+    fn snapshot(&self) -> Result<Snapshot> {
+        let mut files = HashMap::new();
+
+        if self.path.is_dir() {
+            for entry in std::fs::read_dir(&self.path)? {
+                let path = entry?.path();
+                if path.extension().is_some_and(|ext| ext == "toml") {
+                    match std::fs::read(&path) {
+                        Ok(contents) => {
+                            files.insert(path, content_hash(&contents));
+                        }
+                        // Removed since the directory was listed
+                        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                        Err(e) => return Err(e.into()),
+                    }
+                }
+            }
+        } else {
+            let contents = std::fs::read(&self.path)?;
+            files.insert(self.path.clone(), content_hash(&contents));
+        }
+
+        Ok(files)
+    }
+
+    /// Reload a file and sync it.
+    async fn sync_file(&self, path: PathBuf) -> WatchEvent {
+        let result = match DeckDefinition::from_file(&path) {
+            Ok(definition) => {
+                DeckSyncer::new(&self.client, definition)
+                    .sync(self.strategy.clone())
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(result) => WatchEvent::Synced {
+                path,
+                result: Box::new(result),
+            },
+            Err(error) => WatchEvent::Failed { path, error },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOML: &str = r#"
+[package]
+name = "Watched"
+
+[[decks]]
+name = "Watched"
+"#;
+
+    fn unreachable_client() -> AnkiClient {
+        AnkiClient::builder()
+            .url("http://127.0.0.1:1")
+            .timeout(Duration::from_millis(200))
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_sync_on_start() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("deck.toml"), TOML).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let mut events = Vec::new();
+        DeckWatcher::new(dir.path(), SyncStrategy::push_only())
+            .client(unreachable_client())
+            .run(|event| {
+                events.push(event);
+                ControlFlow::Break(())
+            })
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            &events[0],
+            WatchEvent::Failed { path, .. } if path.ends_with("deck.toml")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_detects_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("deck.toml");
+        std::fs::write(&path, TOML).unwrap();
+
+        let writer = {
+            let path = path.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                std::fs::write(&path, "not valid toml [").unwrap();
+            })
+        };
+
+        let mut events = Vec::new();
+        let watch = DeckWatcher::new(&path, SyncStrategy::push_only())
+            .client(unreachable_client())
+            .poll_interval(Duration::from_millis(20))
+            .debounce(Duration::from_millis(20))
+            .sync_on_start(false)
+            .run(|event| {
+                events.push(event);
+                ControlFlow::Break(())
+            });
+        tokio::time::timeout(Duration::from_secs(5), watch)
+            .await
+            .unwrap()
+            .unwrap();
+        writer.await.unwrap();
+
+        assert!(matches!(
+            &events[0],
+            WatchEvent::Failed {
+                error: Error::TomlParse(_),
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_survives_replaced_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("deck.toml");
+        std::fs::write(&path, TOML).unwrap();
+
+        // Delete and rewrite, as editors do when saving atomically
+        let writer = {
+            let path = path.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                std::fs::remove_file(&path).unwrap();
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                std::fs::write(&path, "not valid toml [").unwrap();
+            })
+        };
+
+        let mut events = Vec::new();
+        let watch = DeckWatcher::new(&path, SyncStrategy::push_only())
+            .client(unreachable_client())
+            .poll_interval(Duration::from_millis(20))
+            .debounce(Duration::from_millis(20))
+            .sync_on_start(false)
+            .run(|event| {
+                let parsed = matches!(
+                    &event,
+                    WatchEvent::Failed {
+                        error: Error::TomlParse(_),
+                        ..
+                    }
+                );
+                events.push(event);
+                if parsed {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            });
+        tokio::time::timeout(Duration::from_secs(5), watch)
+            .await
+            .unwrap()
+            .unwrap();
+        writer.await.unwrap();
+
+        // The missing file is reported once, then the new contents synced
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            &events[0],
+            WatchEvent::Failed {
+                error: Error::Io(_),
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_missing_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let watcher = DeckWatcher::new(dir.path().join("missing.toml"), SyncStrategy::default());
+
+        assert!(watcher.run(|_| ControlFlow::Break(())).await.is_err());
+    }
+}
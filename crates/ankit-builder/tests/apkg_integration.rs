@@ -6,7 +6,7 @@
 use std::collections::HashMap;
 use std::io::Read;
 
-use ankit_builder::{DeckBuilder, DeckDefinition};
+use ankit_builder::{ApkgBuilder, ApkgReader, DeckBuilder, DeckDefinition};
 use rusqlite::Connection;
 use tempfile::tempdir;
 use zip::ZipArchive;
@@ -260,6 +260,108 @@ fn test_apkg_note_tags() {
     assert!(tags[1].is_empty() || tags[1].trim().is_empty());
 }
 
+#[test]
+fn test_apkg_card_selection() {
+    let toml = r#"
+[package]
+name = "Card Selection"
+
+[[models]]
+name = "Reversible"
+fields = ["Front", "Back"]
+
+[[models.templates]]
+name = "Forward"
+front = "{{Front}}"
+back = "{{Back}}"
+
+[[models.templates]]
+name = "Reverse"
+front = "{{Back}}"
+back = "{{Front}}"
+
+[[decks]]
+name = "Test"
+
+[[notes]]
+deck = "Test"
+model = "Reversible"
+cards = ["Forward"]
+
+[notes.fields]
+Front = "Hello"
+Back = "Hola"
+
+[[notes]]
+deck = "Test"
+model = "Reversible"
+suspend = ["Reverse"]
+
+[notes.fields]
+Front = "Goodbye"
+Back = "Adios"
+"#;
+
+    let builder = DeckBuilder::parse(toml).unwrap();
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("test.apkg");
+
+    builder.write_apkg(&path).unwrap();
+
+    let conn = open_apkg_database(&path);
+    let mut stmt = conn
+        .prepare(
+            "SELECT n.sfld, c.ord, c.queue FROM cards c JOIN notes n ON c.nid = n.id
+             ORDER BY n.sfld, c.ord",
+        )
+        .unwrap();
+    let cards: Vec<(String, i64, i64)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .unwrap()
+        .map(|row| row.unwrap())
+        .collect();
+
+    assert_eq!(
+        cards,
+        vec![
+            ("Goodbye".to_string(), 0, 0),
+            ("Goodbye".to_string(), 1, -1),
+            ("Hello".to_string(), 0, 0),
+        ]
+    );
+}
+
+#[test]
+fn test_schema_validation_unknown_card_template() {
+    let toml = r#"
+[package]
+name = "Invalid"
+
+[[models]]
+name = "Basic"
+fields = ["Front", "Back"]
+
+[[models.templates]]
+name = "Card 1"
+front = "{{Front}}"
+back = "{{Back}}"
+
+[[decks]]
+name = "Test"
+
+[[notes]]
+deck = "Test"
+model = "Basic"
+cards = ["Card 2"]
+
+[notes.fields]
+Front = "Q"
+"#;
+
+    let err = DeckDefinition::parse(toml).unwrap_err().to_string();
+    assert!(err.contains("no template named 'Card 2'"));
+}
+
 #[test]
 fn test_apkg_deck_in_col() {
     let builder = DeckBuilder::parse(BASIC_TOML).unwrap();
@@ -288,6 +390,91 @@ fn test_apkg_deck_in_col() {
     assert!(deck_names.contains(&"Test Deck"));
 }
 
+#[test]
+fn test_apkg_deck_options() {
+    let toml = r#"
+[package]
+name = "Options"
+
+[[models]]
+name = "Basic"
+fields = ["Front", "Back"]
+
+[[models.templates]]
+name = "Card 1"
+front = "{{Front}}"
+back = "{{Back}}"
+
+[[decks]]
+name = "Spanish"
+
+[decks.options]
+new_per_day = 5
+reviews_per_day = 50
+learning_steps = [1.0, 10.0, 60.0]
+graduating_interval = 2
+leech_action = "tag"
+
+[[decks]]
+name = "French"
+"#;
+
+    let builder = DeckBuilder::parse(toml).unwrap();
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("test.apkg");
+
+    builder.write_apkg(&path).unwrap();
+
+    let conn = open_apkg_database(&path);
+    let (decks_json, dconf_json): (String, String) = conn
+        .query_row("SELECT decks, dconf FROM col", [], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .unwrap();
+    let decks: serde_json::Value = serde_json::from_str(&decks_json).unwrap();
+    let dconf: serde_json::Value = serde_json::from_str(&dconf_json).unwrap();
+
+    let deck_conf = |name: &str| {
+        decks
+            .as_object()
+            .unwrap()
+            .values()
+            .find(|d| d["name"] == name)
+            .unwrap()["conf"]
+            .as_i64()
+            .unwrap()
+    };
+
+    // Decks without options keep the default group
+    assert_eq!(deck_conf("French"), 1);
+
+    let conf = &dconf[deck_conf("Spanish").to_string()];
+    assert_eq!(conf["name"], "Spanish");
+    assert_eq!(conf["new"]["perDay"], 5);
+    assert_eq!(conf["rev"]["perDay"], 50);
+    assert_eq!(conf["new"]["delays"], serde_json::json!([1.0, 10.0, 60.0]));
+    assert_eq!(conf["new"]["ints"], serde_json::json!([2, 4, 7]));
+    assert_eq!(conf["lapse"]["leechAction"], 1);
+    // Unset values keep the defaults
+    assert_eq!(conf["lapse"]["leechFails"], 8);
+}
+
+#[test]
+fn test_apkg_invalid_leech_action() {
+    let toml = r#"
+[package]
+name = "Options"
+
+[[decks]]
+name = "Spanish"
+
+[decks.options]
+leech_action = "delete"
+"#;
+
+    assert!(DeckDefinition::parse(toml).is_err());
+}
+
 #[test]
 fn test_apkg_model_in_col() {
     let builder = DeckBuilder::parse(BASIC_TOML).unwrap();
@@ -431,6 +618,65 @@ Back = "A"
     );
 }
 
+#[test]
+fn test_apkg_css_includes() {
+    let dir = tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("themes")).unwrap();
+    std::fs::write(
+        dir.path().join("themes/base.css"),
+        ".card { color: black; }\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("themes/night.css"),
+        ".night_mode .card { color: white; }",
+    )
+    .unwrap();
+
+    let toml = r#"
+[package]
+name = "Themed"
+
+[[models]]
+name = "Styled"
+fields = ["Front", "Back"]
+include = ["themes/base.css", "themes/night.css"]
+css = ".back { font-size: 18px; }"
+
+[[models.templates]]
+name = "Card 1"
+front = "{{Front}}"
+back = "{{Back}}"
+
+[[decks]]
+name = "Test"
+"#;
+    let toml_path = dir.path().join("deck.toml");
+    std::fs::write(&toml_path, toml).unwrap();
+
+    // Includes resolve against the TOML file, not the working directory
+    let builder = DeckBuilder::from_file(&toml_path).unwrap();
+    let path = dir.path().join("test.apkg");
+    builder.write_apkg(&path).unwrap();
+
+    let conn = open_apkg_database(&path);
+    let models_json: String = conn
+        .query_row("SELECT models FROM col", [], |row| row.get(0))
+        .unwrap();
+    let models: serde_json::Value = serde_json::from_str(&models_json).unwrap();
+    let model = models.as_object().unwrap().values().next().unwrap();
+
+    assert_eq!(
+        model["css"].as_str().unwrap(),
+        ".card { color: black; }\n\n.night_mode .card { color: white; }\n\n.back { font-size: 18px; }"
+    );
+
+    // A missing include fails the build
+    std::fs::remove_file(dir.path().join("themes/night.css")).unwrap();
+    let builder = DeckBuilder::from_file(&toml_path).unwrap();
+    assert!(builder.write_apkg(dir.path().join("missing.apkg")).is_err());
+}
+
 #[test]
 fn test_apkg_sort_field() {
     let toml = r#"
@@ -532,6 +778,83 @@ fn test_apkg_deterministic_ids() {
     assert_eq!(id1, id2, "Model IDs should be deterministic");
 }
 
+#[test]
+fn test_apkg_reproducible_build() {
+    let dir = tempdir().unwrap();
+    let build = |name: &str, toml: &str| {
+        let path = dir.path().join(name);
+        ApkgBuilder::new(DeckDefinition::parse(toml).unwrap())
+            .stable_ids(true)
+            .timestamp(1_700_000_000)
+            .write_to_file(&path)
+            .unwrap();
+        path
+    };
+
+    let path1 = build("test1.apkg", BASIC_TOML);
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    let path2 = build("test2.apkg", BASIC_TOML);
+    assert_eq!(
+        std::fs::read(&path1).unwrap(),
+        std::fs::read(&path2).unwrap(),
+        "stable builds should be byte-identical"
+    );
+
+    // The first note keeps its ID when another note is edited
+    let edited = build("test3.apkg", &BASIC_TOML.replace("Paris", "Paris!"));
+    let note_ids = |path: &std::path::Path| -> Vec<i64> {
+        let conn = open_apkg_database(path);
+        let mut stmt = conn.prepare("SELECT id FROM notes ORDER BY sfld").unwrap();
+        stmt.query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|id| id.unwrap())
+            .collect()
+    };
+    let (before, after) = (note_ids(&path1), note_ids(&edited));
+    assert_eq!(before[1], after[1]);
+    assert_ne!(before[0], after[0]);
+}
+
+#[test]
+fn test_apkg_compare() {
+    let dir = tempdir().unwrap();
+    let build = |name: &str, toml: &str| {
+        let path = dir.path().join(name);
+        DeckBuilder::parse(toml).unwrap().write_apkg(&path).unwrap();
+        ApkgReader::open(&path).unwrap()
+    };
+
+    let original = build("original.apkg", BASIC_TOML);
+    let rebuilt = build("rebuilt.apkg", BASIC_TOML);
+    let diff = original.compare(&rebuilt).unwrap();
+    assert!(diff.is_empty(), "{:?}", diff);
+    assert_eq!(diff.notes_unchanged, 2);
+
+    let edited = BASIC_TOML
+        .replace("\"Paris\"", "\"Paris, France\"")
+        .replace("tags = [\"test\", \"example\"]", "tags = [\"test\"]");
+    let edited = build("edited.apkg", &edited);
+    let diff = original.compare(&edited).unwrap();
+    assert_eq!(diff.notes_changed.len(), 2);
+    let paris = diff
+        .notes_changed
+        .iter()
+        .find(|change| change.note.first_field == "Capital of France?")
+        .unwrap();
+    assert_eq!(paris.fields, vec!["Back"]);
+    assert!(!paris.tags_changed);
+    assert!(diff.notes_added.is_empty() && diff.notes_removed.is_empty());
+
+    let renamed = build(
+        "renamed.apkg",
+        &BASIC_TOML.replace("Test Deck", "Other Deck"),
+    );
+    let diff = original.compare(&renamed).unwrap();
+    assert_eq!(diff.decks_added, vec!["Other Deck"]);
+    assert_eq!(diff.decks_removed, vec!["Test Deck"]);
+    assert!(diff.notes_changed.iter().all(|change| change.deck_changed));
+}
+
 #[test]
 fn test_apkg_cards_reference_correct_notes() {
     let builder = DeckBuilder::parse(BASIC_TOML).unwrap();
@@ -660,3 +983,74 @@ InvalidField = "X"
     let err = result.unwrap_err().to_string();
     assert!(err.contains("field") && err.contains("not found"));
 }
+
+#[test]
+fn test_apkg_image_occlusion() {
+    let dir = tempdir().unwrap();
+    std::fs::write(dir.path().join("heart.png"), b"fake png").unwrap();
+
+    let toml = r#"
+[package]
+name = "Anatomy"
+
+[[decks]]
+name = "Anatomy"
+
+[[media]]
+name = "heart.png"
+path = "heart.png"
+
+[[occlusions]]
+deck = "Anatomy"
+image = "heart.png"
+width = 400
+height = 300
+tags = ["cardio"]
+
+[[occlusions.masks]]
+left = 10
+top = 10
+width = 50
+height = 20
+label = "Aorta"
+
+[[occlusions.masks]]
+left = 100
+top = 100
+width = 50
+height = 20
+label = "Ventricle"
+"#;
+
+    let builder = DeckBuilder::parse(toml)
+        .unwrap()
+        .media_base_path(dir.path());
+    let path = dir.path().join("test.apkg");
+    builder.write_apkg(&path).unwrap();
+
+    let conn = open_apkg_database(&path);
+    let note_count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM notes", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(note_count, 2);
+
+    // The image plus one original mask and a question/answer mask per note
+    let manifest = get_media_manifest(&path);
+    assert_eq!(manifest.len(), 6);
+    let svg_index = manifest
+        .iter()
+        .find(|(_, name)| name.ends_with("-1-q.svg"))
+        .map(|(index, _)| index.clone())
+        .unwrap();
+
+    let file = std::fs::File::open(&path).unwrap();
+    let mut archive = ZipArchive::new(file).unwrap();
+    let mut svg = String::new();
+    archive
+        .by_name(&svg_index)
+        .unwrap()
+        .read_to_string(&mut svg)
+        .unwrap();
+    assert!(svg.starts_with("<svg"));
+    assert_eq!(svg.matches("<rect").count(), 2);
+}
@@ -0,0 +1,297 @@
+//! Integration tests for AnkiConnect import and export.
+//!
+//! These tests run the importer and exporter against a mock AnkiConnect
+//! server and check the requests they send and the definitions they build.
+
+use ankit::testing::MockAnkiServer;
+use ankit_builder::{ConnectImporter, DeckDefinition, DeckExporter, NoteOrder};
+use serde_json::json;
+
+const REVERSIBLE: &str = r#"
+[package]
+name = "Card Selection"
+
+[[models]]
+name = "Reversible"
+fields = ["Front", "Back"]
+
+[[models.templates]]
+name = "Forward"
+front = "{{Front}}"
+back = "{{Back}}"
+
+[[models.templates]]
+name = "Reverse"
+front = "{{Back}}"
+back = "{{Front}}"
+
+[[decks]]
+name = "Test"
+
+[[notes]]
+deck = "Test"
+model = "Reversible"
+cards = ["Forward"]
+
+[notes.fields]
+Front = "Hello"
+Back = "Hola"
+
+[[notes]]
+deck = "Test"
+model = "Reversible"
+
+[notes.fields]
+Front = "Goodbye"
+Back = "Adios"
+"#;
+
+/// A server that knows the `Reversible` model and gives note `n` the
+/// cards `n * 10` (Forward) and `n * 10 + 1` (Reverse).
+async fn reversible_server() -> MockAnkiServer {
+    let server = MockAnkiServer::start().await;
+    server
+        .respond("modelNames", json!(["Reversible"]))
+        .respond("suspend", json!(true));
+
+    let next_note = std::sync::atomic::AtomicI64::new(1);
+    server.handle("addNote", move |_| {
+        Ok(json!(
+            next_note.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+        ))
+    });
+    server.handle("findCards", |params| {
+        let query = params["query"].as_str().unwrap_or_default();
+        let note_id: i64 = query.trim_start_matches("nid:").parse().unwrap_or(0);
+        Ok(json!([note_id * 10, note_id * 10 + 1]))
+    });
+    server.handle("cardsInfo", |params| {
+        let cards: Vec<_> = params["cards"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|id| id.as_i64())
+            .map(|id| json!({ "cardId": id, "note": id / 10, "ord": id % 10 }))
+            .collect();
+        Ok(json!(cards))
+    });
+    server
+}
+
+#[tokio::test]
+async fn test_import_suspends_unselected_cards() {
+    let server = reversible_server().await;
+    let definition = DeckDefinition::parse(REVERSIBLE).unwrap();
+
+    let result = ConnectImporter::with_client(definition, server.client())
+        .import()
+        .await
+        .unwrap();
+
+    assert_eq!(result.notes_created, 2);
+    assert_eq!(result.cards_suspended, 1);
+
+    // Only the first note selects cards, so only its cards are looked up
+    let find = server.requests_for("findCards");
+    assert_eq!(find.len(), 1);
+    assert_eq!(find[0]["params"]["query"], "nid:1");
+
+    let suspend = server.requests_for("suspend");
+    assert_eq!(suspend.len(), 1);
+    assert_eq!(suspend[0]["params"]["cards"], json!([11]));
+}
+
+/// A server with one deck of three `Reversible` notes, returned out of order.
+async fn export_server() -> MockAnkiServer {
+    let server = MockAnkiServer::start().await;
+    let note = |id: i64, front: &str, guid: &str| {
+        json!({
+            "noteId": id,
+            "modelName": "Reversible",
+            "tags": [format!("ankit-guid::{}", guid)],
+            "fields": {
+                "Back": { "value": format!("back {}", id), "order": 1 },
+                "Front": { "value": front, "order": 0 }
+            },
+            "cards": []
+        })
+    };
+    server
+        .respond("findNotes", json!([3, 1, 2]))
+        .respond(
+            "notesInfo",
+            json!([note(3, "apple", "b"), note(1, "cherry", "c"), note(2, "banana", "a")]),
+        )
+        .respond("modelFieldNames", json!(["Front", "Back"]))
+        .respond(
+            "modelTemplates",
+            json!({
+                "Reverse": { "Front": "{{Back}}", "Back": "{{Front}}" },
+                "Forward": { "Front": "{{Front}}", "Back": "{{Back}}" }
+            }),
+        )
+        .respond("modelStyling", json!({ "css": "" }))
+        .respond(
+            "findModelsByName",
+            json!([{ "name": "Reversible", "tmpls": [{ "name": "Forward" }, { "name": "Reverse" }] }]),
+        );
+    server
+}
+
+#[tokio::test]
+async fn test_export_sorts_notes() {
+    let server = export_server().await;
+    let client = server.client();
+
+    let first_fields = |definition: &DeckDefinition| -> Vec<String> {
+        definition
+            .notes
+            .iter()
+            .map(|note| note.fields["Front"].clone())
+            .collect()
+    };
+
+    let definition = DeckExporter::new(&client)
+        .export_deck("Test")
+        .await
+        .unwrap();
+    assert_eq!(first_fields(&definition), ["cherry", "banana", "apple"]);
+
+    let definition = DeckExporter::new(&client)
+        .order(NoteOrder::FirstField)
+        .export_deck("Test")
+        .await
+        .unwrap();
+    assert_eq!(first_fields(&definition), ["apple", "banana", "cherry"]);
+
+    let definition = DeckExporter::new(&client)
+        .order(NoteOrder::Guid)
+        .export_deck("Test")
+        .await
+        .unwrap();
+    assert_eq!(first_fields(&definition), ["banana", "apple", "cherry"]);
+}
+
+#[tokio::test]
+async fn test_export_toml_is_stable() {
+    let server = export_server().await;
+    let client = server.client();
+
+    let definition = DeckExporter::new(&client)
+        .export_deck("Test")
+        .await
+        .unwrap();
+    let templates: Vec<_> = definition.models[0]
+        .templates
+        .iter()
+        .map(|t| t.name.as_str())
+        .collect();
+    assert_eq!(templates, ["Forward", "Reverse"]);
+
+    let toml = definition.to_toml().unwrap();
+    let front = toml.find("Front = \"cherry\"").unwrap();
+    let back = toml.find("Back = \"back 1\"").unwrap();
+    assert!(front < back, "fields should follow model order:\n{}", toml);
+
+    for _ in 0..5 {
+        let again = DeckExporter::new(&client)
+            .export_deck("Test")
+            .await
+            .unwrap();
+        assert_eq!(again.to_toml().unwrap(), toml);
+    }
+}
+
+/// Options group JSON as returned by `getDeckConfig`.
+fn deck_config(id: i64, name: &str, new_per_day: i64) -> serde_json::Value {
+    json!({
+        "id": id,
+        "name": name,
+        "new": { "delays": [1.0, 10.0], "ints": [1, 4, 0], "perDay": new_per_day },
+        "rev": { "perDay": 200, "maxIvl": 36500 },
+        "lapse": { "delays": [10.0], "leechFails": 8, "leechAction": 1, "minInt": 1 }
+    })
+}
+
+#[tokio::test]
+async fn test_export_deck_options() {
+    let server = export_server().await;
+    server.respond("getDeckConfig", deck_config(5, "Languages", 15));
+    let client = server.client();
+
+    let definition = DeckExporter::new(&client)
+        .export_deck("Test")
+        .await
+        .unwrap();
+    assert!(definition.decks[0].options.is_none());
+
+    let definition = DeckExporter::new(&client)
+        .deck_options(true)
+        .export_deck("Test")
+        .await
+        .unwrap();
+    let options = definition.decks[0].options.as_ref().unwrap();
+    assert_eq!(options.group.as_deref(), Some("Languages"));
+    assert_eq!(options.new_per_day, Some(15));
+    assert_eq!(options.learning_steps, Some(vec![1.0, 10.0]));
+    assert_eq!(options.graduating_interval, Some(1));
+    assert_eq!(options.leech_action.as_deref(), Some("tag"));
+
+    // The exported options survive a TOML round trip
+    let reparsed = DeckDefinition::parse(&definition.to_toml().unwrap()).unwrap();
+    assert_eq!(reparsed.decks[0].options.as_ref(), Some(options));
+}
+
+#[tokio::test]
+async fn test_import_reuses_named_options_group() {
+    let server = MockAnkiServer::start().await;
+    server.with_decks(["French", "Spanish"]);
+    let moved = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    server.handle("getDeckConfig", {
+        let moved = moved.clone();
+        move |params| {
+            // French already uses Languages; Spanish moves to it on import
+            let is_moved = moved.load(std::sync::atomic::Ordering::SeqCst);
+            Ok(match params["deck"].as_str() {
+                Some("French") => deck_config(5, "Languages", 15),
+                _ if is_moved => deck_config(5, "Languages", 15),
+                _ => deck_config(1, "Default", 20),
+            })
+        }
+    });
+    server.handle("setDeckConfigId", move |_| {
+        moved.store(true, std::sync::atomic::Ordering::SeqCst);
+        Ok(json!(true))
+    });
+    server
+        .respond("saveDeckConfig", json!(true))
+        .respond("modelNames", json!([]));
+
+    let definition = DeckDefinition::parse(
+        r#"
+[package]
+name = "Spanish"
+
+[[decks]]
+name = "Spanish"
+
+[decks.options]
+group = "Languages"
+new_per_day = 10
+"#,
+    )
+    .unwrap();
+    let result = ConnectImporter::with_client(definition, server.client())
+        .import()
+        .await
+        .unwrap();
+
+    assert_eq!(result.deck_options_applied, 1);
+    assert!(server.requests_for("cloneDeckConfigId").is_empty());
+    let assign = server.requests_for("setDeckConfigId");
+    assert_eq!(assign[0]["params"]["decks"], json!(["Spanish"]));
+    assert_eq!(assign[0]["params"]["configId"], 5);
+    let saved = server.requests_for("saveDeckConfig");
+    assert_eq!(saved[0]["params"]["config"]["id"], 5);
+    assert_eq!(saved[0]["params"]["config"]["new"]["perDay"], 10);
+}
@@ -0,0 +1,121 @@
+//! `ankit analyze` - study statistics and card health.
+
+use ankit_engine::analyze::{ProblemCriteria, ProblemReason};
+use clap::Subcommand;
+
+use crate::output::truncate;
+use crate::{BoxError, Context};
+
+#[derive(Subcommand, Debug)]
+pub enum AnalyzeCommand {
+    /// Reviews and study time per day
+    StudySummary {
+        /// Deck name ("*" for all decks)
+        deck: String,
+        /// Number of days to include
+        #[arg(long, default_value_t = 7)]
+        days: u32,
+    },
+    /// Retention rate, ease, and intervals of a deck's review cards
+    Retention {
+        /// Deck name
+        deck: String,
+    },
+    /// Cards with many lapses, low ease, or poor retention
+    Problems {
+        /// Anki search query
+        #[arg(default_value = "deck:*")]
+        query: String,
+        /// Minimum lapses to flag a card
+        #[arg(long, default_value_t = 5)]
+        min_lapses: i64,
+    },
+}
+
+pub async fn run(command: AnalyzeCommand, context: &Context) -> Result<(), BoxError> {
+    let analyze = context.engine.analyze();
+    let output = &context.output;
+
+    match command {
+        AnalyzeCommand::StudySummary { deck, days } => {
+            let summary = analyze.study_summary(&deck, days).await?;
+            if output.is_json() {
+                return output.json(&summary);
+            }
+            output.fields(&[
+                ("Reviews", summary.total_reviews.to_string()),
+                ("Unique cards", summary.unique_cards.to_string()),
+                ("Study time", format_duration(summary.total_time_seconds)),
+                ("Reviews/day", format!("{:.1}", summary.avg_reviews_per_day)),
+            ]);
+            println!();
+            let rows: Vec<Vec<String>> = summary
+                .daily
+                .iter()
+                .map(|day| {
+                    vec![
+                        day.date.clone(),
+                        day.reviews.to_string(),
+                        format_duration(day.time_seconds),
+                    ]
+                })
+                .collect();
+            output.table(&["Date", "Reviews", "Time"], &rows);
+        }
+        AnalyzeCommand::Retention { deck } => {
+            let stats = analyze.retention_stats(&deck).await?;
+            if output.is_json() {
+                return output.json(&stats);
+            }
+            output.fields(&[
+                ("Review cards", stats.total_cards.to_string()),
+                ("Reviews", stats.total_reviews.to_string()),
+                ("Lapses", stats.total_lapses.to_string()),
+                ("Retention", format!("{:.1}%", stats.retention_rate * 100.0)),
+                ("Average ease", format!("{}%", stats.avg_ease / 10)),
+                ("Average interval", format!("{} days", stats.avg_interval)),
+            ]);
+        }
+        AnalyzeCommand::Problems { query, min_lapses } => {
+            let criteria = ProblemCriteria {
+                min_lapses,
+                ..Default::default()
+            };
+            let problems = analyze.find_problems(&query, criteria).await?;
+            if output.is_json() {
+                return output.json(&problems);
+            }
+            let rows: Vec<Vec<String>> = problems
+                .iter()
+                .map(|card| {
+                    vec![
+                        card.card_id.to_string(),
+                        card.deck_name.clone(),
+                        truncate(&card.front, 40),
+                        describe_reason(&card.reason),
+                    ]
+                })
+                .collect();
+            output.table(&["Card", "Deck", "Front", "Reason"], &rows);
+        }
+    }
+    Ok(())
+}
+
+fn describe_reason(reason: &ProblemReason) -> String {
+    match reason {
+        ProblemReason::HighLapseCount(lapses) => format!("{} lapses", lapses),
+        ProblemReason::LowEase(ease) => format!("ease {}%", ease / 10),
+        ProblemReason::PoorRetention { reps, interval } => {
+            format!("{} reviews, {} day interval", reps, interval)
+        }
+    }
+}
+
+fn format_duration(seconds: u64) -> String {
+    if seconds >= 3600 {
+        format!("{}h {}m", seconds / 3600, seconds % 3600 / 60)
+    } else {
+        format!("{}m {}s", seconds / 60, seconds % 60)
+    }
+}
@@ -0,0 +1,303 @@
+//! `ankit builder` - build, plan, and sync TOML deck definitions.
+
+use std::path::{Path, PathBuf};
+
+use ankit_builder::{
+    DeckBuilder, ReleaseBuilder, ScaffoldKind, SyncBase, SyncPlan, SyncStrategy, VersionBump,
+};
+use clap::{Subcommand, ValueEnum};
+
+use crate::{BoxError, Context};
+
+#[derive(Subcommand, Debug)]
+pub enum BuilderCommand {
+    /// Start a new TOML definition from a starter deck
+    New {
+        /// Kind of deck to start from
+        #[arg(value_enum)]
+        kind: Scaffold,
+        /// Path of the TOML file to write (must not exist)
+        #[arg(short, long, default_value = "deck.toml")]
+        output: PathBuf,
+    },
+    /// Write a deck definition to an .apkg file
+    Build {
+        /// Deck definition (TOML, YAML, or JSON)
+        file: PathBuf,
+        /// Path of the .apkg file to write
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Show what a sync would push, pull, or flag as conflicting
+    Plan {
+        /// Deck definition (TOML, YAML, or JSON)
+        file: PathBuf,
+    },
+    /// Sync a deck definition with Anki
+    Sync {
+        /// Deck definition (TOML, YAML, or JSON)
+        file: PathBuf,
+        /// Which way notes flow
+        #[arg(long, value_enum, default_value_t = Direction::Push)]
+        direction: Direction,
+        /// Where to write the updated definition after pulling (defaults to FILE)
+        #[arg(long)]
+        write: Option<PathBuf>,
+        /// Upgrade versioned models even if their templates or CSS were
+        /// edited in Anki, overwriting the edits
+        #[arg(long)]
+        force_model_upgrade: bool,
+    },
+    /// Write a versioned .apkg and changelog for distributing a deck
+    Release {
+        /// Deck definition (TOML, YAML, or JSON)
+        file: PathBuf,
+        /// Bump package.version before releasing (omit for a first release)
+        #[arg(long, value_enum)]
+        bump: Option<Bump>,
+        /// Directory for packages, snapshots, and the changelog
+        /// (defaults to releases/ next to FILE)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum Direction {
+    /// TOML to Anki; TOML wins conflicts
+    Push,
+    /// Anki to TOML; Anki wins conflicts
+    Pull,
+    /// Both ways; fields changed on one side since the last sync are
+    /// merged, and fields changed on both are skipped as conflicts
+    Both,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum Scaffold {
+    /// Word and meaning, carded both ways
+    BasicVocab,
+    /// Sentences with cloze deletions
+    ClozeSentences,
+    /// An image on the front, its answer on the back
+    ImageBased,
+    /// Words with pronunciation audio and a listening card
+    LanguageAudio,
+}
+
+impl From<Scaffold> for ScaffoldKind {
+    fn from(scaffold: Scaffold) -> Self {
+        match scaffold {
+            Scaffold::BasicVocab => ScaffoldKind::BasicVocab,
+            Scaffold::ClozeSentences => ScaffoldKind::ClozeSentences,
+            Scaffold::ImageBased => ScaffoldKind::ImageBased,
+            Scaffold::LanguageAudio => ScaffoldKind::LanguageAudio,
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum Bump {
+    /// 1.2.3 to 2.0.0
+    Major,
+    /// 1.2.3 to 1.3.0
+    Minor,
+    /// 1.2.3 to 1.2.4
+    Patch,
+}
+
+impl From<Bump> for VersionBump {
+    fn from(bump: Bump) -> Self {
+        match bump {
+            Bump::Major => VersionBump::Major,
+            Bump::Minor => VersionBump::Minor,
+            Bump::Patch => VersionBump::Patch,
+        }
+    }
+}
+
+impl Direction {
+    fn strategy(self) -> SyncStrategy {
+        match self {
+            Direction::Push => SyncStrategy::push_only(),
+            Direction::Pull => SyncStrategy::pull_only(),
+            Direction::Both => SyncStrategy::bidirectional(),
+        }
+    }
+}
+
+pub async fn run(command: BuilderCommand, context: &Context) -> Result<(), BoxError> {
+    let client = context.engine.client();
+    let output = &context.output;
+
+    match command {
+        BuilderCommand::New { kind, output: path } => {
+            ScaffoldKind::from(kind).write(&path)?;
+            println!("Wrote {}", path.display());
+        }
+        BuilderCommand::Build { file, output: path } => {
+            DeckBuilder::from_file(&file)?.write_apkg(&path)?;
+            println!("Wrote {}", path.display());
+        }
+        BuilderCommand::Release {
+            file,
+            bump,
+            output: dir,
+        } => {
+            let mut release = ReleaseBuilder::new(&file);
+            if let Some(bump) = bump {
+                release = release.bump(bump.into());
+            }
+            if let Some(dir) = dir {
+                release = release.output_dir(dir);
+            }
+            // The changelog is the preview
+            if context.engine.options().dry_run {
+                let changelog = release.plan()?;
+                if output.is_json() {
+                    return output.json(&changelog);
+                }
+                print!("{}", changelog.to_markdown());
+                return Ok(());
+            }
+
+            let release = release.release()?;
+            if output.is_json() {
+                return output.json(&release);
+            }
+            print!("{}", release.changelog.to_markdown());
+            println!();
+            println!("Wrote {}", release.apkg_path.display());
+            println!("Updated {}", release.changelog_path.display());
+        }
+        BuilderCommand::Plan { file } => {
+            let plan = load(&file)?.plan_sync_with_client(client).await?;
+            print_plan(&plan, context)?;
+        }
+        BuilderCommand::Sync {
+            file,
+            direction,
+            write,
+            force_model_upgrade,
+        } => {
+            let builder = load(&file)?;
+            // Sync has no dry run of its own; the plan is the preview
+            if context.engine.options().dry_run {
+                let plan = builder.plan_sync_with_client(client).await?;
+                return print_plan(&plan, context);
+            }
+
+            let strategy = SyncStrategy {
+                force_model_upgrade,
+                ..direction.strategy()
+            };
+            let result = builder.sync_with_client(client, strategy).await?;
+            // The snapshot goes next to whichever file now holds the definition
+            let mut synced_file = &file;
+            if let Some(definition) = &result.updated_definition {
+                synced_file = write.as_ref().unwrap_or(&file);
+                definition.write_file(synced_file)?;
+            }
+            if let Some(base) = &result.base {
+                base.write(SyncBase::path_for(synced_file))?;
+            }
+            if output.is_json() {
+                return output.json(&result);
+            }
+
+            output.fields(&[
+                ("Pushed", result.pushed.len().to_string()),
+                ("Pulled", result.pulled.len().to_string()),
+                ("Merged", result.merged.len().to_string()),
+                ("Templates added", result.templates_added.len().to_string()),
+                ("Models upgraded", result.models_upgraded.len().to_string()),
+                (
+                    "Resolved conflicts",
+                    result.resolved_conflicts.len().to_string(),
+                ),
+                (
+                    "Skipped conflicts",
+                    result.skipped_conflicts.len().to_string(),
+                ),
+                ("Errors", result.errors.len().to_string()),
+            ]);
+            for error in &result.errors {
+                eprintln!("error: {}: {}", error.description, error.error);
+            }
+            if result.updated_definition.is_some() {
+                println!("Updated {}", write.as_ref().unwrap_or(&file).display());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Load a definition along with the snapshot from its last sync, if any.
+fn load(file: &Path) -> Result<DeckBuilder, BoxError> {
+    let builder = DeckBuilder::from_file(file)?;
+    let base_path = SyncBase::path_for(file);
+    if base_path.exists() {
+        return Ok(builder.sync_base(SyncBase::from_file(&base_path)?));
+    }
+    Ok(builder)
+}
+
+fn print_plan(plan: &SyncPlan, context: &Context) -> Result<(), BoxError> {
+    let output = &context.output;
+    if output.is_json() {
+        return output.json(plan);
+    }
+
+    let mut rows = Vec::new();
+    for (action, notes) in [("push", &plan.to_push), ("pull", &plan.to_pull)] {
+        for note in notes {
+            rows.push(vec![
+                action.to_string(),
+                note.deck.clone(),
+                note.model.clone(),
+                note.first_field.clone(),
+            ]);
+        }
+    }
+    for merge in &plan.to_merge {
+        rows.push(vec![
+            "merge".to_string(),
+            String::new(),
+            String::new(),
+            merge.first_field.clone(),
+        ]);
+    }
+    for template in &plan.templates_to_add {
+        rows.push(vec![
+            "add template".to_string(),
+            String::new(),
+            template.model.clone(),
+            template.template.clone(),
+        ]);
+    }
+    for upgrade in &plan.models_to_upgrade {
+        let action = if upgrade.locally_modified {
+            "upgrade model (edited)"
+        } else {
+            "upgrade model"
+        };
+        rows.push(vec![
+            action.to_string(),
+            String::new(),
+            upgrade.model.clone(),
+            format!("version {}", upgrade.to_version),
+        ]);
+    }
+    for conflict in &plan.conflicts {
+        rows.push(vec![
+            "conflict".to_string(),
+            String::new(),
+            conflict.model.clone(),
+            conflict.first_field.clone(),
+        ]);
+    }
+    output.table(&["Action", "Deck", "Model", "First field"], &rows);
+    println!();
+    println!("{} notes unchanged", plan.unchanged);
+    Ok(())
+}
@@ -0,0 +1,150 @@
+//! `ankit dedupe` - find, merge, and remove duplicate notes.
+
+use ankit::key::KeyNormalization;
+use ankit_engine::deduplicate::{
+    DedupeQuery, DedupeReport, FuzzyOptions, KeepStrategy, MatchMode, MergeStrategy,
+};
+use clap::{Args, Subcommand, ValueEnum};
+
+use crate::output::truncate;
+use crate::{BoxError, Context};
+
+#[derive(Subcommand, Debug)]
+pub enum DedupeCommand {
+    /// Show duplicate groups without changing anything
+    Preview(DedupeArgs),
+    /// Delete duplicates, keeping one note per group
+    Remove {
+        #[command(flatten)]
+        args: DedupeArgs,
+        /// Merge tags and empty fields from duplicates into the kept note first
+        #[arg(long, default_value_t = false)]
+        merge: bool,
+        /// Append differing values from duplicates to this field of the kept note
+        #[arg(long)]
+        also_field: Option<String>,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct DedupeArgs {
+    /// Anki search query selecting the notes to compare
+    query: String,
+    /// Field whose value identifies duplicates
+    #[arg(long, default_value = "Front")]
+    key_field: String,
+    /// Which note of each group to keep
+    #[arg(long, value_enum, default_value_t = Keep::First)]
+    keep: Keep,
+    /// Group near-identical keys with at least this similarity (0.0-1.0)
+    #[arg(long)]
+    fuzzy: Option<f64>,
+    /// How key values are normalized before comparing
+    #[arg(long, value_enum, default_value_t = Normalize::Default)]
+    normalize: Normalize,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum Keep {
+    First,
+    Last,
+    MostContent,
+    MostTags,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum Normalize {
+    /// Ignore HTML, extra whitespace, and case
+    Default,
+    /// Also fold full-width and half-width forms (NFKC)
+    Cjk,
+    /// Also ignore accents and punctuation
+    Accents,
+    /// Compare values exactly as stored
+    None,
+}
+
+impl DedupeArgs {
+    fn query(self) -> DedupeQuery {
+        DedupeQuery {
+            search: self.query,
+            key_field: self.key_field,
+            keep: match self.keep {
+                Keep::First => KeepStrategy::First,
+                Keep::Last => KeepStrategy::Last,
+                Keep::MostContent => KeepStrategy::MostContent,
+                Keep::MostTags => KeepStrategy::MostTags,
+            },
+            match_mode: match self.fuzzy {
+                Some(threshold) => MatchMode::Fuzzy(FuzzyOptions::with_threshold(threshold)),
+                None => MatchMode::Exact,
+            },
+            normalization: match self.normalize {
+                Normalize::Default => KeyNormalization::default(),
+                Normalize::Cjk => KeyNormalization::cjk(),
+                Normalize::Accents => KeyNormalization::accent_insensitive(),
+                Normalize::None => KeyNormalization::none(),
+            },
+        }
+    }
+}
+
+pub async fn run(command: DedupeCommand, context: &Context) -> Result<(), BoxError> {
+    let dedupe = context.engine.deduplicate();
+
+    let (report, remove) = match command {
+        DedupeCommand::Preview(args) => (dedupe.preview(&args.query()).await?, false),
+        DedupeCommand::Remove {
+            args,
+            merge,
+            also_field,
+        } => {
+            let strategy = MergeStrategy {
+                union_tags: merge,
+                fill_empty: merge,
+                also_field,
+            };
+            let report = dedupe
+                .remove_duplicates_with(&args.query(), &strategy)
+                .await?;
+            (report, true)
+        }
+    };
+
+    print_report(&report, remove, context)
+}
+
+fn print_report(report: &DedupeReport, remove: bool, context: &Context) -> Result<(), BoxError> {
+    let output = &context.output;
+    if output.is_json() {
+        return output.json(report);
+    }
+
+    let rows: Vec<Vec<String>> = report
+        .details
+        .iter()
+        .map(|group| {
+            let duplicates: Vec<String> = group
+                .duplicate_note_ids
+                .iter()
+                .map(|id| id.to_string())
+                .collect();
+            vec![
+                truncate(&group.key_value, 40),
+                group.keep_note_id.to_string(),
+                duplicates.join(", "),
+            ]
+        })
+        .collect();
+    output.table(&["Key", "Keep", "Duplicates"], &rows);
+    println!();
+
+    if remove {
+        return output.report(report);
+    }
+    println!(
+        "{} groups: would delete {} notes, kept {}",
+        report.groups_found, report.deleted, report.kept
+    );
+    Ok(())
+}
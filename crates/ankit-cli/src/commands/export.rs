@@ -0,0 +1,202 @@
+//! `ankit export` - export decks and review history as JSON, JSON Lines, or CSV.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+use ankit_engine::export::{ExportCursor, ExportedCard, ExportedNote, write_reviews_csv};
+use clap::{Subcommand, ValueEnum};
+use serde::Serialize;
+
+use crate::{BoxError, Context};
+
+#[derive(Subcommand, Debug)]
+pub enum ExportCommand {
+    /// Export a deck's notes and cards
+    Deck {
+        /// Deck name
+        deck: String,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+        /// File to write (defaults to stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Cursor file for incremental exports: only notes and cards changed
+        /// since the last run are exported, and the cursor is updated
+        #[arg(long)]
+        cursor: Option<PathBuf>,
+    },
+    /// Export review history for cards matching a query
+    Reviews {
+        /// Anki search query
+        query: String,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+        /// File to write (defaults to stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Export review history grouped by note, with the note's deck, first
+    /// field, and tags
+    NoteReviews {
+        /// Anki search query
+        query: String,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = NoteReviewsFormat::Json)]
+        format: NoteReviewsFormat,
+        /// File to write (defaults to stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum Format {
+    /// A single JSON document
+    Json,
+    /// One JSON object per line: a note with its cards, or a card's reviews
+    Jsonl,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum NoteReviewsFormat {
+    /// A single JSON document
+    Json,
+    /// One JSON object per line: a note with its reviews
+    Jsonl,
+    /// One row per review, with the note's content repeated on each row
+    Csv,
+}
+
+/// A JSON Lines record: one note and its cards.
+#[derive(Serialize)]
+struct NoteRecord<'a> {
+    #[serde(flatten)]
+    note: &'a ExportedNote,
+    cards: Vec<&'a ExportedCard>,
+}
+
+pub async fn run(command: ExportCommand, context: &Context) -> Result<(), BoxError> {
+    let export = context.engine.export();
+
+    match command {
+        ExportCommand::Deck {
+            deck,
+            format,
+            output,
+            cursor,
+        } => {
+            let json = matches!(format, Format::Json);
+            // Fetch everything before touching the output file or cursor
+            let (document, notes, cards, next_cursor) = match &cursor {
+                Some(path) => {
+                    let result = if path.exists() {
+                        let saved = ExportCursor::load(path)?;
+                        if saved.deck_name != deck {
+                            return Err(format!(
+                                "cursor {} belongs to deck '{}'",
+                                path.display(),
+                                saved.deck_name
+                            )
+                            .into());
+                        }
+                        export.resume(&saved).await?
+                    } else {
+                        export.deck_incremental(&deck, 0).await?
+                    };
+                    let document = json
+                        .then(|| serde_json::to_vec_pretty(&result))
+                        .transpose()?;
+                    (document, result.notes, result.cards, Some(result.cursor))
+                }
+                None => {
+                    let result = export.deck(&deck).await?;
+                    let document = json
+                        .then(|| serde_json::to_vec_pretty(&result))
+                        .transpose()?;
+                    (document, result.notes, result.cards, None)
+                }
+            };
+
+            let mut writer = open(output.as_ref())?;
+            match document {
+                Some(document) => {
+                    writer.write_all(&document)?;
+                    writeln!(writer)?;
+                }
+                None => {
+                    for note in &notes {
+                        let record = NoteRecord {
+                            note,
+                            cards: cards.iter().filter(|c| c.note_id == note.note_id).collect(),
+                        };
+                        serde_json::to_writer(&mut writer, &record)?;
+                        writeln!(writer)?;
+                    }
+                }
+            }
+            writer.flush()?;
+
+            if let (Some(path), Some(next)) = (&cursor, next_cursor) {
+                next.save(path)?;
+            }
+            if output.is_some() {
+                eprintln!("Exported {} notes and {} cards", notes.len(), cards.len());
+            }
+        }
+        ExportCommand::Reviews {
+            query,
+            format,
+            output,
+        } => {
+            let history = export.reviews(&query).await?;
+            let mut writer = open(output.as_ref())?;
+            match format {
+                Format::Json => write_json(&mut writer, &history)?,
+                Format::Jsonl => {
+                    for card in &history {
+                        serde_json::to_writer(&mut writer, card)?;
+                        writeln!(writer)?;
+                    }
+                }
+            }
+            writer.flush()?;
+        }
+        ExportCommand::NoteReviews {
+            query,
+            format,
+            output,
+        } => {
+            let history = export.reviews_joined(&query).await?;
+            let mut writer = open(output.as_ref())?;
+            match format {
+                NoteReviewsFormat::Json => write_json(&mut writer, &history)?,
+                NoteReviewsFormat::Jsonl => {
+                    for note in &history {
+                        serde_json::to_writer(&mut writer, note)?;
+                        writeln!(writer)?;
+                    }
+                }
+                NoteReviewsFormat::Csv => write_reviews_csv(&history, &mut writer)?,
+            }
+            writer.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Open the output file, or stdout if none was given.
+fn open(path: Option<&PathBuf>) -> Result<Box<dyn Write>, BoxError> {
+    Ok(match path {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(std::io::stdout().lock())),
+    })
+}
+
+fn write_json<T: Serialize>(writer: &mut dyn Write, value: &T) -> Result<(), BoxError> {
+    serde_json::to_writer_pretty(&mut *writer, value)?;
+    writeln!(writer)?;
+    Ok(())
+}
@@ -0,0 +1,6 @@
+//! Subcommand implementations, one module per command group.
+
+pub mod analyze;
+pub mod builder;
+pub mod dedupe;
+pub mod export;
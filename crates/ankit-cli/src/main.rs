@@ -0,0 +1,123 @@
+//! Command-line interface for Anki workflows via AnkiConnect.
+//!
+//! Exposes ankit-engine workflows and the TOML deck builder as subcommands,
+//! printing tables for people and JSON (with `--json`) for scripts.
+
+mod commands;
+mod output;
+
+use std::path::PathBuf;
+
+use ankit_engine::{ClientBuilder, Engine, EngineOptions};
+use clap::{Parser, Subcommand};
+
+use crate::commands::{analyze, builder, dedupe, export};
+use crate::output::Output;
+
+/// Error type returned by commands.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+// ============================================================================
+// CLI Arguments
+// ============================================================================
+
+/// Anki workflows from the command line.
+#[derive(Parser, Debug)]
+#[command(name = "ankit")]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// AnkiConnect host address [default: 127.0.0.1, or from ANKICONNECT_URL]
+    #[arg(long, global = true)]
+    host: Option<String>,
+
+    /// AnkiConnect port [default: 8765, or from ANKICONNECT_URL]
+    #[arg(long, global = true)]
+    port: Option<u16>,
+
+    /// Print JSON instead of tables
+    #[arg(long, global = true, default_value_t = false)]
+    json: bool,
+
+    /// Report what mutating commands would change without changing anything
+    #[arg(long, global = true, default_value_t = false)]
+    dry_run: bool,
+
+    /// Directory for undo journals written by destructive commands
+    #[arg(long, global = true)]
+    journal_dir: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Study statistics and card health
+    #[command(subcommand)]
+    Analyze(analyze::AnalyzeCommand),
+    /// Find, merge, and remove duplicate notes
+    #[command(subcommand)]
+    Dedupe(dedupe::DedupeCommand),
+    /// Build, plan, and sync TOML deck definitions
+    #[command(subcommand)]
+    Builder(builder::BuilderCommand),
+    /// Export decks and review history
+    #[command(subcommand)]
+    Export(export::ExportCommand),
+}
+
+/// Everything a command needs to talk to Anki and print results.
+pub struct Context {
+    /// Engine configured from the global arguments.
+    pub engine: Engine,
+    /// How results are printed.
+    pub output: Output,
+}
+
+// ============================================================================
+// Main
+// ============================================================================
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    // Settings come from ANKICONNECT_* variables and the config file, with
+    // --host and --port taking precedence
+    let mut builder = match ClientBuilder::from_env() {
+        Ok(builder) => builder,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if args.host.is_some() || args.port.is_some() {
+        builder = builder.url(format!(
+            "http://{}:{}",
+            args.host.as_deref().unwrap_or("127.0.0.1"),
+            args.port.unwrap_or(8765)
+        ));
+    }
+    let client = builder.build();
+    let engine = Engine::from_client(client).with_options(EngineOptions {
+        dry_run: args.dry_run,
+        journal_dir: args.journal_dir,
+        ..Default::default()
+    });
+    let context = Context {
+        engine,
+        output: Output::new(args.json),
+    };
+
+    let result = match args.command {
+        Command::Analyze(command) => analyze::run(command, &context).await,
+        Command::Dedupe(command) => dedupe::run(command, &context).await,
+        Command::Builder(command) => builder::run(command, &context).await,
+        Command::Export(command) => export::run(command, &context).await,
+    };
+
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,94 @@
+//! Printing command results as tables or JSON.
+
+use std::io::Write;
+
+use ankit_engine::report::WorkflowReport;
+use serde::Serialize;
+
+use crate::BoxError;
+
+/// How command results are printed.
+#[derive(Debug, Clone, Copy)]
+pub struct Output {
+    json: bool,
+}
+
+impl Output {
+    /// Print JSON if `json` is set, tables otherwise.
+    pub fn new(json: bool) -> Self {
+        Self { json }
+    }
+
+    /// Whether results are printed as JSON.
+    pub fn is_json(&self) -> bool {
+        self.json
+    }
+
+    /// Print a value as pretty JSON.
+    pub fn json<T: Serialize>(&self, value: &T) -> Result<(), BoxError> {
+        let mut stdout = std::io::stdout().lock();
+        serde_json::to_writer_pretty(&mut stdout, value)?;
+        writeln!(stdout)?;
+        Ok(())
+    }
+
+    /// Print a workflow report as JSON, or as its human-readable rendering.
+    pub fn report(&self, report: &dyn WorkflowReport) -> Result<(), BoxError> {
+        if self.json {
+            return self.json(&report.to_json()?);
+        }
+        println!("{}", report.render());
+        Ok(())
+    }
+
+    /// Print rows under a header, with each column padded to its widest cell.
+    pub fn table(&self, headers: &[&str], rows: &[Vec<String>]) {
+        let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+        for row in rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let print_row = |cells: Vec<&str>| {
+            let padded: Vec<String> = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect();
+            println!("{}", padded.join("  ").trim_end());
+        };
+
+        print_row(headers.to_vec());
+        let rule: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+        print_row(rule.iter().map(String::as_str).collect());
+        for row in rows {
+            print_row(row.iter().map(String::as_str).collect());
+        }
+    }
+
+    /// Print labelled values, one per line.
+    pub fn fields(&self, fields: &[(&str, String)]) {
+        let width = fields
+            .iter()
+            .map(|(k, _)| k.chars().count())
+            .max()
+            .unwrap_or(0);
+        for (label, value) in fields {
+            println!("{:<width$}  {}", label, value, width = width);
+        }
+    }
+}
+
+/// Shorten text to at most `max` characters for a table cell, flattening
+/// newlines.
+pub fn truncate(text: &str, max: usize) -> String {
+    let flat = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if flat.chars().count() <= max {
+        flat
+    } else {
+        let mut short: String = flat.chars().take(max.saturating_sub(1)).collect();
+        short.push('…');
+        short
+    }
+}
@@ -1,12 +1,23 @@
 //! Study statistics and problem card detection.
 //!
 //! This module provides analytics workflows for understanding study
-//! patterns and identifying cards that need attention.
-
-use std::collections::HashMap;
-
-use crate::Result;
-use ankit::AnkiClient;
+//! patterns and identifying cards that need attention, and
+//! [`AnalyzeEngine::remediate_leeches`] for acting on the problem cards found.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::batch::{Batcher, CardsInfo, Ease, FindCards, FindNotes, NotesInfo};
+use crate::changes::PlannedChange;
+use crate::journal::{self, Journal};
+use crate::leech;
+use crate::normalize::{NormalizeOptions, normalize};
+use crate::report::{AffectedIds, WorkflowReport, report_fields};
+use crate::similarity::{Algorithm, Prefilter, candidates_between, score};
+use crate::{EngineOptions, Error, Result};
+use ankit::key::KeyNormalization;
+use ankit::{AnkiClient, CardInfo, ReviewEntry, TagTree};
 use serde::Serialize;
 
 /// Summary of study activity.
@@ -24,6 +35,31 @@ pub struct StudySummary {
     pub daily: Vec<DailyStats>,
 }
 
+impl WorkflowReport for StudySummary {
+    fn summary(&self) -> String {
+        let mut summary = format!(
+            "{} reviews over {} days ({:.1} per day)",
+            self.total_reviews,
+            self.daily.len(),
+            self.avg_reviews_per_day
+        );
+        // Unique cards aren't counted across all decks
+        if self.unique_cards > 0 {
+            summary.push_str(&format!(", {} unique cards", self.unique_cards));
+        }
+        summary
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.daily
+            .iter()
+            .map(|day| format!("{}: {} reviews", day.date, day.reviews))
+            .collect()
+    }
+
+    report_fields!();
+}
+
 /// Study statistics for a single day.
 #[derive(Debug, Clone, Default, Serialize)]
 pub struct DailyStats {
@@ -93,15 +129,182 @@ impl Default for ProblemCriteria {
     }
 }
 
+/// Lowest ease factor Anki allows (130%).
+const MIN_EASE: i64 = 1300;
+
+/// An action to take on a leech.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum LeechAction {
+    /// Suspend the card.
+    Suspend,
+    /// Reset the card to new.
+    Reset,
+    /// Move the card to a deck, creating it if needed.
+    MoveToDeck {
+        /// Destination deck, e.g. "Leeches".
+        deck: String,
+    },
+    /// Tag the card's note.
+    Tag {
+        /// Tag to add.
+        tag: String,
+    },
+    /// Lower the card's ease factor, never below 130%.
+    ReduceEase {
+        /// Amount to subtract (e.g., 200 = 20 percentage points).
+        by: i64,
+    },
+    /// Give the card a new due date.
+    Reschedule {
+        /// Due date specification, as accepted by `setDueDate` (e.g., "0", "1-7").
+        days: String,
+    },
+}
+
+impl fmt::Display for LeechAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LeechAction::Suspend => write!(f, "suspend"),
+            LeechAction::Reset => write!(f, "reset"),
+            LeechAction::MoveToDeck { deck } => write!(f, "move to '{}'", deck),
+            LeechAction::Tag { tag } => write!(f, "tag '{}'", tag),
+            LeechAction::ReduceEase { by } => write!(f, "reduce ease by {}", by),
+            LeechAction::Reschedule { days } => write!(f, "reschedule to '{}'", days),
+        }
+    }
+}
+
+/// Decides what happens to each leech found by
+/// [`AnalyzeEngine::remediate_leeches`].
+///
+/// Cards are matched with [`criteria`](Self::criteria), and the actions come
+/// from the list for the [`ProblemReason`] they were flagged with.
+#[derive(Debug, Clone)]
+pub struct LeechPolicy {
+    /// Criteria for identifying leeches.
+    pub criteria: ProblemCriteria,
+    /// Actions for cards flagged with [`ProblemReason::HighLapseCount`].
+    pub high_lapses: Vec<LeechAction>,
+    /// Actions for cards flagged with [`ProblemReason::LowEase`].
+    pub low_ease: Vec<LeechAction>,
+    /// Actions for cards flagged with [`ProblemReason::PoorRetention`].
+    pub poor_retention: Vec<LeechAction>,
+}
+
+impl Default for LeechPolicy {
+    /// Tag every leech `leech`, and also suspend cards with many lapses.
+    fn default() -> Self {
+        let tag = LeechAction::Tag {
+            tag: "leech".to_string(),
+        };
+        Self {
+            criteria: ProblemCriteria::default(),
+            high_lapses: vec![tag.clone(), LeechAction::Suspend],
+            low_ease: vec![tag.clone()],
+            poor_retention: vec![tag],
+        }
+    }
+}
+
+impl LeechPolicy {
+    /// A policy that takes the same actions for every leech.
+    pub fn all(criteria: ProblemCriteria, actions: Vec<LeechAction>) -> Self {
+        Self {
+            criteria,
+            high_lapses: actions.clone(),
+            low_ease: actions.clone(),
+            poor_retention: actions,
+        }
+    }
+
+    /// The actions for a card flagged for `reason`.
+    pub fn actions_for(&self, reason: &ProblemReason) -> &[LeechAction] {
+        match reason {
+            ProblemReason::HighLapseCount(_) => &self.high_lapses,
+            ProblemReason::LowEase(_) => &self.low_ease,
+            ProblemReason::PoorRetention { .. } => &self.poor_retention,
+        }
+    }
+}
+
+/// What was done to a single leech.
+#[derive(Debug, Clone, Serialize)]
+pub struct LeechOutcome {
+    /// The card ID.
+    pub card_id: i64,
+    /// The note ID.
+    pub note_id: i64,
+    /// Front field content (first field).
+    pub front: String,
+    /// Why the card was flagged.
+    pub reason: ProblemReason,
+    /// Actions taken (or planned, in a dry run).
+    pub actions: Vec<LeechAction>,
+}
+
+/// Report from leech remediation.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LeechReport {
+    /// Per-card actions, one entry per leech found.
+    pub cards: Vec<LeechOutcome>,
+    /// Undo journal recorded before changing cards, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for LeechReport {
+    fn summary(&self) -> String {
+        let acted = self.cards.iter().filter(|c| !c.actions.is_empty()).count();
+        format!(
+            "Found {} leeches, took action on {}",
+            self.cards.len(),
+            acted
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.cards
+            .iter()
+            .filter(|card| !card.actions.is_empty())
+            .map(|card| {
+                let actions: Vec<String> = card.actions.iter().map(|a| a.to_string()).collect();
+                format!("card {}: {}", card.card_id, actions.join(", "))
+            })
+            .collect()
+    }
+
+    fn affected(&self) -> AffectedIds {
+        let mut ids = AffectedIds::from_planned(&self.planned);
+        for card in self.cards.iter().filter(|c| !c.actions.is_empty()) {
+            ids.card_ids.push(card.card_id);
+            if card
+                .actions
+                .iter()
+                .any(|a| matches!(a, LeechAction::Tag { .. }))
+            {
+                ids.note_ids.push(card.note_id);
+            }
+        }
+        ids.normalize()
+    }
+
+    report_fields!(dry_run, journal);
+}
+
 /// Analysis workflow engine.
 #[derive(Debug)]
 pub struct AnalyzeEngine<'a> {
     client: &'a AnkiClient,
+    options: &'a EngineOptions,
 }
 
 impl<'a> AnalyzeEngine<'a> {
-    pub(crate) fn new(client: &'a AnkiClient) -> Self {
-        Self { client }
+    pub(crate) fn new(client: &'a AnkiClient, options: &'a EngineOptions) -> Self {
+        Self { client, options }
     }
 
     /// Get a summary of study activity.
@@ -189,7 +392,7 @@ impl<'a> AnalyzeEngine<'a> {
         }
 
         let cards = self.client.cards().info(&card_ids).await?;
-        let mut problems = Vec::new();
+        let mut flagged = Vec::new();
 
         for card in cards {
             let reason = if card.lapses >= criteria.min_lapses {
@@ -206,33 +409,260 @@ impl<'a> AnalyzeEngine<'a> {
             } else {
                 None
             };
-
             if let Some(reason) = reason {
-                // Get the note to get the front field
-                let note_info = self.client.notes().info(&[card.note_id]).await?;
-                let front = note_info
-                    .first()
-                    .and_then(|n| n.fields.values().next())
-                    .map(|f| f.value.clone())
-                    .unwrap_or_default();
-
-                problems.push(ProblemCard {
-                    card_id: card.card_id,
-                    note_id: card.note_id,
-                    lapses: card.lapses,
-                    reps: card.reps,
-                    ease: card.ease_factor,
-                    interval: card.interval,
-                    deck_name: card.deck_name.clone(),
-                    front,
-                    reason,
-                });
+                flagged.push((card, reason));
             }
         }
 
+        // Get the notes to get the front field, in one request when batching
+        let batch = Batcher::new(self.client, self.options);
+        let mut notes = HashMap::new();
+        if batch.enabled() {
+            let note_ids: Vec<i64> = flagged
+                .iter()
+                .map(|(card, _)| card.note_id)
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect();
+            let found = batch.run(&[NotesInfo(&note_ids)]).await?;
+            notes.extend(found.into_iter().flatten().map(|n| (n.note_id, n)));
+        }
+
+        let mut problems = Vec::with_capacity(flagged.len());
+        for (card, reason) in flagged {
+            let note_info = match notes.get(&card.note_id) {
+                Some(note) => vec![note.clone()],
+                None if batch.enabled() => Vec::new(),
+                None => self.client.notes().info(&[card.note_id]).await?,
+            };
+            let front = note_info
+                .first()
+                .and_then(|n| n.fields.values().next())
+                .map(|f| f.value.clone())
+                .unwrap_or_default();
+
+            problems.push(ProblemCard {
+                card_id: card.card_id,
+                note_id: card.note_id,
+                lapses: card.lapses,
+                reps: card.reps,
+                ease: card.ease_factor,
+                interval: card.interval,
+                deck_name: card.deck_name.clone(),
+                front,
+                reason,
+            });
+        }
+
         Ok(problems)
     }
 
+    /// Find leeches and act on them according to a policy.
+    ///
+    /// Cards are found with [`find_problems`](Self::find_problems) using the
+    /// policy's criteria. Each card gets the actions the policy lists for the
+    /// reason it was flagged. Actions run in a fixed order regardless of how
+    /// they are listed: reset, reduce ease, reschedule, move, tag, and
+    /// suspend last, so a reset doesn't undo a suspension.
+    ///
+    /// Respects [`EngineOptions::dry_run`] and records an undo journal of the
+    /// affected scheduling, decks, and tags when
+    /// [`EngineOptions::journal_dir`] is set.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # use ankit_engine::analyze::{LeechAction, LeechPolicy, ProblemCriteria};
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    ///
+    /// let mut policy = LeechPolicy::default();
+    /// policy.high_lapses = vec![
+    ///     LeechAction::MoveToDeck { deck: "Leeches".to_string() },
+    ///     LeechAction::Reset,
+    /// ];
+    ///
+    /// let report = engine.analyze()
+    ///     .remediate_leeches("deck:Japanese", &policy)
+    ///     .await?;
+    /// for card in &report.cards {
+    ///     println!("{}: {:?}", card.front, card.actions);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn remediate_leeches(
+        &self,
+        query: &str,
+        policy: &LeechPolicy,
+    ) -> Result<LeechReport> {
+        let problems = self.find_problems(query, policy.criteria.clone()).await?;
+
+        let mut report = LeechReport {
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        let mut reset = Vec::new();
+        let mut ease_cards = Vec::new();
+        let mut ease_factors = Vec::new();
+        let mut reschedule: BTreeMap<&str, Vec<i64>> = BTreeMap::new();
+        let mut moves: BTreeMap<&str, Vec<i64>> = BTreeMap::new();
+        let mut tags: BTreeMap<&str, Vec<i64>> = BTreeMap::new();
+        let mut suspend = Vec::new();
+
+        for problem in &problems {
+            let actions = policy.actions_for(&problem.reason);
+            for action in actions {
+                match action {
+                    LeechAction::Suspend => suspend.push(problem.card_id),
+                    LeechAction::Reset => reset.push(problem.card_id),
+                    LeechAction::MoveToDeck { deck } => {
+                        moves.entry(deck).or_default().push(problem.card_id)
+                    }
+                    LeechAction::Tag { tag } => tags.entry(tag).or_default().push(problem.note_id),
+                    LeechAction::ReduceEase { by } => {
+                        // New cards have no ease to reduce
+                        if problem.ease > 0 {
+                            ease_cards.push(problem.card_id);
+                            ease_factors.push((problem.ease - by).max(MIN_EASE));
+                        }
+                    }
+                    LeechAction::Reschedule { days } => {
+                        reschedule.entry(days).or_default().push(problem.card_id)
+                    }
+                }
+            }
+        }
+
+        for list in tags.values_mut() {
+            list.sort_unstable();
+            list.dedup();
+        }
+
+        report.cards = problems
+            .iter()
+            .map(|problem| LeechOutcome {
+                card_id: problem.card_id,
+                note_id: problem.note_id,
+                front: problem.front.clone(),
+                reason: problem.reason.clone(),
+                actions: policy.actions_for(&problem.reason).to_vec(),
+            })
+            .collect();
+
+        if report.dry_run {
+            if !reset.is_empty() {
+                report
+                    .planned
+                    .push(PlannedChange::ForgetCards { card_ids: reset });
+            }
+            if !ease_cards.is_empty() {
+                report.planned.push(PlannedChange::SetEase {
+                    card_ids: ease_cards,
+                    ease_factors,
+                });
+            }
+            for (days, card_ids) in reschedule {
+                report.planned.push(PlannedChange::SetDueDate {
+                    card_ids,
+                    days: days.to_string(),
+                });
+            }
+            for (deck, card_ids) in moves {
+                report.planned.push(PlannedChange::CreateDeck {
+                    deck: deck.to_string(),
+                });
+                report.planned.push(PlannedChange::MoveCards {
+                    card_ids,
+                    deck: deck.to_string(),
+                });
+            }
+            for (tag, note_ids) in tags {
+                report.planned.push(PlannedChange::AddTags {
+                    note_ids,
+                    tags: tag.to_string(),
+                });
+            }
+            if !suspend.is_empty() {
+                report
+                    .planned
+                    .push(PlannedChange::SuspendCards { card_ids: suspend });
+            }
+            return Ok(report);
+        }
+
+        if let Some(dir) = &self.options.journal_dir {
+            let mut scheduled: Vec<i64> = reset
+                .iter()
+                .chain(&ease_cards)
+                .chain(reschedule.values().flatten())
+                .chain(&suspend)
+                .copied()
+                .collect();
+            scheduled.sort_unstable();
+            scheduled.dedup();
+            let moved: Vec<i64> = moves.values().flatten().copied().collect();
+            let mut tagged: Vec<i64> = tags.values().flatten().copied().collect();
+            tagged.sort_unstable();
+            tagged.dedup();
+
+            let mut record = Journal::new("remediate_leeches");
+            if !scheduled.is_empty() {
+                record
+                    .entries
+                    .extend(journal::record_scheduling(self.client, &scheduled).await?);
+            }
+            if !moved.is_empty() {
+                record
+                    .entries
+                    .extend(journal::record_decks(self.client, &moved).await?);
+            }
+            if !tagged.is_empty() {
+                record
+                    .entries
+                    .extend(journal::record_tags(self.client, &tagged).await?);
+            }
+            if !record.entries.is_empty() {
+                report.journal = Some(record.write(dir)?);
+            }
+        }
+
+        let cards = self.client.cards();
+        let batch = Batcher::new(self.client, self.options);
+        for chunk in batch.mutation_chunks(&reset) {
+            cards.forget(chunk).await?;
+        }
+        for (ids, factors) in batch
+            .mutation_chunks(&ease_cards)
+            .zip(batch.mutation_chunks(&ease_factors))
+        {
+            cards.set_ease(ids, factors).await?;
+        }
+        for (days, card_ids) in &reschedule {
+            for chunk in batch.mutation_chunks(card_ids) {
+                cards.set_due_date(chunk, *days).await?;
+            }
+        }
+        for (deck, card_ids) in &moves {
+            self.client.decks().create(deck).await?;
+            for chunk in batch.mutation_chunks(card_ids) {
+                self.client.decks().move_cards(chunk, deck).await?;
+            }
+        }
+        for (tag, note_ids) in &tags {
+            for chunk in batch.mutation_chunks(note_ids) {
+                self.client.notes().add_tags(chunk, tag).await?;
+            }
+        }
+        for chunk in batch.mutation_chunks(&suspend) {
+            cards.suspend(chunk).await?;
+        }
+
+        Ok(report)
+    }
+
     /// Get retention statistics for a deck.
     ///
     /// # Arguments
@@ -258,8 +688,8 @@ impl<'a> AnalyzeEngine<'a> {
             return Ok(RetentionStats::default());
         }
 
-        let cards = self.client.cards().info(&card_ids).await?;
-        let ease_factors = self.client.cards().get_ease(&card_ids).await?;
+        let batch = Batcher::new(self.client, self.options);
+        let (cards, ease_factors) = batch.pair(CardsInfo(&card_ids), Ease(&card_ids)).await?;
 
         let total_lapses: i64 = cards.iter().map(|c| c.lapses).sum();
         let total_reps: i64 = cards.iter().map(|c| c.reps).sum();
@@ -288,6 +718,45 @@ impl<'a> AnalyzeEngine<'a> {
         })
     }
 
+    /// Break down a deck's cards by maturity.
+    ///
+    /// Cards are counted as new, learning (including relearning), young
+    /// (interval under 21 days), mature (21 to 89 days), or very mature (90
+    /// days or more), matching Anki's 21-day mature cutoff. The report also
+    /// holds a histogram of review card intervals in fixed bins, ready to
+    /// plot. [`deck_audit`](Self::deck_audit) includes the same breakdown.
+    ///
+    /// # Arguments
+    ///
+    /// * `deck` - Deck to analyze
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let maturity = engine.analyze().maturity("Japanese").await?;
+    /// println!("{} young, {} mature", maturity.young, maturity.mature);
+    /// for bin in &maturity.histogram {
+    ///     println!("{:>8}: {}", bin.label, bin.cards);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn maturity(&self, deck: &str) -> Result<Maturity> {
+        let card_ids = self
+            .client
+            .cards()
+            .find(&format!("deck:\"{}\"", deck))
+            .await?;
+        if card_ids.is_empty() {
+            return Ok(Maturity::from_cards(&[]));
+        }
+        let cards = self.client.cards().info(&card_ids).await?;
+        Ok(Maturity::from_cards(&cards))
+    }
+
     /// Perform a comprehensive audit of a deck.
     ///
     /// Returns detailed information about deck contents including card counts,
@@ -322,21 +791,38 @@ impl<'a> AnalyzeEngine<'a> {
     pub async fn deck_audit(&self, deck: &str) -> Result<DeckAudit> {
         let mut audit = DeckAudit {
             deck: deck.to_string(),
+            maturity: Maturity::from_cards(&[]),
             ..Default::default()
         };
 
         let query = format!("deck:\"{}\"", deck);
 
-        // Get all cards in deck
-        let card_ids = self.client.cards().find(&query).await?;
+        // Find cards and notes together when batching; otherwise notes are
+        // only looked up for a non-empty deck
+        let batch = Batcher::new(self.client, self.options);
+        let (card_ids, note_ids) = if batch.enabled() {
+            let (cards, notes) = batch.pair(FindCards(&query), FindNotes(&query)).await?;
+            (cards, Some(notes))
+        } else {
+            (self.client.cards().find(&query).await?, None)
+        };
         audit.total_cards = card_ids.len();
 
         if card_ids.is_empty() {
             return Ok(audit);
         }
+        let note_ids = match note_ids {
+            Some(ids) => ids,
+            None => self.client.notes().find(&query).await?,
+        };
+        audit.total_notes = note_ids.len();
 
-        // Get card info for scheduling and model analysis
-        let cards = self.client.cards().info(&card_ids).await?;
+        // Get card info for scheduling and model analysis, and note info for
+        // tags, fields, and duplicates
+        let (cards, notes) = batch
+            .pair(CardsInfo(&card_ids), NotesInfo(&note_ids))
+            .await?;
+        let leeches = self.options.leech.resolve(self.client, &cards).await?;
 
         // Count by model and scheduling state
         let mut ease_sum: i64 = 0;
@@ -362,8 +848,8 @@ impl<'a> AnalyzeEngine<'a> {
                 audit.suspended_count += 1;
             }
 
-            // Check leech (high lapses, default threshold 8)
-            if card.lapses >= 8 {
+            // Check leech against the deck's lapse threshold
+            if leeches.is_leech(card) {
                 audit.leech_count += 1;
             }
 
@@ -378,14 +864,9 @@ impl<'a> AnalyzeEngine<'a> {
         if ease_count > 0 {
             audit.average_ease = ease_sum as f64 / ease_count as f64;
         }
+        audit.maturity = Maturity::from_cards(&cards);
 
-        // Get all notes in deck
-        let note_ids = self.client.notes().find(&query).await?;
-        audit.total_notes = note_ids.len();
-
-        if !note_ids.is_empty() {
-            let notes = self.client.notes().info(&note_ids).await?;
-
+        if !notes.is_empty() {
             // Tag distribution and untagged count
             for note in &notes {
                 if note.tags.is_empty() {
@@ -500,6 +981,7 @@ impl<'a> AnalyzeEngine<'a> {
 
         if !review_card_ids.is_empty() {
             let cards = self.client.cards().info(&review_card_ids).await?;
+            let leeches = self.options.leech.resolve(self.client, &cards).await?;
 
             // Calculate retention and ease
             let total_lapses: i64 = cards.iter().map(|c| c.lapses).sum();
@@ -522,8 +1004,8 @@ impl<'a> AnalyzeEngine<'a> {
 
             // Find problem cards
             for card in &cards {
-                // Leeches: 8+ lapses (Anki default)
-                if card.lapses >= 8 {
+                // Leeches: at or past the deck's lapse threshold
+                if leeches.is_leech(card) {
                     report.leeches.push(card.card_id);
                 }
                 // Low ease: below 200% (2000)
@@ -600,6 +1082,7 @@ impl<'a> AnalyzeEngine<'a> {
     ///     .compare_decks("Japanese::Core", "Japanese::Extra", CompareOptions {
     ///         key_field: "Front".to_string(),
     ///         similarity_threshold: 0.85,
+    ///         ..Default::default()
     ///     })
     ///     .await?;
     ///
@@ -635,31 +1118,28 @@ impl<'a> AnalyzeEngine<'a> {
         let query_a = format!("deck:\"{}\"", deck_a);
         let query_b = format!("deck:\"{}\"", deck_b);
 
-        let note_ids_a = self.client.notes().find(&query_a).await?;
-        let note_ids_b = self.client.notes().find(&query_b).await?;
+        let batch = Batcher::new(self.client, self.options);
+        let (note_ids_a, note_ids_b) = batch.pair(FindNotes(&query_a), FindNotes(&query_b)).await?;
 
         if note_ids_a.is_empty() && note_ids_b.is_empty() {
             return Ok(comparison);
         }
 
         // Get note info
-        let notes_a = if note_ids_a.is_empty() {
-            Vec::new()
-        } else {
-            self.client.notes().info(&note_ids_a).await?
-        };
-
-        let notes_b = if note_ids_b.is_empty() {
-            Vec::new()
-        } else {
-            self.client.notes().info(&note_ids_b).await?
-        };
-
-        // Extract key field values
-        let extract_key = |note: &ankit::NoteInfo| -> Option<(i64, String, Vec<String>)> {
-            note.fields
-                .get(&options.key_field)
-                .map(|f| (note.note_id, f.value.trim().to_string(), note.tags.clone()))
+        let (notes_a, notes_b) = batch
+            .pair(NotesInfo(&note_ids_a), NotesInfo(&note_ids_b))
+            .await?;
+
+        // Extract key field values, with their normalized form for matching
+        let extract_key = |note: &ankit::NoteInfo| -> Option<(i64, String, String, Vec<String>)> {
+            note.fields.get(&options.key_field).map(|f| {
+                (
+                    note.note_id,
+                    f.value.trim().to_string(),
+                    options.normalization.apply(&f.value),
+                    note.tags.clone(),
+                )
+            })
         };
 
         let keys_a: Vec<_> = notes_a.iter().filter_map(extract_key).collect();
@@ -668,7 +1148,7 @@ impl<'a> AnalyzeEngine<'a> {
         // Build lookup map for deck B (for exact matching from A)
         let map_b: HashMap<String, (i64, Vec<String>)> = keys_b
             .iter()
-            .map(|(id, key, tags)| (key.to_lowercase(), (*id, tags.clone())))
+            .map(|(id, _, normalized, tags)| (normalized.clone(), (*id, tags.clone())))
             .collect();
 
         // Track which notes have been matched
@@ -676,9 +1156,8 @@ impl<'a> AnalyzeEngine<'a> {
         let mut matched_in_b: std::collections::HashSet<i64> = std::collections::HashSet::new();
 
         // Find exact matches
-        for (note_id_a, key_a, tags_a) in &keys_a {
-            let key_lower = key_a.to_lowercase();
-            if let Some((note_id_b, tags_b)) = map_b.get(&key_lower) {
+        for (note_id_a, key_a, normalized_a, tags_a) in &keys_a {
+            if let Some((note_id_b, tags_b)) = map_b.get(normalized_a) {
                 matched_in_a.insert(*note_id_a);
                 matched_in_b.insert(*note_id_b);
 
@@ -697,45 +1176,57 @@ impl<'a> AnalyzeEngine<'a> {
             }
         }
 
-        // Find similar matches (only for unmatched notes)
+        // Find similar matches (only for unmatched notes). Candidates come
+        // ordered by A then B, so each note in A takes the first unmatched
+        // note in B that is similar enough. Candidates involving a matched
+        // note are skipped without scoring them.
         if options.similarity_threshold < 1.0 {
-            for (note_id_a, key_a, tags_a) in &keys_a {
-                if matched_in_a.contains(note_id_a) {
+            let unmatched_a: Vec<_> = keys_a
+                .iter()
+                .filter(|(id, ..)| !matched_in_a.contains(id))
+                .collect();
+            let unmatched_b: Vec<_> = keys_b
+                .iter()
+                .filter(|(id, ..)| !matched_in_b.contains(id))
+                .collect();
+            let normalized_a: Vec<&str> = unmatched_a.iter().map(|k| k.2.as_str()).collect();
+            let normalized_b: Vec<&str> = unmatched_b.iter().map(|k| k.2.as_str()).collect();
+
+            for (i, j) in candidates_between(&normalized_a, &normalized_b, options.prefilter) {
+                let (note_id_a, key_a, _, tags_a) = unmatched_a[i];
+                let (note_id_b, key_b, _, tags_b) = unmatched_b[j];
+                if matched_in_a.contains(note_id_a) || matched_in_b.contains(note_id_b) {
                     continue;
                 }
+                let Some(similarity) = score(
+                    normalized_a[i],
+                    normalized_b[j],
+                    options.algorithm,
+                    options.similarity_threshold,
+                ) else {
+                    continue;
+                };
+                matched_in_a.insert(*note_id_a);
+                matched_in_b.insert(*note_id_b);
 
-                for (note_id_b, key_b, tags_b) in &keys_b {
-                    if matched_in_b.contains(note_id_b) {
-                        continue;
-                    }
-
-                    let similarity = string_similarity(key_a, key_b);
-                    if similarity >= options.similarity_threshold {
-                        matched_in_a.insert(*note_id_a);
-                        matched_in_b.insert(*note_id_b);
-
-                        comparison.similar.push(SimilarPair {
-                            note_a: ComparisonNote {
-                                note_id: *note_id_a,
-                                key_value: key_a.clone(),
-                                tags: tags_a.clone(),
-                            },
-                            note_b: ComparisonNote {
-                                note_id: *note_id_b,
-                                key_value: key_b.clone(),
-                                tags: tags_b.clone(),
-                            },
-                            similarity,
-                        });
-
-                        break; // Move to next note in A
-                    }
-                }
+                comparison.similar.push(SimilarPair {
+                    note_a: ComparisonNote {
+                        note_id: *note_id_a,
+                        key_value: key_a.clone(),
+                        tags: tags_a.clone(),
+                    },
+                    note_b: ComparisonNote {
+                        note_id: *note_id_b,
+                        key_value: key_b.clone(),
+                        tags: tags_b.clone(),
+                    },
+                    similarity,
+                });
             }
         }
 
         // Collect unmatched notes
-        for (note_id_a, key_a, tags_a) in &keys_a {
+        for (note_id_a, key_a, _, tags_a) in &keys_a {
             if !matched_in_a.contains(note_id_a) {
                 comparison.only_in_a.push(ComparisonNote {
                     note_id: *note_id_a,
@@ -745,7 +1236,7 @@ impl<'a> AnalyzeEngine<'a> {
             }
         }
 
-        for (note_id_b, key_b, tags_b) in &keys_b {
+        for (note_id_b, key_b, _, tags_b) in &keys_b {
             if !matched_in_b.contains(note_id_b) {
                 comparison.only_in_b.push(ComparisonNote {
                     note_id: *note_id_b,
@@ -801,14 +1292,13 @@ impl<'a> AnalyzeEngine<'a> {
             ..Default::default()
         };
 
-        // Get due cards
+        // Get due and new cards
         let due_query = format!("deck:\"{}\" is:due -is:suspended", deck);
-        let due_card_ids = self.client.cards().find(&due_query).await?;
-        plan.total_due = due_card_ids.len();
-
-        // Get new cards
         let new_query = format!("deck:\"{}\" is:new -is:suspended", deck);
-        let new_card_ids = self.client.cards().find(&new_query).await?;
+        let (due_card_ids, new_card_ids) = Batcher::new(self.client, self.options)
+            .pair(FindCards(&due_query), FindCards(&new_query))
+            .await?;
+        plan.total_due = due_card_ids.len();
         plan.total_new_available = new_card_ids.len();
 
         if due_card_ids.is_empty() && new_card_ids.is_empty() {
@@ -828,11 +1318,15 @@ impl<'a> AnalyzeEngine<'a> {
         let total_seconds = options.target_time_minutes * 60;
 
         // First, identify leeches and calculate how many we can fit
+        let policy = options
+            .leech_threshold
+            .map_or(self.options.leech, leech::LeechThresholdSource::Fixed);
+        let leeches = policy.resolve(self.client, &due_cards).await?;
         let mut leech_ids: Vec<i64> = Vec::new();
         let mut regular_review_ids: Vec<i64> = Vec::new();
 
         for card in &due_cards {
-            if card.lapses >= options.leech_threshold {
+            if leeches.is_leech(card) {
                 leech_ids.push(card.card_id);
             } else {
                 regular_review_ids.push(card.card_id);
@@ -965,67 +1459,1211 @@ impl<'a> AnalyzeEngine<'a> {
 
         Ok(plan)
     }
-}
 
-/// Calculate string similarity using normalized Levenshtein distance.
-///
-/// Returns a value between 0.0 (completely different) and 1.0 (identical).
-fn string_similarity(a: &str, b: &str) -> f64 {
-    let a_lower = a.to_lowercase();
-    let b_lower = b.to_lowercase();
+    /// Report how notes are spread over the tag hierarchy.
+    ///
+    /// Counts, for every tag on the matching notes, how many notes carry the
+    /// tag itself and how many carry it or any tag below it.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - Anki search query selecting notes (use "deck:*" for all)
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let stats = engine.analyze().tag_stats("deck:Japanese").await?;
+    /// for tag in stats.tags.iter().filter(|t| t.depth == 0) {
+    ///     println!("{}: {} notes", tag.tag, tag.total_notes);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn tag_stats(&self, query: &str) -> Result<TagStats> {
+        let note_ids = self.client.notes().find(query).await?;
+        let mut stats = TagStats {
+            total_notes: note_ids.len(),
+            ..Default::default()
+        };
+        if note_ids.is_empty() {
+            return Ok(stats);
+        }
+        let notes = self.client.notes().info(&note_ids).await?;
+
+        let tree = TagTree::from_names(notes.iter().flat_map(|n| n.tags.iter().cloned()));
+        let mut direct: HashMap<String, usize> = HashMap::new();
+        let mut total: HashMap<String, usize> = HashMap::new();
+        for note in &notes {
+            if note.tags.is_empty() {
+                stats.untagged_notes += 1;
+                continue;
+            }
+            let mut branches = HashSet::new();
+            for tag in &note.tags {
+                let tag = tag.to_lowercase();
+                *direct.entry(tag.clone()).or_default() += 1;
+                // Count the note once for the tag and each of its ancestors
+                let mut branch = Some(tag.as_str());
+                while let Some(current) = branch {
+                    branches.insert(current.to_string());
+                    branch = tree.parent(current);
+                }
+            }
+            for branch in branches {
+                *total.entry(branch).or_default() += 1;
+            }
+        }
 
-    if a_lower == b_lower {
-        return 1.0;
-    }
+        stats.tags = tree
+            .iter()
+            .map(|tag| {
+                let key = tag.to_lowercase();
+                TagStat {
+                    tag: tag.to_string(),
+                    depth: TagTree::depth(tag),
+                    notes: direct.get(&key).copied().unwrap_or(0),
+                    total_notes: total.get(&key).copied().unwrap_or(0),
+                    children: tree.children(tag).len(),
+                }
+            })
+            .collect();
+        stats.max_depth = stats.tags.iter().map(|t| t.depth).max().unwrap_or(0);
 
-    if a_lower.is_empty() || b_lower.is_empty() {
-        return 0.0;
+        Ok(stats)
     }
 
-    let distance = levenshtein_distance(&a_lower, &b_lower);
-    let max_len = a_lower.chars().count().max(b_lower.chars().count());
-
-    1.0 - (distance as f64 / max_len as f64)
-}
-
-/// Calculate the Levenshtein distance between two strings.
-fn levenshtein_distance(a: &str, b: &str) -> usize {
-    let a_chars: Vec<char> = a.chars().collect();
-    let b_chars: Vec<char> = b.chars().collect();
+    /// Aggregate review history into the data behind a GitHub-style heatmap.
+    ///
+    /// Every review of the matching cards is bucketed by local day, weekday,
+    /// and hour, using a fixed UTC offset and Anki's day rollover hour.
+    /// Streaks count consecutive local days with at least one review.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - Anki search query selecting cards
+    /// * `options` - Time zone offset and day rollover
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # use ankit_engine::analyze::HeatmapOptions;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let options = HeatmapOptions {
+    ///     utc_offset_minutes: -300, // UTC-5
+    ///     ..Default::default()
+    /// };
+    /// let heatmap = engine.analyze().review_heatmap("deck:Japanese", options).await?;
+    /// for day in &heatmap.days {
+    ///     println!("{}: {} reviews", day.date, day.reviews);
+    /// }
+    /// println!("Longest streak: {} days", heatmap.longest_streak);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn review_heatmap(
+        &self,
+        query: &str,
+        options: HeatmapOptions,
+    ) -> Result<ReviewHeatmap> {
+        let card_ids = self.client.cards().find(query).await?;
+        if card_ids.is_empty() {
+            return Ok(ReviewHeatmap::default());
+        }
 
-    let m = a_chars.len();
-    let n = b_chars.len();
+        let reviews = self
+            .client
+            .statistics()
+            .reviews_for_cards(&card_ids)
+            .await?;
+        let entries: Vec<(i64, i64)> = reviews
+            .values()
+            .flatten()
+            .map(|review| (review.review_id, review.time))
+            .collect();
 
-    if m == 0 {
-        return n;
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as i64;
+        Ok(build_heatmap(&entries, &options, options.day_of(now_ms)))
     }
-    if n == 0 {
-        return m;
+
+    /// Compare review performance between two groups of cards.
+    ///
+    /// Meant for A/B tests of card templates or wording: tag the two variants
+    /// and compare the tags. For each cohort, three metrics are estimated
+    /// with 95% confidence intervals:
+    ///
+    /// - **Retention**: share of review answers (from the review log,
+    ///   excluding learning and relearning steps) that were not "Again"
+    /// - **Ease**: mean ease factor of graduated cards, in percent
+    /// - **Lapse rate**: share of graduated cards that have lapsed at least
+    ///   once
+    ///
+    /// Each [`MetricComparison`] gives the difference (B minus A), its
+    /// confidence interval, and a two-sided p-value. A metric is `None`
+    /// when either cohort has no data for it. Cards matching both queries
+    /// count toward both cohorts, which weakens the comparison; see
+    /// [`CohortComparison::shared_cards`].
+    ///
+    /// # Arguments
+    ///
+    /// * `query_a` - Anki search query selecting the first cohort
+    /// * `query_b` - Anki search query selecting the second cohort
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let comparison = engine
+    ///     .analyze()
+    ///     .compare_cohorts("tag:template::a", "tag:template::b")
+    ///     .await?;
+    /// if let Some(retention) = &comparison.retention {
+    ///     println!(
+    ///         "Retention difference: {:+.1} points (p = {:.3})",
+    ///         retention.difference * 100.0,
+    ///         retention.p_value
+    ///     );
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn compare_cohorts(&self, query_a: &str, query_b: &str) -> Result<CohortComparison> {
+        let (a, ids_a) = self.cohort_stats(query_a).await?;
+        let (b, ids_b) = self.cohort_stats(query_b).await?;
+        let shared_cards = ids_a.intersection(&ids_b).count();
+
+        Ok(CohortComparison {
+            retention: compare_proportions(&a.retention, &b.retention),
+            ease: compare_means(&a.ease, &b.ease),
+            lapse_rate: compare_proportions(&a.lapse_rate, &b.lapse_rate),
+            a,
+            b,
+            shared_cards,
+        })
     }
 
-    // Use two rows instead of full matrix for memory efficiency
-    let mut prev: Vec<usize> = (0..=n).collect();
-    let mut curr = vec![0; n + 1];
+    async fn cohort_stats(&self, query: &str) -> Result<(CohortStats, HashSet<i64>)> {
+        let card_ids = self.client.cards().find(query).await?;
+        let mut stats = CohortStats {
+            query: query.to_string(),
+            cards: card_ids.len(),
+            ..Default::default()
+        };
+        if card_ids.is_empty() {
+            return Ok((stats, HashSet::new()));
+        }
+
+        let cards = self.client.cards().info(&card_ids).await?;
+        let reviews = self
+            .client
+            .statistics()
+            .reviews_for_cards(&card_ids)
+            .await?;
+
+        // Answers to review-queue cards; learning steps don't measure recall
+        let answers: Vec<bool> = reviews
+            .values()
+            .flatten()
+            .filter(|review| review.review_type == 1)
+            .map(|review| review.ease > 1)
+            .collect();
+        stats.reviews = answers.len();
+        stats.retention =
+            Estimate::proportion(answers.iter().filter(|&&p| p).count(), answers.len());
 
-    for i in 1..=m {
-        curr[0] = i;
+        let graduated: Vec<_> = cards
+            .iter()
+            .filter(|card| matches!(card.card_type, 2 | 3))
+            .collect();
+        let ease: Vec<f64> = graduated
+            .iter()
+            .filter(|card| card.ease_factor > 0)
+            .map(|card| card.ease_factor as f64 / 10.0)
+            .collect();
+        stats.ease = Estimate::mean(&ease);
+        stats.graduated_cards = graduated.len();
+        stats.lapse_rate = Estimate::proportion(
+            graduated.iter().filter(|card| card.lapses > 0).count(),
+            graduated.len(),
+        );
+
+        Ok((stats, card_ids.into_iter().collect()))
+    }
 
-        for j in 1..=n {
-            let cost = if a_chars[i - 1] == b_chars[j - 1] {
-                0
-            } else {
-                1
+    /// Break down review performance by hour of day and weekday.
+    ///
+    /// Uses UTC and a 4 AM day rollover; see
+    /// [`time_of_day_with`](Self::time_of_day_with) to set the time zone.
+    ///
+    /// # Arguments
+    ///
+    /// * `deck` - Deck to analyze
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let stats = engine.analyze().time_of_day("Japanese").await?;
+    /// if let Some(hour) = stats.best_hour {
+    ///     println!("Best retention at {}:00", hour);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn time_of_day(&self, deck: &str) -> Result<TimeOfDayStats> {
+        self.time_of_day_with(deck, HeatmapOptions::default()).await
+    }
+
+    /// Break down review performance by local hour of day and weekday.
+    ///
+    /// Every answer in the deck's review log is bucketed by the local hour
+    /// it was given and the weekday of its study day, using the same time
+    /// zone and rollover settings as [`review_heatmap`](Self::review_heatmap).
+    /// Accuracy counts answers to review cards that were not "Again";
+    /// learning steps are left out since they are expected to fail. Answer
+    /// times include every answer.
+    ///
+    /// # Arguments
+    ///
+    /// * `deck` - Deck to analyze
+    /// * `options` - Time zone offset and day rollover
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # use ankit_engine::analyze::HeatmapOptions;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let options = HeatmapOptions {
+    ///     utc_offset_minutes: 60, // UTC+1
+    ///     ..Default::default()
+    /// };
+    /// let stats = engine.analyze().time_of_day_with("Japanese", options).await?;
+    /// for (hour, bucket) in stats.by_hour.iter().enumerate() {
+    ///     if let Some(accuracy) = bucket.accuracy {
+    ///         println!("{:02}:00 {:.0}% ({} ms)", hour, accuracy * 100.0, bucket.avg_answer_ms);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn time_of_day_with(
+        &self,
+        deck: &str,
+        options: HeatmapOptions,
+    ) -> Result<TimeOfDayStats> {
+        let mut stats = TimeOfDayStats {
+            deck: deck.to_string(),
+            ..Default::default()
+        };
+        let card_ids = self
+            .client
+            .cards()
+            .find(&format!("deck:\"{}\"", deck))
+            .await?;
+        if card_ids.is_empty() {
+            return Ok(stats);
+        }
+
+        let reviews = self
+            .client
+            .statistics()
+            .reviews_for_cards(&card_ids)
+            .await?;
+        for review in reviews.values().flatten() {
+            let hour = options.local_seconds(review.review_id).rem_euclid(86400) / 3600;
+            // 1970-01-01 was a Thursday, index 3 counting from Monday
+            let weekday = (options.day_of(review.review_id) + 3).rem_euclid(7);
+            for bucket in [
+                &mut stats.by_hour[hour as usize],
+                &mut stats.by_weekday[weekday as usize],
+            ] {
+                bucket.add(review.review_type, review.ease, review.time);
+            }
+            stats.total_reviews += 1;
+        }
+
+        for bucket in stats.by_hour.iter_mut().chain(stats.by_weekday.iter_mut()) {
+            bucket.finish();
+        }
+        stats.best_hour = best_bucket(&stats.by_hour);
+        stats.best_weekday = best_bucket(&stats.by_weekday);
+        Ok(stats)
+    }
+
+    /// Find cards that take much longer to answer than their deck's median.
+    ///
+    /// Each card's average answer time is taken from its review log, and
+    /// cards with at least three timed answers are compared with the median
+    /// of the other cards in the same deck. Cards at or above `threshold`
+    /// times the median are returned, slowest first, with a suggestion: a
+    /// card with a long or multi-part answer is probably testing several
+    /// facts and should be split, while one with a short answer is more
+    /// likely unclear and should be rewritten.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - Anki search query selecting cards
+    /// * `threshold` - Multiple of the deck median that counts as slow (e.g. 2.0)
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Validation`] if `threshold` is not a positive number.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let report = engine.analyze().slow_cards("deck:Japanese", 2.0).await?;
+    /// for card in &report.cards {
+    ///     println!(
+    ///         "{}: {:.1}s ({:.1}x median) - {}",
+    ///         card.front,
+    ///         card.avg_answer_ms as f64 / 1000.0,
+    ///         card.ratio,
+    ///         card.suggestion
+    ///     );
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn slow_cards(&self, query: &str, threshold: f64) -> Result<SlowCardsReport> {
+        if !(threshold > 0.0 && threshold.is_finite()) {
+            return Err(Error::Validation(format!(
+                "slow card threshold must be a positive number, got {}",
+                threshold
+            )));
+        }
+
+        let mut report = SlowCardsReport {
+            threshold,
+            ..Default::default()
+        };
+        let card_ids = self.client.cards().find(query).await?;
+        if card_ids.is_empty() {
+            return Ok(report);
+        }
+
+        let cards = self.client.cards().info(&card_ids).await?;
+        let reviews = self.review_log(&card_ids).await?;
+
+        // Average answer time of every card with enough timed answers
+        let mut timed: Vec<(&CardInfo, usize, i64)> = Vec::new();
+        for card in &cards {
+            let times: Vec<i64> = reviews
+                .get(&card.card_id)
+                .into_iter()
+                .flatten()
+                .map(|review| review.time)
+                .filter(|&time| time > 0)
+                .collect();
+            if times.len() >= MIN_TIMED_ANSWERS {
+                let average = times.iter().sum::<i64>() / times.len() as i64;
+                timed.push((card, times.len(), average));
+            }
+        }
+        report.cards_timed = timed.len();
+
+        let mut by_deck: BTreeMap<&str, Vec<i64>> = BTreeMap::new();
+        for (card, _, average) in &timed {
+            by_deck.entry(&card.deck_name).or_default().push(*average);
+        }
+        report.deck_medians = by_deck
+            .into_iter()
+            .map(|(deck, mut averages)| {
+                averages.sort_unstable();
+                (deck.to_string(), median(&averages))
+            })
+            .collect();
+
+        for (card, reviews, average) in timed {
+            let median = report.deck_medians[&card.deck_name];
+            if median <= 0 {
+                continue;
+            }
+            let ratio = average as f64 / median as f64;
+            if ratio < threshold {
+                continue;
+            }
+            report.cards.push(SlowCard {
+                card_id: card.card_id,
+                note_id: card.note_id,
+                deck_name: card.deck_name.clone(),
+                front: first_field(card),
+                reviews,
+                avg_answer_ms: average,
+                deck_median_ms: median,
+                ratio,
+                suggestion: SlowCardSuggestion::for_answer(&card.answer),
+            });
+        }
+        report.cards.sort_by(|a, b| b.ratio.total_cmp(&a.ratio));
+
+        Ok(report)
+    }
+
+    /// Review log entries for cards, keyed by card ID.
+    async fn review_log(&self, card_ids: &[i64]) -> Result<HashMap<i64, Vec<ReviewEntry>>> {
+        let reviews = self.client.statistics().reviews_for_cards(card_ids).await?;
+        let mut log: HashMap<i64, Vec<ReviewEntry>> = HashMap::new();
+        for review in reviews.into_values().flatten() {
+            log.entry(review.card_id).or_default().push(review);
+        }
+        Ok(log)
+    }
+}
+
+/// Time zone settings for [`AnalyzeEngine::review_heatmap`].
+#[derive(Debug, Clone, Copy)]
+pub struct HeatmapOptions {
+    /// Offset of local time from UTC, in minutes (e.g., -300 for UTC-5).
+    pub utc_offset_minutes: i32,
+    /// Local hour at which a new study day starts, like Anki's "next day
+    /// starts at" preference. Reviews before it count toward the previous day.
+    pub rollover_hour: u8,
+}
+
+impl Default for HeatmapOptions {
+    fn default() -> Self {
+        Self {
+            utc_offset_minutes: 0,
+            rollover_hour: 4,
+        }
+    }
+}
+
+impl HeatmapOptions {
+    /// Local time in seconds since the epoch.
+    fn local_seconds(&self, timestamp_ms: i64) -> i64 {
+        timestamp_ms.div_euclid(1000) + i64::from(self.utc_offset_minutes) * 60
+    }
+
+    /// Study day, in days since 1970-01-01, that a review falls on.
+    fn day_of(&self, timestamp_ms: i64) -> i64 {
+        (self.local_seconds(timestamp_ms) - i64::from(self.rollover_hour) * 3600).div_euclid(86400)
+    }
+}
+
+/// Interval, in days, at which Anki considers a card mature.
+const MATURE_DAYS: i64 = 21;
+
+/// Interval, in days, from which a card counts as very mature.
+const VERY_MATURE_DAYS: i64 = 90;
+
+/// Lower bounds, in days, of the interval histogram bins.
+const HISTOGRAM_BINS: [i64; 12] = [1, 2, 3, 7, 14, 21, 30, 60, 90, 180, 365, 730];
+
+/// Cards by maturity, from [`AnalyzeEngine::maturity`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Maturity {
+    /// Total number of cards.
+    pub total_cards: usize,
+    /// Cards never studied.
+    pub new: usize,
+    /// Cards in learning or relearning.
+    pub learning: usize,
+    /// Review cards with an interval under 21 days.
+    pub young: usize,
+    /// Review cards with an interval of 21 to 89 days.
+    pub mature: usize,
+    /// Review cards with an interval of 90 days or more.
+    pub very_mature: usize,
+    /// Average interval of review cards, in days.
+    pub average_interval: f64,
+    /// Longest interval of any review card, in days.
+    pub max_interval: i64,
+    /// Review cards per interval range, shortest first. Every bin is
+    /// present, empty or not, so the series can be plotted as is.
+    pub histogram: Vec<IntervalBin>,
+}
+
+impl Maturity {
+    fn from_cards(cards: &[CardInfo]) -> Self {
+        let mut maturity = Self {
+            total_cards: cards.len(),
+            histogram: HISTOGRAM_BINS
+                .iter()
+                .enumerate()
+                .map(|(i, &min_days)| {
+                    let max_days = HISTOGRAM_BINS.get(i + 1).map(|next| next - 1);
+                    let label = match max_days {
+                        Some(max) if max == min_days => format!("{}d", min_days),
+                        Some(max) => format!("{}-{}d", min_days, max),
+                        None => format!("{}d+", min_days),
+                    };
+                    IntervalBin {
+                        label,
+                        min_days,
+                        max_days,
+                        cards: 0,
+                    }
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        let mut interval_sum = 0;
+        let mut review_cards = 0;
+        for card in cards {
+            match card.card_type {
+                0 => maturity.new += 1,
+                1 | 3 => maturity.learning += 1,
+                2 => {
+                    let interval = card.interval.max(1);
+                    match interval {
+                        i if i < MATURE_DAYS => maturity.young += 1,
+                        i if i < VERY_MATURE_DAYS => maturity.mature += 1,
+                        _ => maturity.very_mature += 1,
+                    }
+                    let bin = HISTOGRAM_BINS
+                        .iter()
+                        .rposition(|&min| interval >= min)
+                        .unwrap_or(0);
+                    maturity.histogram[bin].cards += 1;
+                    maturity.max_interval = maturity.max_interval.max(interval);
+                    interval_sum += interval;
+                    review_cards += 1;
+                }
+                _ => {}
+            }
+        }
+        if review_cards > 0 {
+            maturity.average_interval = interval_sum as f64 / review_cards as f64;
+        }
+        maturity
+    }
+}
+
+impl WorkflowReport for Maturity {
+    fn summary(&self) -> String {
+        format!(
+            "{} cards: {} new, {} learning, {} young, {} mature, {} very mature",
+            self.total_cards, self.new, self.learning, self.young, self.mature, self.very_mature
+        )
+    }
+
+    report_fields!();
+}
+
+/// One bin of a [`Maturity`] interval histogram.
+#[derive(Debug, Clone, Serialize)]
+pub struct IntervalBin {
+    /// Label for the bin, such as `"7-13d"`.
+    pub label: String,
+    /// Shortest interval in the bin, in days.
+    pub min_days: i64,
+    /// Longest interval in the bin, in days (`None` for the last bin).
+    pub max_days: Option<i64>,
+    /// Number of review cards in the bin.
+    pub cards: usize,
+}
+
+/// Timed answers a card needs before [`AnalyzeEngine::slow_cards`] judges it.
+const MIN_TIMED_ANSWERS: usize = 3;
+
+/// Cards that are slow to answer, from [`AnalyzeEngine::slow_cards`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SlowCardsReport {
+    /// Multiple of the deck median that counted as slow.
+    pub threshold: f64,
+    /// Number of cards with enough timed answers to be compared.
+    pub cards_timed: usize,
+    /// Median average answer time per deck, in milliseconds.
+    pub deck_medians: BTreeMap<String, i64>,
+    /// Slow cards, slowest relative to their deck first.
+    pub cards: Vec<SlowCard>,
+}
+
+impl WorkflowReport for SlowCardsReport {
+    fn summary(&self) -> String {
+        format!(
+            "{} of {} timed cards take at least {:.1}x their deck's median answer time",
+            self.cards.len(),
+            self.cards_timed,
+            self.threshold
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.cards
+            .iter()
+            .map(|card| {
+                format!(
+                    "{} ({}): {:.1}s, {:.1}x median - {}",
+                    card.card_id,
+                    card.deck_name,
+                    card.avg_answer_ms as f64 / 1000.0,
+                    card.ratio,
+                    card.suggestion
+                )
+            })
+            .collect()
+    }
+
+    report_fields!();
+}
+
+/// A card that takes unusually long to answer.
+#[derive(Debug, Clone, Serialize)]
+pub struct SlowCard {
+    /// The card ID.
+    pub card_id: i64,
+    /// The note ID.
+    pub note_id: i64,
+    /// The deck name.
+    pub deck_name: String,
+    /// Front field content (first field).
+    pub front: String,
+    /// Number of timed answers.
+    pub reviews: usize,
+    /// Average answer time, in milliseconds.
+    pub avg_answer_ms: i64,
+    /// Median average answer time of the card's deck, in milliseconds.
+    pub deck_median_ms: i64,
+    /// Average answer time as a multiple of the deck median.
+    pub ratio: f64,
+    /// What to do about the card.
+    pub suggestion: SlowCardSuggestion,
+}
+
+/// Suggested fix for a slow card.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlowCardSuggestion {
+    /// The answer is long or lists several items; split it into cards that
+    /// each test one fact.
+    Split,
+    /// The answer is short, so the prompt is likely ambiguous or hard to
+    /// parse; reword it.
+    Rewrite,
+}
+
+impl SlowCardSuggestion {
+    /// Answers with more words or items than this suggest splitting.
+    const MAX_WORDS: usize = 25;
+    const MAX_ITEMS: usize = 3;
+
+    /// Pick a suggestion from a card's rendered answer.
+    fn for_answer(answer_html: &str) -> Self {
+        // Only look at the back: rendered answers usually repeat the front
+        let back = answer_html
+            .split_once("<hr id=answer>")
+            .or_else(|| answer_html.split_once("<hr id=\"answer\">"))
+            .map_or(answer_html, |(_, back)| back);
+        let items = back.matches("<li").count();
+        let text = normalize(back, &NormalizeOptions::plain_text());
+        let lines = text.lines().filter(|line| !line.trim().is_empty()).count();
+        let words = text.split_whitespace().count();
+
+        if items.max(lines) >= Self::MAX_ITEMS || words > Self::MAX_WORDS {
+            Self::Split
+        } else {
+            Self::Rewrite
+        }
+    }
+}
+
+impl fmt::Display for SlowCardSuggestion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Split => write!(f, "split into smaller cards"),
+            Self::Rewrite => write!(f, "rewrite the prompt"),
+        }
+    }
+}
+
+/// Median of sorted values (0 when empty).
+fn median(sorted: &[i64]) -> i64 {
+    match sorted.len() {
+        0 => 0,
+        n if n % 2 == 1 => sorted[n / 2],
+        n => (sorted[n / 2 - 1] + sorted[n / 2]) / 2,
+    }
+}
+
+/// Value of a card's first field, by field order.
+fn first_field(card: &CardInfo) -> String {
+    card.fields
+        .values()
+        .min_by_key(|field| field.order)
+        .map(|field| field.value.clone())
+        .unwrap_or_default()
+}
+
+/// Review answers a time bucket needs before it can be the best hour or
+/// weekday of a [`TimeOfDayStats`] report.
+const MIN_BUCKET_ANSWERS: usize = 20;
+
+/// Review performance by time of day from [`AnalyzeEngine::time_of_day`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TimeOfDayStats {
+    /// The deck analyzed.
+    pub deck: String,
+    /// Total number of answers in the review log.
+    pub total_reviews: usize,
+    /// Performance per local hour of the day, midnight first.
+    pub by_hour: [TimeBucket; 24],
+    /// Performance per weekday, Monday first.
+    pub by_weekday: [TimeBucket; 7],
+    /// Hour with the highest accuracy, among hours with at least 20 review
+    /// answers.
+    pub best_hour: Option<usize>,
+    /// Weekday (0 = Monday) with the highest accuracy, among weekdays with at
+    /// least 20 review answers.
+    pub best_weekday: Option<usize>,
+}
+
+impl WorkflowReport for TimeOfDayStats {
+    fn summary(&self) -> String {
+        const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+        let best = match (self.best_hour, self.best_weekday) {
+            (Some(hour), Some(weekday)) => {
+                format!("best at {:02}:00 and on {}", hour, WEEKDAYS[weekday])
+            }
+            (Some(hour), None) => format!("best at {:02}:00", hour),
+            (None, Some(weekday)) => format!("best on {}", WEEKDAYS[weekday]),
+            (None, None) => "not enough reviews to compare".to_string(),
+        };
+        format!("'{}': {} reviews, {}", self.deck, self.total_reviews, best)
+    }
+
+    report_fields!();
+}
+
+/// Answers given in one hour or weekday.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TimeBucket {
+    /// Number of answers, including learning steps.
+    pub reviews: usize,
+    /// Number of answers to review cards.
+    pub review_answers: usize,
+    /// Review answers that were not "Again".
+    pub correct: usize,
+    /// Share of review answers that were not "Again" (0.0 - 1.0), if any.
+    pub accuracy: Option<f64>,
+    /// Average time spent per answer, in milliseconds.
+    pub avg_answer_ms: i64,
+    /// Total time spent answering, in milliseconds.
+    pub total_time_ms: i64,
+}
+
+impl TimeBucket {
+    fn add(&mut self, review_type: i32, ease: i32, time_ms: i64) {
+        self.reviews += 1;
+        self.total_time_ms += time_ms;
+        if review_type == 1 {
+            self.review_answers += 1;
+            if ease > 1 {
+                self.correct += 1;
+            }
+        }
+    }
+
+    fn finish(&mut self) {
+        if self.reviews > 0 {
+            self.avg_answer_ms = self.total_time_ms / self.reviews as i64;
+        }
+        if self.review_answers > 0 {
+            self.accuracy = Some(self.correct as f64 / self.review_answers as f64);
+        }
+    }
+}
+
+/// Index of the most accurate bucket with enough review answers.
+fn best_bucket(buckets: &[TimeBucket]) -> Option<usize> {
+    buckets
+        .iter()
+        .enumerate()
+        .filter(|(_, bucket)| bucket.review_answers >= MIN_BUCKET_ANSWERS)
+        .filter_map(|(index, bucket)| bucket.accuracy.map(|accuracy| (index, accuracy)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(index, _)| index)
+}
+
+/// Review activity aggregated for a heatmap.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReviewHeatmap {
+    /// Days with at least one review, oldest first.
+    pub days: Vec<HeatmapDay>,
+    /// Total number of reviews.
+    pub total_reviews: usize,
+    /// Number of days with at least one review.
+    pub active_days: usize,
+    /// Consecutive days with reviews ending today, or yesterday if nothing has
+    /// been reviewed yet today.
+    pub current_streak: u32,
+    /// Longest run of consecutive days with reviews.
+    pub longest_streak: u32,
+    /// Reviews per weekday, Monday first.
+    pub by_weekday: [usize; 7],
+    /// Reviews per local hour of the day, midnight first.
+    pub by_hour: [usize; 24],
+}
+
+/// Review activity on a single day.
+#[derive(Debug, Clone, Serialize)]
+pub struct HeatmapDay {
+    /// Date in YYYY-MM-DD format.
+    pub date: String,
+    /// Number of reviews.
+    pub reviews: usize,
+    /// Time spent answering, in milliseconds.
+    pub time_ms: i64,
+}
+
+impl WorkflowReport for ReviewHeatmap {
+    fn summary(&self) -> String {
+        format!(
+            "{} reviews on {} days; current streak {} days, longest {}",
+            self.total_reviews, self.active_days, self.current_streak, self.longest_streak
+        )
+    }
+
+    report_fields!();
+}
+
+/// Aggregate `(timestamp ms, time spent ms)` review pairs into a heatmap.
+fn build_heatmap(entries: &[(i64, i64)], options: &HeatmapOptions, today: i64) -> ReviewHeatmap {
+    let mut heatmap = ReviewHeatmap::default();
+    let mut per_day: BTreeMap<i64, (usize, i64)> = BTreeMap::new();
+
+    for &(timestamp, time) in entries {
+        let day = options.day_of(timestamp);
+        let entry = per_day.entry(day).or_default();
+        entry.0 += 1;
+        entry.1 += time;
+
+        // 1970-01-01 was a Thursday, index 3 counting from Monday
+        heatmap.by_weekday[(day + 3).rem_euclid(7) as usize] += 1;
+        let hour = options.local_seconds(timestamp).rem_euclid(86400) / 3600;
+        heatmap.by_hour[hour as usize] += 1;
+    }
+
+    heatmap.total_reviews = entries.len();
+    heatmap.active_days = per_day.len();
+
+    let mut run = 0u32;
+    let mut previous: Option<i64> = None;
+    for &day in per_day.keys() {
+        run = if previous == Some(day - 1) {
+            run + 1
+        } else {
+            1
+        };
+        heatmap.longest_streak = heatmap.longest_streak.max(run);
+        previous = Some(day);
+    }
+    if previous.is_some_and(|last| last >= today - 1) {
+        heatmap.current_streak = run;
+    }
+
+    heatmap.days = per_day
+        .into_iter()
+        .map(|(day, (reviews, time_ms))| HeatmapDay {
+            date: civil_date(day),
+            reviews,
+            time_ms,
+        })
+        .collect();
+    heatmap
+}
+
+/// Format days since 1970-01-01 as a YYYY-MM-DD date.
+fn civil_date(days: i64) -> String {
+    // Howard Hinnant's days-to-civil algorithm
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Two-sided critical value of the standard normal for 95% confidence.
+const Z_95: f64 = 1.959964;
+
+/// Comparison of two card cohorts from [`AnalyzeEngine::compare_cohorts`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CohortComparison {
+    /// Statistics for the first cohort.
+    pub a: CohortStats,
+    /// Statistics for the second cohort.
+    pub b: CohortStats,
+    /// Number of cards matching both queries.
+    pub shared_cards: usize,
+    /// Difference in retention (B minus A).
+    pub retention: Option<MetricComparison>,
+    /// Difference in mean ease, in percentage points (B minus A).
+    pub ease: Option<MetricComparison>,
+    /// Difference in lapse rate (B minus A).
+    pub lapse_rate: Option<MetricComparison>,
+}
+
+impl WorkflowReport for CohortComparison {
+    fn summary(&self) -> String {
+        let significant = [&self.retention, &self.ease, &self.lapse_rate]
+            .into_iter()
+            .flatten()
+            .filter(|metric| metric.significant)
+            .count();
+        format!(
+            "Compared {} cards with {} cards: {} of 3 metrics differ significantly",
+            self.a.cards, self.b.cards, significant
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        let mut details = Vec::new();
+        let metrics = [
+            (
+                "Retention",
+                &self.a.retention,
+                &self.b.retention,
+                &self.retention,
+                100.0,
+            ),
+            ("Ease", &self.a.ease, &self.b.ease, &self.ease, 1.0),
+            (
+                "Lapse rate",
+                &self.a.lapse_rate,
+                &self.b.lapse_rate,
+                &self.lapse_rate,
+                100.0,
+            ),
+        ];
+        for (name, a, b, comparison, scale) in metrics {
+            let line = match comparison {
+                Some(c) => format!(
+                    "{}: A {:.1} vs B {:.1}, difference {:+.1} [{:+.1}, {:+.1}], p = {:.3}",
+                    name,
+                    a.value * scale,
+                    b.value * scale,
+                    c.difference * scale,
+                    c.low * scale,
+                    c.high * scale,
+                    c.p_value
+                ),
+                None => format!("{}: not enough data", name),
             };
+            details.push(line);
+        }
+        if self.shared_cards > 0 {
+            details.push(format!("{} cards are in both cohorts", self.shared_cards));
+        }
+        details
+    }
+
+    report_fields!();
+}
+
+/// Statistics for one cohort in a [`CohortComparison`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CohortStats {
+    /// The query selecting the cohort.
+    pub query: String,
+    /// Number of cards matching the query.
+    pub cards: usize,
+    /// Number of cards past the learning stage (review or relearning).
+    pub graduated_cards: usize,
+    /// Number of review answers in the review log.
+    pub reviews: usize,
+    /// Share of review answers that were not "Again" (0.0 - 1.0).
+    pub retention: Estimate,
+    /// Mean ease factor of graduated cards, in percent.
+    pub ease: Estimate,
+    /// Share of graduated cards that have lapsed at least once (0.0 - 1.0).
+    pub lapse_rate: Estimate,
+}
+
+/// A point estimate with its 95% confidence interval.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Estimate {
+    /// The estimate.
+    pub value: f64,
+    /// Lower bound of the confidence interval.
+    pub low: f64,
+    /// Upper bound of the confidence interval.
+    pub high: f64,
+    /// Number of observations.
+    pub n: usize,
+    /// Standard error of the estimate (means only).
+    #[serde(skip)]
+    std_error: f64,
+}
+
+impl Estimate {
+    /// A proportion with a Wilson score interval, which stays within 0-1
+    /// and behaves well for small samples.
+    fn proportion(successes: usize, n: usize) -> Self {
+        if n == 0 {
+            return Self::default();
+        }
+        let p = successes as f64 / n as f64;
+        let nf = n as f64;
+        let z2 = Z_95 * Z_95;
+        let center = (p + z2 / (2.0 * nf)) / (1.0 + z2 / nf);
+        let margin = Z_95 / (1.0 + z2 / nf) * (p * (1.0 - p) / nf + z2 / (4.0 * nf * nf)).sqrt();
+        Self {
+            value: p,
+            low: (center - margin).max(0.0),
+            high: (center + margin).min(1.0),
+            n,
+            std_error: (p * (1.0 - p) / nf).sqrt(),
+        }
+    }
+
+    /// A mean with a normal-approximation interval.
+    fn mean(values: &[f64]) -> Self {
+        let n = values.len();
+        if n == 0 {
+            return Self::default();
+        }
+        let mean = values.iter().sum::<f64>() / n as f64;
+        let variance = if n > 1 {
+            values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1) as f64
+        } else {
+            0.0
+        };
+        let std_error = (variance / n as f64).sqrt();
+        Self {
+            value: mean,
+            low: mean - Z_95 * std_error,
+            high: mean + Z_95 * std_error,
+            n,
+            std_error,
+        }
+    }
+}
+
+/// Difference between two cohorts for one metric.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MetricComparison {
+    /// Cohort B's value minus cohort A's.
+    pub difference: f64,
+    /// Lower bound of the 95% confidence interval for the difference.
+    pub low: f64,
+    /// Upper bound of the 95% confidence interval for the difference.
+    pub high: f64,
+    /// Two-sided p-value for the hypothesis that the cohorts don't differ.
+    pub p_value: f64,
+    /// Whether the difference is significant at the 5% level.
+    pub significant: bool,
+}
 
-            curr[j] = (prev[j] + 1) // deletion
-                .min(curr[j - 1] + 1) // insertion
-                .min(prev[j - 1] + cost); // substitution
+impl MetricComparison {
+    fn new(difference: f64, low: f64, high: f64, z: f64) -> Self {
+        let p_value = if z.is_finite() {
+            (2.0 * (1.0 - normal_cdf(z.abs()))).clamp(0.0, 1.0)
+        } else if difference == 0.0 {
+            1.0
+        } else {
+            0.0
+        };
+        Self {
+            difference,
+            low,
+            high,
+            p_value,
+            significant: p_value < 0.05,
         }
+    }
+}
+
+/// Compare proportions with Newcombe's interval for the difference and a
+/// pooled two-proportion z-test.
+fn compare_proportions(a: &Estimate, b: &Estimate) -> Option<MetricComparison> {
+    if a.n == 0 || b.n == 0 {
+        return None;
+    }
+    let difference = b.value - a.value;
+    let low = difference - ((b.value - b.low).powi(2) + (a.high - a.value).powi(2)).sqrt();
+    let high = difference + ((b.high - b.value).powi(2) + (a.value - a.low).powi(2)).sqrt();
+
+    let (na, nb) = (a.n as f64, b.n as f64);
+    let pooled = (a.value * na + b.value * nb) / (na + nb);
+    let std_error = (pooled * (1.0 - pooled) * (1.0 / na + 1.0 / nb)).sqrt();
+    Some(MetricComparison::new(
+        difference,
+        low,
+        high,
+        difference / std_error,
+    ))
+}
 
-        std::mem::swap(&mut prev, &mut curr);
+/// Compare means with Welch's standard error and a normal approximation.
+fn compare_means(a: &Estimate, b: &Estimate) -> Option<MetricComparison> {
+    if a.n == 0 || b.n == 0 {
+        return None;
     }
+    let difference = b.value - a.value;
+    let std_error = (a.std_error.powi(2) + b.std_error.powi(2)).sqrt();
+    Some(MetricComparison::new(
+        difference,
+        difference - Z_95 * std_error,
+        difference + Z_95 * std_error,
+        difference / std_error,
+    ))
+}
+
+/// Standard normal cumulative distribution function.
+fn normal_cdf(x: f64) -> f64 {
+    // Abramowitz and Stegun 7.1.26, accurate to about 1.5e-7
+    let t = 1.0 / (1.0 + 0.3275911 * x.abs() / std::f64::consts::SQRT_2);
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-(x * x) / 2.0).exp();
+    if x >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
 
-    prev[n]
+/// Distribution of notes over the tag hierarchy.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TagStats {
+    /// Number of notes matching the query.
+    pub total_notes: usize,
+    /// Number of matching notes with no tags.
+    pub untagged_notes: usize,
+    /// Depth of the deepest tag (0 when all tags are top-level).
+    pub max_depth: usize,
+    /// Every tag, parents before children.
+    pub tags: Vec<TagStat>,
+}
+
+/// Note counts for one tag in a [`TagStats`] report.
+#[derive(Debug, Clone, Serialize)]
+pub struct TagStat {
+    /// The tag.
+    pub tag: String,
+    /// Depth in the hierarchy (0 for top-level tags).
+    pub depth: usize,
+    /// Notes with exactly this tag.
+    pub notes: usize,
+    /// Notes with this tag or any tag below it, each counted once.
+    pub total_notes: usize,
+    /// Number of direct child tags.
+    pub children: usize,
 }
 
 /// Comprehensive study report combining multiple statistics.
@@ -1080,6 +2718,21 @@ pub struct StudyReport {
     pub daily_stats: Vec<ReportDailyStats>,
 }
 
+impl WorkflowReport for StudyReport {
+    fn summary(&self) -> String {
+        format!(
+            "'{}' over {} days: {} reviews, {:.1}% retention, {} leeches",
+            self.deck,
+            self.period_days,
+            self.total_reviews,
+            self.retention_rate * 100.0,
+            self.leeches.len()
+        )
+    }
+
+    report_fields!();
+}
+
 /// Daily statistics for a study report.
 #[derive(Debug, Clone, Default, Serialize)]
 pub struct ReportDailyStats {
@@ -1098,6 +2751,13 @@ pub struct CompareOptions {
     /// Cards with similarity >= this value are considered similar.
     /// Set to 1.0 for exact matches only.
     pub similarity_threshold: f64,
+    /// How key values are normalized before matching.
+    pub normalization: KeyNormalization,
+    /// How normalized key values are scored for fuzzy matching.
+    pub algorithm: Algorithm,
+    /// Filter for candidate pairs in large decks. By default every pair of
+    /// unmatched notes is scored.
+    pub prefilter: Prefilter,
 }
 
 impl Default for CompareOptions {
@@ -1105,6 +2765,9 @@ impl Default for CompareOptions {
         Self {
             key_field: "Front".to_string(),
             similarity_threshold: 0.9,
+            normalization: KeyNormalization::default(),
+            algorithm: Algorithm::default(),
+            prefilter: Prefilter::default(),
         }
     }
 }
@@ -1216,6 +2879,8 @@ pub struct DeckAudit {
     pub review_cards: usize,
     /// Average ease factor (percentage * 10, e.g., 2500 = 250%).
     pub average_ease: f64,
+    /// Cards by maturity, with an interval histogram.
+    pub maturity: Maturity,
 }
 
 /// Options for generating a study plan.
@@ -1232,7 +2897,10 @@ pub struct PlanOptions {
     /// Estimated seconds per review card.
     pub seconds_per_review_card: u32,
     /// Leech threshold (minimum lapses to consider a card a leech).
-    pub leech_threshold: i64,
+    ///
+    /// None uses the engine's [`leech::LeechThresholdSource`], which by default reads the
+    /// threshold from each deck's options.
+    pub leech_threshold: Option<i64>,
 }
 
 impl Default for PlanOptions {
@@ -1243,7 +2911,7 @@ impl Default for PlanOptions {
             prioritize_leeches: true,
             seconds_per_new_card: 30,   // 30 seconds for new cards
             seconds_per_review_card: 8, // 8 seconds for reviews
-            leech_threshold: 8,
+            leech_threshold: None,
         }
     }
 }
@@ -1281,3 +2949,72 @@ enum CardPriority {
     /// New cards.
     New,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Milliseconds since the epoch for a UTC date and hour.
+    fn at(days: i64, hour: i64) -> i64 {
+        (days * 86400 + hour * 3600) * 1000
+    }
+
+    #[test]
+    fn test_civil_date() {
+        assert_eq!(civil_date(0), "1970-01-01");
+        assert_eq!(civil_date(19723), "2024-01-01");
+        assert_eq!(civil_date(19782), "2024-02-29");
+        assert_eq!(civil_date(-1), "1969-12-31");
+    }
+
+    #[test]
+    fn test_build_heatmap_streaks() {
+        let options = HeatmapOptions {
+            rollover_hour: 0,
+            ..Default::default()
+        };
+        // Days 100-102, a gap, then 104-105
+        let entries = [
+            (at(100, 10), 5000),
+            (at(100, 11), 3000),
+            (at(101, 10), 1000),
+            (at(102, 10), 1000),
+            (at(104, 10), 1000),
+            (at(105, 10), 1000),
+        ];
+
+        let heatmap = build_heatmap(&entries, &options, 106);
+        assert_eq!(heatmap.total_reviews, 6);
+        assert_eq!(heatmap.active_days, 5);
+        assert_eq!(heatmap.longest_streak, 3);
+        assert_eq!(heatmap.current_streak, 2);
+        assert_eq!(heatmap.days[0].reviews, 2);
+        assert_eq!(heatmap.days[0].time_ms, 8000);
+        assert_eq!(heatmap.by_hour[10], 5);
+
+        // Streak is broken once a whole day passes without reviews
+        assert_eq!(build_heatmap(&entries, &options, 107).current_streak, 0);
+    }
+
+    #[test]
+    fn test_build_heatmap_offset_and_rollover() {
+        // 02:00 UTC on a Thursday (day 0)
+        let entries = [(at(0, 2), 0)];
+
+        // Before the 04:00 rollover, so it counts toward Wednesday
+        let heatmap = build_heatmap(&entries, &HeatmapOptions::default(), 0);
+        assert_eq!(heatmap.days[0].date, "1969-12-31");
+        assert_eq!(heatmap.by_weekday[2], 1);
+        assert_eq!(heatmap.by_hour[2], 1);
+
+        // At UTC+3 it's 05:00 local time on Thursday
+        let options = HeatmapOptions {
+            utc_offset_minutes: 180,
+            ..Default::default()
+        };
+        let heatmap = build_heatmap(&entries, &options, 0);
+        assert_eq!(heatmap.days[0].date, "1970-01-01");
+        assert_eq!(heatmap.by_weekday[3], 1);
+        assert_eq!(heatmap.by_hour[5], 1);
+    }
+}
@@ -0,0 +1,249 @@
+//! Pronunciation annotators for reading enrichment.
+//!
+//! [`EnrichEngine::annotate`](crate::enrich::EnrichEngine::annotate) fills a
+//! field with a pronunciation derived from another field using an
+//! [`Annotator`]. Implement the trait to produce IPA, pinyin, or any other
+//! reading. With the `japanese` feature, [`JapaneseReadings`] generates kana
+//! readings or furigana with a morphological analyzer.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ankit_engine::Engine;
+//! use ankit_engine::annotate::Annotator;
+//! use ankit_engine::enrich::AnnotationField;
+//!
+//! /// Upper-cases text, standing in for a real pronunciation source.
+//! struct Shout;
+//!
+//! impl Annotator for Shout {
+//!     async fn annotate(&self, text: &str) -> ankit_engine::Result<String> {
+//!         Ok(text.to_uppercase())
+//!     }
+//! }
+//!
+//! # async fn example() -> ankit_engine::Result<()> {
+//! let engine = Engine::new();
+//!
+//! let field = AnnotationField::new("Front", "Pronunciation");
+//! let report = engine.enrich().annotate("deck:English", &field, &Shout).await?;
+//! println!("Annotated {} notes", report.notes_updated);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+
+use crate::Result;
+
+/// A source of pronunciation annotations.
+pub trait Annotator {
+    /// Produce the annotation for plain text.
+    ///
+    /// Returning an empty string leaves the note unchanged.
+    fn annotate(&self, text: &str) -> impl Future<Output = Result<String>> + Send;
+}
+
+#[cfg(feature = "japanese")]
+pub use japanese::{JapaneseReadings, ReadingStyle};
+
+#[cfg(feature = "japanese")]
+mod japanese {
+    use std::borrow::Cow;
+    use std::path::Path;
+
+    use lindera::dictionary::load_dictionary;
+    use lindera::mode::Mode;
+    use lindera::segmenter::Segmenter;
+
+    use super::Annotator;
+    use crate::{Error, Result};
+
+    /// How [`JapaneseReadings`] writes a reading.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum ReadingStyle {
+        /// The whole expression in hiragana (`日本語を話す` becomes
+        /// `にほんごをはなす`).
+        #[default]
+        Kana,
+        /// Anki furigana syntax, annotating only the kanji
+        /// (`日本語[にほんご]を 話[はな]す`).
+        Furigana,
+    }
+
+    /// An [`Annotator`] that generates Japanese readings.
+    ///
+    /// Text is split into words with [lindera](https://docs.rs/lindera) and
+    /// each word's dictionary reading is converted to hiragana. Words the
+    /// dictionary does not know are kept as written.
+    ///
+    /// The dictionary is loaded at runtime from a compiled lindera dictionary
+    /// such as IPADIC or UniDic, so it is not embedded in the binary.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ankit_engine::Engine;
+    /// use ankit_engine::annotate::{JapaneseReadings, ReadingStyle};
+    /// use ankit_engine::enrich::AnnotationField;
+    ///
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let readings = JapaneseReadings::open("dictionaries/ipadic")?.style(ReadingStyle::Furigana);
+    ///
+    /// let field = AnnotationField::new("Expression", "Reading");
+    /// let report = engine.enrich().annotate("deck:Japanese", &field, &readings).await?;
+    /// println!("Added readings to {} notes", report.notes_updated);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub struct JapaneseReadings {
+        segmenter: Segmenter,
+        style: ReadingStyle,
+    }
+
+    impl std::fmt::Debug for JapaneseReadings {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("JapaneseReadings")
+                .field("style", &self.style)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl JapaneseReadings {
+        /// Load a compiled lindera dictionary from a directory.
+        pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+            let path = path.as_ref();
+            let dictionary = load_dictionary(&path.to_string_lossy()).map_err(|e| {
+                Error::Annotation(format!(
+                    "failed to load dictionary {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+            Ok(Self {
+                segmenter: Segmenter::new(Mode::Normal, dictionary, None),
+                style: ReadingStyle::default(),
+            })
+        }
+
+        /// Set how readings are written.
+        pub fn style(mut self, style: ReadingStyle) -> Self {
+            self.style = style;
+            self
+        }
+
+        /// Generate the reading for `text`.
+        pub fn reading(&self, text: &str) -> Result<String> {
+            let tokens = self
+                .segmenter
+                .segment(Cow::Borrowed(text))
+                .map_err(|e| Error::Annotation(e.to_string()))?;
+
+            let mut out = String::with_capacity(text.len() * 2);
+            for mut token in tokens {
+                let surface = token.surface.to_string();
+                let reading = token
+                    .get("reading")
+                    .filter(|r| !r.is_empty() && *r != "*")
+                    .map(to_hiragana)
+                    .unwrap_or_else(|| surface.clone());
+                match self.style {
+                    ReadingStyle::Kana => out.push_str(&reading),
+                    ReadingStyle::Furigana => push_furigana(&mut out, &surface, &reading),
+                }
+            }
+            Ok(out)
+        }
+    }
+
+    impl Annotator for JapaneseReadings {
+        async fn annotate(&self, text: &str) -> Result<String> {
+            self.reading(text)
+        }
+    }
+
+    /// Convert katakana to hiragana, leaving other characters alone.
+    fn to_hiragana(text: &str) -> String {
+        text.chars()
+            .map(|c| match c {
+                'ァ'..='ヶ' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+                _ => c,
+            })
+            .collect()
+    }
+
+    /// Append `surface` with its reading in furigana syntax.
+    ///
+    /// Kana shared by the start or end of the surface and the reading is left
+    /// outside the brackets, so `食べる` becomes `食[た]べる`.
+    fn push_furigana(out: &mut String, surface: &str, reading: &str) {
+        if !surface.chars().any(is_kanji) {
+            out.push_str(surface);
+            return;
+        }
+
+        let surface: Vec<char> = surface.chars().collect();
+        let reading: Vec<char> = reading.chars().collect();
+        let hiragana: Vec<char> = to_hiragana(&surface.iter().collect::<String>())
+            .chars()
+            .collect();
+
+        let prefix = hiragana
+            .iter()
+            .zip(&reading)
+            .take_while(|(s, r)| s == r && !is_kanji(**s))
+            .count();
+        let suffix = hiragana[prefix..]
+            .iter()
+            .rev()
+            .zip(reading[prefix..].iter().rev())
+            .take_while(|(s, r)| s == r && !is_kanji(**s))
+            .count();
+        let base: String = surface[prefix..surface.len() - suffix].iter().collect();
+        let ruby: String = reading[prefix..reading.len() - suffix].iter().collect();
+
+        out.extend(&surface[..prefix]);
+        // A space marks where the annotated text starts
+        if prefix > 0 || !out.is_empty() {
+            out.push(' ');
+        }
+        out.push_str(&base);
+        if !ruby.is_empty() && ruby != base {
+            out.push('[');
+            out.push_str(&ruby);
+            out.push(']');
+        }
+        out.extend(&surface[surface.len() - suffix..]);
+    }
+
+    fn is_kanji(c: char) -> bool {
+        matches!(c, '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}' | '々' | '〆' | 'ヶ')
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn furigana(surface: &str, reading: &str) -> String {
+            let mut out = String::new();
+            push_furigana(&mut out, surface, reading);
+            out
+        }
+
+        #[test]
+        fn test_to_hiragana() {
+            assert_eq!(to_hiragana("ニホンゴ"), "にほんご");
+            assert_eq!(to_hiragana("コーヒー"), "こーひー");
+            assert_eq!(to_hiragana("abc"), "abc");
+        }
+
+        #[test]
+        fn test_furigana() {
+            assert_eq!(furigana("日本語", "にほんご"), "日本語[にほんご]");
+            assert_eq!(furigana("食べる", "たべる"), "食[た]べる");
+            assert_eq!(furigana("お茶", "おちゃ"), "お 茶[ちゃ]");
+            assert_eq!(furigana("です", "です"), "です");
+        }
+    }
+}
@@ -1,7 +1,8 @@
 //! Backup and restore workflows for Anki decks.
 //!
 //! This module provides high-level operations for backing up and restoring
-//! Anki decks to/from .apkg files.
+//! Anki decks to/from .apkg files, and for taking collection-wide
+//! [snapshots](BackupEngine::snapshot) that can be restored selectively.
 //!
 //! # Example
 //!
@@ -25,19 +26,68 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! # Snapshots
+//!
+//! A snapshot stores every deck's notes, tags, card scheduling, and options
+//! as JSON, in a directory like this:
+//!
+//! ```text
+//! snapshot-20240115-093000/
+//!   manifest.json         format version, per-deck counts and SHA-256 checksums
+//!   decks/
+//!     0001-Default.json
+//!     0002-Japanese__Vocab.json
+//! ```
+//!
+//! Unlike `.apkg` backups, snapshots can be restored in part: one deck, or
+//! only the notes with a tag or matching a search.
+//!
+//! ```no_run
+//! use ankit_engine::Engine;
+//! use ankit_engine::backup::RestoreFilter;
+//!
+//! # async fn example() -> ankit_engine::Result<()> {
+//! let engine = Engine::new();
+//!
+//! let snapshot = engine.backup().snapshot("/home/user/anki-snapshots").await?;
+//!
+//! // Later: put back only the verbs in the Japanese deck
+//! let filter = RestoreFilter::default().deck("Japanese").tag("verb");
+//! let report = engine.backup().restore_snapshot(&snapshot.path, &filter).await?;
+//! println!("Restored {} notes", report.notes_updated + report.notes_recreated);
+//! # Ok(())
+//! # }
+//! ```
 
-use crate::{Error, Result};
-use ankit::AnkiClient;
+use crate::changes::PlannedChange;
+use crate::journal::{self, CardState, Journal};
+use crate::report::{WorkflowReport, report_fields};
+use crate::{EngineOptions, Error, NoteBuilder, Result};
+use ankit::{AnkiClient, CardInfo, DeckConfig};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
+/// Version of the snapshot layout written by [`BackupEngine::snapshot`].
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// Name of the manifest file in a snapshot directory.
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Number of note IDs per `nid:` search when checking which notes exist.
+const NID_CHUNK_SIZE: usize = 500;
+
 /// Engine for backup and restore operations.
 pub struct BackupEngine<'a> {
     client: &'a AnkiClient,
+    options: &'a EngineOptions,
 }
 
 impl<'a> BackupEngine<'a> {
-    pub(crate) fn new(client: &'a AnkiClient) -> Self {
-        Self { client }
+    pub(crate) fn new(client: &'a AnkiClient, options: &'a EngineOptions) -> Self {
+        Self { client, options }
     }
 
     /// Backup a deck to an .apkg file.
@@ -308,7 +358,7 @@ impl<'a> BackupEngine<'a> {
         collect_apkg_files(backup_dir, &mut backups)?;
 
         // Sort by modification time (newest first)
-        backups.sort_by(|a, b| b.modified.cmp(&a.modified));
+        backups.sort_by_key(|b| std::cmp::Reverse(b.modified));
 
         Ok(backups)
     }
@@ -361,6 +411,449 @@ impl<'a> BackupEngine<'a> {
 
         Ok(deleted)
     }
+
+    /// Snapshot every deck in the collection.
+    ///
+    /// Writes a timestamped directory under `snapshot_dir` with one JSON file
+    /// per deck (notes, tags, card scheduling, and deck options) and a
+    /// manifest recording a SHA-256 checksum of each file. Sub-decks get
+    /// their own files; each deck file holds only the cards directly in it.
+    ///
+    /// The manifest is written last, so an interrupted snapshot is never
+    /// listed by [`list_snapshots()`](Self::list_snapshots).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ankit_engine::Engine;
+    ///
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let result = engine.backup().snapshot("/home/user/anki-snapshots").await?;
+    /// println!(
+    ///     "Snapshot of {} notes in {} decks: {}",
+    ///     result.notes,
+    ///     result.decks,
+    ///     result.path.display()
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn snapshot(&self, snapshot_dir: impl AsRef<Path>) -> Result<SnapshotResult> {
+        let created_at = unix_now();
+        let path = snapshot_dir
+            .as_ref()
+            .join(format!("snapshot-{}", chrono_lite_timestamp()));
+        let decks_dir = path.join("decks");
+        std::fs::create_dir_all(&decks_dir).map_err(|e| {
+            Error::Backup(format!(
+                "Failed to create snapshot directory '{}': {}",
+                decks_dir.display(),
+                e
+            ))
+        })?;
+
+        let mut deck_names = self
+            .client
+            .decks()
+            .names()
+            .await
+            .map_err(|e| Error::Backup(format!("Failed to list decks: {}", e)))?;
+        deck_names.sort();
+
+        let mut manifest = SnapshotManifest {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            created_at,
+            decks: Vec::new(),
+        };
+        for (index, deck) in deck_names.iter().enumerate() {
+            let snapshot = self
+                .snapshot_deck(deck)
+                .await
+                .map_err(|e| Error::Backup(format!("Failed to snapshot deck '{}': {}", deck, e)))?;
+            let file = format!("decks/{:04}-{}.json", index + 1, sanitize_filename(deck));
+            let bytes = serde_json::to_vec(&snapshot).map_err(|e| {
+                Error::Backup(format!("Failed to serialize deck '{}': {}", deck, e))
+            })?;
+            std::fs::write(path.join(&file), &bytes)?;
+
+            manifest.decks.push(SnapshotEntry {
+                deck_name: deck.clone(),
+                file,
+                notes: snapshot.notes.len(),
+                cards: snapshot.notes.iter().map(|n| n.cards.len()).sum(),
+                sha256: sha256_hex(&bytes),
+            });
+        }
+
+        let json = serde_json::to_string_pretty(&manifest)
+            .map_err(|e| Error::Backup(format!("Failed to serialize manifest: {}", e)))?;
+        std::fs::write(path.join(MANIFEST_FILE), json)?;
+
+        Ok(SnapshotResult {
+            path,
+            decks: manifest.decks.len(),
+            notes: manifest.decks.iter().map(|d| d.notes).sum(),
+            cards: manifest.decks.iter().map(|d| d.cards).sum(),
+        })
+    }
+
+    /// Capture the notes, scheduling, and options of a single deck.
+    async fn snapshot_deck(&self, deck: &str) -> Result<DeckSnapshot> {
+        let query = format!("deck:\"{}\" -deck:\"{}::*\"", deck, deck);
+        let card_ids = self.client.cards().find(&query).await?;
+        let cards: Vec<CardInfo> = if card_ids.is_empty() {
+            Vec::new()
+        } else {
+            self.client.cards().info(&card_ids).await?
+        };
+
+        let mut by_note: HashMap<i64, Vec<CardState>> = HashMap::new();
+        for card in &cards {
+            by_note
+                .entry(card.note_id)
+                .or_default()
+                .push(CardState::from(card));
+        }
+        let mut note_ids: Vec<i64> = by_note.keys().copied().collect();
+        note_ids.sort_unstable();
+
+        let notes = if note_ids.is_empty() {
+            Vec::new()
+        } else {
+            self.client.notes().info(&note_ids).await?
+        };
+        let notes = notes
+            .into_iter()
+            .map(|note| {
+                let mut cards = by_note.remove(&note.note_id).unwrap_or_default();
+                cards.sort_by_key(|c| c.card_id);
+                SnapshotNote {
+                    note_id: note.note_id,
+                    model: note.model_name,
+                    fields: note
+                        .fields
+                        .into_iter()
+                        .map(|(name, field)| (name, field.value))
+                        .collect(),
+                    tags: note.tags,
+                    cards,
+                }
+            })
+            .collect();
+
+        Ok(DeckSnapshot {
+            deck_name: deck.to_string(),
+            // Filtered decks have no options group
+            config: self.client.decks().config(deck).await.ok(),
+            notes,
+        })
+    }
+
+    /// List snapshots in a directory, newest first.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ankit_engine::Engine;
+    ///
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// for snapshot in engine.backup().list_snapshots("/home/user/anki-snapshots").await? {
+    ///     println!("{}: {} notes", snapshot.path.display(), snapshot.notes);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_snapshots(
+        &self,
+        snapshot_dir: impl AsRef<Path>,
+    ) -> Result<Vec<SnapshotInfo>> {
+        let snapshot_dir = snapshot_dir.as_ref();
+        if !snapshot_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let entries = std::fs::read_dir(snapshot_dir).map_err(|e| {
+            Error::Backup(format!(
+                "Failed to read directory '{}': {}",
+                snapshot_dir.display(),
+                e
+            ))
+        })?;
+
+        let mut snapshots = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.join(MANIFEST_FILE).is_file() {
+                continue;
+            }
+            let manifest = load_manifest(&path)?;
+            snapshots.push(SnapshotInfo {
+                created_at: manifest.created_at,
+                format_version: manifest.format_version,
+                decks: manifest.decks.len(),
+                notes: manifest.decks.iter().map(|d| d.notes).sum(),
+                cards: manifest.decks.iter().map(|d| d.cards).sum(),
+                path,
+            });
+        }
+
+        snapshots.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+        Ok(snapshots)
+    }
+
+    /// Check a snapshot's files against the checksums in its manifest.
+    ///
+    /// Returns the files that are missing or whose contents changed. An empty
+    /// list means the snapshot is intact.
+    pub async fn verify_snapshot(&self, snapshot: impl AsRef<Path>) -> Result<Vec<String>> {
+        let snapshot = snapshot.as_ref();
+        let manifest = load_manifest(snapshot)?;
+        Ok(corrupt_files(snapshot, manifest.decks.iter()))
+    }
+
+    /// Restore notes from a snapshot.
+    ///
+    /// Notes that still exist get their fields and tags written back; notes
+    /// that were deleted are re-created in their deck. Card scheduling is
+    /// restored too unless [`RestoreFilter::scheduling`] is turned off.
+    /// Notes in Anki that are not in the snapshot are left alone.
+    ///
+    /// The filter selects what to restore: whole decks (including their
+    /// sub-decks), notes with a tag, or notes currently matching a search.
+    /// Checksums of the selected deck files are verified first, and the
+    /// restore is refused if any file is corrupt.
+    ///
+    /// Respects [`EngineOptions::dry_run`] and records the overwritten fields
+    /// and scheduling in a journal when [`EngineOptions::journal_dir`] is set.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ankit_engine::Engine;
+    /// use ankit_engine::backup::RestoreFilter;
+    ///
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let filter = RestoreFilter::default().query("deck:Japanese is:suspended");
+    /// let report = engine
+    ///     .backup()
+    ///     .restore_snapshot("/home/user/anki-snapshots/snapshot-20240115-093000", &filter)
+    ///     .await?;
+    /// println!("Updated {} notes", report.notes_updated);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn restore_snapshot(
+        &self,
+        snapshot: impl AsRef<Path>,
+        filter: &RestoreFilter,
+    ) -> Result<SnapshotRestoreReport> {
+        let snapshot = snapshot.as_ref();
+        let manifest = load_manifest(snapshot)?;
+        let mut report = SnapshotRestoreReport {
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        // Select deck files
+        for deck in &filter.decks {
+            if !manifest.decks.iter().any(|e| in_deck(&e.deck_name, deck)) {
+                return Err(Error::DeckNotFound(deck.clone()));
+            }
+        }
+        let entries: Vec<&SnapshotEntry> = manifest
+            .decks
+            .iter()
+            .filter(|e| {
+                filter.decks.is_empty() || filter.decks.iter().any(|d| in_deck(&e.deck_name, d))
+            })
+            .collect();
+        let corrupt = corrupt_files(snapshot, entries.iter().copied());
+        if !corrupt.is_empty() {
+            return Err(Error::Backup(format!(
+                "Snapshot '{}' failed integrity check: {}",
+                snapshot.display(),
+                corrupt.join(", ")
+            )));
+        }
+        let decks = entries
+            .iter()
+            .map(|e| load_deck(&snapshot.join(&e.file)))
+            .collect::<Result<Vec<_>>>()?;
+
+        // Select notes; a note with cards in several decks appears in each
+        let allowed: Option<HashSet<i64>> = match &filter.query {
+            Some(query) => Some(self.client.notes().find(query).await?.into_iter().collect()),
+            None => None,
+        };
+        let mut seen = HashSet::new();
+        let mut selected: Vec<(&str, &SnapshotNote)> = Vec::new();
+        for deck in &decks {
+            for note in &deck.notes {
+                let tag_match = filter
+                    .tag
+                    .as_ref()
+                    .is_none_or(|tag| note.tags.iter().any(|t| tag_matches(t, tag)));
+                let query_match = allowed
+                    .as_ref()
+                    .is_none_or(|ids| ids.contains(&note.note_id));
+                if tag_match && query_match && seen.insert(note.note_id) {
+                    selected.push((&deck.deck_name, note));
+                }
+            }
+        }
+        report.notes_matched = selected.len();
+
+        let ids: Vec<i64> = selected.iter().map(|(_, n)| n.note_id).collect();
+        let existing = self.existing_notes(&ids).await?;
+        let (present, missing): (Vec<_>, Vec<_>) = selected
+            .into_iter()
+            .partition(|(_, note)| existing.contains(&note.note_id));
+
+        if report.dry_run {
+            for (_, note) in &present {
+                report.planned.push(PlannedChange::UpdateNoteFields {
+                    note_id: note.note_id,
+                    fields: note.fields.clone(),
+                });
+            }
+            for (deck, note) in &missing {
+                let mut builder = NoteBuilder::new(*deck, &note.model).tags(note.tags.clone());
+                for (name, value) in &note.fields {
+                    builder = builder.field(name, value);
+                }
+                report.planned.push(PlannedChange::AddNote {
+                    note: builder.build(),
+                });
+            }
+            report.notes_updated = present.len();
+            report.notes_recreated = missing.len();
+            return Ok(report);
+        }
+
+        // Cards of present notes that still exist, keyed by ID
+        let present_ids: Vec<i64> = present.iter().map(|(_, n)| n.note_id).collect();
+        let current_cards: HashSet<i64> = if present_ids.is_empty() {
+            HashSet::new()
+        } else {
+            self.client
+                .notes()
+                .info(&present_ids)
+                .await?
+                .into_iter()
+                .flat_map(|n| n.cards)
+                .collect()
+        };
+
+        if let Some(dir) = &self.options.journal_dir {
+            if !present_ids.is_empty() {
+                let mut record = Journal::new("restore_snapshot");
+                record.entries = journal::record_fields(self.client, &present_ids).await?;
+                if filter.scheduling && !current_cards.is_empty() {
+                    let card_ids: Vec<i64> = current_cards.iter().copied().collect();
+                    record
+                        .entries
+                        .extend(journal::record_scheduling(self.client, &card_ids).await?);
+                }
+                report.journal = Some(record.write(dir)?);
+            }
+        }
+
+        for (_, note) in present {
+            if let Err(e) = self
+                .client
+                .notes()
+                .update(note.note_id, Some(&note.fields), Some(&note.tags))
+                .await
+            {
+                report
+                    .failures
+                    .push(format!("note {}: {}", note.note_id, e));
+                continue;
+            }
+            report.notes_updated += 1;
+
+            if filter.scheduling {
+                for state in note
+                    .cards
+                    .iter()
+                    .filter(|c| current_cards.contains(&c.card_id))
+                {
+                    match journal::restore_scheduling(self.client, state.card_id, state).await {
+                        Ok(()) => report.cards_rescheduled += 1,
+                        Err(e) => report
+                            .failures
+                            .push(format!("card {}: {}", state.card_id, e)),
+                    }
+                }
+            }
+        }
+
+        let mut created_decks = HashSet::new();
+        for (deck, note) in missing {
+            if created_decks.insert(deck) {
+                self.client.decks().create(deck).await?;
+            }
+            let new_cards = match journal::recreate_note(
+                self.client,
+                deck,
+                &note.model,
+                note.fields.clone(),
+                note.tags.clone(),
+            )
+            .await
+            {
+                Ok(cards) => cards,
+                Err(e) => {
+                    report
+                        .failures
+                        .push(format!("note {}: {}", note.note_id, e));
+                    continue;
+                }
+            };
+            report.notes_recreated += 1;
+
+            if filter.scheduling {
+                for (card_id, state) in new_cards.into_iter().zip(&note.cards) {
+                    match journal::restore_scheduling(self.client, card_id, state).await {
+                        Ok(()) => report.cards_rescheduled += 1,
+                        Err(e) => report
+                            .failures
+                            .push(format!("card {}: {}", state.card_id, e)),
+                    }
+                }
+            }
+        }
+
+        if filter.deck_config {
+            for config in decks.iter().filter_map(|d| d.config.as_ref()) {
+                match self.client.decks().save_config(config).await {
+                    Ok(true) => report.configs_restored += 1,
+                    Ok(false) => report
+                        .failures
+                        .push(format!("deck options '{}': not saved", config.name)),
+                    Err(e) => report
+                        .failures
+                        .push(format!("deck options '{}': {}", config.name, e)),
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Which of the given notes still exist in the collection.
+    async fn existing_notes(&self, note_ids: &[i64]) -> Result<HashSet<i64>> {
+        let mut existing = HashSet::new();
+        for chunk in note_ids.chunks(NID_CHUNK_SIZE) {
+            let ids: Vec<String> = chunk.iter().map(i64::to_string).collect();
+            let query = format!("nid:{}", ids.join(","));
+            existing.extend(self.client.notes().find(&query).await?);
+        }
+        Ok(existing)
+    }
 }
 
 /// Options for backup operations.
@@ -380,7 +873,7 @@ impl Default for BackupOptions {
 }
 
 /// Result of a deck backup operation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct BackupResult {
     /// Path to the created backup file.
     pub path: PathBuf,
@@ -392,8 +885,21 @@ pub struct BackupResult {
     pub include_scheduling: bool,
 }
 
+impl WorkflowReport for BackupResult {
+    fn summary(&self) -> String {
+        format!(
+            "Backed up '{}' to {} ({} bytes)",
+            self.deck_name,
+            self.path.display(),
+            self.size_bytes
+        )
+    }
+
+    report_fields!();
+}
+
 /// Result of a restore operation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct RestoreResult {
     /// Path to the restored backup file.
     pub path: PathBuf,
@@ -401,8 +907,20 @@ pub struct RestoreResult {
     pub success: bool,
 }
 
+impl WorkflowReport for RestoreResult {
+    fn summary(&self) -> String {
+        if self.success {
+            format!("Restored {}", self.path.display())
+        } else {
+            format!("Failed to restore {}", self.path.display())
+        }
+    }
+
+    report_fields!();
+}
+
 /// Result of a collection backup operation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct CollectionBackupResult {
     /// Directory containing the backup files.
     pub backup_dir: PathBuf,
@@ -412,6 +930,26 @@ pub struct CollectionBackupResult {
     pub failed: Vec<(String, String)>,
 }
 
+impl WorkflowReport for CollectionBackupResult {
+    fn summary(&self) -> String {
+        format!(
+            "Backed up {} decks to {} ({} failed)",
+            self.successful.len(),
+            self.backup_dir.display(),
+            self.failed.len()
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.failed
+            .iter()
+            .map(|(deck, error)| format!("{}: {}", deck, error))
+            .collect()
+    }
+
+    report_fields!();
+}
+
 /// Information about a backup file.
 #[derive(Debug, Clone)]
 pub struct BackupInfo {
@@ -423,6 +961,297 @@ pub struct BackupInfo {
     pub modified: u64,
 }
 
+/// Result of a collection snapshot.
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotResult {
+    /// Directory containing the snapshot.
+    pub path: PathBuf,
+    /// Number of decks captured.
+    pub decks: usize,
+    /// Number of notes captured.
+    pub notes: usize,
+    /// Number of cards captured.
+    pub cards: usize,
+}
+
+impl WorkflowReport for SnapshotResult {
+    fn summary(&self) -> String {
+        format!(
+            "Snapshot of {} decks, {} notes, and {} cards written to {}",
+            self.decks,
+            self.notes,
+            self.cards,
+            self.path.display()
+        )
+    }
+
+    report_fields!();
+}
+
+/// Summary of a snapshot found by [`BackupEngine::list_snapshots`].
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotInfo {
+    /// Directory containing the snapshot.
+    pub path: PathBuf,
+    /// Creation time (Unix timestamp, seconds).
+    pub created_at: u64,
+    /// Layout version of the snapshot.
+    pub format_version: u32,
+    /// Number of decks captured.
+    pub decks: usize,
+    /// Number of notes captured.
+    pub notes: usize,
+    /// Number of cards captured.
+    pub cards: usize,
+}
+
+/// Contents of a snapshot's `manifest.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    /// Layout version; see [`SNAPSHOT_FORMAT_VERSION`].
+    pub format_version: u32,
+    /// Creation time (Unix timestamp, seconds).
+    pub created_at: u64,
+    /// One entry per deck file.
+    pub decks: Vec<SnapshotEntry>,
+}
+
+/// A deck file listed in a snapshot manifest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    /// Deck name.
+    pub deck_name: String,
+    /// Path of the deck file, relative to the snapshot directory.
+    pub file: String,
+    /// Number of notes in the file.
+    pub notes: usize,
+    /// Number of cards in the file.
+    pub cards: usize,
+    /// SHA-256 of the file contents, as lowercase hex.
+    pub sha256: String,
+}
+
+/// Contents of a snapshot deck file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeckSnapshot {
+    /// Deck name.
+    pub deck_name: String,
+    /// The deck's options group, if it has one.
+    pub config: Option<DeckConfig>,
+    /// Notes with cards in this deck.
+    pub notes: Vec<SnapshotNote>,
+}
+
+/// A note captured in a snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotNote {
+    /// The note ID at snapshot time.
+    pub note_id: i64,
+    /// Note type name.
+    pub model: String,
+    /// Field values, keyed by field name.
+    pub fields: HashMap<String, String>,
+    /// Tags on the note.
+    pub tags: Vec<String>,
+    /// Scheduling of the note's cards in this deck.
+    pub cards: Vec<CardState>,
+}
+
+/// Selects what [`BackupEngine::restore_snapshot`] restores.
+///
+/// The default restores every note in the snapshot with its scheduling,
+/// but not deck options.
+#[derive(Debug, Clone)]
+pub struct RestoreFilter {
+    /// Decks to restore, including their sub-decks. Empty means all decks.
+    pub decks: Vec<String>,
+    /// Only restore notes with this tag (or a child tag).
+    pub tag: Option<String>,
+    /// Only restore notes currently matching this Anki search. Deleted notes
+    /// cannot match, so they are never re-created when a query is given.
+    pub query: Option<String>,
+    /// Restore card scheduling.
+    pub scheduling: bool,
+    /// Restore deck options groups.
+    pub deck_config: bool,
+}
+
+impl Default for RestoreFilter {
+    fn default() -> Self {
+        Self {
+            decks: Vec::new(),
+            tag: None,
+            query: None,
+            scheduling: true,
+            deck_config: false,
+        }
+    }
+}
+
+impl RestoreFilter {
+    /// Restore a deck and its sub-decks. Can be called more than once.
+    pub fn deck(mut self, deck: impl Into<String>) -> Self {
+        self.decks.push(deck.into());
+        self
+    }
+
+    /// Only restore notes with a tag.
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    /// Only restore notes currently matching an Anki search.
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.query = Some(query.into());
+        self
+    }
+
+    /// Set whether card scheduling is restored.
+    pub fn scheduling(mut self, scheduling: bool) -> Self {
+        self.scheduling = scheduling;
+        self
+    }
+
+    /// Set whether deck options groups are restored.
+    pub fn deck_config(mut self, deck_config: bool) -> Self {
+        self.deck_config = deck_config;
+        self
+    }
+}
+
+/// Report from restoring a snapshot.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SnapshotRestoreReport {
+    /// Number of snapshot notes selected by the filter.
+    pub notes_matched: usize,
+    /// Number of existing notes whose fields and tags were written back.
+    pub notes_updated: usize,
+    /// Number of deleted notes that were re-created.
+    pub notes_recreated: usize,
+    /// Number of cards whose scheduling was restored.
+    pub cards_rescheduled: usize,
+    /// Number of deck options groups restored.
+    pub configs_restored: usize,
+    /// Items that could not be restored, with the reason.
+    pub failures: Vec<String>,
+    /// Undo journal recorded before restoring, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for SnapshotRestoreReport {
+    fn summary(&self) -> String {
+        format!(
+            "Matched {} notes: updated {}, recreated {}, rescheduled {} cards, restored {} configs",
+            self.notes_matched,
+            self.notes_updated,
+            self.notes_recreated,
+            self.cards_rescheduled,
+            self.configs_restored
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.failures.clone()
+    }
+
+    report_fields!(dry_run, journal);
+}
+
+/// Read and check the manifest of a snapshot directory.
+fn load_manifest(snapshot: &Path) -> Result<SnapshotManifest> {
+    let path = snapshot.join(MANIFEST_FILE);
+    let contents = std::fs::read_to_string(&path).map_err(|e| {
+        Error::Backup(format!(
+            "Failed to read snapshot manifest '{}': {}",
+            path.display(),
+            e
+        ))
+    })?;
+    let manifest: SnapshotManifest = serde_json::from_str(&contents).map_err(|e| {
+        Error::Backup(format!(
+            "Failed to parse snapshot manifest '{}': {}",
+            path.display(),
+            e
+        ))
+    })?;
+    if manifest.format_version > SNAPSHOT_FORMAT_VERSION {
+        return Err(Error::Backup(format!(
+            "Snapshot '{}' uses format version {}, newer than supported version {}",
+            snapshot.display(),
+            manifest.format_version,
+            SNAPSHOT_FORMAT_VERSION
+        )));
+    }
+    Ok(manifest)
+}
+
+/// Read a snapshot deck file.
+fn load_deck(path: &Path) -> Result<DeckSnapshot> {
+    let contents = std::fs::read(path)?;
+    serde_json::from_slice(&contents).map_err(|e| {
+        Error::Backup(format!(
+            "Failed to parse snapshot deck '{}': {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+/// Files of a snapshot that are missing or don't match their checksum.
+fn corrupt_files<'m>(
+    snapshot: &Path,
+    entries: impl Iterator<Item = &'m SnapshotEntry>,
+) -> Vec<String> {
+    entries
+        .filter(|entry| {
+            std::fs::read(snapshot.join(&entry.file))
+                .map(|bytes| sha256_hex(&bytes) != entry.sha256)
+                .unwrap_or(true)
+        })
+        .map(|entry| entry.file.clone())
+        .collect()
+}
+
+/// SHA-256 of some bytes, as lowercase hex.
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Whether `deck` is `parent` or one of its sub-decks.
+fn in_deck(deck: &str, parent: &str) -> bool {
+    deck == parent
+        || deck
+            .strip_prefix(parent)
+            .is_some_and(|rest| rest.starts_with("::"))
+}
+
+/// Whether a note tag is `tag` or one of its child tags (case-insensitive,
+/// like Anki's tag search).
+fn tag_matches(note_tag: &str, tag: &str) -> bool {
+    let note_tag = note_tag.to_lowercase();
+    let tag = tag.to_lowercase();
+    note_tag == tag
+        || note_tag
+            .strip_prefix(&tag)
+            .is_some_and(|rest| rest.starts_with("::"))
+}
+
+/// Current Unix time in seconds.
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
 /// Generate a simple timestamp without external dependencies.
 fn chrono_lite_timestamp() -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -562,6 +1391,16 @@ mod tests {
         assert_eq!(days_to_ymd(19723), (2024, 1, 1));
     }
 
+    #[test]
+    fn test_in_deck_and_tag_matches() {
+        assert!(in_deck("Japanese", "Japanese"));
+        assert!(in_deck("Japanese::Vocab", "Japanese"));
+        assert!(!in_deck("Japanese2", "Japanese"));
+
+        assert!(tag_matches("Lang::JP::verb", "lang::jp"));
+        assert!(!tag_matches("language", "lang"));
+    }
+
     #[test]
     fn test_is_leap_year() {
         assert!(!is_leap_year(1970));
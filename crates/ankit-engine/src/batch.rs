@@ -0,0 +1,303 @@
+//! Request batching for read-heavy workflows.
+//!
+//! With [`EngineOptions::batching`](crate::EngineOptions::batching) on,
+//! workflows send reads that don't depend on each other in one `multi`
+//! request, and split info lookups for large id lists into chunks inside
+//! that request. Mutations are sent in chunks of [`MUTATION_CHUNK`] ids so
+//! Anki stays responsive. With batching off, every read is its own request.
+
+use ankit::{AnkiClient, CardInfo, MultiAction, NoteInfo};
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+
+use crate::{EngineOptions, Result};
+
+/// Ids per `cardsInfo`/`notesInfo` action inside a batched request.
+const INFO_CHUNK: usize = 500;
+
+/// Ids per mutation request when batching.
+pub(crate) const MUTATION_CHUNK: usize = 500;
+
+/// A read that can be sent alone or inside a `multi` request.
+///
+/// Each read knows the type of its results, so an answer can't be taken as
+/// the wrong kind.
+pub(crate) trait Read: Copy {
+    /// One element of the result.
+    type Item: DeserializeOwned;
+
+    /// The `multi` actions for this read. Id lists are chunked, and empty
+    /// ones need no action at all.
+    fn actions(self) -> Vec<MultiAction<'static>>;
+
+    /// Send the read as its own request.
+    async fn send(self, client: &AnkiClient) -> ankit::Result<Vec<Self::Item>>;
+}
+
+/// Card ids matching a search.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct FindCards<'q>(pub &'q str);
+
+/// Note ids matching a search.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct FindNotes<'q>(pub &'q str);
+
+/// Card info for card ids.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CardsInfo<'q>(pub &'q [i64]);
+
+/// Note info for note ids.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct NotesInfo<'q>(pub &'q [i64]);
+
+/// Ease factors for card ids.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Ease<'q>(pub &'q [i64]);
+
+impl Read for FindCards<'_> {
+    type Item = i64;
+
+    fn actions(self) -> Vec<MultiAction<'static>> {
+        vec![MultiAction::with_params(
+            "findCards",
+            json!({ "query": self.0 }),
+        )]
+    }
+
+    async fn send(self, client: &AnkiClient) -> ankit::Result<Vec<i64>> {
+        client.cards().find(self.0).await
+    }
+}
+
+impl Read for FindNotes<'_> {
+    type Item = i64;
+
+    fn actions(self) -> Vec<MultiAction<'static>> {
+        vec![MultiAction::with_params(
+            "findNotes",
+            json!({ "query": self.0 }),
+        )]
+    }
+
+    async fn send(self, client: &AnkiClient) -> ankit::Result<Vec<i64>> {
+        client.notes().find(self.0).await
+    }
+}
+
+// Empty id lists need no request
+impl Read for CardsInfo<'_> {
+    type Item = CardInfo;
+
+    fn actions(self) -> Vec<MultiAction<'static>> {
+        chunked("cardsInfo", "cards", self.0)
+    }
+
+    async fn send(self, client: &AnkiClient) -> ankit::Result<Vec<CardInfo>> {
+        match self.0 {
+            [] => Ok(Vec::new()),
+            ids => client.cards().info(ids).await,
+        }
+    }
+}
+
+impl Read for NotesInfo<'_> {
+    type Item = NoteInfo;
+
+    fn actions(self) -> Vec<MultiAction<'static>> {
+        chunked("notesInfo", "notes", self.0)
+    }
+
+    async fn send(self, client: &AnkiClient) -> ankit::Result<Vec<NoteInfo>> {
+        match self.0 {
+            [] => Ok(Vec::new()),
+            ids => client.notes().info(ids).await,
+        }
+    }
+}
+
+impl Read for Ease<'_> {
+    type Item = i64;
+
+    fn actions(self) -> Vec<MultiAction<'static>> {
+        chunked("getEaseFactors", "cards", self.0)
+    }
+
+    async fn send(self, client: &AnkiClient) -> ankit::Result<Vec<i64>> {
+        match self.0 {
+            [] => Ok(Vec::new()),
+            ids => client.cards().get_ease(ids).await,
+        }
+    }
+}
+
+/// Plans how a workflow's reads and writes are sent to AnkiConnect.
+pub(crate) struct Batcher<'a> {
+    client: &'a AnkiClient,
+    enabled: bool,
+}
+
+impl<'a> Batcher<'a> {
+    pub fn new(client: &'a AnkiClient, options: &EngineOptions) -> Self {
+        Self {
+            client,
+            enabled: options.batching,
+        }
+    }
+
+    /// Whether reads are merged into `multi` requests.
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Run independent reads of one kind, returning one result per read in
+    /// order.
+    pub async fn run<R: Read>(&self, reads: &[R]) -> Result<Vec<Vec<R::Item>>> {
+        let actions: Vec<Vec<MultiAction<'static>>> =
+            reads.iter().map(|read| read.actions()).collect();
+        if !self.merges(actions.iter().map(Vec::len).sum()) {
+            let mut results = Vec::with_capacity(reads.len());
+            for read in reads {
+                results.push(read.send(self.client).await?);
+            }
+            return Ok(results);
+        }
+
+        let counts: Vec<usize> = actions.iter().map(Vec::len).collect();
+        let mut responses = self.multi(actions.into_iter().flatten().collect()).await?;
+        counts
+            .into_iter()
+            .map(|count| collect(responses.by_ref().take(count)))
+            .collect()
+    }
+
+    /// Run two independent reads.
+    pub async fn pair<A: Read, B: Read>(
+        &self,
+        first: A,
+        second: B,
+    ) -> Result<(Vec<A::Item>, Vec<B::Item>)> {
+        let (first_actions, second_actions) = (first.actions(), second.actions());
+        if !self.merges(first_actions.len() + second_actions.len()) {
+            return Ok((
+                first.send(self.client).await?,
+                second.send(self.client).await?,
+            ));
+        }
+
+        let count = first_actions.len();
+        let mut responses = self
+            .multi(first_actions.into_iter().chain(second_actions).collect())
+            .await?;
+        Ok((
+            collect(responses.by_ref().take(count))?,
+            collect(responses)?,
+        ))
+    }
+
+    /// Split ids into the chunks a mutation should be sent in.
+    pub fn mutation_chunks<'s>(&self, ids: &'s [i64]) -> std::slice::Chunks<'s, i64> {
+        let size = if self.enabled {
+            MUTATION_CHUNK
+        } else {
+            ids.len()
+        };
+        ids.chunks(size.max(1))
+    }
+
+    /// Whether reads needing this many actions go in one `multi` request.
+    fn merges(&self, actions: usize) -> bool {
+        self.enabled && actions > 1
+    }
+
+    /// Send actions in one `multi` request, with one response per action.
+    async fn multi(&self, actions: Vec<MultiAction<'static>>) -> Result<std::vec::IntoIter<Value>> {
+        let responses = self.client.misc().multi(&actions).await?;
+        if responses.len() != actions.len() {
+            return Err(ankit::Error::AnkiConnect(format!(
+                "multi returned {} results for {} actions",
+                responses.len(),
+                actions.len()
+            ))
+            .into());
+        }
+        Ok(responses.into_iter())
+    }
+}
+
+fn chunked(action: &'static str, key: &str, ids: &[i64]) -> Vec<MultiAction<'static>> {
+    ids.chunks(INFO_CHUNK)
+        .map(|chunk| MultiAction::with_params(action, json!({ key: chunk })))
+        .collect()
+}
+
+/// The results of the chunks of one read, joined.
+fn collect<T: DeserializeOwned>(responses: impl Iterator<Item = Value>) -> Result<Vec<T>> {
+    let mut items = Vec::new();
+    for response in responses {
+        items.extend(unwrap::<Vec<T>>(response)?);
+    }
+    Ok(items)
+}
+
+/// The result of a `multi` response entry.
+///
+/// AnkiConnect wraps each result as `{"result": ..., "error": ...}`; older
+/// versions return the bare result.
+fn unwrap<T: DeserializeOwned>(response: Value) -> Result<T> {
+    let result = match response {
+        Value::Object(mut wrapped)
+            if wrapped.contains_key("result") || wrapped.contains_key("error") =>
+        {
+            if let Some(error) = wrapped.get("error").and_then(Value::as_str) {
+                return Err(ankit::Error::from_message(error).into());
+            }
+            wrapped.remove("result").unwrap_or(Value::Null)
+        }
+        other => other,
+    };
+    Ok(serde_json::from_value(result).map_err(ankit::Error::from)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_info_reads_are_chunked() {
+        let ids: Vec<i64> = (0..1200).collect();
+        let actions = CardsInfo(&ids).actions();
+        assert_eq!(actions.len(), 3);
+        assert_eq!(actions[2].params.as_ref().unwrap()["cards"][0], 1000);
+        assert!(NotesInfo(&[]).actions().is_empty());
+    }
+
+    #[test]
+    fn test_unwrap_handles_both_response_shapes() {
+        let ids: Vec<i64> = unwrap(json!({"result": [1, 2], "error": null})).unwrap();
+        assert_eq!(ids, vec![1, 2]);
+        let ids: Vec<i64> = unwrap(json!([3])).unwrap();
+        assert_eq!(ids, vec![3]);
+        assert!(unwrap::<Vec<i64>>(json!({"result": null, "error": "bad query"})).is_err());
+        assert!(matches!(
+            unwrap::<Vec<i64>>(json!({"result": null, "error": "deck was not found: Missing"})),
+            Err(crate::Error::Client(ankit::Error::DeckNotFound(deck))) if deck == "Missing"
+        ));
+    }
+
+    #[test]
+    fn test_mutation_chunks() {
+        let client = AnkiClient::new();
+        let ids: Vec<i64> = (0..1200).collect();
+
+        let off = Batcher::new(&client, &EngineOptions::default());
+        assert_eq!(off.mutation_chunks(&ids).count(), 1);
+
+        let options = EngineOptions {
+            batching: true,
+            ..Default::default()
+        };
+        let on = Batcher::new(&client, &options);
+        assert_eq!(on.mutation_chunks(&ids).count(), 3);
+        assert_eq!(on.mutation_chunks(&[]).count(), 0);
+    }
+}
@@ -0,0 +1,379 @@
+//! Typed change sets for dry-run reporting.
+//!
+//! When an [`Engine`](crate::Engine) is configured with
+//! [`EngineOptions::dry_run`](crate::EngineOptions::dry_run), mutating workflows
+//! skip every write and instead record the changes they would have made as a
+//! list of [`PlannedChange`] values on their report.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ankit_engine::{Engine, EngineOptions};
+//! use ankit_engine::changes::PlannedChange;
+//!
+//! # async fn example() -> ankit_engine::Result<()> {
+//! let engine = Engine::new().with_options(EngineOptions {
+//!     dry_run: true,
+//!     ..Default::default()
+//! });
+//!
+//! let report = engine.progress().reset_deck("Japanese").await?;
+//! for change in &report.planned {
+//!     if let PlannedChange::ForgetCards { card_ids } = change {
+//!         println!("Would reset {} cards", card_ids.len());
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{CardAnswer, Note};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+
+/// A single write that a workflow would perform.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum PlannedChange {
+    /// Create a deck (no-op if it already exists).
+    CreateDeck {
+        /// Name of the deck.
+        deck: String,
+    },
+    /// Create a filtered deck from a search.
+    CreateFilteredDeck {
+        /// Name of the filtered deck.
+        deck: String,
+        /// Search that gathers the cards.
+        search: String,
+        /// Maximum number of cards to gather.
+        limit: usize,
+    },
+    /// Delete decks (and any cards left in them).
+    DeleteDecks {
+        /// Names of the decks.
+        decks: Vec<String>,
+    },
+    /// Move cards into a deck.
+    MoveCards {
+        /// Cards to move.
+        card_ids: Vec<i64>,
+        /// Destination deck.
+        deck: String,
+    },
+    /// Add a new note.
+    AddNote {
+        /// The note that would be added.
+        note: Note,
+    },
+    /// Overwrite field values on an existing note.
+    UpdateNoteFields {
+        /// The note to update.
+        note_id: i64,
+        /// New field values, keyed by field name.
+        fields: HashMap<String, String>,
+    },
+    /// Delete notes (and all of their cards).
+    DeleteNotes {
+        /// Notes to delete.
+        note_ids: Vec<i64>,
+    },
+    /// Reset cards to the new state.
+    ForgetCards {
+        /// Cards to reset.
+        card_ids: Vec<i64>,
+    },
+    /// Answer cards, in order.
+    AnswerCards {
+        /// Answers to submit.
+        answers: Vec<CardAnswer>,
+    },
+    /// Store a file in the media folder.
+    StoreMedia {
+        /// Name of the media file.
+        filename: String,
+    },
+    /// Delete a file from the media folder.
+    DeleteMedia {
+        /// Name of the media file.
+        filename: String,
+    },
+    /// Suspend cards.
+    SuspendCards {
+        /// Cards to suspend.
+        card_ids: Vec<i64>,
+    },
+    /// Unsuspend cards.
+    UnsuspendCards {
+        /// Cards to unsuspend.
+        card_ids: Vec<i64>,
+    },
+    /// Bury cards until the next day.
+    BuryCards {
+        /// Cards to bury.
+        card_ids: Vec<i64>,
+    },
+    /// Unbury cards.
+    UnburyCards {
+        /// Cards to unbury.
+        card_ids: Vec<i64>,
+    },
+    /// Set the ease factor of cards.
+    SetEase {
+        /// Cards to update.
+        card_ids: Vec<i64>,
+        /// New ease factor for each card, in the same order (e.g., 2500 = 250%).
+        ease_factors: Vec<i64>,
+    },
+    /// Set the interval of cards.
+    SetIntervals {
+        /// Cards to update.
+        card_ids: Vec<i64>,
+        /// New interval in days for each card, in the same order.
+        intervals: Vec<i64>,
+    },
+    /// Set the due date of cards.
+    SetDueDate {
+        /// Cards to reschedule.
+        card_ids: Vec<i64>,
+        /// Due date specification, as accepted by `setDueDate` (e.g., "0", "1-7").
+        days: String,
+    },
+    /// Shift the due date of cards by a number of days.
+    ShiftDue {
+        /// Cards to reschedule.
+        card_ids: Vec<i64>,
+        /// Days to shift by; negative moves cards earlier.
+        days: i64,
+        /// Whether intervals are left unchanged, rather than shifted too.
+        preserve_interval: bool,
+    },
+    /// Move new cards within the new card queue.
+    RepositionCards {
+        /// Cards to move.
+        card_ids: Vec<i64>,
+        /// New queue position for each card, in the same order.
+        positions: Vec<i64>,
+    },
+    /// Add a field to a model.
+    AddField {
+        /// Model to change.
+        model: String,
+        /// Name of the new field.
+        field: String,
+        /// Position of the new field (0-based), or the end if `None`.
+        index: Option<i32>,
+    },
+    /// Rename a field of a model.
+    RenameField {
+        /// Model to change.
+        model: String,
+        /// Current field name.
+        old: String,
+        /// New field name.
+        new: String,
+    },
+    /// Move a field of a model to another position.
+    RepositionField {
+        /// Model to change.
+        model: String,
+        /// Field to move.
+        field: String,
+        /// New position (0-based).
+        index: i32,
+    },
+    /// Remove a field, and its content on every note, from a model.
+    RemoveField {
+        /// Model to change.
+        model: String,
+        /// Field to remove.
+        field: String,
+    },
+    /// Copy a deck, with its note types and media, into another profile.
+    CopyDeckToProfile {
+        /// Deck to copy.
+        deck: String,
+        /// Profile to copy it into.
+        profile: String,
+        /// Number of notes in the deck.
+        notes: usize,
+    },
+    /// Add tags to notes.
+    AddTags {
+        /// Notes to tag.
+        note_ids: Vec<i64>,
+        /// Space-separated tags to add.
+        tags: String,
+    },
+    /// Remove tags from notes.
+    RemoveTags {
+        /// Notes to untag.
+        note_ids: Vec<i64>,
+        /// Space-separated tags to remove.
+        tags: String,
+    },
+    /// Replace one tag with another on notes.
+    ReplaceTags {
+        /// Notes to update.
+        note_ids: Vec<i64>,
+        /// Tag to replace.
+        old: String,
+        /// Replacement tag.
+        new: String,
+    },
+}
+
+impl PlannedChange {
+    /// IDs of existing notes this change would modify or delete.
+    pub fn note_ids(&self) -> Vec<i64> {
+        match self {
+            PlannedChange::UpdateNoteFields { note_id, .. } => vec![*note_id],
+            PlannedChange::DeleteNotes { note_ids }
+            | PlannedChange::AddTags { note_ids, .. }
+            | PlannedChange::RemoveTags { note_ids, .. }
+            | PlannedChange::ReplaceTags { note_ids, .. } => note_ids.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// IDs of existing cards this change would modify.
+    pub fn card_ids(&self) -> Vec<i64> {
+        match self {
+            PlannedChange::MoveCards { card_ids, .. }
+            | PlannedChange::ForgetCards { card_ids }
+            | PlannedChange::SuspendCards { card_ids }
+            | PlannedChange::UnsuspendCards { card_ids }
+            | PlannedChange::BuryCards { card_ids }
+            | PlannedChange::UnburyCards { card_ids }
+            | PlannedChange::SetEase { card_ids, .. }
+            | PlannedChange::SetIntervals { card_ids, .. }
+            | PlannedChange::SetDueDate { card_ids, .. }
+            | PlannedChange::ShiftDue { card_ids, .. }
+            | PlannedChange::RepositionCards { card_ids, .. } => card_ids.clone(),
+            PlannedChange::AnswerCards { answers } => {
+                answers.iter().map(|answer| answer.card_id).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for PlannedChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlannedChange::CreateDeck { deck } => write!(f, "create deck '{}'", deck),
+            PlannedChange::CreateFilteredDeck {
+                deck,
+                search,
+                limit,
+            } => write!(
+                f,
+                "create filtered deck '{}' with up to {} cards from '{}'",
+                deck, limit, search
+            ),
+            PlannedChange::DeleteDecks { decks } => {
+                write!(f, "delete {} decks: {}", decks.len(), decks.join(", "))
+            }
+            PlannedChange::MoveCards { card_ids, deck } => {
+                write!(f, "move {} cards to '{}'", card_ids.len(), deck)
+            }
+            PlannedChange::AddNote { note } => {
+                write!(
+                    f,
+                    "add a '{}' note to '{}'",
+                    note.model_name, note.deck_name
+                )
+            }
+            PlannedChange::UpdateNoteFields { note_id, fields } => {
+                let mut names: Vec<&str> = fields.keys().map(String::as_str).collect();
+                names.sort_unstable();
+                write!(f, "update note {}: {}", note_id, names.join(", "))
+            }
+            PlannedChange::DeleteNotes { note_ids } => {
+                write!(f, "delete {} notes", note_ids.len())
+            }
+            PlannedChange::ForgetCards { card_ids } => {
+                write!(f, "reset {} cards to new", card_ids.len())
+            }
+            PlannedChange::AnswerCards { answers } => {
+                write!(f, "answer {} cards", answers.len())
+            }
+            PlannedChange::StoreMedia { filename } => write!(f, "store media '{}'", filename),
+            PlannedChange::DeleteMedia { filename } => write!(f, "delete media '{}'", filename),
+            PlannedChange::SuspendCards { card_ids } => {
+                write!(f, "suspend {} cards", card_ids.len())
+            }
+            PlannedChange::UnsuspendCards { card_ids } => {
+                write!(f, "unsuspend {} cards", card_ids.len())
+            }
+            PlannedChange::BuryCards { card_ids } => {
+                write!(f, "bury {} cards", card_ids.len())
+            }
+            PlannedChange::UnburyCards { card_ids } => {
+                write!(f, "unbury {} cards", card_ids.len())
+            }
+            PlannedChange::SetEase { card_ids, .. } => {
+                write!(f, "set ease on {} cards", card_ids.len())
+            }
+            PlannedChange::SetIntervals { card_ids, .. } => {
+                write!(f, "set interval on {} cards", card_ids.len())
+            }
+            PlannedChange::SetDueDate { card_ids, days } => {
+                write!(f, "set {} cards due in '{}' days", card_ids.len(), days)
+            }
+            PlannedChange::ShiftDue { card_ids, days, .. } => {
+                write!(f, "shift {} cards due by {:+} days", card_ids.len(), days)
+            }
+            PlannedChange::RepositionCards { card_ids, .. } => {
+                write!(f, "reposition {} new cards", card_ids.len())
+            }
+            PlannedChange::AddField { model, field, .. } => {
+                write!(f, "add field '{}' to model '{}'", field, model)
+            }
+            PlannedChange::RenameField { model, old, new } => {
+                write!(
+                    f,
+                    "rename field '{}' to '{}' in model '{}'",
+                    old, new, model
+                )
+            }
+            PlannedChange::RepositionField {
+                model,
+                field,
+                index,
+            } => write!(
+                f,
+                "move field '{}' to position {} in model '{}'",
+                field, index, model
+            ),
+            PlannedChange::RemoveField { model, field } => {
+                write!(f, "remove field '{}' from model '{}'", field, model)
+            }
+            PlannedChange::CopyDeckToProfile {
+                deck,
+                profile,
+                notes,
+            } => write!(
+                f,
+                "copy deck '{}' ({} notes) into profile '{}'",
+                deck, notes, profile
+            ),
+            PlannedChange::AddTags { note_ids, tags } => {
+                write!(f, "add tags '{}' to {} notes", tags, note_ids.len())
+            }
+            PlannedChange::RemoveTags { note_ids, tags } => {
+                write!(f, "remove tags '{}' from {} notes", tags, note_ids.len())
+            }
+            PlannedChange::ReplaceTags { note_ids, old, new } => {
+                write!(
+                    f,
+                    "rename tag '{}' to '{}' on {} notes",
+                    old,
+                    new,
+                    note_ids.len()
+                )
+            }
+        }
+    }
+}
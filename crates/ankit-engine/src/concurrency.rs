@@ -0,0 +1,20 @@
+//! Bounded concurrency for bulk AnkiConnect calls.
+
+use futures_util::stream::{self, StreamExt};
+use std::future::Future;
+
+/// Run `f` on every item with at most `limit` calls in flight, returning the
+/// results in the order of `items`.
+///
+/// A `limit` of 0 is treated as 1, which runs the calls one at a time.
+pub(crate) async fn bounded<I, F, Fut>(limit: usize, items: I, f: F) -> Vec<Fut::Output>
+where
+    I: IntoIterator,
+    F: FnMut(I::Item) -> Fut,
+    Fut: Future,
+{
+    // Create the futures up front; they don't start until polled. Keeping
+    // the closure out of the stream type lets callers stay `Send`.
+    let futures: Vec<Fut> = items.into_iter().map(f).collect();
+    stream::iter(futures).buffered(limit.max(1)).collect().await
+}
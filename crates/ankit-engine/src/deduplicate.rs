@@ -1,7 +1,10 @@
 //! Duplicate note detection and removal.
 //!
 //! This module provides workflows for finding and removing duplicate notes
-//! based on a key field.
+//! based on a key field. Keys match exactly by default; with
+//! [`MatchMode::Fuzzy`], near-identical keys (typos, stray punctuation,
+//! leftover HTML) are grouped as well. A [`MergeStrategy`] carries tags and
+//! field content from the deleted duplicates over to the note that is kept.
 //!
 //! # Example
 //!
@@ -17,6 +20,8 @@
 //!     search: "deck:Japanese".to_string(),
 //!     key_field: "Front".to_string(),
 //!     keep: KeepStrategy::First,
+//!     match_mode: Default::default(),
+//!     normalization: Default::default(),
 //! };
 //!
 //! let groups = engine.deduplicate().find_duplicates(&query).await?;
@@ -29,10 +34,16 @@
 //! # }
 //! ```
 
-use crate::Result;
-use ankit::AnkiClient;
+use crate::changes::PlannedChange;
+use crate::journal::{self, Journal};
+use crate::report::{AffectedIds, WorkflowReport, report_fields};
+use crate::similarity::{Algorithm, Prefilter, levenshtein_similarity, pairs};
+use crate::{EngineOptions, Error, Result};
+use ankit::key::KeyNormalization;
+use ankit::{AnkiClient, NoteInfo};
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 
 /// Strategy for which duplicate to keep.
 #[derive(Debug, Clone, Copy, Default)]
@@ -57,6 +68,131 @@ pub struct DedupeQuery {
     pub key_field: String,
     /// Strategy for which duplicate to keep.
     pub keep: KeepStrategy,
+    /// How key values are compared.
+    pub match_mode: MatchMode,
+    /// How key values are normalized before comparing.
+    pub normalization: KeyNormalization,
+}
+
+/// How key field values are compared when looking for duplicates.
+#[derive(Debug, Clone, Default)]
+pub enum MatchMode {
+    /// Keys must be equal after stripping HTML, collapsing whitespace, and
+    /// lowercasing.
+    #[default]
+    Exact,
+    /// Keys must be at least as similar as the threshold.
+    Fuzzy(FuzzyOptions),
+}
+
+/// Options for [`MatchMode::Fuzzy`].
+///
+/// Similarity is scored with `algorithm`, by default normalized Levenshtein
+/// distance: 1.0 for identical keys, 0.9 when one character in ten differs.
+/// Matches chain, so if A matches B and B matches C, all three form one
+/// group.
+///
+/// To avoid comparing every pair of notes, Levenshtein candidates are first
+/// filtered by shared character n-grams and by length. The filter never
+/// drops a real match. It prunes most pairs when `threshold` is above
+/// `1 - 1/ngram_size` (about 0.67 for the default trigrams); below that, and
+/// for the other algorithms, every pair of keys is compared unless
+/// `prefilter` is set to [`Prefilter::Lsh`].
+#[derive(Debug, Clone)]
+pub struct FuzzyOptions {
+    /// Minimum similarity for two keys to match, from 0.0 to 1.0.
+    pub threshold: f64,
+    /// Remove HTML tags before comparing.
+    pub strip_html: bool,
+    /// Compare case-insensitively.
+    pub case_fold: bool,
+    /// Length of the character n-grams used to find candidate pairs.
+    pub ngram_size: usize,
+    /// How keys are scored.
+    pub algorithm: Algorithm,
+    /// Approximate filter for candidate pairs, replacing the n-gram filter.
+    pub prefilter: Prefilter,
+}
+
+impl Default for FuzzyOptions {
+    fn default() -> Self {
+        Self {
+            threshold: 0.85,
+            strip_html: true,
+            case_fold: true,
+            ngram_size: 3,
+            algorithm: Algorithm::default(),
+            prefilter: Prefilter::default(),
+        }
+    }
+}
+
+impl FuzzyOptions {
+    /// Fuzzy matching with the given threshold and default normalization.
+    pub fn with_threshold(threshold: f64) -> Self {
+        Self {
+            threshold,
+            ..Default::default()
+        }
+    }
+
+    /// Normalize a key value according to these options, on top of the
+    /// query's normalization.
+    fn normalize(&self, value: &str, base: &KeyNormalization) -> String {
+        KeyNormalization {
+            strip_html: self.strip_html,
+            case_fold: self.case_fold,
+            ..base.clone()
+        }
+        .apply(value)
+    }
+}
+
+/// What to carry over from deleted duplicates to the kept note.
+///
+/// The default merges nothing, so duplicates are simply deleted.
+///
+/// # Example
+///
+/// ```
+/// use ankit_engine::deduplicate::MergeStrategy;
+///
+/// // Keep every tag, fill blanks, and collect differing values in "Also"
+/// let merge = MergeStrategy::all().also_field("Also");
+/// assert!(merge.union_tags && merge.fill_empty);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MergeStrategy {
+    /// Add the duplicates' tags to the kept note.
+    pub union_tags: bool,
+    /// Fill empty fields of the kept note with the duplicates' values.
+    pub fill_empty: bool,
+    /// Field of the kept note to append differing values to, separated by
+    /// `<br>`. Values already present in the kept note are skipped, and
+    /// nothing is appended if the kept note has no such field.
+    pub also_field: Option<String>,
+}
+
+impl MergeStrategy {
+    /// Union tags and fill empty fields.
+    pub fn all() -> Self {
+        Self {
+            union_tags: true,
+            fill_empty: true,
+            also_field: None,
+        }
+    }
+
+    /// Append differing values to the given field.
+    pub fn also_field(mut self, field: impl Into<String>) -> Self {
+        self.also_field = Some(field.into());
+        self
+    }
+
+    /// Whether this strategy changes the kept note at all.
+    fn merges(&self) -> bool {
+        self.union_tags || self.fill_empty || self.also_field.is_some()
+    }
 }
 
 /// A group of duplicate notes.
@@ -72,10 +208,10 @@ pub struct DuplicateGroup {
 
 /// Information about a note for duplicate comparison.
 #[derive(Debug, Clone)]
-struct NoteForDedupe {
-    note_id: i64,
-    non_empty_count: usize,
-    tag_count: usize,
+pub(crate) struct NoteForDedupe {
+    pub(crate) note_id: i64,
+    pub(crate) non_empty_count: usize,
+    pub(crate) tag_count: usize,
 }
 
 /// Report from a deduplication operation.
@@ -87,19 +223,47 @@ pub struct DedupeReport {
     pub deleted: usize,
     /// Number of notes kept (one per group).
     pub kept: usize,
+    /// Number of kept notes updated with content from their duplicates.
+    pub merged: usize,
     /// Details about deleted notes per key.
     pub details: Vec<DuplicateGroup>,
+    /// Undo journal recorded before deleting, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for DedupeReport {
+    fn summary(&self) -> String {
+        format!(
+            "{} duplicate groups: deleted {} notes, kept {}, merged {}",
+            self.groups_found, self.deleted, self.kept, self.merged
+        )
+    }
+
+    fn affected(&self) -> AffectedIds {
+        let mut ids = AffectedIds::from_planned(&self.planned);
+        for group in &self.details {
+            ids.note_ids.extend(&group.duplicate_note_ids);
+        }
+        ids.normalize()
+    }
+
+    report_fields!(dry_run, journal);
 }
 
 /// Deduplication workflow engine.
 #[derive(Debug)]
 pub struct DeduplicateEngine<'a> {
     client: &'a AnkiClient,
+    options: &'a EngineOptions,
 }
 
 impl<'a> DeduplicateEngine<'a> {
-    pub(crate) fn new(client: &'a AnkiClient) -> Self {
-        Self { client }
+    pub(crate) fn new(client: &'a AnkiClient, options: &'a EngineOptions) -> Self {
+        Self { client, options }
     }
 
     /// Find groups of duplicate notes.
@@ -123,6 +287,8 @@ impl<'a> DeduplicateEngine<'a> {
     ///     search: "deck:Vocabulary".to_string(),
     ///     key_field: "Word".to_string(),
     ///     keep: KeepStrategy::MostContent,
+    ///     match_mode: Default::default(),
+    ///     normalization: Default::default(),
     /// };
     ///
     /// let groups = engine.deduplicate().find_duplicates(&query).await?;
@@ -141,23 +307,14 @@ impl<'a> DeduplicateEngine<'a> {
         }
 
         let note_infos = self.client.notes().info(&note_ids).await?;
-
-        // Group notes by key field value
-        let mut groups: HashMap<String, Vec<NoteForDedupe>> = HashMap::new();
-
-        for info in note_infos {
+        let notes = note_infos.iter().map(|info| {
             // Get the key field value
-            let key_value = info
+            let raw = info
                 .fields
                 .get(&query.key_field)
-                .map(|f| normalize_key(&f.value))
+                .map(|f| f.value.as_str())
                 .unwrap_or_default();
 
-            // Skip notes with empty key
-            if key_value.is_empty() {
-                continue;
-            }
-
             // Count non-empty fields
             let non_empty_count = info
                 .fields
@@ -165,61 +322,14 @@ impl<'a> DeduplicateEngine<'a> {
                 .filter(|f| !f.value.trim().is_empty())
                 .count();
 
-            groups.entry(key_value).or_default().push(NoteForDedupe {
+            let note = NoteForDedupe {
                 note_id: info.note_id,
                 non_empty_count,
                 tag_count: info.tags.len(),
-            });
-        }
-
-        // Convert to DuplicateGroups (only groups with more than one note)
-        let mut result = Vec::new();
-
-        for (key, mut notes) in groups {
-            if notes.len() <= 1 {
-                continue;
-            }
-
-            // Sort notes based on keep strategy
-            match query.keep {
-                KeepStrategy::First => {
-                    notes.sort_by_key(|n| n.note_id);
-                }
-                KeepStrategy::Last => {
-                    notes.sort_by_key(|n| std::cmp::Reverse(n.note_id));
-                }
-                KeepStrategy::MostContent => {
-                    // Sort by non-empty count descending, then by note_id ascending for ties
-                    notes.sort_by(|a, b| {
-                        b.non_empty_count
-                            .cmp(&a.non_empty_count)
-                            .then_with(|| a.note_id.cmp(&b.note_id))
-                    });
-                }
-                KeepStrategy::MostTags => {
-                    // Sort by tag count descending, then by note_id ascending for ties
-                    notes.sort_by(|a, b| {
-                        b.tag_count
-                            .cmp(&a.tag_count)
-                            .then_with(|| a.note_id.cmp(&b.note_id))
-                    });
-                }
-            }
-
-            let keep_note_id = notes[0].note_id;
-            let duplicate_note_ids: Vec<i64> = notes[1..].iter().map(|n| n.note_id).collect();
-
-            result.push(DuplicateGroup {
-                key_value: key,
-                keep_note_id,
-                duplicate_note_ids,
-            });
-        }
-
-        // Sort by key for consistent output
-        result.sort_by(|a, b| a.key_value.cmp(&b.key_value));
-
-        Ok(result)
+            };
+            (raw, note)
+        });
+        group_duplicates(query, notes)
     }
 
     /// Preview deduplication without making changes.
@@ -235,6 +345,7 @@ impl<'a> DeduplicateEngine<'a> {
             deleted,
             kept: groups.len(),
             details: groups,
+            ..Default::default()
         })
     }
 
@@ -258,6 +369,8 @@ impl<'a> DeduplicateEngine<'a> {
     ///     search: "deck:Vocabulary tag:imported".to_string(),
     ///     key_field: "Word".to_string(),
     ///     keep: KeepStrategy::MostContent,
+    ///     match_mode: Default::default(),
+    ///     normalization: Default::default(),
     /// };
     ///
     /// let report = engine.deduplicate().remove_duplicates(&query).await?;
@@ -266,10 +379,54 @@ impl<'a> DeduplicateEngine<'a> {
     /// # }
     /// ```
     pub async fn remove_duplicates(&self, query: &DedupeQuery) -> Result<DedupeReport> {
+        self.remove_duplicates_with(query, &MergeStrategy::default())
+            .await
+    }
+
+    /// Remove duplicate notes, first merging them into the kept note.
+    ///
+    /// Before anything is deleted, each kept note is updated according to
+    /// `merge`: duplicates are visited in keep-strategy order, so when
+    /// filling empty fields the best-ranked duplicate wins. With journaling
+    /// enabled, the kept notes' fields and tags are recorded along with the
+    /// deleted notes, so a rollback undoes both.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # use ankit_engine::deduplicate::{DedupeQuery, KeepStrategy, MergeStrategy};
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    ///
+    /// let query = DedupeQuery {
+    ///     search: "deck:Vocabulary".to_string(),
+    ///     key_field: "Word".to_string(),
+    ///     keep: KeepStrategy::MostContent,
+    ///     match_mode: Default::default(),
+    ///     normalization: Default::default(),
+    /// };
+    ///
+    /// let merge = MergeStrategy::all().also_field("Also");
+    /// let report = engine.deduplicate().remove_duplicates_with(&query, &merge).await?;
+    /// println!("Merged {} notes, deleted {}", report.merged, report.deleted);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn remove_duplicates_with(
+        &self,
+        query: &DedupeQuery,
+        merge: &MergeStrategy,
+    ) -> Result<DedupeReport> {
         let groups = self.find_duplicates(query).await?;
 
+        let dry_run = self.options.dry_run;
+
         if groups.is_empty() {
-            return Ok(DedupeReport::default());
+            return Ok(DedupeReport {
+                dry_run,
+                ..Default::default()
+            });
         }
 
         // Collect all note IDs to delete
@@ -280,20 +437,113 @@ impl<'a> DeduplicateEngine<'a> {
 
         let deleted_count = to_delete.len();
         let kept_count = groups.len();
+        let merges = if merge.merges() {
+            self.plan_merges(&groups, merge).await?
+        } else {
+            Vec::new()
+        };
+        let merged_ids: Vec<i64> = merges.iter().map(|(id, _, _)| *id).collect();
 
-        // Delete the duplicates
-        if !to_delete.is_empty() {
+        // Merge into the kept notes, then delete the duplicates
+        let mut planned = Vec::new();
+        let mut journal_path = None;
+        if dry_run {
+            for (note_id, fields, tags) in merges {
+                if !fields.is_empty() {
+                    planned.push(PlannedChange::UpdateNoteFields { note_id, fields });
+                }
+                if !tags.is_empty() {
+                    planned.push(PlannedChange::AddTags {
+                        note_ids: vec![note_id],
+                        tags: tags.join(" "),
+                    });
+                }
+            }
+            planned.push(PlannedChange::DeleteNotes {
+                note_ids: to_delete,
+            });
+        } else {
+            if let Some(dir) = &self.options.journal_dir {
+                let mut record = Journal::new("remove_duplicates");
+                record.entries = journal::record_fields(self.client, &merged_ids).await?;
+                record
+                    .entries
+                    .extend(journal::record_tags(self.client, &merged_ids).await?);
+                record
+                    .entries
+                    .extend(journal::record_notes(self.client, &to_delete).await?);
+                journal_path = Some(record.write(dir)?);
+            }
+            let tracker = self.options.track("merging notes", merges.len());
+            for (note_id, fields, tags) in &merges {
+                tracker.check()?;
+                if !fields.is_empty() {
+                    self.client.notes().update_fields(*note_id, fields).await?;
+                }
+                if !tags.is_empty() {
+                    self.client
+                        .notes()
+                        .add_tags(&[*note_id], &tags.join(" "))
+                        .await?;
+                }
+                tracker.advance(1);
+            }
+            let tracker = self.options.track("deleting notes", to_delete.len());
+            tracker.check()?;
             self.client.notes().delete(&to_delete).await?;
+            tracker.advance(to_delete.len());
         }
 
         Ok(DedupeReport {
             groups_found: groups.len(),
             deleted: deleted_count,
             kept: kept_count,
+            merged: merged_ids.len(),
             details: groups,
+            journal: journal_path,
+            dry_run,
+            planned,
         })
     }
 
+    /// Work out the field and tag changes for each kept note, skipping
+    /// notes that would not change.
+    async fn plan_merges(
+        &self,
+        groups: &[DuplicateGroup],
+        merge: &MergeStrategy,
+    ) -> Result<Vec<(i64, HashMap<String, String>, Vec<String>)>> {
+        let ids: Vec<i64> = groups
+            .iter()
+            .flat_map(|g| std::iter::once(g.keep_note_id).chain(g.duplicate_note_ids.clone()))
+            .collect();
+        let infos: HashMap<i64, NoteInfo> = self
+            .client
+            .notes()
+            .info(&ids)
+            .await?
+            .into_iter()
+            .map(|info| (info.note_id, info))
+            .collect();
+
+        let mut merges = Vec::new();
+        for group in groups {
+            let Some(kept) = infos.get(&group.keep_note_id) else {
+                continue;
+            };
+            let duplicates: Vec<&NoteInfo> = group
+                .duplicate_note_ids
+                .iter()
+                .filter_map(|id| infos.get(id))
+                .collect();
+            let (fields, tags) = merge_notes(kept, &duplicates, merge);
+            if !fields.is_empty() || !tags.is_empty() {
+                merges.push((kept.note_id, fields, tags));
+            }
+        }
+        Ok(merges)
+    }
+
     /// Delete specific duplicate notes.
     ///
     /// Use this after reviewing the results from `find_duplicates` to selectively
@@ -302,9 +552,12 @@ impl<'a> DeduplicateEngine<'a> {
     /// # Arguments
     ///
     /// * `note_ids` - Note IDs to delete
+    ///
+    /// In dry-run mode nothing is deleted and the number of notes that would
+    /// be deleted is returned.
     pub async fn delete_notes(&self, note_ids: &[i64]) -> Result<usize> {
-        if note_ids.is_empty() {
-            return Ok(0);
+        if note_ids.is_empty() || self.options.dry_run {
+            return Ok(note_ids.len());
         }
 
         self.client.notes().delete(note_ids).await?;
@@ -312,29 +565,314 @@ impl<'a> DeduplicateEngine<'a> {
     }
 }
 
+/// Group notes by their raw key field values, keeping one note per group
+/// according to the query's strategy.
+pub(crate) fn group_duplicates<'a>(
+    query: &DedupeQuery,
+    notes: impl IntoIterator<Item = (&'a str, NoteForDedupe)>,
+) -> Result<Vec<DuplicateGroup>> {
+    let mut keyed = Vec::new();
+    for (raw, note) in notes {
+        let key_value = match &query.match_mode {
+            MatchMode::Exact => query.normalization.apply(raw),
+            MatchMode::Fuzzy(options) => options.normalize(raw, &query.normalization),
+        };
+
+        // Skip notes with empty key
+        if key_value.is_empty() {
+            continue;
+        }
+        keyed.push((key_value, note));
+    }
+
+    // Group notes by key field value
+    let groups: Vec<Vec<(String, NoteForDedupe)>> = match &query.match_mode {
+        MatchMode::Exact => {
+            let mut groups: HashMap<String, Vec<(String, NoteForDedupe)>> = HashMap::new();
+            for (key, note) in keyed {
+                groups.entry(key.clone()).or_default().push((key, note));
+            }
+            groups.into_values().collect()
+        }
+        MatchMode::Fuzzy(options) => {
+            let keys: Vec<&str> = keyed.iter().map(|(k, _)| k.as_str()).collect();
+            fuzzy_clusters(&keys, options)?
+                .into_iter()
+                .map(|members| members.into_iter().map(|i| keyed[i].clone()).collect())
+                .collect()
+        }
+    };
+
+    // Convert to DuplicateGroups (only groups with more than one note)
+    let mut result = Vec::new();
+
+    for mut notes in groups {
+        if notes.len() <= 1 {
+            continue;
+        }
+
+        // Sort notes based on keep strategy
+        match query.keep {
+            KeepStrategy::First => {
+                notes.sort_by_key(|(_, n)| n.note_id);
+            }
+            KeepStrategy::Last => {
+                notes.sort_by_key(|(_, n)| std::cmp::Reverse(n.note_id));
+            }
+            KeepStrategy::MostContent => {
+                // Sort by non-empty count descending, then by note_id ascending for ties
+                notes.sort_by(|(_, a), (_, b)| {
+                    b.non_empty_count
+                        .cmp(&a.non_empty_count)
+                        .then_with(|| a.note_id.cmp(&b.note_id))
+                });
+            }
+            KeepStrategy::MostTags => {
+                // Sort by tag count descending, then by note_id ascending for ties
+                notes.sort_by(|(_, a), (_, b)| {
+                    b.tag_count
+                        .cmp(&a.tag_count)
+                        .then_with(|| a.note_id.cmp(&b.note_id))
+                });
+            }
+        }
+
+        // With fuzzy matching, keys differ; report the kept note's
+        let (key, kept) = &notes[0];
+        let duplicate_note_ids: Vec<i64> = notes[1..].iter().map(|(_, n)| n.note_id).collect();
+
+        result.push(DuplicateGroup {
+            key_value: key.clone(),
+            keep_note_id: kept.note_id,
+            duplicate_note_ids,
+        });
+    }
+
+    // Sort by key for consistent output
+    result.sort_by(|a, b| a.key_value.cmp(&b.key_value));
+
+    Ok(result)
+}
+
 /// Normalize a key value for comparison.
 ///
 /// Strips HTML, collapses whitespace, and converts to lowercase.
 fn normalize_key(value: &str) -> String {
-    // Simple HTML stripping (remove tags)
-    let mut result = String::with_capacity(value.len());
-    let mut in_tag = false;
-
-    for ch in value.chars() {
-        match ch {
-            '<' => in_tag = true,
-            '>' => in_tag = false,
-            _ if !in_tag => result.push(ch),
-            _ => {}
+    KeyNormalization::default().apply(value)
+}
+
+/// Merge duplicates into the kept note, returning the fields that change
+/// (with their new values) and the tags to add.
+fn merge_notes(
+    kept: &NoteInfo,
+    duplicates: &[&NoteInfo],
+    merge: &MergeStrategy,
+) -> (HashMap<String, String>, Vec<String>) {
+    let also = merge
+        .also_field
+        .as_deref()
+        .filter(|name| kept.fields.contains_key(*name));
+
+    let mut names: Vec<&String> = kept
+        .fields
+        .keys()
+        .filter(|name| Some(name.as_str()) != also)
+        .collect();
+    names.sort_by_key(|name| kept.fields[*name].order);
+
+    let mut values: HashMap<String, String> = kept
+        .fields
+        .iter()
+        .map(|(name, field)| (name.clone(), field.value.clone()))
+        .collect();
+
+    // Values already on the kept note, including earlier alternates
+    let mut seen: HashSet<String> = kept
+        .fields
+        .values()
+        .flat_map(|field| field.value.split("<br>"))
+        .map(normalize_key)
+        .collect();
+    let mut alternates = Vec::new();
+
+    for duplicate in duplicates {
+        for name in &names {
+            let Some(field) = duplicate.fields.get(*name) else {
+                continue;
+            };
+            if field.value.trim().is_empty() {
+                continue;
+            }
+            if merge.fill_empty && values[*name].trim().is_empty() {
+                values.insert((*name).clone(), field.value.clone());
+                seen.insert(normalize_key(&field.value));
+            } else if also.is_some() && seen.insert(normalize_key(&field.value)) {
+                alternates.push(field.value.clone());
+            }
+        }
+    }
+
+    if let Some(also) = also {
+        if !alternates.is_empty() {
+            let value = values.get_mut(also).expect("also field exists");
+            if !value.trim().is_empty() {
+                value.push_str("<br>");
+            }
+            value.push_str(&alternates.join("<br>"));
+        }
+    }
+
+    let fields = values
+        .into_iter()
+        .filter(|(name, value)| kept.fields[name].value != *value)
+        .collect();
+
+    let mut tags = Vec::new();
+    if merge.union_tags {
+        let mut have: HashSet<String> = kept.tags.iter().map(|t| t.to_lowercase()).collect();
+        for duplicate in duplicates {
+            for tag in &duplicate.tags {
+                if have.insert(tag.to_lowercase()) {
+                    tags.push(tag.clone());
+                }
+            }
+        }
+    }
+
+    (fields, tags)
+}
+
+/// Group keys whose similarity reaches the threshold, returning clusters of
+/// two or more key indices.
+fn fuzzy_clusters(keys: &[&str], options: &FuzzyOptions) -> Result<Vec<Vec<usize>>> {
+    if !(0.0..=1.0).contains(&options.threshold) {
+        return Err(Error::Validation(format!(
+            "fuzzy threshold must be between 0.0 and 1.0, got {}",
+            options.threshold
+        )));
+    }
+    if options.ngram_size == 0 {
+        return Err(Error::Validation(
+            "fuzzy n-gram size must be at least 1".to_string(),
+        ));
+    }
+
+    let mut parent: Vec<usize> = (0..keys.len()).collect();
+    if options.algorithm == Algorithm::Levenshtein && options.prefilter == Prefilter::None {
+        join_ngram_matches(keys, options, &mut parent);
+    } else {
+        for pair in pairs(
+            keys,
+            options.algorithm,
+            options.threshold,
+            options.prefilter,
+        ) {
+            let (a, b) = (
+                find_root(&mut parent, pair.left),
+                find_root(&mut parent, pair.right),
+            );
+            parent[a] = b;
+        }
+    }
+
+    let mut clusters: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..keys.len() {
+        let root = find_root(&mut parent, i);
+        clusters.entry(root).or_default().push(i);
+    }
+    Ok(clusters.into_values().filter(|c| c.len() > 1).collect())
+}
+
+/// Join keys within the Levenshtein threshold in `parent`.
+///
+/// Candidate pairs come from an inverted index of character n-grams. By the
+/// q-gram lemma, two strings within edit distance `d` share at least
+/// `max_len - n + 1 - d * n` n-grams (counting repeats), so any pair sharing
+/// fewer can be skipped without computing its distance. Keys for which that
+/// bound is not positive are compared against every other key.
+fn join_ngram_matches(keys: &[&str], options: &FuzzyOptions, parent: &mut [usize]) {
+    let n = options.ngram_size;
+    let lengths: Vec<usize> = keys.iter().map(|k| k.chars().count()).collect();
+    // Largest edit distance that still meets the threshold at a length
+    let max_distance =
+        |len: usize| ((1.0 - options.threshold) * len as f64 + 1e-9).floor() as usize;
+
+    // n-gram -> (key index, occurrences in that key)
+    let mut index: HashMap<Vec<char>, Vec<(usize, usize)>> = HashMap::new();
+    for (i, key) in keys.iter().enumerate() {
+        for (gram, count) in ngram_counts(key, n) {
+            index.entry(gram).or_default().push((i, count));
+        }
+    }
+
+    // Each pair is compared once, from its shorter key (ties by index)
+    let is_partner =
+        |i: usize, j: usize| lengths[j] > lengths[i] || (lengths[j] == lengths[i] && j > i);
+
+    for i in 0..keys.len() {
+        let len = lengths[i];
+        // A partner of length L within distance (1 - t) * L shares at least
+        // L * (1 - n * (1 - t)) - n + 1 n-grams. When n * (1 - t) < 1 that
+        // grows with L, so this key's own length gives a safe bound.
+        let slope = 1.0 - n as f64 * (1.0 - options.threshold);
+        let bound = if slope > 0.0 {
+            (len as f64 * slope - n as f64 + 1.0 - 1e-9).ceil().max(0.0) as usize
+        } else {
+            0
+        };
+
+        let candidates: Vec<usize> = if bound == 0 {
+            (0..keys.len()).filter(|&j| is_partner(i, j)).collect()
+        } else {
+            let mut shared: HashMap<usize, usize> = HashMap::new();
+            for (gram, count) in ngram_counts(keys[i], n) {
+                for &(j, other) in &index[&gram] {
+                    if is_partner(i, j) {
+                        *shared.entry(j).or_default() += count.min(other);
+                    }
+                }
+            }
+            shared
+                .into_iter()
+                .filter(|&(_, count)| count >= bound)
+                .map(|(j, _)| j)
+                .collect()
+        };
+
+        for j in candidates {
+            if lengths[j] - len > max_distance(lengths[j]) {
+                continue;
+            }
+            if levenshtein_similarity(keys[i], keys[j]) >= options.threshold {
+                let (a, b) = (find_root(parent, i), find_root(parent, j));
+                parent[a] = b;
+            }
         }
     }
+}
+
+/// Character n-grams of a key with their counts. Keys shorter than `n` are
+/// a single n-gram.
+fn ngram_counts(key: &str, n: usize) -> HashMap<Vec<char>, usize> {
+    let chars: Vec<char> = key.chars().collect();
+    let mut counts = HashMap::new();
+    if chars.len() < n {
+        counts.insert(chars, 1);
+    } else {
+        for gram in chars.windows(n) {
+            *counts.entry(gram.to_vec()).or_default() += 1;
+        }
+    }
+    counts
+}
 
-    // Collapse whitespace and trim
-    result
-        .split_whitespace()
-        .collect::<Vec<_>>()
-        .join(" ")
-        .to_lowercase()
+/// Union-find root lookup with path halving.
+fn find_root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
 }
 
 #[cfg(test)]
@@ -382,6 +920,196 @@ mod tests {
         assert_eq!(normalize_key("hello\tworld"), "hello world");
     }
 
+    /// Sorted clusters, for comparing results independent of order.
+    fn sorted(mut clusters: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+        for cluster in &mut clusters {
+            cluster.sort_unstable();
+        }
+        clusters.sort();
+        clusters
+    }
+
+    #[test]
+    fn test_fuzzy_clusters_groups_typos() {
+        let keys = [
+            "accommodate",
+            "acommodate",
+            "accomodate",
+            "separate",
+            "seperate",
+            "banana",
+        ];
+        let clusters = fuzzy_clusters(&keys, &FuzzyOptions::with_threshold(0.85)).unwrap();
+        assert_eq!(sorted(clusters), vec![vec![0, 1, 2], vec![3, 4]]);
+
+        let lsh = FuzzyOptions {
+            prefilter: Prefilter::lsh(),
+            ..FuzzyOptions::with_threshold(0.85)
+        };
+        let clusters = fuzzy_clusters(&keys, &lsh).unwrap();
+        assert_eq!(sorted(clusters), vec![vec![0, 1, 2], vec![3, 4]]);
+    }
+
+    #[test]
+    fn test_fuzzy_clusters_token_set() {
+        let keys = ["capital of france", "france capital of", "capital of spain"];
+        let options = FuzzyOptions {
+            algorithm: Algorithm::TokenSet,
+            ..FuzzyOptions::with_threshold(0.95)
+        };
+        let clusters = fuzzy_clusters(&keys, &options).unwrap();
+        assert_eq!(sorted(clusters), vec![vec![0, 1]]);
+    }
+
+    #[test]
+    fn test_fuzzy_prefilter_matches_brute_force() {
+        // Deterministic pseudo-random keys over a small alphabet, with
+        // near-copies mixed in
+        let mut seed = 7u32;
+        let mut next = || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) as usize
+        };
+        let mut keys: Vec<String> = Vec::new();
+        for _ in 0..150 {
+            let len = 1 + next() % 12;
+            let key: String = (0..len)
+                .map(|_| (b'a' + (next() % 4) as u8) as char)
+                .collect();
+            if next() % 3 == 0 && !keys.is_empty() {
+                let mut copy: Vec<char> = keys[next() % keys.len()].chars().collect();
+                let at = next() % copy.len();
+                copy[at] = 'z';
+                keys.push(copy.into_iter().collect());
+            }
+            keys.push(key);
+        }
+        let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+
+        for threshold in [0.5, 0.7, 0.8, 0.9, 1.0] {
+            let options = FuzzyOptions::with_threshold(threshold);
+            let mut parent: Vec<usize> = (0..keys.len()).collect();
+            for i in 0..keys.len() {
+                for j in i + 1..keys.len() {
+                    if levenshtein_similarity(keys[i], keys[j]) >= threshold {
+                        let (a, b) = (find_root(&mut parent, i), find_root(&mut parent, j));
+                        parent[a] = b;
+                    }
+                }
+            }
+            let mut expected: HashMap<usize, Vec<usize>> = HashMap::new();
+            for i in 0..keys.len() {
+                let root = find_root(&mut parent, i);
+                expected.entry(root).or_default().push(i);
+            }
+            let expected: Vec<Vec<usize>> =
+                expected.into_values().filter(|c| c.len() > 1).collect();
+
+            assert_eq!(
+                sorted(fuzzy_clusters(&keys, &options).unwrap()),
+                sorted(expected),
+                "threshold {}",
+                threshold
+            );
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_normalization_and_validation() {
+        let options = FuzzyOptions::default();
+        let base = KeyNormalization::default();
+        assert_eq!(
+            options.normalize("<b>Hello</b>  World", &base),
+            "hello world"
+        );
+
+        let raw = FuzzyOptions {
+            strip_html: false,
+            case_fold: false,
+            ..Default::default()
+        };
+        assert_eq!(raw.normalize("<b>Hello</b>", &base), "<b>Hello</b>");
+
+        let invalid = FuzzyOptions::with_threshold(1.5);
+        assert!(fuzzy_clusters(&["a"], &invalid).is_err());
+    }
+
+    fn note(note_id: i64, fields: &[(&str, &str)], tags: &[&str]) -> NoteInfo {
+        NoteInfo {
+            note_id,
+            model_name: "Basic".to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            fields: fields
+                .iter()
+                .enumerate()
+                .map(|(order, (name, value))| {
+                    (
+                        name.to_string(),
+                        ankit::NoteField {
+                            value: value.to_string(),
+                            order: order as i32,
+                        },
+                    )
+                })
+                .collect(),
+            cards: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_merge_notes_fills_and_unions() {
+        let kept = note(1, &[("Front", "dog"), ("Back", ""), ("Also", "")], &["a"]);
+        let first = note(
+            2,
+            &[("Front", "Dog"), ("Back", "perro"), ("Also", "")],
+            &["A", "b"],
+        );
+        let second = note(
+            3,
+            &[("Front", "dog"), ("Back", "can"), ("Also", "")],
+            &["c"],
+        );
+
+        let (fields, tags) = merge_notes(&kept, &[&first, &second], &MergeStrategy::all());
+        assert_eq!(
+            fields,
+            HashMap::from([("Back".to_string(), "perro".to_string())])
+        );
+        assert_eq!(tags, vec!["b", "c"]);
+
+        let merge = MergeStrategy::all().also_field("Also");
+        let (fields, _) = merge_notes(&kept, &[&first, &second], &merge);
+        assert_eq!(fields["Back"], "perro");
+        assert_eq!(fields["Also"], "can");
+    }
+
+    #[test]
+    fn test_merge_notes_appends_only_new_alternates() {
+        let kept = note(
+            1,
+            &[("Front", "dog"), ("Back", "perro"), ("Also", "can")],
+            &[],
+        );
+        let dup = note(
+            2,
+            &[("Front", "dogs"), ("Back", "Can"), ("Also", "")],
+            &["x"],
+        );
+        let merge = MergeStrategy::default().also_field("Also");
+
+        let (fields, tags) = merge_notes(&kept, &[&dup], &merge);
+        assert_eq!(
+            fields,
+            HashMap::from([("Also".to_string(), "can<br>dogs".to_string())])
+        );
+        assert!(tags.is_empty());
+
+        // Without the field on the kept note, nothing is appended
+        let merge = MergeStrategy::default().also_field("Extra");
+        assert!(merge_notes(&kept, &[&dup], &merge).0.is_empty());
+        assert!(!MergeStrategy::default().merges());
+    }
+
     #[test]
     fn test_keep_strategy_default() {
         let strategy = KeepStrategy::default();
@@ -394,6 +1122,8 @@ mod tests {
             search: "deck:Test".to_string(),
             key_field: "Front".to_string(),
             keep: KeepStrategy::MostContent,
+            match_mode: MatchMode::Exact,
+            normalization: KeyNormalization::default(),
         };
 
         assert_eq!(query.search, "deck:Test");
@@ -451,6 +1181,7 @@ mod tests {
             deleted: 2,
             kept: 1,
             details: vec![group],
+            ..Default::default()
         };
 
         assert_eq!(report.groups_found, 1);
@@ -466,6 +1197,7 @@ mod tests {
             deleted: 5,
             kept: 2,
             details: vec![],
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&report).unwrap();
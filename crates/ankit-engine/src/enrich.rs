@@ -1,7 +1,8 @@
 //! Note enrichment operations.
 //!
-//! This module provides workflows for finding notes with empty fields
-//! and updating them with new content.
+//! This module provides workflows for finding notes with empty fields,
+//! updating them with new content, finding and replacing field text, and
+//! generating audio and pronunciation annotations.
 //!
 //! # Example
 //!
@@ -30,10 +31,20 @@
 //! # }
 //! ```
 
-use crate::Result;
+use crate::annotate::Annotator;
+use crate::changes::PlannedChange;
+use crate::concurrency;
+use crate::journal::{self, Journal};
+use crate::normalize::{NormalizeOptions, normalize};
+use crate::report::{WorkflowReport, report_fields};
+use crate::tts::TtsProvider;
+use crate::{EngineOptions, Error, Result};
 use ankit::AnkiClient;
+use ankit::types::{FindReplaceParams, StoreMediaParams};
+use base64::Engine as _;
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 
 /// Query parameters for finding notes to enrich.
 #[derive(Debug, Clone)]
@@ -70,6 +81,21 @@ pub struct EnrichReport {
     pub failures: Vec<EnrichFailure>,
 }
 
+impl WorkflowReport for EnrichReport {
+    fn summary(&self) -> String {
+        format!("Updated {} notes ({} failed)", self.updated, self.failed)
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.failures
+            .iter()
+            .map(|failure| format!("note {}: {}", failure.note_id, failure.error))
+            .collect()
+    }
+
+    report_fields!();
+}
+
 /// Details about a failed enrichment.
 #[derive(Debug, Clone, Serialize)]
 pub struct EnrichFailure {
@@ -79,15 +105,253 @@ pub struct EnrichFailure {
     pub error: String,
 }
 
+/// A field value before and after a find and replace.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldReplacement {
+    /// The note ID.
+    pub note_id: i64,
+    /// The field that changed.
+    pub field: String,
+    /// Value before replacing.
+    pub before: String,
+    /// Value after replacing.
+    pub after: String,
+}
+
+impl FieldReplacement {
+    /// Render the change as a two-line `-`/`+` diff.
+    pub fn diff(&self) -> String {
+        format!(
+            "note {} {}\n- {}\n+ {}",
+            self.note_id, self.field, self.before, self.after
+        )
+    }
+}
+
+/// Report from a find and replace in note fields.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FindReplaceReport {
+    /// Number of notes checked.
+    pub notes_checked: usize,
+    /// Number of notes whose field changed (or would change, in a dry run).
+    pub notes_changed: usize,
+    /// Before and after values of every changed field.
+    pub replacements: Vec<FieldReplacement>,
+    /// Whether AnkiConnect's find and replace action made the changes.
+    pub native: bool,
+    /// Details about failed updates.
+    pub failures: Vec<EnrichFailure>,
+    /// Undo journal recorded before updating, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for FindReplaceReport {
+    fn summary(&self) -> String {
+        format!(
+            "Replaced text in {} of {} notes",
+            self.notes_changed, self.notes_checked
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        let replacements = self.replacements.iter().map(FieldReplacement::diff);
+        let failures = self
+            .failures
+            .iter()
+            .map(|failure| format!("note {}: {}", failure.note_id, failure.error));
+        replacements.chain(failures).collect()
+    }
+
+    report_fields!(dry_run, journal);
+}
+
+/// Report from normalizing note fields.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NormalizeReport {
+    /// Number of notes checked.
+    pub notes_checked: usize,
+    /// Number of notes whose fields changed (or would change, in a dry run).
+    pub notes_changed: usize,
+    /// Number of individual fields changed.
+    pub fields_changed: usize,
+    /// Details about failed updates.
+    pub failures: Vec<EnrichFailure>,
+    /// Undo journal recorded before updating, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for NormalizeReport {
+    fn summary(&self) -> String {
+        format!(
+            "Normalized {} fields on {} of {} notes",
+            self.fields_changed, self.notes_changed, self.notes_checked
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.failures
+            .iter()
+            .map(|failure| format!("note {}: {}", failure.note_id, failure.error))
+            .collect()
+    }
+
+    report_fields!(dry_run, journal);
+}
+
+/// Source and target fields for [`EnrichEngine::generate_audio`].
+#[derive(Debug, Clone)]
+pub struct AudioField {
+    /// Field whose text is spoken.
+    pub source: String,
+    /// Field that receives the `[sound:...]` reference.
+    pub target: String,
+    /// Regenerate audio even if the target already has a sound reference.
+    ///
+    /// When set, the target field is replaced. Otherwise the reference is
+    /// appended to any existing content.
+    pub overwrite: bool,
+}
+
+impl AudioField {
+    /// Speak `source` into `target`, skipping notes that already have audio.
+    pub fn new(source: impl Into<String>, target: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            target: target.into(),
+            overwrite: false,
+        }
+    }
+
+    /// Set whether existing audio in the target field is replaced.
+    pub fn overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+}
+
+/// Report from generating audio for notes.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AudioReport {
+    /// Number of notes checked.
+    pub notes_checked: usize,
+    /// Number of audio files synthesized and stored.
+    pub generated: usize,
+    /// Number of notes whose target field was updated (or would be, in a dry run).
+    pub notes_updated: usize,
+    /// Notes skipped because the source was empty or the target already had audio.
+    pub skipped: usize,
+    /// Details about notes that failed to synthesize or update.
+    pub failures: Vec<EnrichFailure>,
+    /// Undo journal recorded before updating, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for AudioReport {
+    fn summary(&self) -> String {
+        format!(
+            "Generated {} audio files for {} of {} notes ({} skipped)",
+            self.generated, self.notes_updated, self.notes_checked, self.skipped
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.failures
+            .iter()
+            .map(|failure| format!("note {}: {}", failure.note_id, failure.error))
+            .collect()
+    }
+
+    report_fields!(dry_run, journal);
+}
+
+/// Source and target fields for [`EnrichEngine::annotate`].
+#[derive(Debug, Clone)]
+pub struct AnnotationField {
+    /// Field whose text is annotated (for example `"Expression"`).
+    pub source: String,
+    /// Field that receives the annotation (for example `"Reading"`).
+    pub target: String,
+    /// Replace annotations already present in the target field.
+    pub overwrite: bool,
+}
+
+impl AnnotationField {
+    /// Annotate `source` into `target`, skipping notes whose target is filled.
+    pub fn new(source: impl Into<String>, target: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            target: target.into(),
+            overwrite: false,
+        }
+    }
+
+    /// Set whether a non-empty target field is replaced.
+    pub fn overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+}
+
+/// Report from annotating notes.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AnnotationReport {
+    /// Number of notes checked.
+    pub notes_checked: usize,
+    /// Number of notes whose target field was updated (or would be, in a dry run).
+    pub notes_updated: usize,
+    /// Notes skipped because the source was empty, the target was already
+    /// filled, or the annotation was unchanged.
+    pub skipped: usize,
+    /// Details about notes that failed to annotate or update.
+    pub failures: Vec<EnrichFailure>,
+    /// Undo journal recorded before updating, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for AnnotationReport {
+    fn summary(&self) -> String {
+        format!(
+            "Annotated {} of {} notes ({} skipped)",
+            self.notes_updated, self.notes_checked, self.skipped
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.failures
+            .iter()
+            .map(|failure| format!("note {}: {}", failure.note_id, failure.error))
+            .collect()
+    }
+
+    report_fields!(dry_run, journal);
+}
+
 /// Enrichment workflow engine.
 #[derive(Debug)]
 pub struct EnrichEngine<'a> {
     client: &'a AnkiClient,
+    options: &'a EngineOptions,
 }
 
 impl<'a> EnrichEngine<'a> {
-    pub(crate) fn new(client: &'a AnkiClient) -> Self {
-        Self { client }
+    pub(crate) fn new(client: &'a AnkiClient, options: &'a EngineOptions) -> Self {
+        Self { client, options }
     }
 
     /// Find notes that have empty fields matching the query criteria.
@@ -217,8 +481,14 @@ impl<'a> EnrichEngine<'a> {
     ) -> Result<EnrichReport> {
         let mut report = EnrichReport::default();
 
-        for (note_id, fields) in updates {
-            match self.client.notes().update_fields(*note_id, fields).await {
+        let results =
+            concurrency::bounded(self.options.concurrency, updates, |(note_id, fields)| {
+                let notes = self.client.notes();
+                async move { notes.update_fields(*note_id, fields).await }
+            })
+            .await;
+        for ((note_id, _), result) in updates.iter().zip(results) {
+            match result {
                 Ok(_) => report.updated += 1,
                 Err(e) => {
                     report.failed += 1;
@@ -295,6 +565,541 @@ impl<'a> EnrichEngine<'a> {
         let candidates = self.find_candidates(query).await?;
         Ok(EnrichmentPipeline::new(candidates))
     }
+
+    /// Clean up the HTML in note fields.
+    ///
+    /// Applies [`normalize`] to the fields of every note matching the query
+    /// (or only `options.fields`, if set) and writes back the fields that
+    /// changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - Anki search query to filter notes
+    /// * `options` - How to normalize field values
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # use ankit_engine::normalize::NormalizeOptions;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    ///
+    /// let options = NormalizeOptions::default().fields(["Front", "Back"]);
+    /// let report = engine.enrich().normalize_fields("deck:Imported", &options).await?;
+    /// println!("Cleaned {} fields in {} notes", report.fields_changed, report.notes_changed);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn normalize_fields(
+        &self,
+        query: &str,
+        options: &NormalizeOptions,
+    ) -> Result<NormalizeReport> {
+        let mut report = NormalizeReport {
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        let note_ids = self.client.notes().find(query).await?;
+        if note_ids.is_empty() {
+            return Ok(report);
+        }
+        let notes = self.client.notes().info(&note_ids).await?;
+        report.notes_checked = notes.len();
+
+        let mut updates = Vec::new();
+        for note in notes {
+            let changed: HashMap<String, String> = note
+                .fields
+                .into_iter()
+                .filter(|(name, _)| options.fields.is_empty() || options.fields.contains(name))
+                .filter_map(|(name, field)| {
+                    let cleaned = normalize(&field.value, options);
+                    (cleaned != field.value).then_some((name, cleaned))
+                })
+                .collect();
+            if !changed.is_empty() {
+                updates.push((note.note_id, changed));
+            }
+        }
+
+        report.notes_changed = updates.len();
+        report.fields_changed = updates.iter().map(|(_, fields)| fields.len()).sum();
+
+        if report.dry_run {
+            report.planned = updates
+                .into_iter()
+                .map(|(note_id, fields)| PlannedChange::UpdateNoteFields { note_id, fields })
+                .collect();
+            return Ok(report);
+        }
+
+        if updates.is_empty() {
+            return Ok(report);
+        }
+        if let Some(dir) = &self.options.journal_dir {
+            let note_ids: Vec<i64> = updates.iter().map(|(id, _)| *id).collect();
+            let mut record = Journal::new("normalize_fields");
+            record.entries = journal::record_fields(self.client, &note_ids).await?;
+            report.journal = Some(record.write(dir)?);
+        }
+
+        let result = self.update_notes(&updates).await?;
+        report.failures = result.failures;
+        Ok(report)
+    }
+
+    /// Find and replace text in one field of the notes matching a query.
+    ///
+    /// With `regex` set, `pattern` is a Rust regular expression and
+    /// `replacement` may refer to capture groups as `$1` or `${name}`.
+    /// Otherwise both are plain text. Matching is case-sensitive, and notes
+    /// without the field are skipped.
+    ///
+    /// The before and after value of every changed field is listed in the
+    /// report, so a dry run doubles as a preview. For plain text, AnkiConnect's
+    /// `findAndReplaceInModels` action makes the changes when it is available;
+    /// otherwise, and always for regexes, the fields are updated one note at a
+    /// time.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - Anki search query to filter notes
+    /// * `field` - Field to search in
+    /// * `pattern` - Text or regex to find
+    /// * `replacement` - Replacement text
+    /// * `regex` - Whether `pattern` is a regex
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    ///
+    /// // "to run" -> "run (v.)"
+    /// let report = engine
+    ///     .enrich()
+    ///     .find_replace("deck:English", "Back", r"^to (\w+)$", "$1 (v.)", true)
+    ///     .await?;
+    /// for replacement in &report.replacements {
+    ///     println!("{}", replacement.diff());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn find_replace(
+        &self,
+        query: &str,
+        field: &str,
+        pattern: &str,
+        replacement: &str,
+        regex: bool,
+    ) -> Result<FindReplaceReport> {
+        if pattern.is_empty() {
+            return Err(Error::Validation("pattern must not be empty".to_string()));
+        }
+        let compiled = if regex {
+            let re = regex_lite::Regex::new(pattern)
+                .map_err(|e| Error::Validation(format!("invalid regex: {}", e)))?;
+            Some(re)
+        } else {
+            None
+        };
+
+        let mut report = FindReplaceReport {
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        let note_ids = self.client.notes().find(query).await?;
+        if note_ids.is_empty() {
+            return Ok(report);
+        }
+        let notes = self.client.notes().info(&note_ids).await?;
+        report.notes_checked = notes.len();
+
+        // Changed notes grouped by model, for the native action
+        let mut by_model: HashMap<String, Vec<i64>> = HashMap::new();
+        for note in notes {
+            let Some(value) = note.fields.get(field).map(|f| &f.value) else {
+                continue;
+            };
+            let after = match compiled {
+                Some(ref re) => re.replace_all(value, replacement).into_owned(),
+                None => value.replace(pattern, replacement),
+            };
+            if after == *value {
+                continue;
+            }
+            by_model
+                .entry(note.model_name)
+                .or_default()
+                .push(note.note_id);
+            report.replacements.push(FieldReplacement {
+                note_id: note.note_id,
+                field: field.to_string(),
+                before: value.clone(),
+                after,
+            });
+        }
+        report.notes_changed = report.replacements.len();
+
+        let updates: Vec<(i64, HashMap<String, String>)> = report
+            .replacements
+            .iter()
+            .map(|r| {
+                (
+                    r.note_id,
+                    HashMap::from([(r.field.clone(), r.after.clone())]),
+                )
+            })
+            .collect();
+
+        if report.dry_run {
+            report.planned = updates
+                .into_iter()
+                .map(|(note_id, fields)| PlannedChange::UpdateNoteFields { note_id, fields })
+                .collect();
+            return Ok(report);
+        }
+
+        if updates.is_empty() {
+            return Ok(report);
+        }
+        if let Some(dir) = &self.options.journal_dir {
+            let note_ids: Vec<i64> = updates.iter().map(|(id, _)| *id).collect();
+            let mut record = Journal::new("find_replace");
+            record.entries = journal::record_fields(self.client, &note_ids).await?;
+            report.journal = Some(record.write(dir)?);
+        }
+
+        // Python and Rust regexes differ, so only plain text goes to Anki
+        if compiled.is_none() && self.supports_native_replace().await {
+            for (model, note_ids) in by_model {
+                let params = FindReplaceParams::new(model, field, pattern, replacement)
+                    .notes(note_ids)
+                    .regex(false)
+                    .match_case(true);
+                self.client.models().find_and_replace(params).await?;
+            }
+            report.native = true;
+            return Ok(report);
+        }
+
+        let result = self.update_notes(&updates).await?;
+        report.failures = result.failures;
+        Ok(report)
+    }
+
+    /// Whether AnkiConnect reports supporting `findAndReplaceInModels`.
+    ///
+    /// Add-ons too old to list their actions are assumed not to.
+    async fn supports_native_replace(&self) -> bool {
+        self.client
+            .capabilities()
+            .await
+            .ok()
+            .and_then(|c| c.actions.as_ref())
+            .is_some_and(|actions| actions.contains("findAndReplaceInModels"))
+    }
+
+    /// Generate spoken audio for a field with a text-to-speech provider.
+    ///
+    /// For each matching note, the source field is reduced to plain text and
+    /// passed to `provider`. The audio is stored in the media folder and a
+    /// `[sound:...]` reference is written to the target field. Files are named
+    /// after a hash of the text, so identical text shares one file.
+    ///
+    /// Notes with an empty source, or whose target already contains a sound
+    /// reference (unless [`AudioField::overwrite`] is set), are skipped.
+    /// Provider failures are recorded per note and do not stop the run.
+    ///
+    /// In a dry run nothing is synthesized; the report lists the media files
+    /// and field updates that would be made.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - Anki search query to filter notes
+    /// * `field` - Source and target fields
+    /// * `provider` - Text-to-speech provider
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # use ankit_engine::enrich::AudioField;
+    /// # use ankit_engine::tts::CommandTts;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    ///
+    /// let tts = CommandTts::new("espeak-ng", "wav").args(["-w", "{output}", "{text}"]);
+    /// let field = AudioField::new("Front", "Audio");
+    /// let report = engine.enrich().generate_audio("deck:French", &field, &tts).await?;
+    /// println!("Added audio to {} notes", report.notes_updated);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn generate_audio<P: TtsProvider>(
+        &self,
+        query: &str,
+        field: &AudioField,
+        provider: &P,
+    ) -> Result<AudioReport> {
+        let mut report = AudioReport {
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        let note_ids = self.client.notes().find(query).await?;
+        if note_ids.is_empty() {
+            return Ok(report);
+        }
+        let notes = self.client.notes().info(&note_ids).await?;
+        report.notes_checked = notes.len();
+
+        let text_options = NormalizeOptions::plain_text();
+        let mut pending = Vec::new();
+        for note in &notes {
+            let (Some(source), Some(target)) = (
+                note.fields.get(&field.source),
+                note.fields.get(&field.target),
+            ) else {
+                report.skipped += 1;
+                continue;
+            };
+            let text = normalize(&strip_sound(&source.value), &text_options);
+            if text.is_empty() || (!field.overwrite && target.value.contains("[sound:")) {
+                report.skipped += 1;
+                continue;
+            }
+            let filename = audio_filename(&text, provider.extension());
+            let value = if field.overwrite || target.value.is_empty() {
+                format!("[sound:{}]", filename)
+            } else {
+                format!("{}[sound:{}]", target.value, filename)
+            };
+            pending.push((note.note_id, text, filename, value));
+        }
+
+        if report.dry_run {
+            let mut planned_files = HashSet::new();
+            for (note_id, _, filename, value) in pending {
+                if planned_files.insert(filename.clone()) {
+                    report.planned.push(PlannedChange::StoreMedia { filename });
+                }
+                report.planned.push(PlannedChange::UpdateNoteFields {
+                    note_id,
+                    fields: HashMap::from([(field.target.clone(), value)]),
+                });
+                report.notes_updated += 1;
+            }
+            return Ok(report);
+        }
+
+        if pending.is_empty() {
+            return Ok(report);
+        }
+        if let Some(dir) = &self.options.journal_dir {
+            let note_ids: Vec<i64> = pending.iter().map(|(id, ..)| *id).collect();
+            let mut record = Journal::new("generate_audio");
+            record.entries = journal::record_fields(self.client, &note_ids).await?;
+            report.journal = Some(record.write(dir)?);
+        }
+
+        let mut stored = HashSet::new();
+        for (note_id, text, filename, value) in pending {
+            if !stored.contains(&filename) {
+                let audio = match provider.synthesize(&text).await {
+                    Ok(audio) => audio,
+                    Err(e) => {
+                        report.failures.push(EnrichFailure {
+                            note_id,
+                            error: e.to_string(),
+                        });
+                        continue;
+                    }
+                };
+                let data = base64::engine::general_purpose::STANDARD.encode(audio);
+                if let Err(e) = self
+                    .client
+                    .media()
+                    .store(StoreMediaParams::from_base64(&filename, data))
+                    .await
+                {
+                    report.failures.push(EnrichFailure {
+                        note_id,
+                        error: e.to_string(),
+                    });
+                    continue;
+                }
+                report.generated += 1;
+                stored.insert(filename);
+            }
+
+            let fields = HashMap::from([(field.target.clone(), value)]);
+            match self.client.notes().update_fields(note_id, &fields).await {
+                Ok(_) => report.notes_updated += 1,
+                Err(e) => report.failures.push(EnrichFailure {
+                    note_id,
+                    error: e.to_string(),
+                }),
+            }
+        }
+
+        Ok(report)
+    }
+    /// Fill a field with pronunciation annotations from another field.
+    ///
+    /// For each matching note, the source field is reduced to plain text and
+    /// passed to `annotator`, and the result is written to the target field.
+    /// Use [`JapaneseReadings`](crate::annotate::JapaneseReadings) (with the
+    /// `japanese` feature) for kana readings, or implement
+    /// [`Annotator`] for other languages.
+    ///
+    /// Notes with an empty source, or whose target is already filled (unless
+    /// [`AnnotationField::overwrite`] is set), are skipped. Annotator failures
+    /// are recorded per note and do not stop the run.
+    ///
+    /// Annotations are generated in a dry run too, so the planned changes
+    /// show the readings that would be written.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - Anki search query to filter notes
+    /// * `field` - Source and target fields
+    /// * `annotator` - Produces the annotation for each source text
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # use ankit_engine::annotate::Annotator;
+    /// # use ankit_engine::enrich::AnnotationField;
+    /// # struct Pinyin;
+    /// # impl Annotator for Pinyin {
+    /// #     async fn annotate(&self, text: &str) -> ankit_engine::Result<String> { Ok(text.into()) }
+    /// # }
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    ///
+    /// let field = AnnotationField::new("Hanzi", "Pinyin");
+    /// let report = engine.enrich().annotate("deck:Chinese", &field, &Pinyin).await?;
+    /// println!("Added pinyin to {} notes", report.notes_updated);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn annotate<A: Annotator>(
+        &self,
+        query: &str,
+        field: &AnnotationField,
+        annotator: &A,
+    ) -> Result<AnnotationReport> {
+        let mut report = AnnotationReport {
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        let note_ids = self.client.notes().find(query).await?;
+        if note_ids.is_empty() {
+            return Ok(report);
+        }
+        let notes = self.client.notes().info(&note_ids).await?;
+        report.notes_checked = notes.len();
+
+        let text_options = NormalizeOptions::plain_text();
+        let mut pending = Vec::new();
+        for note in &notes {
+            let (Some(source), Some(target)) = (
+                note.fields.get(&field.source),
+                note.fields.get(&field.target),
+            ) else {
+                report.skipped += 1;
+                continue;
+            };
+            let text = normalize(&strip_sound(&source.value), &text_options);
+            if text.is_empty() || (!field.overwrite && !target.value.trim().is_empty()) {
+                report.skipped += 1;
+                continue;
+            }
+            match annotator.annotate(&text).await {
+                Ok(value) if value.is_empty() || value == target.value => report.skipped += 1,
+                Ok(value) => pending.push((note.note_id, value)),
+                Err(e) => report.failures.push(EnrichFailure {
+                    note_id: note.note_id,
+                    error: e.to_string(),
+                }),
+            }
+        }
+
+        if report.dry_run {
+            for (note_id, value) in pending {
+                report.planned.push(PlannedChange::UpdateNoteFields {
+                    note_id,
+                    fields: HashMap::from([(field.target.clone(), value)]),
+                });
+                report.notes_updated += 1;
+            }
+            return Ok(report);
+        }
+
+        if pending.is_empty() {
+            return Ok(report);
+        }
+        if let Some(dir) = &self.options.journal_dir {
+            let note_ids: Vec<i64> = pending.iter().map(|(id, _)| *id).collect();
+            let mut record = Journal::new("annotate");
+            record.entries = journal::record_fields(self.client, &note_ids).await?;
+            report.journal = Some(record.write(dir)?);
+        }
+
+        let results =
+            concurrency::bounded(self.options.concurrency, &pending, |(note_id, value)| {
+                let fields = HashMap::from([(field.target.clone(), value.clone())]);
+                let notes = self.client.notes();
+                async move { notes.update_fields(*note_id, &fields).await }
+            })
+            .await;
+        for ((note_id, _), result) in pending.iter().zip(results) {
+            match result {
+                Ok(_) => report.notes_updated += 1,
+                Err(e) => report.failures.push(EnrichFailure {
+                    note_id: *note_id,
+                    error: e.to_string(),
+                }),
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+/// Remove `[sound:...]` references so they are not read aloud.
+fn strip_sound(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("[sound:") {
+        out.push_str(&rest[..start]);
+        match rest[start..].find(']') {
+            Some(end) => rest = &rest[start + end + 1..],
+            None => {
+                rest = &rest[start..];
+                break;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Media filename for synthesized text, stable across runs.
+fn audio_filename(text: &str, extension: &str) -> String {
+    // FNV-1a, so the same text always maps to the same file
+    let hash = text.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("ankit-tts-{:016x}.{}", hash, extension)
 }
 
 /// A pipeline for batch enrichment operations.
@@ -473,6 +1278,26 @@ pub struct EnrichPipelineReport {
     pub skipped: usize,
 }
 
+impl WorkflowReport for EnrichPipelineReport {
+    fn summary(&self) -> String {
+        format!(
+            "Updated {} notes ({} failed, {} skipped)",
+            self.updated,
+            self.failed.len(),
+            self.skipped
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.failed
+            .iter()
+            .map(|(note_id, error)| format!("note {}: {}", note_id, error))
+            .collect()
+    }
+
+    report_fields!();
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
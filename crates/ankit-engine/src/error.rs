@@ -46,6 +46,9 @@ pub enum Error {
     /// A model (note type) was not found.
     ModelNotFound(String),
 
+    /// An Anki profile was not found.
+    ProfileNotFound(String),
+
     /// A required field is missing from a note.
     MissingField {
         /// The model name.
@@ -68,6 +71,30 @@ pub enum Error {
 
     /// A backup operation failed.
     Backup(String),
+
+    /// A journal could not be written, read, or rolled back.
+    Journal(String),
+
+    /// A text-to-speech provider failed to generate audio.
+    Tts(String),
+
+    /// An annotator failed to produce a reading.
+    Annotation(String),
+
+    /// A media file could not be re-encoded.
+    Encode(String),
+
+    /// A notification could not be delivered.
+    Notify(String),
+
+    /// A note snapshot could not be written or read.
+    Snapshot(String),
+
+    /// A document could not be converted to PDF.
+    Pdf(String),
+
+    /// A note index could not be written or read.
+    Index(String),
 }
 
 impl std::error::Error for Error {
@@ -86,6 +113,7 @@ impl fmt::Display for Error {
             Error::Client(e) => write!(f, "{}", e),
             Error::DeckNotFound(name) => write!(f, "deck not found: {}", name),
             Error::ModelNotFound(name) => write!(f, "model not found: {}", name),
+            Error::ProfileNotFound(name) => write!(f, "profile not found: {}", name),
             Error::MissingField { model, field } => {
                 write!(f, "missing field '{}' for model '{}'", field, model)
             }
@@ -94,6 +122,14 @@ impl fmt::Display for Error {
             Error::Validation(msg) => write!(f, "validation error: {}", msg),
             Error::Io(e) => write!(f, "I/O error: {}", e),
             Error::Backup(msg) => write!(f, "backup error: {}", msg),
+            Error::Journal(msg) => write!(f, "journal error: {}", msg),
+            Error::Tts(msg) => write!(f, "text-to-speech error: {}", msg),
+            Error::Annotation(msg) => write!(f, "annotation error: {}", msg),
+            Error::Encode(msg) => write!(f, "encode error: {}", msg),
+            Error::Notify(msg) => write!(f, "notification error: {}", msg),
+            Error::Snapshot(msg) => write!(f, "snapshot error: {}", msg),
+            Error::Pdf(msg) => write!(f, "PDF error: {}", msg),
+            Error::Index(msg) => write!(f, "index error: {}", msg),
         }
     }
 }
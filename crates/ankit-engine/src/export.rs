@@ -2,10 +2,83 @@
 //!
 //! This module provides high-level export workflows for extracting
 //! deck contents and review history.
+//!
+//! # Reviews with Note Content
+//!
+//! [`ExportEngine::reviews_joined`] groups review history by note, alongside
+//! the note's deck, first field, and tags. [`write_reviews_csv`] flattens it
+//! to one row per review, ready for a dataframe:
+//!
+//! ```no_run
+//! use ankit_engine::Engine;
+//! use ankit_engine::export::write_reviews_csv;
+//!
+//! # async fn example() -> ankit_engine::Result<()> {
+//! let engine = Engine::new();
+//! let history = engine.export().reviews_joined("deck:Japanese").await?;
+//! let file = std::fs::File::create("reviews.csv")?;
+//! write_reviews_csv(&history, file)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Markdown
+//!
+//! [`ExportEngine::to_markdown`] writes a deck as Obsidian-flavored Markdown,
+//! with front matter for note metadata and media copied alongside:
+//!
+//! ```no_run
+//! use ankit_engine::Engine;
+//! use ankit_engine::export::MarkdownOptions;
+//!
+//! # async fn example() -> ankit_engine::Result<()> {
+//! let engine = Engine::new();
+//! let export = engine
+//!     .export()
+//!     .to_markdown("Japanese", "vault/Anki", &MarkdownOptions::default())
+//!     .await?;
+//! println!("Wrote {} files", export.files.len());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Incremental Export
+//!
+//! For repeated backups of large collections, [`ExportEngine::deck_incremental`]
+//! fetches only notes and cards modified since a timestamp. Each export
+//! returns an [`ExportCursor`] that can be saved and resumed from, so the
+//! next run picks up where the last one stopped:
+//!
+//! ```no_run
+//! use ankit_engine::Engine;
+//! use ankit_engine::export::ExportCursor;
+//!
+//! # async fn example() -> ankit_engine::Result<()> {
+//! let engine = Engine::new();
+//! let cursor_path = "japanese.cursor.json";
+//!
+//! let export = match ExportCursor::load(cursor_path) {
+//!     Ok(cursor) => engine.export().resume(&cursor).await?,
+//!     Err(_) => engine.export().deck_incremental("Japanese", 0).await?,
+//! };
+//! println!("{} notes changed", export.notes.len());
+//! export.cursor.save(cursor_path)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::normalize::{LineBreaks, NormalizeOptions, TagPolicy, normalize};
+use crate::{EngineOptions, Error, Result};
+use ankit::{AnkiClient, CardInfo, NoteInfo, QueryBuilder, ReviewEntry};
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::Result;
-use ankit::AnkiClient;
-use serde::Serialize;
+/// Notes or cards fetched per `notesInfo`/`cardsInfo` request.
+const FETCH_BATCH_SIZE: usize = 100;
 
 /// Exported note with all fields and metadata.
 #[derive(Debug, Clone, Serialize)]
@@ -61,15 +134,299 @@ pub struct DeckExport {
     pub cards: Vec<ExportedCard>,
 }
 
+/// Export of the notes and cards in a deck modified since a timestamp.
+#[derive(Debug, Clone, Serialize)]
+pub struct IncrementalExport {
+    /// Deck name.
+    pub deck_name: String,
+    /// Only notes and cards modified at or after this time were fetched
+    /// (seconds since epoch).
+    pub since: i64,
+    /// Notes modified since `since`.
+    pub notes: Vec<ExportedNote>,
+    /// Cards modified since `since`.
+    pub cards: Vec<ExportedCard>,
+    /// Number of notes in the deck that were unchanged and not fetched.
+    pub unchanged_notes: usize,
+    /// Number of cards in the deck that were unchanged and not fetched.
+    pub unchanged_cards: usize,
+    /// Cursor for the next incremental export of this deck.
+    pub cursor: ExportCursor,
+}
+
+/// Position of an incremental export, persisted between runs.
+///
+/// Stored as JSON. `since` is the newest modification time seen, so the
+/// next export fetches anything modified at or after it. Items modified
+/// within that same second are fetched again; duplicates are harmless for
+/// a backup, while skipping them could lose changes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportCursor {
+    /// Deck the cursor belongs to.
+    pub deck_name: String,
+    /// Modification time to resume from (seconds since epoch).
+    pub since: i64,
+    /// When the export that produced this cursor ran (seconds since epoch).
+    pub exported_at: i64,
+}
+
+impl ExportCursor {
+    /// Load a cursor from a JSON file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents).map_err(std::io::Error::from)?)
+    }
+
+    /// Write the cursor to a JSON file, replacing any existing cursor.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::from)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+}
+
+/// How [`ExportEngine::to_markdown`] lays out files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MarkdownLayout {
+    /// One file per note, named after its first field, with the note's ID,
+    /// deck, model, and tags in YAML front matter.
+    #[default]
+    FilePerNote,
+    /// One file per deck, with a section per note. Each section ends with a
+    /// block reference (`^anki-<note id>`) and the note's tags.
+    FilePerDeck,
+}
+
+/// Options for [`ExportEngine::to_markdown`].
+#[derive(Debug, Clone)]
+pub struct MarkdownOptions {
+    /// How notes are split into files.
+    pub layout: MarkdownLayout,
+    /// Folder for media files, relative to the output directory.
+    pub media_dir: String,
+    /// Copy referenced media files from Anki's media folder.
+    pub copy_media: bool,
+    /// Fields to export, in order. Empty means all fields in model order.
+    pub fields: Vec<String>,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        Self {
+            layout: MarkdownLayout::default(),
+            media_dir: "media".to_string(),
+            copy_media: true,
+            fields: Vec::new(),
+        }
+    }
+}
+
+/// Result of a Markdown export.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MarkdownExport {
+    /// Deck that was exported.
+    pub deck_name: String,
+    /// Number of notes written.
+    pub notes: usize,
+    /// Markdown files written.
+    pub files: Vec<PathBuf>,
+    /// Media files copied.
+    pub media: Vec<String>,
+    /// Referenced media files that could not be retrieved from Anki.
+    pub missing_media: Vec<String>,
+}
+
+/// How [`ExportEngine::printable`] lays out cards.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PrintLayout {
+    /// A two-column table with the question on the left and the answer on
+    /// the right, for reading or covering one side.
+    #[default]
+    TwoColumn,
+    /// Pages of cut-out flashcards. Each page of questions is followed by a
+    /// page of answers, mirrored so they line up when printed double-sided
+    /// and flipped on the long edge.
+    Grid {
+        /// Cards per row.
+        columns: usize,
+        /// Rows per page.
+        rows: usize,
+    },
+}
+
+/// What [`ExportEngine::printable`] does with images.
+///
+/// Audio references are always removed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum PrintMedia {
+    /// Remove images.
+    #[default]
+    Strip,
+    /// Keep images, pointing them at files in this directory or URL, such as
+    /// Anki's `collection.media` folder.
+    Reference(String),
+}
+
+/// Options for [`ExportEngine::printable`].
+#[derive(Debug, Clone, Default)]
+pub struct PrintableOptions {
+    /// How cards are laid out.
+    pub layout: PrintLayout,
+    /// What to do with images.
+    pub media: PrintMedia,
+    /// Document title. Defaults to the deck name.
+    pub title: Option<String>,
+}
+
+/// A deck rendered as a printable HTML document.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PrintableExport {
+    /// Deck that was exported.
+    pub deck_name: String,
+    /// Number of cards in the document.
+    pub cards: usize,
+    /// The HTML document.
+    pub html: String,
+}
+
+impl PrintableExport {
+    /// Write the HTML document to a file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        std::fs::write(path, &self.html)?;
+        Ok(())
+    }
+
+    /// Render the document to PDF with an external command.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # use ankit_engine::export::{PdfCommand, PrintableOptions};
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let sheet = engine
+    ///     .export()
+    ///     .printable("Japanese", &PrintableOptions::default())
+    ///     .await?;
+    /// sheet.save_pdf("japanese.pdf", &PdfCommand::wkhtmltopdf()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "pdf")]
+    pub async fn save_pdf(&self, path: impl AsRef<Path>, command: &PdfCommand) -> Result<()> {
+        command.render(&self.html, path.as_ref()).await
+    }
+}
+
+/// An HTML-to-PDF converter run as a local command.
+///
+/// Arguments may contain two placeholders:
+///
+/// - `{input}` - replaced with the path of a temporary HTML file
+/// - `{output}` - replaced with the path of the PDF to write
+#[cfg(feature = "pdf")]
+#[derive(Debug, Clone)]
+pub struct PdfCommand {
+    program: String,
+    args: Vec<String>,
+}
+
+#[cfg(feature = "pdf")]
+impl PdfCommand {
+    /// Create a converter that runs `program`.
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+        }
+    }
+
+    /// Set the command's arguments.
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Convert with `wkhtmltopdf`.
+    pub fn wkhtmltopdf() -> Self {
+        Self::new("wkhtmltopdf").args(["--quiet", "{input}", "{output}"])
+    }
+
+    /// Convert with headless Chromium.
+    pub fn chromium() -> Self {
+        Self::new("chromium").args([
+            "--headless",
+            "--disable-gpu",
+            "--no-pdf-header-footer",
+            "--print-to-pdf={output}",
+            "{input}",
+        ])
+    }
+
+    async fn render(&self, html: &str, output: &Path) -> Result<()> {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        // Unique per call, so concurrent exports don't share an input file
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let input = std::env::temp_dir().join(format!(
+            "ankit-print-{}-{}.html",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        // A PDF left from an earlier run must not pass for this one
+        match std::fs::remove_file(output) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        std::fs::write(&input, html)?;
+        let input_str = input.to_string_lossy();
+        let output_str = output.to_string_lossy();
+
+        let result = tokio::process::Command::new(&self.program)
+            .args(self.args.iter().map(|a| {
+                a.replace("{input}", &input_str)
+                    .replace("{output}", &output_str)
+            }))
+            .stdin(std::process::Stdio::null())
+            .output()
+            .await;
+        let _ = std::fs::remove_file(&input);
+
+        let result =
+            result.map_err(|e| Error::Pdf(format!("failed to run '{}': {}", self.program, e)))?;
+        if !result.status.success() {
+            return Err(Error::Pdf(format!(
+                "'{}' exited with {}: {}",
+                self.program,
+                result.status,
+                String::from_utf8_lossy(&result.stderr).trim()
+            )));
+        }
+        if !output.exists() {
+            return Err(Error::Pdf(format!(
+                "'{}' did not write a PDF",
+                self.program
+            )));
+        }
+        Ok(())
+    }
+}
+
 /// Export workflow engine.
 #[derive(Debug)]
 pub struct ExportEngine<'a> {
     client: &'a AnkiClient,
+    options: &'a EngineOptions,
 }
 
 impl<'a> ExportEngine<'a> {
-    pub(crate) fn new(client: &'a AnkiClient) -> Self {
-        Self { client }
+    pub(crate) fn new(client: &'a AnkiClient, options: &'a EngineOptions) -> Self {
+        Self { client, options }
     }
 
     /// Export all notes and cards from a deck.
@@ -93,40 +450,30 @@ impl<'a> ExportEngine<'a> {
         // Find all notes in deck
         let query = format!("deck:\"{}\"", deck_name);
         let note_ids = self.client.notes().find(&query).await?;
-        let note_infos = self.client.notes().info(&note_ids).await?;
+        let tracker = self.options.track("fetching notes", note_ids.len());
+        let mut note_infos = Vec::with_capacity(note_ids.len());
+        for batch in note_ids.chunks(FETCH_BATCH_SIZE) {
+            tracker.check()?;
+            note_infos.extend(self.client.notes().info(batch).await?);
+            tracker.advance(batch.len());
+        }
 
         // Find all cards in deck
         let card_ids = self.client.cards().find(&query).await?;
-        let card_infos = self.client.cards().info(&card_ids).await?;
+        let tracker = self.options.track("fetching cards", card_ids.len());
+        let mut card_infos = Vec::with_capacity(card_ids.len());
+        for batch in card_ids.chunks(FETCH_BATCH_SIZE) {
+            tracker.check()?;
+            card_infos.extend(self.client.cards().info(batch).await?);
+            tracker.advance(batch.len());
+        }
 
         // Convert to export format
         let notes = note_infos
             .into_iter()
-            .map(|info| ExportedNote {
-                note_id: info.note_id,
-                model_name: info.model_name,
-                deck_name: deck_name.to_string(),
-                fields: info.fields.into_iter().map(|(k, v)| (k, v.value)).collect(),
-                tags: info.tags,
-            })
-            .collect();
-
-        let cards = card_infos
-            .into_iter()
-            .map(|info| ExportedCard {
-                card_id: info.card_id,
-                note_id: info.note_id,
-                deck_name: info.deck_name,
-                reps: info.reps,
-                lapses: info.lapses,
-                interval: info.interval,
-                due: info.due,
-                ease_factor: info.ease_factor,
-                card_type: info.card_type,
-                queue: info.queue,
-                mod_time: info.mod_time,
-            })
+            .map(|info| exported_note(info, deck_name))
             .collect();
+        let cards = card_infos.into_iter().map(exported_card).collect();
 
         Ok(DeckExport {
             deck_name: deck_name.to_string(),
@@ -135,6 +482,191 @@ impl<'a> ExportEngine<'a> {
         })
     }
 
+    /// Export the notes and cards in a deck modified since a timestamp.
+    ///
+    /// Modification times are checked with `notesModTime` and `cardsModTime`,
+    /// which are cheap compared to fetching full note and card info, so only
+    /// changed items are fetched in full. Pass `0` to export everything.
+    ///
+    /// The returned [`cursor`](IncrementalExport::cursor) records where this
+    /// export stopped; save it and pass it to [`resume()`](Self::resume) next
+    /// time.
+    ///
+    /// # Arguments
+    ///
+    /// * `deck_name` - Name of the deck to export
+    /// * `since` - Only export items modified at or after this time (seconds since epoch)
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let last_week = 1_700_000_000;
+    /// let export = engine.export().deck_incremental("Japanese", last_week).await?;
+    /// println!(
+    ///     "{} notes changed, {} unchanged",
+    ///     export.notes.len(),
+    ///     export.unchanged_notes
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn deck_incremental(&self, deck_name: &str, since: i64) -> Result<IncrementalExport> {
+        let exported_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        let query = format!("deck:\"{}\"", deck_name);
+        let mut newest = since;
+
+        let note_ids = self.client.notes().find(&query).await?;
+        let mut changed_notes = Vec::new();
+        if !note_ids.is_empty() {
+            for entry in self.client.notes().mod_time(&note_ids).await? {
+                if entry.mod_time >= since {
+                    newest = newest.max(entry.mod_time);
+                    changed_notes.push(entry.note_id);
+                }
+            }
+        }
+
+        let card_ids = self.client.cards().find(&query).await?;
+        let mut changed_cards = Vec::new();
+        if !card_ids.is_empty() {
+            for entry in self.client.cards().mod_time(&card_ids).await? {
+                if entry.mod_time >= since {
+                    newest = newest.max(entry.mod_time);
+                    changed_cards.push(entry.card_id);
+                }
+            }
+        }
+
+        let notes = if changed_notes.is_empty() {
+            Vec::new()
+        } else {
+            self.client
+                .notes()
+                .info(&changed_notes)
+                .await?
+                .into_iter()
+                .map(|info| exported_note(info, deck_name))
+                .collect()
+        };
+        let cards = if changed_cards.is_empty() {
+            Vec::new()
+        } else {
+            self.client
+                .cards()
+                .info(&changed_cards)
+                .await?
+                .into_iter()
+                .map(exported_card)
+                .collect()
+        };
+
+        Ok(IncrementalExport {
+            deck_name: deck_name.to_string(),
+            since,
+            unchanged_notes: note_ids.len() - changed_notes.len(),
+            unchanged_cards: card_ids.len() - changed_cards.len(),
+            notes,
+            cards,
+            cursor: ExportCursor {
+                deck_name: deck_name.to_string(),
+                since: newest,
+                exported_at,
+            },
+        })
+    }
+
+    /// Continue an incremental export from a saved cursor.
+    ///
+    /// Equivalent to [`deck_incremental()`](Self::deck_incremental) with the
+    /// cursor's deck and timestamp.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # use ankit_engine::export::ExportCursor;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let cursor = ExportCursor::load("japanese.cursor.json")?;
+    /// let export = engine.export().resume(&cursor).await?;
+    /// export.cursor.save("japanese.cursor.json")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn resume(&self, cursor: &ExportCursor) -> Result<IncrementalExport> {
+        self.deck_incremental(&cursor.deck_name, cursor.since).await
+    }
+
+    /// Export the notes added in the last `days` days, including today.
+    ///
+    /// Searches the whole collection. Each note's deck is the deck of its
+    /// first card. Notes are ordered oldest first.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// for note in engine.export().added_since(7).await? {
+    ///     println!("{}: {:?}", note.deck_name, note.fields);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn added_since(&self, days: u32) -> Result<Vec<ExportedNote>> {
+        self.recent_notes(QueryBuilder::new().added(..days), days)
+            .await
+    }
+
+    /// Export the notes edited in the last `days` days, including today.
+    ///
+    /// Works like [`added_since()`](Self::added_since). Adding a note counts
+    /// as editing it.
+    pub async fn edited_since(&self, days: u32) -> Result<Vec<ExportedNote>> {
+        self.recent_notes(QueryBuilder::new().edited(..days), days)
+            .await
+    }
+
+    /// Export the notes matching a time-based query.
+    async fn recent_notes(&self, query: QueryBuilder, days: u32) -> Result<Vec<ExportedNote>> {
+        if days == 0 {
+            return Ok(Vec::new());
+        }
+        let mut card_ids = self.client.cards().find(&query.build()).await?;
+        card_ids.sort_unstable();
+
+        let tracker = self.options.track("fetching cards", card_ids.len());
+        let mut note_decks: BTreeMap<i64, String> = BTreeMap::new();
+        for batch in card_ids.chunks(FETCH_BATCH_SIZE) {
+            tracker.check()?;
+            for card in self.client.cards().info(batch).await? {
+                note_decks.entry(card.note_id).or_insert(card.deck_name);
+            }
+            tracker.advance(batch.len());
+        }
+
+        let note_ids: Vec<i64> = note_decks.keys().copied().collect();
+        let tracker = self.options.track("fetching notes", note_ids.len());
+        let mut notes = Vec::with_capacity(note_ids.len());
+        for batch in note_ids.chunks(FETCH_BATCH_SIZE) {
+            tracker.check()?;
+            for info in self.client.notes().info(batch).await? {
+                let deck_name = note_decks[&info.note_id].clone();
+                notes.push(exported_note(info, &deck_name));
+            }
+            tracker.advance(batch.len());
+        }
+        notes.sort_by_key(|note| note.note_id);
+        Ok(notes)
+    }
+
     /// Export review history for cards.
     ///
     /// # Arguments
@@ -169,16 +701,8 @@ impl<'a> ExportEngine<'a> {
 
         for (card_id_str, card_reviews) in reviews {
             let card_id: i64 = card_id_str.parse().unwrap_or(0);
-            let entries: Vec<ExportedReviewEntry> = card_reviews
-                .iter()
-                .map(|r| ExportedReviewEntry {
-                    timestamp: r.review_id,
-                    ease: r.ease,
-                    interval: r.interval,
-                    last_interval: r.last_interval,
-                    time_ms: r.time,
-                })
-                .collect();
+            let entries: Vec<ExportedReviewEntry> =
+                card_reviews.iter().map(exported_review).collect();
             result.push(CardReviewHistory {
                 card_id,
                 reviews: entries,
@@ -187,6 +711,643 @@ impl<'a> ExportEngine<'a> {
 
         Ok(result)
     }
+
+    /// Export review history grouped by note, joined with note content.
+    ///
+    /// Each record carries the note's deck, first field (by field order),
+    /// and tags, with the reviews of all its matching cards in chronological
+    /// order. Notes are sorted by ID, and notes whose cards have no reviews
+    /// are included with an empty list.
+    ///
+    /// Use [`write_reviews_csv`] for one row per review.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - Anki search query to select cards
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// for note in engine.export().reviews_joined("deck:Japanese").await? {
+    ///     println!("{}: {} reviews", note.first_field, note.reviews.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn reviews_joined(&self, query: &str) -> Result<Vec<NoteReviewHistory>> {
+        let card_ids = self.client.cards().find(query).await?;
+        if card_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let cards = self.client.cards().info(&card_ids).await?;
+        let mut card_notes: HashMap<i64, i64> = HashMap::new();
+        let mut decks: HashMap<i64, String> = HashMap::new();
+        for card in cards {
+            card_notes.insert(card.card_id, card.note_id);
+            decks.entry(card.note_id).or_insert(card.deck_name);
+        }
+
+        let mut note_ids: Vec<i64> = decks.keys().copied().collect();
+        note_ids.sort_unstable();
+        let notes = self.client.notes().info(&note_ids).await?;
+
+        let mut records: BTreeMap<i64, NoteReviewHistory> = notes
+            .into_iter()
+            .map(|note| {
+                let mut fields: Vec<_> = note.fields.into_values().collect();
+                fields.sort_by_key(|field| field.order);
+                let record = NoteReviewHistory {
+                    note_id: note.note_id,
+                    model_name: note.model_name,
+                    deck_name: decks.remove(&note.note_id).unwrap_or_default(),
+                    first_field: fields
+                        .into_iter()
+                        .next()
+                        .map(|f| f.value)
+                        .unwrap_or_default(),
+                    tags: note.tags,
+                    reviews: Vec::new(),
+                };
+                (record.note_id, record)
+            })
+            .collect();
+
+        let reviews = self
+            .client
+            .statistics()
+            .reviews_for_cards(&card_ids)
+            .await?;
+        for (card_id, entries) in reviews {
+            let card_id: i64 = card_id.parse().unwrap_or(0);
+            let Some(record) = card_notes
+                .get(&card_id)
+                .and_then(|note_id| records.get_mut(note_id))
+            else {
+                continue;
+            };
+            record
+                .reviews
+                .extend(entries.iter().map(|entry| NoteReviewEntry {
+                    card_id,
+                    review: exported_review(entry),
+                }));
+        }
+
+        let mut records: Vec<NoteReviewHistory> = records.into_values().collect();
+        for record in &mut records {
+            record
+                .reviews
+                .sort_by_key(|entry| (entry.review.timestamp, entry.card_id));
+        }
+        Ok(records)
+    }
+
+    /// Export a deck as Obsidian-flavored Markdown.
+    ///
+    /// Field HTML is converted to Markdown: bold and italics become `**` and
+    /// `*`, line breaks become newlines, and other markup is dropped.
+    /// `<img>` tags and `[sound:...]` references become Markdown embeds of
+    /// files in [`MarkdownOptions::media_dir`], where the media is copied
+    /// unless [`MarkdownOptions::copy_media`] is off. Hierarchical tags
+    /// (`lang::ja`) are written as Obsidian nested tags (`lang/ja`).
+    ///
+    /// Sub-decks are included. Existing files with the same names are
+    /// replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `deck_name` - Name of the deck to export
+    /// * `dir` - Output directory, created if missing
+    /// * `options` - Layout and media options
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # use ankit_engine::export::{MarkdownLayout, MarkdownOptions};
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let options = MarkdownOptions {
+    ///     layout: MarkdownLayout::FilePerDeck,
+    ///     ..Default::default()
+    /// };
+    /// let export = engine.export().to_markdown("Japanese", "vault", &options).await?;
+    /// println!("Exported {} notes", export.notes);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn to_markdown(
+        &self,
+        deck_name: &str,
+        dir: impl AsRef<Path>,
+        options: &MarkdownOptions,
+    ) -> Result<MarkdownExport> {
+        let dir = dir.as_ref();
+        let mut export = MarkdownExport {
+            deck_name: deck_name.to_string(),
+            ..Default::default()
+        };
+
+        let query = format!("deck:\"{}\"", deck_name);
+        let card_ids = self.client.cards().find(&query).await?;
+        if card_ids.is_empty() {
+            return Ok(export);
+        }
+        let mut note_decks: HashMap<i64, String> = HashMap::new();
+        for card in self.client.cards().info(&card_ids).await? {
+            note_decks.entry(card.note_id).or_insert(card.deck_name);
+        }
+        let mut note_ids: Vec<i64> = note_decks.keys().copied().collect();
+        note_ids.sort_unstable();
+        let notes = self.client.notes().info(&note_ids).await?;
+        export.notes = notes.len();
+
+        std::fs::create_dir_all(dir)?;
+        let mut media = HashSet::new();
+        let mut deck_files: BTreeMap<String, String> = BTreeMap::new();
+        let mut used_names = HashSet::new();
+
+        for note in &notes {
+            let deck = note_decks.get(&note.note_id).cloned().unwrap_or_default();
+            let fields: Vec<(String, String)> = markdown_fields(note, options)
+                .into_iter()
+                .map(|(name, value)| {
+                    let (markdown, refs) = html_to_markdown(&value, &options.media_dir);
+                    media.extend(refs);
+                    (name, markdown)
+                })
+                .collect();
+            let title = fields
+                .first()
+                .map(|(_, value)| file_stem(value))
+                .unwrap_or_default();
+            let tags: Vec<String> = note.tags.iter().map(|t| t.replace("::", "/")).collect();
+
+            match options.layout {
+                MarkdownLayout::FilePerNote => {
+                    let mut name = title;
+                    if name.is_empty() || !used_names.insert(name.to_lowercase()) {
+                        name = format!("{} {}", name, note.note_id).trim().to_string();
+                    }
+                    let mut out = String::from("---\n");
+                    out.push_str(&format!("note_id: {}\n", note.note_id));
+                    out.push_str(&format!("deck: {}\n", yaml_string(&deck)));
+                    out.push_str(&format!("model: {}\n", yaml_string(&note.model_name)));
+                    if tags.is_empty() {
+                        out.push_str("tags: []\n");
+                    } else {
+                        out.push_str("tags:\n");
+                        for tag in &tags {
+                            out.push_str(&format!("  - {}\n", yaml_string(tag)));
+                        }
+                    }
+                    out.push_str("---\n");
+                    for (field, value) in &fields {
+                        out.push_str(&format!("\n## {}\n\n{}\n", field, value));
+                    }
+
+                    let path = dir.join(format!("{}.md", name));
+                    std::fs::write(&path, out)?;
+                    export.files.push(path);
+                }
+                MarkdownLayout::FilePerDeck => {
+                    let out = deck_files.entry(deck).or_default();
+                    let heading = fields
+                        .first()
+                        .map(|(_, value)| value.replace('\n', " "))
+                        .unwrap_or_default();
+                    out.push_str(&format!("\n## {}\n\n", heading));
+                    for (_, value) in fields.iter().skip(1) {
+                        out.push_str(&format!("{}\n\n", value));
+                    }
+                    for tag in &tags {
+                        out.push_str(&format!("#{} ", tag));
+                    }
+                    out.push_str(&format!("^anki-{}\n", note.note_id));
+                }
+            }
+        }
+
+        for (deck, body) in deck_files {
+            let path = dir.join(format!("{}.md", file_stem(&deck)));
+            let out = format!("---\ndeck: {}\n---\n{}", yaml_string(&deck), body);
+            std::fs::write(&path, out)?;
+            export.files.push(path);
+        }
+
+        if options.copy_media && !media.is_empty() {
+            let media_dir = dir.join(&options.media_dir);
+            std::fs::create_dir_all(&media_dir)?;
+            let mut media: Vec<String> = media.into_iter().collect();
+            media.sort();
+            for filename in media {
+                let data = match self.client.media().retrieve(&filename).await {
+                    Ok(data) => data,
+                    Err(_) => {
+                        export.missing_media.push(filename);
+                        continue;
+                    }
+                };
+                let Ok(bytes) = base64::engine::general_purpose::STANDARD.decode(data) else {
+                    export.missing_media.push(filename);
+                    continue;
+                };
+                std::fs::write(media_dir.join(&filename), bytes)?;
+                export.media.push(filename);
+            }
+        }
+
+        Ok(export)
+    }
+
+    /// Render a deck as a printable HTML study sheet.
+    ///
+    /// Each card's question and answer come from Anki's own rendering, so
+    /// templates, cloze deletions, and conditional fields print as they
+    /// appear in review. Styling, scripts, audio, and type-in-the-answer
+    /// boxes are removed; images are removed or referenced according to
+    /// [`PrintableOptions::media`]. Cards are ordered by deck, then by
+    /// creation, and sub-decks are included.
+    ///
+    /// Save the result with [`PrintableExport::save`], or convert it to PDF
+    /// with `PrintableExport::save_pdf` (requires the `pdf` feature).
+    ///
+    /// # Arguments
+    ///
+    /// * `deck_name` - Name of the deck to export
+    /// * `options` - Layout, media, and title options
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # use ankit_engine::export::{PrintLayout, PrintableOptions};
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let options = PrintableOptions {
+    ///     layout: PrintLayout::Grid { columns: 3, rows: 4 },
+    ///     ..Default::default()
+    /// };
+    /// let sheet = engine.export().printable("Japanese", &options).await?;
+    /// sheet.save("japanese.html")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn printable(
+        &self,
+        deck_name: &str,
+        options: &PrintableOptions,
+    ) -> Result<PrintableExport> {
+        if let PrintLayout::Grid { columns, rows } = options.layout
+            && (columns == 0 || rows == 0)
+        {
+            return Err(Error::Validation(
+                "grid layout needs at least one column and one row".to_string(),
+            ));
+        }
+
+        let query = format!("deck:\"{}\"", deck_name);
+        let card_ids = self.client.cards().find(&query).await?;
+        let tracker = self.options.track("fetching cards", card_ids.len());
+        let mut cards = Vec::with_capacity(card_ids.len());
+        for batch in card_ids.chunks(FETCH_BATCH_SIZE) {
+            tracker.check()?;
+            cards.extend(self.client.cards().info(batch).await?);
+            tracker.advance(batch.len());
+        }
+        cards.sort_by(|a, b| {
+            a.deck_name
+                .cmp(&b.deck_name)
+                .then(a.card_id.cmp(&b.card_id))
+        });
+
+        let sides: Vec<PrintedCard> = cards
+            .iter()
+            .map(|card| PrintedCard {
+                deck_name: card.deck_name.clone(),
+                question: printable_html(&card.question, &options.media),
+                answer: printable_html(answer_side(&card.answer), &options.media),
+            })
+            .collect();
+        let title = options.title.as_deref().unwrap_or(deck_name);
+
+        Ok(PrintableExport {
+            deck_name: deck_name.to_string(),
+            cards: sides.len(),
+            html: printable_document(title, &sides, options.layout),
+        })
+    }
+}
+
+/// The fields of a note to export, in order.
+fn markdown_fields(note: &NoteInfo, options: &MarkdownOptions) -> Vec<(String, String)> {
+    if !options.fields.is_empty() {
+        return options
+            .fields
+            .iter()
+            .filter_map(|name| {
+                note.fields
+                    .get(name)
+                    .map(|field| (name.clone(), field.value.clone()))
+            })
+            .collect();
+    }
+    let mut fields: Vec<(&String, &ankit::NoteField)> = note.fields.iter().collect();
+    fields.sort_by_key(|(_, field)| field.order);
+    fields
+        .into_iter()
+        .map(|(name, field)| (name.clone(), field.value.clone()))
+        .collect()
+}
+
+/// Convert field HTML to Markdown, returning the media files it references.
+fn html_to_markdown(html: &str, media_dir: &str) -> (String, Vec<String>) {
+    let sound = regex_lite::Regex::new(r"\[sound:([^\]]+)\]").unwrap();
+    let img = regex_lite::Regex::new(r#"(?i)<img\b[^>]*?\bsrc\s*=\s*["']?([^"'\s>]+)["']?[^>]*>"#)
+        .unwrap();
+    let mut media = Vec::new();
+
+    let html = sound.replace_all(html, |caps: &regex_lite::Captures| {
+        media.push(caps[1].to_string());
+        format!("![]({})", media_link(media_dir, &caps[1]))
+    });
+    let html = img.replace_all(&html, |caps: &regex_lite::Captures| {
+        media.push(caps[1].to_string());
+        format!("![]({})", media_link(media_dir, &caps[1]))
+    });
+
+    let options = NormalizeOptions {
+        tags: TagPolicy::Allow(["b", "strong", "i", "em"].map(String::from).to_vec()),
+        line_breaks: LineBreaks::Newline,
+        ..Default::default()
+    };
+    let markdown = normalize(&html, &options)
+        .replace("<b>", "**")
+        .replace("</b>", "**")
+        .replace("<strong>", "**")
+        .replace("</strong>", "**")
+        .replace("<i>", "*")
+        .replace("</i>", "*")
+        .replace("<em>", "*")
+        .replace("</em>", "*")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&");
+    (markdown, media)
+}
+
+/// Relative link to a media file, with spaces escaped.
+fn media_link(media_dir: &str, filename: &str) -> String {
+    let path = if media_dir.is_empty() {
+        filename.to_string()
+    } else {
+        format!("{}/{}", media_dir.trim_end_matches('/'), filename)
+    };
+    path.replace(' ', "%20")
+}
+
+/// A file name derived from text, without characters that are invalid in
+/// file names or Obsidian links.
+fn file_stem(text: &str) -> String {
+    let embed = regex_lite::Regex::new(r"!\[[^\]]*\]\([^)]*\)").unwrap();
+    let text = embed.replace_all(text, "");
+    let line = text
+        .lines()
+        .find(|l| !l.trim().is_empty())
+        .unwrap_or_default();
+    let cleaned: String = line
+        .replace("::", " - ")
+        .chars()
+        .filter(|c| {
+            !matches!(
+                c,
+                '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '#' | '^' | '[' | ']' | '!'
+            )
+        })
+        .collect();
+    cleaned
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(80)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// Quote a string for YAML front matter.
+fn yaml_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+/// One card's sides, cleaned for printing.
+struct PrintedCard {
+    deck_name: String,
+    question: String,
+    answer: String,
+}
+
+const PRINT_STYLE: &str = "body{font-family:-apple-system,'Segoe UI',Helvetica,Arial,sans-serif;\
+color:#000;margin:0}h1{font-size:1.4em}h2{font-size:1.1em;margin:1em 0 .3em}\
+img{max-width:100%;max-height:12em}\
+table.sheet{border-collapse:collapse;width:100%}\
+table.sheet td{border:1px solid #999;padding:.4em .6em;vertical-align:top;width:50%}\
+table.sheet tr{break-inside:avoid}\
+.page{display:grid;gap:0;height:100vh;box-sizing:border-box;break-after:page}\
+.card{border:1px dashed #999;padding:.6em;display:flex;align-items:center;\
+justify-content:center;text-align:center;overflow:hidden}\
+@page{margin:1cm}";
+
+/// The answer side of a rendered card, without the repeated question.
+fn answer_side(answer: &str) -> &str {
+    let divider =
+        regex_lite::Regex::new(r#"(?i)<hr[^>]*\bid\s*=\s*["']?answer["']?[^>]*>"#).unwrap();
+    match divider.find(answer) {
+        Some(m) => &answer[m.end()..],
+        None => answer,
+    }
+}
+
+/// Clean rendered card HTML for printing.
+fn printable_html(html: &str, media: &PrintMedia) -> String {
+    let blocks = regex_lite::Regex::new(r"(?is)<(style|script)\b.*?</(style|script)\s*>").unwrap();
+    let sound = regex_lite::Regex::new(r"\[sound:[^\]]*\]|\[\[type:[^\]]*\]\]").unwrap();
+    let img = regex_lite::Regex::new(r#"(?i)<img\b[^>]*?\bsrc\s*=\s*["']?([^"'\s>]+)["']?[^>]*>"#)
+        .unwrap();
+
+    let html = blocks.replace_all(html, "");
+    let html = sound.replace_all(&html, "");
+    let html = img.replace_all(&html, |caps: &regex_lite::Captures| match media {
+        PrintMedia::Strip => String::new(),
+        PrintMedia::Reference(_) if caps[1].contains("://") => caps[0].to_string(),
+        PrintMedia::Reference(base) => {
+            format!("<img src=\"{}\">", media_link(base, &caps[1]))
+        }
+    });
+    normalize(&html, &NormalizeOptions::default())
+}
+
+/// Assemble the printable document.
+fn printable_document(title: &str, cards: &[PrintedCard], layout: PrintLayout) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n",
+        html_escape(title),
+        PRINT_STYLE
+    );
+
+    match layout {
+        PrintLayout::TwoColumn => {
+            out.push_str(&format!("<h1>{}</h1>\n", html_escape(title)));
+            let several_decks = cards.windows(2).any(|w| w[0].deck_name != w[1].deck_name);
+            let mut current: Option<&str> = None;
+            for card in cards {
+                if current != Some(card.deck_name.as_str()) {
+                    if current.is_some() {
+                        out.push_str("</table>\n");
+                    }
+                    if several_decks {
+                        out.push_str(&format!("<h2>{}</h2>\n", html_escape(&card.deck_name)));
+                    }
+                    out.push_str("<table class=\"sheet\">\n");
+                    current = Some(&card.deck_name);
+                }
+                out.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td></tr>\n",
+                    card.question, card.answer
+                ));
+            }
+            if current.is_some() {
+                out.push_str("</table>\n");
+            }
+        }
+        PrintLayout::Grid { columns, rows } => {
+            let grid = format!(
+                "grid-template-columns:repeat({},1fr);grid-template-rows:repeat({},1fr)",
+                columns, rows
+            );
+            for page in cards.chunks(columns * rows) {
+                out.push_str(&format!("<div class=\"page\" style=\"{}\">\n", grid));
+                for card in page {
+                    out.push_str(&format!("<div class=\"card\">{}</div>\n", card.question));
+                }
+                out.push_str("</div>\n");
+
+                // Backs are mirrored within each row so they line up with
+                // their fronts when the sheet is flipped on the long edge.
+                out.push_str(&format!("<div class=\"page\" style=\"{}\">\n", grid));
+                for row in page.chunks(columns) {
+                    for _ in row.len()..columns {
+                        out.push_str("<div class=\"card\"></div>\n");
+                    }
+                    for card in row.iter().rev() {
+                        out.push_str(&format!("<div class=\"card\">{}</div>\n", card.answer));
+                    }
+                }
+                out.push_str("</div>\n");
+            }
+        }
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Write joined review history as CSV, one row per review.
+///
+/// Columns are `note_id`, `card_id`, `deck`, `model`, `first_field`, `tags`
+/// (space-separated), `timestamp`, `ease`, `interval`, `last_interval`, and
+/// `time_ms`. Notes without reviews produce no rows.
+pub fn write_reviews_csv(records: &[NoteReviewHistory], mut writer: impl Write) -> Result<()> {
+    writeln!(
+        writer,
+        "note_id,card_id,deck,model,first_field,tags,timestamp,ease,interval,last_interval,time_ms"
+    )?;
+    for record in records {
+        let deck = csv_field(&record.deck_name);
+        let model = csv_field(&record.model_name);
+        let first_field = csv_field(&record.first_field);
+        let tags = csv_field(&record.tags.join(" "));
+        for entry in &record.reviews {
+            let review = &entry.review;
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{},{},{},{}",
+                record.note_id,
+                entry.card_id,
+                deck,
+                model,
+                first_field,
+                tags,
+                review.timestamp,
+                review.ease,
+                review.interval,
+                review.last_interval,
+                review.time_ms
+            )?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Quote a CSV field if it contains a delimiter, quote, or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Convert a review entry to the export format.
+fn exported_review(review: &ReviewEntry) -> ExportedReviewEntry {
+    ExportedReviewEntry {
+        timestamp: review.review_id,
+        ease: review.ease,
+        interval: review.interval,
+        last_interval: review.last_interval,
+        time_ms: review.time,
+    }
+}
+
+/// Convert note info to the export format.
+fn exported_note(info: NoteInfo, deck_name: &str) -> ExportedNote {
+    ExportedNote {
+        note_id: info.note_id,
+        model_name: info.model_name,
+        deck_name: deck_name.to_string(),
+        fields: info.fields.into_iter().map(|(k, v)| (k, v.value)).collect(),
+        tags: info.tags,
+    }
+}
+
+/// Convert card info to the export format.
+fn exported_card(info: CardInfo) -> ExportedCard {
+    ExportedCard {
+        card_id: info.card_id,
+        note_id: info.note_id,
+        deck_name: info.deck_name,
+        reps: info.reps,
+        lapses: info.lapses,
+        interval: info.interval,
+        due: info.due,
+        ease_factor: info.ease_factor,
+        card_type: info.card_type,
+        queue: info.queue,
+        mod_time: info.mod_time,
+    }
 }
 
 /// Review history for a single card.
@@ -198,6 +1359,33 @@ pub struct CardReviewHistory {
     pub reviews: Vec<ExportedReviewEntry>,
 }
 
+/// Review history for a note, joined with its content.
+#[derive(Debug, Clone, Serialize)]
+pub struct NoteReviewHistory {
+    /// The note ID.
+    pub note_id: i64,
+    /// The model (note type) name.
+    pub model_name: String,
+    /// Deck of the note's first matching card.
+    pub deck_name: String,
+    /// Value of the note's first field.
+    pub first_field: String,
+    /// Tags on the note.
+    pub tags: Vec<String>,
+    /// Reviews of the note's matching cards, in chronological order.
+    pub reviews: Vec<NoteReviewEntry>,
+}
+
+/// A review of one of a note's cards.
+#[derive(Debug, Clone, Serialize)]
+pub struct NoteReviewEntry {
+    /// The reviewed card.
+    pub card_id: i64,
+    /// The review.
+    #[serde(flatten)]
+    pub review: ExportedReviewEntry,
+}
+
 /// A single review entry.
 #[derive(Debug, Clone, Serialize)]
 pub struct ExportedReviewEntry {
@@ -24,12 +24,39 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! # Markdown Vaults
+//!
+//! [`ImportEngine::markdown`] turns fenced flashcard blocks in a directory of
+//! Markdown files (such as an Obsidian vault) into notes, and keeps them in
+//! sync on re-import:
+//!
+//! ````markdown
+//! ```anki
+//! Q: What is the capital of **France**?
+//! A: Paris
+//! ```
+//! ^k3j9xa
+//! ````
+
+use crate::changes::PlannedChange;
+use crate::concurrency;
+use crate::report::{AffectedIds, WorkflowReport, report_fields};
+use crate::rules::{RuleViolation, ValidationRules};
+use crate::source::{ImportSource, Provenance, RecordError};
+use crate::{EngineOptions, Note, NoteBuilder, Result};
+use ankit::types::StoreMediaParams;
+use ankit::{AnkiClient, NoteInfo};
+use base64::Engine as _;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
-use crate::{Note, Result};
-use ankit::AnkiClient;
+/// Notes sent per `addNotes` request, so large imports report progress.
+const ADD_BATCH_SIZE: usize = 100;
 
 /// Strategy for handling duplicate notes during import.
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Default)]
 pub enum OnDuplicate {
     /// Skip duplicate notes (default).
     #[default]
@@ -38,25 +65,61 @@ pub enum OnDuplicate {
     Update,
     /// Allow duplicates to be created.
     Allow,
+    /// Treat the named field (e.g., `"ExternalID"`) as a unique key.
+    ///
+    /// A note whose key matches an existing note of the same note type
+    /// updates that note's fields; any other note is created, even if Anki
+    /// would consider it a duplicate. Importing the same data again changes
+    /// nothing, so this is safe for repeated syncs from an external source.
+    /// Per-note results are listed in [`ImportReport::outcomes`].
+    UpsertByField(String),
 }
 
 /// Report of an import operation.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct ImportReport {
     /// Number of notes successfully added.
     pub added: usize,
     /// Number of notes skipped (duplicates).
     pub skipped: usize,
-    /// Number of notes updated (when using OnDuplicate::Update).
+    /// Number of notes updated (when using OnDuplicate::Update or
+    /// OnDuplicate::UpsertByField).
     pub updated: usize,
+    /// Number of notes already up to date (when using
+    /// OnDuplicate::UpsertByField).
+    pub unchanged: usize,
     /// Number of notes that failed to import.
     pub failed: usize,
     /// Details about failed imports.
     pub failures: Vec<ImportFailure>,
+    /// What happened to each note (when using OnDuplicate::UpsertByField).
+    pub outcomes: Vec<ImportOutcome>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for ImportReport {
+    fn summary(&self) -> String {
+        format!(
+            "Added {} notes, updated {}, unchanged {}, skipped {} ({} failed)",
+            self.added, self.updated, self.unchanged, self.skipped, self.failed
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.failures
+            .iter()
+            .map(|failure| format!("note {}: {}", failure.index, failure.error))
+            .collect()
+    }
+
+    report_fields!(dry_run);
 }
 
 /// Details about a failed import.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ImportFailure {
     /// Index of the note in the input list.
     pub index: usize,
@@ -64,15 +127,372 @@ pub struct ImportFailure {
     pub error: String,
 }
 
+/// What happened to one note in an upsert import.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportOutcome {
+    /// Index of the note in the input list.
+    pub index: usize,
+    /// Value of the key field.
+    pub key: String,
+    /// The note updated or created; `None` for notes a dry run would create.
+    pub note_id: Option<i64>,
+    /// What was done.
+    pub action: ImportAction,
+}
+
+/// Action taken for a note in an upsert import.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportAction {
+    /// No note had the key, so one was created.
+    Created,
+    /// A note with the key was updated.
+    Updated,
+    /// A note with the key already had these field values.
+    Unchanged,
+}
+
+/// Report of [`ImportEngine::notes_detailed`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DetailedImportReport {
+    /// ID of each note in input order; `None` for notes that failed, and
+    /// for every note in a dry run.
+    pub note_ids: Vec<Option<i64>>,
+    /// Number of notes added.
+    pub added: usize,
+    /// Why each failed note was rejected.
+    pub errors: Vec<AddError>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for DetailedImportReport {
+    fn summary(&self) -> String {
+        format!("Added {} notes ({} failed)", self.added, self.errors.len())
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.errors
+            .iter()
+            .map(|error| format!("note {}: {}", error.index, error))
+            .collect()
+    }
+
+    fn affected(&self) -> AffectedIds {
+        let mut ids = AffectedIds::from_planned(&self.planned);
+        ids.note_ids.extend(self.note_ids.iter().flatten());
+        ids.normalize()
+    }
+
+    report_fields!(dry_run);
+}
+
+/// A note that could not be added.
+#[derive(Debug, Clone, Serialize)]
+pub struct AddError {
+    /// Index of the note in the input list.
+    pub index: usize,
+    /// What kind of problem AnkiConnect reported.
+    pub kind: AddErrorKind,
+    /// AnkiConnect's message, if it gave one.
+    pub message: Option<String>,
+}
+
+impl AddError {
+    fn new(index: usize, message: Option<String>) -> Self {
+        Self {
+            index,
+            kind: AddErrorKind::from_message(message.as_deref()),
+            message,
+        }
+    }
+}
+
+impl std::fmt::Display for AddError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.message {
+            Some(message) => write!(f, "{} ({})", self.kind.as_str(), message),
+            None => f.write_str(self.kind.as_str()),
+        }
+    }
+}
+
+/// Why AnkiConnect rejected a note.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AddErrorKind {
+    /// The note duplicates an existing note.
+    Duplicate,
+    /// The note's first field is empty or missing.
+    MissingField,
+    /// The note type doesn't exist.
+    ModelNotFound,
+    /// The deck doesn't exist.
+    DeckNotFound,
+    /// Some other problem; see the message.
+    Other,
+    /// AnkiConnect gave no reason, for example because it predates
+    /// `canAddNotesWithErrorDetail`.
+    Unknown,
+}
+
+impl AddErrorKind {
+    /// Classify an AnkiConnect error message.
+    fn from_message(message: Option<&str>) -> Self {
+        let Some(message) = message else {
+            return Self::Unknown;
+        };
+        match ankit::Error::from_message(message) {
+            ankit::Error::DuplicateNote => Self::Duplicate,
+            ankit::Error::NoteValidation(_) => Self::MissingField,
+            ankit::Error::ModelNotFound(_) => Self::ModelNotFound,
+            ankit::Error::DeckNotFound(_) => Self::DeckNotFound,
+            _ => Self::Other,
+        }
+    }
+
+    /// Short description, e.g. `"duplicate"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Duplicate => "duplicate",
+            Self::MissingField => "missing field",
+            Self::ModelNotFound => "model not found",
+            Self::DeckNotFound => "deck not found",
+            Self::Other => "rejected",
+            Self::Unknown => "unknown error",
+        }
+    }
+}
+
+/// Options for [`ImportEngine::markdown`].
+#[derive(Debug, Clone)]
+pub struct MarkdownImportOptions {
+    /// Deck for notes from files without a `deck` in their front matter.
+    pub deck: String,
+    /// Info string of fenced question/answer blocks.
+    pub qa_marker: String,
+    /// Info string of fenced cloze blocks.
+    pub cloze_marker: String,
+    /// Prefix of the question line in question/answer blocks.
+    pub question_prefix: String,
+    /// Prefix of the line that starts the answer in question/answer blocks.
+    pub answer_prefix: String,
+    /// Model for question/answer blocks.
+    pub basic_model: String,
+    /// Field that receives the question.
+    pub front_field: String,
+    /// Field that receives the answer.
+    pub back_field: String,
+    /// Model for cloze blocks.
+    pub cloze_model: String,
+    /// Field that receives the cloze text.
+    pub cloze_field: String,
+    /// Tag prefix linking notes to their blocks (`md-block::<id>`).
+    pub id_tag: String,
+    /// Extra tags added to every note.
+    pub tags: Vec<String>,
+    /// Write generated block IDs back into the Markdown files.
+    ///
+    /// Without stored IDs, a block whose text changes cannot be matched to
+    /// its note and is imported again as a new note.
+    pub write_ids: bool,
+}
+
+impl Default for MarkdownImportOptions {
+    fn default() -> Self {
+        Self {
+            deck: "Default".to_string(),
+            qa_marker: "anki".to_string(),
+            cloze_marker: "anki-cloze".to_string(),
+            question_prefix: "Q:".to_string(),
+            answer_prefix: "A:".to_string(),
+            basic_model: "Basic".to_string(),
+            front_field: "Front".to_string(),
+            back_field: "Back".to_string(),
+            cloze_model: "Cloze".to_string(),
+            cloze_field: "Text".to_string(),
+            id_tag: "md-block".to_string(),
+            tags: Vec::new(),
+            write_ids: true,
+        }
+    }
+}
+
+/// Report of a Markdown import.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MarkdownImportReport {
+    /// Number of Markdown files scanned.
+    pub files: usize,
+    /// Number of flashcard blocks found.
+    pub blocks: usize,
+    /// Number of notes added.
+    pub added: usize,
+    /// Number of notes updated because their block changed.
+    pub updated: usize,
+    /// Number of blocks whose note was already up to date.
+    pub unchanged: usize,
+    /// Number of block IDs generated (and written back, unless disabled or
+    /// in a dry run).
+    pub ids_generated: usize,
+    /// Blocks that could not be imported.
+    pub failures: Vec<BlockFailure>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for MarkdownImportReport {
+    fn summary(&self) -> String {
+        format!(
+            "Imported {} blocks from {} files: {} added, {} updated, {} unchanged ({} failed)",
+            self.blocks,
+            self.files,
+            self.added,
+            self.updated,
+            self.unchanged,
+            self.failures.len()
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.failures
+            .iter()
+            .map(|failure| {
+                format!(
+                    "{}:{}: {}",
+                    failure.file.display(),
+                    failure.line,
+                    failure.error
+                )
+            })
+            .collect()
+    }
+
+    report_fields!(dry_run);
+}
+
+/// Report of an import from an [`ImportSource`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SourceImportReport {
+    /// Number of records read from the source, including invalid ones.
+    pub records_read: usize,
+    /// Number of notes added.
+    pub added: usize,
+    /// Number of notes updated.
+    pub updated: usize,
+    /// Number of notes already up to date.
+    pub unchanged: usize,
+    /// Number of notes skipped as duplicates.
+    pub skipped: usize,
+    /// Records that could not be read or imported.
+    pub errors: Vec<RecordError>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for SourceImportReport {
+    fn summary(&self) -> String {
+        format!(
+            "Read {} records: {} added, {} updated, {} unchanged, {} skipped ({} failed)",
+            self.records_read,
+            self.added,
+            self.updated,
+            self.unchanged,
+            self.skipped,
+            self.errors.len()
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.errors
+            .iter()
+            .map(|error| format!("{}: {}", error.provenance, error.error))
+            .collect()
+    }
+
+    report_fields!(dry_run);
+}
+
+/// A Markdown block that could not be imported.
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockFailure {
+    /// File containing the block.
+    pub file: PathBuf,
+    /// Line of the block's opening fence (1-based).
+    pub line: usize,
+    /// Error message.
+    pub error: String,
+}
+
+/// A flashcard block parsed from a Markdown file.
+#[derive(Debug)]
+struct MarkdownBlock {
+    file: PathBuf,
+    /// Line of the opening fence (0-based).
+    line: usize,
+    /// Line after the closing fence, where a generated ID is inserted.
+    id_line: usize,
+    id: Option<String>,
+    deck: String,
+    tags: Vec<String>,
+    content: BlockContent,
+}
+
+#[derive(Debug)]
+enum BlockContent {
+    QuestionAnswer { question: String, answer: String },
+    Cloze(String),
+}
+
+/// A note in an upsert import with its key.
+struct KeyedNote<'n> {
+    index: usize,
+    note: &'n Note,
+    key: &'n str,
+}
+
+/// The write an upsert import makes for one note.
+enum UpsertWrite {
+    Create,
+    Update(i64),
+    Unchanged(i64),
+}
+
+/// Escape text for use inside a quoted search term.
+fn escape_search(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '"' | '*' | '_' | ':') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// What happened to one note in an [`OnDuplicate::Update`] import.
+enum UpdateOutcome {
+    Added,
+    Updated(i64),
+    Skipped,
+    Failed(String),
+}
+
 /// Import workflow engine.
 #[derive(Debug)]
 pub struct ImportEngine<'a> {
     client: &'a AnkiClient,
+    options: &'a EngineOptions,
 }
 
 impl<'a> ImportEngine<'a> {
-    pub(crate) fn new(client: &'a AnkiClient) -> Self {
-        Self { client }
+    pub(crate) fn new(client: &'a AnkiClient, options: &'a EngineOptions) -> Self {
+        Self { client, options }
     }
 
     /// Import notes with duplicate handling.
@@ -104,11 +524,18 @@ impl<'a> ImportEngine<'a> {
     /// # }
     /// ```
     pub async fn notes(&self, notes: &[Note], on_duplicate: OnDuplicate) -> Result<ImportReport> {
+        let mut report = ImportReport {
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
         if notes.is_empty() {
-            return Ok(ImportReport::default());
+            return Ok(report);
+        }
+        if let OnDuplicate::UpsertByField(field) = &on_duplicate {
+            self.upsert_by_field(notes, field, &mut report).await?;
+            return Ok(report);
         }
-
-        let mut report = ImportReport::default();
 
         // Check which notes can be added
         let can_add = self.client.notes().can_add_detailed(notes).await?;
@@ -116,18 +543,26 @@ impl<'a> ImportEngine<'a> {
         match on_duplicate {
             OnDuplicate::Skip => {
                 // Filter to only notes that can be added
-                let addable: Vec<_> = notes
+                let (indices, addable): (Vec<usize>, Vec<Note>) = notes
                     .iter()
                     .zip(can_add.iter())
-                    .filter(|(_, result)| result.can_add)
-                    .map(|(note, _)| note.clone())
-                    .collect();
+                    .enumerate()
+                    .filter(|(_, (_, result))| result.can_add)
+                    .map(|(i, (note, _))| (i, note.clone()))
+                    .unzip();
 
                 report.skipped = notes.len() - addable.len();
 
-                if !addable.is_empty() {
-                    let results = self.client.notes().add_many(&addable).await?;
-                    for (i, result) in results.iter().enumerate() {
+                if report.dry_run {
+                    report.added = addable.len();
+                    report.planned.extend(
+                        addable
+                            .into_iter()
+                            .map(|note| PlannedChange::AddNote { note }),
+                    );
+                } else if !addable.is_empty() {
+                    let results = self.add_in_batches(&addable).await?;
+                    for (&i, result) in indices.iter().zip(results.iter()) {
                         if result.is_some() {
                             report.added += 1;
                         } else {
@@ -152,7 +587,17 @@ impl<'a> ImportEngine<'a> {
                     })
                     .collect();
 
-                let results = self.client.notes().add_many(&notes_with_allow).await?;
+                if report.dry_run {
+                    report.added = notes_with_allow.len();
+                    report.planned.extend(
+                        notes_with_allow
+                            .into_iter()
+                            .map(|note| PlannedChange::AddNote { note }),
+                    );
+                    return Ok(report);
+                }
+
+                let results = self.add_in_batches(&notes_with_allow).await?;
                 for (i, result) in results.iter().enumerate() {
                     if result.is_some() {
                         report.added += 1;
@@ -165,52 +610,50 @@ impl<'a> ImportEngine<'a> {
                     }
                 }
             }
+            OnDuplicate::UpsertByField(_) => unreachable!("handled above"),
             OnDuplicate::Update => {
-                // For duplicates, find and update existing notes
-                for (i, (note, result)) in notes.iter().zip(can_add.iter()).enumerate() {
-                    if result.can_add {
-                        // Not a duplicate, add it
-                        match self.client.notes().add(note.clone()).await {
-                            Ok(_) => report.added += 1,
-                            Err(e) => {
-                                report.failed += 1;
-                                report.failures.push(ImportFailure {
-                                    index: i,
-                                    error: e.to_string(),
-                                });
+                // New notes are added, duplicates update the existing note
+                let tracker = self.options.track("importing notes", notes.len());
+                let outcomes = concurrency::bounded(
+                    self.options.concurrency,
+                    notes.iter().zip(can_add.iter()),
+                    |(note, result)| {
+                        let tracker = &tracker;
+                        async move {
+                            if tracker.is_cancelled() {
+                                return UpdateOutcome::Skipped;
                             }
+                            let outcome = self.add_or_update(note, result.can_add).await;
+                            tracker.advance(1);
+                            outcome
                         }
-                    } else {
-                        // Duplicate - find and update
-                        // Use the first field value to search for duplicates
-                        if let Some((field_name, field_value)) = note.fields.iter().next() {
-                            let query =
-                                format!("\"{}:{}\"", field_name, field_value.replace('\"', "\\\""));
-                            match self.client.notes().find(&query).await {
-                                Ok(existing) if !existing.is_empty() => {
-                                    // Update the first match
-                                    match self
-                                        .client
-                                        .notes()
-                                        .update_fields(existing[0], &note.fields)
-                                        .await
-                                    {
-                                        Ok(_) => report.updated += 1,
-                                        Err(e) => {
-                                            report.failed += 1;
-                                            report.failures.push(ImportFailure {
-                                                index: i,
-                                                error: e.to_string(),
-                                            });
-                                        }
-                                    }
-                                }
-                                _ => {
-                                    report.skipped += 1;
-                                }
+                    },
+                )
+                .await;
+                tracker.check()?;
+                for (i, (note, outcome)) in notes.iter().zip(outcomes).enumerate() {
+                    match outcome {
+                        UpdateOutcome::Added => {
+                            report.added += 1;
+                            if report.dry_run {
+                                report
+                                    .planned
+                                    .push(PlannedChange::AddNote { note: note.clone() });
                             }
-                        } else {
-                            report.skipped += 1;
+                        }
+                        UpdateOutcome::Updated(note_id) => {
+                            report.updated += 1;
+                            if report.dry_run {
+                                report.planned.push(PlannedChange::UpdateNoteFields {
+                                    note_id,
+                                    fields: note.fields.clone(),
+                                });
+                            }
+                        }
+                        UpdateOutcome::Skipped => report.skipped += 1,
+                        UpdateOutcome::Failed(error) => {
+                            report.failed += 1;
+                            report.failures.push(ImportFailure { index: i, error });
                         }
                     }
                 }
@@ -220,6 +663,644 @@ impl<'a> ImportEngine<'a> {
         Ok(report)
     }
 
+    /// Import notes keyed by a field: update the note with the same key, or
+    /// create one.
+    async fn upsert_by_field(
+        &self,
+        notes: &[Note],
+        field: &str,
+        report: &mut ImportReport,
+    ) -> Result<()> {
+        // Each note's key, rejecting notes without one and repeated keys
+        let mut keyed = Vec::new();
+        let mut seen = HashSet::new();
+        for (index, note) in notes.iter().enumerate() {
+            let key = note.fields.get(field).map(|value| value.trim());
+            let error = match key {
+                None | Some("") => format!("key field '{}' is empty", field),
+                Some(key) if !seen.insert((note.model_name.as_str(), key)) => {
+                    format!("key '{}' appears more than once in the import", key)
+                }
+                Some(key) => {
+                    keyed.push(KeyedNote { index, note, key });
+                    continue;
+                }
+            };
+            report.failed += 1;
+            report.failures.push(ImportFailure { index, error });
+        }
+
+        // Existing notes with each key
+        let found = concurrency::bounded(self.options.concurrency, &keyed, |keyed| {
+            let query = format!(
+                "note:\"{}\" \"{}:{}\"",
+                escape_search(&keyed.note.model_name),
+                field,
+                escape_search(keyed.key)
+            );
+            let client = self.client;
+            async move { client.notes().find(&query).await }
+        })
+        .await;
+        let mut candidate_ids = Vec::new();
+        let mut lookups = Vec::new();
+        for result in found {
+            let ids = result?;
+            candidate_ids.extend(&ids);
+            lookups.push(ids);
+        }
+        let mut existing: HashMap<i64, NoteInfo> = HashMap::new();
+        for chunk in candidate_ids.chunks(100) {
+            for info in self.client.notes().info(chunk).await? {
+                existing.insert(info.note_id, info);
+            }
+        }
+
+        // Decide what to do with each note
+        let mut writes = Vec::new();
+        for (keyed, ids) in keyed.into_iter().zip(lookups) {
+            // Field searches ignore case, so compare the keys exactly
+            let matches: Vec<&NoteInfo> = ids
+                .iter()
+                .filter_map(|id| existing.get(id))
+                .filter(|info| {
+                    info.model_name == keyed.note.model_name
+                        && info
+                            .fields
+                            .get(field)
+                            .is_some_and(|value| value.value.trim() == keyed.key)
+                })
+                .collect();
+            let write = match matches[..] {
+                [] => UpsertWrite::Create,
+                [info] => {
+                    let changed = keyed.note.fields.iter().any(|(name, value)| {
+                        info.fields
+                            .get(name)
+                            .is_none_or(|field| &field.value != value)
+                    });
+                    if changed {
+                        UpsertWrite::Update(info.note_id)
+                    } else {
+                        UpsertWrite::Unchanged(info.note_id)
+                    }
+                }
+                _ => {
+                    report.failed += 1;
+                    report.failures.push(ImportFailure {
+                        index: keyed.index,
+                        error: format!("key '{}' matches {} notes", keyed.key, matches.len()),
+                    });
+                    continue;
+                }
+            };
+            writes.push((keyed, write));
+        }
+
+        let tracker = self.options.track("importing notes", writes.len());
+        let results = concurrency::bounded(self.options.concurrency, &writes, |(keyed, write)| {
+            let tracker = &tracker;
+            async move {
+                tracker.check()?;
+                let result = self.apply_upsert(keyed.note, write).await;
+                tracker.advance(1);
+                result
+            }
+        })
+        .await;
+        tracker.check()?;
+        for ((keyed, write), result) in writes.iter().zip(results) {
+            let (note_id, action) = match (write, result) {
+                (_, Err(e)) => {
+                    report.failed += 1;
+                    report.failures.push(ImportFailure {
+                        index: keyed.index,
+                        error: e.to_string(),
+                    });
+                    continue;
+                }
+                (UpsertWrite::Create, Ok(note_id)) => {
+                    report.added += 1;
+                    (note_id, ImportAction::Created)
+                }
+                (UpsertWrite::Update(note_id), Ok(_)) => {
+                    report.updated += 1;
+                    (Some(*note_id), ImportAction::Updated)
+                }
+                (UpsertWrite::Unchanged(note_id), Ok(_)) => {
+                    report.unchanged += 1;
+                    (Some(*note_id), ImportAction::Unchanged)
+                }
+            };
+            report.outcomes.push(ImportOutcome {
+                index: keyed.index,
+                key: keyed.key.to_string(),
+                note_id,
+                action,
+            });
+            if report.dry_run {
+                report.planned.extend(match write {
+                    UpsertWrite::Create => Some(PlannedChange::AddNote {
+                        note: keyed.note.clone(),
+                    }),
+                    UpsertWrite::Update(note_id) => Some(PlannedChange::UpdateNoteFields {
+                        note_id: *note_id,
+                        fields: keyed.note.fields.clone(),
+                    }),
+                    UpsertWrite::Unchanged(_) => None,
+                });
+            }
+        }
+        report.outcomes.sort_by_key(|outcome| outcome.index);
+
+        Ok(())
+    }
+
+    /// Perform one upsert write, returning the ID of a created note. Nothing
+    /// is written in dry-run mode.
+    async fn apply_upsert(&self, note: &Note, write: &UpsertWrite) -> Result<Option<i64>> {
+        if self.options.dry_run {
+            return Ok(None);
+        }
+        match write {
+            UpsertWrite::Create => {
+                // The key decides identity, not Anki's first-field check
+                let mut note = note.clone();
+                let options = note.options.get_or_insert_with(Default::default);
+                options.allow_duplicate = Some(true);
+                Ok(Some(self.client.notes().add(note).await?))
+            }
+            UpsertWrite::Update(note_id) => {
+                self.client
+                    .notes()
+                    .update_fields(*note_id, &note.fields)
+                    .await?;
+                Ok(None)
+            }
+            UpsertWrite::Unchanged(_) => Ok(None),
+        }
+    }
+
+    /// Add notes in batches of [`ADD_BATCH_SIZE`], reporting progress after
+    /// each batch.
+    async fn add_in_batches(&self, notes: &[Note]) -> Result<Vec<Option<i64>>> {
+        let tracker = self.options.track("adding notes", notes.len());
+        let mut results = Vec::with_capacity(notes.len());
+        for batch in notes.chunks(ADD_BATCH_SIZE) {
+            tracker.check()?;
+            results.extend(self.client.notes().add_many(batch).await?);
+            tracker.advance(batch.len());
+        }
+        Ok(results)
+    }
+
+    /// Add a note, or update the existing note it duplicates. Nothing is
+    /// written in dry-run mode.
+    async fn add_or_update(&self, note: &Note, can_add: bool) -> UpdateOutcome {
+        if can_add {
+            if self.options.dry_run {
+                return UpdateOutcome::Added;
+            }
+            return match self.client.notes().add(note.clone()).await {
+                Ok(_) => UpdateOutcome::Added,
+                Err(e) => UpdateOutcome::Failed(e.to_string()),
+            };
+        }
+
+        // Use the first field value to search for duplicates
+        let Some((field_name, field_value)) = note.fields.iter().next() else {
+            return UpdateOutcome::Skipped;
+        };
+        let query = format!("\"{}:{}\"", field_name, field_value.replace('\"', "\\\""));
+        let note_id = match self.client.notes().find(&query).await {
+            Ok(existing) if !existing.is_empty() => existing[0],
+            _ => return UpdateOutcome::Skipped,
+        };
+        if self.options.dry_run {
+            return UpdateOutcome::Updated(note_id);
+        }
+        // Update the first match
+        match self
+            .client
+            .notes()
+            .update_fields(note_id, &note.fields)
+            .await
+        {
+            Ok(_) => UpdateOutcome::Updated(note_id),
+            Err(e) => UpdateOutcome::Failed(e.to_string()),
+        }
+    }
+
+    /// Add notes and explain why any were rejected.
+    ///
+    /// `addNotes` returns `null` for notes it can't add, with no reason.
+    /// This adds the notes, then asks `canAddNotesWithErrorDetail` about the
+    /// ones that failed and classifies each reason as an [`AddErrorKind`].
+    /// Against an AnkiConnect without that action, failures are reported as
+    /// [`AddErrorKind::Unknown`].
+    ///
+    /// Unlike [`notes`](Self::notes), nothing is checked before adding;
+    /// duplicates are rejected unless a note's options allow them. In a dry
+    /// run, every note is checked with `canAddNotesWithErrorDetail` instead.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::{Engine, NoteBuilder};
+    /// # use ankit_engine::import::AddErrorKind;
+    /// # async fn example(notes: Vec<ankit_engine::Note>) -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let report = engine.import().notes_detailed(&notes).await?;
+    /// for error in &report.errors {
+    ///     if error.kind == AddErrorKind::ModelNotFound {
+    ///         eprintln!("note {} uses an unknown note type", error.index);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn notes_detailed(&self, notes: &[Note]) -> Result<DetailedImportReport> {
+        let mut report = DetailedImportReport {
+            note_ids: vec![None; notes.len()],
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+        if notes.is_empty() {
+            return Ok(report);
+        }
+
+        if report.dry_run {
+            let results = self.client.notes().can_add_detailed(notes).await?;
+            for (index, (note, result)) in notes.iter().zip(results).enumerate() {
+                if result.can_add {
+                    report.added += 1;
+                    report
+                        .planned
+                        .push(PlannedChange::AddNote { note: note.clone() });
+                } else {
+                    report.errors.push(AddError::new(index, result.error));
+                }
+            }
+            return Ok(report);
+        }
+
+        report.note_ids = self.add_in_batches(notes).await?;
+        let failed: Vec<usize> = (0..notes.len())
+            .filter(|&i| report.note_ids[i].is_none())
+            .collect();
+        report.added = notes.len() - failed.len();
+        if failed.is_empty() {
+            return Ok(report);
+        }
+
+        let failed_notes: Vec<Note> = failed.iter().map(|&i| notes[i].clone()).collect();
+        let reasons = match self.client.notes().can_add_detailed(&failed_notes).await {
+            Ok(results) => results.into_iter().map(|result| result.error).collect(),
+            // Versions before canAddNotesWithErrorDetail reject it, or the client
+            // refuses it after detecting their capabilities
+            Err(ankit::Error::AnkiConnect(_) | ankit::Error::UnsupportedAction { .. }) => {
+                vec![None; failed.len()]
+            }
+            Err(e) => return Err(e.into()),
+        };
+        report.errors = failed
+            .into_iter()
+            .zip(reasons)
+            .map(|(index, reason)| AddError::new(index, reason))
+            .collect();
+        Ok(report)
+    }
+
+    /// Import notes from a record source.
+    ///
+    /// Records are read until the source is exhausted, then imported with
+    /// [`notes`](Self::notes). Records the source can't read and notes that
+    /// fail to import are listed in [`SourceImportReport::errors`] with
+    /// their provenance; the rest of the batch is still imported.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Records to import, e.g. a
+    ///   [`DelimitedSource`](crate::source::DelimitedSource)
+    /// * `on_duplicate` - Strategy for handling duplicates
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # use ankit_engine::import::OnDuplicate;
+    /// # use ankit_engine::source::{FieldMapping, JsonlSource};
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let source = JsonlSource::from_path("cards.jsonl", FieldMapping::new("Default", "Basic"))?;
+    /// let report = engine.import().from_source(source, OnDuplicate::Skip).await?;
+    /// println!("{} added, {} errors", report.added, report.errors.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn from_source(
+        &self,
+        mut source: impl ImportSource,
+        on_duplicate: OnDuplicate,
+    ) -> Result<SourceImportReport> {
+        let mut report = SourceImportReport {
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+        let mut notes = Vec::new();
+        let mut provenance: Vec<Provenance> = Vec::new();
+        while let Some(record) = source.next_record() {
+            report.records_read += 1;
+            match record {
+                Ok(record) => {
+                    notes.push(record.note);
+                    provenance.push(record.provenance);
+                }
+                Err(error) => report.errors.push(error),
+            }
+        }
+
+        let imported = self.notes(&notes, on_duplicate).await?;
+        report.added = imported.added;
+        report.updated = imported.updated;
+        report.unchanged = imported.unchanged;
+        report.skipped = imported.skipped;
+        report.planned = imported.planned;
+        report
+            .errors
+            .extend(imported.failures.into_iter().map(|failure| RecordError {
+                provenance: provenance[failure.index].clone(),
+                error: failure.error,
+            }));
+        report.errors.sort_by_key(|error| error.provenance.line);
+        Ok(report)
+    }
+
+    /// Import flashcard blocks from a directory of Markdown files.
+    ///
+    /// Every `.md` file under `dir` is scanned (hidden directories such as
+    /// `.obsidian` are skipped) for fenced blocks whose info string is
+    /// [`qa_marker`](MarkdownImportOptions::qa_marker) or
+    /// [`cloze_marker`](MarkdownImportOptions::cloze_marker). In a
+    /// question/answer block, the answer starts at the line beginning with
+    /// the answer prefix. A cloze block holds Anki cloze text; if it has no
+    /// `{{c1::...}}` deletions, each `==highlight==` becomes one.
+    ///
+    /// Each block is identified by an Obsidian block ID (`^id` on the line
+    /// after the closing fence), and its note is tagged `md-block::<id>`.
+    /// Re-importing updates the fields of notes whose blocks changed rather
+    /// than adding duplicates. Blocks without an ID get a generated one,
+    /// which is written back to the file.
+    ///
+    /// Bold, italics, and line breaks are converted to HTML. Embedded images
+    /// and audio (`![](img.png)` or `![[clip.mp3]]`) are stored in Anki's
+    /// media folder. A file's front matter may set its `deck` and `tags`.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Directory to scan
+    /// * `options` - Block markers, models, and tagging
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # use ankit_engine::import::MarkdownImportOptions;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let options = MarkdownImportOptions {
+    ///     deck: "Vault".to_string(),
+    ///     ..Default::default()
+    /// };
+    /// let report = engine.import().markdown("notes/", &options).await?;
+    /// println!("{} added, {} updated", report.added, report.updated);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn markdown(
+        &self,
+        dir: impl AsRef<Path>,
+        options: &MarkdownImportOptions,
+    ) -> Result<MarkdownImportReport> {
+        let dir = dir.as_ref();
+        let mut report = MarkdownImportReport {
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        let mut files = Vec::new();
+        collect_markdown_files(dir, &mut files)?;
+        files.sort();
+        report.files = files.len();
+
+        let mut blocks = Vec::new();
+        let mut contents = HashMap::new();
+        for file in &files {
+            let content = std::fs::read_to_string(file)?;
+            blocks.extend(parse_markdown_blocks(file, &content, options));
+            contents.insert(file.clone(), content);
+        }
+        report.blocks = blocks.len();
+
+        // Give every block an ID, remembering which files need them written
+        let mut seen = HashSet::new();
+        let mut generated: HashMap<PathBuf, Vec<(usize, String)>> = HashMap::new();
+        let mut valid = Vec::new();
+        for mut block in blocks {
+            let id = match &block.id {
+                Some(id) => id.to_lowercase(),
+                None => {
+                    let id = generate_block_id(&block, &seen);
+                    generated
+                        .entry(block.file.clone())
+                        .or_default()
+                        .push((block.id_line, id.clone()));
+                    report.ids_generated += 1;
+                    id
+                }
+            };
+            if !seen.insert(id.clone()) {
+                report.failures.push(BlockFailure {
+                    file: block.file.clone(),
+                    line: block.line + 1,
+                    error: format!("duplicate block ID '{}'", id),
+                });
+                continue;
+            }
+            block.id = Some(id);
+            valid.push(block);
+        }
+
+        let existing = self.tracked_notes(&options.id_tag).await?;
+        let mut stored_media = HashSet::new();
+        for block in &valid {
+            let id = block.id.as_deref().unwrap_or_default();
+            let base = block.file.parent().unwrap_or(dir);
+            let mut media = Vec::new();
+            let (model, fields) = match &block.content {
+                BlockContent::QuestionAnswer { question, answer } => (
+                    &options.basic_model,
+                    HashMap::from([
+                        (
+                            options.front_field.clone(),
+                            markdown_to_html(question, base, dir, &mut media),
+                        ),
+                        (
+                            options.back_field.clone(),
+                            markdown_to_html(answer, base, dir, &mut media),
+                        ),
+                    ]),
+                ),
+                BlockContent::Cloze(text) => (
+                    &options.cloze_model,
+                    HashMap::from([(
+                        options.cloze_field.clone(),
+                        markdown_to_html(&highlights_to_cloze(text), base, dir, &mut media),
+                    )]),
+                ),
+            };
+
+            if let Err(e) = self
+                .store_block_media(media, &mut stored_media, &mut report)
+                .await
+            {
+                report.failures.push(BlockFailure {
+                    file: block.file.clone(),
+                    line: block.line + 1,
+                    error: e.to_string(),
+                });
+                continue;
+            }
+
+            if let Some(note) = existing.get(id) {
+                let changed = fields.iter().any(|(name, value)| {
+                    note.fields
+                        .get(name)
+                        .is_none_or(|field| &field.value != value)
+                });
+                if !changed {
+                    report.unchanged += 1;
+                } else if report.dry_run {
+                    report.updated += 1;
+                    report.planned.push(PlannedChange::UpdateNoteFields {
+                        note_id: note.note_id,
+                        fields,
+                    });
+                } else {
+                    match self
+                        .client
+                        .notes()
+                        .update_fields(note.note_id, &fields)
+                        .await
+                    {
+                        Ok(()) => report.updated += 1,
+                        Err(e) => report.failures.push(BlockFailure {
+                            file: block.file.clone(),
+                            line: block.line + 1,
+                            error: e.to_string(),
+                        }),
+                    }
+                }
+                continue;
+            }
+
+            let mut builder = NoteBuilder::new(&block.deck, model)
+                .tags(options.tags.iter().cloned())
+                .tags(block.tags.iter().cloned())
+                .tag(format!("{}::{}", options.id_tag, id));
+            for (name, value) in fields {
+                builder = builder.field(name, value);
+            }
+            let note = builder.build();
+            if report.dry_run {
+                report.added += 1;
+                report.planned.push(PlannedChange::AddNote { note });
+                continue;
+            }
+            match self.client.notes().add(note).await {
+                Ok(_) => report.added += 1,
+                Err(e) => report.failures.push(BlockFailure {
+                    file: block.file.clone(),
+                    line: block.line + 1,
+                    error: e.to_string(),
+                }),
+            }
+        }
+
+        if options.write_ids && !report.dry_run {
+            for (file, mut ids) in generated {
+                let Some(content) = contents.get(&file) else {
+                    continue;
+                };
+                let mut lines: Vec<&str> = content.lines().collect();
+                let id_lines: Vec<String> = {
+                    ids.sort();
+                    ids.iter().map(|(_, id)| format!("^{}", id)).collect()
+                };
+                // Insert from the bottom so earlier line numbers stay valid
+                for ((line, _), id_line) in ids.iter().zip(&id_lines).rev() {
+                    lines.insert((*line).min(lines.len()), id_line);
+                }
+                let mut updated = lines.join("\n");
+                if content.ends_with('\n') {
+                    updated.push('\n');
+                }
+                std::fs::write(&file, updated)?;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Notes tagged with a block ID, keyed by lowercase ID.
+    async fn tracked_notes(&self, id_tag: &str) -> Result<HashMap<String, NoteInfo>> {
+        let note_ids = self
+            .client
+            .notes()
+            .find(&format!("\"tag:{}::*\"", id_tag))
+            .await?;
+        if note_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let prefix = format!("{}::", id_tag.to_lowercase());
+        let mut tracked = HashMap::new();
+        for note in self.client.notes().info(&note_ids).await? {
+            let id = note.tags.iter().find_map(|tag| {
+                let tag = tag.to_lowercase();
+                tag.strip_prefix(&prefix).map(str::to_string)
+            });
+            if let Some(id) = id {
+                tracked.insert(id, note);
+            }
+        }
+        Ok(tracked)
+    }
+
+    /// Store media files embedded in a block, skipping ones already stored.
+    async fn store_block_media(
+        &self,
+        media: Vec<(PathBuf, String)>,
+        stored: &mut HashSet<String>,
+        report: &mut MarkdownImportReport,
+    ) -> Result<()> {
+        for (path, filename) in media {
+            if !stored.insert(filename.clone()) {
+                continue;
+            }
+            if report.dry_run {
+                report.planned.push(PlannedChange::StoreMedia { filename });
+                continue;
+            }
+            let data = std::fs::read(&path)?;
+            let data = base64::engine::general_purpose::STANDARD.encode(data);
+            self.client
+                .media()
+                .store(StoreMediaParams::from_base64(filename, data))
+                .await?;
+        }
+        Ok(())
+    }
+
     /// Validate notes before import without actually importing.
     ///
     /// Returns detailed validation results for each note.
@@ -254,17 +1335,68 @@ impl<'a> ImportEngine<'a> {
             results.push(ValidationResult {
                 valid: errors.is_empty(),
                 errors,
+                violations: Vec::new(),
             });
         }
 
         Ok(results)
     }
 
+    /// Validate notes against configurable rules.
+    ///
+    /// Runs the checks of [`validate`](Self::validate), then checks each
+    /// note against `rules`. Broken rules are listed in
+    /// [`ValidationResult::violations`] and make the note invalid.
+    ///
+    /// Returns an error if a rule's pattern is not a valid regular
+    /// expression.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::{Engine, NoteBuilder};
+    /// # use ankit_engine::rules::ValidationRules;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let rules = ValidationRules::new()
+    ///     .required("Front")
+    ///     .pattern("Audio", r"^\[sound:[^\]]+\]$")
+    ///     .max_length("Front", 200);
+    ///
+    /// let notes = vec![NoteBuilder::new("Default", "Basic").field("Front", "Q").build()];
+    /// for result in engine.import().validate_with(&notes, &rules).await? {
+    ///     for violation in &result.violations {
+    ///         println!("{}", violation.message);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn validate_with(
+        &self,
+        notes: &[Note],
+        rules: &ValidationRules,
+    ) -> Result<Vec<ValidationResult>> {
+        let patterns = rules.compile()?;
+        let mut results = self.validate(notes).await?;
+        for (note, result) in notes.iter().zip(results.iter_mut()) {
+            result.violations = rules.check_compiled(note, &patterns);
+            result
+                .errors
+                .extend(result.violations.iter().map(|v| v.message.clone()));
+            result.valid = result.errors.is_empty();
+        }
+        Ok(results)
+    }
+
     /// Smart add a single note with duplicate checking and tag suggestions.
     ///
     /// Combines validation, duplicate detection, and tag suggestions into
     /// a single atomic operation.
     ///
+    /// In dry-run mode all checks run but the note is not added; an accepted
+    /// note is reported with its would-be status and no `note_id`.
+    ///
     /// # Arguments
     ///
     /// * `note` - Note to add
@@ -373,7 +1505,7 @@ impl<'a> ImportEngine<'a> {
 
                                 // Sort by frequency and take top suggestions
                                 let mut tags: Vec<_> = tag_counts.into_iter().collect();
-                                tags.sort_by(|a, b| b.1.cmp(&a.1));
+                                tags.sort_by_key(|t| std::cmp::Reverse(t.1));
                                 result.suggested_tags = tags
                                     .into_iter()
                                     .take(5)
@@ -416,7 +1548,7 @@ impl<'a> ImportEngine<'a> {
 
                 // Sort by frequency and take top suggestions
                 let mut tags: Vec<_> = tag_counts.into_iter().collect();
-                tags.sort_by(|a, b| b.1.cmp(&a.1));
+                tags.sort_by_key(|t| std::cmp::Reverse(t.1));
                 result.suggested_tags = tags
                     .into_iter()
                     .take(5)
@@ -435,9 +1567,15 @@ impl<'a> ImportEngine<'a> {
             options.allow_duplicate = Some(true);
         }
 
-        match self.client.notes().add(note_to_add).await {
+        let added = if self.options.dry_run {
+            Ok(None)
+        } else {
+            self.client.notes().add(note_to_add).await.map(Some)
+        };
+
+        match added {
             Ok(note_id) => {
-                result.note_id = Some(note_id);
+                result.note_id = note_id;
                 if !result.similar_notes.is_empty() {
                     result.status = SmartAddStatus::AddedWithWarning {
                         warning: format!(
@@ -460,6 +1598,260 @@ impl<'a> ImportEngine<'a> {
     }
 }
 
+/// Recursively collect `.md` files, skipping hidden directories.
+fn collect_markdown_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if hidden {
+            continue;
+        }
+        if path.is_dir() {
+            collect_markdown_files(&path, files)?;
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("md"))
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Find the flashcard blocks in one Markdown file.
+fn parse_markdown_blocks(
+    file: &Path,
+    content: &str,
+    options: &MarkdownImportOptions,
+) -> Vec<MarkdownBlock> {
+    let lines: Vec<&str> = content.lines().collect();
+    let (deck, tags, mut i) = parse_front_matter(&lines);
+    let deck = deck.unwrap_or_else(|| options.deck.clone());
+
+    let mut blocks = Vec::new();
+    while i < lines.len() {
+        let Some(info) = lines[i].trim().strip_prefix("```") else {
+            i += 1;
+            continue;
+        };
+        let info = info.trim();
+        let start = i;
+        let end = (start + 1..lines.len())
+            .find(|&j| lines[j].trim() == "```")
+            .unwrap_or(lines.len());
+        i = end + 1;
+
+        let body = lines[start + 1..end].join("\n");
+        let content = if info == options.qa_marker {
+            let Some(content) = parse_question_answer(&body, options) else {
+                continue;
+            };
+            content
+        } else if info == options.cloze_marker {
+            BlockContent::Cloze(body.trim().to_string())
+        } else {
+            continue;
+        };
+
+        let id = lines
+            .get(i)
+            .and_then(|line| line.trim().strip_prefix('^'))
+            .filter(|id| {
+                !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            })
+            .map(str::to_string);
+        blocks.push(MarkdownBlock {
+            file: file.to_path_buf(),
+            line: start,
+            id_line: i,
+            id,
+            deck: deck.clone(),
+            tags: tags.clone(),
+            content,
+        });
+    }
+    blocks
+}
+
+/// Read `deck` and `tags` from YAML front matter, returning the line after it.
+fn parse_front_matter(lines: &[&str]) -> (Option<String>, Vec<String>, usize) {
+    if lines.first().map(|l| l.trim()) != Some("---") {
+        return (None, Vec::new(), 0);
+    }
+    let Some(end) = (1..lines.len()).find(|&i| lines[i].trim() == "---") else {
+        return (None, Vec::new(), 0);
+    };
+
+    let unquote = |value: &str| value.trim().trim_matches(['"', '\'']).to_string();
+    let mut deck = None;
+    let mut tags = Vec::new();
+    let mut in_tags = false;
+    for line in &lines[1..end] {
+        if let Some(item) = line.trim_start().strip_prefix("- ") {
+            if in_tags {
+                tags.push(unquote(item));
+            }
+            continue;
+        }
+        in_tags = false;
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        match key.trim() {
+            "deck" => deck = Some(unquote(value)).filter(|d| !d.is_empty()),
+            "tags" => {
+                let value = value.trim();
+                if let Some(list) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+                    tags.extend(list.split(',').map(unquote).filter(|t| !t.is_empty()));
+                } else if value.is_empty() {
+                    in_tags = true;
+                } else {
+                    tags.extend(value.split_whitespace().map(unquote));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Obsidian nests tags with '/', Anki with '::'
+    let tags = tags
+        .into_iter()
+        .map(|tag| tag.trim_start_matches('#').replace('/', "::"))
+        .collect();
+    (deck, tags, end + 1)
+}
+
+/// Split a question/answer block at the answer prefix.
+fn parse_question_answer(body: &str, options: &MarkdownImportOptions) -> Option<BlockContent> {
+    let mut question = Vec::new();
+    let mut answer: Option<Vec<&str>> = None;
+    for line in body.lines() {
+        match &mut answer {
+            Some(answer) => answer.push(line),
+            None => match line
+                .trim_start()
+                .strip_prefix(options.answer_prefix.as_str())
+            {
+                Some(rest) => answer = Some(vec![rest.trim_start()]),
+                None => question.push(line),
+            },
+        }
+    }
+
+    let question = question.join("\n");
+    let question = question.trim();
+    let question = question
+        .strip_prefix(options.question_prefix.as_str())
+        .unwrap_or(question)
+        .trim();
+    let answer = answer?.join("\n").trim().to_string();
+    if question.is_empty() || answer.is_empty() {
+        return None;
+    }
+    Some(BlockContent::QuestionAnswer {
+        question: question.to_string(),
+        answer,
+    })
+}
+
+/// Turn `==highlights==` into cloze deletions, unless the text already has some.
+fn highlights_to_cloze(text: &str) -> String {
+    if text.contains("{{c") {
+        return text.to_string();
+    }
+    let highlight = regex_lite::Regex::new(r"==([^=]+)==").unwrap();
+    let mut number = 0;
+    highlight
+        .replace_all(text, |caps: &regex_lite::Captures| {
+            number += 1;
+            format!("{{{{c{}::{}}}}}", number, &caps[1])
+        })
+        .into_owned()
+}
+
+/// Convert Markdown to field HTML, collecting embedded media as
+/// `(path, filename)` pairs.
+fn markdown_to_html(
+    markdown: &str,
+    base: &Path,
+    root: &Path,
+    media: &mut Vec<(PathBuf, String)>,
+) -> String {
+    let embed =
+        regex_lite::Regex::new(r"!\[\[([^\]|]+)(?:\|[^\]]*)?\]\]|!\[[^\]]*\]\(<?([^)>]+)>?\)")
+            .unwrap();
+    let bold = regex_lite::Regex::new(r"\*\*(.+?)\*\*").unwrap();
+    let italic = regex_lite::Regex::new(r"\*([^*]+)\*").unwrap();
+
+    let format_text = |text: &str| {
+        let text = text
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        let text = bold.replace_all(&text, "<b>$1</b>");
+        italic.replace_all(&text, "<i>$1</i>").into_owned()
+    };
+
+    let mut html = String::new();
+    let mut last = 0;
+    for caps in embed.captures_iter(markdown) {
+        let whole = caps.get(0).unwrap();
+        html.push_str(&format_text(&markdown[last..whole.start()]));
+        last = whole.end();
+
+        let target = caps
+            .get(1)
+            .or_else(|| caps.get(2))
+            .map(|m| m.as_str().trim().replace("%20", " "))
+            .unwrap_or_default();
+        let path = [base.join(&target), root.join(&target)]
+            .into_iter()
+            .find(|p| p.is_file())
+            .unwrap_or_else(|| base.join(&target));
+        let filename = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or(target);
+        let audio = Path::new(&filename).extension().is_some_and(|ext| {
+            matches!(
+                ext.to_string_lossy().to_lowercase().as_str(),
+                "mp3" | "wav" | "ogg" | "m4a" | "flac" | "opus"
+            )
+        });
+        if audio {
+            html.push_str(&format!("[sound:{}]", filename));
+        } else {
+            html.push_str(&format!("<img src=\"{}\">", filename));
+        }
+        media.push((path, filename));
+    }
+    html.push_str(&format_text(&markdown[last..]));
+
+    html.trim().replace('\n', "<br>")
+}
+
+/// A new block ID, unique among `taken`.
+fn generate_block_id(block: &MarkdownBlock, taken: &HashSet<String>) -> String {
+    let mut seed = format!("{}:{}", block.file.display(), block.line);
+    loop {
+        // FNV-1a, rendered in base 36 like Obsidian's generated IDs
+        let mut hash = seed.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        let mut id = String::new();
+        for _ in 0..6 {
+            id.push(char::from_digit((hash % 36) as u32, 36).unwrap_or('0'));
+            hash /= 36;
+        }
+        if !taken.contains(&id) {
+            return id;
+        }
+        seed.push('+');
+    }
+}
+
 /// Result of validating a single note.
 #[derive(Debug, Clone)]
 pub struct ValidationResult {
@@ -467,6 +1859,9 @@ pub struct ValidationResult {
     pub valid: bool,
     /// Validation errors, if any.
     pub errors: Vec<String>,
+    /// Broken rules (from [`ImportEngine::validate_with`]); their messages
+    /// are also included in `errors`.
+    pub violations: Vec<RuleViolation>,
 }
 
 /// Options for smart add operation.
@@ -494,7 +1889,8 @@ impl Default for SmartAddOptions {
 }
 
 /// Status of a smart add operation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum SmartAddStatus {
     /// Note was successfully added.
     Added,
@@ -521,7 +1917,7 @@ pub enum SmartAddStatus {
 }
 
 /// Result of a smart add operation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SmartAddResult {
     /// The note ID if successfully added, None if rejected.
     pub note_id: Option<i64>,
@@ -532,3 +1928,48 @@ pub struct SmartAddResult {
     /// IDs of similar notes found (potential duplicates).
     pub similar_notes: Vec<i64>,
 }
+
+impl WorkflowReport for SmartAddResult {
+    fn summary(&self) -> String {
+        let note = match self.note_id {
+            Some(id) => format!("note {}", id),
+            None => "note".to_string(),
+        };
+        match &self.status {
+            SmartAddStatus::Added => format!("Added {}", note),
+            SmartAddStatus::AddedWithWarning { warning } => {
+                format!("Added {} with a warning: {}", note, warning)
+            }
+            SmartAddStatus::RejectedDuplicate { existing_id } => {
+                format!("Rejected as a duplicate of note {}", existing_id)
+            }
+            SmartAddStatus::RejectedEmptyFields { fields } => {
+                format!("Rejected with empty fields: {}", fields.join(", "))
+            }
+            SmartAddStatus::RejectedInvalid { errors } => {
+                format!("Rejected as invalid: {}", errors.join("; "))
+            }
+        }
+    }
+
+    fn details(&self) -> Vec<String> {
+        let mut details = Vec::new();
+        if !self.suggested_tags.is_empty() {
+            details.push(format!("suggested tags: {}", self.suggested_tags.join(" ")));
+        }
+        if !self.similar_notes.is_empty() {
+            let ids: Vec<String> = self.similar_notes.iter().map(|id| id.to_string()).collect();
+            details.push(format!("similar notes: {}", ids.join(", ")));
+        }
+        details
+    }
+
+    fn affected(&self) -> AffectedIds {
+        AffectedIds {
+            note_ids: self.note_id.into_iter().collect(),
+            card_ids: Vec::new(),
+        }
+    }
+
+    report_fields!();
+}
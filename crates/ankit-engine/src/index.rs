@@ -0,0 +1,601 @@
+//! Local full-text index of notes for offline querying.
+//!
+//! An index records the fields and tags of every note matching a search,
+//! along with an inverted index of the words in each field. Searching it
+//! needs no round trips to Anki, so repeated analyses can build it once,
+//! save it as JSON, and query it, look for similar notes, or find duplicates
+//! as often as they like.
+//!
+//! Words are indexed after stripping HTML and lowercasing. Queries are
+//! words separated by spaces, all of which must match; a query may also use:
+//!
+//! - `OR` between words to match either side (`eat OR drink`)
+//! - `-word` to exclude notes containing a word
+//! - `field:word` to match in one field only (`back:eat`)
+//! - `word*` to match words starting with `word`
+//! - `word~` to match words within one edit (two for words over five
+//!   characters), or `word~N` for at most `N` edits
+//!
+//! `OR` binds loosest, so `a b OR c` matches notes with both `a` and `b`,
+//! or with `c`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ankit_engine::Engine;
+//! use ankit_engine::index::NoteIndex;
+//!
+//! # async fn example() -> ankit_engine::Result<()> {
+//! let engine = Engine::new();
+//!
+//! let index = engine.index().build("deck:Italian").await?;
+//! index.write("italian-index.json")?;
+//!
+//! // Later, without Anki
+//! let index = NoteIndex::load("italian-index.json")?;
+//! for note in index.search("back:eat OR mangiare~")? {
+//!     println!("{}: {:?}", note.note_id, note.fields.get("Front"));
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::similarity::{levenshtein_distance, levenshtein_similarity};
+use crate::{Error, Result};
+use ankit::AnkiClient;
+use ankit::key::KeyNormalization;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Version of the file format written by [`NoteIndex::write`].
+pub const NOTE_INDEX_FORMAT_VERSION: u32 = 1;
+
+/// Engine for building note indexes.
+pub struct IndexEngine<'a> {
+    client: &'a AnkiClient,
+}
+
+impl<'a> IndexEngine<'a> {
+    pub(crate) fn new(client: &'a AnkiClient) -> Self {
+        Self { client }
+    }
+
+    /// Index the notes matching `query`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let index = engine.index().build("deck:Japanese").await?;
+    /// println!("Indexed {} notes", index.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn build(&self, query: &str) -> Result<NoteIndex> {
+        let mut note_ids = self.client.notes().find(query).await?;
+        note_ids.sort_unstable();
+        let infos = if note_ids.is_empty() {
+            Vec::new()
+        } else {
+            self.client.notes().info(&note_ids).await?
+        };
+
+        let mut notes: Vec<IndexedNote> = infos
+            .into_iter()
+            .map(|info| {
+                let mut tags = info.tags;
+                tags.sort();
+                IndexedNote {
+                    note_id: info.note_id,
+                    model: info.model_name,
+                    fields: info
+                        .fields
+                        .into_iter()
+                        .map(|(name, field)| (name, field.value))
+                        .collect(),
+                    tags,
+                }
+            })
+            .collect();
+        notes.sort_by_key(|note| note.note_id);
+
+        Ok(NoteIndex::from(IndexData {
+            version: NOTE_INDEX_FORMAT_VERSION,
+            query: query.to_string(),
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            notes,
+        }))
+    }
+}
+
+/// One note in a [`NoteIndex`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedNote {
+    /// The note ID.
+    pub note_id: i64,
+    /// The note type (model) name.
+    pub model: String,
+    /// Field values as stored in Anki, keyed by field name.
+    pub fields: BTreeMap<String, String>,
+    /// Tags, sorted.
+    pub tags: Vec<String>,
+}
+
+/// A note similar to another, found with [`NoteIndex::similar`].
+#[derive(Debug, Clone, Serialize)]
+pub struct SimilarNote {
+    /// The note ID.
+    pub note_id: i64,
+    /// Similarity of the field values, from 0.0 to 1.0.
+    pub similarity: f64,
+}
+
+/// Notes indexed for offline full-text search.
+///
+/// Built with [`IndexEngine::build`] or loaded with [`NoteIndex::load`].
+/// See the [module documentation](self) for the query syntax.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "IndexData")]
+pub struct NoteIndex {
+    version: u32,
+    query: String,
+    created_at: u64,
+    notes: Vec<IndexedNote>,
+    /// Word -> notes containing it in any field.
+    #[serde(skip)]
+    terms: HashMap<String, BTreeSet<usize>>,
+    /// Lowercased field name -> word -> notes containing it in that field.
+    #[serde(skip)]
+    field_terms: HashMap<String, HashMap<String, BTreeSet<usize>>>,
+}
+
+/// The stored part of a [`NoteIndex`]; the postings are rebuilt on load.
+#[derive(Deserialize)]
+struct IndexData {
+    version: u32,
+    query: String,
+    created_at: u64,
+    notes: Vec<IndexedNote>,
+}
+
+impl From<IndexData> for NoteIndex {
+    fn from(data: IndexData) -> Self {
+        let mut terms: HashMap<String, BTreeSet<usize>> = HashMap::new();
+        let mut field_terms: HashMap<String, HashMap<String, BTreeSet<usize>>> = HashMap::new();
+        for (i, note) in data.notes.iter().enumerate() {
+            for (name, value) in &note.fields {
+                let field = field_terms.entry(name.to_lowercase()).or_default();
+                for word in words(value) {
+                    terms.entry(word.clone()).or_default().insert(i);
+                    field.entry(word).or_default().insert(i);
+                }
+            }
+        }
+        Self {
+            version: data.version,
+            query: data.query,
+            created_at: data.created_at,
+            notes: data.notes,
+            terms,
+            field_terms,
+        }
+    }
+}
+
+impl NoteIndex {
+    /// Load an index from a file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        let index: Self = serde_json::from_str(&contents).map_err(|e| {
+            Error::Index(format!("Failed to parse index '{}': {}", path.display(), e))
+        })?;
+        if index.version > NOTE_INDEX_FORMAT_VERSION {
+            return Err(Error::Index(format!(
+                "index '{}' has unsupported format version {}",
+                path.display(),
+                index.version
+            )));
+        }
+        Ok(index)
+    }
+
+    /// Write the index to a file, creating parent directories if needed.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string(self)
+            .map_err(|e| Error::Index(format!("Failed to serialize index: {}", e)))?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// The search the notes were found with.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// When the index was built (Unix timestamp, seconds).
+    pub fn created_at(&self) -> u64 {
+        self.created_at
+    }
+
+    /// The indexed notes, in ascending ID order.
+    pub fn notes(&self) -> &[IndexedNote] {
+        &self.notes
+    }
+
+    /// Number of indexed notes.
+    pub fn len(&self) -> usize {
+        self.notes.len()
+    }
+
+    /// Whether the index has no notes.
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    /// Look up an indexed note by ID.
+    pub fn get(&self, note_id: i64) -> Option<&IndexedNote> {
+        self.position(note_id).map(|i| &self.notes[i])
+    }
+
+    fn position(&self, note_id: i64) -> Option<usize> {
+        self.notes
+            .binary_search_by_key(&note_id, |note| note.note_id)
+            .ok()
+    }
+
+    /// Find the notes matching a query, in ascending ID order.
+    ///
+    /// Returns an error if the query is malformed, such as a `~` followed by
+    /// something other than a number.
+    pub fn search(&self, query: &str) -> Result<Vec<&IndexedNote>> {
+        let mut found: BTreeSet<usize> = BTreeSet::new();
+        for alternative in parse_query(query)? {
+            let mut matches: Option<BTreeSet<usize>> = None;
+            let mut excluded = BTreeSet::new();
+            for clause in &alternative {
+                let notes = self.clause_notes(clause);
+                if clause.negated {
+                    excluded.extend(notes);
+                    continue;
+                }
+                matches = Some(match matches {
+                    Some(matches) => matches.intersection(&notes).copied().collect(),
+                    None => notes,
+                });
+            }
+            let matches = matches.unwrap_or_else(|| (0..self.notes.len()).collect());
+            found.extend(matches.difference(&excluded));
+        }
+        Ok(found.into_iter().map(|i| &self.notes[i]).collect())
+    }
+
+    /// Notes matching every word of a clause.
+    fn clause_notes(&self, clause: &Clause) -> BTreeSet<usize> {
+        let empty = HashMap::new();
+        let terms = match &clause.field {
+            Some(field) => self.field_terms.get(field).unwrap_or(&empty),
+            None => &self.terms,
+        };
+        let mut result: Option<BTreeSet<usize>> = None;
+        for word in &clause.words {
+            let notes: BTreeSet<usize> = match clause.kind {
+                TermKind::Exact => terms.get(word).cloned().unwrap_or_default(),
+                TermKind::Prefix => terms
+                    .iter()
+                    .filter(|(term, _)| term.starts_with(word.as_str()))
+                    .flat_map(|(_, notes)| notes.iter().copied())
+                    .collect(),
+                TermKind::Fuzzy(edits) => {
+                    let len = word.chars().count();
+                    terms
+                        .iter()
+                        .filter(|(term, _)| term.chars().count().abs_diff(len) <= edits)
+                        .filter(|(term, _)| levenshtein_distance(term, word) <= edits)
+                        .flat_map(|(_, notes)| notes.iter().copied())
+                        .collect()
+                }
+            };
+            result = Some(match result {
+                Some(result) => result.intersection(&notes).copied().collect(),
+                None => notes,
+            });
+        }
+        result.unwrap_or_default()
+    }
+
+    /// Notes whose `field` is at least `threshold` similar to the same field
+    /// of `note_id`, most similar first.
+    ///
+    /// Similarity is normalized Levenshtein distance of the values without
+    /// HTML, ignoring case. Only notes sharing a word with the note in that
+    /// field are compared, so notes with no words in common are never
+    /// reported.
+    pub fn similar(&self, note_id: i64, field: &str, threshold: f64) -> Vec<SimilarNote> {
+        let Some(i) = self.position(note_id) else {
+            return Vec::new();
+        };
+        let Some(value) = self.notes[i].fields.get(field) else {
+            return Vec::new();
+        };
+        let Some(terms) = self.field_terms.get(&field.to_lowercase()) else {
+            return Vec::new();
+        };
+
+        let candidates: BTreeSet<usize> = words(value)
+            .filter_map(|word| terms.get(&word))
+            .flatten()
+            .copied()
+            .filter(|&j| j != i)
+            .collect();
+        let key = KeyNormalization::default().apply(value);
+        let mut similar: Vec<SimilarNote> = candidates
+            .into_iter()
+            .filter_map(|j| {
+                let other = self.notes[j].fields.get(field)?;
+                let similarity =
+                    levenshtein_similarity(&key, &KeyNormalization::default().apply(other));
+                (similarity >= threshold).then(|| SimilarNote {
+                    note_id: self.notes[j].note_id,
+                    similarity,
+                })
+            })
+            .collect();
+        similar.sort_by(|a, b| {
+            b.similarity
+                .total_cmp(&a.similarity)
+                .then(a.note_id.cmp(&b.note_id))
+        });
+        similar
+    }
+
+    /// Find duplicate notes among the indexed notes.
+    ///
+    /// Works like
+    /// [`find_duplicates`](crate::deduplicate::DeduplicateEngine::find_duplicates)
+    /// without contacting Anki. The query's `search` is ignored; every
+    /// indexed note is considered.
+    #[cfg(feature = "deduplicate")]
+    pub fn find_duplicates(
+        &self,
+        query: &crate::deduplicate::DedupeQuery,
+    ) -> Result<Vec<crate::deduplicate::DuplicateGroup>> {
+        use crate::deduplicate::{NoteForDedupe, group_duplicates};
+
+        let notes = self.notes.iter().map(|note| {
+            let raw = note
+                .fields
+                .get(&query.key_field)
+                .map(String::as_str)
+                .unwrap_or_default();
+            let entry = NoteForDedupe {
+                note_id: note.note_id,
+                non_empty_count: note
+                    .fields
+                    .values()
+                    .filter(|value| !value.trim().is_empty())
+                    .count(),
+                tag_count: note.tags.len(),
+            };
+            (raw, entry)
+        });
+        group_duplicates(query, notes)
+    }
+}
+
+/// The words of a field value: HTML stripped, lowercased, and split on
+/// anything that isn't a letter or digit.
+fn words(value: &str) -> impl Iterator<Item = String> {
+    KeyNormalization::default()
+        .apply(value)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect::<Vec<_>>()
+        .into_iter()
+}
+
+/// How a query word is matched against indexed words.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TermKind {
+    Exact,
+    Prefix,
+    /// At most this many edits.
+    Fuzzy(usize),
+}
+
+/// One space-separated part of a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Clause {
+    negated: bool,
+    /// Lowercased field name, if restricted to one field.
+    field: Option<String>,
+    /// The words of the clause, all of which must match.
+    words: Vec<String>,
+    kind: TermKind,
+}
+
+/// Parse a query into alternatives separated by `OR`, each a list of
+/// clauses that must all match.
+fn parse_query(query: &str) -> Result<Vec<Vec<Clause>>> {
+    let invalid = |message: String| Error::Validation(format!("invalid index query: {}", message));
+
+    let mut alternatives = vec![Vec::new()];
+    for token in query.split_whitespace() {
+        if token == "OR" {
+            alternatives.push(Vec::new());
+            continue;
+        }
+
+        let (negated, token) = match token.strip_prefix('-') {
+            Some(rest) if !rest.is_empty() => (true, rest),
+            _ => (false, token),
+        };
+        let (field, token) = match token.split_once(':') {
+            Some((field, rest)) if !field.is_empty() && !rest.is_empty() => {
+                (Some(field.to_lowercase()), rest)
+            }
+            _ => (None, token),
+        };
+        let (kind, token) = if let Some(rest) = token.strip_suffix('*') {
+            (TermKind::Prefix, rest)
+        } else if let Some((rest, edits)) = token.split_once('~') {
+            let edits = if edits.is_empty() {
+                if rest.chars().count() > 5 { 2 } else { 1 }
+            } else {
+                edits
+                    .parse()
+                    .map_err(|_| invalid(format!("'{}' is not a number of edits", edits)))?
+            };
+            (TermKind::Fuzzy(edits), rest)
+        } else {
+            (TermKind::Exact, token)
+        };
+
+        let words: Vec<String> = words(token).collect();
+        if words.is_empty() {
+            return Err(invalid(format!("'{}' has no words to match", token)));
+        }
+        alternatives
+            .last_mut()
+            .expect("alternatives is never empty")
+            .push(Clause {
+                negated,
+                field,
+                words,
+                kind,
+            });
+    }
+
+    if alternatives.iter().any(Vec::is_empty) {
+        return Err(invalid(format!("'{}' has an empty alternative", query)));
+    }
+    Ok(alternatives)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(note_id: i64, front: &str, back: &str) -> IndexedNote {
+        IndexedNote {
+            note_id,
+            model: "Basic".to_string(),
+            fields: BTreeMap::from([
+                ("Front".to_string(), front.to_string()),
+                ("Back".to_string(), back.to_string()),
+            ]),
+            tags: vec![],
+        }
+    }
+
+    fn index() -> NoteIndex {
+        NoteIndex::from(IndexData {
+            version: NOTE_INDEX_FORMAT_VERSION,
+            query: "deck:Italian".to_string(),
+            created_at: 0,
+            notes: vec![
+                note(1, "mangiare", "to <b>eat</b>"),
+                note(2, "bere", "to drink"),
+                note(3, "mangiamo", "we eat"),
+                note(4, "dormire", "to sleep"),
+            ],
+        })
+    }
+
+    fn ids(index: &NoteIndex, query: &str) -> Vec<i64> {
+        index
+            .search(query)
+            .unwrap()
+            .iter()
+            .map(|note| note.note_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_boolean_queries() {
+        let index = index();
+        assert_eq!(ids(&index, "eat"), vec![1, 3]);
+        assert_eq!(ids(&index, "to eat"), vec![1]);
+        assert_eq!(ids(&index, "eat OR drink"), vec![1, 2, 3]);
+        assert_eq!(ids(&index, "to -eat"), vec![2, 4]);
+        assert_eq!(ids(&index, "-to"), vec![3]);
+        assert_eq!(ids(&index, "EAT"), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_field_prefix_and_fuzzy_queries() {
+        let index = index();
+        assert_eq!(ids(&index, "front:mangia*"), vec![1, 3]);
+        assert_eq!(ids(&index, "back:mangia*"), Vec::<i64>::new());
+        assert_eq!(ids(&index, "mangare~"), vec![1]);
+        assert_eq!(ids(&index, "mangare~0"), Vec::<i64>::new());
+        assert_eq!(ids(&index, "drnk~"), vec![2]);
+    }
+
+    #[test]
+    fn test_invalid_queries() {
+        let index = index();
+        for query in ["eat~x", "eat OR", "OR eat", "!!"] {
+            assert!(
+                matches!(index.search(query), Err(Error::Validation(_))),
+                "{}",
+                query
+            );
+        }
+    }
+
+    #[test]
+    fn test_similar() {
+        let index = index();
+        let similar = index.similar(1, "Back", 0.5);
+        let similar: Vec<i64> = similar.iter().map(|s| s.note_id).collect();
+        assert_eq!(similar, vec![3, 4]);
+
+        assert_eq!(index.similar(1, "Back", 0.9).len(), 0);
+        assert!(index.similar(99, "Back", 0.5).is_empty());
+        assert!(index.similar(1, "Missing", 0.5).is_empty());
+    }
+
+    #[test]
+    fn test_roundtrip_rebuilds_postings() {
+        let index = index();
+        let json = serde_json::to_string(&index).unwrap();
+        assert!(!json.contains("field_terms"));
+
+        let loaded: NoteIndex = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.notes(), index.notes());
+        assert_eq!(ids(&loaded, "eat OR drink"), vec![1, 2, 3]);
+        assert_eq!(loaded.get(2).unwrap().fields["Front"], "bere");
+    }
+
+    #[cfg(feature = "deduplicate")]
+    #[test]
+    fn test_find_duplicates() {
+        use crate::deduplicate::{DedupeQuery, FuzzyOptions, KeepStrategy, MatchMode};
+
+        let index = index();
+        let query = DedupeQuery {
+            search: String::new(),
+            key_field: "Front".to_string(),
+            keep: KeepStrategy::First,
+            match_mode: MatchMode::Fuzzy(FuzzyOptions::with_threshold(0.7)),
+            normalization: Default::default(),
+        };
+        let groups = index.find_duplicates(&query).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].keep_note_id, 1);
+        assert_eq!(groups[0].duplicate_note_ids, vec![3]);
+    }
+}
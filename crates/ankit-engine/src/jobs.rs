@@ -0,0 +1,666 @@
+//! Scheduled workflows.
+//!
+//! A [`JobScheduler`] runs workflows on cron-style [`Schedule`]s in a
+//! long-lived process: a nightly backup, a weekly duplicate preview, a daily
+//! leech report. The last run of each job can be persisted to a state file,
+//! so a job whose run was missed while the process was down runs as soon as
+//! it starts again. Every run is reported as a [`JobRun`] to an optional
+//! callback, appended to an optional JSON Lines log, and sent to any
+//! [`Notifier`]s.
+//!
+//! A failing job is recorded and retried at its next scheduled time; it does
+//! not stop the scheduler.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ankit_engine::Engine;
+//! use ankit_engine::analyze::ProblemCriteria;
+//! use ankit_engine::jobs::{JobOutput, Schedule};
+//! use ankit_engine::notify::{WebhookFormat, WebhookNotifier};
+//!
+//! # async fn example() -> ankit_engine::Result<()> {
+//! let engine = Engine::new();
+//!
+//! engine
+//!     .jobs()
+//!     .job("nightly-backup", "0 3 * * *".parse()?, |engine| {
+//!         Box::pin(async move {
+//!             let result = engine.backup().backup_collection("backups/").await?;
+//!             Ok(JobOutput::from_report(&result))
+//!         })
+//!     })
+//!     .job("daily-leeches", Schedule::parse("@daily")?, |engine| {
+//!         Box::pin(async move {
+//!             let problems = engine
+//!                 .analyze()
+//!                 .find_problems("deck:*", ProblemCriteria::default())
+//!                 .await?;
+//!             Ok(JobOutput::new(format!("{} problem cards", problems.len())))
+//!         })
+//!     })
+//!     .state_file("jobs-state.json")
+//!     .log_file("jobs.jsonl")
+//!     .notifier(WebhookNotifier::new("https://discord.com/api/webhooks/...").format(WebhookFormat::Discord))
+//!     .run()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+
+use crate::notify::{Notification, Notifier};
+use crate::report::WorkflowReport;
+use crate::{Engine, Error, Result};
+
+/// Longest time the scheduler sleeps before checking the clock again.
+const MAX_SLEEP_SECS: u64 = 60;
+
+/// A cron-style schedule, evaluated in UTC unless an offset is set.
+///
+/// Expressions have five fields: minute (0-59), hour (0-23), day of month
+/// (1-31), month (1-12 or `jan`-`dec`), and day of week (0-7 or
+/// `sun`-`sat`, where both 0 and 7 are Sunday). Each field is `*`, a value,
+/// a range `a-b`, a step `*/n` or `a-b/n`, or a comma-separated list of
+/// these. As in cron, when both day fields are restricted a day matching
+/// either one is used.
+///
+/// The shortcuts `@hourly`, `@daily` (or `@midnight`), `@weekly`, and
+/// `@monthly` are also accepted.
+///
+/// # Example
+///
+/// ```
+/// use ankit_engine::jobs::Schedule;
+///
+/// // 03:30 on weekdays
+/// let schedule = Schedule::parse("30 3 * * mon-fri").unwrap();
+/// // Thursday 1970-01-01 00:00 UTC -> Thursday 03:30
+/// assert_eq!(schedule.next_after(0), Some(3 * 3600 + 30 * 60));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    expression: String,
+    minutes: u64,
+    hours: u32,
+    days: u32,
+    months: u16,
+    weekdays: u8,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+    offset_secs: i64,
+}
+
+impl Schedule {
+    /// Parse a cron expression or shortcut.
+    pub fn parse(expression: &str) -> Result<Self> {
+        let expanded = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(Error::Validation(format!(
+                "schedule '{}' must have 5 fields",
+                expression
+            )));
+        }
+        let invalid =
+            |e: String| Error::Validation(format!("invalid schedule '{}': {}", expression, e));
+
+        let weekdays = parse_field(fields[4], 0, 7, DAY_NAMES).map_err(invalid)?;
+        // Fold 7 (Sunday) onto 0
+        let weekdays = (weekdays | (weekdays >> 7)) & 0x7f;
+        Ok(Self {
+            expression: expression.trim().to_string(),
+            minutes: parse_field(fields[0], 0, 59, &[]).map_err(invalid)?,
+            hours: parse_field(fields[1], 0, 23, &[]).map_err(invalid)? as u32,
+            days: parse_field(fields[2], 1, 31, &[]).map_err(invalid)? as u32,
+            months: parse_field(fields[3], 1, 12, MONTH_NAMES).map_err(invalid)? as u16,
+            weekdays: weekdays as u8,
+            days_restricted: fields[2] != "*",
+            weekdays_restricted: fields[4] != "*",
+            offset_secs: 0,
+        })
+    }
+
+    /// Evaluate the schedule at a fixed offset from UTC, in minutes (e.g.
+    /// `-300` for UTC-5).
+    pub fn utc_offset(mut self, minutes: i32) -> Self {
+        self.offset_secs = i64::from(minutes) * 60;
+        self
+    }
+
+    /// The first matching time strictly after `after`, both in Unix seconds.
+    ///
+    /// Returns `None` if nothing matches within about five years (e.g.
+    /// `0 0 31 2 *`).
+    pub fn next_after(&self, after: u64) -> Option<u64> {
+        let mut local = (after as i64 + self.offset_secs).div_euclid(60) * 60 + 60;
+        let limit = local + 5 * 366 * 86_400;
+        while local < limit {
+            let days = local.div_euclid(86_400);
+            let (_, month, day) = civil(days);
+            let weekday = (days + 4).rem_euclid(7) as u32;
+            if !self.day_matches(month, day, weekday) {
+                local = (days + 1) * 86_400;
+                continue;
+            }
+            let hour = (local.rem_euclid(86_400) / 3600) as u32;
+            if self.hours & (1 << hour) == 0 {
+                local = (local.div_euclid(3600) + 1) * 3600;
+                continue;
+            }
+            let minute = (local.rem_euclid(3600) / 60) as u32;
+            if self.minutes & (1 << minute) == 0 {
+                local += 60;
+                continue;
+            }
+            return u64::try_from(local - self.offset_secs).ok();
+        }
+        None
+    }
+
+    fn day_matches(&self, month: u32, day: u32, weekday: u32) -> bool {
+        if self.months & (1 << month) == 0 {
+            return false;
+        }
+        let day_ok = self.days & (1 << day) != 0;
+        let weekday_ok = self.weekdays & (1 << weekday) != 0;
+        if self.days_restricted && self.weekdays_restricted {
+            day_ok || weekday_ok
+        } else {
+            day_ok && weekday_ok
+        }
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.expression)
+    }
+}
+
+const DAY_NAMES: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+const MONTH_NAMES: &[&str] = &[
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+/// Parse one cron field into a bitset of allowed values.
+fn parse_field(
+    field: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+) -> std::result::Result<u64, String> {
+    let value = |s: &str| -> std::result::Result<u32, String> {
+        let lower = s.to_ascii_lowercase();
+        // Named values count from `min` (jan = 1, sun = 0)
+        if let Some(index) = names.iter().position(|name| *name == lower) {
+            return Ok(index as u32 + min);
+        }
+        let n: u32 = s.parse().map_err(|_| format!("'{}' is not a number", s))?;
+        if n < min || n > max {
+            return Err(format!("{} is outside {}-{}", n, min, max));
+        }
+        Ok(n)
+    };
+
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| format!("invalid step in '{}'", part))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (value(a)?, value(b)?)
+        } else {
+            let n = value(range)?;
+            (n, if step > 1 { max } else { n })
+        };
+        if start > end {
+            return Err(format!("empty range '{}'", range));
+        }
+        for n in (start..=end).step_by(step as usize) {
+            bits |= 1 << n;
+        }
+    }
+    Ok(bits)
+}
+
+/// Year, month, and day for days since 1970-01-01.
+fn civil(days: i64) -> (i64, u32, u32) {
+    // Howard Hinnant's days-to-civil algorithm
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    (yoe + era * 400 + i64::from(month <= 2), month, day)
+}
+
+/// What a job produced.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JobOutput {
+    /// One-line summary of the result.
+    pub summary: String,
+    /// The full report, if the job produced one.
+    pub report: Option<serde_json::Value>,
+}
+
+impl JobOutput {
+    /// Output with only a summary.
+    pub fn new(summary: impl Into<String>) -> Self {
+        Self {
+            summary: summary.into(),
+            report: None,
+        }
+    }
+
+    /// Output from a workflow report: its summary and JSON.
+    pub fn from_report(report: &impl WorkflowReport) -> Self {
+        Self {
+            summary: report.summary(),
+            report: report.to_json().ok(),
+        }
+    }
+}
+
+/// The result of one job run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRun {
+    /// Name of the job.
+    pub job: String,
+    /// When the run started, in Unix seconds.
+    pub started_at: u64,
+    /// When the run finished, in Unix seconds.
+    pub finished_at: u64,
+    /// Whether the job succeeded.
+    pub success: bool,
+    /// The job's summary, or the error message if it failed.
+    pub summary: String,
+    /// The job's full report, if any.
+    pub report: Option<serde_json::Value>,
+}
+
+impl From<&JobRun> for Notification {
+    fn from(run: &JobRun) -> Self {
+        Self {
+            source: run.job.clone(),
+            success: run.success,
+            summary: run.summary.clone(),
+            details: Vec::new(),
+            report: run.report.clone(),
+        }
+    }
+}
+
+/// A job and when it runs next, from [`JobScheduler::status`].
+#[derive(Debug, Clone, Serialize)]
+pub struct JobStatus {
+    /// Name of the job.
+    pub name: String,
+    /// The job's schedule expression.
+    pub schedule: String,
+    /// When the job runs next, in Unix seconds.
+    pub next_run: Option<u64>,
+    /// The most recent run, including runs loaded from the state file.
+    pub last_run: Option<JobRun>,
+}
+
+/// A workflow run by the scheduler.
+type JobFn = Box<dyn for<'e> Fn(&'e Engine) -> BoxFuture<'e, Result<JobOutput>> + Send + Sync>;
+
+/// Callback invoked after every run.
+type RunCallback = Box<dyn Fn(&JobRun) + Send + Sync>;
+
+struct ScheduledJob {
+    name: String,
+    schedule: Schedule,
+    run: JobFn,
+    next: Option<u64>,
+    last: Option<JobRun>,
+}
+
+/// Runs workflows on schedules.
+///
+/// Created with [`Engine::jobs`]; see the [module docs](self) for an
+/// example.
+pub struct JobScheduler {
+    engine: Engine,
+    jobs: Vec<ScheduledJob>,
+    state_file: Option<PathBuf>,
+    log_file: Option<PathBuf>,
+    on_run: Option<RunCallback>,
+    notifiers: Vec<Box<dyn Notifier>>,
+    loaded: bool,
+}
+
+impl fmt::Debug for JobScheduler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JobScheduler")
+            .field(
+                "jobs",
+                &self.jobs.iter().map(|job| &job.name).collect::<Vec<_>>(),
+            )
+            .field("state_file", &self.state_file)
+            .field("log_file", &self.log_file)
+            .finish_non_exhaustive()
+    }
+}
+
+impl JobScheduler {
+    /// Create a scheduler with no jobs.
+    pub fn new(engine: Engine) -> Self {
+        Self {
+            engine,
+            jobs: Vec::new(),
+            state_file: None,
+            log_file: None,
+            on_run: None,
+            notifiers: Vec::new(),
+            loaded: false,
+        }
+    }
+
+    /// Add a job. Jobs with the same name replace earlier ones.
+    pub fn job<F>(mut self, name: impl Into<String>, schedule: Schedule, run: F) -> Self
+    where
+        F: for<'e> Fn(&'e Engine) -> BoxFuture<'e, Result<JobOutput>> + Send + Sync + 'static,
+    {
+        let name = name.into();
+        self.jobs.retain(|job| job.name != name);
+        self.jobs.push(ScheduledJob {
+            name,
+            schedule,
+            run: Box::new(run),
+            next: None,
+            last: None,
+        });
+        self
+    }
+
+    /// Persist the last run of each job to `path` (JSON), and load it on
+    /// start so missed runs are caught up once.
+    pub fn state_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.state_file = Some(path.into());
+        self
+    }
+
+    /// Append every run to `path` as a line of JSON.
+    pub fn log_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.log_file = Some(path.into());
+        self
+    }
+
+    /// Call `callback` after every run.
+    pub fn on_run(mut self, callback: impl Fn(&JobRun) + Send + Sync + 'static) -> Self {
+        self.on_run = Some(Box::new(callback));
+        self
+    }
+
+    /// Send a notification after every run.
+    ///
+    /// Delivery failures are ignored, so an unreachable webhook doesn't stop
+    /// the scheduler.
+    pub fn notifier(mut self, notifier: impl Notifier + 'static) -> Self {
+        self.notifiers.push(Box::new(notifier));
+        self
+    }
+
+    /// The registered jobs, their next run, and their last run.
+    pub fn status(&self) -> Vec<JobStatus> {
+        self.jobs
+            .iter()
+            .map(|job| JobStatus {
+                name: job.name.clone(),
+                schedule: job.schedule.to_string(),
+                next_run: job.next,
+                last_run: job.last.clone(),
+            })
+            .collect()
+    }
+
+    /// Run jobs forever, sleeping until the next one is due.
+    ///
+    /// Only returns on an error reading or writing the state or log file.
+    pub async fn run(mut self) -> Result<()> {
+        loop {
+            self.run_due(unix_now()).await?;
+            let now = unix_now();
+            let wait = self
+                .jobs
+                .iter()
+                .filter_map(|job| job.next)
+                .min()
+                .map_or(MAX_SLEEP_SECS, |next| next.saturating_sub(now))
+                .clamp(1, MAX_SLEEP_SECS);
+            tokio::time::sleep(Duration::from_secs(wait)).await;
+        }
+    }
+
+    /// Run every job due at `now` (Unix seconds), returning their results.
+    ///
+    /// A job is due when its next scheduled time, counted from its last
+    /// run (or from when the scheduler first saw it), is not after `now`.
+    pub async fn run_due(&mut self, now: u64) -> Result<Vec<JobRun>> {
+        self.load_state()?;
+        let mut runs = Vec::new();
+        for index in 0..self.jobs.len() {
+            let job = &mut self.jobs[index];
+            let next = *job.next.get_or_insert_with(|| {
+                let from = job.last.as_ref().map_or(now, |run| run.started_at);
+                job.schedule.next_after(from).unwrap_or(u64::MAX)
+            });
+            if next <= now {
+                runs.push(self.run_index(index, now).await?);
+            }
+        }
+        Ok(runs)
+    }
+
+    /// Run a job immediately, whatever its schedule.
+    pub async fn run_job(&mut self, name: &str) -> Result<JobRun> {
+        self.load_state()?;
+        let index = self
+            .jobs
+            .iter()
+            .position(|job| job.name == name)
+            .ok_or_else(|| Error::Validation(format!("no job named '{}'", name)))?;
+        self.run_index(index, unix_now()).await
+    }
+
+    async fn run_index(&mut self, index: usize, now: u64) -> Result<JobRun> {
+        let job = &self.jobs[index];
+        let started_at = unix_now();
+        let result = (job.run)(&self.engine).await;
+        let (success, output) = match result {
+            Ok(output) => (true, output),
+            Err(e) => (false, JobOutput::new(e.to_string())),
+        };
+        let run = JobRun {
+            job: job.name.clone(),
+            started_at,
+            finished_at: unix_now(),
+            success,
+            summary: output.summary,
+            report: output.report,
+        };
+
+        let job = &mut self.jobs[index];
+        job.next = job.schedule.next_after(now.max(started_at));
+        job.last = Some(run.clone());
+        if let Some(callback) = &self.on_run {
+            callback(&run);
+        }
+        let notification = Notification::from(&run);
+        for notifier in &self.notifiers {
+            let _ = notifier.notify(&notification).await;
+        }
+        self.append_log(&run)?;
+        self.save_state()?;
+        Ok(run)
+    }
+
+    fn load_state(&mut self) -> Result<()> {
+        if self.loaded {
+            return Ok(());
+        }
+        self.loaded = true;
+        let Some(path) = &self.state_file else {
+            return Ok(());
+        };
+        if !path.exists() {
+            return Ok(());
+        }
+        let contents = std::fs::read_to_string(path)?;
+        let mut state: BTreeMap<String, JobRun> =
+            serde_json::from_str(&contents).map_err(std::io::Error::from)?;
+        for job in &mut self.jobs {
+            if job.last.is_none() {
+                job.last = state.remove(&job.name);
+            }
+        }
+        Ok(())
+    }
+
+    fn save_state(&self) -> Result<()> {
+        let Some(path) = &self.state_file else {
+            return Ok(());
+        };
+        let state: BTreeMap<&str, &JobRun> = self
+            .jobs
+            .iter()
+            .filter_map(|job| job.last.as_ref().map(|run| (job.name.as_str(), run)))
+            .collect();
+        let json = serde_json::to_string_pretty(&state).map_err(std::io::Error::from)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    fn append_log(&self, run: &JobRun) -> Result<()> {
+        let Some(path) = &self.log_file else {
+            return Ok(());
+        };
+        let line = serde_json::to_string(run).map_err(std::io::Error::from)?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+}
+
+/// Current Unix time in seconds.
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Unix seconds for a UTC date and time.
+    fn at(year: i64, month: u32, day: u32, hour: u64, minute: u64) -> u64 {
+        // Inverse of `civil`, by search from the epoch; fine for tests
+        let days = (0..30_000)
+            .find(|&d| civil(d) == (year, month, day))
+            .unwrap();
+        days as u64 * 86_400 + hour * 3600 + minute * 60
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Schedule::parse("* * * *").is_err());
+        assert!(Schedule::parse("60 * * * *").is_err());
+        assert!(Schedule::parse("*/0 * * * *").is_err());
+        assert!(Schedule::parse("5-1 * * * *").is_err());
+        assert!(Schedule::parse("0 3 * * funday").is_err());
+    }
+
+    #[test]
+    fn test_next_after_steps_and_lists() {
+        let schedule = Schedule::parse("*/15 9,17 * * *").unwrap();
+        let start = at(2026, 3, 10, 9, 50);
+        assert_eq!(schedule.next_after(start), Some(at(2026, 3, 10, 17, 0)));
+        assert_eq!(
+            schedule.next_after(at(2026, 3, 10, 17, 45)),
+            Some(at(2026, 3, 11, 9, 0))
+        );
+    }
+
+    #[test]
+    fn test_next_after_day_fields() {
+        // 2026-03-10 is a Tuesday
+        let weekly = Schedule::parse("0 4 * * sun").unwrap();
+        assert_eq!(
+            weekly.next_after(at(2026, 3, 10, 0, 0)),
+            Some(at(2026, 3, 15, 4, 0))
+        );
+        let seven = Schedule::parse("0 4 * * 7").unwrap();
+        assert_eq!(
+            seven.next_after(at(2026, 3, 10, 0, 0)),
+            Some(at(2026, 3, 15, 4, 0))
+        );
+
+        // Either the 1st or a Friday
+        let either = Schedule::parse("0 0 1 * fri").unwrap();
+        assert_eq!(
+            either.next_after(at(2026, 3, 10, 0, 0)),
+            Some(at(2026, 3, 13, 0, 0))
+        );
+
+        let leap = Schedule::parse("@monthly").unwrap();
+        assert_eq!(
+            leap.next_after(at(2028, 2, 15, 0, 0)),
+            Some(at(2028, 3, 1, 0, 0))
+        );
+        assert_eq!(Schedule::parse("0 0 31 2 *").unwrap().next_after(0), None);
+    }
+
+    #[test]
+    fn test_utc_offset() {
+        // 03:00 at UTC-5 is 08:00 UTC
+        let schedule = Schedule::parse("0 3 * * *").unwrap().utc_offset(-300);
+        assert_eq!(
+            schedule.next_after(at(2026, 3, 10, 0, 0)),
+            Some(at(2026, 3, 10, 8, 0))
+        );
+    }
+}
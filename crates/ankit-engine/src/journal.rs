@@ -0,0 +1,503 @@
+//! Undo journal for destructive workflows.
+//!
+//! When an [`Engine`](crate::Engine) is configured with
+//! [`EngineOptions::journal_dir`](crate::EngineOptions::journal_dir), destructive
+//! workflows record the state they are about to change to a JSON journal file
+//! before writing anything:
+//!
+//! - `deduplicate().remove_duplicates()` records the full content, tags, deck,
+//!   and card scheduling of every note it deletes
+//! - `progress().reset_deck()` records the scheduling of every card it resets
+//! - `organize().merge_decks()` records the original deck of every card it moves
+//! - `enrich().normalize_fields()` records the field values of every note it
+//!   rewrites
+//!
+//! The path of the journal is returned on the workflow's report, and
+//! [`Engine::rollback`](crate::Engine::rollback) restores the recorded state.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ankit_engine::{Engine, EngineOptions};
+//!
+//! # async fn example() -> ankit_engine::Result<()> {
+//! let engine = Engine::new().with_options(EngineOptions {
+//!     journal_dir: Some("/home/user/anki-journals".into()),
+//!     ..Default::default()
+//! });
+//!
+//! let report = engine.progress().reset_deck("Japanese").await?;
+//!
+//! // Changed our mind: put the scheduling back
+//! if let Some(journal) = report.journal {
+//!     let restored = engine.rollback(&journal).await?;
+//!     println!("Rescheduled {} cards", restored.cards_rescheduled);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::report::{WorkflowReport, report_fields};
+use crate::{Error, NoteBuilder, Result};
+use ankit::{AnkiClient, CardInfo};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A journal of state recorded before a destructive operation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Journal {
+    /// Name of the operation that produced the journal.
+    pub operation: String,
+    /// Creation time (Unix timestamp, seconds).
+    pub created_at: u64,
+    /// Recorded prior state.
+    pub entries: Vec<JournalEntry>,
+}
+
+/// A single piece of recorded state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JournalEntry {
+    /// A note that was deleted. Rollback re-creates it.
+    DeletedNote {
+        /// ID of the deleted note.
+        note_id: i64,
+        /// Deck the note's cards were in.
+        deck: String,
+        /// Note type name.
+        model: String,
+        /// Field values, keyed by field name.
+        fields: HashMap<String, String>,
+        /// Tags on the note.
+        tags: Vec<String>,
+        /// Scheduling of the note's cards, in card order.
+        cards: Vec<CardState>,
+    },
+    /// Scheduling of a card before it was changed. Rollback restores it.
+    Scheduling(CardState),
+    /// Deck of a card before it was moved. Rollback moves it back.
+    CardDeck {
+        /// The card ID.
+        card_id: i64,
+        /// The deck the card was in.
+        deck: String,
+    },
+    /// Field values of a note before they were rewritten. Rollback writes
+    /// them back.
+    NoteFields {
+        /// The note ID.
+        note_id: i64,
+        /// Field values, keyed by field name.
+        fields: HashMap<String, String>,
+    },
+    /// Tags of a note before they were changed. Rollback sets them back.
+    NoteTags {
+        /// The note ID.
+        note_id: i64,
+        /// Tags on the note.
+        tags: Vec<String>,
+    },
+}
+
+/// Scheduling state of a single card.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CardState {
+    /// The card ID.
+    pub card_id: i64,
+    /// Card type (0 = new, 1 = learning, 2 = review, 3 = relearning).
+    pub card_type: i32,
+    /// Queue the card was in.
+    pub queue: i32,
+    /// Due position/date.
+    pub due: i64,
+    /// Interval in days.
+    pub interval: i64,
+    /// Ease factor (e.g., 2500 = 250%).
+    pub ease_factor: i64,
+    /// Number of reviews.
+    pub reps: i64,
+    /// Number of lapses.
+    pub lapses: i64,
+    /// Reviews left today.
+    pub left: i64,
+}
+
+impl From<&CardInfo> for CardState {
+    fn from(card: &CardInfo) -> Self {
+        Self {
+            card_id: card.card_id,
+            card_type: card.card_type,
+            queue: card.queue,
+            due: card.due,
+            interval: card.interval,
+            ease_factor: card.ease_factor,
+            reps: card.reps,
+            lapses: card.lapses,
+            left: card.left,
+        }
+    }
+}
+
+/// Report from rolling back a journal.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RollbackReport {
+    /// Number of deleted notes that were re-created.
+    pub notes_restored: usize,
+    /// Number of cards whose scheduling was restored.
+    pub cards_rescheduled: usize,
+    /// Number of cards moved back to their original deck.
+    pub cards_moved: usize,
+    /// Number of notes whose field values were written back.
+    pub fields_restored: usize,
+    /// Number of notes whose tags were set back.
+    pub tags_restored: usize,
+    /// Entries that could not be restored, with the reason.
+    pub failures: Vec<String>,
+}
+
+impl WorkflowReport for RollbackReport {
+    fn summary(&self) -> String {
+        format!(
+            "Restored {} notes, {} fields, and {} tags; rescheduled {} cards; moved {} cards",
+            self.notes_restored,
+            self.fields_restored,
+            self.tags_restored,
+            self.cards_rescheduled,
+            self.cards_moved
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.failures.clone()
+    }
+
+    report_fields!();
+}
+
+impl Journal {
+    /// Create an empty journal for an operation.
+    pub fn new(operation: impl Into<String>) -> Self {
+        Self {
+            operation: operation.into(),
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Load a journal from a file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        serde_json::from_str(&contents).map_err(|e| {
+            Error::Journal(format!(
+                "Failed to parse journal '{}': {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    /// Write the journal to a new file in `dir`, returning its path.
+    ///
+    /// The directory is created if it does not exist.
+    pub fn write(&self, dir: impl AsRef<Path>) -> Result<PathBuf> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .subsec_nanos();
+        let path = dir.join(format!(
+            "{}-{}-{:09}.json",
+            self.operation, self.created_at, nanos
+        ));
+
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Journal(format!("Failed to serialize journal: {}", e)))?;
+        std::fs::write(&path, json)?;
+
+        Ok(path)
+    }
+}
+
+/// Record the full content of notes that are about to be deleted.
+#[cfg(any(feature = "deduplicate", feature = "organize"))]
+pub(crate) async fn record_notes(
+    client: &AnkiClient,
+    note_ids: &[i64],
+) -> Result<Vec<JournalEntry>> {
+    let notes = client.notes().info(note_ids).await?;
+    let card_ids: Vec<i64> = notes.iter().flat_map(|n| n.cards.iter().copied()).collect();
+    let cards: HashMap<i64, CardInfo> = client
+        .cards()
+        .info(&card_ids)
+        .await?
+        .into_iter()
+        .map(|c| (c.card_id, c))
+        .collect();
+
+    Ok(notes
+        .into_iter()
+        .map(|note| {
+            let note_cards: Vec<&CardInfo> =
+                note.cards.iter().filter_map(|id| cards.get(id)).collect();
+            JournalEntry::DeletedNote {
+                note_id: note.note_id,
+                deck: note_cards
+                    .first()
+                    .map(|c| c.deck_name.clone())
+                    .unwrap_or_else(|| "Default".to_string()),
+                model: note.model_name,
+                fields: note
+                    .fields
+                    .into_iter()
+                    .map(|(name, field)| (name, field.value))
+                    .collect(),
+                tags: note.tags,
+                cards: note_cards.into_iter().map(CardState::from).collect(),
+            }
+        })
+        .collect())
+}
+
+/// Record the scheduling of cards that are about to be changed.
+#[cfg(any(
+    feature = "analyze",
+    feature = "backup",
+    feature = "organize",
+    feature = "progress"
+))]
+pub(crate) async fn record_scheduling(
+    client: &AnkiClient,
+    card_ids: &[i64],
+) -> Result<Vec<JournalEntry>> {
+    let cards = client.cards().info(card_ids).await?;
+    Ok(cards
+        .iter()
+        .map(|c| JournalEntry::Scheduling(CardState::from(c)))
+        .collect())
+}
+
+/// Record the current deck of cards that are about to be moved.
+#[cfg(any(feature = "analyze", feature = "organize"))]
+pub(crate) async fn record_decks(
+    client: &AnkiClient,
+    card_ids: &[i64],
+) -> Result<Vec<JournalEntry>> {
+    let cards = client.cards().info(card_ids).await?;
+    Ok(cards
+        .into_iter()
+        .map(|c| JournalEntry::CardDeck {
+            card_id: c.card_id,
+            deck: c.deck_name,
+        })
+        .collect())
+}
+
+/// Record the field values of notes that are about to be rewritten.
+#[cfg(any(
+    feature = "backup",
+    feature = "deduplicate",
+    feature = "enrich",
+    feature = "media"
+))]
+pub(crate) async fn record_fields(
+    client: &AnkiClient,
+    note_ids: &[i64],
+) -> Result<Vec<JournalEntry>> {
+    let notes = client.notes().info(note_ids).await?;
+    Ok(notes
+        .into_iter()
+        .map(|note| JournalEntry::NoteFields {
+            note_id: note.note_id,
+            fields: note
+                .fields
+                .into_iter()
+                .map(|(name, field)| (name, field.value))
+                .collect(),
+        })
+        .collect())
+}
+
+/// Record the tags of notes that are about to be retagged.
+#[cfg(any(feature = "analyze", feature = "deduplicate", feature = "organize"))]
+pub(crate) async fn record_tags(
+    client: &AnkiClient,
+    note_ids: &[i64],
+) -> Result<Vec<JournalEntry>> {
+    let notes = client.notes().info(note_ids).await?;
+    Ok(notes
+        .into_iter()
+        .map(|note| JournalEntry::NoteTags {
+            note_id: note.note_id,
+            tags: note.tags,
+        })
+        .collect())
+}
+
+/// Restore the state recorded in a journal file.
+pub(crate) async fn rollback(client: &AnkiClient, path: &Path) -> Result<RollbackReport> {
+    let journal = Journal::load(path)?;
+    let mut report = RollbackReport::default();
+    let mut moves: HashMap<String, Vec<i64>> = HashMap::new();
+
+    for entry in journal.entries {
+        match entry {
+            JournalEntry::DeletedNote {
+                note_id,
+                deck,
+                model,
+                fields,
+                tags,
+                cards,
+            } => {
+                let new_cards = match recreate_note(client, &deck, &model, fields, tags).await {
+                    Ok(cards) => cards,
+                    Err(e) => {
+                        report.failures.push(format!("note {}: {}", note_id, e));
+                        continue;
+                    }
+                };
+                report.notes_restored += 1;
+
+                // Re-created cards come back as new; carry the old scheduling over
+                for (new_card_id, state) in new_cards.into_iter().zip(cards) {
+                    match restore_scheduling(client, new_card_id, &state).await {
+                        Ok(()) => report.cards_rescheduled += 1,
+                        Err(e) => report
+                            .failures
+                            .push(format!("card {}: {}", state.card_id, e)),
+                    }
+                }
+            }
+            JournalEntry::Scheduling(state) => {
+                match restore_scheduling(client, state.card_id, &state).await {
+                    Ok(()) => report.cards_rescheduled += 1,
+                    Err(e) => report
+                        .failures
+                        .push(format!("card {}: {}", state.card_id, e)),
+                }
+            }
+            JournalEntry::CardDeck { card_id, deck } => {
+                moves.entry(deck).or_default().push(card_id);
+            }
+            JournalEntry::NoteFields { note_id, fields } => {
+                match client.notes().update_fields(note_id, &fields).await {
+                    Ok(()) => report.fields_restored += 1,
+                    Err(e) => report.failures.push(format!("note {}: {}", note_id, e)),
+                }
+            }
+            JournalEntry::NoteTags { note_id, tags } => {
+                match client.notes().set_tags(note_id, &tags).await {
+                    Ok(()) => report.tags_restored += 1,
+                    Err(e) => report.failures.push(format!("note {}: {}", note_id, e)),
+                }
+            }
+        }
+    }
+
+    for (deck, card_ids) in moves {
+        client.decks().create(&deck).await?;
+        client.decks().move_cards(&card_ids, &deck).await?;
+        report.cards_moved += card_ids.len();
+    }
+
+    Ok(report)
+}
+
+/// Add a note back, returning the IDs of its new cards in card order.
+pub(crate) async fn recreate_note(
+    client: &AnkiClient,
+    deck: &str,
+    model: &str,
+    fields: HashMap<String, String>,
+    tags: Vec<String>,
+) -> Result<Vec<i64>> {
+    let mut builder = NoteBuilder::new(deck, model).tags(tags);
+    for (name, value) in fields {
+        builder = builder.field(name, value);
+    }
+    let note = builder.allow_duplicate(true).build();
+
+    let new_id = client.notes().add(note).await?;
+    Ok(client
+        .notes()
+        .info(&[new_id])
+        .await?
+        .into_iter()
+        .next()
+        .map(|n| n.cards)
+        .unwrap_or_default())
+}
+
+/// Write recorded scheduling values onto a card.
+pub(crate) async fn restore_scheduling(
+    client: &AnkiClient,
+    card_id: i64,
+    state: &CardState,
+) -> Result<()> {
+    let values = [
+        state.card_type.to_string(),
+        state.queue.to_string(),
+        state.due.to_string(),
+        state.interval.to_string(),
+        state.ease_factor.to_string(),
+        state.reps.to_string(),
+        state.lapses.to_string(),
+        state.left.to_string(),
+    ];
+    let values: Vec<&str> = values.iter().map(String::as_str).collect();
+
+    client
+        .cards()
+        .set_specific_value(
+            card_id,
+            &[
+                "type", "queue", "due", "ivl", "factor", "reps", "lapses", "left",
+            ],
+            &values,
+            true,
+        )
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_journal_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut journal = Journal::new("reset_deck");
+        journal.entries.push(JournalEntry::CardDeck {
+            card_id: 1,
+            deck: "Japanese".to_string(),
+        });
+
+        let path = journal.write(dir.path()).unwrap();
+        assert!(path.starts_with(dir.path()));
+
+        let loaded = Journal::load(&path).unwrap();
+        assert_eq!(loaded.operation, "reset_deck");
+        assert!(matches!(
+            &loaded.entries[0],
+            JournalEntry::CardDeck { card_id: 1, deck } if deck == "Japanese"
+        ));
+    }
+
+    #[test]
+    fn test_journal_load_invalid() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bad.json");
+        std::fs::write(&path, "not json").unwrap();
+
+        assert!(matches!(Journal::load(&path), Err(Error::Journal(_))));
+    }
+}
@@ -0,0 +1,108 @@
+//! Leech detection that matches Anki's own leech marking.
+//!
+//! Anki marks a card as a leech once its lapses reach the `leech_fails`
+//! option of the card's deck, which is 8 unless changed.
+//! [`LeechThresholdSource`] decides which threshold the analysis and progress reports count leeches
+//! against: each deck's own option, or a fixed override for every card.
+//!
+//! The engine-wide policy is set with
+//! [`EngineOptions::leech`](crate::EngineOptions::leech). It only decides
+//! which cards count as leeches; what to do with them is up to
+//! [`analyze::LeechPolicy`](crate::analyze::LeechPolicy).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ankit_engine::{Engine, EngineOptions};
+//! use ankit_engine::leech::LeechThresholdSource;
+//!
+//! # async fn example() -> ankit_engine::Result<()> {
+//! // Count cards with 5 or more lapses as leeches, whatever the deck options say
+//! let engine = Engine::new().with_options(EngineOptions {
+//!     leech: LeechThresholdSource::Fixed(5),
+//!     ..Default::default()
+//! });
+//! let audit = engine.analyze().deck_audit("Japanese").await?;
+//! println!("Leeches: {}", audit.leech_count);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+use ankit::{AnkiClient, CardInfo};
+
+use crate::Result;
+
+/// Anki's default lapse threshold for marking a card as a leech.
+pub const DEFAULT_LEECH_THRESHOLD: i64 = 8;
+
+/// Where the lapse threshold for leeches comes from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LeechThresholdSource {
+    /// Read `lapse.leech_fails` from the options group of each card's deck.
+    ///
+    /// Decks AnkiConnect has no options for use
+    /// [`DEFAULT_LEECH_THRESHOLD`].
+    #[default]
+    DeckConfig,
+    /// Use this threshold for every card.
+    Fixed(i64),
+}
+
+impl LeechThresholdSource {
+    /// Look up the thresholds for the decks the given cards are in.
+    ///
+    /// With [`LeechThresholdSource::DeckConfig`], this reads the options of each
+    /// distinct deck once.
+    pub async fn resolve(
+        &self,
+        client: &AnkiClient,
+        cards: &[CardInfo],
+    ) -> Result<LeechThresholds> {
+        let mut thresholds = LeechThresholds {
+            fixed: None,
+            by_deck: HashMap::new(),
+        };
+        match *self {
+            LeechThresholdSource::Fixed(threshold) => thresholds.fixed = Some(threshold),
+            LeechThresholdSource::DeckConfig => {
+                for card in cards {
+                    if thresholds.by_deck.contains_key(&card.deck_name) {
+                        continue;
+                    }
+                    let threshold = client
+                        .decks()
+                        .config_if_exists(&card.deck_name)
+                        .await?
+                        .map_or(DEFAULT_LEECH_THRESHOLD, |config| config.lapse.leech_fails);
+                    thresholds.by_deck.insert(card.deck_name.clone(), threshold);
+                }
+            }
+        }
+        Ok(thresholds)
+    }
+}
+
+/// Lapse thresholds resolved by [`LeechThresholdSource::resolve`].
+#[derive(Debug, Clone)]
+pub struct LeechThresholds {
+    fixed: Option<i64>,
+    by_deck: HashMap<String, i64>,
+}
+
+impl LeechThresholds {
+    /// The lapse threshold for cards in `deck`.
+    ///
+    /// Decks that weren't resolved use [`DEFAULT_LEECH_THRESHOLD`].
+    pub fn threshold(&self, deck: &str) -> i64 {
+        self.fixed
+            .or_else(|| self.by_deck.get(deck).copied())
+            .unwrap_or(DEFAULT_LEECH_THRESHOLD)
+    }
+
+    /// Whether a card has lapsed often enough to be a leech.
+    pub fn is_leech(&self, card: &CardInfo) -> bool {
+        card.lapses >= self.threshold(&card.deck_name)
+    }
+}
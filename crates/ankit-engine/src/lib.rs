@@ -33,19 +33,79 @@
 //!
 //! Available features:
 //! - `import` - Bulk import with duplicate handling
-//! - `export` - Deck and review history export
+//! - `export` - Deck, review history, Markdown, and printable HTML export
+//! - `pdf` - PDF output for printable exports (not default)
 //! - `organize` - Deck cloning, merging, reorganization
 //! - `analyze` - Study statistics and problem card detection
 //! - `migrate` - Note type migration with field mapping
 //! - `media` - Media audit and cleanup
 //! - `progress` - Card state management and performance tagging
-//! - `enrich` - Find and update notes with empty fields
+//! - `enrich` - Find and update notes with empty fields, find and replace in fields,
+//!   generate audio and pronunciation annotations
+//! - `japanese` - Japanese kana and furigana readings for `enrich` (not default)
 //! - `deduplicate` - Duplicate detection and removal
 //! - `backup` - Deck backup and restore to .apkg files
+//! - `simulate` - What-if review simulation for choosing deck options
+//! - `snapshot` - Capture notes matching a search and diff them later
+//! - `index` - Local full-text index for offline querying (not default)
+//! - `jobs` - Run workflows on cron-style schedules
+//! - `notify` - Send workflow results to webhooks (Slack, Discord) or stdout
 //! - `search` - Content search helpers (always enabled)
+//! - `status` - Progress reporting for bulk workflows (always enabled)
+//!
+//! # Dry Runs
+//!
+//! Configure the engine with [`EngineOptions`] to preview mutating workflows.
+//! In dry-run mode, organize, migrate, deduplicate, progress, and import
+//! workflows perform their read queries as usual but skip every write, and
+//! their reports list the [`PlannedChange`](changes::PlannedChange)s instead:
+//!
+//! ```no_run
+//! use ankit_engine::{Engine, EngineOptions};
+//!
+//! # async fn example() -> ankit_engine::Result<()> {
+//! let engine = Engine::new().with_options(EngineOptions {
+//!     dry_run: true,
+//!     ..Default::default()
+//! });
+//!
+//! let report = engine.organize().merge_decks(&["A", "B"], "Combined").await?;
+//! println!("Would move {} cards", report.cards_moved);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Undo Journal
+//!
+//! Set [`EngineOptions::journal_dir`] to have destructive workflows record the
+//! state they change before running; see the [`journal`] module for details
+//! and [`Engine::rollback`] to restore it.
 
+#[cfg(feature = "analyze")]
+mod batch;
+pub mod changes;
+#[cfg(any(
+    feature = "import",
+    feature = "migrate",
+    feature = "enrich",
+    feature = "simulate"
+))]
+mod concurrency;
 mod error;
+pub mod journal;
+pub mod leech;
+pub mod normalize;
+pub mod profile;
+pub mod report;
 pub mod search;
+#[cfg(any(
+    feature = "analyze",
+    feature = "progress",
+    feature = "deduplicate",
+    feature = "index"
+))]
+pub mod similarity;
+pub mod status;
 
 #[cfg(feature = "analyze")]
 pub mod analyze;
@@ -56,6 +116,12 @@ pub mod export;
 #[cfg(feature = "import")]
 pub mod import;
 
+#[cfg(feature = "import")]
+pub mod rules;
+
+#[cfg(feature = "import")]
+pub mod source;
+
 #[cfg(feature = "media")]
 pub mod media;
 
@@ -71,20 +137,43 @@ pub mod progress;
 #[cfg(feature = "enrich")]
 pub mod enrich;
 
+#[cfg(feature = "enrich")]
+pub mod tts;
+
+#[cfg(feature = "enrich")]
+pub mod annotate;
+
 #[cfg(feature = "deduplicate")]
 pub mod deduplicate;
 
 #[cfg(feature = "backup")]
 pub mod backup;
 
+#[cfg(feature = "simulate")]
+pub mod simulate;
+
+#[cfg(feature = "snapshot")]
+pub mod snapshot;
+
+#[cfg(feature = "index")]
+pub mod index;
+
+#[cfg(feature = "jobs")]
+pub mod jobs;
+
+#[cfg(feature = "notify")]
+pub mod notify;
+
 pub use error::{Error, Result};
 
 // Re-export ankit types for convenience
+pub use ankit::key::{KeyNormalization, UnicodeForm};
 pub use ankit::{
     AnkiClient, CanAddResult, CardAnswer, CardInfo, CardModTime, CardTemplate, ClientBuilder,
-    CreateModelParams, DeckConfig, DeckStats, DuplicateScope, Ease, FieldFont, FindReplaceParams,
-    LapseConfig, MediaAttachment, ModelField, ModelStyling, NewCardConfig, Note, NoteBuilder,
-    NoteField, NoteInfo, NoteModTime, NoteOptions, ReviewConfig, StoreMediaParams,
+    CreateModelParams, DeckConfig, DeckStats, DeckTree, DuplicateScope, Ease, FieldFont,
+    FindReplaceParams, Flag, LapseConfig, MediaAttachment, ModelField, ModelStyling, NewCardConfig,
+    Note, NoteBuilder, NoteField, NoteInfo, NoteModTime, NoteOptions, ReviewConfig,
+    StoreMediaParams, TagTree,
 };
 
 #[cfg(feature = "analyze")]
@@ -117,7 +206,20 @@ use deduplicate::DeduplicateEngine;
 #[cfg(feature = "backup")]
 use backup::BackupEngine;
 
+#[cfg(feature = "simulate")]
+use simulate::SimulateEngine;
+
+#[cfg(feature = "snapshot")]
+use snapshot::SnapshotEngine;
+
+#[cfg(feature = "index")]
+use index::IndexEngine;
+
+use leech::LeechThresholdSource;
 use search::SearchEngine;
+use status::ProgressSink;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// High-level workflow engine for Anki operations.
 ///
@@ -147,6 +249,100 @@ use search::SearchEngine;
 #[derive(Debug, Clone)]
 pub struct Engine {
     client: AnkiClient,
+    options: EngineOptions,
+}
+
+/// Options controlling how engine workflows behave.
+///
+/// # Example
+///
+/// ```no_run
+/// use ankit_engine::{Engine, EngineOptions};
+///
+/// let engine = Engine::new().with_options(EngineOptions {
+///     dry_run: true,
+///     ..Default::default()
+/// });
+/// assert!(engine.options().dry_run);
+/// ```
+#[derive(Clone)]
+pub struct EngineOptions {
+    /// Report planned changes from mutating workflows without executing them.
+    ///
+    /// Applies to organize, migrate, deduplicate, progress, import, and leech
+    /// remediation workflows.
+    pub dry_run: bool,
+    /// Directory for undo journals.
+    ///
+    /// When set, `remove_duplicates`, `reset_deck`, and `merge_decks` record the
+    /// state they are about to change before writing. See [`journal`].
+    pub journal_dir: Option<PathBuf>,
+    /// Maximum number of note updates or additions sent to AnkiConnect at
+    /// once by bulk workflows. Defaults to 1, which sends them one at a time.
+    ///
+    /// Applies to `import().notes` when updating duplicates,
+    /// `enrich().update_notes`, `enrich().annotate`, and
+    /// `migrate().notes`. Anki applies the calls one by one either way;
+    /// higher values hide the round-trip latency of each request. Keep it
+    /// small (4-8) so Anki stays responsive.
+    pub concurrency: usize,
+    /// Where bulk workflows report their progress. See [`status`].
+    pub progress_sink: Option<Arc<dyn ProgressSink>>,
+    /// Merge independent reads into `multi` requests and send large
+    /// mutations in chunks.
+    ///
+    /// Applies to `analyze().deck_audit`, `retention_stats`,
+    /// `find_problems`, `compare_decks`, `study_plan`, and
+    /// `remediate_leeches`, roughly halving their round trips on large
+    /// decks. Off by default, since each `multi` request holds Anki's
+    /// collection for longer.
+    pub batching: bool,
+    /// Lapse threshold for counting leeches. See [`leech`].
+    ///
+    /// Applies to `analyze().deck_audit`, `study_report`, and `study_plan`,
+    /// and `progress().deck_health`. Defaults to each deck's own option, as
+    /// Anki uses when marking leeches.
+    pub leech: LeechThresholdSource,
+}
+
+impl EngineOptions {
+    /// Start tracking a phase of `total` items.
+    #[cfg(any(
+        feature = "deduplicate",
+        feature = "export",
+        feature = "import",
+        feature = "media",
+        feature = "migrate"
+    ))]
+    pub(crate) fn track(&self, phase: &'static str, total: usize) -> status::Tracker {
+        status::Tracker::new(self.progress_sink.as_ref(), phase, total)
+    }
+}
+
+impl Default for EngineOptions {
+    fn default() -> Self {
+        Self {
+            dry_run: false,
+            journal_dir: None,
+            concurrency: 1,
+            progress_sink: None,
+            batching: false,
+            leech: LeechThresholdSource::default(),
+        }
+    }
+}
+
+impl std::fmt::Debug for EngineOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EngineOptions")
+            .field("dry_run", &self.dry_run)
+            .field("journal_dir", &self.journal_dir)
+            .field("concurrency", &self.concurrency)
+            .field("progress_sink", &self.progress_sink.is_some())
+            .field("batching", &self.batching)
+            .field("leech", &self.leech)
+            .finish()
+    }
 }
 
 impl Engine {
@@ -154,14 +350,44 @@ impl Engine {
     ///
     /// Connects to AnkiConnect at `http://127.0.0.1:8765`.
     pub fn new() -> Self {
-        Self {
-            client: AnkiClient::new(),
-        }
+        Self::from_client(AnkiClient::new())
     }
 
     /// Create an engine from an existing client.
     pub fn from_client(client: AnkiClient) -> Self {
-        Self { client }
+        Self {
+            client,
+            options: EngineOptions::default(),
+        }
+    }
+
+    /// Set the options used by workflows created from this engine.
+    pub fn with_options(mut self, options: EngineOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Turn request batching on or off. See [`EngineOptions::batching`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ankit_engine::Engine;
+    ///
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new().with_batching(true);
+    /// let audit = engine.analyze().deck_audit("Japanese").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_batching(mut self, enabled: bool) -> Self {
+        self.options.batching = enabled;
+        self
+    }
+
+    /// Get the options used by workflows created from this engine.
+    pub fn options(&self) -> &EngineOptions {
+        &self.options
     }
 
     /// Get a reference to the underlying client.
@@ -176,7 +402,7 @@ impl Engine {
     /// Provides bulk import with duplicate detection and conflict resolution.
     #[cfg(feature = "import")]
     pub fn import(&self) -> ImportEngine<'_> {
-        ImportEngine::new(&self.client)
+        ImportEngine::new(&self.client, &self.options)
     }
 
     /// Access export workflows.
@@ -184,7 +410,7 @@ impl Engine {
     /// Provides deck export and review history extraction.
     #[cfg(feature = "export")]
     pub fn export(&self) -> ExportEngine<'_> {
-        ExportEngine::new(&self.client)
+        ExportEngine::new(&self.client, &self.options)
     }
 
     /// Access organization workflows.
@@ -192,7 +418,7 @@ impl Engine {
     /// Provides deck cloning, merging, and tag-based reorganization.
     #[cfg(feature = "organize")]
     pub fn organize(&self) -> OrganizeEngine<'_> {
-        OrganizeEngine::new(&self.client)
+        OrganizeEngine::new(&self.client, &self.options)
     }
 
     /// Access analysis workflows.
@@ -200,7 +426,7 @@ impl Engine {
     /// Provides study statistics and problem card (leech) detection.
     #[cfg(feature = "analyze")]
     pub fn analyze(&self) -> AnalyzeEngine<'_> {
-        AnalyzeEngine::new(&self.client)
+        AnalyzeEngine::new(&self.client, &self.options)
     }
 
     /// Access migration workflows.
@@ -208,15 +434,15 @@ impl Engine {
     /// Provides note type migration with field mapping.
     #[cfg(feature = "migrate")]
     pub fn migrate(&self) -> MigrateEngine<'_> {
-        MigrateEngine::new(&self.client)
+        MigrateEngine::new(&self.client, &self.options)
     }
 
     /// Access media workflows.
     ///
-    /// Provides media audit and cleanup operations.
+    /// Provides media audit, cleanup, and renaming operations.
     #[cfg(feature = "media")]
     pub fn media(&self) -> MediaEngine<'_> {
-        MediaEngine::new(&self.client)
+        MediaEngine::new(&self.client, &self.options)
     }
 
     /// Access progress management workflows.
@@ -224,7 +450,7 @@ impl Engine {
     /// Provides card state management, performance tagging, and bulk operations.
     #[cfg(feature = "progress")]
     pub fn progress(&self) -> ProgressEngine<'_> {
-        ProgressEngine::new(&self.client)
+        ProgressEngine::new(&self.client, &self.options)
     }
 
     /// Access enrichment workflows.
@@ -232,7 +458,7 @@ impl Engine {
     /// Provides tools for finding notes with empty fields and updating them.
     #[cfg(feature = "enrich")]
     pub fn enrich(&self) -> EnrichEngine<'_> {
-        EnrichEngine::new(&self.client)
+        EnrichEngine::new(&self.client, &self.options)
     }
 
     /// Access deduplication workflows.
@@ -240,7 +466,7 @@ impl Engine {
     /// Provides duplicate detection and removal based on key fields.
     #[cfg(feature = "deduplicate")]
     pub fn deduplicate(&self) -> DeduplicateEngine<'_> {
-        DeduplicateEngine::new(&self.client)
+        DeduplicateEngine::new(&self.client, &self.options)
     }
 
     /// Access backup and restore workflows.
@@ -248,7 +474,42 @@ impl Engine {
     /// Provides deck backup to .apkg files and restore operations.
     #[cfg(feature = "backup")]
     pub fn backup(&self) -> BackupEngine<'_> {
-        BackupEngine::new(&self.client)
+        BackupEngine::new(&self.client, &self.options)
+    }
+
+    /// Access review simulations.
+    ///
+    /// Projects review workload and maturity under different deck options.
+    #[cfg(feature = "simulate")]
+    pub fn simulate(&self) -> SimulateEngine<'_> {
+        SimulateEngine::new(&self.client, &self.options)
+    }
+
+    /// Access note snapshot workflows.
+    ///
+    /// Provides capturing notes matching a search and diffing them against
+    /// the collection later.
+    #[cfg(feature = "snapshot")]
+    pub fn snapshot(&self) -> SnapshotEngine<'_> {
+        SnapshotEngine::new(&self.client)
+    }
+
+    /// Access local note indexes.
+    ///
+    /// Provides building an index of the notes matching a search, for fast
+    /// offline queries, similarity lookups, and duplicate detection.
+    #[cfg(feature = "index")]
+    pub fn index(&self) -> IndexEngine<'_> {
+        IndexEngine::new(&self.client)
+    }
+
+    /// Create a scheduler for running workflows on schedules.
+    ///
+    /// The scheduler owns a clone of this engine, so jobs use its options.
+    /// See the [`jobs`] module for an example.
+    #[cfg(feature = "jobs")]
+    pub fn jobs(&self) -> jobs::JobScheduler {
+        jobs::JobScheduler::new(self.clone())
     }
 
     /// Access content search helpers.
@@ -274,6 +535,38 @@ impl Engine {
     pub fn search(&self) -> SearchEngine<'_> {
         SearchEngine::new(&self.client)
     }
+
+    /// Run operations with another Anki profile loaded.
+    ///
+    /// The previous profile is loaded again afterwards unless
+    /// [`switch_back(false)`](profile::ProfileGuard::switch_back) is set.
+    /// See the [`profile`] module for an example.
+    pub fn with_profile(&self, profile: impl Into<String>) -> profile::ProfileGuard<'_> {
+        profile::ProfileGuard::new(self, profile.into())
+    }
+
+    /// Restore the state recorded in an undo journal.
+    ///
+    /// Re-creates deleted notes (with their scheduling), restores card
+    /// scheduling, and moves cards back to their original decks.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let report = engine.rollback("/home/user/anki-journals/reset_deck-1700000000-000000000.json").await?;
+    /// println!("Restored {} notes", report.notes_restored);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn rollback(
+        &self,
+        journal_path: impl AsRef<Path>,
+    ) -> Result<journal::RollbackReport> {
+        journal::rollback(&self.client, journal_path.as_ref()).await
+    }
 }
 
 impl Default for Engine {
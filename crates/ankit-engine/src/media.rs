@@ -1,12 +1,24 @@
 //! Media audit and cleanup operations.
 //!
-//! This module provides workflows for auditing media files
-//! and cleaning up orphaned or missing references.
+//! This module provides workflows for auditing media files and their
+//! sizes, cleaning up orphaned or missing references, renaming files along
+//! with the notes that reference them, compressing oversized images and
+//! audio with `ffmpeg`, and downloading remote media so decks work offline.
 
-use crate::Result;
-use ankit::AnkiClient;
+use crate::changes::PlannedChange;
+use crate::journal::{self, Journal};
+use crate::report::{WorkflowReport, report_fields};
+use crate::{EngineOptions, Error, Result};
+use ankit::{AnkiClient, StoreMediaParams};
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
 use serde::Serialize;
-use std::collections::HashSet;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::process::Command;
 
 /// Result of a media audit.
 #[derive(Debug, Clone, Default, Serialize)]
@@ -56,15 +68,434 @@ pub struct CleanupReport {
     pub failed: Vec<String>,
 }
 
+impl WorkflowReport for CleanupReport {
+    fn summary(&self) -> String {
+        format!(
+            "Deleted {} media files, freeing {} bytes",
+            self.files_deleted, self.bytes_freed
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.failed
+            .iter()
+            .map(|file| format!("could not delete {}", file))
+            .collect()
+    }
+
+    report_fields!();
+}
+
+/// Result of renaming a media file.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RenameMediaReport {
+    /// Previous filename.
+    pub old_name: String,
+    /// New filename.
+    pub new_name: String,
+    /// Number of notes whose references were rewritten.
+    pub notes_updated: usize,
+    /// Number of references rewritten across all notes.
+    pub references_rewritten: usize,
+    /// Undo journal of the rewritten fields, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for RenameMediaReport {
+    fn summary(&self) -> String {
+        format!(
+            "Renamed {} to {}, rewriting {} references in {} notes",
+            self.old_name, self.new_name, self.references_rewritten, self.notes_updated
+        )
+    }
+
+    report_fields!(dry_run, journal);
+}
+
+/// Media footprint of the collection, per file and per deck.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MediaSizeReport {
+    /// Total number of media files.
+    pub total_files: usize,
+    /// Total size of media files in bytes.
+    pub total_bytes: u64,
+    /// Bytes used by files no note references.
+    pub unreferenced_bytes: u64,
+    /// Every file, largest first.
+    pub files: Vec<MediaFileSize>,
+    /// Media referenced from each deck, largest first.
+    ///
+    /// A file referenced from several decks counts toward each of them.
+    pub by_deck: Vec<DeckMediaSize>,
+}
+
+impl WorkflowReport for MediaSizeReport {
+    fn summary(&self) -> String {
+        format!(
+            "{} media files using {} bytes ({} unreferenced)",
+            self.total_files, self.total_bytes, self.unreferenced_bytes
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.by_deck
+            .iter()
+            .map(|deck| format!("{}: {} files, {} bytes", deck.deck, deck.files, deck.bytes))
+            .collect()
+    }
+
+    report_fields!();
+}
+
+/// Size of a single media file.
+#[derive(Debug, Clone, Serialize)]
+pub struct MediaFileSize {
+    /// The filename.
+    pub filename: String,
+    /// Size in bytes.
+    pub bytes: u64,
+    /// Decks with cards referencing the file.
+    pub decks: Vec<String>,
+}
+
+/// Media referenced from one deck.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeckMediaSize {
+    /// Deck name.
+    pub deck: String,
+    /// Number of distinct files referenced.
+    pub files: usize,
+    /// Total size of those files in bytes.
+    pub bytes: u64,
+}
+
+/// Options for [`MediaEngine::compress`].
+#[derive(Debug, Clone)]
+pub struct CompressOptions {
+    /// Only files at least this large are re-encoded.
+    pub min_bytes: u64,
+    /// How to re-encode images, or `None` to leave them alone.
+    pub image: Option<ImageCompression>,
+    /// How to re-encode audio, or `None` to leave it alone.
+    pub audio: Option<AudioCompression>,
+    /// The `ffmpeg` program to run.
+    pub ffmpeg: String,
+}
+
+impl Default for CompressOptions {
+    fn default() -> Self {
+        Self {
+            min_bytes: 200 * 1024,
+            image: Some(ImageCompression::default()),
+            audio: Some(AudioCompression::default()),
+            ffmpeg: "ffmpeg".to_string(),
+        }
+    }
+}
+
+/// Image re-encoding settings.
+#[derive(Debug, Clone)]
+pub struct ImageCompression {
+    /// Maximum width in pixels; wider images are scaled down.
+    pub max_width: u32,
+    /// Maximum height in pixels; taller images are scaled down.
+    pub max_height: u32,
+    /// Encoding quality from 1 (smallest) to 100 (best), for JPEG and WebP.
+    pub quality: u8,
+    /// Extension to convert to (e.g., `"jpg"`), or `None` to keep the format.
+    pub format: Option<String>,
+}
+
+impl Default for ImageCompression {
+    fn default() -> Self {
+        Self {
+            max_width: 1600,
+            max_height: 1600,
+            quality: 80,
+            format: None,
+        }
+    }
+}
+
+/// Audio re-encoding settings.
+#[derive(Debug, Clone)]
+pub struct AudioCompression {
+    /// Target bitrate in kbit/s.
+    pub bitrate_kbps: u32,
+    /// Extension to convert to (e.g., `"mp3"`), or `None` to keep the format.
+    pub format: Option<String>,
+}
+
+impl Default for AudioCompression {
+    fn default() -> Self {
+        Self {
+            bitrate_kbps: 64,
+            format: None,
+        }
+    }
+}
+
+/// Result of compressing media files.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CompressReport {
+    /// Number of files large enough to re-encode.
+    pub files_checked: usize,
+    /// Files that got smaller (or would, in a dry run).
+    pub files: Vec<CompressedFile>,
+    /// Number of files re-encoding didn't make smaller, left unchanged.
+    pub files_not_smaller: usize,
+    /// Total bytes saved.
+    pub bytes_saved: u64,
+    /// Number of notes whose references were rewritten for a new extension.
+    pub notes_updated: usize,
+    /// Files that could not be compressed.
+    pub failed: Vec<CompressFailure>,
+    /// Undo journal of the rewritten fields, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for CompressReport {
+    fn summary(&self) -> String {
+        format!(
+            "Compressed {} of {} files, saving {} bytes ({} failed)",
+            self.files.len(),
+            self.files_checked,
+            self.bytes_saved,
+            self.failed.len()
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.files
+            .iter()
+            .map(|file| {
+                format!(
+                    "{} -> {}: {} -> {} bytes",
+                    file.original, file.compressed, file.bytes_before, file.bytes_after
+                )
+            })
+            .chain(
+                self.failed
+                    .iter()
+                    .map(|failure| format!("{}: {}", failure.filename, failure.error)),
+            )
+            .collect()
+    }
+
+    report_fields!(dry_run, journal);
+}
+
+/// A file that was re-encoded.
+#[derive(Debug, Clone, Serialize)]
+pub struct CompressedFile {
+    /// Original filename.
+    pub original: String,
+    /// Filename after compression; differs when the format changed.
+    pub compressed: String,
+    /// Size before, in bytes.
+    pub bytes_before: u64,
+    /// Size after, in bytes.
+    pub bytes_after: u64,
+}
+
+impl CompressedFile {
+    /// Bytes saved by re-encoding.
+    pub fn saved(&self) -> u64 {
+        self.bytes_before.saturating_sub(self.bytes_after)
+    }
+}
+
+/// A file that could not be compressed.
+#[derive(Debug, Clone, Serialize)]
+pub struct CompressFailure {
+    /// The filename.
+    pub filename: String,
+    /// What went wrong.
+    pub error: String,
+}
+
+/// Result of downloading remote media into the collection.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LocalizeReport {
+    /// Number of notes matching the query.
+    pub notes_checked: usize,
+    /// Number of notes whose fields were rewritten.
+    pub notes_updated: usize,
+    /// Number of remote references replaced with local ones.
+    pub references_rewritten: usize,
+    /// Remote files stored in the media folder (or that would be).
+    pub downloaded: Vec<RemoteMedia>,
+    /// Remote files that could not be downloaded; their references are left
+    /// unchanged.
+    pub failed: Vec<DownloadFailure>,
+    /// Undo journal of the rewritten fields, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for LocalizeReport {
+    fn summary(&self) -> String {
+        format!(
+            "Downloaded {} remote files, rewrote {} references in {} notes ({} failed)",
+            self.downloaded.len(),
+            self.references_rewritten,
+            self.notes_updated,
+            self.failed.len()
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.downloaded
+            .iter()
+            .map(|media| format!("{} -> {}", media.url, media.filename))
+            .chain(
+                self.failed
+                    .iter()
+                    .map(|failure| format!("{}: {}", failure.url, failure.error)),
+            )
+            .collect()
+    }
+
+    report_fields!(dry_run, journal);
+}
+
+/// A remote file stored locally.
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteMedia {
+    /// The URL it was downloaded from.
+    pub url: String,
+    /// The media filename it was stored as.
+    pub filename: String,
+}
+
+/// A remote file that could not be downloaded.
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadFailure {
+    /// The URL.
+    pub url: String,
+    /// What went wrong.
+    pub error: String,
+}
+
+/// Result of verifying media integrity.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MediaVerifyReport {
+    /// Number of media files checked.
+    pub files_checked: usize,
+    /// Number of media references found in notes.
+    pub references_checked: usize,
+    /// References to files that don't exist under any case.
+    pub missing: Vec<MissingMedia>,
+    /// References to files that only exist with different letter case.
+    ///
+    /// These work on case-insensitive file systems (Windows, macOS) but
+    /// break after syncing to a case-sensitive one (Linux, AnkiDroid).
+    pub case_mismatches: Vec<CaseMismatch>,
+    /// Files with no content.
+    pub empty_files: Vec<String>,
+    /// Groups of files with identical content.
+    pub duplicates: Vec<DuplicateMedia>,
+    /// Size and SHA-256 of every file, for comparing against later runs.
+    pub checksums: Vec<MediaChecksum>,
+}
+
+impl MediaVerifyReport {
+    /// Whether no missing, mis-cased, or empty files were found.
+    ///
+    /// Duplicates waste space but don't break cards, so they don't count.
+    pub fn is_healthy(&self) -> bool {
+        self.missing.is_empty() && self.case_mismatches.is_empty() && self.empty_files.is_empty()
+    }
+}
+
+impl WorkflowReport for MediaVerifyReport {
+    fn summary(&self) -> String {
+        format!(
+            "Verified {} files: {} missing, {} case mismatches, {} empty, {} duplicate groups",
+            self.files_checked,
+            self.missing.len(),
+            self.case_mismatches.len(),
+            self.empty_files.len(),
+            self.duplicates.len()
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        let missing = self
+            .missing
+            .iter()
+            .map(|m| format!("missing: {} (note {})", m.filename, m.note_id));
+        let case = self.case_mismatches.iter().map(|m| {
+            format!(
+                "case mismatch: {} is stored as {} (note {})",
+                m.referenced, m.actual, m.note_id
+            )
+        });
+        let empty = self.empty_files.iter().map(|f| format!("empty: {}", f));
+        let duplicates = self
+            .duplicates
+            .iter()
+            .map(|d| format!("duplicates: {}", d.files.join(", ")));
+        missing.chain(case).chain(empty).chain(duplicates).collect()
+    }
+
+    report_fields!();
+}
+
+/// A reference whose file exists only with different letter case.
+#[derive(Debug, Clone, Serialize)]
+pub struct CaseMismatch {
+    /// The note with the reference.
+    pub note_id: i64,
+    /// The filename as referenced.
+    pub referenced: String,
+    /// The filename as stored.
+    pub actual: String,
+}
+
+/// Files with identical content.
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateMedia {
+    /// SHA-256 of the content, as lowercase hex.
+    pub sha256: String,
+    /// Size of each copy in bytes.
+    pub bytes: u64,
+    /// The files sharing this content.
+    pub files: Vec<String>,
+}
+
+/// Checksum of a media file.
+#[derive(Debug, Clone, Serialize)]
+pub struct MediaChecksum {
+    /// The filename.
+    pub filename: String,
+    /// Size in bytes.
+    pub bytes: u64,
+    /// SHA-256 of the content, as lowercase hex.
+    pub sha256: String,
+}
+
 /// Media workflow engine.
 #[derive(Debug)]
 pub struct MediaEngine<'a> {
     client: &'a AnkiClient,
+    options: &'a EngineOptions,
 }
 
 impl<'a> MediaEngine<'a> {
-    pub(crate) fn new(client: &'a AnkiClient) -> Self {
-        Self { client }
+    pub(crate) fn new(client: &'a AnkiClient, options: &'a EngineOptions) -> Self {
+        Self { client, options }
     }
 
     /// Audit media files in the collection.
@@ -205,11 +636,14 @@ impl<'a> MediaEngine<'a> {
 
         let mut report = CleanupReport::default();
 
+        let tracker = self.options.track("deleting files", audit.orphaned.len());
         for filename in audit.orphaned {
+            tracker.check()?;
             match self.client.media().delete(&filename).await {
                 Ok(_) => report.files_deleted += 1,
                 Err(_) => report.failed.push(filename),
             }
+            tracker.advance(1);
         }
 
         Ok(report)
@@ -234,6 +668,1010 @@ impl<'a> MediaEngine<'a> {
     pub async fn list(&self, pattern: &str) -> Result<Vec<String>> {
         Ok(self.client.media().list(pattern).await?)
     }
+
+    /// Rename a media file and rewrite every reference to it.
+    ///
+    /// `[sound:old]` tags and `src="old"` (or single-quoted) attributes in
+    /// all note fields are rewritten to the new name, so cards keep showing
+    /// the file after, for example, deduplicating media by content.
+    ///
+    /// AnkiConnect has no transactions, so the rename is staged: the file is
+    /// copied to the new name, notes are rewritten, and only then is the old
+    /// file deleted. If a note can't be updated, notes already rewritten are
+    /// restored and the copy is removed before the error is returned.
+    ///
+    /// Respects [`EngineOptions::dry_run`] and records an undo journal of
+    /// the rewritten fields when [`EngineOptions::journal_dir`] is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Validation`] if the names are empty or equal, the
+    /// old file doesn't exist, a file with the new name already exists, or
+    /// Anki stores the copy under a different, sanitized name. In the last
+    /// case the copy is removed and no note is changed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let report = engine
+    ///     .media()
+    ///     .rename_with_references("recording (1).mp3", "perro.mp3")
+    ///     .await?;
+    /// println!("Updated {} notes", report.notes_updated);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn rename_with_references(
+        &self,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<RenameMediaReport> {
+        if old_name.is_empty() || new_name.is_empty() {
+            return Err(Error::Validation("media names cannot be empty".to_string()));
+        }
+        if old_name == new_name {
+            return Err(Error::Validation(format!(
+                "'{}' already has that name",
+                old_name
+            )));
+        }
+        if !self.exists(old_name).await? {
+            return Err(Error::Validation(format!(
+                "media file '{}' does not exist",
+                old_name
+            )));
+        }
+        if self.exists(new_name).await? {
+            return Err(Error::Validation(format!(
+                "media file '{}' already exists",
+                new_name
+            )));
+        }
+
+        let mut report = RenameMediaReport {
+            old_name: old_name.to_string(),
+            new_name: new_name.to_string(),
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        let rewrites = self.collect_rewrites(old_name, new_name).await?;
+        report.references_rewritten = rewrites.iter().map(|r| r.references).sum();
+        report.notes_updated = rewrites.len();
+
+        if report.dry_run {
+            report.planned.push(PlannedChange::StoreMedia {
+                filename: new_name.to_string(),
+            });
+            for rewrite in rewrites {
+                report.planned.push(PlannedChange::UpdateNoteFields {
+                    note_id: rewrite.note_id,
+                    fields: rewrite.updated,
+                });
+            }
+            report.planned.push(PlannedChange::DeleteMedia {
+                filename: old_name.to_string(),
+            });
+            return Ok(report);
+        }
+
+        if let Some(dir) = &self.options.journal_dir {
+            if !rewrites.is_empty() {
+                let ids: Vec<i64> = rewrites.iter().map(|r| r.note_id).collect();
+                let mut record = Journal::new("rename_media");
+                record.entries = journal::record_fields(self.client, &ids).await?;
+                report.journal = Some(record.write(dir)?);
+            }
+        }
+
+        let data = self.client.media().retrieve(old_name).await?;
+        let stored = self
+            .client
+            .media()
+            .store(StoreMediaParams::from_base64(new_name, data))
+            .await?;
+        // Anki may store the file under a sanitized name, which the notes
+        // would then not reference
+        if stored != new_name {
+            let _ = self.client.media().delete(&stored).await;
+            return Err(Error::Validation(format!(
+                "Anki stored '{}' as '{}'; choose a name it keeps unchanged",
+                new_name, stored
+            )));
+        }
+
+        if let Err(e) = self.apply_rewrites(&rewrites).await {
+            let _ = self.client.media().delete(new_name).await;
+            return Err(e);
+        }
+
+        self.client.media().delete(old_name).await?;
+        Ok(report)
+    }
+
+    /// Find the note fields referencing `old_name` and rewrite them to
+    /// `new_name`, without changing anything yet.
+    async fn collect_rewrites(&self, old_name: &str, new_name: &str) -> Result<Vec<FieldRewrite>> {
+        let mut rewrites = Vec::new();
+        let note_ids = self.client.notes().find(&literal_search(old_name)).await?;
+        for chunk in note_ids.chunks(100) {
+            for info in self.client.notes().info(chunk).await? {
+                let mut rewrite = FieldRewrite {
+                    note_id: info.note_id,
+                    original: HashMap::new(),
+                    updated: HashMap::new(),
+                    references: 0,
+                };
+                for (name, field) in &info.fields {
+                    let (value, count) = rewrite_media_references(&field.value, old_name, new_name);
+                    if count > 0 {
+                        rewrite.references += count;
+                        rewrite.original.insert(name.clone(), field.value.clone());
+                        rewrite.updated.insert(name.clone(), value);
+                    }
+                }
+                if rewrite.references > 0 {
+                    rewrites.push(rewrite);
+                }
+            }
+        }
+        Ok(rewrites)
+    }
+
+    /// Write rewritten fields, restoring the notes already written if one
+    /// fails.
+    async fn apply_rewrites(&self, rewrites: &[FieldRewrite]) -> Result<()> {
+        let notes = self.client.notes();
+        for (done, rewrite) in rewrites.iter().enumerate() {
+            if let Err(e) = notes.update_fields(rewrite.note_id, &rewrite.updated).await {
+                // Put back what was already rewritten, best effort
+                for rewrite in &rewrites[..done] {
+                    let _ = notes
+                        .update_fields(rewrite.note_id, &rewrite.original)
+                        .await;
+                }
+                return Err(e.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a media file with exactly this name exists.
+    async fn exists(&self, filename: &str) -> Result<bool> {
+        Ok(self
+            .client
+            .media()
+            .list(&glob_escape(filename))
+            .await?
+            .iter()
+            .any(|name| name == filename))
+    }
+
+    /// Report how much space media files take, per file and per deck.
+    ///
+    /// File sizes are read from the media folder when it is on this machine,
+    /// and otherwise by downloading each file through AnkiConnect.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let report = engine.media().audit_sizes().await?;
+    /// for file in report.files.iter().take(10) {
+    ///     println!("{}: {} bytes", file.filename, file.bytes);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn audit_sizes(&self) -> Result<MediaSizeReport> {
+        let all_files = self.client.media().list("*").await?;
+        let sizes = self.file_sizes(&all_files).await?;
+
+        // Decks referencing each file, through the cards' note fields
+        let mut decks: HashMap<String, HashSet<String>> = HashMap::new();
+        let card_ids = self.client.cards().find("deck:*").await?;
+        for chunk in card_ids.chunks(100) {
+            for card in self.client.cards().info(chunk).await? {
+                for field in card.fields.values() {
+                    for filename in extract_media_references(&field.value) {
+                        decks
+                            .entry(filename)
+                            .or_default()
+                            .insert(card.deck_name.clone());
+                    }
+                }
+            }
+        }
+
+        let mut report = MediaSizeReport {
+            total_files: all_files.len(),
+            ..Default::default()
+        };
+        let mut by_deck: BTreeMap<String, (usize, u64)> = BTreeMap::new();
+        for filename in all_files {
+            let bytes = sizes.get(&filename).copied().unwrap_or(0);
+            report.total_bytes += bytes;
+            let mut file_decks: Vec<String> = decks
+                .get(&filename)
+                .map(|d| d.iter().cloned().collect())
+                .unwrap_or_default();
+            file_decks.sort();
+            if file_decks.is_empty() {
+                report.unreferenced_bytes += bytes;
+            }
+            for deck in &file_decks {
+                let entry = by_deck.entry(deck.clone()).or_default();
+                entry.0 += 1;
+                entry.1 += bytes;
+            }
+            report.files.push(MediaFileSize {
+                filename,
+                bytes,
+                decks: file_decks,
+            });
+        }
+
+        report.files.sort_by(|a, b| {
+            b.bytes
+                .cmp(&a.bytes)
+                .then_with(|| a.filename.cmp(&b.filename))
+        });
+        report.by_deck = by_deck
+            .into_iter()
+            .map(|(deck, (files, bytes))| DeckMediaSize { deck, files, bytes })
+            .collect();
+        report
+            .by_deck
+            .sort_by_key(|deck| std::cmp::Reverse(deck.bytes));
+        Ok(report)
+    }
+
+    /// Re-encode oversized images and audio with `ffmpeg`.
+    ///
+    /// Images larger than [`CompressOptions::min_bytes`] are scaled down to
+    /// fit the maximum dimensions and re-encoded at the given quality; audio
+    /// is re-encoded at the given bitrate. A result is kept only if it is
+    /// smaller than the original. Files keep their names unless a `format`
+    /// is set, in which case the file gets the new extension and every
+    /// reference to it is rewritten, as with
+    /// [`rename_with_references`](Self::rename_with_references).
+    ///
+    /// Files are encoded locally even in a dry run, so the report shows the
+    /// real savings per file. Respects [`EngineOptions::dry_run`] and
+    /// records an undo journal of rewritten fields when
+    /// [`EngineOptions::journal_dir`] is set. Media replaced in place can't
+    /// be restored from the journal; take a backup first.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # use ankit_engine::media::{CompressOptions, ImageCompression};
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let options = CompressOptions {
+    ///     image: Some(ImageCompression {
+    ///         format: Some("jpg".to_string()),
+    ///         ..Default::default()
+    ///     }),
+    ///     ..Default::default()
+    /// };
+    /// let report = engine.media().compress(&options).await?;
+    /// println!("Saved {} bytes", report.bytes_saved);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn compress(&self, options: &CompressOptions) -> Result<CompressReport> {
+        let mut report = CompressReport {
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        let all_files = self.client.media().list("*").await?;
+        let candidates: Vec<String> = all_files
+            .into_iter()
+            .filter(|name| encode_args(name, &extension(name), options).is_some())
+            .collect();
+        let sizes = self.file_sizes(&candidates).await?;
+
+        // Encode everything first, so nothing changes if encoding fails
+        let mut encoded = Vec::new();
+        for filename in candidates {
+            if sizes.get(&filename).copied().unwrap_or(0) < options.min_bytes {
+                continue;
+            }
+            report.files_checked += 1;
+            match self.encode_file(&filename, options).await {
+                Ok(Some(file)) => encoded.push(file),
+                Ok(None) => report.files_not_smaller += 1,
+                Err(e) => report.failed.push(CompressFailure {
+                    filename,
+                    error: e.to_string(),
+                }),
+            }
+        }
+
+        let mut rewrites = Vec::new();
+        for file in &encoded {
+            if file.new_name != file.name {
+                rewrites.push(self.collect_rewrites(&file.name, &file.new_name).await?);
+            } else {
+                rewrites.push(Vec::new());
+            }
+        }
+        report.notes_updated = rewrites.iter().map(Vec::len).sum();
+
+        if report.dry_run {
+            for (file, rewrites) in encoded.iter().zip(rewrites) {
+                report.planned.push(PlannedChange::StoreMedia {
+                    filename: file.new_name.clone(),
+                });
+                if file.new_name != file.name {
+                    for rewrite in rewrites {
+                        report.planned.push(PlannedChange::UpdateNoteFields {
+                            note_id: rewrite.note_id,
+                            fields: rewrite.updated,
+                        });
+                    }
+                    report.planned.push(PlannedChange::DeleteMedia {
+                        filename: file.name.clone(),
+                    });
+                }
+                report.bytes_saved += file.summary.saved();
+                report.files.push(file.summary.clone());
+            }
+            return Ok(report);
+        }
+
+        if let Some(dir) = &self.options.journal_dir {
+            let ids: Vec<i64> = rewrites.iter().flatten().map(|r| r.note_id).collect();
+            if !ids.is_empty() {
+                let mut record = Journal::new("compress_media");
+                record.entries = journal::record_fields(self.client, &ids).await?;
+                report.journal = Some(record.write(dir)?);
+            }
+        }
+
+        report.notes_updated = 0;
+        for (file, rewrites) in encoded.into_iter().zip(rewrites) {
+            match self.replace_file(&file, &rewrites).await {
+                Ok(()) => {
+                    report.notes_updated += rewrites.len();
+                    report.bytes_saved += file.summary.saved();
+                    report.files.push(file.summary);
+                }
+                Err(e) => report.failed.push(CompressFailure {
+                    filename: file.name,
+                    error: e.to_string(),
+                }),
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Download and re-encode one file, or `None` if it didn't get smaller.
+    async fn encode_file(
+        &self,
+        filename: &str,
+        options: &CompressOptions,
+    ) -> Result<Option<EncodedFile>> {
+        let from = extension(filename);
+        let to = target_extension(filename, options).unwrap_or_else(|| from.clone());
+        let new_name = if to == from {
+            filename.to_string()
+        } else {
+            let stem = filename.rsplit_once('.').map_or(filename, |(stem, _)| stem);
+            let new_name = format!("{}.{}", stem, to);
+            if self.exists(&new_name).await? {
+                return Err(Error::Validation(format!(
+                    "media file '{}' already exists",
+                    new_name
+                )));
+            }
+            new_name
+        };
+
+        let original = STANDARD
+            .decode(self.client.media().retrieve(filename).await?)
+            .map_err(|e| Error::Encode(format!("'{}' is not valid base64: {}", filename, e)))?;
+        let args = encode_args(filename, &to, options).unwrap_or_default();
+        let data = run_ffmpeg(&options.ffmpeg, &args, &original, &from, &to).await?;
+        if data.len() >= original.len() {
+            return Ok(None);
+        }
+
+        Ok(Some(EncodedFile {
+            summary: CompressedFile {
+                original: filename.to_string(),
+                compressed: new_name.clone(),
+                bytes_before: original.len() as u64,
+                bytes_after: data.len() as u64,
+            },
+            name: filename.to_string(),
+            new_name,
+            data,
+        }))
+    }
+
+    /// Upload a re-encoded file, pointing references at it if it was renamed.
+    async fn replace_file(&self, file: &EncodedFile, rewrites: &[FieldRewrite]) -> Result<()> {
+        self.client
+            .media()
+            .store(StoreMediaParams::from_base64(
+                &file.new_name,
+                STANDARD.encode(&file.data),
+            ))
+            .await?;
+        if file.new_name == file.name {
+            return Ok(());
+        }
+        if let Err(e) = self.apply_rewrites(rewrites).await {
+            let _ = self.client.media().delete(&file.new_name).await;
+            return Err(e);
+        }
+        self.client.media().delete(&file.name).await?;
+        Ok(())
+    }
+
+    /// Sizes of media files in bytes.
+    ///
+    /// Reads the media folder directly when it is accessible, and downloads
+    /// the remaining files through AnkiConnect.
+    async fn file_sizes(&self, files: &[String]) -> Result<HashMap<String, u64>> {
+        let dir = self
+            .client
+            .media()
+            .directory()
+            .await
+            .ok()
+            .map(PathBuf::from);
+        let mut sizes = HashMap::new();
+        for filename in files {
+            let local = dir
+                .as_ref()
+                .and_then(|dir| std::fs::metadata(dir.join(filename)).ok())
+                .map(|meta| meta.len());
+            let bytes = match local {
+                Some(bytes) => bytes,
+                None => decoded_len(&self.client.media().retrieve(filename).await?),
+            };
+            sizes.insert(filename.clone(), bytes);
+        }
+        Ok(sizes)
+    }
+
+    /// Download remote images and audio referenced by notes into the media
+    /// folder, and point the notes at the local copies.
+    ///
+    /// Looks for `http://` and `https://` URLs in `src="..."` attributes and
+    /// `[sound:...]` tags of notes matching `query`. Anki downloads each URL
+    /// once, storing it under a name derived from the URL, so running this
+    /// again reuses files already downloaded. References to URLs that fail
+    /// to download are left as they were.
+    ///
+    /// Respects [`EngineOptions::dry_run`] (nothing is downloaded) and
+    /// records an undo journal of rewritten fields when
+    /// [`EngineOptions::journal_dir`] is set.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let report = engine.media().localize_remote("deck:Imported").await?;
+    /// println!("Downloaded {} files", report.downloaded.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn localize_remote(&self, query: &str) -> Result<LocalizeReport> {
+        let mut report = LocalizeReport {
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        // Notes with remote references, and each distinct reference in order
+        let note_ids = self.client.notes().find(query).await?;
+        report.notes_checked = note_ids.len();
+        let mut notes = Vec::new();
+        let mut references: Vec<String> = Vec::new();
+        for chunk in note_ids.chunks(100) {
+            for info in self.client.notes().info(chunk).await? {
+                let mut ordered: Vec<_> = info.fields.into_iter().collect();
+                ordered.sort_by_key(|(_, field)| field.order);
+                let mut fields = HashMap::new();
+                for (name, field) in ordered {
+                    let remote = extract_remote_references(&field.value);
+                    if remote.is_empty() {
+                        continue;
+                    }
+                    for reference in remote {
+                        if !references.contains(&reference) {
+                            references.push(reference);
+                        }
+                    }
+                    fields.insert(name, field.value);
+                }
+                if !fields.is_empty() {
+                    notes.push((info.note_id, fields));
+                }
+            }
+        }
+        if notes.is_empty() {
+            return Ok(report);
+        }
+
+        // Local name for each reference, downloading what isn't stored yet
+        let existing: HashSet<String> = self.client.media().list("*").await?.into_iter().collect();
+        let mut local: HashMap<String, String> = HashMap::new();
+        for reference in references {
+            let url = reference.replace("&amp;", "&");
+            let filename = remote_filename(&url);
+            if existing.contains(&filename) {
+                local.insert(reference, filename);
+                continue;
+            }
+            if report.dry_run {
+                report.planned.push(PlannedChange::StoreMedia {
+                    filename: filename.clone(),
+                });
+                report.downloaded.push(RemoteMedia {
+                    url,
+                    filename: filename.clone(),
+                });
+                local.insert(reference, filename);
+                continue;
+            }
+            let stored = self
+                .client
+                .media()
+                .store(StoreMediaParams::from_url(&filename, &url))
+                .await;
+            match stored {
+                Ok(name) => {
+                    report.downloaded.push(RemoteMedia {
+                        url,
+                        filename: name.clone(),
+                    });
+                    local.insert(reference, name);
+                }
+                Err(e) => report.failed.push(DownloadFailure {
+                    url,
+                    error: e.to_string(),
+                }),
+            }
+        }
+
+        let mut rewrites = Vec::new();
+        for (note_id, fields) in notes {
+            let mut rewrite = FieldRewrite {
+                note_id,
+                original: HashMap::new(),
+                updated: HashMap::new(),
+                references: 0,
+            };
+            for (name, original) in fields {
+                let mut value = original.clone();
+                for (reference, filename) in &local {
+                    let (rewritten, count) = rewrite_media_references(&value, reference, filename);
+                    rewrite.references += count;
+                    value = rewritten;
+                }
+                if value != original {
+                    rewrite.original.insert(name.clone(), original);
+                    rewrite.updated.insert(name, value);
+                }
+            }
+            if rewrite.references > 0 {
+                rewrites.push(rewrite);
+            }
+        }
+        report.notes_updated = rewrites.len();
+        report.references_rewritten = rewrites.iter().map(|r| r.references).sum();
+
+        if report.dry_run {
+            for rewrite in rewrites {
+                report.planned.push(PlannedChange::UpdateNoteFields {
+                    note_id: rewrite.note_id,
+                    fields: rewrite.updated,
+                });
+            }
+            return Ok(report);
+        }
+
+        if let Some(dir) = &self.options.journal_dir {
+            if !rewrites.is_empty() {
+                let ids: Vec<i64> = rewrites.iter().map(|r| r.note_id).collect();
+                let mut record = Journal::new("localize_remote");
+                record.entries = journal::record_fields(self.client, &ids).await?;
+                report.journal = Some(record.write(dir)?);
+            }
+        }
+
+        self.apply_rewrites(&rewrites).await?;
+        Ok(report)
+    }
+
+    /// Check that every media reference resolves to a usable file.
+    ///
+    /// Hashes the content of every media file, and reports references to
+    /// missing files, references that only match a file with different
+    /// letter case, zero-byte files, and files with identical content.
+    /// Content is read from the media folder when it is on this machine,
+    /// and otherwise downloaded through AnkiConnect.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let report = engine.media().verify().await?;
+    /// for mismatch in &report.case_mismatches {
+    ///     println!("{} should be {}", mismatch.referenced, mismatch.actual);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn verify(&self) -> Result<MediaVerifyReport> {
+        let all_files = self.client.media().list("*").await?;
+        let dir = self
+            .client
+            .media()
+            .directory()
+            .await
+            .ok()
+            .map(PathBuf::from);
+
+        let mut report = MediaVerifyReport {
+            files_checked: all_files.len(),
+            ..Default::default()
+        };
+
+        let mut by_hash: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for filename in &all_files {
+            let content = self.file_content(dir.as_deref(), filename).await?;
+            let sha256: String = Sha256::digest(&content)
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect();
+            if content.is_empty() {
+                report.empty_files.push(filename.clone());
+            } else {
+                by_hash
+                    .entry(sha256.clone())
+                    .or_default()
+                    .push(filename.clone());
+            }
+            report.checksums.push(MediaChecksum {
+                filename: filename.clone(),
+                bytes: content.len() as u64,
+                sha256,
+            });
+        }
+        let sizes: HashMap<&str, u64> = report
+            .checksums
+            .iter()
+            .map(|c| (c.sha256.as_str(), c.bytes))
+            .collect();
+        report.duplicates = by_hash
+            .into_iter()
+            .filter(|(_, files)| files.len() > 1)
+            .map(|(sha256, files)| DuplicateMedia {
+                bytes: sizes.get(sha256.as_str()).copied().unwrap_or(0),
+                sha256,
+                files,
+            })
+            .collect();
+
+        let exact: HashSet<&str> = all_files.iter().map(String::as_str).collect();
+        let folded: HashMap<String, &str> = all_files
+            .iter()
+            .map(|name| (name.to_lowercase(), name.as_str()))
+            .collect();
+        let note_ids = self.client.notes().find("*").await?;
+        for chunk in note_ids.chunks(100) {
+            for info in self.client.notes().info(chunk).await? {
+                let mut seen = HashSet::new();
+                for field in info.fields.values() {
+                    for filename in extract_media_references(&field.value) {
+                        report.references_checked += 1;
+                        if exact.contains(filename.as_str()) || !seen.insert(filename.clone()) {
+                            continue;
+                        }
+                        match folded.get(&filename.to_lowercase()) {
+                            Some(actual) => report.case_mismatches.push(CaseMismatch {
+                                note_id: info.note_id,
+                                referenced: filename,
+                                actual: actual.to_string(),
+                            }),
+                            None => report.missing.push(MissingMedia {
+                                note_id: info.note_id,
+                                filename,
+                            }),
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Content of a media file, from the media folder when accessible.
+    async fn file_content(&self, dir: Option<&Path>, filename: &str) -> Result<Vec<u8>> {
+        if let Some(content) = dir.and_then(|dir| std::fs::read(dir.join(filename)).ok()) {
+            return Ok(content);
+        }
+        STANDARD
+            .decode(self.client.media().retrieve(filename).await?)
+            .map_err(|e| Error::Validation(format!("'{}' is not valid base64: {}", filename, e)))
+    }
+}
+
+/// Remote `http(s)` media references in HTML field content, as written in
+/// the field.
+fn extract_remote_references(html: &str) -> Vec<String> {
+    let pattern =
+        regex_lite::Regex::new(r#"(?:\bsrc="|\bsrc='|\[sound:)(https?://[^"'\]\s]+)"#).unwrap();
+    let mut references = Vec::new();
+    for cap in pattern.captures_iter(html) {
+        if let Some(m) = cap.get(1) {
+            let reference = m.as_str().to_string();
+            if !references.contains(&reference) {
+                references.push(reference);
+            }
+        }
+    }
+    references
+}
+
+/// Media filename for a downloaded URL.
+///
+/// Keeps the last path segment for readability and adds a hash of the whole
+/// URL, so different URLs ending in the same name don't collide and the same
+/// URL always maps to the same file.
+fn remote_filename(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let path = path.split_once("://").map_or(path, |(_, rest)| rest);
+    let segment = path
+        .split_once('/')
+        .and_then(|(_, path)| path.rsplit('/').next())
+        .unwrap_or("");
+    let segment: String = segment
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let (stem, ext) = match segment.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() && !ext.is_empty() => (stem, Some(ext)),
+        _ => (segment.as_str(), None),
+    };
+    let stem = if stem.is_empty() { "remote" } else { stem };
+    let hash: String = Sha256::digest(url.as_bytes())[..4]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    match ext {
+        Some(ext) => format!("{}-{}.{}", stem, hash, ext),
+        None => format!("{}-{}", stem, hash),
+    }
+}
+
+/// A re-encoded file waiting to be uploaded.
+struct EncodedFile {
+    name: String,
+    new_name: String,
+    data: Vec<u8>,
+    summary: CompressedFile,
+}
+
+/// Image extensions `compress` re-encodes.
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp"];
+
+/// Audio extensions `compress` re-encodes.
+const AUDIO_EXTENSIONS: &[&str] = &["mp3", "wav", "ogg", "m4a", "flac"];
+
+/// Lowercase extension of a filename, without the dot.
+fn extension(filename: &str) -> String {
+    filename
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_lowercase())
+        .unwrap_or_default()
+}
+
+/// Extension a file is converted to, if its format is set to change.
+fn target_extension(filename: &str, options: &CompressOptions) -> Option<String> {
+    let ext = extension(filename);
+    let format = if IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+        options.image.as_ref()?.format.as_ref()
+    } else {
+        options.audio.as_ref()?.format.as_ref()
+    };
+    format.map(|f| f.trim_start_matches('.').to_lowercase())
+}
+
+/// `ffmpeg` output arguments for re-encoding a file as `to`, or `None` if
+/// the file isn't an image or audio type being compressed.
+fn encode_args(filename: &str, to: &str, options: &CompressOptions) -> Option<Vec<String>> {
+    let ext = extension(filename);
+    if IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+        let image = options.image.as_ref()?;
+        let mut args = vec![
+            "-vf".to_string(),
+            format!(
+                "scale=w='min({},iw)':h='min({},ih)':force_original_aspect_ratio=decrease",
+                image.max_width, image.max_height
+            ),
+        ];
+        let quality = image.quality.clamp(1, 100);
+        match to {
+            "jpg" | "jpeg" => {
+                // ffmpeg's JPEG scale runs from 2 (best) to 31 (smallest)
+                let q = 2 + (100 - u32::from(quality)) * 29 / 99;
+                args.extend(["-q:v".to_string(), q.to_string()]);
+            }
+            "webp" => args.extend(["-quality".to_string(), quality.to_string()]),
+            _ => {}
+        }
+        Some(args)
+    } else if AUDIO_EXTENSIONS.contains(&ext.as_str()) {
+        let audio = options.audio.as_ref()?;
+        Some(vec![
+            "-vn".to_string(),
+            "-b:a".to_string(),
+            format!("{}k", audio.bitrate_kbps),
+        ])
+    } else {
+        None
+    }
+}
+
+/// Run `ffmpeg` on `data`, returning the encoded output.
+async fn run_ffmpeg(
+    program: &str,
+    args: &[String],
+    data: &[u8],
+    from: &str,
+    to: &str,
+) -> Result<Vec<u8>> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let temp = |ext: &str| {
+        std::env::temp_dir().join(format!(
+            "ankit-media-{}-{}.{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed),
+            ext
+        ))
+    };
+    let input = temp(from);
+    let output = temp(to);
+    std::fs::write(&input, data)?;
+
+    let result = Command::new(program)
+        .args(["-y", "-loglevel", "error", "-i"])
+        .arg(&input)
+        .args(args)
+        .arg(&output)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .await;
+    let _ = std::fs::remove_file(&input);
+    let result = result.map_err(|e| Error::Encode(format!("failed to run '{}': {}", program, e)));
+
+    let encoded = result.and_then(|result| {
+        if result.status.success() {
+            read_output(program, &output)
+        } else {
+            Err(Error::Encode(format!(
+                "'{}' exited with {}: {}",
+                program,
+                result.status,
+                String::from_utf8_lossy(&result.stderr).trim()
+            )))
+        }
+    });
+    let _ = std::fs::remove_file(&output);
+    encoded
+}
+
+/// Read the file an encoder wrote.
+fn read_output(program: &str, path: &Path) -> Result<Vec<u8>> {
+    let data = std::fs::read(path)
+        .map_err(|e| Error::Encode(format!("'{}' did not write output: {}", program, e)))?;
+    if data.is_empty() {
+        return Err(Error::Encode(format!("'{}' produced no output", program)));
+    }
+    Ok(data)
+}
+
+/// Size of the data a base64 string decodes to.
+fn decoded_len(base64: &str) -> u64 {
+    let base64 = base64.trim_end();
+    let padding = base64.bytes().rev().take_while(|&b| b == b'=').count();
+    (base64.len() / 4 * 3).saturating_sub(padding) as u64
+}
+
+/// Fields of one note rewritten by a media rename.
+struct FieldRewrite {
+    note_id: i64,
+    /// Values before the rename, for restoring on failure.
+    original: HashMap<String, String>,
+    /// Values with references rewritten.
+    updated: HashMap<String, String>,
+    /// Number of references rewritten.
+    references: usize,
+}
+
+/// A quoted search term matching notes that contain `text` anywhere.
+fn literal_search(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for c in text.chars() {
+        if matches!(c, '\\' | '"' | '*' | '_' | ':') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// A `getMediaFilesNames` pattern matching only `filename`.
+///
+/// AnkiConnect matches names with Python's `fnmatch`, where `*`, `?`, and
+/// `[` are special; each is wrapped in a character class to match itself.
+fn glob_escape(filename: &str) -> String {
+    let mut escaped = String::with_capacity(filename.len());
+    for c in filename.chars() {
+        if matches!(c, '*' | '?' | '[') {
+            escaped.push('[');
+            escaped.push(c);
+            escaped.push(']');
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+/// Replace references to a media file in field HTML.
+///
+/// Returns the rewritten HTML and the number of references replaced.
+fn rewrite_media_references(html: &str, old_name: &str, new_name: &str) -> (String, usize) {
+    let mut result = html.to_string();
+    let mut count = 0;
+    for (from, to) in [
+        (
+            format!("[sound:{}]", old_name),
+            format!("[sound:{}]", new_name),
+        ),
+        (
+            format!("src=\"{}\"", old_name),
+            format!("src=\"{}\"", new_name),
+        ),
+        (format!("src='{}'", old_name), format!("src='{}'", new_name)),
+    ] {
+        let found = result.matches(&from).count();
+        if found > 0 {
+            count += found;
+            result = result.replace(&from, &to);
+        }
+    }
+    (result, count)
 }
 
 /// Extract media filenames from HTML field content.
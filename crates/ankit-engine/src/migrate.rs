@@ -1,11 +1,42 @@
 //! Note type migration operations.
 //!
 //! This module provides workflows for migrating notes from one
-//! note type (model) to another with field mapping.
+//! note type (model) to another with field mapping, including suggesting a
+//! mapping and previewing its effect on each note before running it, and
+//! for restructuring a model's fields in place.
 
-use crate::{Error, NoteBuilder, Result};
+use crate::changes::PlannedChange;
+use crate::concurrency;
+use crate::report::{WorkflowReport, report_fields};
+use crate::{EngineOptions, Error, NoteBuilder, NoteInfo, Result};
 use ankit::AnkiClient;
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Number of notes sampled per model when comparing field content.
+const SAMPLE_SIZE: usize = 20;
+
+/// Minimum score for a field pair to be suggested.
+const MIN_MATCH_SCORE: f64 = 0.3;
+
+/// Field names that usually hold the same kind of content.
+const FIELD_SYNONYMS: &[&[&str]] = &[
+    &[
+        "front",
+        "question",
+        "prompt",
+        "term",
+        "word",
+        "expression",
+        "text",
+    ],
+    &["back", "answer", "definition", "meaning", "translation"],
+    &["extra", "backextra", "notes", "comments", "info"],
+    &["reading", "pronunciation", "furigana", "kana"],
+    &["example", "examples", "sentence", "context"],
+    &["image", "picture", "photo"],
+    &["audio", "sound"],
+];
 
 /// Configuration for a note type migration.
 #[derive(Debug, Clone)]
@@ -25,7 +56,7 @@ pub struct MigrationConfig {
 }
 
 /// Report of a migration operation.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct MigrationReport {
     /// Number of notes successfully migrated.
     pub migrated: usize,
@@ -35,10 +66,32 @@ pub struct MigrationReport {
     pub deleted: usize,
     /// Errors encountered during migration.
     pub errors: Vec<MigrationError>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for MigrationReport {
+    fn summary(&self) -> String {
+        format!(
+            "Migrated {} notes, deleted {} ({} failed)",
+            self.migrated, self.deleted, self.failed
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.errors
+            .iter()
+            .map(|error| format!("note {}: {}", error.note_id, error.error))
+            .collect()
+    }
+
+    report_fields!(dry_run);
 }
 
 /// Error during migration of a single note.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct MigrationError {
     /// The source note ID.
     pub note_id: i64,
@@ -46,15 +99,142 @@ pub struct MigrationError {
     pub error: String,
 }
 
+/// A single change to a model's fields, as part of a restructuring plan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum FieldOperation {
+    /// Add an empty field.
+    Add {
+        /// Name of the new field.
+        name: String,
+        /// Position of the new field (0-based), or the end if `None`.
+        index: Option<i32>,
+    },
+    /// Rename a field, keeping its content.
+    Rename {
+        /// Current field name.
+        from: String,
+        /// New field name.
+        to: String,
+    },
+    /// Move a field to another position, keeping its content.
+    Reposition {
+        /// Field to move.
+        name: String,
+        /// New position (0-based).
+        index: i32,
+    },
+    /// Remove a field and its content.
+    Remove {
+        /// Field to remove.
+        name: String,
+    },
+}
+
+impl FieldOperation {
+    /// The planned change applying this operation to a model.
+    fn planned(&self, model: &str) -> PlannedChange {
+        let model = model.to_string();
+        match self.clone() {
+            FieldOperation::Add { name, index } => PlannedChange::AddField {
+                model,
+                field: name,
+                index,
+            },
+            FieldOperation::Rename { from, to } => PlannedChange::RenameField {
+                model,
+                old: from,
+                new: to,
+            },
+            FieldOperation::Reposition { name, index } => PlannedChange::RepositionField {
+                model,
+                field: name,
+                index,
+            },
+            FieldOperation::Remove { name } => PlannedChange::RemoveField { model, field: name },
+        }
+    }
+}
+
+/// Report of restructuring a model's fields.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RestructureReport {
+    /// Model that was restructured.
+    pub model: String,
+    /// Field names before the plan was applied.
+    pub fields_before: Vec<String>,
+    /// Field names after the plan was applied (expected, in a dry run).
+    pub fields_after: Vec<String>,
+    /// Number of operations applied.
+    pub operations_applied: usize,
+    /// Number of notes of the model whose content was checked.
+    pub notes_checked: usize,
+    /// Number of non-empty field values dropped by `Remove` operations.
+    pub values_removed: usize,
+    /// Fields whose content did not survive the restructuring.
+    pub mismatches: Vec<FieldMismatch>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl RestructureReport {
+    /// Whether every kept field still holds its original content.
+    pub fn verified(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+impl WorkflowReport for RestructureReport {
+    fn summary(&self) -> String {
+        format!(
+            "Applied {} field operations to '{}', checked {} notes ({} mismatches)",
+            self.operations_applied,
+            self.model,
+            self.notes_checked,
+            self.mismatches.len()
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.mismatches
+            .iter()
+            .map(|mismatch| {
+                format!(
+                    "note {}: field '{}' changed unexpectedly",
+                    mismatch.note_id, mismatch.field
+                )
+            })
+            .collect()
+    }
+
+    report_fields!(dry_run);
+}
+
+/// A field whose content differs from what the restructuring should keep.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldMismatch {
+    /// The note ID.
+    pub note_id: i64,
+    /// Field name after restructuring.
+    pub field: String,
+    /// Content the field should hold.
+    pub expected: String,
+    /// Content the field holds, or `None` if the field is missing.
+    pub actual: Option<String>,
+}
+
 /// Migration workflow engine.
 #[derive(Debug)]
 pub struct MigrateEngine<'a> {
     client: &'a AnkiClient,
+    options: &'a EngineOptions,
 }
 
 impl<'a> MigrateEngine<'a> {
-    pub(crate) fn new(client: &'a AnkiClient) -> Self {
-        Self { client }
+    pub(crate) fn new(client: &'a AnkiClient, options: &'a EngineOptions) -> Self {
+        Self { client, options }
     }
 
     /// Migrate notes from one model to another.
@@ -129,17 +309,15 @@ impl<'a> MigrateEngine<'a> {
         let note_ids = self.client.notes().find(&full_query).await?;
         let note_infos = self.client.notes().info(&note_ids).await?;
 
-        let mut report = MigrationReport::default();
+        let mut report = MigrationReport {
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
         let mut notes_to_delete = Vec::new();
+        let mut pending = Vec::new();
 
         for info in note_infos {
-            // Map fields
-            let mut new_fields = HashMap::new();
-            for (source_field, target_field) in &config.field_mapping {
-                if let Some(field_info) = info.fields.get(source_field) {
-                    new_fields.insert(target_field.clone(), field_info.value.clone());
-                }
-            }
+            let new_fields = map_fields(&info, &config.field_mapping);
 
             // Determine deck
             // Get deck from first card of source note
@@ -171,17 +349,46 @@ impl<'a> MigrateEngine<'a> {
             // Allow duplicate since we're migrating
             let note = builder.allow_duplicate(true).build();
 
-            match self.client.notes().add(note).await {
+            if report.dry_run {
+                report.planned.push(PlannedChange::AddNote { note });
+                report.migrated += 1;
+                if config.delete_source {
+                    notes_to_delete.push(info.note_id);
+                }
+                continue;
+            }
+            pending.push((info.note_id, note));
+        }
+
+        let tracker = self.options.track("migrating notes", pending.len());
+        let results = concurrency::bounded(self.options.concurrency, pending, |(note_id, note)| {
+            let notes = self.client.notes();
+            let tracker = &tracker;
+            async move {
+                if tracker.is_cancelled() {
+                    return (note_id, None);
+                }
+                let result = notes.add(note).await;
+                tracker.advance(1);
+                (note_id, Some(result))
+            }
+        })
+        .await;
+        // Source notes of migrated copies are kept when cancelled
+        tracker.check()?;
+        for (note_id, result) in results {
+            let Some(result) = result else { continue };
+            match result {
                 Ok(_) => {
                     report.migrated += 1;
                     if config.delete_source {
-                        notes_to_delete.push(info.note_id);
+                        notes_to_delete.push(note_id);
                     }
                 }
                 Err(e) => {
                     report.failed += 1;
                     report.errors.push(MigrationError {
-                        note_id: info.note_id,
+                        note_id,
                         error: e.to_string(),
                     });
                 }
@@ -190,16 +397,146 @@ impl<'a> MigrateEngine<'a> {
 
         // Delete source notes if requested
         if !notes_to_delete.is_empty() {
-            self.client.notes().delete(&notes_to_delete).await?;
             report.deleted = notes_to_delete.len();
+            if report.dry_run {
+                report.planned.push(PlannedChange::DeleteNotes {
+                    note_ids: notes_to_delete,
+                });
+            } else {
+                self.client.notes().delete(&notes_to_delete).await?;
+            }
         }
 
         Ok(report)
     }
 
+    /// Suggest a field mapping between two models.
+    ///
+    /// Fields are paired by name (exact matches, common synonyms such as
+    /// `Front`/`Question`, and names containing one another) and by the
+    /// shape of their content in a sample of existing notes of each model
+    /// (length, HTML, media, and cloze markers). Each source field is mapped
+    /// to at most one target field.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let suggestion = engine.migrate().suggest_mapping("Basic", "Vocabulary").await?;
+    /// for m in &suggestion.matches {
+    ///     println!("{} -> {} ({:.2})", m.source, m.target, m.score);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn suggest_mapping(
+        &self,
+        source_model: &str,
+        target_model: &str,
+    ) -> Result<MappingSuggestion> {
+        let models = self.client.models().names().await?;
+        for model in [source_model, target_model] {
+            if !models.iter().any(|m| m == model) {
+                return Err(Error::ModelNotFound(model.to_string()));
+            }
+        }
+
+        let source_fields = self.client.models().field_names(source_model).await?;
+        let target_fields = self.client.models().field_names(target_model).await?;
+        let source_profiles = self.sample_profiles(source_model, &source_fields).await?;
+        let target_profiles = self.sample_profiles(target_model, &target_fields).await?;
+
+        let mut candidates = Vec::new();
+        for (si, source) in source_fields.iter().enumerate() {
+            for (ti, target) in target_fields.iter().enumerate() {
+                let name_score = name_similarity(source, target);
+                let content_score = match (&source_profiles[si], &target_profiles[ti]) {
+                    (Some(a), Some(b)) => Some(a.similarity(b)),
+                    _ => None,
+                };
+                let position_score = if si == ti { 1.0 } else { 0.0 };
+                let score =
+                    0.7 * name_score + 0.2 * content_score.unwrap_or(0.5) + 0.1 * position_score;
+
+                if score >= MIN_MATCH_SCORE {
+                    candidates.push(FieldMatch {
+                        source: source.clone(),
+                        target: target.clone(),
+                        score,
+                        name_score,
+                        content_score,
+                    });
+                }
+            }
+        }
+
+        // Greedily take the best remaining pair
+        candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+        let mut used_sources = HashSet::new();
+        let mut used_targets = HashSet::new();
+        let mut matches = Vec::new();
+        for candidate in candidates {
+            if used_sources.contains(&candidate.source) || used_targets.contains(&candidate.target)
+            {
+                continue;
+            }
+            used_sources.insert(candidate.source.clone());
+            used_targets.insert(candidate.target.clone());
+            matches.push(candidate);
+        }
+
+        Ok(MappingSuggestion {
+            source_model: source_model.to_string(),
+            target_model: target_model.to_string(),
+            matches,
+            unmapped_source: source_fields
+                .into_iter()
+                .filter(|f| !used_sources.contains(f))
+                .collect(),
+            unmapped_target: target_fields
+                .into_iter()
+                .filter(|f| !used_targets.contains(f))
+                .collect(),
+        })
+    }
+
+    /// Profile the content of each field over a sample of a model's notes.
+    async fn sample_profiles(
+        &self,
+        model: &str,
+        fields: &[String],
+    ) -> Result<Vec<Option<ContentProfile>>> {
+        let mut note_ids = self
+            .client
+            .notes()
+            .find(&format!("note:\"{}\"", model))
+            .await?;
+        note_ids.truncate(SAMPLE_SIZE);
+        let notes = if note_ids.is_empty() {
+            Vec::new()
+        } else {
+            self.client.notes().info(&note_ids).await?
+        };
+
+        Ok(fields
+            .iter()
+            .map(|field| {
+                ContentProfile::from_values(
+                    notes
+                        .iter()
+                        .filter_map(|n| n.fields.get(field))
+                        .map(|f| f.value.as_str()),
+                )
+            })
+            .collect())
+    }
+
     /// Preview a migration without making changes.
     ///
-    /// Returns information about what would be migrated.
+    /// Returns information about what would be migrated, including the
+    /// before and after field values of every matching note.
     pub async fn preview(
         &self,
         config: &MigrationConfig,
@@ -240,28 +577,419 @@ impl<'a> MigrateEngine<'a> {
             }
         }
 
-        // Count notes
-        let note_count = if source_exists {
+        // Show each note before and after
+        let note_infos = if source_exists {
             let base_query = format!("note:\"{}\"", config.source_model);
             let full_query = match query {
                 Some(q) => format!("{} {}", base_query, q),
                 None => base_query,
             };
-            let notes = self.client.notes().find(&full_query).await?;
-            notes.len()
+            let note_ids = self.client.notes().find(&full_query).await?;
+            if note_ids.is_empty() {
+                Vec::new()
+            } else {
+                self.client.notes().info(&note_ids).await?
+            }
         } else {
-            0
+            Vec::new()
         };
 
+        let notes: Vec<NotePreview> = note_infos
+            .iter()
+            .map(|info| {
+                let mut after: HashMap<String, String> = target_fields
+                    .iter()
+                    .map(|f| (f.clone(), String::new()))
+                    .collect();
+                after.extend(map_fields(info, &config.field_mapping));
+
+                let mut dropped: Vec<String> = info
+                    .fields
+                    .iter()
+                    .filter(|(name, field)| {
+                        !field.value.trim().is_empty() && !config.field_mapping.contains_key(*name)
+                    })
+                    .map(|(name, _)| name.clone())
+                    .collect();
+                dropped.sort();
+
+                NotePreview {
+                    note_id: info.note_id,
+                    before: info
+                        .fields
+                        .iter()
+                        .map(|(name, field)| (name.clone(), field.value.clone()))
+                        .collect(),
+                    after,
+                    dropped,
+                }
+            })
+            .collect();
+
         Ok(MigrationPreview {
             source_model_exists: source_exists,
             target_model_exists: target_exists,
             source_fields,
             target_fields,
-            notes_to_migrate: note_count,
+            notes_to_migrate: notes.len(),
             mapping_issues,
+            notes,
         })
     }
+
+    /// Apply a sequence of field operations to a model and verify that note
+    /// content survived.
+    ///
+    /// The whole plan is checked against the model's current fields before
+    /// anything changes, so a plan naming a missing field or creating a
+    /// duplicate fails up front. After the operations run, every note of the
+    /// model is re-read and each kept field is compared with its content
+    /// from before; differences are reported in
+    /// [`RestructureReport::mismatches`]. Added fields are expected to be
+    /// empty and removed fields are counted in
+    /// [`RestructureReport::values_removed`].
+    ///
+    /// Respects [`EngineOptions::dry_run`]: the plan is validated and the
+    /// resulting fields reported without changing the model.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ModelNotFound`] if the model doesn't exist,
+    /// [`Error::MissingField`] if an operation names a field the model won't
+    /// have at that point, and [`Error::Validation`] for duplicate names or
+    /// out-of-range positions.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # use ankit_engine::migrate::FieldOperation;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let plan = [
+    ///     FieldOperation::Rename { from: "Back".into(), to: "Meaning".into() },
+    ///     FieldOperation::Add { name: "Reading".into(), index: Some(1) },
+    ///     FieldOperation::Remove { name: "Unused".into() },
+    /// ];
+    ///
+    /// let report = engine.migrate().restructure_model("Vocabulary", &plan).await?;
+    /// assert!(report.verified());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn restructure_model(
+        &self,
+        model: &str,
+        plan: &[FieldOperation],
+    ) -> Result<RestructureReport> {
+        let models = self.client.models().names().await?;
+        if !models.iter().any(|name| name == model) {
+            return Err(Error::ModelNotFound(model.to_string()));
+        }
+
+        let fields_before = self.client.models().field_names(model).await?;
+        let layout = plan_fields(model, &fields_before, plan)?;
+
+        let mut report = RestructureReport {
+            model: model.to_string(),
+            fields_before,
+            fields_after: layout.iter().map(|(name, _)| name.clone()).collect(),
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        let note_ids = self
+            .client
+            .notes()
+            .find(&format!("note:\"{}\"", model))
+            .await?;
+        let before = if note_ids.is_empty() {
+            Vec::new()
+        } else {
+            self.client.notes().info(&note_ids).await?
+        };
+        report.notes_checked = before.len();
+
+        let kept: HashSet<&str> = layout
+            .iter()
+            .filter_map(|(_, original)| original.as_deref())
+            .collect();
+        report.values_removed = before
+            .iter()
+            .flat_map(|info| &info.fields)
+            .filter(|(name, field)| !kept.contains(name.as_str()) && !field.value.trim().is_empty())
+            .count();
+
+        if report.dry_run {
+            report.planned = plan.iter().map(|op| op.planned(model)).collect();
+            report.operations_applied = plan.len();
+            return Ok(report);
+        }
+
+        let models = self.client.models();
+        for op in plan {
+            match op {
+                FieldOperation::Add { name, index } => {
+                    models.add_field(model, name, *index).await?
+                }
+                FieldOperation::Rename { from, to } => models.rename_field(model, from, to).await?,
+                FieldOperation::Reposition { name, index } => {
+                    models.reposition_field(model, name, *index).await?
+                }
+                FieldOperation::Remove { name } => models.remove_field(model, name).await?,
+            }
+            report.operations_applied += 1;
+        }
+
+        report.fields_after = self.client.models().field_names(model).await?;
+        if before.is_empty() {
+            return Ok(report);
+        }
+        let after: HashMap<i64, NoteInfo> = self
+            .client
+            .notes()
+            .info(&note_ids)
+            .await?
+            .into_iter()
+            .map(|info| (info.note_id, info))
+            .collect();
+
+        for info in &before {
+            for (name, original) in &layout {
+                let expected = original
+                    .as_ref()
+                    .and_then(|original| info.fields.get(original))
+                    .map(|field| field.value.clone())
+                    .unwrap_or_default();
+                let actual = after
+                    .get(&info.note_id)
+                    .and_then(|note| note.fields.get(name))
+                    .map(|field| field.value.clone());
+                if actual.as_deref() != Some(expected.as_str()) {
+                    report.mismatches.push(FieldMismatch {
+                        note_id: info.note_id,
+                        field: name.clone(),
+                        expected,
+                        actual,
+                    });
+                }
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+/// Map a note's field values through a field mapping.
+fn map_fields(info: &NoteInfo, mapping: &HashMap<String, String>) -> HashMap<String, String> {
+    mapping
+        .iter()
+        .filter_map(|(source, target)| {
+            info.fields
+                .get(source)
+                .map(|field| (target.clone(), field.value.clone()))
+        })
+        .collect()
+}
+
+/// Simulate a restructuring plan against a model's fields.
+///
+/// Returns the resulting fields in order, each paired with the name it had
+/// before the plan (or `None` for added fields).
+fn plan_fields(
+    model: &str,
+    fields: &[String],
+    plan: &[FieldOperation],
+) -> Result<Vec<(String, Option<String>)>> {
+    let mut layout: Vec<(String, Option<String>)> = fields
+        .iter()
+        .map(|name| (name.clone(), Some(name.clone())))
+        .collect();
+    let position = |layout: &[(String, Option<String>)], name: &str| {
+        layout
+            .iter()
+            .position(|(current, _)| current == name)
+            .ok_or_else(|| Error::MissingField {
+                model: model.to_string(),
+                field: name.to_string(),
+            })
+    };
+    let check_new = |layout: &[(String, Option<String>)], name: &str| {
+        if name.trim().is_empty() {
+            Err(Error::Validation("field name cannot be empty".to_string()))
+        } else if layout.iter().any(|(current, _)| current == name) {
+            Err(Error::Validation(format!(
+                "model '{}' already has a field named '{}'",
+                model, name
+            )))
+        } else {
+            Ok(())
+        }
+    };
+    let check_index = |index: i32, len: usize| {
+        usize::try_from(index)
+            .ok()
+            .filter(|&index| index <= len)
+            .ok_or_else(|| Error::Validation(format!("field position {} is out of range", index)))
+    };
+
+    for op in plan {
+        match op {
+            FieldOperation::Add { name, index } => {
+                check_new(&layout, name)?;
+                let at = match index {
+                    Some(index) => check_index(*index, layout.len())?,
+                    None => layout.len(),
+                };
+                layout.insert(at, (name.clone(), None));
+            }
+            FieldOperation::Rename { from, to } => {
+                let at = position(&layout, from)?;
+                check_new(&layout, to)?;
+                layout[at].0 = to.clone();
+            }
+            FieldOperation::Reposition { name, index } => {
+                let at = position(&layout, name)?;
+                let field = layout.remove(at);
+                let to = check_index(*index, layout.len())?;
+                layout.insert(to, field);
+            }
+            FieldOperation::Remove { name } => {
+                let at = position(&layout, name)?;
+                if layout.len() == 1 {
+                    return Err(Error::Validation(format!(
+                        "cannot remove '{}', the last field of model '{}'",
+                        name, model
+                    )));
+                }
+                layout.remove(at);
+            }
+        }
+    }
+
+    Ok(layout)
+}
+
+/// Score how likely two field names are to hold the same content (0.0 - 1.0).
+fn name_similarity(a: &str, b: &str) -> f64 {
+    let normalize = |s: &str| -> String {
+        s.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+    let (a, b) = (normalize(a), normalize(b));
+
+    if a.is_empty() || b.is_empty() {
+        0.0
+    } else if a == b {
+        1.0
+    } else if FIELD_SYNONYMS
+        .iter()
+        .any(|group| group.contains(&a.as_str()) && group.contains(&b.as_str()))
+    {
+        0.9
+    } else if a.contains(&b) || b.contains(&a) {
+        0.7
+    } else {
+        0.0
+    }
+}
+
+/// Shape of a field's content, averaged over sampled notes.
+#[derive(Debug, Clone, Copy)]
+struct ContentProfile {
+    /// Average length in characters.
+    length: f64,
+    /// Fraction of values containing HTML tags.
+    html: f64,
+    /// Fraction of values referencing media.
+    media: f64,
+    /// Fraction of values containing cloze deletions.
+    cloze: f64,
+}
+
+impl ContentProfile {
+    /// Profile non-empty values, or `None` if there are none.
+    fn from_values<'a>(values: impl Iterator<Item = &'a str>) -> Option<Self> {
+        let values: Vec<&str> = values.filter(|v| !v.trim().is_empty()).collect();
+        if values.is_empty() {
+            return None;
+        }
+
+        let n = values.len() as f64;
+        let fraction =
+            |pred: &dyn Fn(&str) -> bool| values.iter().filter(|v| pred(v)).count() as f64 / n;
+        Some(Self {
+            length: values.iter().map(|v| v.chars().count()).sum::<usize>() as f64 / n,
+            html: fraction(&|v| v.contains('<') && v.contains('>')),
+            media: fraction(&|v| v.contains("<img") || v.contains("[sound:")),
+            cloze: fraction(&|v| v.contains("{{c") && v.contains("::")),
+        })
+    }
+
+    /// Similarity to another profile (0.0 - 1.0).
+    fn similarity(&self, other: &Self) -> f64 {
+        let length = 1.0 - (self.length - other.length).abs() / self.length.max(other.length);
+        let html = 1.0 - (self.html - other.html).abs();
+        let media = 1.0 - (self.media - other.media).abs();
+        let cloze = 1.0 - (self.cloze - other.cloze).abs();
+        (length + html + media + cloze) / 4.0
+    }
+}
+
+/// A suggested pairing of a source field with a target field.
+#[derive(Debug, Clone)]
+pub struct FieldMatch {
+    /// Source field name.
+    pub source: String,
+    /// Target field name.
+    pub target: String,
+    /// Overall confidence (0.0 - 1.0).
+    pub score: f64,
+    /// How similar the field names are (0.0 - 1.0).
+    pub name_score: f64,
+    /// How similar the sampled content is (0.0 - 1.0), if both models
+    /// have notes with content in these fields.
+    pub content_score: Option<f64>,
+}
+
+/// Suggested field mapping between two models.
+#[derive(Debug, Clone)]
+pub struct MappingSuggestion {
+    /// Source model name.
+    pub source_model: String,
+    /// Target model name.
+    pub target_model: String,
+    /// Suggested field pairs, best match first.
+    pub matches: Vec<FieldMatch>,
+    /// Source fields with no suggested target (their content would be lost).
+    pub unmapped_source: Vec<String>,
+    /// Target fields with no suggested source (they would be left empty).
+    pub unmapped_target: Vec<String>,
+}
+
+impl MappingSuggestion {
+    /// The suggested mapping, ready for [`MigrationConfig::field_mapping`].
+    pub fn field_mapping(&self) -> HashMap<String, String> {
+        self.matches
+            .iter()
+            .map(|m| (m.source.clone(), m.target.clone()))
+            .collect()
+    }
+}
+
+/// Before and after field values for one note in a migration preview.
+#[derive(Debug, Clone)]
+pub struct NotePreview {
+    /// The source note ID.
+    pub note_id: i64,
+    /// Source field values.
+    pub before: HashMap<String, String>,
+    /// Field values the migrated note would have.
+    pub after: HashMap<String, String>,
+    /// Non-empty source fields that are not mapped and would be lost.
+    pub dropped: Vec<String>,
 }
 
 /// Preview of a migration operation.
@@ -279,4 +1007,6 @@ pub struct MigrationPreview {
     pub notes_to_migrate: usize,
     /// Issues with the field mapping.
     pub mapping_issues: Vec<String>,
+    /// Before and after field values for each note.
+    pub notes: Vec<NotePreview>,
 }
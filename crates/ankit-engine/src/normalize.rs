@@ -0,0 +1,513 @@
+//! HTML cleanup for note fields.
+//!
+//! Fields edited in Anki, pasted from web pages, or exported from other tools
+//! tend to accumulate messy HTML: inline styles, `<span>` wrappers, `&nbsp;`
+//! runs, and a mix of `<div>` and `<br>` line breaks. [`normalize`] cleans a
+//! field value according to [`NormalizeOptions`], and
+//! [`EnrichEngine::normalize_fields`](crate::enrich::EnrichEngine::normalize_fields)
+//! applies it to notes in bulk.
+//!
+//! # Example
+//!
+//! ```
+//! use ankit_engine::normalize::{normalize, NormalizeOptions};
+//!
+//! let messy = "<div><span style=\"color: red\">hola</span>&nbsp; &nbsp;mundo</div><div><b>adiós</b></div>";
+//! let clean = normalize(messy, &NormalizeOptions::default());
+//! assert_eq!(clean, "hola mundo<br><b>adiós</b>");
+//! ```
+
+/// Tags kept by [`TagPolicy::default()`]: basic formatting, media, and lists.
+pub const DEFAULT_ALLOWED_TAGS: &[&str] = &[
+    "b", "i", "u", "s", "strong", "em", "sub", "sup", "code", "img", "a", "ruby", "rb", "rt", "ul",
+    "ol", "li",
+];
+
+/// Attributes kept on allowed tags. All others (`style`, `class`, ...) are
+/// removed.
+const KEPT_ATTRIBUTES: &[&str] = &["src", "alt", "href"];
+
+/// Tags that start a new line.
+const BLOCK_TAGS: &[&str] = &[
+    "div",
+    "p",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "blockquote",
+    "pre",
+    "tr",
+];
+
+/// Which HTML tags to keep.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagPolicy {
+    /// Keep every tag as-is, including attributes.
+    Keep,
+    /// Remove every tag, leaving plain text.
+    StripAll,
+    /// Keep only the listed tags (lowercase names), without styling
+    /// attributes.
+    Allow(Vec<String>),
+}
+
+impl Default for TagPolicy {
+    fn default() -> Self {
+        Self::Allow(DEFAULT_ALLOWED_TAGS.iter().map(|t| t.to_string()).collect())
+    }
+}
+
+/// How to represent line breaks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineBreaks {
+    /// Leave `<div>`, `<p>`, and `<br>` as they are (subject to the tag policy).
+    Keep,
+    /// Convert block elements and `<br>` to `<br>`.
+    #[default]
+    Br,
+    /// Convert block elements and `<br>` to newline characters.
+    Newline,
+}
+
+/// Options for [`normalize`].
+#[derive(Debug, Clone)]
+pub struct NormalizeOptions {
+    /// Which tags to keep.
+    pub tags: TagPolicy,
+    /// How to represent line breaks.
+    pub line_breaks: LineBreaks,
+    /// Collapse runs of whitespace (including `&nbsp;`) into single spaces.
+    pub collapse_whitespace: bool,
+    /// Decode HTML entities such as `&eacute;` and `&#233;`.
+    ///
+    /// Unless tags are stripped entirely, `&lt;`, `&gt;`, and `&amp;` stay
+    /// encoded so decoded text cannot turn into markup.
+    pub decode_entities: bool,
+    /// Remove leading and trailing whitespace and line breaks.
+    pub trim: bool,
+    /// Fields to normalize when applied to notes. Empty means all fields.
+    pub fields: Vec<String>,
+}
+
+impl Default for NormalizeOptions {
+    fn default() -> Self {
+        Self {
+            tags: TagPolicy::default(),
+            line_breaks: LineBreaks::default(),
+            collapse_whitespace: true,
+            decode_entities: true,
+            trim: true,
+            fields: Vec::new(),
+        }
+    }
+}
+
+impl NormalizeOptions {
+    /// Options that reduce a field to plain text with newline line breaks.
+    pub fn plain_text() -> Self {
+        Self {
+            tags: TagPolicy::StripAll,
+            line_breaks: LineBreaks::Newline,
+            ..Default::default()
+        }
+    }
+
+    /// Restrict normalization to specific fields.
+    pub fn fields<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.fields = fields.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+/// Clean up a field's HTML.
+pub fn normalize(html: &str, options: &NormalizeOptions) -> String {
+    let mut out = Output::new(options);
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        out.text(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(after) = rest.strip_prefix("<!--") {
+            rest = after.find("-->").map_or("", |end| &after[end + 3..]);
+            continue;
+        }
+        let Some(end) = rest.find('>') else {
+            break;
+        };
+        match Tag::parse(&rest[..=end]) {
+            Some(tag) => out.tag(&tag, &rest[..=end]),
+            None => out.text(&rest[..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    out.text(rest);
+
+    out.finish()
+}
+
+/// A parsed HTML tag.
+struct Tag<'a> {
+    name: String,
+    closing: bool,
+    attributes: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Tag<'a> {
+    /// Parse `<name attr="value">` or `</name>`; `None` if it is not a tag.
+    fn parse(raw: &'a str) -> Option<Self> {
+        let inner = raw.strip_prefix('<')?.strip_suffix('>')?;
+        let inner = inner.strip_suffix('/').unwrap_or(inner);
+        let (closing, inner) = match inner.strip_prefix('/') {
+            Some(inner) => (true, inner),
+            None => (false, inner),
+        };
+        let name_len = inner
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(inner.len());
+        if name_len == 0 || !inner.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return None;
+        }
+
+        Some(Self {
+            name: inner[..name_len].to_ascii_lowercase(),
+            closing,
+            attributes: parse_attributes(&inner[name_len..]),
+        })
+    }
+
+    fn is_block(&self) -> bool {
+        BLOCK_TAGS.contains(&self.name.as_str())
+    }
+
+    /// Render the tag with only the kept attributes.
+    fn render_clean(&self) -> String {
+        if self.closing {
+            return format!("</{}>", self.name);
+        }
+        let mut tag = format!("<{}", self.name);
+        for (name, value) in &self.attributes {
+            if KEPT_ATTRIBUTES.contains(&name.to_ascii_lowercase().as_str()) {
+                tag.push_str(&format!(" {}=\"{}\"", name.to_ascii_lowercase(), value));
+            }
+        }
+        tag.push('>');
+        tag
+    }
+}
+
+/// Parse `name="value"`, `name='value'`, and `name=value` attributes.
+fn parse_attributes(mut rest: &str) -> Vec<(&str, &str)> {
+    let mut attributes = Vec::new();
+    loop {
+        rest = rest.trim_start();
+        let name_len = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        if name_len == 0 {
+            return attributes;
+        }
+        let name = &rest[..name_len];
+        rest = rest[name_len..].trim_start();
+
+        let Some(after) = rest.strip_prefix('=') else {
+            attributes.push((name, ""));
+            continue;
+        };
+        let after = after.trim_start();
+        let (value, remaining) = match after.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let body = &after[1..];
+                match body.find(quote) {
+                    Some(end) => (&body[..end], &body[end + 1..]),
+                    None => (body, ""),
+                }
+            }
+            _ => {
+                let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                (&after[..end], &after[end..])
+            }
+        };
+        attributes.push((name, value));
+        rest = remaining;
+    }
+}
+
+/// Accumulates normalized output.
+struct Output<'a> {
+    options: &'a NormalizeOptions,
+    html: String,
+    /// A block boundary was seen; a line break is due before more content.
+    pending_break: bool,
+    /// Whitespace was seen; a single space is due before more content.
+    pending_space: bool,
+}
+
+impl<'a> Output<'a> {
+    fn new(options: &'a NormalizeOptions) -> Self {
+        Self {
+            options,
+            html: String::new(),
+            pending_break: false,
+            pending_space: false,
+        }
+    }
+
+    fn line_break(&self) -> &'static str {
+        match self.options.line_breaks {
+            LineBreaks::Newline => "\n",
+            _ => "<br>",
+        }
+    }
+
+    fn push_break(&mut self) {
+        self.html.push_str(self.line_break());
+        self.pending_break = false;
+        self.pending_space = false;
+    }
+
+    /// Emit any pending line break or space before new content.
+    fn flush(&mut self) {
+        if self.pending_break {
+            if !self.html.is_empty() && !self.html.ends_with(self.line_break()) {
+                self.push_break();
+            }
+            self.pending_break = false;
+            self.pending_space = false;
+        }
+        if self.pending_space {
+            self.html.push(' ');
+            self.pending_space = false;
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let text = if self.options.decode_entities {
+            decode_entities(text, self.options.tags != TagPolicy::StripAll)
+        } else {
+            text.to_string()
+        };
+
+        if !self.options.collapse_whitespace {
+            self.flush();
+            self.html.push_str(&text);
+            return;
+        }
+
+        for c in text.chars() {
+            if is_space(c) {
+                self.pending_space = !self.html.ends_with(self.line_break());
+            } else {
+                self.flush();
+                self.html.push(c);
+            }
+        }
+    }
+
+    fn tag(&mut self, tag: &Tag<'_>, raw: &str) {
+        if self.options.line_breaks != LineBreaks::Keep {
+            if tag.name == "br" {
+                self.pending_break = false;
+                self.push_break();
+                return;
+            }
+            if tag.is_block() {
+                self.pending_break = true;
+                return;
+            }
+        }
+
+        let rendered = match &self.options.tags {
+            TagPolicy::Keep => raw.to_string(),
+            TagPolicy::StripAll => return,
+            TagPolicy::Allow(allowed) => {
+                if !allowed.iter().any(|a| a.eq_ignore_ascii_case(&tag.name)) {
+                    return;
+                }
+                tag.render_clean()
+            }
+        };
+        // Spaces go outside closing tags: `<b>a </b>b` becomes `<b>a</b> b`
+        if !tag.closing {
+            self.flush();
+        }
+        self.html.push_str(&rendered);
+    }
+
+    fn finish(mut self) -> String {
+        if !self.options.trim {
+            if self.pending_space {
+                self.html.push(' ');
+            }
+            return self.html;
+        }
+
+        let line_break = self.line_break();
+        let mut html = self.html.as_str();
+        loop {
+            let trimmed = html.trim_matches(is_space);
+            let trimmed = trimmed.strip_prefix(line_break).unwrap_or(trimmed);
+            let trimmed = trimmed.strip_suffix(line_break).unwrap_or(trimmed);
+            if trimmed.len() == html.len() {
+                break;
+            }
+            html = trimmed;
+        }
+        html.to_string()
+    }
+}
+
+/// Whitespace, including non-breaking spaces.
+fn is_space(c: char) -> bool {
+    c.is_whitespace() || c == '\u{a0}'
+}
+
+/// Decode HTML entities. With `keep_markup`, `<`, `>`, and `&` stay encoded.
+fn decode_entities(text: &str, keep_markup: bool) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|&len| len > 0 && len <= 10)
+            .and_then(|len| Some((decode_entity(&rest[1..=len])?, len + 2)));
+        match decoded {
+            Some((c, len)) => {
+                match (keep_markup, c) {
+                    (true, '<') => out.push_str("&lt;"),
+                    (true, '>') => out.push_str("&gt;"),
+                    (true, '&') => out.push_str("&amp;"),
+                    _ => out.push(c),
+                }
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Decode a single entity name (without `&` and `;`).
+fn decode_entity(name: &str) -> Option<char> {
+    if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        "ndash" => '\u{2013}',
+        "mdash" => '\u{2014}',
+        "hellip" => '\u{2026}',
+        "lsquo" => '\u{2018}',
+        "rsquo" => '\u{2019}',
+        "ldquo" => '\u{201c}',
+        "rdquo" => '\u{201d}',
+        "laquo" => '\u{ab}',
+        "raquo" => '\u{bb}',
+        "middot" => '\u{b7}',
+        "copy" => '\u{a9}',
+        "deg" => '\u{b0}',
+        "times" => '\u{d7}',
+        "aacute" => 'á',
+        "eacute" => 'é',
+        "iacute" => 'í',
+        "oacute" => 'ó',
+        "uacute" => 'ú',
+        "ntilde" => 'ñ',
+        "uuml" => 'ü',
+        "ouml" => 'ö',
+        "auml" => 'ä',
+        "szlig" => 'ß',
+        "ccedil" => 'ç',
+        "egrave" => 'è',
+        "agrave" => 'à',
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_cleanup() {
+        let options = NormalizeOptions::default();
+
+        assert_eq!(
+            normalize(
+                "<div style=\"x\">one</div><div>two<br></div><p>three</p>",
+                &options
+            ),
+            "one<br>two<br>three"
+        );
+        assert_eq!(
+            normalize("<span class=\"x\">a</span>  <b style=\"y\">b</b>", &options),
+            "a <b>b</b>"
+        );
+        assert_eq!(
+            normalize("<img src=\"cat.jpg\" width=\"100\"> &nbsp;", &options),
+            "<img src=\"cat.jpg\">"
+        );
+        assert_eq!(normalize("line<br><br>gap", &options), "line<br><br>gap");
+    }
+
+    #[test]
+    fn test_entities() {
+        let options = NormalizeOptions::default();
+        assert_eq!(
+            normalize("caf&eacute; &lt;b&gt; &#233; &unknown;", &options),
+            "café &lt;b&gt; é &unknown;"
+        );
+
+        let plain = NormalizeOptions::plain_text();
+        assert_eq!(normalize("1 &lt; 2<div>ok</div>", &plain), "1 < 2\nok");
+    }
+
+    #[test]
+    fn test_keep_options() {
+        let options = NormalizeOptions {
+            tags: TagPolicy::Keep,
+            line_breaks: LineBreaks::Keep,
+            collapse_whitespace: false,
+            decode_entities: false,
+            trim: false,
+            fields: Vec::new(),
+        };
+        let html = " <div style=\"x\">a&nbsp;&nbsp;b</div> ";
+
+        assert_eq!(normalize(html, &options), html);
+    }
+
+    #[test]
+    fn test_comments_and_stray_brackets() {
+        let options = NormalizeOptions::default();
+        assert_eq!(normalize("a <!-- note --> < b", &options), "a < b");
+    }
+}
@@ -0,0 +1,234 @@
+//! Notifications for workflow results.
+//!
+//! A [`Notifier`] receives a [`Notification`] when a workflow finishes, so
+//! unattended runs (a nightly backup, a leech report) are visible somewhere
+//! other than a log file. [`WebhookNotifier`] posts to a URL, with payloads
+//! for Slack and Discord incoming webhooks, and [`StdoutNotifier`] prints.
+//!
+//! [`JobScheduler::notifier`](crate::jobs::JobScheduler::notifier) sends a
+//! notification after every scheduled run.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ankit_engine::Engine;
+//! use ankit_engine::notify::{Notification, Notifier, WebhookFormat, WebhookNotifier};
+//!
+//! # async fn example() -> ankit_engine::Result<()> {
+//! let engine = Engine::new();
+//! let slack = WebhookNotifier::new("https://hooks.slack.com/services/T000/B000/XXXX")
+//!     .format(WebhookFormat::Slack);
+//!
+//! let result = engine.backup().backup_collection("backups/").await?;
+//! slack
+//!     .notify(&Notification::from_report("backup", &result))
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io::Write;
+
+use futures_util::future::BoxFuture;
+use serde::Serialize;
+
+use crate::report::WorkflowReport;
+use crate::{Error, Result};
+
+/// A destination for workflow notifications.
+///
+/// Notifiers are stored as trait objects by the scheduler, so `notify`
+/// returns a boxed future.
+pub trait Notifier: Send + Sync {
+    /// Deliver a notification.
+    fn notify<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<()>>;
+}
+
+/// The outcome of a workflow run.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Notification {
+    /// What ran, such as a job or workflow name.
+    pub source: String,
+    /// Whether it succeeded.
+    pub success: bool,
+    /// One-line summary of the result.
+    pub summary: String,
+    /// Extra lines, such as individual failures.
+    pub details: Vec<String>,
+    /// The full report, if any.
+    pub report: Option<serde_json::Value>,
+}
+
+impl Notification {
+    /// A successful result with only a summary.
+    pub fn new(source: impl Into<String>, summary: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            success: true,
+            summary: summary.into(),
+            ..Default::default()
+        }
+    }
+
+    /// A failed run.
+    pub fn failure(source: impl Into<String>, error: impl std::fmt::Display) -> Self {
+        Self {
+            success: false,
+            ..Self::new(source, error.to_string())
+        }
+    }
+
+    /// A successful result from a workflow report.
+    pub fn from_report(source: impl Into<String>, report: &impl WorkflowReport) -> Self {
+        Self {
+            details: report.details(),
+            report: report.to_json().ok(),
+            ..Self::new(source, report.summary())
+        }
+    }
+
+    /// The notification as a line of text, e.g. `backup: 12 decks backed up`
+    /// or `backup failed: ...`.
+    pub fn text(&self) -> String {
+        if self.success {
+            format!("{}: {}", self.source, self.summary)
+        } else {
+            format!("{} failed: {}", self.source, self.summary)
+        }
+    }
+}
+
+/// Prints notifications to standard output.
+#[derive(Debug, Clone, Default)]
+pub struct StdoutNotifier {
+    json: bool,
+}
+
+impl StdoutNotifier {
+    /// Print the summary and details as text.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Print each notification as a line of JSON instead.
+    pub fn json() -> Self {
+        Self { json: true }
+    }
+}
+
+impl Notifier for StdoutNotifier {
+    fn notify<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let mut out = std::io::stdout().lock();
+            if self.json {
+                let line = serde_json::to_string(notification).map_err(std::io::Error::from)?;
+                writeln!(out, "{}", line)?;
+            } else {
+                writeln!(out, "{}", notification.text())?;
+                for line in &notification.details {
+                    writeln!(out, "  {}", line)?;
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Payload sent by a [`WebhookNotifier`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WebhookFormat {
+    /// The [`Notification`] as JSON.
+    #[default]
+    Json,
+    /// A Slack incoming webhook message (`{"text": ...}`).
+    Slack,
+    /// A Discord webhook message (`{"content": ...}`).
+    Discord,
+}
+
+/// Posts notifications to a webhook URL.
+#[derive(Debug, Clone)]
+pub struct WebhookNotifier {
+    url: String,
+    format: WebhookFormat,
+    http: reqwest::Client,
+}
+
+impl WebhookNotifier {
+    /// Post JSON notifications to `url`.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            format: WebhookFormat::default(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Set the payload format.
+    pub fn format(mut self, format: WebhookFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    fn payload(&self, notification: &Notification) -> serde_json::Value {
+        let mut text = notification.text();
+        for line in &notification.details {
+            text.push_str("\n- ");
+            text.push_str(line);
+        }
+        match self.format {
+            WebhookFormat::Json => serde_json::to_value(notification).unwrap_or_default(),
+            WebhookFormat::Slack => serde_json::json!({ "text": text }),
+            WebhookFormat::Discord => serde_json::json!({ "content": text }),
+        }
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn notify<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let response = self
+                .http
+                .post(&self.url)
+                .json(&self.payload(notification))
+                .send()
+                .await
+                .map_err(|e| Error::Notify(format!("webhook request failed: {}", e)))?;
+            if !response.status().is_success() {
+                return Err(Error::Notify(format!(
+                    "webhook returned {}",
+                    response.status()
+                )));
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_webhook_payloads() {
+        let mut notification = Notification::new("backup", "12 decks backed up");
+        notification.details.push("Japanese: failed".to_string());
+
+        let slack = WebhookNotifier::new("http://localhost").format(WebhookFormat::Slack);
+        assert_eq!(
+            slack.payload(&notification)["text"],
+            "backup: 12 decks backed up\n- Japanese: failed"
+        );
+        let discord = WebhookNotifier::new("http://localhost").format(WebhookFormat::Discord);
+        assert!(discord.payload(&notification)["content"].is_string());
+        let json = WebhookNotifier::new("http://localhost");
+        assert_eq!(json.payload(&notification)["source"], "backup");
+    }
+
+    #[test]
+    fn test_failure_text() {
+        let notification = Notification::failure("leeches", "anki not running");
+        assert!(!notification.success);
+        assert_eq!(notification.text(), "leeches failed: anki not running");
+    }
+}
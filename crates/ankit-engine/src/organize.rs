@@ -2,12 +2,41 @@
 //!
 //! This module provides high-level workflows for deck cloning,
 //! merging, and tag-based reorganization.
+//!
+//! [`clone_deck`](OrganizeEngine::clone_deck) and
+//! [`merge_decks`](OrganizeEngine::merge_decks) flatten sub-decks into the
+//! destination. Their `_tree` counterparts keep the sub-deck structure, and
+//! [`move_deck_tree`](OrganizeEngine::move_deck_tree) moves a deck and its
+//! sub-decks under a new parent.
+//!
+//! Tag hierarchies (`lang::jp::verb`) can be renamed or re-parented with
+//! [`rename_tag_tree`](OrganizeEngine::rename_tag_tree) and
+//! [`move_tag_branch`](OrganizeEngine::move_tag_branch), and converted to and
+//! from deck hierarchies with
+//! [`deck_tree_to_tags`](OrganizeEngine::deck_tree_to_tags) and
+//! [`tag_tree_to_decks`](OrganizeEngine::tag_tree_to_decks).
+//!
+//! [`copy_to_profile`](OrganizeEngine::copy_to_profile) copies a deck into
+//! another Anki profile's collection.
+//!
+//! [`find_orphaned_notes`](OrganizeEngine::find_orphaned_notes) finds notes
+//! left without any cards, and
+//! [`resolve_orphaned_notes`](OrganizeEngine::resolve_orphaned_notes)
+//! deletes them or has Anki generate their cards again.
 
-use crate::{Error, NoteBuilder, Result};
-use ankit::AnkiClient;
+use crate::changes::PlannedChange;
+use crate::journal::{self, Journal};
+use crate::report::{WorkflowReport, report_fields};
+use crate::{EngineOptions, Error, NoteBuilder, Result};
+use ankit::render::{RenderContext, render_question};
+use ankit::{AnkiClient, CardInfo, DeckTree, TagTree};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 /// Report of a deck clone operation.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct CloneReport {
     /// Number of notes cloned.
     pub notes_cloned: usize,
@@ -15,10 +44,30 @@ pub struct CloneReport {
     pub notes_failed: usize,
     /// Name of the destination deck.
     pub destination: String,
+    /// Decks created, including sub-decks.
+    pub decks: Vec<String>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for CloneReport {
+    fn summary(&self) -> String {
+        format!(
+            "Cloned {} notes from {} decks into '{}' ({} failed)",
+            self.notes_cloned,
+            self.decks.len(),
+            self.destination,
+            self.notes_failed
+        )
+    }
+
+    report_fields!(dry_run);
 }
 
 /// Report of a deck merge operation.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct MergeReport {
     /// Number of cards moved.
     pub cards_moved: usize,
@@ -26,17 +75,37 @@ pub struct MergeReport {
     pub sources: Vec<String>,
     /// Destination deck.
     pub destination: String,
+    /// Undo journal recorded before moving cards, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for MergeReport {
+    fn summary(&self) -> String {
+        format!(
+            "Moved {} cards from {} decks into '{}'",
+            self.cards_moved,
+            self.sources.len(),
+            self.destination
+        )
+    }
+
+    report_fields!(dry_run, journal);
 }
 
 /// Organization workflow engine.
 #[derive(Debug)]
 pub struct OrganizeEngine<'a> {
     client: &'a AnkiClient,
+    options: &'a EngineOptions,
 }
 
 impl<'a> OrganizeEngine<'a> {
-    pub(crate) fn new(client: &'a AnkiClient) -> Self {
-        Self { client }
+    pub(crate) fn new(client: &'a AnkiClient, options: &'a EngineOptions) -> Self {
+        Self { client, options }
     }
 
     /// Clone a deck with all its notes.
@@ -67,19 +136,84 @@ impl<'a> OrganizeEngine<'a> {
             return Err(Error::DeckNotFound(source.to_string()));
         }
 
-        // Create destination deck
-        self.client.decks().create(destination).await?;
+        let mut report = CloneReport {
+            destination: destination.to_string(),
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        self.create_deck(destination, &mut report.planned).await?;
+        report.decks.push(destination.to_string());
 
         // Get all notes from source
         let query = format!("deck:\"{}\"", source);
         let note_ids = self.client.notes().find(&query).await?;
-        let note_infos = self.client.notes().info(&note_ids).await?;
+        self.clone_notes(&note_ids, destination, &mut report)
+            .await?;
+
+        Ok(report)
+    }
+
+    /// Clone a deck and its sub-decks, keeping the sub-deck structure.
+    ///
+    /// `Japanese::Vocab` is cloned to `destination::Vocab`, and so on. A note
+    /// with cards in several sub-decks is cloned once, into the highest of
+    /// them. Scheduling information is not preserved.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let report = engine.organize().clone_deck_tree("Japanese", "Japanese Copy").await?;
+    /// println!("Cloned {} notes into {} decks", report.notes_cloned, report.decks.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn clone_deck_tree(&self, source: &str, destination: &str) -> Result<CloneReport> {
+        let tree = self.client.decks().tree().await?;
+        if !tree.contains(source) {
+            return Err(Error::DeckNotFound(source.to_string()));
+        }
 
         let mut report = CloneReport {
             destination: destination.to_string(),
+            dry_run: self.options.dry_run,
             ..Default::default()
         };
 
+        let mut seen = HashSet::new();
+        for deck in tree.subtree(source) {
+            let target = DeckTree::rebase(deck, source, destination).unwrap_or_default();
+            self.create_deck(&target, &mut report.planned).await?;
+            report.decks.push(target.clone());
+
+            let note_ids: Vec<i64> = self
+                .client
+                .notes()
+                .find(&own_cards_query(deck))
+                .await?
+                .into_iter()
+                .filter(|id| seen.insert(*id))
+                .collect();
+            if !note_ids.is_empty() {
+                self.clone_notes(&note_ids, &target, &mut report).await?;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Copy notes into a deck, counting results in the report.
+    async fn clone_notes(
+        &self,
+        note_ids: &[i64],
+        destination: &str,
+        report: &mut CloneReport,
+    ) -> Result<()> {
+        let note_infos = self.client.notes().info(note_ids).await?;
+
         // Clone each note
         for info in note_infos {
             let mut builder = NoteBuilder::new(destination, &info.model_name);
@@ -93,13 +227,31 @@ impl<'a> OrganizeEngine<'a> {
             // Allow duplicates in the new deck
             let note = builder.allow_duplicate(true).build();
 
+            if report.dry_run {
+                report.planned.push(PlannedChange::AddNote { note });
+                report.notes_cloned += 1;
+                continue;
+            }
+
             match self.client.notes().add(note).await {
                 Ok(_) => report.notes_cloned += 1,
                 Err(_) => report.notes_failed += 1,
             }
         }
 
-        Ok(report)
+        Ok(())
+    }
+
+    /// Create a deck, or plan to in dry-run mode.
+    async fn create_deck(&self, deck: &str, planned: &mut Vec<PlannedChange>) -> Result<()> {
+        if self.options.dry_run {
+            planned.push(PlannedChange::CreateDeck {
+                deck: deck.to_string(),
+            });
+        } else {
+            self.client.decks().create(deck).await?;
+        }
+        Ok(())
     }
 
     /// Merge multiple decks into one.
@@ -125,34 +277,536 @@ impl<'a> OrganizeEngine<'a> {
     /// # }
     /// ```
     pub async fn merge_decks(&self, sources: &[&str], destination: &str) -> Result<MergeReport> {
-        // Create destination if it doesn't exist
-        self.client.decks().create(destination).await?;
-
         let mut report = MergeReport {
             destination: destination.to_string(),
             sources: sources.iter().map(|s| s.to_string()).collect(),
+            dry_run: self.options.dry_run,
             ..Default::default()
         };
 
-        // Move cards from each source
+        // Create destination if it doesn't exist
+        if report.dry_run {
+            report.planned.push(PlannedChange::CreateDeck {
+                deck: destination.to_string(),
+            });
+        } else {
+            self.client.decks().create(destination).await?;
+        }
+
+        // Collect cards from each source
+        let mut batches = Vec::new();
         for source in sources {
             let query = format!("deck:\"{}\"", source);
             let card_ids = self.client.cards().find(&query).await?;
 
             if !card_ids.is_empty() {
+                batches.push(card_ids);
+            }
+        }
+
+        if !report.dry_run {
+            if let Some(dir) = &self.options.journal_dir {
+                let all_cards: Vec<i64> = batches.iter().flatten().copied().collect();
+                if !all_cards.is_empty() {
+                    let mut record = Journal::new("merge_decks");
+                    record.entries = journal::record_decks(self.client, &all_cards).await?;
+                    report.journal = Some(record.write(dir)?);
+                }
+            }
+        }
+
+        // Move cards from each source
+        for card_ids in batches {
+            report.cards_moved += card_ids.len();
+            if report.dry_run {
+                report.planned.push(PlannedChange::MoveCards {
+                    card_ids,
+                    deck: destination.to_string(),
+                });
+            } else {
                 self.client
                     .decks()
                     .move_cards(&card_ids, destination)
                     .await?;
-                report.cards_moved += card_ids.len();
             }
         }
 
         Ok(report)
     }
 
+    /// Merge decks into one, keeping their sub-deck structure.
+    ///
+    /// Cards directly in each source go to `destination`; cards in
+    /// `source::Sub` go to `destination::Sub`, and so on. Sub-decks with the
+    /// same name in different sources end up merged. Does not delete the
+    /// source decks.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// // "Spanish 1::Verbs" and "Spanish 2::Verbs" both end up in "Spanish::Verbs"
+    /// let report = engine.organize()
+    ///     .merge_deck_trees(&["Spanish 1", "Spanish 2"], "Spanish")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn merge_deck_trees(
+        &self,
+        sources: &[&str],
+        destination: &str,
+    ) -> Result<MergeReport> {
+        let tree = self.client.decks().tree().await?;
+        if let Some(missing) = sources.iter().find(|s| !tree.contains(s)) {
+            return Err(Error::DeckNotFound(missing.to_string()));
+        }
+
+        let mut report = MergeReport {
+            destination: destination.to_string(),
+            sources: sources.iter().map(|s| s.to_string()).collect(),
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        let mut moves = Vec::new();
+        for source in sources {
+            for deck in tree.subtree(source) {
+                let target = DeckTree::rebase(deck, source, destination).unwrap_or_default();
+                let card_ids = self.client.cards().find(&own_cards_query(deck)).await?;
+                moves.push((target, card_ids));
+            }
+        }
+
+        report.journal = self.record_moves("merge_deck_trees", &moves).await?;
+        report.cards_moved = self.apply_moves(moves, &mut report.planned).await?;
+
+        Ok(report)
+    }
+
+    /// Move a deck and its sub-decks under a new parent.
+    ///
+    /// With `new_parent` of `Some("Archive")`, `Japanese::Vocab` becomes
+    /// `Archive::Vocab` and `Japanese::Vocab::N5` becomes
+    /// `Archive::Vocab::N5`. With `None`, the deck becomes top-level. If the
+    /// new name already exists, the decks are merged.
+    ///
+    /// AnkiConnect cannot rename decks, so cards are moved into the new decks
+    /// and the old decks are deleted once they are empty.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let report = engine.organize()
+    ///     .move_deck_tree("Japanese::Vocab", Some("Archive"))
+    ///     .await?;
+    /// for (old, new) in &report.renamed {
+    ///     println!("{} -> {}", old, new);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn move_deck_tree(
+        &self,
+        deck: &str,
+        new_parent: Option<&str>,
+    ) -> Result<MoveTreeReport> {
+        let tree = self.client.decks().tree().await?;
+        if !tree.contains(deck) {
+            return Err(Error::DeckNotFound(deck.to_string()));
+        }
+        if let Some(parent) = new_parent {
+            if DeckTree::rebase(parent, deck, deck).is_some() {
+                return Err(Error::Validation(format!(
+                    "cannot move deck '{}' under itself ('{}')",
+                    deck, parent
+                )));
+            }
+        }
+
+        let leaf = DeckTree::leaf_name(deck);
+        let new_root = match new_parent {
+            Some(parent) => format!("{}::{}", parent, leaf),
+            None => leaf.to_string(),
+        };
+
+        let mut report = MoveTreeReport {
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+        if new_root == deck {
+            return Ok(report);
+        }
+
+        let mut moves = Vec::new();
+        for old in tree.subtree(deck) {
+            let new = DeckTree::rebase(old, deck, &new_root).unwrap_or_default();
+            let card_ids = self.client.cards().find(&own_cards_query(old)).await?;
+            report.renamed.push((old.to_string(), new.clone()));
+            moves.push((new, card_ids));
+        }
+
+        report.journal = self.record_moves("move_deck_tree", &moves).await?;
+        report.cards_moved = self.apply_moves(moves, &mut report.planned).await?;
+
+        // Only delete the old tree if nothing was left behind in it
+        if report.dry_run {
+            report.planned.push(PlannedChange::DeleteDecks {
+                decks: vec![deck.to_string()],
+            });
+        } else {
+            let remaining = self
+                .client
+                .cards()
+                .find(&format!("deck:\"{}\"", deck))
+                .await?;
+            if remaining.is_empty() {
+                self.client.decks().delete(&[deck], true).await?;
+                report.source_removed = true;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Rename a tag and every tag below it, across the collection.
+    ///
+    /// `lang::jp` renamed to `japanese` turns `lang::jp::verb` into
+    /// `japanese::verb`. Matching is case-insensitive, like Anki's.
+    ///
+    /// Records the old tags in a journal when
+    /// [`EngineOptions::journal_dir`] is set.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let report = engine.organize().rename_tag_tree("lang::jp", "japanese").await?;
+    /// println!("Retagged {} notes", report.notes_updated);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn rename_tag_tree(&self, from: &str, to: &str) -> Result<RetagReport> {
+        self.retag("rename_tag_tree", None, from, to).await
+    }
+
+    /// Move the notes matching a query from one tag branch to another.
+    ///
+    /// Like [`rename_tag_tree`](Self::rename_tag_tree), but only notes
+    /// matching `query` are retagged; other notes keep their tags.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// // Verbs reviewed enough get promoted from "todo::" to "known::"
+    /// let report = engine.organize()
+    ///     .move_tag_branch("prop:ivl>=21", "todo", "known")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn move_tag_branch(&self, query: &str, from: &str, to: &str) -> Result<RetagReport> {
+        self.retag("move_tag_branch", Some(query), from, to).await
+    }
+
+    /// Replace the `from` tag branch with `to` on notes, optionally limited
+    /// to those matching a query.
+    async fn retag(
+        &self,
+        operation: &str,
+        query: Option<&str>,
+        from: &str,
+        to: &str,
+    ) -> Result<RetagReport> {
+        if to.is_empty() || to.contains(char::is_whitespace) {
+            return Err(Error::Validation(format!("invalid tag '{}'", to)));
+        }
+        // Removing a tag also removes its children, so the new branch can't
+        // live under the old one
+        if TagTree::rebase(to, from, from).is_some() {
+            return Err(Error::Validation(format!(
+                "cannot move tag '{}' under itself ('{}')",
+                from, to
+            )));
+        }
+
+        let mut report = RetagReport {
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        let mut search = tag_branch_query(from);
+        if let Some(query) = query {
+            search = format!("({}) {}", query, search);
+        }
+        let note_ids = self.client.notes().find(&search).await?;
+        if note_ids.is_empty() {
+            return Ok(report);
+        }
+
+        let mut renames: BTreeMap<(String, String), Vec<i64>> = BTreeMap::new();
+        let mut affected = Vec::new();
+        for note in self.client.notes().info(&note_ids).await? {
+            let mut changed = false;
+            for tag in &note.tags {
+                if let Some(new) = TagTree::rebase(tag, from, to) {
+                    renames
+                        .entry((tag.clone(), new))
+                        .or_default()
+                        .push(note.note_id);
+                    changed = true;
+                }
+            }
+            if changed {
+                affected.push(note.note_id);
+            }
+        }
+        report.notes_updated = affected.len();
+        report.renamed = renames.keys().cloned().collect();
+
+        if report.dry_run {
+            for ((old, new), note_ids) in renames {
+                report
+                    .planned
+                    .push(PlannedChange::ReplaceTags { note_ids, old, new });
+            }
+            return Ok(report);
+        }
+
+        if let Some(dir) = &self.options.journal_dir {
+            if !affected.is_empty() {
+                let mut record = Journal::new(operation);
+                record.entries = journal::record_tags(self.client, &affected).await?;
+                report.journal = Some(record.write(dir)?);
+            }
+        }
+
+        // Add every new tag before removing any old one
+        for ((_, new), note_ids) in &renames {
+            self.client.notes().add_tags(note_ids, new).await?;
+        }
+        for ((old, _), note_ids) in &renames {
+            self.client.notes().remove_tags(note_ids, old).await?;
+        }
+
+        Ok(report)
+    }
+
+    /// Tag notes after the deck hierarchy their cards are in.
+    ///
+    /// Notes with cards in `deck` get the tag `tag_root`, notes in
+    /// `deck::Sub` get `tag_root::Sub`, and so on. `tag_root` defaults to the
+    /// deck name. Spaces in deck names become underscores, since tags can't
+    /// contain spaces. Existing tags are kept.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// // Japanese::Vocab::N5 -> jp::Vocab::N5
+    /// let report = engine.organize().deck_tree_to_tags("Japanese", Some("jp")).await?;
+    /// println!("Tagged {} notes", report.notes_tagged);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn deck_tree_to_tags(
+        &self,
+        deck: &str,
+        tag_root: Option<&str>,
+    ) -> Result<DeckTagReport> {
+        let tree = self.client.decks().tree().await?;
+        if !tree.contains(deck) {
+            return Err(Error::DeckNotFound(deck.to_string()));
+        }
+        let root = tag_root.unwrap_or(deck);
+
+        let mut report = DeckTagReport {
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        let mut batches = Vec::new();
+        let mut affected = HashSet::new();
+        for sub_deck in tree.subtree(deck) {
+            let tag = DeckTree::rebase(sub_deck, deck, root)
+                .unwrap_or_default()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join("_");
+            let note_ids = self.client.notes().find(&own_cards_query(sub_deck)).await?;
+            if note_ids.is_empty() {
+                continue;
+            }
+            affected.extend(note_ids.iter().copied());
+            report
+                .tagged
+                .push((sub_deck.to_string(), tag.clone(), note_ids.len()));
+            batches.push((tag, note_ids));
+        }
+        report.notes_tagged = affected.len();
+
+        if report.dry_run {
+            for (tags, note_ids) in batches {
+                report
+                    .planned
+                    .push(PlannedChange::AddTags { note_ids, tags });
+            }
+            return Ok(report);
+        }
+
+        if let Some(dir) = &self.options.journal_dir {
+            if !affected.is_empty() {
+                let note_ids: Vec<i64> = affected.into_iter().collect();
+                let mut record = Journal::new("deck_tree_to_tags");
+                record.entries = journal::record_tags(self.client, &note_ids).await?;
+                report.journal = Some(record.write(dir)?);
+            }
+        }
+
+        for (tag, note_ids) in &batches {
+            self.client.notes().add_tags(note_ids, tag).await?;
+        }
+
+        Ok(report)
+    }
+
+    /// Move cards into a deck hierarchy that mirrors a tag hierarchy.
+    ///
+    /// Cards of notes tagged `tag` go to `deck_root`, notes tagged `tag::Sub`
+    /// to `deck_root::Sub`, and so on. `deck_root` defaults to the tag. A
+    /// note with several tags in the branch goes to the deepest one.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// // lang::jp::verb -> Japanese::verb
+    /// let report = engine.organize()
+    ///     .tag_tree_to_decks("lang::jp", Some("Japanese"))
+    ///     .await?;
+    /// for (tag, deck, count) in &report.moved {
+    ///     println!("{} -> {}: {} cards", tag, deck, count);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn tag_tree_to_decks(
+        &self,
+        tag: &str,
+        deck_root: Option<&str>,
+    ) -> Result<ReorganizeReport> {
+        let root = deck_root.unwrap_or(tag);
+        let mut report = ReorganizeReport {
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        let note_ids = self.client.notes().find(&tag_branch_query(tag)).await?;
+        if note_ids.is_empty() {
+            return Ok(report);
+        }
+
+        // Deck -> (tag it came from, cards); sorted so parents come first
+        let mut targets: BTreeMap<String, (String, Vec<i64>)> = BTreeMap::new();
+        for note in self.client.notes().info(&note_ids).await? {
+            let deepest = note
+                .tags
+                .iter()
+                .filter(|t| TagTree::rebase(t, tag, tag).is_some())
+                .max_by(|a, b| {
+                    TagTree::depth(a)
+                        .cmp(&TagTree::depth(b))
+                        .then_with(|| b.cmp(a))
+                });
+            let Some(deepest) = deepest else {
+                continue;
+            };
+            let deck = TagTree::rebase(deepest, tag, root).unwrap_or_default();
+            targets
+                .entry(deck)
+                .or_insert_with(|| (deepest.clone(), Vec::new()))
+                .1
+                .extend(note.cards);
+        }
+
+        let mut moves = Vec::new();
+        for (deck, (tag, card_ids)) in targets {
+            report.moved.push((tag, deck.clone(), card_ids.len()));
+            moves.push((deck, card_ids));
+        }
+
+        report.journal = self.record_moves("tag_tree_to_decks", &moves).await?;
+        self.apply_moves(moves, &mut report.planned).await?;
+
+        Ok(report)
+    }
+
+    /// Journal the current decks of cards about to be moved.
+    async fn record_moves(
+        &self,
+        operation: &str,
+        moves: &[(String, Vec<i64>)],
+    ) -> Result<Option<PathBuf>> {
+        let Some(dir) = &self.options.journal_dir else {
+            return Ok(None);
+        };
+        if self.options.dry_run {
+            return Ok(None);
+        }
+        let all_cards: Vec<i64> = moves.iter().flat_map(|(_, c)| c).copied().collect();
+        if all_cards.is_empty() {
+            return Ok(None);
+        }
+        let mut record = Journal::new(operation);
+        record.entries = journal::record_decks(self.client, &all_cards).await?;
+        Ok(Some(record.write(dir)?))
+    }
+
+    /// Create each target deck and move its cards in, parents first.
+    /// Returns the number of cards moved.
+    async fn apply_moves(
+        &self,
+        moves: Vec<(String, Vec<i64>)>,
+        planned: &mut Vec<PlannedChange>,
+    ) -> Result<usize> {
+        let mut created = HashSet::new();
+        let mut moved = 0;
+        for (deck, card_ids) in moves {
+            if created.insert(deck.clone()) {
+                self.create_deck(&deck, planned).await?;
+            }
+            if card_ids.is_empty() {
+                continue;
+            }
+            moved += card_ids.len();
+            if self.options.dry_run {
+                planned.push(PlannedChange::MoveCards { card_ids, deck });
+            } else {
+                self.client.decks().move_cards(&card_ids, &deck).await?;
+            }
+        }
+        Ok(moved)
+    }
+
     /// Move notes matching a tag to a different deck.
     ///
+    /// Returns the number of cards moved. In dry-run mode nothing is moved and
+    /// the number of cards that would be moved is returned.
+    ///
     /// # Arguments
     ///
     /// * `tag` - Tag to search for
@@ -172,13 +826,17 @@ impl<'a> OrganizeEngine<'a> {
     /// # }
     /// ```
     pub async fn move_by_tag(&self, tag: &str, destination: &str) -> Result<usize> {
-        // Create destination if needed
-        self.client.decks().create(destination).await?;
-
         // Find cards with tag
         let query = format!("tag:{}", tag);
         let card_ids = self.client.cards().find(&query).await?;
 
+        if self.options.dry_run {
+            return Ok(card_ids.len());
+        }
+
+        // Create destination if needed
+        self.client.decks().create(destination).await?;
+
         if !card_ids.is_empty() {
             self.client
                 .decks()
@@ -218,7 +876,10 @@ impl<'a> OrganizeEngine<'a> {
         parent_deck: &str,
         tags: &[&str],
     ) -> Result<ReorganizeReport> {
-        let mut report = ReorganizeReport::default();
+        let mut report = ReorganizeReport {
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
 
         for tag in tags {
             let subdeck = format!("{}::{}", parent_deck, tag);
@@ -228,21 +889,844 @@ impl<'a> OrganizeEngine<'a> {
             let card_ids = self.client.cards().find(&query).await?;
 
             if !card_ids.is_empty() {
-                self.client.decks().create(&subdeck).await?;
-                self.client.decks().move_cards(&card_ids, &subdeck).await?;
+                let count = card_ids.len();
+                if report.dry_run {
+                    report.planned.push(PlannedChange::CreateDeck {
+                        deck: subdeck.clone(),
+                    });
+                    report.planned.push(PlannedChange::MoveCards {
+                        card_ids,
+                        deck: subdeck.clone(),
+                    });
+                } else {
+                    self.client.decks().create(&subdeck).await?;
+                    self.client.decks().move_cards(&card_ids, &subdeck).await?;
+                }
+                report.moved.push((tag.to_string(), subdeck, count));
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Find cards whose front side is empty, like Anki's Empty Cards tool.
+    ///
+    /// A card is empty when its question shows none of the note's content:
+    /// for example the reverse card of a note whose optional "Add Reverse"
+    /// field is blank, or a cloze card whose deletion was removed from the
+    /// text. Templates are rendered locally with the note's fields and
+    /// compared with a rendering where every field is blank. A cloze card
+    /// is empty when no field contains its cloze number.
+    ///
+    /// This only reads the collection; see
+    /// [`remove_empty_cards`](Self::remove_empty_cards) to clean up.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let report = engine.organize().find_empty_cards("Japanese").await?;
+    /// for card in &report.empty_cards {
+    ///     println!("{} ({}): {}", card.card_id, card.model_name, card.template);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn find_empty_cards(&self, deck: &str) -> Result<EmptyCardsReport> {
+        let mut report = EmptyCardsReport {
+            deck: deck.to_string(),
+            ..Default::default()
+        };
+
+        let card_ids = self
+            .client
+            .cards()
+            .find(&format!("deck:\"{}\"", deck))
+            .await?;
+        if card_ids.is_empty() {
+            return Ok(report);
+        }
+        let cards = self.client.cards().info(&card_ids).await?;
+        report.cards_checked = cards.len();
+
+        let mut model_names: Vec<&str> = cards.iter().map(|c| c.model_name.as_str()).collect();
+        model_names.sort_unstable();
+        model_names.dedup();
+        let mut layouts = HashMap::new();
+        for raw in self.client.models().find_by_name(&model_names).await? {
+            let layout: TemplateLayout = serde_json::from_value(raw).map_err(ankit::Error::from)?;
+            layouts.insert(layout.name.clone(), layout);
+        }
+
+        for card in &cards {
+            let Some(layout) = layouts.get(&card.model_name) else {
+                continue;
+            };
+            if let Some(template) = layout.empty_card_template(card) {
+                report.empty_cards.push(EmptyCard {
+                    card_id: card.card_id,
+                    note_id: card.note_id,
+                    model_name: card.model_name.clone(),
+                    template,
+                });
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Find and clean up cards whose front side is empty.
+    ///
+    /// Uses the same check as [`find_empty_cards`](Self::find_empty_cards).
+    /// Notes whose every card is empty are deleted. AnkiConnect can't delete
+    /// single cards, so the remaining empty cards are suspended instead;
+    /// Anki's Tools > Empty Cards removes them for good.
+    ///
+    /// Respects [`EngineOptions::dry_run`] and records an undo journal of
+    /// the deleted notes and suspended cards when
+    /// [`EngineOptions::journal_dir`] is set.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let report = engine.organize().remove_empty_cards("Japanese").await?;
+    /// println!(
+    ///     "Deleted {} notes, suspended {} cards",
+    ///     report.notes_deleted, report.cards_suspended
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn remove_empty_cards(&self, deck: &str) -> Result<EmptyCardsReport> {
+        let mut report = self.find_empty_cards(deck).await?;
+        report.dry_run = self.options.dry_run;
+        if report.empty_cards.is_empty() {
+            return Ok(report);
+        }
+
+        let empty: HashSet<i64> = report.empty_cards.iter().map(|c| c.card_id).collect();
+        let mut note_ids: Vec<i64> = report.empty_cards.iter().map(|c| c.note_id).collect();
+        note_ids.sort_unstable();
+        note_ids.dedup();
+
+        // A note goes only if all of its cards, in any deck, are empty
+        let mut delete = Vec::new();
+        let mut deleted_cards = HashSet::new();
+        for note in self.client.notes().info(&note_ids).await? {
+            if !note.cards.is_empty() && note.cards.iter().all(|id| empty.contains(id)) {
+                delete.push(note.note_id);
+                deleted_cards.extend(note.cards);
+            }
+        }
+        let suspend: Vec<i64> = report
+            .empty_cards
+            .iter()
+            .map(|c| c.card_id)
+            .filter(|id| !deleted_cards.contains(id))
+            .collect();
+
+        report.notes_deleted = delete.len();
+        report.cards_suspended = suspend.len();
+        if report.dry_run {
+            if !delete.is_empty() {
                 report
-                    .moved
-                    .push((tag.to_string(), subdeck, card_ids.len()));
+                    .planned
+                    .push(PlannedChange::DeleteNotes { note_ids: delete });
+            }
+            if !suspend.is_empty() {
+                report
+                    .planned
+                    .push(PlannedChange::SuspendCards { card_ids: suspend });
+            }
+            return Ok(report);
+        }
+
+        if let Some(dir) = &self.options.journal_dir {
+            let mut record = Journal::new("remove_empty_cards");
+            if !delete.is_empty() {
+                record.entries = journal::record_notes(self.client, &delete).await?;
+            }
+            if !suspend.is_empty() {
+                record
+                    .entries
+                    .extend(journal::record_scheduling(self.client, &suspend).await?);
+            }
+            report.journal = Some(record.write(dir)?);
+        }
+
+        if !delete.is_empty() {
+            self.client.notes().delete(&delete).await?;
+        }
+        if !suspend.is_empty() {
+            self.client.cards().suspend(&suspend).await?;
+        }
+
+        Ok(report)
+    }
+
+    /// Find notes that have no cards.
+    ///
+    /// A note is orphaned when all of its cards were deleted, or when its
+    /// templates never generated any. Anki can't show such notes for
+    /// review, and they only surface in Tools > Check Database.
+    ///
+    /// Searches on card properties such as `deck:` or `is:due` only match
+    /// notes that still have cards, so select notes by note type, tag, or
+    /// field instead, e.g. `note:Basic` or `tag:imported`.
+    ///
+    /// This only reads the collection; see
+    /// [`resolve_orphaned_notes`](Self::resolve_orphaned_notes) to clean up.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let report = engine.organize().find_orphaned_notes("note:Basic").await?;
+    /// for note in &report.orphaned {
+    ///     println!("{} ({})", note.note_id, note.model_name);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn find_orphaned_notes(&self, query: &str) -> Result<OrphanedNotesReport> {
+        let mut report = OrphanedNotesReport {
+            query: query.to_string(),
+            ..Default::default()
+        };
+
+        let note_ids = self.client.notes().find(query).await?;
+        if note_ids.is_empty() {
+            return Ok(report);
+        }
+        let notes = self.client.notes().info(&note_ids).await?;
+        report.notes_checked = notes.len();
+
+        report.orphaned = notes
+            .into_iter()
+            .filter(|note| note.cards.is_empty())
+            .map(|note| OrphanedNote {
+                note_id: note.note_id,
+                model_name: note.model_name,
+                fields: note
+                    .fields
+                    .into_iter()
+                    .map(|(name, field)| (name, field.value))
+                    .collect(),
+                tags: note.tags,
+            })
+            .collect();
+
+        Ok(report)
+    }
+
+    /// Find notes that have no cards and delete or regenerate them.
+    ///
+    /// Uses the same check as
+    /// [`find_orphaned_notes`](Self::find_orphaned_notes).
+    /// [`OrphanAction::Regenerate`] adds each note again, which makes Anki
+    /// generate its cards, and then deletes the original; the new notes get
+    /// new IDs. Notes whose templates still produce no cards can't be added
+    /// and are left in place, counted in
+    /// [`notes_failed`](OrphanedNotesReport::notes_failed).
+    ///
+    /// Respects [`EngineOptions::dry_run`] and records an undo journal of
+    /// the deleted notes when [`EngineOptions::journal_dir`] is set.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # use ankit_engine::organize::OrphanAction;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let action = OrphanAction::Regenerate {
+    ///     deck: "Recovered".to_string(),
+    /// };
+    /// let report = engine
+    ///     .organize()
+    ///     .resolve_orphaned_notes("note:Basic", &action)
+    ///     .await?;
+    /// println!("Regenerated {} notes", report.notes_regenerated);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn resolve_orphaned_notes(
+        &self,
+        query: &str,
+        action: &OrphanAction,
+    ) -> Result<OrphanedNotesReport> {
+        let mut report = self.find_orphaned_notes(query).await?;
+        report.dry_run = self.options.dry_run;
+        if report.orphaned.is_empty() {
+            return Ok(report);
+        }
+
+        let mut delete = Vec::new();
+        match action {
+            OrphanAction::Delete => {
+                delete.extend(report.orphaned.iter().map(|note| note.note_id));
+                report.notes_deleted = delete.len();
+            }
+            OrphanAction::Regenerate { deck } => {
+                self.create_deck(deck, &mut report.planned).await?;
+                for note in &report.orphaned {
+                    let note_id = note.note_id;
+                    if report.dry_run {
+                        let mut builder =
+                            NoteBuilder::new(deck, &note.model_name).tags(note.tags.clone());
+                        for (name, value) in &note.fields {
+                            builder = builder.field(name, value);
+                        }
+                        let note = builder.allow_duplicate(true).build();
+                        report.planned.push(PlannedChange::AddNote { note });
+                        report.notes_regenerated += 1;
+                        delete.push(note_id);
+                        continue;
+                    }
+
+                    match journal::recreate_note(
+                        self.client,
+                        deck,
+                        &note.model_name,
+                        note.fields.clone(),
+                        note.tags.clone(),
+                    )
+                    .await
+                    {
+                        Ok(_) => {
+                            report.notes_regenerated += 1;
+                            delete.push(note_id);
+                        }
+                        Err(_) => report.notes_failed += 1,
+                    }
+                }
             }
         }
 
+        if delete.is_empty() {
+            return Ok(report);
+        }
+        if report.dry_run {
+            report
+                .planned
+                .push(PlannedChange::DeleteNotes { note_ids: delete });
+            return Ok(report);
+        }
+
+        if let Some(dir) = &self.options.journal_dir {
+            let mut record = Journal::new("resolve_orphaned_notes");
+            record.entries = journal::record_notes(self.client, &delete).await?;
+            report.journal = Some(record.write(dir)?);
+        }
+        self.client.notes().delete(&delete).await?;
+
+        Ok(report)
+    }
+
+    /// Copy a deck, with its note types and media, into another profile.
+    ///
+    /// Exports the deck (including sub-decks) to a temporary `.apkg` file,
+    /// loads `target_profile`, imports the package, and loads the current
+    /// profile again, even if the import fails. Anki creates any note types
+    /// the target collection is missing; notes already there from an earlier
+    /// copy are not duplicated. Scheduling information is not copied, so the
+    /// cards start as new.
+    ///
+    /// Anki writes and reads the package itself, so this needs Anki to run
+    /// on the same machine. In dry-run mode nothing is exported and no
+    /// profile is loaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::DeckNotFound`] if the deck doesn't exist,
+    /// [`Error::ProfileNotFound`] if no profile has that name, and
+    /// [`Error::Validation`] if it is the current profile.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let report = engine.organize().copy_to_profile("Japanese", "Work").await?;
+    /// println!("{} notes now in Work", report.notes_imported);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn copy_to_profile(
+        &self,
+        deck: &str,
+        target_profile: &str,
+    ) -> Result<ProfileCopyReport> {
+        let misc = self.client.misc();
+        let profiles = misc.profiles().await?;
+        if !profiles.iter().any(|p| p == target_profile) {
+            return Err(Error::ProfileNotFound(target_profile.to_string()));
+        }
+        let source_profile = misc.active_profile().await?;
+        if source_profile == target_profile {
+            return Err(Error::Validation(format!(
+                "'{}' is already the current profile",
+                target_profile
+            )));
+        }
+        let decks = self.client.decks().names().await?;
+        if !decks.iter().any(|d| d == deck) {
+            return Err(Error::DeckNotFound(deck.to_string()));
+        }
+
+        let query = format!("deck:\"{}\"", deck);
+        let mut report = ProfileCopyReport {
+            deck: deck.to_string(),
+            source_profile,
+            target_profile: target_profile.to_string(),
+            notes_exported: self.client.notes().find(&query).await?.len(),
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        if report.dry_run {
+            report.planned.push(PlannedChange::CopyDeckToProfile {
+                deck: deck.to_string(),
+                profile: target_profile.to_string(),
+                notes: report.notes_exported,
+            });
+            return Ok(report);
+        }
+
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let package = std::env::temp_dir().join(format!(
+            "ankit-copy-{}-{}.apkg",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let path = package.to_string_lossy().into_owned();
+        if !misc.export_package(deck, &path, None).await? {
+            return Err(Error::Validation(format!(
+                "Anki could not export deck '{}'",
+                deck
+            )));
+        }
+
+        let imported = match self.load_profile(target_profile).await {
+            Ok(()) => {
+                let imported = self.import_into_current(&path, &query).await;
+                let restored = self.load_profile(&report.source_profile).await;
+                imported.and_then(|counts| restored.map(|()| counts))
+            }
+            Err(e) => Err(e),
+        };
+        let _ = std::fs::remove_file(&package);
+
+        let (notes_imported, models_created) = imported?;
+        report.notes_imported = notes_imported;
+        report.models_created = models_created;
         Ok(report)
     }
+
+    /// Import a package into the loaded profile, returning how many notes
+    /// matching `query` it added and which note types it created.
+    async fn import_into_current(&self, path: &str, query: &str) -> Result<(usize, Vec<String>)> {
+        let models_before: HashSet<String> =
+            self.client.models().names().await?.into_iter().collect();
+        let notes_before = self.client.notes().find(query).await?.len();
+
+        if !self.client.misc().import_package(path).await? {
+            return Err(Error::Validation(format!(
+                "Anki could not import package '{}'",
+                path
+            )));
+        }
+
+        let notes_after = self.client.notes().find(query).await?.len();
+        let mut models_created: Vec<String> = self
+            .client
+            .models()
+            .names()
+            .await?
+            .into_iter()
+            .filter(|name| !models_before.contains(name))
+            .collect();
+        models_created.sort();
+        Ok((notes_after.saturating_sub(notes_before), models_created))
+    }
+
+    /// Load a profile, failing if Anki refuses.
+    async fn load_profile(&self, profile: &str) -> Result<()> {
+        if self.client.misc().load_profile(profile).await? {
+            Ok(())
+        } else {
+            Err(Error::Validation(format!(
+                "Anki could not load profile '{}'",
+                profile
+            )))
+        }
+    }
 }
 
 /// Report of a reorganization operation.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct ReorganizeReport {
     /// List of (tag, destination deck, card count) for each reorganization.
     pub moved: Vec<(String, String, usize)>,
+    /// Undo journal recorded before moving cards, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for ReorganizeReport {
+    fn summary(&self) -> String {
+        let cards: usize = self.moved.iter().map(|(_, _, count)| count).sum();
+        format!("Moved {} cards for {} tags", cards, self.moved.len())
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.moved
+            .iter()
+            .map(|(tag, deck, count)| format!("{} -> {}: {} cards", tag, deck, count))
+            .collect()
+    }
+
+    report_fields!(dry_run, journal);
+}
+
+/// Report of moving a deck tree under a new parent.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MoveTreeReport {
+    /// (old name, new name) for each deck in the moved tree.
+    pub renamed: Vec<(String, String)>,
+    /// Number of cards moved.
+    pub cards_moved: usize,
+    /// Whether the old decks were deleted after being emptied.
+    pub source_removed: bool,
+    /// Undo journal recorded before moving cards, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for MoveTreeReport {
+    fn summary(&self) -> String {
+        format!(
+            "Renamed {} decks, moving {} cards",
+            self.renamed.len(),
+            self.cards_moved
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.renamed
+            .iter()
+            .map(|(old, new)| format!("{} -> {}", old, new))
+            .collect()
+    }
+
+    report_fields!(dry_run, journal);
+}
+
+/// Report of renaming or moving a tag branch.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RetagReport {
+    /// Number of notes whose tags changed.
+    pub notes_updated: usize,
+    /// (old tag, new tag) for each tag that was replaced.
+    pub renamed: Vec<(String, String)>,
+    /// Undo journal recorded before retagging, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for RetagReport {
+    fn summary(&self) -> String {
+        format!(
+            "Renamed {} tags on {} notes",
+            self.renamed.len(),
+            self.notes_updated
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.renamed
+            .iter()
+            .map(|(old, new)| format!("{} -> {}", old, new))
+            .collect()
+    }
+
+    report_fields!(dry_run, journal);
+}
+
+/// Report of tagging notes after their deck hierarchy.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DeckTagReport {
+    /// (deck, tag added, note count) for each deck with notes.
+    pub tagged: Vec<(String, String, usize)>,
+    /// Number of distinct notes tagged.
+    pub notes_tagged: usize,
+    /// Undo journal recorded before tagging, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for DeckTagReport {
+    fn summary(&self) -> String {
+        format!(
+            "Tagged {} notes in {} decks",
+            self.notes_tagged,
+            self.tagged.len()
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.tagged
+            .iter()
+            .map(|(deck, tag, count)| format!("{} -> {}: {} notes", deck, tag, count))
+            .collect()
+    }
+
+    report_fields!(dry_run, journal);
+}
+
+/// Report of finding or removing cards with an empty front side.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EmptyCardsReport {
+    /// Deck that was checked.
+    pub deck: String,
+    /// Number of cards checked.
+    pub cards_checked: usize,
+    /// Cards whose front side is empty.
+    pub empty_cards: Vec<EmptyCard>,
+    /// Number of notes deleted because all of their cards were empty.
+    pub notes_deleted: usize,
+    /// Number of empty cards suspended.
+    pub cards_suspended: usize,
+    /// Undo journal recorded before the cleanup, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for EmptyCardsReport {
+    fn summary(&self) -> String {
+        format!(
+            "Found {} empty cards in {} checked; deleted {} notes, suspended {} cards",
+            self.empty_cards.len(),
+            self.cards_checked,
+            self.notes_deleted,
+            self.cards_suspended
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.empty_cards
+            .iter()
+            .map(|card| {
+                format!(
+                    "card {} of note {}: {} ({})",
+                    card.card_id, card.note_id, card.template, card.model_name
+                )
+            })
+            .collect()
+    }
+
+    report_fields!(dry_run, journal);
+}
+
+/// A card whose front side shows none of its note's content.
+#[derive(Debug, Clone, Serialize)]
+pub struct EmptyCard {
+    /// The card ID.
+    pub card_id: i64,
+    /// The note the card belongs to.
+    pub note_id: i64,
+    /// Note type name.
+    pub model_name: String,
+    /// Template that generated the card, or `Cloze N` for cloze cards.
+    pub template: String,
+}
+
+/// What to do with notes that have no cards.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum OrphanAction {
+    /// Delete the notes.
+    Delete,
+    /// Add each note again so Anki generates its cards, then delete the
+    /// original.
+    Regenerate {
+        /// Deck for the new cards, created if needed.
+        deck: String,
+    },
+}
+
+/// Report of finding or resolving notes that have no cards.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OrphanedNotesReport {
+    /// Query used to select notes.
+    pub query: String,
+    /// Number of notes checked.
+    pub notes_checked: usize,
+    /// Notes without any cards.
+    pub orphaned: Vec<OrphanedNote>,
+    /// Number of orphaned notes deleted.
+    pub notes_deleted: usize,
+    /// Number of orphaned notes added again with cards.
+    pub notes_regenerated: usize,
+    /// Number of orphaned notes that could not be regenerated.
+    pub notes_failed: usize,
+    /// Undo journal recorded before the cleanup, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for OrphanedNotesReport {
+    fn summary(&self) -> String {
+        format!(
+            "Found {} orphaned notes in {} checked; deleted {}, regenerated {}, failed {}",
+            self.orphaned.len(),
+            self.notes_checked,
+            self.notes_deleted,
+            self.notes_regenerated,
+            self.notes_failed
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.orphaned
+            .iter()
+            .map(|note| format!("note {} ({})", note.note_id, note.model_name))
+            .collect()
+    }
+
+    report_fields!(dry_run, journal);
+}
+
+/// A note that has no cards.
+#[derive(Debug, Clone, Serialize)]
+pub struct OrphanedNote {
+    /// The note ID.
+    pub note_id: i64,
+    /// Note type name.
+    pub model_name: String,
+    /// Field values, keyed by field name.
+    pub fields: HashMap<String, String>,
+    /// Tags on the note.
+    pub tags: Vec<String>,
+}
+
+/// Report of copying a deck into another profile.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProfileCopyReport {
+    /// The deck copied.
+    pub deck: String,
+    /// Profile the deck was copied from.
+    pub source_profile: String,
+    /// Profile the deck was copied into.
+    pub target_profile: String,
+    /// Number of notes in the exported deck.
+    pub notes_exported: usize,
+    /// Number of notes added to the target profile.
+    ///
+    /// Lower than `notes_exported` when some notes were already there.
+    pub notes_imported: usize,
+    /// Note types the import created in the target profile.
+    pub models_created: Vec<String>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for ProfileCopyReport {
+    fn summary(&self) -> String {
+        format!(
+            "Copied '{}' from profile '{}' to '{}': {} of {} notes added",
+            self.deck,
+            self.source_profile,
+            self.target_profile,
+            self.notes_imported,
+            self.notes_exported
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.models_created
+            .iter()
+            .map(|model| format!("created note type '{}'", model))
+            .collect()
+    }
+
+    report_fields!(dry_run);
+}
+
+/// The parts of a note type definition needed to check for empty cards.
+#[derive(Debug, Deserialize)]
+struct TemplateLayout {
+    name: String,
+    /// 1 for cloze note types.
+    #[serde(rename = "type", default)]
+    kind: i32,
+    #[serde(default)]
+    tmpls: Vec<LayoutTemplate>,
+}
+
+/// A card template from a note type definition.
+#[derive(Debug, Deserialize)]
+struct LayoutTemplate {
+    name: String,
+    ord: i32,
+    qfmt: String,
+}
+
+impl TemplateLayout {
+    /// The name of the card's template if its front side is empty.
+    fn empty_card_template(&self, card: &CardInfo) -> Option<String> {
+        if self.kind == 1 {
+            let marker = format!("{{{{c{}::", card.ord + 1);
+            return (!card.fields.values().any(|f| f.value.contains(&marker)))
+                .then(|| format!("Cloze {}", card.ord + 1));
+        }
+
+        let template = self.tmpls.iter().find(|t| t.ord == card.ord)?;
+        let filled = RenderContext::new(
+            card.fields
+                .iter()
+                .map(|(name, field)| (name.as_str(), field.value.as_str())),
+        );
+        let blank = RenderContext::new(card.fields.keys().map(|name| (name.as_str(), "")));
+        (render_question(&template.qfmt, &filled) == render_question(&template.qfmt, &blank))
+            .then(|| template.name.clone())
+    }
+}
+
+/// Search for notes with a tag or any tag below it.
+fn tag_branch_query(tag: &str) -> String {
+    format!("(tag:\"{}\" OR tag:\"{}::*\")", tag, tag)
+}
+
+/// Search for the cards directly in a deck, excluding its sub-decks.
+fn own_cards_query(deck: &str) -> String {
+    format!("deck:\"{}\" -deck:\"{}::*\"", deck, deck)
 }
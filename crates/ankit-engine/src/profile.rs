@@ -0,0 +1,104 @@
+//! Running workflows against a specific Anki profile.
+//!
+//! Each Anki profile has its own collection, and AnkiConnect always works on
+//! whichever one is loaded. [`Engine::with_profile`] loads a profile, runs a
+//! set of operations, and loads the previous profile again afterwards, so a
+//! script can touch a work and a personal collection without leaving Anki in
+//! the wrong one.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ankit_engine::Engine;
+//!
+//! # async fn example() -> ankit_engine::Result<()> {
+//! let engine = Engine::new();
+//!
+//! let due = engine
+//!     .with_profile("Work")
+//!     .run(async |engine| {
+//!         let ids = engine.client().cards().find("is:due").await?;
+//!         Ok(ids.len())
+//!     })
+//!     .await?;
+//! println!("{} cards due at work", due);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{Engine, Error, Result};
+
+/// Runs operations with a profile loaded. Created by [`Engine::with_profile`].
+#[derive(Debug)]
+pub struct ProfileGuard<'a> {
+    engine: &'a Engine,
+    profile: String,
+    switch_back: bool,
+}
+
+impl<'a> ProfileGuard<'a> {
+    pub(crate) fn new(engine: &'a Engine, profile: String) -> Self {
+        Self {
+            engine,
+            profile,
+            switch_back: true,
+        }
+    }
+
+    /// Whether to load the previous profile again when done. Defaults to
+    /// `true`.
+    pub fn switch_back(mut self, switch_back: bool) -> Self {
+        self.switch_back = switch_back;
+        self
+    }
+
+    /// Load the profile, run `operations`, and restore the previous profile.
+    ///
+    /// The profile is switched even in dry-run mode, since the operations
+    /// need to read its collection. The previous profile is restored whether
+    /// or not the operations succeed; if both fail, the operations' error is
+    /// returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ProfileNotFound`] if no profile has this name, before
+    /// anything is switched.
+    pub async fn run<T, F>(self, operations: F) -> Result<T>
+    where
+        F: AsyncFnOnce(&Engine) -> Result<T>,
+    {
+        let misc = self.engine.client().misc();
+        let profiles = misc.profiles().await?;
+        if !profiles.contains(&self.profile) {
+            return Err(Error::ProfileNotFound(self.profile));
+        }
+
+        let previous = misc.active_profile().await?;
+        let switched = previous != self.profile;
+        if switched {
+            self.load(&self.profile).await?;
+        }
+
+        let result = operations(self.engine).await;
+
+        if switched && self.switch_back {
+            let restored = self.load(&previous).await;
+            if result.is_ok() {
+                restored?;
+            }
+        }
+        result
+    }
+
+    /// Load a profile, failing if Anki refuses.
+    async fn load(&self, profile: &str) -> Result<()> {
+        if self.engine.client().misc().load_profile(profile).await? {
+            Ok(())
+        } else {
+            Err(Error::Validation(format!(
+                "Anki could not load profile '{}'",
+                profile
+            )))
+        }
+    }
+}
@@ -1,12 +1,23 @@
 //! Progress management and card state operations.
 //!
 //! This module provides workflows for managing card progress, including
-//! resetting progress, tagging cards by performance, and bulk tag operations.
-
-use std::collections::HashSet;
-
-use crate::Result;
-use ankit::AnkiClient;
+//! resetting progress, tagging cards by performance, bulk tag operations,
+//! ordering new cards by word frequency, and smoothing review workload.
+//!
+//! [`suspend_by_tag`](ProgressEngine::suspend_by_tag) and
+//! [`unsuspend_by_tag`](ProgressEngine::unsuspend_by_tag) remember which
+//! cards were already suspended, so unsuspending restores the prior state.
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+use crate::changes::PlannedChange;
+use crate::journal::{self, Journal};
+use crate::normalize::{NormalizeOptions, normalize};
+use crate::report::{AffectedIds, WorkflowReport, report_fields};
+use crate::similarity::{Algorithm, Prefilter, pairs};
+use crate::{EngineOptions, Error, Result};
+use ankit::{AnkiClient, CardAnswer, DueSpec, Ease};
 use serde::Serialize;
 
 /// Report from resetting deck progress.
@@ -16,6 +27,20 @@ pub struct ResetReport {
     pub cards_reset: usize,
     /// Deck that was reset.
     pub deck: String,
+    /// Undo journal recorded before the reset, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for ResetReport {
+    fn summary(&self) -> String {
+        format!("Reset {} cards in '{}'", self.cards_reset, self.deck)
+    }
+
+    report_fields!(dry_run, journal);
 }
 
 /// Criteria for categorizing card performance.
@@ -53,6 +78,21 @@ pub struct TagReport {
     pub struggling_tag: String,
     /// Tag used for mastered cards.
     pub mastered_tag: String,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for TagReport {
+    fn summary(&self) -> String {
+        format!(
+            "Tagged {} struggling notes '{}' and {} mastered notes '{}'",
+            self.struggling_count, self.struggling_tag, self.mastered_count, self.mastered_tag
+        )
+    }
+
+    report_fields!(dry_run);
 }
 
 /// Criteria for suspending cards.
@@ -83,6 +123,26 @@ pub struct SuspendReport {
     pub cards_suspended: usize,
     /// Card IDs that were suspended.
     pub suspended_ids: Vec<i64>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for SuspendReport {
+    fn summary(&self) -> String {
+        format!("Suspended {} cards", self.cards_suspended)
+    }
+
+    fn affected(&self) -> AffectedIds {
+        AffectedIds {
+            note_ids: Vec::new(),
+            card_ids: self.suspended_ids.clone(),
+        }
+        .normalize()
+    }
+
+    report_fields!(dry_run);
 }
 
 /// Comprehensive health report for a deck.
@@ -114,6 +174,23 @@ pub struct HealthReport {
     pub total_reps: i64,
 }
 
+impl WorkflowReport for HealthReport {
+    fn summary(&self) -> String {
+        format!(
+            "'{}': {} cards ({} new, {} learning, {} review, {} suspended), {} leeches",
+            self.deck,
+            self.total_cards,
+            self.new_cards,
+            self.learning_cards,
+            self.review_cards,
+            self.suspended_cards,
+            self.leech_count
+        )
+    }
+
+    report_fields!();
+}
+
 /// Tag operation to perform.
 #[derive(Debug, Clone)]
 pub enum TagOperation {
@@ -132,6 +209,18 @@ pub struct BulkTagReport {
     pub notes_affected: usize,
     /// Operation performed.
     pub operation: String,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for BulkTagReport {
+    fn summary(&self) -> String {
+        format!("{}: {} notes", self.operation, self.notes_affected)
+    }
+
+    report_fields!(dry_run);
 }
 
 /// Criteria for smart suspension based on content similarity.
@@ -141,9 +230,16 @@ pub struct SimilarityCriteria {
     pub threshold: f64,
     /// Field to compare for similarity.
     pub field: String,
+    /// How field values are scored. Case is ignored.
+    pub algorithm: Algorithm,
+    /// Filter for candidate pairs when checking many cards. By default every
+    /// pair is scored.
+    pub prefilter: Prefilter,
     /// Strategy for which card to keep in each similar group.
     pub keep_strategy: KeepStrategy,
     /// If true, don't actually suspend - just report what would be suspended.
+    ///
+    /// Engine-level dry runs ([`EngineOptions::dry_run`]) also apply.
     pub dry_run: bool,
 }
 
@@ -152,6 +248,8 @@ impl Default for SimilarityCriteria {
         Self {
             threshold: 0.85,
             field: "Front".to_string(),
+            algorithm: Algorithm::default(),
+            prefilter: Prefilter::default(),
             keep_strategy: KeepStrategy::MostMature,
             dry_run: false,
         }
@@ -200,17 +298,566 @@ pub struct SmartSuspendReport {
     pub groups: Vec<SimilarGroup>,
     /// Whether this was a dry run.
     pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for SmartSuspendReport {
+    fn summary(&self) -> String {
+        format!(
+            "Suspended {} of {} cards in {} similar groups",
+            self.cards_suspended, self.cards_analyzed, self.groups_found
+        )
+    }
+
+    fn affected(&self) -> AffectedIds {
+        let mut ids = AffectedIds::from_planned(&self.planned);
+        ids.card_ids.extend(
+            self.groups
+                .iter()
+                .flat_map(|group| group.suspend.iter().copied()),
+        );
+        ids.normalize()
+    }
+
+    report_fields!(dry_run);
+}
+
+/// A review decision to replay, such as one exported from another SRS tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ReviewDecision {
+    /// Card that was reviewed.
+    pub card_id: i64,
+    /// Answer given.
+    pub ease: Ease,
+    /// When the review happened, in milliseconds since the Unix epoch.
+    pub reviewed_at: i64,
+}
+
+impl ReviewDecision {
+    /// Create a new review decision.
+    pub fn new(card_id: i64, ease: Ease, reviewed_at: i64) -> Self {
+        Self {
+            card_id,
+            ease,
+            reviewed_at,
+        }
+    }
+}
+
+/// Report from replaying review decisions.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReplayReport {
+    /// Number of review decisions submitted.
+    pub submitted: usize,
+    /// Number of reviews Anki recorded.
+    pub answered: usize,
+    /// Card IDs of reviews that could not be recorded, in replay order.
+    pub failed: Vec<i64>,
+    /// Undo journal recorded before replaying, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for ReplayReport {
+    fn summary(&self) -> String {
+        format!(
+            "Answered {} of {} reviews ({} failed)",
+            self.answered,
+            self.submitted,
+            self.failed.len()
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.failed
+            .iter()
+            .map(|card_id| format!("card {} was not answered", card_id))
+            .collect()
+    }
+
+    report_fields!(dry_run, journal);
+}
+
+/// A word-frequency list, most frequent words first.
+///
+/// Lists are plain text with one word per line. Columns may be separated by
+/// tabs, commas, or spaces; the first non-numeric column is the word. If every
+/// line has a count after the word (`the\t23135851`), words are ranked by
+/// count. Otherwise, including rank-first lists (`1 the`), they are ranked by
+/// line order. Blank lines and lines starting with `#` are ignored.
+///
+/// Words are matched case-insensitively.
+///
+/// # Example
+///
+/// ```
+/// use ankit_engine::progress::FrequencyList;
+///
+/// let list = FrequencyList::parse("de\t120\nla\t300\ncasa\t40\n");
+/// assert_eq!(list.rank("la"), Some(0));
+/// assert_eq!(list.rank("Casa"), Some(2));
+/// assert_eq!(list.rank("perro"), None);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FrequencyList {
+    ranks: HashMap<String, usize>,
+}
+
+impl FrequencyList {
+    /// Build a list from words, most frequent first.
+    pub fn from_words<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut ranks = HashMap::new();
+        for word in words {
+            let key = frequency_key(word.as_ref());
+            if !key.is_empty() {
+                let rank = ranks.len();
+                ranks.entry(key).or_insert(rank);
+            }
+        }
+        Self { ranks }
+    }
+
+    /// Parse a frequency list from text.
+    pub fn parse(content: &str) -> Self {
+        let mut entries: Vec<(&str, Option<u64>)> = Vec::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let columns: Vec<&str> = if line.contains(['\t', ',']) {
+                line.split(['\t', ',']).map(str::trim).collect()
+            } else {
+                line.split_whitespace().collect()
+            };
+            let Some(word_index) = columns.iter().position(|c| c.parse::<f64>().is_err()) else {
+                continue;
+            };
+            let count = columns[word_index + 1..]
+                .iter()
+                .find_map(|c| c.parse::<u64>().ok());
+            entries.push((columns[word_index], count));
+        }
+
+        if entries.iter().all(|(_, count)| count.is_some()) {
+            // Stable, so equal counts keep their line order
+            entries.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        }
+        Self::from_words(entries.into_iter().map(|(word, _)| word))
+    }
+
+    /// Read and parse a frequency list file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?))
+    }
+
+    /// Rank of a word (0 is the most frequent), if it is on the list.
+    pub fn rank(&self, word: &str) -> Option<usize> {
+        self.ranks.get(&frequency_key(word)).copied()
+    }
+
+    /// Number of distinct words on the list.
+    pub fn len(&self) -> usize {
+        self.ranks.len()
+    }
+
+    /// Whether the list has no words.
+    pub fn is_empty(&self) -> bool {
+        self.ranks.is_empty()
+    }
+}
+
+fn frequency_key(word: &str) -> String {
+    word.trim().to_lowercase()
+}
+
+/// Lowest ease factor Anki allows (130%).
+const MIN_EASE: i64 = 1300;
+
+/// Number of cards per `setEaseFactors` call.
+const EASE_CHUNK_SIZE: usize = 500;
+
+/// Due values at or above this are timestamps (cards learning within the
+/// day) rather than day numbers.
+const DUE_TIMESTAMP_MIN: i64 = 1_000_000_000;
+
+/// Spread cards due per day so no day has more than `max` cards.
+///
+/// Returns the cards for each day, and the cards that fit nowhere paired
+/// with their original day.
+fn smooth_days(due: &[Vec<i64>], max: usize) -> (Vec<Vec<i64>>, Vec<(usize, i64)>) {
+    let mut after: Vec<Vec<i64>> = vec![Vec::new(); due.len()];
+    let mut carry: VecDeque<(usize, i64)> = VecDeque::new();
+    for (day, card_ids) in due.iter().enumerate() {
+        carry.extend(card_ids.iter().map(|&id| (day, id)));
+        while after[day].len() < max {
+            let Some((_, card_id)) = carry.pop_front() else {
+                break;
+            };
+            after[day].push(card_id);
+        }
+    }
+
+    // Pull what's left forward into days with room, latest first
+    for day in (0..due.len()).rev() {
+        while after[day].len() < max {
+            let Some((_, card_id)) = carry.pop_back() else {
+                break;
+            };
+            after[day].push(card_id);
+        }
+    }
+
+    (after, carry.into_iter().collect())
+}
+
+/// Report from ordering new cards by word frequency.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PrioritizeReport {
+    /// Number of new cards considered.
+    pub cards_checked: usize,
+    /// Number of cards whose key field was found on the frequency list.
+    pub cards_matched: usize,
+    /// Number of cards moved to a new queue position (or that would be, in a dry run).
+    pub cards_repositioned: usize,
+    /// Cards whose position could not be updated.
+    pub failed: Vec<i64>,
+    /// Undo journal recorded before repositioning, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for PrioritizeReport {
+    fn summary(&self) -> String {
+        format!(
+            "Repositioned {} of {} new cards ({} on the frequency list)",
+            self.cards_repositioned, self.cards_checked, self.cards_matched
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.failed
+            .iter()
+            .map(|card_id| format!("card {} was not repositioned", card_id))
+            .collect()
+    }
+
+    report_fields!(dry_run, journal);
+}
+
+/// Report from burying or unburying cards.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BuryReport {
+    /// Number of cards buried or unburied (or that would be, in a dry run).
+    pub cards_changed: usize,
+    /// Cards that could not be updated.
+    pub failed: Vec<i64>,
+    /// Undo journal recorded before the change, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for BuryReport {
+    fn summary(&self) -> String {
+        format!(
+            "Changed {} cards ({} failed)",
+            self.cards_changed,
+            self.failed.len()
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.failed
+            .iter()
+            .map(|card_id| format!("card {} was not updated", card_id))
+            .collect()
+    }
+
+    report_fields!(dry_run, journal);
+}
+
+/// Prefix of the tags that remember which cards
+/// [`ProgressEngine::suspend_by_tag`] suspended.
+///
+/// A note gets `ankit-suspended::<tag>::ord<N>` for each of its cards
+/// (by template ordinal) that the call suspended.
+pub const SUSPENDED_TAG_PREFIX: &str = "ankit-suspended::";
+
+/// The tag recording that card `ord` of a note was suspended for `tag`.
+fn suspended_marker(tag: &str, ord: i32) -> String {
+    format!("{}{}::ord{}", SUSPENDED_TAG_PREFIX, tag, ord)
+}
+
+/// Report from suspending or unsuspending the cards of a tag.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TagSuspendReport {
+    /// The tag whose cards were selected.
+    pub tag: String,
+    /// Cards suspended or unsuspended (or that would be, in a dry run).
+    pub changed: Vec<i64>,
+    /// Suspended cards left alone: already suspended before
+    /// [`suspend_by_tag`](ProgressEngine::suspend_by_tag), or not suspended
+    /// by it when unsuspending.
+    pub kept_suspended: Vec<i64>,
+    /// Undo journal recorded before the change, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for TagSuspendReport {
+    fn summary(&self) -> String {
+        format!(
+            "Changed {} cards tagged '{}', left {} suspended",
+            self.changed.len(),
+            self.tag,
+            self.kept_suspended.len()
+        )
+    }
+
+    fn affected(&self) -> AffectedIds {
+        AffectedIds {
+            note_ids: Vec::new(),
+            card_ids: self.changed.clone(),
+        }
+        .normalize()
+    }
+
+    report_fields!(dry_run, journal);
+}
+
+/// Options for [`ProgressEngine::cram_with`].
+#[derive(Debug, Clone)]
+pub struct CramOptions {
+    /// Name of the filtered deck to create.
+    pub deck: String,
+    /// Maximum number of cards to gather.
+    pub limit: usize,
+    /// Whether reviews in the cram deck affect scheduling.
+    pub reschedule: bool,
+    /// Whether to open the deck for review in Anki once it is created.
+    pub start_review: bool,
+}
+
+impl Default for CramOptions {
+    fn default() -> Self {
+        Self {
+            deck: "Cram".to_string(),
+            limit: 100,
+            reschedule: false,
+            start_review: false,
+        }
+    }
+}
+
+/// Report from creating a cram deck.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CramReport {
+    /// Name of the filtered deck.
+    pub deck: String,
+    /// ID of the created deck (not set in a dry run).
+    pub deck_id: Option<i64>,
+    /// Number of cards gathered into the deck. In a dry run, the number of
+    /// cards matching the search, up to the limit.
+    pub cards: usize,
+    /// Whether review was started in Anki.
+    pub review_started: bool,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for CramReport {
+    fn summary(&self) -> String {
+        format!("Gathered {} cards into '{}'", self.cards, self.deck)
+    }
+
+    report_fields!(dry_run);
+}
+
+/// Report from spreading due reviews over a window of days.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SmoothReport {
+    /// The deck smoothed.
+    pub deck: String,
+    /// Review cards due per day before smoothing. Index 0 is today,
+    /// including overdue cards.
+    pub before: Vec<usize>,
+    /// Review cards due per day after smoothing (or as planned, in a dry run).
+    pub after: Vec<usize>,
+    /// Number of cards given a new due date (or that would be, in a dry run).
+    pub cards_moved: usize,
+    /// Cards that did not fit in the window and kept their due date.
+    pub unplaced: usize,
+    /// Cards whose due date could not be updated.
+    pub failed: Vec<i64>,
+    /// Undo journal recorded before rescheduling, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for SmoothReport {
+    fn summary(&self) -> String {
+        let peak = |days: &[usize]| days.iter().copied().max().unwrap_or(0);
+        format!(
+            "Moved {} cards in '{}'; busiest day {} -> {} reviews ({} did not fit)",
+            self.cards_moved,
+            self.deck,
+            peak(&self.before),
+            peak(&self.after),
+            self.unplaced
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        let mut details: Vec<String> = self
+            .before
+            .iter()
+            .zip(&self.after)
+            .enumerate()
+            .map(|(day, (before, after))| format!("day {}: {} -> {}", day, before, after))
+            .collect();
+        details.extend(
+            self.failed
+                .iter()
+                .map(|card_id| format!("card {} was not rescheduled", card_id)),
+        );
+        details
+    }
+
+    report_fields!(dry_run, journal);
+}
+
+/// Options for [`ProgressEngine::postpone_with`] and
+/// [`ProgressEngine::resume_with`].
+///
+/// Pass the same options to both so a resume shifts back exactly the cards
+/// a postpone moved.
+#[derive(Debug, Clone, Default)]
+pub struct ShiftOptions {
+    /// Keep intervals unchanged instead of shifting them with the due date.
+    pub preserve_interval: bool,
+    /// Leave cards in learning or relearning where they are.
+    pub skip_learning: bool,
+}
+
+/// Report from postponing or resuming cards.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PostponeReport {
+    /// Number of cards whose due date was shifted (or would be, in a dry run).
+    pub cards_shifted: usize,
+    /// Number of cards made due today because resuming would have put them
+    /// in the past (resume only).
+    pub cards_due_today: usize,
+    /// Number of new cards left alone, since they have no due date.
+    pub skipped_new: usize,
+    /// Number of learning cards left alone because of
+    /// [`ShiftOptions::skip_learning`].
+    pub skipped_learning: usize,
+    /// Cards that could not be updated.
+    pub failed: Vec<i64>,
+    /// Undo journal recorded before the change, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for PostponeReport {
+    fn summary(&self) -> String {
+        format!(
+            "Shifted {} cards, made {} due today, skipped {} new and {} learning ({} failed)",
+            self.cards_shifted,
+            self.cards_due_today,
+            self.skipped_new,
+            self.skipped_learning,
+            self.failed.len()
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.failed
+            .iter()
+            .map(|card_id| format!("card {} was not rescheduled", card_id))
+            .collect()
+    }
+
+    report_fields!(dry_run, journal);
+}
+
+/// Report from resetting ease factors.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EaseResetReport {
+    /// Number of review cards matching the query.
+    pub cards_checked: usize,
+    /// Number of cards whose ease was reset (or would be, in a dry run).
+    pub cards_reset: usize,
+    /// Number of cards already at the target ease.
+    pub already_at_target: usize,
+    /// Number of cards whose interval was scaled to match the new ease.
+    pub intervals_adjusted: usize,
+    /// Cards that could not be updated.
+    pub failed: Vec<i64>,
+    /// Undo journal recorded before the change, if journaling is enabled.
+    pub journal: Option<PathBuf>,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Changes that would have been made (dry run only).
+    pub planned: Vec<PlannedChange>,
+}
+
+impl WorkflowReport for EaseResetReport {
+    fn summary(&self) -> String {
+        format!(
+            "Reset ease on {} of {} cards, adjusted {} intervals ({} failed)",
+            self.cards_reset,
+            self.cards_checked,
+            self.intervals_adjusted,
+            self.failed.len()
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.failed
+            .iter()
+            .map(|card_id| format!("card {} was not updated", card_id))
+            .collect()
+    }
+
+    report_fields!(dry_run, journal);
 }
 
 /// Progress management workflow engine.
 #[derive(Debug)]
 pub struct ProgressEngine<'a> {
     client: &'a AnkiClient,
+    options: &'a EngineOptions,
 }
 
 impl<'a> ProgressEngine<'a> {
-    pub(crate) fn new(client: &'a AnkiClient) -> Self {
-        Self { client }
+    pub(crate) fn new(client: &'a AnkiClient, options: &'a EngineOptions) -> Self {
+        Self { client, options }
     }
 
     /// Reset all cards in a deck to new state.
@@ -236,14 +883,27 @@ impl<'a> ProgressEngine<'a> {
         let query = format!("deck:\"{}\"", deck);
         let card_ids = self.client.cards().find(&query).await?;
 
+        let mut report = ResetReport {
+            cards_reset: card_ids.len(),
+            deck: deck.to_string(),
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
         if !card_ids.is_empty() {
-            self.client.cards().forget(&card_ids).await?;
+            if report.dry_run {
+                report.planned.push(PlannedChange::ForgetCards { card_ids });
+            } else {
+                if let Some(dir) = &self.options.journal_dir {
+                    let mut record = Journal::new("reset_deck");
+                    record.entries = journal::record_scheduling(self.client, &card_ids).await?;
+                    report.journal = Some(record.write(dir)?);
+                }
+                self.client.cards().forget(&card_ids).await?;
+            }
         }
 
-        Ok(ResetReport {
-            cards_reset: card_ids.len(),
-            deck: deck.to_string(),
-        })
+        Ok(report)
     }
 
     /// Tag cards based on their performance.
@@ -290,6 +950,7 @@ impl<'a> ProgressEngine<'a> {
             return Ok(TagReport {
                 struggling_tag: struggling_tag.to_string(),
                 mastered_tag: mastered_tag.to_string(),
+                dry_run: self.options.dry_run,
                 ..Default::default()
             });
         }
@@ -318,26 +979,33 @@ impl<'a> ProgressEngine<'a> {
         let struggling_ids: Vec<_> = struggling_notes.into_iter().collect();
         let mastered_ids: Vec<_> = mastered_notes.into_iter().collect();
 
-        if !struggling_ids.is_empty() {
-            self.client
-                .notes()
-                .add_tags(&struggling_ids, struggling_tag)
-                .await?;
-        }
-
-        if !mastered_ids.is_empty() {
-            self.client
-                .notes()
-                .add_tags(&mastered_ids, mastered_tag)
-                .await?;
-        }
-
-        Ok(TagReport {
+        let mut report = TagReport {
             struggling_count: struggling_ids.len(),
             mastered_count: mastered_ids.len(),
             struggling_tag: struggling_tag.to_string(),
             mastered_tag: mastered_tag.to_string(),
-        })
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        for (note_ids, tag) in [
+            (struggling_ids, struggling_tag),
+            (mastered_ids, mastered_tag),
+        ] {
+            if note_ids.is_empty() {
+                continue;
+            }
+            if report.dry_run {
+                report.planned.push(PlannedChange::AddTags {
+                    note_ids,
+                    tags: tag.to_string(),
+                });
+            } else {
+                self.client.notes().add_tags(&note_ids, tag).await?;
+            }
+        }
+
+        Ok(report)
     }
 
     /// Suspend cards matching performance criteria.
@@ -369,7 +1037,10 @@ impl<'a> ProgressEngine<'a> {
         let card_ids = self.client.cards().find(query).await?;
 
         if card_ids.is_empty() {
-            return Ok(SuspendReport::default());
+            return Ok(SuspendReport {
+                dry_run: self.options.dry_run,
+                ..Default::default()
+            });
         }
 
         let cards = self.client.cards().info(&card_ids).await?;
@@ -396,13 +1067,24 @@ impl<'a> ProgressEngine<'a> {
             }
         }
 
+        let dry_run = self.options.dry_run;
+        let mut planned = Vec::new();
+
         if !to_suspend.is_empty() {
-            self.client.cards().suspend(&to_suspend).await?;
+            if dry_run {
+                planned.push(PlannedChange::SuspendCards {
+                    card_ids: to_suspend.clone(),
+                });
+            } else {
+                self.client.cards().suspend(&to_suspend).await?;
+            }
         }
 
         Ok(SuspendReport {
             cards_suspended: to_suspend.len(),
             suspended_ids: to_suspend,
+            dry_run,
+            planned,
         })
     }
 
@@ -436,6 +1118,7 @@ impl<'a> ProgressEngine<'a> {
         }
 
         let cards = self.client.cards().info(&card_ids).await?;
+        let leeches = self.options.leech.resolve(self.client, &cards).await?;
 
         let mut report = HealthReport {
             deck: deck.to_string(),
@@ -473,8 +1156,8 @@ impl<'a> ProgressEngine<'a> {
             report.total_lapses += card.lapses;
             report.total_reps += card.reps;
 
-            // Leech threshold: 8+ lapses (Anki's default)
-            if card.lapses >= 8 {
+            // Leech threshold: the deck's lapse option
+            if leeches.is_leech(card) {
                 report.leech_count += 1;
             }
         }
@@ -531,25 +1214,51 @@ impl<'a> ProgressEngine<'a> {
         if note_ids.is_empty() {
             return Ok(BulkTagReport {
                 operation: format!("{:?}", operation),
+                dry_run: self.options.dry_run,
                 ..Default::default()
             });
         }
 
+        let dry_run = self.options.dry_run;
+        let mut planned = Vec::new();
+
         let op_description = match &operation {
             TagOperation::Add(tags) => {
-                self.client.notes().add_tags(&note_ids, tags).await?;
+                if dry_run {
+                    planned.push(PlannedChange::AddTags {
+                        note_ids: note_ids.clone(),
+                        tags: tags.clone(),
+                    });
+                } else {
+                    self.client.notes().add_tags(&note_ids, tags).await?;
+                }
                 format!("Added '{}'", tags)
             }
             TagOperation::Remove(tags) => {
-                self.client.notes().remove_tags(&note_ids, tags).await?;
+                if dry_run {
+                    planned.push(PlannedChange::RemoveTags {
+                        note_ids: note_ids.clone(),
+                        tags: tags.clone(),
+                    });
+                } else {
+                    self.client.notes().remove_tags(&note_ids, tags).await?;
+                }
                 format!("Removed '{}'", tags)
             }
             TagOperation::Replace { old, new } => {
-                // Replace on specific notes
-                self.client
-                    .notes()
-                    .replace_tags(&note_ids, old, new)
-                    .await?;
+                if dry_run {
+                    planned.push(PlannedChange::ReplaceTags {
+                        note_ids: note_ids.clone(),
+                        old: old.clone(),
+                        new: new.clone(),
+                    });
+                } else {
+                    // Replace on specific notes
+                    self.client
+                        .notes()
+                        .replace_tags(&note_ids, old, new)
+                        .await?;
+                }
                 format!("Replaced '{}' with '{}'", old, new)
             }
         };
@@ -557,6 +1266,8 @@ impl<'a> ProgressEngine<'a> {
         Ok(BulkTagReport {
             notes_affected: note_ids.len(),
             operation: op_description,
+            dry_run,
+            planned,
         })
     }
 
@@ -585,6 +1296,7 @@ impl<'a> ProgressEngine<'a> {
     ///         field: "Front".to_string(),
     ///         keep_strategy: KeepStrategy::MostMature,
     ///         dry_run: true,
+    ///         ..SimilarityCriteria::default()
     ///     })
     ///     .await?;
     ///
@@ -606,11 +1318,12 @@ impl<'a> ProgressEngine<'a> {
         query: &str,
         criteria: SimilarityCriteria,
     ) -> Result<SmartSuspendReport> {
+        let dry_run = criteria.dry_run || self.options.dry_run;
         let card_ids = self.client.cards().find(query).await?;
 
         if card_ids.is_empty() {
             return Ok(SmartSuspendReport {
-                dry_run: criteria.dry_run,
+                dry_run,
                 ..Default::default()
             });
         }
@@ -662,7 +1375,7 @@ impl<'a> ProgressEngine<'a> {
         if card_data.len() < 2 {
             return Ok(SmartSuspendReport {
                 cards_analyzed: card_data.len(),
-                dry_run: criteria.dry_run,
+                dry_run,
                 ..Default::default()
             });
         }
@@ -686,14 +1399,16 @@ impl<'a> ProgressEngine<'a> {
             }
         }
 
-        // Compare all pairs and union similar cards
-        for i in 0..n {
-            for j in (i + 1)..n {
-                let sim = string_similarity(&card_data[i].2, &card_data[j].2);
-                if sim >= criteria.threshold {
-                    union(&mut parent, i, j);
-                }
-            }
+        // Compare field values ignoring case and union similar cards
+        let keys: Vec<String> = card_data.iter().map(|c| c.2.to_lowercase()).collect();
+        let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+        for pair in pairs(
+            &keys,
+            criteria.algorithm,
+            criteria.threshold,
+            criteria.prefilter,
+        ) {
+            union(&mut parent, pair.left, pair.right);
         }
 
         // Group cards by their root
@@ -707,7 +1422,7 @@ impl<'a> ProgressEngine<'a> {
         // Process groups with more than one card
         let mut report = SmartSuspendReport {
             cards_analyzed: card_data.len(),
-            dry_run: criteria.dry_run,
+            dry_run,
             ..Default::default()
         };
 
@@ -745,7 +1460,7 @@ impl<'a> ProgressEngine<'a> {
             for &i in indices {
                 for &j in indices {
                     if i < j {
-                        let sim = string_similarity(&card_data[i].2, &card_data[j].2);
+                        let sim = criteria.algorithm.similarity(keys[i], keys[j]);
                         min_sim = min_sim.min(sim);
                     }
                 }
@@ -766,66 +1481,1160 @@ impl<'a> ProgressEngine<'a> {
         report.cards_kept = report.groups_found;
 
         // Actually suspend if not a dry run
-        if !criteria.dry_run && !to_suspend.is_empty() {
-            self.client.cards().suspend(&to_suspend).await?;
+        if !to_suspend.is_empty() {
+            if dry_run {
+                report.planned.push(PlannedChange::SuspendCards {
+                    card_ids: to_suspend,
+                });
+            } else {
+                self.client.cards().suspend(&to_suspend).await?;
+            }
         }
 
         Ok(report)
     }
-}
-
-/// Calculate string similarity using normalized Levenshtein distance.
-fn string_similarity(a: &str, b: &str) -> f64 {
-    let a_lower = a.to_lowercase();
-    let b_lower = b.to_lowercase();
-
-    if a_lower == b_lower {
-        return 1.0;
-    }
 
-    if a_lower.is_empty() || b_lower.is_empty() {
-        return 0.0;
-    }
-
-    let distance = levenshtein_distance(&a_lower, &b_lower);
-    let max_len = a_lower.chars().count().max(b_lower.chars().count());
-
-    1.0 - (distance as f64 / max_len as f64)
-}
+    /// Replay a sequence of review decisions.
+    ///
+    /// Decisions are sorted by `reviewed_at` (ties keep their input order)
+    /// and submitted with [`CardActions::answer_batch()`](ankit::actions::CardActions::answer_batch),
+    /// so large histories take a handful of requests instead of one per
+    /// review. Useful for migrating review history from other SRS tools or
+    /// for simulating study sessions.
+    ///
+    /// Anki schedules each answer as if it were given now; `reviewed_at`
+    /// only determines the order in which reviews are applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `decisions` - Review decisions to replay
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::{Engine, Ease};
+    /// # use ankit_engine::progress::ReviewDecision;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let history = vec![
+    ///     ReviewDecision::new(1234567890, Ease::Good, 1_700_000_000_000),
+    ///     ReviewDecision::new(1234567890, Ease::Again, 1_700_086_400_000),
+    /// ];
+    /// let report = engine.progress().replay_reviews(&history).await?;
+    /// println!("Replayed {} of {} reviews", report.answered, report.submitted);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn replay_reviews(&self, decisions: &[ReviewDecision]) -> Result<ReplayReport> {
+        let mut ordered = decisions.to_vec();
+        ordered.sort_by_key(|d| d.reviewed_at);
+        let answers: Vec<CardAnswer> = ordered
+            .iter()
+            .map(|d| CardAnswer::new(d.card_id, d.ease))
+            .collect();
+
+        let mut report = ReplayReport {
+            submitted: answers.len(),
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        if answers.is_empty() {
+            return Ok(report);
+        }
 
-/// Calculate the Levenshtein distance between two strings.
-fn levenshtein_distance(a: &str, b: &str) -> usize {
-    let a_chars: Vec<char> = a.chars().collect();
-    let b_chars: Vec<char> = b.chars().collect();
+        if report.dry_run {
+            report.planned.push(PlannedChange::AnswerCards { answers });
+            return Ok(report);
+        }
 
-    let m = a_chars.len();
-    let n = b_chars.len();
+        if let Some(dir) = &self.options.journal_dir {
+            let card_ids: Vec<i64> = ordered
+                .iter()
+                .map(|d| d.card_id)
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect();
+            let mut record = Journal::new("replay_reviews");
+            record.entries = journal::record_scheduling(self.client, &card_ids).await?;
+            report.journal = Some(record.write(dir)?);
+        }
+
+        let results = self.client.cards().answer_batch(&answers).await?;
+        for (decision, answered) in ordered.iter().zip(results) {
+            if answered {
+                report.answered += 1;
+            } else {
+                report.failed.push(decision.card_id);
+            }
+        }
 
-    if m == 0 {
-        return n;
+        Ok(report)
     }
-    if n == 0 {
-        return m;
+
+    /// Reorder new cards so that high-frequency words are introduced first.
+    ///
+    /// The `field` of each new card matching `query` is reduced to plain text
+    /// and looked up in `frequency`. Cards are then given the queue positions
+    /// the matched cards already occupy, in frequency order: cards on the list
+    /// come first, most frequent first, followed by the rest in their
+    /// original order. Cards outside the query keep their positions, and
+    /// sibling cards stay together.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - Anki search query; only new cards are considered
+    /// * `frequency` - Word-frequency list
+    /// * `field` - Field holding the word to look up (e.g., "Front")
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # use ankit_engine::progress::FrequencyList;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let frequency = FrequencyList::from_file("es_50k.txt")?;
+    /// let report = engine
+    ///     .progress()
+    ///     .prioritize_by_frequency("deck:Spanish", &frequency, "Front")
+    ///     .await?;
+    /// println!("Repositioned {} cards", report.cards_repositioned);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn prioritize_by_frequency(
+        &self,
+        query: &str,
+        frequency: &FrequencyList,
+        field: &str,
+    ) -> Result<PrioritizeReport> {
+        let mut report = PrioritizeReport {
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        let card_ids = self
+            .client
+            .cards()
+            .find(&format!("({}) is:new", query))
+            .await?;
+        if card_ids.is_empty() {
+            return Ok(report);
+        }
+        let cards: Vec<_> = self
+            .client
+            .cards()
+            .info(&card_ids)
+            .await?
+            .into_iter()
+            .filter(|card| card.card_type == 0)
+            .collect();
+        report.cards_checked = cards.len();
+
+        let text_options = NormalizeOptions::plain_text();
+        let mut ranked: Vec<(Option<usize>, i64, i64)> = cards
+            .iter()
+            .map(|card| {
+                let rank = card
+                    .fields
+                    .get(field)
+                    .and_then(|f| frequency.rank(&normalize(&f.value, &text_options)));
+                (rank, card.due, card.card_id)
+            })
+            .collect();
+        report.cards_matched = ranked.iter().filter(|(rank, ..)| rank.is_some()).count();
+
+        let mut slots: Vec<i64> = ranked.iter().map(|(_, due, _)| *due).collect();
+        slots.sort_unstable();
+        ranked.sort_by_key(|&(rank, due, card_id)| (rank.is_none(), rank, due, card_id));
+
+        let moves: Vec<(i64, i64)> = ranked
+            .iter()
+            .zip(slots)
+            .filter(|((_, due, _), slot)| due != slot)
+            .map(|((_, _, card_id), slot)| (*card_id, slot))
+            .collect();
+        if moves.is_empty() {
+            return Ok(report);
+        }
+
+        if report.dry_run {
+            report.cards_repositioned = moves.len();
+            report.planned.push(PlannedChange::RepositionCards {
+                card_ids: moves.iter().map(|(card_id, _)| *card_id).collect(),
+                positions: moves.iter().map(|(_, position)| *position).collect(),
+            });
+            return Ok(report);
+        }
+
+        if let Some(dir) = &self.options.journal_dir {
+            let card_ids: Vec<i64> = moves.iter().map(|(card_id, _)| *card_id).collect();
+            let mut record = Journal::new("prioritize_by_frequency");
+            record.entries = journal::record_scheduling(self.client, &card_ids).await?;
+            report.journal = Some(record.write(dir)?);
+        }
+
+        let results = self.client.cards().reposition(&moves).await?;
+        for ((card_id, _), moved) in moves.iter().zip(results) {
+            if moved {
+                report.cards_repositioned += 1;
+            } else {
+                report.failed.push(*card_id);
+            }
+        }
+
+        Ok(report)
     }
 
-    let mut prev: Vec<usize> = (0..=n).collect();
-    let mut curr = vec![0; n + 1];
+    /// Bury cards until the next day.
+    ///
+    /// Buried cards are hidden from review until Anki unburies them at the
+    /// start of the next day, or until [`unbury`](Self::unbury) is called.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let cards = engine.client().cards().find("deck:Japanese tag:later").await?;
+    /// let report = engine.progress().bury_cards(&cards).await?;
+    /// println!("Buried {} cards", report.cards_changed);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn bury_cards(&self, card_ids: &[i64]) -> Result<BuryReport> {
+        let mut report = BuryReport {
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+        if card_ids.is_empty() {
+            return Ok(report);
+        }
+
+        if report.dry_run {
+            report.cards_changed = card_ids.len();
+            report.planned.push(PlannedChange::BuryCards {
+                card_ids: card_ids.to_vec(),
+            });
+            return Ok(report);
+        }
+
+        if let Some(dir) = &self.options.journal_dir {
+            let mut record = Journal::new("bury_cards");
+            record.entries = journal::record_scheduling(self.client, card_ids).await?;
+            report.journal = Some(record.write(dir)?);
+        }
+
+        let results = self.client.cards().bury(card_ids).await?;
+        Self::tally(&mut report, card_ids, results);
+        Ok(report)
+    }
 
-    for i in 1..=m {
-        curr[0] = i;
+    /// Unbury every buried card in a deck.
+    ///
+    /// Restores both cards buried manually and siblings buried by the
+    /// scheduler, including those in sub-decks.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let report = engine.progress().unbury("Japanese").await?;
+    /// println!("Unburied {} cards", report.cards_changed);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn unbury(&self, deck: &str) -> Result<BuryReport> {
+        let mut report = BuryReport {
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
 
-        for j in 1..=n {
-            let cost = if a_chars[i - 1] == b_chars[j - 1] {
-                0
+        let query = format!("deck:\"{}\" is:buried", deck);
+        let card_ids = self.client.cards().find(&query).await?;
+        if card_ids.is_empty() {
+            return Ok(report);
+        }
+
+        if report.dry_run {
+            report.cards_changed = card_ids.len();
+            report.planned.push(PlannedChange::UnburyCards { card_ids });
+            return Ok(report);
+        }
+
+        if let Some(dir) = &self.options.journal_dir {
+            let mut record = Journal::new("unbury");
+            record.entries = journal::record_scheduling(self.client, &card_ids).await?;
+            report.journal = Some(record.write(dir)?);
+        }
+
+        let results = self.client.cards().unbury(&card_ids).await?;
+        Self::tally(&mut report, &card_ids, results);
+        Ok(report)
+    }
+
+    fn tally(report: &mut BuryReport, card_ids: &[i64], results: Vec<bool>) {
+        for (&card_id, changed) in card_ids.iter().zip(results) {
+            if changed {
+                report.cards_changed += 1;
+            } else {
+                report.failed.push(card_id);
+            }
+        }
+    }
+
+    /// Suspend every card of the notes tagged `tag`, remembering which
+    /// ones were already suspended.
+    ///
+    /// Each card this suspends is recorded with a tag on its note (see
+    /// [`SUSPENDED_TAG_PREFIX`]), so
+    /// [`unsuspend_by_tag`](Self::unsuspend_by_tag) restores exactly the
+    /// earlier state: cards that were suspended before, such as leeches,
+    /// stay suspended. Child tags (`tag::child`) are included.
+    ///
+    /// Respects [`EngineOptions::dry_run`] and records an undo journal when
+    /// [`EngineOptions::journal_dir`] is set.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let report = engine.progress().suspend_by_tag("exam-later").await?;
+    /// println!(
+    ///     "Suspended {} cards, {} already were",
+    ///     report.changed.len(),
+    ///     report.kept_suspended.len()
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn suspend_by_tag(&self, tag: &str) -> Result<TagSuspendReport> {
+        let mut report = TagSuspendReport {
+            tag: tag.to_string(),
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        let card_ids = self
+            .client
+            .cards()
+            .find(&format!("tag:\"{}\"", tag))
+            .await?;
+        if card_ids.is_empty() {
+            return Ok(report);
+        }
+
+        // Notes to mark, by marker tag
+        let mut markers: BTreeMap<String, Vec<i64>> = BTreeMap::new();
+        for card in self.client.cards().info(&card_ids).await? {
+            if card.queue == -1 {
+                report.kept_suspended.push(card.card_id);
             } else {
-                1
+                report.changed.push(card.card_id);
+                markers
+                    .entry(suspended_marker(tag, card.ord))
+                    .or_default()
+                    .push(card.note_id);
+            }
+        }
+        if report.changed.is_empty() {
+            return Ok(report);
+        }
+
+        if report.dry_run {
+            report.planned.push(PlannedChange::SuspendCards {
+                card_ids: report.changed.clone(),
+            });
+            for (marker, note_ids) in markers {
+                report.planned.push(PlannedChange::AddTags {
+                    note_ids,
+                    tags: marker,
+                });
+            }
+            return Ok(report);
+        }
+
+        if let Some(dir) = &self.options.journal_dir {
+            let mut record = Journal::new("suspend_by_tag");
+            record.entries = journal::record_scheduling(self.client, &report.changed).await?;
+            report.journal = Some(record.write(dir)?);
+        }
+
+        // Mark before suspending, so no suspension goes unrecorded
+        for (marker, note_ids) in &markers {
+            self.client.notes().add_tags(note_ids, marker).await?;
+        }
+        self.client.cards().suspend(&report.changed).await?;
+
+        Ok(report)
+    }
+
+    /// Unsuspend the cards that [`suspend_by_tag`](Self::suspend_by_tag)
+    /// suspended for `tag`.
+    ///
+    /// Suspended cards without a record from `suspend_by_tag`, because they
+    /// were suspended before it ran or by something else, stay suspended.
+    /// The records of the unsuspended cards are removed from the notes;
+    /// records made for a child tag such as `exam::ch1` are kept.
+    ///
+    /// Respects [`EngineOptions::dry_run`] and records an undo journal when
+    /// [`EngineOptions::journal_dir`] is set.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let report = engine.progress().unsuspend_by_tag("exam-later").await?;
+    /// println!("Unsuspended {} cards", report.changed.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn unsuspend_by_tag(&self, tag: &str) -> Result<TagSuspendReport> {
+        let mut report = TagSuspendReport {
+            tag: tag.to_string(),
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        let card_ids = self
+            .client
+            .cards()
+            .find(&format!("tag:\"{}\" is:suspended", tag))
+            .await?;
+        if card_ids.is_empty() {
+            return Ok(report);
+        }
+        let cards = self.client.cards().info(&card_ids).await?;
+
+        let mut note_ids: Vec<i64> = cards.iter().map(|c| c.note_id).collect();
+        note_ids.sort_unstable();
+        note_ids.dedup();
+        // Markers are matched exactly: a child tag's markers share this
+        // tag's prefix but belong to its own suspension
+        let note_tags: HashMap<i64, HashSet<String>> = self
+            .client
+            .notes()
+            .info(&note_ids)
+            .await?
+            .into_iter()
+            .map(|note| (note.note_id, note.tags.into_iter().collect()))
+            .collect();
+
+        let mut marked = Vec::new();
+        let mut markers = Vec::new();
+        for card in &cards {
+            let marker = suspended_marker(tag, card.ord);
+            if note_tags
+                .get(&card.note_id)
+                .is_some_and(|tags| tags.contains(&marker))
+            {
+                report.changed.push(card.card_id);
+                marked.push(card.note_id);
+                markers.push(marker);
+            } else {
+                report.kept_suspended.push(card.card_id);
+            }
+        }
+
+        marked.sort_unstable();
+        marked.dedup();
+        markers.sort_unstable();
+        markers.dedup();
+        let markers = markers.join(" ");
+
+        if report.dry_run {
+            if !report.changed.is_empty() {
+                report.planned.push(PlannedChange::UnsuspendCards {
+                    card_ids: report.changed.clone(),
+                });
+            }
+            if !marked.is_empty() {
+                report.planned.push(PlannedChange::RemoveTags {
+                    note_ids: marked,
+                    tags: markers,
+                });
+            }
+            return Ok(report);
+        }
+
+        if !report.changed.is_empty() {
+            if let Some(dir) = &self.options.journal_dir {
+                let mut record = Journal::new("unsuspend_by_tag");
+                record.entries = journal::record_scheduling(self.client, &report.changed).await?;
+                report.journal = Some(record.write(dir)?);
+            }
+            self.client.cards().unsuspend(&report.changed).await?;
+        }
+        if !marked.is_empty() {
+            self.client.notes().remove_tags(&marked, &markers).await?;
+        }
+
+        Ok(report)
+    }
+
+    /// Create a cram deck from a search.
+    ///
+    /// Gathers up to `limit` cards matching `query` into a filtered deck
+    /// named "Cram". Reviews in the deck do not change the cards'
+    /// scheduling. Use [`cram_with`](Self::cram_with) to choose the deck name,
+    /// reschedule, or start reviewing right away.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let report = engine.progress().cram("deck:Japanese tag:exam", 50).await?;
+    /// println!("{} cards ready in '{}'", report.cards, report.deck);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn cram(&self, query: &str, limit: usize) -> Result<CramReport> {
+        let options = CramOptions {
+            limit,
+            ..Default::default()
+        };
+        self.cram_with(query, &options).await
+    }
+
+    /// Create a cram deck from a search with custom options.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # use ankit_engine::progress::CramOptions;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let options = CramOptions {
+    ///     deck: "Cram tonight".to_string(),
+    ///     limit: 80,
+    ///     start_review: true,
+    ///     ..Default::default()
+    /// };
+    /// engine.progress().cram_with("deck:Japanese prop:ease<2.1", &options).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn cram_with(&self, query: &str, options: &CramOptions) -> Result<CramReport> {
+        if options.limit == 0 {
+            return Err(Error::Validation(
+                "cram limit must be at least 1".to_string(),
+            ));
+        }
+
+        let mut report = CramReport {
+            deck: options.deck.clone(),
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        if report.dry_run {
+            let matching = self.client.cards().find(query).await?;
+            report.cards = matching.len().min(options.limit);
+            report.planned.push(PlannedChange::CreateFilteredDeck {
+                deck: options.deck.clone(),
+                search: query.to_string(),
+                limit: options.limit,
+            });
+            return Ok(report);
+        }
+
+        let deck_id = self
+            .client
+            .decks()
+            .create_filtered(&options.deck, query, options.limit, options.reschedule)
+            .await?;
+        report.deck_id = Some(deck_id);
+        report.cards = self
+            .client
+            .cards()
+            .find(&format!("deck:\"{}\"", options.deck))
+            .await?
+            .len();
+
+        if options.start_review && report.cards > 0 {
+            report.review_started = self.client.gui().deck_review(&options.deck).await?;
+        }
+
+        Ok(report)
+    }
+    /// Spread due reviews evenly over the next days.
+    ///
+    /// After a break, overdue reviews pile up on a single day. This looks at
+    /// the review cards due from today (counting overdue cards as due today)
+    /// through `horizon_days - 1` days from now, and gives no day more than
+    /// `max_per_day` cards. Excess cards are pushed to the next day with
+    /// room, cards pushed furthest being the latest due, so overdue cards are
+    /// seen first. Cards still left at the end of the window are pulled
+    /// forward into days with room, and any that fit nowhere keep their due
+    /// date.
+    ///
+    /// Due dates are changed with [`set_due_date`](ankit::actions::CardActions::set_due_date),
+    /// which keeps each card's interval. The report holds the daily due
+    /// counts before and after, so a dry run previews the new workload.
+    ///
+    /// Respects [`EngineOptions::dry_run`] and records an undo journal of
+    /// the moved cards' scheduling when [`EngineOptions::journal_dir`] is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `deck` - Deck whose reviews to smooth
+    /// * `horizon_days` - Number of days, starting today, to spread reviews over
+    /// * `max_per_day` - Most reviews any day in the window should have
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Validation`] if `horizon_days` or `max_per_day` is 0.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::{Engine, EngineOptions};
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let preview = Engine::new().with_options(EngineOptions {
+    ///     dry_run: true,
+    ///     ..Default::default()
+    /// });
+    /// let report = preview.progress().smooth_due("Japanese", 7, 150).await?;
+    /// for (day, (before, after)) in report.before.iter().zip(&report.after).enumerate() {
+    ///     println!("day {}: {} -> {}", day, before, after);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn smooth_due(
+        &self,
+        deck: &str,
+        horizon_days: u32,
+        max_per_day: usize,
+    ) -> Result<SmoothReport> {
+        if horizon_days == 0 || max_per_day == 0 {
+            return Err(Error::Validation(
+                "horizon_days and max_per_day must be at least 1".to_string(),
+            ));
+        }
+        let mut report = SmoothReport {
+            deck: deck.to_string(),
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        // Review cards due on each day of the window, relative to today
+        let base = format!(
+            "deck:\"{}\" is:review -is:learn -is:suspended -is:buried",
+            deck
+        );
+        let mut due: Vec<Vec<i64>> = Vec::with_capacity(horizon_days as usize);
+        for day in 0..horizon_days {
+            let filter = if day == 0 {
+                "prop:due<=0".to_string()
+            } else {
+                format!("prop:due={}", day)
             };
+            let mut card_ids = self
+                .client
+                .cards()
+                .find(&format!("{} {}", base, filter))
+                .await?;
+            card_ids.sort_unstable();
+            due.push(card_ids);
+        }
+        report.before = due.iter().map(Vec::len).collect();
+
+        let (after, unplaced) = smooth_days(&due, max_per_day);
+        report.unplaced = unplaced.len();
+        report.after = after.iter().map(Vec::len).collect();
+        for (day, _) in unplaced {
+            report.after[day] += 1;
+        }
+
+        // Group the cards whose day changed by their new day
+        let original: HashMap<i64, usize> = due
+            .iter()
+            .enumerate()
+            .flat_map(|(day, card_ids)| card_ids.iter().map(move |&id| (id, day)))
+            .collect();
+        let mut moves: BTreeMap<usize, Vec<i64>> = BTreeMap::new();
+        for (day, card_ids) in after.iter().enumerate() {
+            for &card_id in card_ids {
+                if original.get(&card_id) != Some(&day) {
+                    moves.entry(day).or_default().push(card_id);
+                }
+            }
+        }
+        if moves.is_empty() {
+            return Ok(report);
+        }
+
+        if report.dry_run {
+            for (day, card_ids) in moves {
+                report.cards_moved += card_ids.len();
+                report.planned.push(PlannedChange::SetDueDate {
+                    card_ids,
+                    days: day.to_string(),
+                });
+            }
+            return Ok(report);
+        }
+
+        if let Some(dir) = &self.options.journal_dir {
+            let card_ids: Vec<i64> = moves.values().flatten().copied().collect();
+            let mut record = Journal::new("smooth_due");
+            record.entries = journal::record_scheduling(self.client, &card_ids).await?;
+            report.journal = Some(record.write(dir)?);
+        }
+
+        for (day, card_ids) in moves {
+            if self
+                .client
+                .cards()
+                .set_due_date(&card_ids, &day.to_string())
+                .await?
+            {
+                report.cards_moved += card_ids.len();
+            } else {
+                report.failed.extend(card_ids);
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Push the due dates of cards back, for example before a vacation.
+    ///
+    /// Every matching card that has a due date is shifted `days` later:
+    /// review cards and learning cards alike, including suspended and buried
+    /// ones. New cards have no due date and are skipped. Use
+    /// [`postpone_with`](Self::postpone_with) to leave cards in learning
+    /// alone.
+    ///
+    /// With `preserve_interval`, intervals stay as they are, so the next
+    /// review is scheduled as if the card had been reviewed on time. Without
+    /// it, the intervals of review cards grow by `days` as well, crediting
+    /// the extra time the card was remembered.
+    ///
+    /// Respects [`EngineOptions::dry_run`] and records an undo journal of
+    /// the cards' scheduling when [`EngineOptions::journal_dir`] is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - Anki search query selecting cards
+    /// * `days` - Number of days to postpone by
+    /// * `preserve_interval` - Keep intervals unchanged
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Validation`] if `days` is 0.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// // Two weeks away: nothing comes due until we're back
+    /// let report = engine.progress().postpone("deck:Japanese", 14, true).await?;
+    /// println!("Postponed {} cards", report.cards_shifted);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn postpone(
+        &self,
+        query: &str,
+        days: u32,
+        preserve_interval: bool,
+    ) -> Result<PostponeReport> {
+        let options = ShiftOptions {
+            preserve_interval,
+            ..Default::default()
+        };
+        self.postpone_with(query, days, &options).await
+    }
+
+    /// Push the due dates of cards back with custom options.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # use ankit_engine::progress::ShiftOptions;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let options = ShiftOptions {
+    ///     preserve_interval: true,
+    ///     skip_learning: true,
+    /// };
+    /// engine.progress().postpone_with("deck:Japanese", 14, &options).await?;
+    /// // Back early: the same options shift the same cards back
+    /// engine.progress().resume_with("deck:Japanese", 4, &options).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn postpone_with(
+        &self,
+        query: &str,
+        days: u32,
+        options: &ShiftOptions,
+    ) -> Result<PostponeReport> {
+        self.shift_due("postpone", query, i64::from(days), options)
+            .await
+    }
+
+    /// Bring postponed cards back early.
+    ///
+    /// The counterpart of [`postpone`](Self::postpone): due dates of
+    /// matching cards move `days` earlier, for example after coming back
+    /// from a trip sooner than planned. Review cards that would become due
+    /// before today are made due today instead, keeping their intervals.
+    ///
+    /// Without `preserve_interval`, the intervals of shifted review cards
+    /// shrink by `days` (to at least one day), undoing a `postpone` that
+    /// grew them. Use [`resume_with`](Self::resume_with) to leave cards in
+    /// learning alone.
+    ///
+    /// Respects [`EngineOptions::dry_run`] and records an undo journal of
+    /// the cards' scheduling when [`EngineOptions::journal_dir`] is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - Anki search query selecting cards
+    /// * `days` - Number of days to bring cards forward by
+    /// * `preserve_interval` - Keep intervals unchanged
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Validation`] if `days` is 0.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// // Back four days early
+    /// let report = engine.progress().resume("deck:Japanese", 4, true).await?;
+    /// println!("{} cards due today", report.cards_due_today);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn resume(
+        &self,
+        query: &str,
+        days: u32,
+        preserve_interval: bool,
+    ) -> Result<PostponeReport> {
+        let options = ShiftOptions {
+            preserve_interval,
+            ..Default::default()
+        };
+        self.resume_with(query, days, &options).await
+    }
+
+    /// Bring postponed cards back early with custom options.
+    ///
+    /// Pass the options the cards were postponed with; see
+    /// [`postpone_with`](Self::postpone_with).
+    pub async fn resume_with(
+        &self,
+        query: &str,
+        days: u32,
+        options: &ShiftOptions,
+    ) -> Result<PostponeReport> {
+        self.shift_due("resume", query, -i64::from(days), options)
+            .await
+    }
+
+    /// Shift due dates by `days`, which is negative when resuming.
+    async fn shift_due(
+        &self,
+        operation: &str,
+        query: &str,
+        days: i64,
+        options: &ShiftOptions,
+    ) -> Result<PostponeReport> {
+        if days == 0 {
+            return Err(Error::Validation(format!(
+                "{} needs at least one day",
+                operation
+            )));
+        }
+        let mut report = PostponeReport {
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        let card_ids = self.client.cards().find(query).await?;
+        if card_ids.is_empty() {
+            return Ok(report);
+        }
+        let cards = self.client.cards().info(&card_ids).await?;
+
+        // Review cards that resuming would move into the past become due today
+        let due_today: HashSet<i64> = if days < 0 {
+            self.client
+                .cards()
+                .find(&format!("({}) is:review prop:due<={}", query, -days))
+                .await?
+                .into_iter()
+                .collect()
+        } else {
+            HashSet::new()
+        };
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        let mut shifts: Vec<(i64, i64, Option<i64>)> = Vec::new();
+        let mut today: Vec<i64> = Vec::new();
+        for card in &cards {
+            if card.card_type == 0 {
+                report.skipped_new += 1;
+            } else if options.skip_learning && matches!(card.card_type, 1 | 3) {
+                report.skipped_learning += 1;
+            } else if due_today.contains(&card.card_id) {
+                today.push(card.card_id);
+            } else {
+                // Cards learning within the day have a timestamp due, the
+                // rest a day number
+                let due = if card.due >= DUE_TIMESTAMP_MIN {
+                    (card.due + days * 86400).max(now.min(card.due))
+                } else {
+                    card.due + days
+                };
+                let interval = (!options.preserve_interval && matches!(card.card_type, 2 | 3))
+                    .then(|| (card.interval + days).max(1));
+                shifts.push((card.card_id, due, interval));
+            }
+        }
+
+        if report.dry_run {
+            report.cards_shifted = shifts.len();
+            report.cards_due_today = today.len();
+            if !shifts.is_empty() {
+                report.planned.push(PlannedChange::ShiftDue {
+                    card_ids: shifts.iter().map(|(card_id, ..)| *card_id).collect(),
+                    days,
+                    preserve_interval: options.preserve_interval,
+                });
+            }
+            if !today.is_empty() {
+                report.planned.push(PlannedChange::SetDueDate {
+                    card_ids: today,
+                    days: "0".to_string(),
+                });
+            }
+            return Ok(report);
+        }
+
+        if let Some(dir) = &self.options.journal_dir {
+            let changed: Vec<i64> = shifts
+                .iter()
+                .map(|(card_id, ..)| *card_id)
+                .chain(today.iter().copied())
+                .collect();
+            if !changed.is_empty() {
+                let mut record = Journal::new(operation);
+                record.entries = journal::record_scheduling(self.client, &changed).await?;
+                report.journal = Some(record.write(dir)?);
+            }
+        }
 
-            curr[j] = (prev[j] + 1).min(curr[j - 1] + 1).min(prev[j - 1] + cost);
+        for (card_id, due, interval) in shifts {
+            let due = due.to_string();
+            let result = match interval {
+                Some(interval) => {
+                    let interval = interval.to_string();
+                    self.client
+                        .cards()
+                        .set_specific_value(card_id, &["due", "ivl"], &[&due, &interval], true)
+                        .await
+                }
+                None => {
+                    self.client
+                        .cards()
+                        .set_specific_value(card_id, &["due"], &[&due], true)
+                        .await
+                }
+            };
+            match result {
+                Ok(results) if results.iter().all(|&ok| ok) => report.cards_shifted += 1,
+                _ => report.failed.push(card_id),
+            }
+        }
+        if !today.is_empty() {
+            if self
+                .client
+                .cards()
+                .set_due_date(&today, DueSpec::today())
+                .await?
+            {
+                report.cards_due_today = today.len();
+            } else {
+                report.failed.extend(today);
+            }
         }
 
-        std::mem::swap(&mut prev, &mut curr);
+        Ok(report)
     }
 
-    prev[n]
+    /// Reset ease factors to a single target, repairing "ease hell".
+    ///
+    /// Every review card matching the query gets `target_ease` (e.g., 2500
+    /// for 250%), the common fix once repeated "Hard" and "Again" answers
+    /// have driven eases down. New cards have no ease and are ignored.
+    ///
+    /// Raising ease lengthens every future interval at once, and lowering
+    /// it shortens them. With `adjust_intervals`, each card's interval is
+    /// scaled by `old_ease / target_ease` so its next "Good" interval stays
+    /// about where it was; the new ease only takes effect from there, and
+    /// the review load doesn't spike.
+    ///
+    /// Respects [`EngineOptions::dry_run`] and records an undo journal of
+    /// the cards' scheduling when [`EngineOptions::journal_dir`] is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - Anki search query selecting cards
+    /// * `target_ease` - Ease factor to set, in permille (e.g., 2500)
+    /// * `adjust_intervals` - Scale intervals to keep the review load steady
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Validation`] if `target_ease` is below Anki's
+    /// minimum of 1300.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let report = engine
+    ///     .progress()
+    ///     .reset_ease("deck:Japanese", 2500, true)
+    ///     .await?;
+    /// println!("Reset {} cards", report.cards_reset);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn reset_ease(
+        &self,
+        query: &str,
+        target_ease: i64,
+        adjust_intervals: bool,
+    ) -> Result<EaseResetReport> {
+        if target_ease < MIN_EASE {
+            return Err(Error::Validation(format!(
+                "target ease {} is below the minimum of {}",
+                target_ease, MIN_EASE
+            )));
+        }
+        let mut report = EaseResetReport {
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+
+        let card_ids = self.client.cards().find(query).await?;
+        if card_ids.is_empty() {
+            return Ok(report);
+        }
+        let cards = self.client.cards().info(&card_ids).await?;
+
+        let mut reset = Vec::new();
+        let mut intervals: Vec<(i64, i64)> = Vec::new();
+        for card in &cards {
+            if card.card_type == 0 || card.ease_factor <= 0 {
+                continue;
+            }
+            report.cards_checked += 1;
+            if card.ease_factor == target_ease {
+                report.already_at_target += 1;
+                continue;
+            }
+            reset.push(card.card_id);
+            if adjust_intervals && card.interval > 0 {
+                let scaled = (card.interval as f64 * card.ease_factor as f64 / target_ease as f64)
+                    .round() as i64;
+                let scaled = scaled.max(1);
+                if scaled != card.interval {
+                    intervals.push((card.card_id, scaled));
+                }
+            }
+        }
+
+        if report.dry_run {
+            report.cards_reset = reset.len();
+            report.intervals_adjusted = intervals.len();
+            if !reset.is_empty() {
+                report.planned.push(PlannedChange::SetEase {
+                    ease_factors: vec![target_ease; reset.len()],
+                    card_ids: reset,
+                });
+            }
+            if !intervals.is_empty() {
+                report.planned.push(PlannedChange::SetIntervals {
+                    card_ids: intervals.iter().map(|(card_id, _)| *card_id).collect(),
+                    intervals: intervals.iter().map(|(_, interval)| *interval).collect(),
+                });
+            }
+            return Ok(report);
+        }
+
+        if reset.is_empty() {
+            return Ok(report);
+        }
+        if let Some(dir) = &self.options.journal_dir {
+            let mut record = Journal::new("reset_ease");
+            record.entries = journal::record_scheduling(self.client, &reset).await?;
+            report.journal = Some(record.write(dir)?);
+        }
+
+        let mut failed = HashSet::new();
+        for chunk in reset.chunks(EASE_CHUNK_SIZE) {
+            let results = self
+                .client
+                .cards()
+                .set_ease(chunk, &vec![target_ease; chunk.len()])
+                .await?;
+            for (card_id, ok) in chunk.iter().zip(results) {
+                if !ok {
+                    failed.insert(*card_id);
+                }
+            }
+        }
+        report.cards_reset = reset.len() - failed.len();
+
+        for (card_id, interval) in intervals {
+            if failed.contains(&card_id) {
+                continue;
+            }
+            let interval = interval.to_string();
+            match self
+                .client
+                .cards()
+                .set_specific_value(card_id, &["ivl"], &[&interval], true)
+                .await
+            {
+                Ok(results) if results.iter().all(|&ok| ok) => report.intervals_adjusted += 1,
+                _ => {
+                    failed.insert(card_id);
+                }
+            }
+        }
+
+        report.failed = failed.into_iter().collect();
+        report.failed.sort_unstable();
+        Ok(report)
+    }
 }
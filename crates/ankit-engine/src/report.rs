@@ -0,0 +1,236 @@
+//! A common interface over workflow reports.
+//!
+//! Every workflow returns its own report type with fields specific to what it
+//! did. [`WorkflowReport`] is implemented by all of them, so front ends can
+//! present any result the same way: a one-line summary, the notes and cards
+//! involved, a JSON form, and a multi-line rendering that includes planned
+//! changes and the undo journal.
+//!
+//! [`ReportDocument`] renders study, audit, and health reports as a
+//! standalone HTML page or Markdown, for sharing outside the terminal.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ankit_engine::Engine;
+//! use ankit_engine::report::WorkflowReport;
+//!
+//! # async fn example() -> ankit_engine::Result<()> {
+//! let engine = Engine::new();
+//!
+//! let reports: Vec<Box<dyn WorkflowReport>> = vec![
+//!     Box::new(engine.progress().reset_deck("Japanese").await?),
+//!     Box::new(engine.organize().merge_decks(&["A", "B"], "Combined").await?),
+//! ];
+//! for report in &reports {
+//!     println!("{}", report.summary());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::changes::PlannedChange;
+use serde::Serialize;
+use std::fmt::Write;
+use std::path::Path;
+
+#[cfg(any(feature = "analyze", feature = "progress"))]
+mod render;
+
+#[cfg(any(feature = "analyze", feature = "progress"))]
+pub use render::{ReportDocument, ReportFormat};
+
+/// Note and card IDs a workflow changed, or would change in a dry run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AffectedIds {
+    /// Affected note IDs, sorted and without duplicates.
+    pub note_ids: Vec<i64>,
+    /// Affected card IDs, sorted and without duplicates.
+    pub card_ids: Vec<i64>,
+}
+
+impl AffectedIds {
+    /// Collect the IDs touched by a list of planned changes.
+    pub fn from_planned(planned: &[PlannedChange]) -> Self {
+        let mut ids = Self::default();
+        for change in planned {
+            ids.note_ids.extend(change.note_ids());
+            ids.card_ids.extend(change.card_ids());
+        }
+        ids.normalize()
+    }
+
+    /// Whether no notes or cards were affected.
+    pub fn is_empty(&self) -> bool {
+        self.note_ids.is_empty() && self.card_ids.is_empty()
+    }
+
+    /// Sort and deduplicate both lists.
+    pub(crate) fn normalize(mut self) -> Self {
+        self.note_ids.sort_unstable();
+        self.note_ids.dedup();
+        self.card_ids.sort_unstable();
+        self.card_ids.dedup();
+        self
+    }
+}
+
+/// Behavior shared by every workflow report.
+///
+/// Reports only keep counts for most real runs, so [`affected`](Self::affected)
+/// is most complete in dry runs, where it is derived from the planned changes.
+pub trait WorkflowReport {
+    /// One-line human-readable summary of the result.
+    fn summary(&self) -> String;
+
+    /// Extra lines for [`render`](Self::render), such as individual failures.
+    fn details(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Notes and cards the workflow changed.
+    fn affected(&self) -> AffectedIds {
+        AffectedIds::from_planned(self.planned())
+    }
+
+    /// Whether the report describes a dry run.
+    fn dry_run(&self) -> bool {
+        false
+    }
+
+    /// Changes that would have been made, populated in dry runs.
+    fn planned(&self) -> &[PlannedChange] {
+        &[]
+    }
+
+    /// Undo journal written before the workflow ran, if any.
+    fn journal(&self) -> Option<&Path> {
+        None
+    }
+
+    /// The full report as JSON.
+    fn to_json(&self) -> serde_json::Result<serde_json::Value>;
+
+    /// Multi-line human-readable rendering: the summary, details, planned
+    /// changes, and journal path.
+    fn render(&self) -> String {
+        let mut out = String::new();
+        if self.dry_run() {
+            out.push_str("Dry run: ");
+        }
+        out.push_str(&self.summary());
+        for line in self.details() {
+            let _ = write!(out, "\n  {}", line);
+        }
+        let planned = self.planned();
+        if !planned.is_empty() {
+            out.push_str("\nPlanned changes:");
+            for change in planned {
+                let _ = write!(out, "\n  - {}", change);
+            }
+        }
+        if let Some(journal) = self.journal() {
+            let _ = write!(out, "\nUndo journal: {}", journal.display());
+        }
+        out
+    }
+}
+
+/// Implements [`WorkflowReport::to_json`] and, for reports that have the
+/// fields, the dry-run and journal accessors.
+macro_rules! report_fields {
+    () => {
+        fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+            serde_json::to_value(self)
+        }
+    };
+    (dry_run) => {
+        $crate::report::report_fields!();
+
+        fn dry_run(&self) -> bool {
+            self.dry_run
+        }
+
+        fn planned(&self) -> &[$crate::changes::PlannedChange] {
+            &self.planned
+        }
+    };
+    (dry_run, journal) => {
+        $crate::report::report_fields!(dry_run);
+
+        fn journal(&self) -> Option<&std::path::Path> {
+            self.journal.as_deref()
+        }
+    };
+}
+
+#[allow(unused_imports)]
+pub(crate) use report_fields;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct TestReport {
+        deleted: usize,
+        journal: Option<std::path::PathBuf>,
+        dry_run: bool,
+        planned: Vec<PlannedChange>,
+    }
+
+    impl WorkflowReport for TestReport {
+        fn summary(&self) -> String {
+            format!("Deleted {} notes", self.deleted)
+        }
+
+        report_fields!(dry_run, journal);
+    }
+
+    #[test]
+    fn test_render_dry_run() {
+        let report = TestReport {
+            deleted: 2,
+            journal: None,
+            dry_run: true,
+            planned: vec![
+                PlannedChange::DeleteNotes {
+                    note_ids: vec![3, 1],
+                },
+                PlannedChange::MoveCards {
+                    card_ids: vec![10],
+                    deck: "Archive".to_string(),
+                },
+            ],
+        };
+
+        assert_eq!(
+            report.render(),
+            "Dry run: Deleted 2 notes\nPlanned changes:\n  - delete 2 notes\n  - move 1 cards to 'Archive'"
+        );
+        assert_eq!(
+            report.affected(),
+            AffectedIds {
+                note_ids: vec![1, 3],
+                card_ids: vec![10],
+            }
+        );
+        assert_eq!(report.to_json().unwrap()["deleted"], 2);
+    }
+
+    #[test]
+    fn test_render_with_journal() {
+        let report = TestReport {
+            deleted: 1,
+            journal: Some("/tmp/journal.json".into()),
+            dry_run: false,
+            planned: Vec::new(),
+        };
+
+        assert_eq!(
+            report.render(),
+            "Deleted 1 notes\nUndo journal: /tmp/journal.json"
+        );
+        assert!(report.affected().is_empty());
+    }
+}
@@ -0,0 +1,453 @@
+//! Self-contained HTML and Markdown reports.
+//!
+//! A [`ReportDocument`] collects study statistics, deck audits, and health
+//! reports into one document with tables and bar charts. The HTML form is a
+//! single file with inline CSS and SVG charts, so it can be attached to an
+//! email or opened offline; the Markdown form draws charts as text bars.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ankit_engine::Engine;
+//! use ankit_engine::report::ReportDocument;
+//!
+//! # async fn example() -> ankit_engine::Result<()> {
+//! let engine = Engine::new();
+//! let html = ReportDocument::new("Weekly Japanese summary")
+//!     .study(&engine.analyze().study_report("Japanese", 7).await?)
+//!     .audit(&engine.analyze().deck_audit("Japanese").await?)
+//!     .health(&engine.progress().deck_health("Japanese").await?)
+//!     .to_html();
+//! std::fs::write("weekly.html", html)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt::Write;
+
+#[cfg(feature = "analyze")]
+use crate::analyze::{DeckAudit, StudyReport};
+#[cfg(feature = "progress")]
+use crate::progress::HealthReport;
+
+/// Width of the longest bar in Markdown charts, in characters.
+const MARKDOWN_BAR_WIDTH: usize = 20;
+
+/// Output format for a [`ReportDocument`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportFormat {
+    /// A standalone HTML page with inline CSS and SVG charts.
+    #[default]
+    Html,
+    /// GitHub-flavored Markdown with text bar charts.
+    Markdown,
+}
+
+/// A report document built from one or more workflow reports.
+///
+/// Sections appear in the order they are added.
+#[derive(Debug, Clone)]
+pub struct ReportDocument {
+    title: String,
+    sections: Vec<Section>,
+}
+
+#[derive(Debug, Clone)]
+struct Section {
+    heading: String,
+    blocks: Vec<Block>,
+}
+
+#[derive(Debug, Clone)]
+enum Block {
+    Table {
+        headers: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+    Chart {
+        title: String,
+        bars: Vec<(String, f64)>,
+    },
+}
+
+impl ReportDocument {
+    /// Create an empty document with a title.
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            sections: Vec::new(),
+        }
+    }
+
+    /// Add a section for a study report: activity, performance, upcoming
+    /// workload, and a chart of daily reviews.
+    #[cfg(feature = "analyze")]
+    pub fn study(mut self, report: &StudyReport) -> Self {
+        let mut blocks = vec![metrics(vec![
+            ("Period", format!("{} days", report.period_days)),
+            ("Reviews", report.total_reviews.to_string()),
+            ("Time studied", format!("{} min", report.total_time_minutes)),
+            (
+                "Reviews per day",
+                format!("{:.1}", report.average_reviews_per_day),
+            ),
+            ("Study streak", format!("{} days", report.study_streak)),
+            ("Retention", percent(report.retention_rate)),
+            ("Average ease", ease(report.average_ease)),
+            ("New cards studied", report.new_cards_studied.to_string()),
+            (
+                "Review cards studied",
+                report.review_cards_studied.to_string(),
+            ),
+            ("Relearning cards", report.relearning_cards.to_string()),
+            ("Leeches", report.leeches.len().to_string()),
+            ("Low-ease cards", report.low_ease_cards.len().to_string()),
+            ("Due tomorrow", report.due_tomorrow.to_string()),
+            ("Due this week", report.due_this_week.to_string()),
+        ])];
+        if !report.daily_stats.is_empty() {
+            blocks.push(Block::Chart {
+                title: "Reviews per day".to_string(),
+                bars: report
+                    .daily_stats
+                    .iter()
+                    .map(|day| (day.date.clone(), day.reviews as f64))
+                    .collect(),
+            });
+        }
+        self.sections.push(Section {
+            heading: format!("Study activity: {}", report.deck),
+            blocks,
+        });
+        self
+    }
+
+    /// Add a section for a deck audit: contents, scheduling state, a card
+    /// maturity chart, and breakdowns by note type, tag, and empty field.
+    #[cfg(feature = "analyze")]
+    pub fn audit(mut self, audit: &DeckAudit) -> Self {
+        let mut blocks = vec![metrics(vec![
+            ("Cards", audit.total_cards.to_string()),
+            ("Notes", audit.total_notes.to_string()),
+            ("New", audit.new_cards.to_string()),
+            ("Learning", audit.learning_cards.to_string()),
+            ("Review", audit.review_cards.to_string()),
+            ("Suspended", audit.suspended_count.to_string()),
+            ("Leeches", audit.leech_count.to_string()),
+            ("Possible duplicates", audit.duplicate_count.to_string()),
+            ("Untagged notes", audit.untagged_notes.to_string()),
+            ("Average ease", ease(audit.average_ease)),
+        ])];
+        if audit.maturity.histogram.iter().any(|bin| bin.cards > 0) {
+            blocks.push(Block::Chart {
+                title: "Review intervals".to_string(),
+                bars: audit
+                    .maturity
+                    .histogram
+                    .iter()
+                    .map(|bin| (bin.label.clone(), bin.cards as f64))
+                    .collect(),
+            });
+        }
+        for (label, counts) in [
+            ("Note type", &audit.cards_by_model),
+            ("Tag", &audit.tag_distribution),
+            ("Empty field", &audit.empty_field_counts),
+        ] {
+            if !counts.is_empty() {
+                let mut rows: Vec<(&String, &usize)> = counts.iter().collect();
+                rows.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+                blocks.push(Block::Table {
+                    headers: vec![label.to_string(), "Count".to_string()],
+                    rows: rows
+                        .into_iter()
+                        .map(|(name, count)| vec![name.clone(), count.to_string()])
+                        .collect(),
+                });
+            }
+        }
+        self.sections.push(Section {
+            heading: format!("Deck audit: {}", audit.deck),
+            blocks,
+        });
+        self
+    }
+
+    /// Add a section for a deck health report, with a chart of card states.
+    #[cfg(feature = "progress")]
+    pub fn health(mut self, report: &HealthReport) -> Self {
+        let blocks = vec![
+            metrics(vec![
+                ("Cards", report.total_cards.to_string()),
+                ("Average ease", ease(report.avg_ease as f64)),
+                ("Average interval", format!("{} days", report.avg_interval)),
+                ("Leeches", report.leech_count.to_string()),
+                ("Total reviews", report.total_reps.to_string()),
+                ("Total lapses", report.total_lapses.to_string()),
+            ]),
+            Block::Chart {
+                title: "Card states".to_string(),
+                bars: [
+                    ("New", report.new_cards),
+                    ("Learning", report.learning_cards),
+                    ("Review", report.review_cards),
+                    ("Suspended", report.suspended_cards),
+                    ("Buried", report.buried_cards),
+                ]
+                .into_iter()
+                .map(|(label, count)| (label.to_string(), count as f64))
+                .collect(),
+            },
+        ];
+        self.sections.push(Section {
+            heading: format!("Deck health: {}", report.deck),
+            blocks,
+        });
+        self
+    }
+
+    /// Render the document in the given format.
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Html => self.to_html(),
+            ReportFormat::Markdown => self.to_markdown(),
+        }
+    }
+
+    /// Render a standalone HTML page.
+    pub fn to_html(&self) -> String {
+        let mut out = String::new();
+        let _ = write!(
+            out,
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+            escape(&self.title),
+            STYLE,
+            escape(&self.title)
+        );
+        for section in &self.sections {
+            let _ = writeln!(out, "<section>\n<h2>{}</h2>", escape(&section.heading));
+            for block in &section.blocks {
+                match block {
+                    Block::Table { headers, rows } => html_table(&mut out, headers, rows),
+                    Block::Chart { title, bars } => svg_chart(&mut out, title, bars),
+                }
+            }
+            out.push_str("</section>\n");
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    /// Render GitHub-flavored Markdown.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n", self.title);
+        for section in &self.sections {
+            let _ = write!(out, "\n## {}\n", section.heading);
+            for block in &section.blocks {
+                out.push('\n');
+                match block {
+                    Block::Table { headers, rows } => markdown_table(&mut out, headers, rows),
+                    Block::Chart { title, bars } => {
+                        let _ = writeln!(out, "**{}**\n", title);
+                        let max = bars.iter().map(|(_, v)| *v).fold(0.0, f64::max);
+                        let rows: Vec<Vec<String>> = bars
+                            .iter()
+                            .map(|(label, value)| {
+                                let width = if max > 0.0 {
+                                    (value / max * MARKDOWN_BAR_WIDTH as f64).round() as usize
+                                } else {
+                                    0
+                                };
+                                vec![label.clone(), number(*value), "█".repeat(width)]
+                            })
+                            .collect();
+                        markdown_table(
+                            &mut out,
+                            &["".to_string(), "Count".to_string(), "".to_string()],
+                            &rows,
+                        );
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+/// A two-column table of labelled values.
+fn metrics(rows: Vec<(&str, String)>) -> Block {
+    Block::Table {
+        headers: vec!["Metric".to_string(), "Value".to_string()],
+        rows: rows
+            .into_iter()
+            .map(|(label, value)| vec![label.to_string(), value])
+            .collect(),
+    }
+}
+
+#[cfg(feature = "analyze")]
+fn percent(rate: f64) -> String {
+    format!("{:.1}%", rate * 100.0)
+}
+
+/// Format an ease factor stored as permille (2500 = 250%).
+fn ease(factor: f64) -> String {
+    format!("{:.0}%", factor / 10.0)
+}
+
+fn number(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{:.0}", value)
+    } else {
+        format!("{:.1}", value)
+    }
+}
+
+const STYLE: &str = "body{font-family:-apple-system,'Segoe UI',Helvetica,Arial,sans-serif;\
+max-width:760px;margin:2em auto;padding:0 1em;color:#222}\
+h2{border-bottom:1px solid #ddd;padding-bottom:.3em;margin-top:2em}\
+table{border-collapse:collapse;margin:1em 0;min-width:50%}\
+th,td{border:1px solid #ddd;padding:.3em .7em;text-align:left}\
+td:last-child{text-align:right}th{background:#f5f5f5}\
+figure{margin:1em 0}figcaption{font-weight:600;margin-bottom:.3em}\
+svg text{font-size:11px;fill:#555}";
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn html_table(out: &mut String, headers: &[String], rows: &[Vec<String>]) {
+    out.push_str("<table>\n<tr>");
+    for header in headers {
+        let _ = write!(out, "<th>{}</th>", escape(header));
+    }
+    out.push_str("</tr>\n");
+    for row in rows {
+        out.push_str("<tr>");
+        for cell in row {
+            let _ = write!(out, "<td>{}</td>", escape(cell));
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table>\n");
+}
+
+/// Draw a vertical bar chart as inline SVG.
+fn svg_chart(out: &mut String, title: &str, bars: &[(String, f64)]) {
+    const WIDTH: f64 = 720.0;
+    const HEIGHT: f64 = 180.0;
+    const LABELS: f64 = 20.0;
+
+    let max = bars.iter().map(|(_, v)| *v).fold(0.0, f64::max);
+    let slot = WIDTH / bars.len().max(1) as f64;
+    // Label at most about 12 bars so dates don't overlap
+    let label_every = bars.len().div_ceil(12).max(1);
+
+    let _ = write!(
+        out,
+        "<figure>\n<figcaption>{}</figcaption>\n\
+         <svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {} {}\" role=\"img\">\n",
+        escape(title),
+        WIDTH,
+        HEIGHT + LABELS
+    );
+    for (i, (label, value)) in bars.iter().enumerate() {
+        let height = if max > 0.0 { value / max * HEIGHT } else { 0.0 };
+        let x = i as f64 * slot;
+        let _ = writeln!(
+            out,
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#4a7bd0\">\
+             <title>{}: {}</title></rect>",
+            x + slot * 0.1,
+            HEIGHT - height,
+            slot * 0.8,
+            height,
+            escape(label),
+            number(*value)
+        );
+        if i % label_every == 0 {
+            let _ = writeln!(
+                out,
+                "<text x=\"{:.1}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+                x + slot / 2.0,
+                HEIGHT + LABELS - 5.0,
+                escape(label)
+            );
+        }
+    }
+    out.push_str("</svg>\n</figure>\n");
+}
+
+fn markdown_table(out: &mut String, headers: &[String], rows: &[Vec<String>]) {
+    let cell = |text: &str| text.replace('|', "\\|");
+    let _ = writeln!(
+        out,
+        "| {} |",
+        headers
+            .iter()
+            .map(|h| cell(h))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    );
+    let _ = writeln!(
+        out,
+        "|{}",
+        headers
+            .iter()
+            .enumerate()
+            .map(|(i, _)| if i == 0 { " --- |" } else { " ---: |" })
+            .collect::<String>()
+    );
+    for row in rows {
+        let _ = writeln!(
+            out,
+            "| {} |",
+            row.iter().map(|c| cell(c)).collect::<Vec<_>>().join(" | ")
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document() -> ReportDocument {
+        ReportDocument {
+            title: "Weekly <summary>".to_string(),
+            sections: vec![Section {
+                heading: "Japanese".to_string(),
+                blocks: vec![
+                    metrics(vec![("Reviews", "12".to_string())]),
+                    Block::Chart {
+                        title: "Reviews per day".to_string(),
+                        bars: vec![("Mon".to_string(), 10.0), ("Tue".to_string(), 5.0)],
+                    },
+                ],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_to_html() {
+        let html = document().to_html();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<h1>Weekly &lt;summary&gt;</h1>"));
+        assert!(html.contains("<td>Reviews</td><td>12</td>"));
+        assert_eq!(html.matches("<rect").count(), 2);
+        assert!(html.contains("<title>Tue: 5</title>"));
+        assert!(!html.contains("<script"));
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let markdown = document().render(ReportFormat::Markdown);
+        assert!(markdown.starts_with("# Weekly <summary>\n\n## Japanese\n"));
+        assert!(markdown.contains("| Metric | Value |\n| --- | ---: |\n| Reviews | 12 |\n"));
+        assert!(markdown.contains(&format!("| Mon | 10 | {} |", "█".repeat(20))));
+        assert!(markdown.contains(&format!("| Tue | 5 | {} |", "█".repeat(10))));
+    }
+}
@@ -0,0 +1,332 @@
+//! Configurable validation rules for notes.
+//!
+//! [`ValidationRules`] describe what a well-formed note looks like: required
+//! fields, per-field patterns and length limits, fields that must be plain
+//! text, and a tag whitelist. Rules are built in code or loaded from TOML,
+//! and [`ImportEngine::validate_with`](crate::import::ImportEngine::validate_with)
+//! checks them alongside the usual deck and model checks.
+//!
+//! # Example
+//!
+//! ```
+//! use ankit_engine::NoteBuilder;
+//! use ankit_engine::rules::{Rule, ValidationRules};
+//!
+//! let rules = ValidationRules::from_toml(r#"
+//!     allowed_tags = ["verb", "noun"]
+//!
+//!     [fields.Front]
+//!     required = true
+//!     html = false
+//!
+//!     [fields.Audio]
+//!     pattern = '^\[sound:[^\]]+\]$'
+//! "#).unwrap();
+//!
+//! let note = NoteBuilder::new("Spanish", "Vocab")
+//!     .field("Front", "<b>hablar</b>")
+//!     .field("Audio", "hablar.mp3")
+//!     .tag("verb")
+//!     .build();
+//!
+//! let violations = rules.check(&note).unwrap();
+//! assert_eq!(violations.len(), 2);
+//! assert_eq!(violations[0].rule, Rule::Pattern);
+//! assert_eq!(violations[1].rule, Rule::Html);
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+
+use regex_lite::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::normalize::{NormalizeOptions, normalize};
+use crate::{Error, Note, Result};
+
+/// Rules a note must satisfy.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ValidationRules {
+    /// Only check notes of this note type. `None` checks every note.
+    pub model: Option<String>,
+    /// Tags a note may have. `None` allows any tag.
+    pub allowed_tags: Option<Vec<String>>,
+    /// Rules for individual fields, keyed by field name.
+    pub fields: BTreeMap<String, FieldRule>,
+}
+
+/// Rules for a single field.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FieldRule {
+    /// The field must have text or media.
+    pub required: bool,
+    /// Regular expression a non-empty value must match.
+    pub pattern: Option<String>,
+    /// Maximum length in characters, including any HTML.
+    pub max_length: Option<usize>,
+    /// Whether HTML tags are allowed.
+    pub html: bool,
+}
+
+impl Default for FieldRule {
+    fn default() -> Self {
+        Self {
+            required: false,
+            pattern: None,
+            max_length: None,
+            html: true,
+        }
+    }
+}
+
+/// Kind of rule a note broke.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rule {
+    /// A required field is missing or empty.
+    Required,
+    /// A field doesn't match its pattern.
+    Pattern,
+    /// A field is longer than its maximum length.
+    MaxLength,
+    /// A plain-text field contains HTML.
+    Html,
+    /// The note has a tag outside the whitelist.
+    Tag,
+}
+
+/// A broken rule.
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleViolation {
+    /// Which rule was broken.
+    pub rule: Rule,
+    /// The field involved; `None` for tag violations.
+    pub field: Option<String>,
+    /// Human-readable description.
+    pub message: String,
+}
+
+impl ValidationRules {
+    /// Create an empty rule set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse rules from TOML.
+    pub fn from_toml(toml: &str) -> Result<Self> {
+        toml::from_str(toml).map_err(|e| Error::Validation(format!("invalid rules: {}", e)))
+    }
+
+    /// Only check notes of `model`.
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Require `field` to have text or media.
+    pub fn required(mut self, field: impl Into<String>) -> Self {
+        self.field_rule(field).required = true;
+        self
+    }
+
+    /// Require non-empty values of `field` to match `pattern`.
+    pub fn pattern(mut self, field: impl Into<String>, pattern: impl Into<String>) -> Self {
+        self.field_rule(field).pattern = Some(pattern.into());
+        self
+    }
+
+    /// Limit `field` to `max` characters.
+    pub fn max_length(mut self, field: impl Into<String>, max: usize) -> Self {
+        self.field_rule(field).max_length = Some(max);
+        self
+    }
+
+    /// Disallow HTML in `field`.
+    pub fn no_html(mut self, field: impl Into<String>) -> Self {
+        self.field_rule(field).html = false;
+        self
+    }
+
+    /// Only allow these tags.
+    pub fn allowed_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed_tags = Some(tags.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Check a note against the rules.
+    ///
+    /// Returns an error if a pattern is not a valid regular expression.
+    pub fn check(&self, note: &Note) -> Result<Vec<RuleViolation>> {
+        Ok(self.check_compiled(note, &self.compile()?))
+    }
+
+    /// Compile every field pattern.
+    pub(crate) fn compile(&self) -> Result<HashMap<&str, Regex>> {
+        let mut patterns = HashMap::new();
+        for (field, rule) in &self.fields {
+            if let Some(pattern) = &rule.pattern {
+                let regex = Regex::new(pattern).map_err(|e| {
+                    Error::Validation(format!("invalid pattern for field '{}': {}", field, e))
+                })?;
+                patterns.insert(field.as_str(), regex);
+            }
+        }
+        Ok(patterns)
+    }
+
+    /// Check a note using patterns from [`compile`](Self::compile).
+    pub(crate) fn check_compiled(
+        &self,
+        note: &Note,
+        patterns: &HashMap<&str, Regex>,
+    ) -> Vec<RuleViolation> {
+        let mut violations = Vec::new();
+        if self.model.as_ref().is_some_and(|m| *m != note.model_name) {
+            return violations;
+        }
+
+        for (field, rule) in &self.fields {
+            let value = note.fields.get(field).map_or("", String::as_str);
+            let mut violation = |rule: Rule, message: String| {
+                violations.push(RuleViolation {
+                    rule,
+                    field: Some(field.clone()),
+                    message,
+                });
+            };
+
+            if is_empty(value) {
+                if rule.required {
+                    violation(Rule::Required, format!("Field '{}' is required", field));
+                }
+                continue;
+            }
+            if let Some(regex) = patterns.get(field.as_str()) {
+                if !regex.is_match(value) {
+                    violation(
+                        Rule::Pattern,
+                        format!("Field '{}' does not match '{}'", field, regex.as_str()),
+                    );
+                }
+            }
+            if let Some(max) = rule.max_length {
+                let length = value.chars().count();
+                if length > max {
+                    violation(
+                        Rule::MaxLength,
+                        format!("Field '{}' is {} characters (max {})", field, length, max),
+                    );
+                }
+            }
+            if !rule.html && has_html(value) {
+                violation(Rule::Html, format!("Field '{}' contains HTML", field));
+            }
+        }
+
+        if let Some(allowed) = &self.allowed_tags {
+            for tag in &note.tags {
+                if !allowed.iter().any(|a| a.eq_ignore_ascii_case(tag)) {
+                    violations.push(RuleViolation {
+                        rule: Rule::Tag,
+                        field: None,
+                        message: format!("Tag '{}' is not allowed", tag),
+                    });
+                }
+            }
+        }
+
+        violations
+    }
+
+    fn field_rule(&mut self, field: impl Into<String>) -> &mut FieldRule {
+        self.fields.entry(field.into()).or_default()
+    }
+}
+
+/// Whether a field has neither text nor media.
+fn is_empty(value: &str) -> bool {
+    normalize(value, &NormalizeOptions::plain_text()).is_empty()
+        && !value.contains("<img")
+        && !value.contains("[sound:")
+}
+
+/// Whether a value contains an HTML tag or entity.
+fn has_html(value: &str) -> bool {
+    let tag = Regex::new(r"</?[a-zA-Z][^>]*>|&[a-zA-Z]+;|&#[0-9]+;").unwrap();
+    tag.is_match(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NoteBuilder;
+
+    fn note(fields: &[(&str, &str)]) -> Note {
+        let mut builder = NoteBuilder::new("Default", "Basic");
+        for (name, value) in fields {
+            builder = builder.field(*name, *value);
+        }
+        builder.build()
+    }
+
+    #[test]
+    fn test_required_allows_media_only_fields() {
+        let rules = ValidationRules::new().required("Front").required("Back");
+        let violations = rules
+            .check(&note(&[
+                ("Front", "<img src=\"a.png\">"),
+                ("Back", "<br>&nbsp;"),
+            ]))
+            .unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, Rule::Required);
+        assert_eq!(violations[0].field.as_deref(), Some("Back"));
+    }
+
+    #[test]
+    fn test_max_length_and_html() {
+        let rules = ValidationRules::new()
+            .max_length("Front", 5)
+            .no_html("Front");
+        let violations = rules.check(&note(&[("Front", "a&amp;b")])).unwrap();
+        let kinds: Vec<Rule> = violations.iter().map(|v| v.rule).collect();
+        assert_eq!(kinds, vec![Rule::MaxLength, Rule::Html]);
+        assert!(
+            rules
+                .check(&note(&[("Front", "a < b")]))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_model_filter_and_tags() {
+        let rules = ValidationRules::new()
+            .model("Cloze")
+            .required("Text")
+            .allowed_tags(["verb"]);
+        assert!(rules.check(&note(&[])).unwrap().is_empty());
+
+        let rules = rules.model("Basic");
+        let mut note = note(&[("Text", "x")]);
+        note.tags = vec!["Verb".to_string(), "noun".to_string()];
+        let violations = rules.check(&note).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].message, "Tag 'noun' is not allowed");
+    }
+
+    #[test]
+    fn test_invalid_toml_and_pattern() {
+        assert!(ValidationRules::from_toml("[fields.Front]\nunknown = 1").is_err());
+        let rules = ValidationRules::new().pattern("Front", "(");
+        assert!(matches!(
+            rules.check(&note(&[("Front", "x")])),
+            Err(Error::Validation(_))
+        ));
+    }
+}
@@ -18,13 +18,24 @@
 //!
 //! // Regex search
 //! let notes = engine.search().regex("Back", r"^to\s+", None).await?;
+//!
+//! // Which fields matched, with highlighted snippets
+//! let matches = engine.search().notes("mangiare", &Default::default()).await?;
+//! for note in &matches {
+//!     for field in &note.fields {
+//!         println!("{} {}: {:?}", note.note_id, field.field, field.snippets);
+//!     }
+//! }
 //! # Ok(())
 //! # }
 //! ```
 
+use ankit::key::KeyNormalization;
 use ankit::{AnkiClient, NoteInfo, QueryBuilder};
+use regex_lite::Regex;
+use serde::Serialize;
 
-use crate::Result;
+use crate::{Error, Result};
 
 /// Content search engine for finding notes.
 #[derive(Debug)]
@@ -223,6 +234,45 @@ impl<'a> SearchEngine<'a> {
         self.execute_query(query).await
     }
 
+    /// Search note content and report which fields matched.
+    ///
+    /// Notes matching [`SearchOptions::filter`] are fetched and `pattern` is
+    /// matched against their fields here rather than by Anki, so it can be a
+    /// full regex, match whole words, and match text split by HTML tags.
+    /// Each matching field comes with snippets of the surrounding text.
+    ///
+    /// Returns an error if `pattern` is not a valid regex.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ankit_engine::search::SearchOptions;
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    ///
+    /// let options = SearchOptions {
+    ///     filter: "deck:Italian".to_string(),
+    ///     fields: vec!["Back".to_string()],
+    ///     regex: true,
+    ///     whole_word: true,
+    ///     ..Default::default()
+    /// };
+    /// for note in engine.search().notes(r"to (eat|drink)", &options).await? {
+    ///     println!("{}: {}", note.note_id, note.fields[0].snippets.join(" | "));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn notes(&self, pattern: &str, options: &SearchOptions) -> Result<Vec<NoteMatch>> {
+        let matcher = Matcher::new(pattern, options)?;
+        let notes = self.execute_query(&options.filter).await?;
+        Ok(notes
+            .iter()
+            .filter_map(|note| matcher.match_note(note, options))
+            .collect())
+    }
+
     /// Execute a query and return full note info.
     async fn execute_query(&self, query: &str) -> Result<Vec<NoteInfo>> {
         let note_ids = self.client.notes().find(query).await?;
@@ -233,3 +283,330 @@ impl<'a> SearchEngine<'a> {
         Ok(notes)
     }
 }
+
+/// Options for [`SearchEngine::notes()`].
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    /// Anki query selecting the notes to search (default: all notes).
+    pub filter: String,
+    /// Fields to search; empty searches every field.
+    pub fields: Vec<String>,
+    /// Treat the pattern as a regex rather than literal text.
+    pub regex: bool,
+    /// Match case exactly.
+    ///
+    /// Case-insensitive literal searches fold all of Unicode, while
+    /// case-insensitive regexes only fold ASCII letters.
+    pub case_sensitive: bool,
+    /// Only match whole words.
+    pub whole_word: bool,
+    /// Remove HTML tags before matching, so snippets are plain text.
+    pub strip_html: bool,
+    /// Characters of context on each side of a match in a snippet.
+    pub context: usize,
+    /// Maximum number of snippets per field; further matches are only
+    /// counted.
+    pub max_snippets: usize,
+    /// Text inserted before and after each match in a snippet.
+    pub highlight: (String, String),
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            filter: "deck:*".to_string(),
+            fields: Vec::new(),
+            regex: false,
+            case_sensitive: false,
+            whole_word: false,
+            strip_html: true,
+            context: 30,
+            max_snippets: 3,
+            highlight: ("**".to_string(), "**".to_string()),
+        }
+    }
+}
+
+/// A note whose content matched a search.
+#[derive(Debug, Clone, Serialize)]
+pub struct NoteMatch {
+    /// The note ID.
+    pub note_id: i64,
+    /// The note type (model) name.
+    pub model_name: String,
+    /// Tags on the note.
+    pub tags: Vec<String>,
+    /// Fields that matched, in note type order.
+    pub fields: Vec<FieldMatch>,
+}
+
+/// A field that matched a search.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldMatch {
+    /// Field name.
+    pub field: String,
+    /// Number of matches in the field.
+    pub matches: usize,
+    /// Matches with surrounding text, highlighted.
+    pub snippets: Vec<String>,
+}
+
+/// A compiled search pattern.
+struct Matcher {
+    regex: Regex,
+    /// Whether text is lowercased before matching.
+    fold: bool,
+}
+
+impl Matcher {
+    fn new(pattern: &str, options: &SearchOptions) -> Result<Self> {
+        // Literal text is folded along with the field, which handles all of
+        // Unicode; regexes can only use regex-lite's ASCII-only (?i)
+        let fold = !options.case_sensitive && !options.regex;
+        let mut source = if options.regex {
+            pattern.to_string()
+        } else if fold {
+            regex_lite::escape(&pattern.to_lowercase())
+        } else {
+            regex_lite::escape(pattern)
+        };
+        if options.whole_word {
+            source = format!(r"\b(?:{})\b", source);
+        }
+        if options.regex && !options.case_sensitive {
+            source = format!("(?i){}", source);
+        }
+        let regex = Regex::new(&source)
+            .map_err(|e| Error::Validation(format!("invalid search pattern: {}", e)))?;
+        Ok(Self { regex, fold })
+    }
+
+    /// The note's matching fields, or `None` if no field matched.
+    fn match_note(&self, note: &NoteInfo, options: &SearchOptions) -> Option<NoteMatch> {
+        let mut fields: Vec<_> = note
+            .fields
+            .iter()
+            .filter(|(name, _)| options.fields.is_empty() || options.fields.contains(name))
+            .collect();
+        fields.sort_by_key(|(_, field)| field.order);
+
+        let fields: Vec<FieldMatch> = fields
+            .into_iter()
+            .filter_map(|(name, field)| self.match_field(name, &field.value, options))
+            .collect();
+        if fields.is_empty() {
+            return None;
+        }
+        Some(NoteMatch {
+            note_id: note.note_id,
+            model_name: note.model_name.clone(),
+            tags: note.tags.clone(),
+            fields,
+        })
+    }
+
+    fn match_field(&self, name: &str, value: &str, options: &SearchOptions) -> Option<FieldMatch> {
+        let text = if options.strip_html {
+            KeyNormalization {
+                strip_html: true,
+                ..KeyNormalization::none()
+            }
+            .apply(value)
+        } else {
+            value.to_string()
+        };
+        let folded = Folded::new(&text, self.fold);
+
+        let mut matches = 0;
+        let mut snippets = Vec::new();
+        for found in self.regex.find_iter(&folded.text) {
+            if found.is_empty() {
+                continue;
+            }
+            matches += 1;
+            if snippets.len() < options.max_snippets {
+                let start = folded.original(found.start(), false);
+                let end = folded.original(found.end(), true);
+                snippets.push(snippet(&text, start, end, options));
+            }
+        }
+        (matches > 0).then(|| FieldMatch {
+            field: name.to_string(),
+            matches,
+            snippets,
+        })
+    }
+}
+
+/// Text as matched, with a map back to the original text's offsets.
+struct Folded {
+    text: String,
+    /// Offsets in `text` where each original character starts, with the
+    /// matching original offset.
+    starts: Vec<(usize, usize)>,
+}
+
+impl Folded {
+    fn new(text: &str, fold: bool) -> Self {
+        if !fold {
+            return Self {
+                text: text.to_string(),
+                starts: Vec::new(),
+            };
+        }
+        let mut folded = String::with_capacity(text.len());
+        let mut starts = Vec::with_capacity(text.len() + 1);
+        for (offset, c) in text.char_indices() {
+            starts.push((folded.len(), offset));
+            folded.extend(c.to_lowercase());
+        }
+        starts.push((folded.len(), text.len()));
+        Self {
+            text: folded,
+            starts,
+        }
+    }
+
+    /// The original offset for an offset in the folded text. An offset
+    /// inside a character that lowercased to several is moved to the start
+    /// of that character, or past it for the end of a match.
+    fn original(&self, offset: usize, end: bool) -> usize {
+        if self.starts.is_empty() {
+            return offset;
+        }
+        match self
+            .starts
+            .binary_search_by_key(&offset, |&(folded, _)| folded)
+        {
+            Ok(i) => self.starts[i].1,
+            Err(i) if end => self.starts[i].1,
+            Err(i) => self.starts[i - 1].1,
+        }
+    }
+}
+
+/// A match in `text` with up to `options.context` characters on each side.
+fn snippet(text: &str, start: usize, end: usize, options: &SearchOptions) -> String {
+    let before: String = {
+        let chars: Vec<char> = text[..start].chars().rev().take(options.context).collect();
+        chars.into_iter().rev().collect()
+    };
+    let after: String = text[end..].chars().take(options.context).collect();
+
+    let mut snippet = String::new();
+    if before.len() < start {
+        snippet.push('…');
+    }
+    snippet.push_str(&before);
+    snippet.push_str(&options.highlight.0);
+    snippet.push_str(&text[start..end]);
+    snippet.push_str(&options.highlight.1);
+    snippet.push_str(&after);
+    if end + after.len() < text.len() {
+        snippet.push('…');
+    }
+    snippet.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use ankit::NoteField;
+
+    use super::*;
+
+    fn note(fields: &[(&str, &str)]) -> NoteInfo {
+        NoteInfo {
+            note_id: 1,
+            model_name: "Basic".to_string(),
+            tags: vec![],
+            fields: fields
+                .iter()
+                .enumerate()
+                .map(|(order, (name, value))| {
+                    let field = NoteField {
+                        value: value.to_string(),
+                        order: order as i32,
+                    };
+                    (name.to_string(), field)
+                })
+                .collect::<HashMap<_, _>>(),
+            cards: vec![],
+        }
+    }
+
+    fn search(pattern: &str, note: &NoteInfo, options: &SearchOptions) -> Option<NoteMatch> {
+        Matcher::new(pattern, options)
+            .unwrap()
+            .match_note(note, options)
+    }
+
+    #[test]
+    fn test_literal_search_reports_fields() {
+        let note = note(&[
+            ("Front", "to <b>EAT</b>"),
+            ("Back", "mangiare"),
+            ("Extra", "eat up"),
+        ]);
+        let found = search("eat", &note, &SearchOptions::default()).unwrap();
+
+        let fields: Vec<_> = found.fields.iter().map(|f| f.field.as_str()).collect();
+        assert_eq!(fields, vec!["Front", "Extra"]);
+        assert_eq!(found.fields[0].snippets, vec!["to **EAT**"]);
+
+        let case_sensitive = SearchOptions {
+            case_sensitive: true,
+            ..Default::default()
+        };
+        let found = search("eat", &note, &case_sensitive).unwrap();
+        assert_eq!(found.fields.len(), 1);
+        assert_eq!(found.fields[0].field, "Extra");
+
+        assert!(search("beber", &note, &SearchOptions::default()).is_none());
+    }
+
+    #[test]
+    fn test_field_filter_and_whole_word() {
+        let note = note(&[("Front", "eaten"), ("Back", "eat")]);
+        let options = SearchOptions {
+            whole_word: true,
+            ..Default::default()
+        };
+        let found = search("eat", &note, &options).unwrap();
+        assert_eq!(found.fields.len(), 1);
+        assert_eq!(found.fields[0].field, "Back");
+
+        let options = SearchOptions {
+            fields: vec!["Front".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(search("eat", &note, &options).unwrap().fields.len(), 1);
+        assert!(search("eat.", &note, &options).is_none());
+    }
+
+    #[test]
+    fn test_regex_search() {
+        let note = note(&[("Back", "to eat, to drink, to sleep")]);
+        let options = SearchOptions {
+            regex: true,
+            max_snippets: 1,
+            context: 4,
+            highlight: ("[".to_string(), "]".to_string()),
+            ..Default::default()
+        };
+        let found = search(r"to (eat|drink)", &note, &options).unwrap();
+        assert_eq!(found.fields[0].matches, 2);
+        assert_eq!(found.fields[0].snippets, vec!["[to eat], to…"]);
+
+        let invalid = Matcher::new("(", &options);
+        assert!(matches!(invalid, Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn test_unicode_case_folding() {
+        let note = note(&[("Front", "Ärger and ÉCOLE")]);
+        let found = search("école", &note, &SearchOptions::default()).unwrap();
+        assert_eq!(found.fields[0].snippets, vec!["Ärger and **ÉCOLE**"]);
+    }
+}
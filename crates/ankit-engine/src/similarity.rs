@@ -0,0 +1,501 @@
+//! String similarity shared by the analysis, progress, and deduplication
+//! workflows.
+//!
+//! [`Algorithm`] selects how two strings are scored, from 0.0 (completely
+//! different) to 1.0 (identical). Strings are compared exactly, so callers
+//! normalize them first (see [`KeyNormalization`](ankit::key::KeyNormalization)).
+//!
+//! Comparing every pair of a large set of strings is quadratic. A
+//! [`Prefilter::Lsh`] only scores pairs that MinHash locality-sensitive
+//! hashing marks as candidates, which is much faster but may miss a few
+//! matches.
+//!
+//! # Example
+//!
+//! ```
+//! use ankit_engine::similarity::{Algorithm, Prefilter, pairs};
+//!
+//! let keys = ["the cat sat", "sat the cat", "a dog ran"];
+//! let found = pairs(&keys, Algorithm::TokenSet, 0.9, Prefilter::None);
+//! assert_eq!((found[0].left, found[0].right), (0, 1));
+//!
+//! assert!(Algorithm::JaroWinkler.similarity("martha", "marhta") > 0.95);
+//! ```
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// How two strings are scored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Algorithm {
+    /// Normalized Levenshtein distance: 0.9 when one character in ten
+    /// differs. Good for typos.
+    #[default]
+    Levenshtein,
+    /// Jaro-Winkler similarity, which favors strings sharing a prefix. Good
+    /// for short keys such as names.
+    JaroWinkler,
+    /// Jaccard similarity of character trigrams. Tolerates reordered
+    /// fragments and is cheap for long values.
+    Trigram,
+    /// Token set ratio: words are compared as sets, so word order and
+    /// repeated words don't matter.
+    TokenSet,
+}
+
+impl Algorithm {
+    /// Similarity of two strings, from 0.0 to 1.0.
+    pub fn similarity(self, a: &str, b: &str) -> f64 {
+        match self {
+            Algorithm::Levenshtein => levenshtein_similarity(a, b),
+            Algorithm::JaroWinkler => jaro_winkler(a, b),
+            Algorithm::Trigram => trigram_similarity(a, b),
+            Algorithm::TokenSet => token_set_ratio(a, b),
+        }
+    }
+}
+
+/// Which pairs of strings are scored when searching a set for similar pairs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Prefilter {
+    /// Score every pair, or use a workflow's own exact filter where it has
+    /// one.
+    #[default]
+    None,
+    /// Only score pairs whose MinHash signatures over character trigrams
+    /// agree on all `rows` of at least one of `bands` bands.
+    ///
+    /// Pairs whose trigram sets have Jaccard similarity `j` are scored with
+    /// probability `1 - (1 - j^rows)^bands`, so more bands or fewer rows
+    /// find more matches and prune fewer pairs.
+    Lsh {
+        /// Number of bands.
+        bands: usize,
+        /// Hashes per band.
+        rows: usize,
+    },
+}
+
+impl Prefilter {
+    /// LSH with 50 bands of 3 rows, which scores 99% of pairs with trigram
+    /// Jaccard similarity 0.45 (typical of one typo in a short word) and 5%
+    /// of pairs at 0.1.
+    pub fn lsh() -> Self {
+        Prefilter::Lsh { bands: 50, rows: 3 }
+    }
+}
+
+/// Two strings at least as similar as a threshold, found with [`pairs`] or
+/// [`pairs_between`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScoredPair {
+    /// Index of the first string.
+    pub left: usize,
+    /// Index of the second string.
+    pub right: usize,
+    /// Their similarity.
+    pub similarity: f64,
+}
+
+/// Pairs of `keys` with similarity of at least `threshold`, ordered by
+/// `left` and then `right`, with `left < right`.
+pub fn pairs(
+    keys: &[&str],
+    algorithm: Algorithm,
+    threshold: f64,
+    prefilter: Prefilter,
+) -> Vec<ScoredPair> {
+    candidates(keys, prefilter)
+        .filter_map(|(i, j)| {
+            score(keys[i], keys[j], algorithm, threshold).map(|similarity| ScoredPair {
+                left: i,
+                right: j,
+                similarity,
+            })
+        })
+        .collect()
+}
+
+/// Pairs of one string from `left` and one from `right` with similarity of
+/// at least `threshold`, ordered by `left` and then `right`.
+pub fn pairs_between(
+    left: &[&str],
+    right: &[&str],
+    algorithm: Algorithm,
+    threshold: f64,
+    prefilter: Prefilter,
+) -> Vec<ScoredPair> {
+    candidates_between(left, right, prefilter)
+        .filter_map(|(i, j)| {
+            score(left[i], right[j], algorithm, threshold).map(|similarity| ScoredPair {
+                left: i,
+                right: j,
+                similarity,
+            })
+        })
+        .collect()
+}
+
+/// The similarity of two strings, if it reaches `threshold`.
+pub(crate) fn score(a: &str, b: &str, algorithm: Algorithm, threshold: f64) -> Option<f64> {
+    let similarity = algorithm.similarity(a, b);
+    (similarity >= threshold).then_some(similarity)
+}
+
+/// Index pairs of `keys` that [`pairs`] scores, with `i < j`, generated
+/// lazily in order.
+pub(crate) fn candidates<'a>(
+    keys: &'a [&str],
+    prefilter: Prefilter,
+) -> Box<dyn Iterator<Item = (usize, usize)> + 'a> {
+    match prefilter {
+        Prefilter::None => {
+            Box::new((0..keys.len()).flat_map(move |i| (i + 1..keys.len()).map(move |j| (i, j))))
+        }
+        Prefilter::Lsh { bands, rows } => {
+            let buckets = lsh_buckets(keys, bands, rows);
+            Box::new((0..keys.len()).flat_map(move |i| {
+                let row: BTreeSet<usize> = lsh_signature(keys[i], bands, rows)
+                    .iter()
+                    .filter_map(|bucket| buckets.get(bucket))
+                    .flatten()
+                    .copied()
+                    .filter(|&j| j > i)
+                    .collect();
+                row.into_iter().map(move |j| (i, j))
+            }))
+        }
+    }
+}
+
+/// Index pairs of `left` and `right` that [`pairs_between`] scores,
+/// generated lazily in order.
+pub(crate) fn candidates_between<'a>(
+    left: &'a [&str],
+    right: &'a [&str],
+    prefilter: Prefilter,
+) -> Box<dyn Iterator<Item = (usize, usize)> + 'a> {
+    match prefilter {
+        Prefilter::None => {
+            Box::new((0..left.len()).flat_map(move |i| (0..right.len()).map(move |j| (i, j))))
+        }
+        Prefilter::Lsh { bands, rows } => {
+            let right_buckets = lsh_buckets(right, bands, rows);
+            Box::new((0..left.len()).flat_map(move |i| {
+                let row: BTreeSet<usize> = lsh_signature(left[i], bands, rows)
+                    .iter()
+                    .filter_map(|bucket| right_buckets.get(bucket))
+                    .flatten()
+                    .copied()
+                    .collect();
+                row.into_iter().map(move |j| (i, j))
+            }))
+        }
+    }
+}
+
+/// LSH buckets of `keys`: (band, band hash) -> indices of keys in it.
+fn lsh_buckets(keys: &[&str], bands: usize, rows: usize) -> HashMap<(usize, u64), Vec<usize>> {
+    let mut buckets: HashMap<(usize, u64), Vec<usize>> = HashMap::new();
+    for (i, key) in keys.iter().enumerate() {
+        for bucket in lsh_signature(key, bands, rows) {
+            buckets.entry(bucket).or_default().push(i);
+        }
+    }
+    buckets
+}
+
+/// The (band, band hash) bucket of `key` in each band.
+fn lsh_signature(key: &str, bands: usize, rows: usize) -> Vec<(usize, u64)> {
+    let shingles: Vec<u64> = trigrams(key).iter().map(|gram| fnv(gram)).collect();
+    (0..bands)
+        .map(|band| {
+            let mut hash = 0xcbf2_9ce4_8422_2325u64;
+            for row in 0..rows {
+                let seed = mix((band * rows + row) as u64 + 1);
+                let min = shingles
+                    .iter()
+                    .map(|&shingle| mix(shingle ^ seed))
+                    .min()
+                    .unwrap_or_default();
+                hash = mix(hash ^ min);
+            }
+            (band, hash)
+        })
+        .collect()
+}
+
+/// SplitMix64 finalizer, used to derive independent hash functions.
+fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// FNV-1a hash of a string, stable across runs.
+fn fnv(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Character trigrams of a string padded with a space on each side, so
+/// short strings have some.
+fn trigrams(text: &str) -> HashSet<String> {
+    let chars: Vec<char> = std::iter::once(' ')
+        .chain(text.chars())
+        .chain(std::iter::once(' '))
+        .collect();
+    chars.windows(3).map(|gram| gram.iter().collect()).collect()
+}
+
+/// Normalized Levenshtein similarity, comparing characters exactly.
+///
+/// Returns a value between 0.0 (completely different) and 1.0 (identical).
+pub fn levenshtein_similarity(a: &str, b: &str) -> f64 {
+    if a == b {
+        return 1.0;
+    }
+
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    let distance = levenshtein_distance(a, b);
+    let max_len = a.chars().count().max(b.chars().count());
+
+    1.0 - (distance as f64 / max_len as f64)
+}
+
+/// Calculate the Levenshtein distance between two strings.
+pub fn levenshtein_distance(a: &str, b: &str) -> usize {
+    let a_chars: Vec<char> = a.chars().collect();
+    let b_chars: Vec<char> = b.chars().collect();
+
+    let m = a_chars.len();
+    let n = b_chars.len();
+
+    if m == 0 {
+        return n;
+    }
+    if n == 0 {
+        return m;
+    }
+
+    // Use two rows instead of full matrix for memory efficiency
+    let mut prev: Vec<usize> = (0..=n).collect();
+    let mut curr = vec![0; n + 1];
+
+    for i in 1..=m {
+        curr[0] = i;
+
+        for j in 1..=n {
+            let cost = if a_chars[i - 1] == b_chars[j - 1] {
+                0
+            } else {
+                1
+            };
+
+            curr[j] = (prev[j] + 1) // deletion
+                .min(curr[j - 1] + 1) // insertion
+                .min(prev[j - 1] + cost); // substitution
+        }
+
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[n]
+}
+
+/// Jaro-Winkler similarity, with the standard prefix scale of 0.1 over at
+/// most four characters.
+pub fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a == b {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    // Characters match if equal and no further apart than this
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut a_matched = vec![false; a.len()];
+    let mut b_matched = vec![false; b.len()];
+    let mut matches = 0usize;
+    for (i, &c) in a.iter().enumerate() {
+        let start = i.saturating_sub(window);
+        let end = (i + window + 1).min(b.len());
+        for j in start..end {
+            if !b_matched[j] && b[j] == c {
+                a_matched[i] = true;
+                b_matched[j] = true;
+                matches += 1;
+                break;
+            }
+        }
+    }
+    if matches == 0 {
+        return 0.0;
+    }
+
+    // Half the matched characters that are out of order
+    let a_order = a
+        .iter()
+        .zip(&a_matched)
+        .filter(|(_, m)| **m)
+        .map(|(c, _)| c);
+    let b_order = b
+        .iter()
+        .zip(&b_matched)
+        .filter(|(_, m)| **m)
+        .map(|(c, _)| c);
+    let transpositions = a_order.zip(b_order).filter(|(x, y)| x != y).count() / 2;
+
+    let m = matches as f64;
+    let jaro = (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64) / m) / 3.0;
+    let prefix = a.iter().zip(&b).take(4).take_while(|(x, y)| x == y).count();
+    jaro + prefix as f64 * 0.1 * (1.0 - jaro)
+}
+
+/// Jaccard similarity of the strings' character trigrams.
+pub fn trigram_similarity(a: &str, b: &str) -> f64 {
+    if a == b {
+        return 1.0;
+    }
+    let (a, b) = (trigrams(a), trigrams(b));
+    let shared = a.intersection(&b).count();
+    shared as f64 / (a.len() + b.len() - shared) as f64
+}
+
+/// Token set ratio: the best Levenshtein similarity between the shared
+/// words and the shared words plus each string's other words, all sorted.
+///
+/// `"the cat sat"` and `"sat the cat the"` score 1.0.
+pub fn token_set_ratio(a: &str, b: &str) -> f64 {
+    let a: BTreeSet<&str> = a.split_whitespace().collect();
+    let b: BTreeSet<&str> = b.split_whitespace().collect();
+    if a == b {
+        return 1.0;
+    }
+
+    let join = |words: Vec<&str>| words.join(" ");
+    let shared = join(a.intersection(&b).copied().collect());
+    let with = |rest: &BTreeSet<&str>, other: &BTreeSet<&str>| {
+        let rest = join(rest.difference(other).copied().collect());
+        match (shared.is_empty(), rest.is_empty()) {
+            (true, _) => rest,
+            (_, true) => shared.clone(),
+            _ => format!("{} {}", shared, rest),
+        }
+    };
+    let (full_a, full_b) = (with(&a, &b), with(&b, &a));
+
+    let mut best = levenshtein_similarity(&full_a, &full_b);
+    if !shared.is_empty() {
+        best = best
+            .max(levenshtein_similarity(&shared, &full_a))
+            .max(levenshtein_similarity(&shared, &full_b));
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levenshtein_distance() {
+        assert_eq!(levenshtein_distance("kitten", "sitting"), 3);
+        assert_eq!(levenshtein_distance("", "abc"), 3);
+        assert_eq!(levenshtein_distance("日本語", "日本"), 1);
+    }
+
+    #[test]
+    fn test_jaro_winkler() {
+        assert!((jaro_winkler("martha", "marhta") - 0.9611).abs() < 1e-4);
+        assert!((jaro_winkler("dixon", "dicksonx") - 0.8133).abs() < 1e-4);
+        assert_eq!(jaro_winkler("abc", "abc"), 1.0);
+        assert_eq!(jaro_winkler("abc", "xyz"), 0.0);
+        assert_eq!(jaro_winkler("", "abc"), 0.0);
+    }
+
+    #[test]
+    fn test_trigram_similarity() {
+        assert_eq!(trigram_similarity("night", "night"), 1.0);
+        // " ni", "nig", "igh", "ght", "ht " against " na", "nac", "ach", "cht", "ht "
+        assert!((trigram_similarity("night", "nacht") - 1.0 / 9.0).abs() < 1e-9);
+        assert_eq!(trigram_similarity("abc", "xyz"), 0.0);
+    }
+
+    #[test]
+    fn test_token_set_ratio() {
+        assert_eq!(token_set_ratio("the cat sat", "sat the cat the"), 1.0);
+        // All of one side's words are shared
+        assert_eq!(token_set_ratio("new york", "new york mets"), 1.0);
+        assert!(token_set_ratio("red apple", "green pear") < 0.5);
+        assert_eq!(token_set_ratio("", ""), 1.0);
+    }
+
+    #[test]
+    fn test_algorithm_dispatch() {
+        assert_eq!(Algorithm::default(), Algorithm::Levenshtein);
+        for algorithm in [
+            Algorithm::Levenshtein,
+            Algorithm::JaroWinkler,
+            Algorithm::Trigram,
+            Algorithm::TokenSet,
+        ] {
+            assert_eq!(algorithm.similarity("same", "same"), 1.0);
+            assert!(algorithm.similarity("same", "zzzz") < 0.5);
+        }
+    }
+
+    #[test]
+    fn test_pairs() {
+        let keys = ["separate", "seperate", "banana", "bananas"];
+        let found = pairs(&keys, Algorithm::Levenshtein, 0.85, Prefilter::None);
+        let found: Vec<(usize, usize)> = found.iter().map(|p| (p.left, p.right)).collect();
+        assert_eq!(found, vec![(0, 1), (2, 3)]);
+
+        let left = ["banana", "cherry"];
+        let found = pairs_between(&left, &keys, Algorithm::Levenshtein, 0.85, Prefilter::None);
+        let found: Vec<(usize, usize)> = found.iter().map(|p| (p.left, p.right)).collect();
+        assert_eq!(found, vec![(0, 2), (0, 3)]);
+    }
+
+    #[test]
+    fn test_lsh_finds_near_duplicates() {
+        // Pairs differing by one character in words of 8 to 12 letters
+        let words = [
+            "accommodate",
+            "necessary",
+            "definitely",
+            "separately",
+            "government",
+            "environment",
+            "restaurant",
+            "beginning",
+            "occasionally",
+            "recommend",
+        ];
+        let mut keys: Vec<String> = Vec::new();
+        for word in words {
+            keys.push(word.to_string());
+            let mut typo: Vec<char> = word.chars().collect();
+            typo[word.len() / 2] = 'x';
+            keys.push(typo.into_iter().collect());
+        }
+        let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+
+        let all = pairs(&keys, Algorithm::Levenshtein, 0.85, Prefilter::None);
+        let lsh = pairs(&keys, Algorithm::Levenshtein, 0.85, Prefilter::lsh());
+        assert_eq!(all.len(), words.len());
+        assert_eq!(lsh, all);
+
+        let between = pairs_between(&keys, &keys, Algorithm::Trigram, 1.0, Prefilter::lsh());
+        assert_eq!(between.len(), keys.len());
+        assert!(between.iter().all(|p| p.left == p.right));
+    }
+}
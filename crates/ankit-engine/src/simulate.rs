@@ -0,0 +1,611 @@
+//! What-if review simulation.
+//!
+//! Before raising a deck's new card limit or capping its reviews, it helps
+//! to know what the change does to the workload a month or a year out.
+//! [`SimulateEngine`] loads a deck's current card states and plays through
+//! the coming days under [`SimulationOptions`]: daily limits, the share of
+//! reviews answered correctly, and how intervals grow. The resulting
+//! [`SimulationReport`] has a day-by-day workload and maturity curve.
+//!
+//! The scheduler model is a simplified SM-2: a correct answer multiplies
+//! the interval by the card's ease, a lapse resets the interval to one day
+//! and lowers the ease, and new cards graduate to a one-day interval on the
+//! day they are introduced. Learning steps, fuzz, and FSRS are not modeled,
+//! so treat the numbers as estimates for comparing settings rather than
+//! predictions.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ankit_engine::Engine;
+//! use ankit_engine::simulate::SimulationOptions;
+//!
+//! # async fn example() -> ankit_engine::Result<()> {
+//! let engine = Engine::new();
+//!
+//! let scenarios: Vec<SimulationOptions> = [10, 20, 30]
+//!     .into_iter()
+//!     .map(|new_per_day| SimulationOptions {
+//!         days: 90,
+//!         new_per_day,
+//!         ..Default::default()
+//!     })
+//!     .collect();
+//!
+//! for report in engine.simulate().scenarios("Japanese", &scenarios).await? {
+//!     println!(
+//!         "{} new/day: {:.0} reviews/day on average, peak {}, {} mature",
+//!         report.options.new_per_day,
+//!         report.average_reviews,
+//!         report.peak_reviews,
+//!         report.final_mature
+//!     );
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::concurrency;
+use crate::report::{WorkflowReport, report_fields};
+use crate::{EngineOptions, Error, Result};
+use ankit::AnkiClient;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Lowest ease factor a lapse can bring a card to, as in Anki.
+const MIN_EASE: u32 = 1300;
+
+/// Ease factor lost on each lapse.
+const LAPSE_EASE_PENALTY: u32 = 200;
+
+/// How review intervals grow after a correct answer.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "model", rename_all = "snake_case")]
+pub enum EaseModel {
+    /// Multiply by each card's own ease factor, lowered on every lapse.
+    Sm2,
+    /// Multiply every interval by the same factor, e.g. 2.5.
+    Fixed {
+        /// Interval multiplier.
+        multiplier: f64,
+    },
+}
+
+/// Assumptions for a simulation run.
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulationOptions {
+    /// Number of days to simulate, starting today.
+    pub days: u32,
+    /// New cards introduced per day.
+    pub new_per_day: usize,
+    /// Most reviews done per day; the rest carry over to the next day.
+    pub max_reviews_per_day: usize,
+    /// Share of reviews answered correctly, from 0.0 to 1.0.
+    pub retention: f64,
+    /// How intervals grow.
+    pub ease_model: EaseModel,
+    /// Ease factor given to new cards (2500 = 250%).
+    pub starting_ease: u32,
+    /// Interval in days at which a card counts as mature.
+    pub mature_interval: u32,
+    /// Average time spent per card, for the daily minutes estimate.
+    pub seconds_per_card: f64,
+    /// Seed for the random answers, so runs are repeatable.
+    pub seed: u64,
+}
+
+impl Default for SimulationOptions {
+    fn default() -> Self {
+        Self {
+            days: 30,
+            new_per_day: 20,
+            max_reviews_per_day: 200,
+            retention: 0.9,
+            ease_model: EaseModel::Sm2,
+            starting_ease: 2500,
+            mature_interval: 21,
+            seconds_per_card: 10.0,
+            seed: 1,
+        }
+    }
+}
+
+/// The state of one card at the start of a simulation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimCard {
+    /// Whether the card has never been studied.
+    pub new: bool,
+    /// Current interval in days (0 for new cards).
+    pub interval: u32,
+    /// Ease factor (2500 = 250%).
+    pub ease: u32,
+    /// Days from today until the card is due; 0 for due or overdue cards.
+    /// `None` for new cards, and for review cards due after the
+    /// simulation window.
+    pub due_in: Option<u32>,
+}
+
+/// One simulated day.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SimulatedDay {
+    /// Days from today.
+    pub day: u32,
+    /// Reviews done.
+    pub reviews: usize,
+    /// New cards introduced.
+    pub new_cards: usize,
+    /// Reviews answered incorrectly.
+    pub lapses: usize,
+    /// Due reviews left over for the next day because of the review limit.
+    pub backlog: usize,
+    /// Estimated study time in minutes.
+    pub minutes: f64,
+    /// Studied cards with an interval below the mature threshold, at the
+    /// end of the day.
+    pub young: usize,
+    /// Cards with a mature interval at the end of the day.
+    pub mature: usize,
+    /// New cards not yet introduced at the end of the day.
+    pub unseen: usize,
+}
+
+/// Projected workload and maturity for one set of options.
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulationReport {
+    /// The deck simulated, if loaded from Anki.
+    pub deck: Option<String>,
+    /// Options the simulation ran with.
+    pub options: SimulationOptions,
+    /// Day-by-day results, starting today.
+    pub days: Vec<SimulatedDay>,
+    /// Reviews over the whole simulation.
+    pub total_reviews: usize,
+    /// Average reviews per day.
+    pub average_reviews: f64,
+    /// Most reviews on a single day.
+    pub peak_reviews: usize,
+    /// Average study minutes per day.
+    pub average_minutes: f64,
+    /// Mature cards at the end.
+    pub final_mature: usize,
+    /// Due reviews still pending at the end.
+    pub final_backlog: usize,
+}
+
+impl WorkflowReport for SimulationReport {
+    fn summary(&self) -> String {
+        format!(
+            "{} days: {:.0} reviews/day on average (peak {}), {:.0} minutes/day, {} mature at the end",
+            self.days.len(),
+            self.average_reviews,
+            self.peak_reviews,
+            self.average_minutes,
+            self.final_mature
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.days
+            .iter()
+            .map(|day| {
+                format!(
+                    "day {}: {} reviews, {} new, {} lapses, {} backlog, {} mature",
+                    day.day, day.reviews, day.new_cards, day.lapses, day.backlog, day.mature
+                )
+            })
+            .collect()
+    }
+
+    report_fields!();
+}
+
+/// Engine for review simulations.
+#[derive(Debug)]
+pub struct SimulateEngine<'a> {
+    client: &'a AnkiClient,
+    options: &'a EngineOptions,
+}
+
+impl<'a> SimulateEngine<'a> {
+    pub(crate) fn new(client: &'a AnkiClient, options: &'a EngineOptions) -> Self {
+        Self { client, options }
+    }
+
+    /// Load the current state of a deck's cards, including sub-decks.
+    ///
+    /// Suspended cards are left out. Learning cards count as due today.
+    /// Review cards are placed on the days they fall due within the next
+    /// `days` days; later ones only count toward maturity.
+    pub async fn cards(&self, deck: &str, days: u32) -> Result<Vec<SimCard>> {
+        let base = format!("deck:\"{}\" -is:suspended", deck);
+
+        let card_ids = self.client.cards().find(&base).await?;
+        if card_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Anki only exposes due days relative to today through searches
+        let searches: Vec<(u32, String)> = (0..days)
+            .map(|day| {
+                let filter = if day == 0 {
+                    "(is:learn OR (is:review prop:due<=0))".to_string()
+                } else {
+                    format!("is:review -is:learn prop:due={}", day)
+                };
+                (day, format!("{} {}", base, filter))
+            })
+            .collect();
+        let found = concurrency::bounded(self.options.concurrency, searches, |(day, query)| {
+            let client = self.client;
+            async move { (day, client.cards().find(&query).await) }
+        })
+        .await;
+        let mut due_in: HashMap<i64, u32> = HashMap::new();
+        for (day, card_ids) in found {
+            for card_id in card_ids? {
+                due_in.entry(card_id).or_insert(day);
+            }
+        }
+
+        let cards = self.client.cards().info(&card_ids).await?;
+        Ok(cards
+            .into_iter()
+            .map(|card| {
+                let new = card.card_type == 0;
+                SimCard {
+                    new,
+                    interval: card.interval.max(0) as u32,
+                    ease: card.ease_factor.max(0) as u32,
+                    due_in: if new {
+                        None
+                    } else {
+                        due_in.get(&card.card_id).copied()
+                    },
+                }
+            })
+            .collect())
+    }
+
+    /// Simulate a deck under one set of options.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Validation`] if the options are out of range.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # use ankit_engine::simulate::SimulationOptions;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let options = SimulationOptions {
+    ///     days: 60,
+    ///     retention: 0.85,
+    ///     ..Default::default()
+    /// };
+    /// let report = engine.simulate().deck("Japanese", &options).await?;
+    /// for day in &report.days {
+    ///     println!("day {}: {} reviews, {} mature", day.day, day.reviews, day.mature);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn deck(&self, deck: &str, options: &SimulationOptions) -> Result<SimulationReport> {
+        let mut reports = self.scenarios(deck, std::slice::from_ref(options)).await?;
+        Ok(reports.remove(0))
+    }
+
+    /// Simulate a deck under several sets of options, loading its cards
+    /// once.
+    ///
+    /// Reports are returned in the order of `scenarios`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Validation`] if any options are out of range.
+    pub async fn scenarios(
+        &self,
+        deck: &str,
+        scenarios: &[SimulationOptions],
+    ) -> Result<Vec<SimulationReport>> {
+        for options in scenarios {
+            validate(options)?;
+        }
+        let days = scenarios.iter().map(|o| o.days).max().unwrap_or(0);
+        let cards = self.cards(deck, days).await?;
+
+        scenarios
+            .iter()
+            .map(|options| {
+                let mut report = run(&cards, options)?;
+                report.deck = Some(deck.to_string());
+                Ok(report)
+            })
+            .collect()
+    }
+}
+
+fn validate(options: &SimulationOptions) -> Result<()> {
+    if options.days == 0 {
+        return Err(Error::Validation("days must be at least 1".to_string()));
+    }
+    if !(0.0..=1.0).contains(&options.retention) {
+        return Err(Error::Validation(format!(
+            "retention must be between 0.0 and 1.0, got {}",
+            options.retention
+        )));
+    }
+    if let EaseModel::Fixed { multiplier } = options.ease_model {
+        if multiplier < 1.0 {
+            return Err(Error::Validation(format!(
+                "interval multiplier must be at least 1.0, got {}",
+                multiplier
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// A card during the simulation.
+struct Card {
+    interval: u32,
+    ease: u32,
+    /// Day the card is next due, if within the simulation.
+    due: Option<u32>,
+}
+
+/// Simulate cards under the given options.
+///
+/// Use this with cards from [`SimulateEngine::cards`], or with
+/// hypothetical ones, to run simulations without a connection to Anki.
+///
+/// # Errors
+///
+/// Returns [`Error::Validation`] if the options are out of range.
+///
+/// # Example
+///
+/// ```
+/// use ankit_engine::simulate::{SimCard, SimulationOptions, run};
+///
+/// // A fresh deck of 500 new cards
+/// let cards = vec![
+///     SimCard { new: true, interval: 0, ease: 0, due_in: None };
+///     500
+/// ];
+/// let options = SimulationOptions {
+///     days: 50,
+///     new_per_day: 10,
+///     ..Default::default()
+/// };
+/// let report = run(&cards, &options).unwrap();
+/// assert_eq!(report.days.last().unwrap().unseen, 0);
+/// ```
+pub fn run(cards: &[SimCard], options: &SimulationOptions) -> Result<SimulationReport> {
+    validate(options)?;
+
+    let mut rng = Rng::new(options.seed);
+    let mut unseen = cards.iter().filter(|c| c.new).count();
+    let mut studied: Vec<Card> = cards
+        .iter()
+        .filter(|c| !c.new)
+        .map(|c| Card {
+            interval: c.interval.max(1),
+            ease: if c.ease == 0 {
+                options.starting_ease
+            } else {
+                c.ease
+            },
+            due: c.due_in,
+        })
+        .collect();
+
+    let mut days = Vec::with_capacity(options.days as usize);
+    for day in 0..options.days {
+        let mut stats = SimulatedDay {
+            day,
+            ..Default::default()
+        };
+
+        // Most overdue first, like Anki's review order
+        let mut due: Vec<usize> = (0..studied.len())
+            .filter(|&i| studied[i].due.is_some_and(|d| d <= day))
+            .collect();
+        due.sort_by_key(|&i| studied[i].due);
+        stats.backlog = due.len().saturating_sub(options.max_reviews_per_day);
+        due.truncate(options.max_reviews_per_day);
+
+        for i in due {
+            let card = &mut studied[i];
+            if rng.next_f64() < options.retention {
+                let multiplier = match options.ease_model {
+                    EaseModel::Sm2 => f64::from(card.ease) / 1000.0,
+                    EaseModel::Fixed { multiplier } => multiplier,
+                };
+                let next = (f64::from(card.interval) * multiplier).round() as u32;
+                card.interval = next.max(card.interval + 1);
+            } else {
+                stats.lapses += 1;
+                card.interval = 1;
+                if options.ease_model == EaseModel::Sm2 {
+                    card.ease = card.ease.saturating_sub(LAPSE_EASE_PENALTY).max(MIN_EASE);
+                }
+            }
+            card.due = Some(day + card.interval);
+            stats.reviews += 1;
+        }
+
+        stats.new_cards = options.new_per_day.min(unseen);
+        unseen -= stats.new_cards;
+        studied.extend((0..stats.new_cards).map(|_| Card {
+            interval: 1,
+            ease: options.starting_ease,
+            due: Some(day + 1),
+        }));
+
+        stats.minutes = (stats.reviews + stats.new_cards) as f64 * options.seconds_per_card / 60.0;
+        stats.mature = studied
+            .iter()
+            .filter(|c| c.interval >= options.mature_interval)
+            .count();
+        stats.young = studied.len() - stats.mature;
+        stats.unseen = unseen;
+        days.push(stats);
+    }
+
+    let total_reviews: usize = days.iter().map(|d| d.reviews).sum();
+    let count = days.len() as f64;
+    Ok(SimulationReport {
+        deck: None,
+        options: options.clone(),
+        total_reviews,
+        average_reviews: total_reviews as f64 / count,
+        peak_reviews: days.iter().map(|d| d.reviews).max().unwrap_or(0),
+        average_minutes: days.iter().map(|d| d.minutes).sum::<f64>() / count,
+        final_mature: days.last().map_or(0, |d| d.mature),
+        final_backlog: days.last().map_or(0, |d| d.backlog),
+        days,
+    })
+}
+
+/// SplitMix64, enough for repeatable simulated answers.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn review(interval: u32, due_in: u32) -> SimCard {
+        SimCard {
+            new: false,
+            interval,
+            ease: 2500,
+            due_in: Some(due_in),
+        }
+    }
+
+    #[test]
+    fn test_perfect_retention_grows_intervals() {
+        let options = SimulationOptions {
+            days: 10,
+            new_per_day: 0,
+            retention: 1.0,
+            ..Default::default()
+        };
+        let report = run(&[review(10, 0)], &options).unwrap();
+
+        // Reviewed today, next due in 25 days
+        assert_eq!(report.total_reviews, 1);
+        assert_eq!(report.days[0].reviews, 1);
+        assert_eq!(report.final_mature, 1);
+        assert_eq!(report.days[0].lapses, 0);
+    }
+
+    #[test]
+    fn test_zero_retention_lapses_every_review() {
+        let options = SimulationOptions {
+            days: 5,
+            new_per_day: 0,
+            retention: 0.0,
+            ..Default::default()
+        };
+        let report = run(&[review(30, 0)], &options).unwrap();
+
+        // Lapsed cards come back every day
+        assert_eq!(report.total_reviews, 5);
+        assert_eq!(report.days.iter().map(|d| d.lapses).sum::<usize>(), 5);
+        assert_eq!(report.final_mature, 0);
+    }
+
+    #[test]
+    fn test_review_limit_creates_backlog() {
+        let options = SimulationOptions {
+            days: 1,
+            new_per_day: 0,
+            max_reviews_per_day: 3,
+            ..Default::default()
+        };
+        let cards = vec![review(5, 0); 5];
+        let report = run(&cards, &options).unwrap();
+
+        assert_eq!(report.days[0].reviews, 3);
+        assert_eq!(report.days[0].backlog, 2);
+    }
+
+    #[test]
+    fn test_new_cards_introduced_until_exhausted() {
+        let cards = vec![
+            SimCard {
+                new: true,
+                interval: 0,
+                ease: 0,
+                due_in: None,
+            };
+            25
+        ];
+        let options = SimulationOptions {
+            days: 3,
+            new_per_day: 10,
+            ..Default::default()
+        };
+        let report = run(&cards, &options).unwrap();
+
+        let introduced: Vec<usize> = report.days.iter().map(|d| d.new_cards).collect();
+        assert_eq!(introduced, vec![10, 10, 5]);
+        // Day 0's new cards are due for review on day 1
+        assert_eq!(report.days[1].reviews, 10);
+        assert_eq!(report.days[2].unseen, 0);
+    }
+
+    #[test]
+    fn test_same_seed_is_repeatable() {
+        let cards = vec![review(3, 0); 50];
+        let options = SimulationOptions {
+            days: 20,
+            retention: 0.7,
+            ..Default::default()
+        };
+        let a = run(&cards, &options).unwrap();
+        let b = run(&cards, &options).unwrap();
+        assert_eq!(a.total_reviews, b.total_reviews);
+        assert_eq!(a.final_mature, b.final_mature);
+    }
+
+    #[test]
+    fn test_validation() {
+        let bad_retention = SimulationOptions {
+            retention: 1.5,
+            ..Default::default()
+        };
+        assert!(matches!(
+            run(&[], &bad_retention),
+            Err(Error::Validation(_))
+        ));
+
+        let no_days = SimulationOptions {
+            days: 0,
+            ..Default::default()
+        };
+        assert!(run(&[], &no_days).is_err());
+    }
+}
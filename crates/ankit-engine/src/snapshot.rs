@@ -0,0 +1,459 @@
+//! Note snapshots for auditing changes.
+//!
+//! A snapshot records the fields and tags of every note matching a search,
+//! as JSON. Diffing it against the collection later shows which notes an
+//! add-on, a sync, or a bulk operation changed, field by field, and which
+//! notes were added or deleted in the meantime.
+//!
+//! Unlike [`backup().snapshot()`](crate::backup::BackupEngine::snapshot),
+//! these snapshots cover any search rather than whole decks, leave out
+//! scheduling, and are meant for comparison rather than restoring.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ankit_engine::Engine;
+//!
+//! # async fn example() -> ankit_engine::Result<()> {
+//! let engine = Engine::new();
+//!
+//! engine.snapshot().capture("deck:Japanese", "japanese-before.json").await?;
+//! // ... run an add-on or a bulk edit ...
+//! let diff = engine.snapshot().diff_snapshot("japanese-before.json").await?;
+//! for change in &diff.changed {
+//!     let fields: Vec<_> = change.fields.iter().map(|f| f.field.as_str()).collect();
+//!     println!("note {}: {} {:?}", change.note_id, fields.join(", "), change.tags_added);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::report::{AffectedIds, WorkflowReport, report_fields};
+use crate::{Error, Result};
+use ankit::{AnkiClient, NoteInfo};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Version of the file format written by [`SnapshotEngine::capture`].
+pub const NOTE_SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// Number of note IDs per `nid:` search when checking which notes exist.
+const NID_CHUNK_SIZE: usize = 500;
+
+/// Engine for note snapshot workflows.
+pub struct SnapshotEngine<'a> {
+    client: &'a AnkiClient,
+}
+
+/// The recorded state of the notes matching a search.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteSnapshot {
+    /// File format version.
+    pub version: u32,
+    /// The search the notes were found with.
+    pub query: String,
+    /// Capture time (Unix timestamp, seconds).
+    pub created_at: u64,
+    /// The notes, in ascending ID order.
+    pub notes: Vec<SnapshotNoteState>,
+}
+
+/// One note in a [`NoteSnapshot`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotNoteState {
+    /// The note ID.
+    pub note_id: i64,
+    /// The note type (model) name.
+    pub model: String,
+    /// Field values, keyed by field name.
+    pub fields: BTreeMap<String, String>,
+    /// Tags, sorted.
+    pub tags: Vec<String>,
+}
+
+/// Report from capturing a snapshot to a file.
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptureReport {
+    /// The file the snapshot was written to.
+    pub path: PathBuf,
+    /// The search the notes were found with.
+    pub query: String,
+    /// Number of notes recorded.
+    pub notes_captured: usize,
+}
+
+impl WorkflowReport for CaptureReport {
+    fn summary(&self) -> String {
+        format!(
+            "Captured {} notes matching '{}' to {}",
+            self.notes_captured,
+            self.query,
+            self.path.display()
+        )
+    }
+
+    report_fields!();
+}
+
+/// Differences between a snapshot and the current collection.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SnapshotDiff {
+    /// The snapshot's search.
+    pub query: String,
+    /// When the snapshot was captured (Unix timestamp, seconds).
+    pub captured_at: u64,
+    /// Notes whose fields, tags, or note type changed.
+    pub changed: Vec<NoteChange>,
+    /// Notes that now match the search but weren't in the snapshot.
+    pub added: Vec<i64>,
+    /// Notes in the snapshot that no longer exist.
+    pub deleted: Vec<i64>,
+    /// Number of notes that are identical.
+    pub unchanged: usize,
+}
+
+impl SnapshotDiff {
+    /// Whether nothing changed since the snapshot.
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.added.is_empty() && self.deleted.is_empty()
+    }
+}
+
+impl WorkflowReport for SnapshotDiff {
+    fn summary(&self) -> String {
+        format!(
+            "Since snapshot of '{}': {} notes changed, {} added, {} deleted, {} unchanged",
+            self.query,
+            self.changed.len(),
+            self.added.len(),
+            self.deleted.len(),
+            self.unchanged
+        )
+    }
+
+    fn details(&self) -> Vec<String> {
+        self.changed.iter().map(NoteChange::describe).collect()
+    }
+
+    fn affected(&self) -> AffectedIds {
+        AffectedIds {
+            note_ids: self
+                .changed
+                .iter()
+                .map(|change| change.note_id)
+                .chain(self.added.iter().copied())
+                .chain(self.deleted.iter().copied())
+                .collect(),
+            card_ids: Vec::new(),
+        }
+        .normalize()
+    }
+
+    report_fields!();
+}
+
+/// How one note changed since the snapshot.
+#[derive(Debug, Clone, Serialize)]
+pub struct NoteChange {
+    /// The note ID.
+    pub note_id: i64,
+    /// The current note type.
+    pub model: String,
+    /// The note type in the snapshot, if it has changed.
+    pub previous_model: Option<String>,
+    /// Fields whose values differ.
+    pub fields: Vec<FieldChange>,
+    /// Tags added since the snapshot.
+    pub tags_added: Vec<String>,
+    /// Tags removed since the snapshot.
+    pub tags_removed: Vec<String>,
+}
+
+impl NoteChange {
+    fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(ref previous) = self.previous_model {
+            parts.push(format!("note type {} -> {}", previous, self.model));
+        }
+        if !self.fields.is_empty() {
+            let names: Vec<&str> = self.fields.iter().map(|f| f.field.as_str()).collect();
+            parts.push(format!("fields {}", names.join(", ")));
+        }
+        if !self.tags_added.is_empty() {
+            parts.push(format!("+tags {}", self.tags_added.join(" ")));
+        }
+        if !self.tags_removed.is_empty() {
+            parts.push(format!("-tags {}", self.tags_removed.join(" ")));
+        }
+        format!("note {}: {}", self.note_id, parts.join("; "))
+    }
+}
+
+/// A field whose value differs from the snapshot.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldChange {
+    /// Field name.
+    pub field: String,
+    /// Value in the snapshot (`None` if the field didn't exist).
+    pub before: Option<String>,
+    /// Current value (`None` if the field no longer exists).
+    pub after: Option<String>,
+}
+
+impl NoteSnapshot {
+    /// Load a snapshot from a file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        let snapshot: Self = serde_json::from_str(&contents).map_err(|e| {
+            Error::Snapshot(format!(
+                "Failed to parse snapshot '{}': {}",
+                path.display(),
+                e
+            ))
+        })?;
+        if snapshot.version > NOTE_SNAPSHOT_FORMAT_VERSION {
+            return Err(Error::Snapshot(format!(
+                "snapshot '{}' has unsupported format version {}",
+                path.display(),
+                snapshot.version
+            )));
+        }
+        Ok(snapshot)
+    }
+
+    /// Write the snapshot to a file, creating parent directories if needed.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Snapshot(format!("Failed to serialize snapshot: {}", e)))?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+}
+
+impl SnapshotNoteState {
+    fn from_info(info: NoteInfo) -> Self {
+        let mut tags = info.tags;
+        tags.sort();
+        Self {
+            note_id: info.note_id,
+            model: info.model_name,
+            fields: info
+                .fields
+                .into_iter()
+                .map(|(name, field)| (name, field.value))
+                .collect(),
+            tags,
+        }
+    }
+
+    /// Compare against the current state, or None if nothing changed.
+    fn compare(&self, current: &SnapshotNoteState) -> Option<NoteChange> {
+        let names: std::collections::BTreeSet<&String> =
+            self.fields.keys().chain(current.fields.keys()).collect();
+        let fields: Vec<FieldChange> = names
+            .into_iter()
+            .filter(|name| self.fields.get(*name) != current.fields.get(*name))
+            .map(|name| FieldChange {
+                field: name.clone(),
+                before: self.fields.get(name).cloned(),
+                after: current.fields.get(name).cloned(),
+            })
+            .collect();
+        let before: HashSet<&String> = self.tags.iter().collect();
+        let after: HashSet<&String> = current.tags.iter().collect();
+        let tags_added: Vec<String> = current
+            .tags
+            .iter()
+            .filter(|tag| !before.contains(tag))
+            .cloned()
+            .collect();
+        let tags_removed: Vec<String> = self
+            .tags
+            .iter()
+            .filter(|tag| !after.contains(tag))
+            .cloned()
+            .collect();
+        let previous_model = Some(self.model.clone()).filter(|model| *model != current.model);
+
+        if fields.is_empty()
+            && tags_added.is_empty()
+            && tags_removed.is_empty()
+            && previous_model.is_none()
+        {
+            return None;
+        }
+        Some(NoteChange {
+            note_id: self.note_id,
+            model: current.model.clone(),
+            previous_model,
+            fields,
+            tags_added,
+            tags_removed,
+        })
+    }
+}
+
+impl<'a> SnapshotEngine<'a> {
+    pub(crate) fn new(client: &'a AnkiClient) -> Self {
+        Self { client }
+    }
+
+    /// Record the notes matching `query` without writing a file.
+    pub async fn take(&self, query: &str) -> Result<NoteSnapshot> {
+        let mut note_ids = self.client.notes().find(query).await?;
+        note_ids.sort_unstable();
+        let notes = self.notes(&note_ids).await?;
+
+        Ok(NoteSnapshot {
+            version: NOTE_SNAPSHOT_FORMAT_VERSION,
+            query: query.to_string(),
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            notes: note_ids
+                .iter()
+                .filter_map(|id| notes.get(id).cloned())
+                .collect(),
+        })
+    }
+
+    /// Record the notes matching `query` to a JSON file.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit_engine::Engine;
+    /// # async fn example() -> ankit_engine::Result<()> {
+    /// let engine = Engine::new();
+    /// let report = engine.snapshot().capture("tag:verbs", "verbs.json").await?;
+    /// println!("Captured {} notes", report.notes_captured);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn capture(&self, query: &str, path: impl AsRef<Path>) -> Result<CaptureReport> {
+        let path = path.as_ref();
+        let snapshot = self.take(query).await?;
+        snapshot.write(path)?;
+
+        Ok(CaptureReport {
+            path: path.to_path_buf(),
+            query: snapshot.query,
+            notes_captured: snapshot.notes.len(),
+        })
+    }
+
+    /// Compare a snapshot file against the current collection.
+    ///
+    /// The snapshot's search is run again to find notes added since it was
+    /// captured. Notes in the snapshot are compared even if they no longer
+    /// match the search.
+    pub async fn diff_snapshot(&self, path: impl AsRef<Path>) -> Result<SnapshotDiff> {
+        let snapshot = NoteSnapshot::load(path)?;
+        self.diff(&snapshot).await
+    }
+
+    /// Compare a snapshot against the current collection.
+    pub async fn diff(&self, snapshot: &NoteSnapshot) -> Result<SnapshotDiff> {
+        let recorded: Vec<i64> = snapshot.notes.iter().map(|note| note.note_id).collect();
+        let recorded_set: HashSet<i64> = recorded.iter().copied().collect();
+
+        let mut existing = HashSet::new();
+        for chunk in recorded.chunks(NID_CHUNK_SIZE) {
+            let ids: Vec<String> = chunk.iter().map(i64::to_string).collect();
+            let query = format!("nid:{}", ids.join(","));
+            existing.extend(self.client.notes().find(&query).await?);
+        }
+
+        let mut added: Vec<i64> = self
+            .client
+            .notes()
+            .find(&snapshot.query)
+            .await?
+            .into_iter()
+            .filter(|id| !recorded_set.contains(id))
+            .collect();
+        added.sort_unstable();
+
+        let still_there: Vec<i64> = recorded
+            .iter()
+            .copied()
+            .filter(|id| existing.contains(id))
+            .collect();
+        let current = self.notes(&still_there).await?;
+
+        let mut diff = SnapshotDiff {
+            query: snapshot.query.clone(),
+            captured_at: snapshot.created_at,
+            added,
+            ..Default::default()
+        };
+        for note in &snapshot.notes {
+            match current.get(&note.note_id) {
+                None => diff.deleted.push(note.note_id),
+                Some(now) => match note.compare(now) {
+                    Some(change) => diff.changed.push(change),
+                    None => diff.unchanged += 1,
+                },
+            }
+        }
+        Ok(diff)
+    }
+
+    /// Fetch the current state of notes, keyed by ID.
+    async fn notes(&self, note_ids: &[i64]) -> Result<HashMap<i64, SnapshotNoteState>> {
+        if note_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        Ok(self
+            .client
+            .notes()
+            .info(note_ids)
+            .await?
+            .into_iter()
+            .map(|info| (info.note_id, SnapshotNoteState::from_info(info)))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(fields: &[(&str, &str)], tags: &[&str]) -> SnapshotNoteState {
+        SnapshotNoteState {
+            note_id: 1,
+            model: "Basic".to_string(),
+            fields: fields
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_compare_notes() {
+        let before = state(&[("Front", "cat"), ("Back", "neko")], &["animal", "n5"]);
+        assert!(before.compare(&before.clone()).is_none());
+
+        let after = state(&[("Front", "cat"), ("Back", "猫")], &["animal", "kanji"]);
+        let change = before.compare(&after).unwrap();
+        assert_eq!(change.fields.len(), 1);
+        assert_eq!(change.fields[0].field, "Back");
+        assert_eq!(change.fields[0].before.as_deref(), Some("neko"));
+        assert_eq!(change.tags_added, vec!["kanji"]);
+        assert_eq!(change.tags_removed, vec!["n5"]);
+        assert_eq!(
+            change.describe(),
+            "note 1: fields Back; +tags kanji; -tags n5"
+        );
+    }
+}
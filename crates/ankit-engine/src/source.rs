@@ -0,0 +1,513 @@
+//! Record sources for bulk import.
+//!
+//! [`ImportEngine::from_source`](crate::import::ImportEngine::from_source)
+//! imports notes from any [`ImportSource`], using the same duplicate
+//! handling as [`ImportEngine::notes`](crate::import::ImportEngine::notes).
+//! Every record carries its [`Provenance`], so a bad row is reported with
+//! its file and line instead of failing the whole batch.
+//!
+//! [`DelimitedSource`] reads CSV and TSV files and [`JsonlSource`] reads
+//! JSON Lines. Any iterator of `Result<SourceRecord, RecordError>` is also a
+//! source, so other formats only need to produce records.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ankit_engine::Engine;
+//! use ankit_engine::import::OnDuplicate;
+//! use ankit_engine::source::{DelimitedSource, FieldMapping};
+//!
+//! # async fn example() -> ankit_engine::Result<()> {
+//! let engine = Engine::new();
+//!
+//! // Header row: word,meaning,tags
+//! let mapping = FieldMapping::new("Spanish", "Basic")
+//!     .field("word", "Front")
+//!     .field("meaning", "Back")
+//!     .tags_column("tags");
+//! let source = DelimitedSource::from_path("vocab.csv", mapping)?;
+//!
+//! let report = engine.import().from_source(source, OnDuplicate::Skip).await?;
+//! for error in &report.errors {
+//!     println!("{}: {}", error.provenance, error.error);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::{Note, Result};
+
+/// A source of records to import.
+///
+/// Implemented for every iterator of `Result<SourceRecord, RecordError>`.
+pub trait ImportSource {
+    /// The next record, or `None` when the source is exhausted.
+    ///
+    /// A record that can't be turned into a note is returned as an error;
+    /// the import continues with the next one.
+    fn next_record(&mut self) -> Option<std::result::Result<SourceRecord, RecordError>>;
+}
+
+impl<I> ImportSource for I
+where
+    I: Iterator<Item = std::result::Result<SourceRecord, RecordError>>,
+{
+    fn next_record(&mut self) -> Option<std::result::Result<SourceRecord, RecordError>> {
+        self.next()
+    }
+}
+
+/// Where a record came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Provenance {
+    /// Name of the source, usually a file path.
+    pub source: String,
+    /// Line the record starts on (1-based).
+    pub line: usize,
+}
+
+impl fmt::Display for Provenance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.source, self.line)
+    }
+}
+
+/// A note read from a source.
+#[derive(Debug, Clone)]
+pub struct SourceRecord {
+    /// The note to import.
+    pub note: Note,
+    /// Where it came from.
+    pub provenance: Provenance,
+}
+
+/// A record that could not be imported.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordError {
+    /// Where the record came from.
+    pub provenance: Provenance,
+    /// What went wrong.
+    pub error: String,
+}
+
+/// How the columns of a record become a note.
+///
+/// Without any [`field`](Self::field) mappings, every column except the tags
+/// and deck columns becomes a note field of the same name.
+#[derive(Debug, Clone, Default)]
+pub struct FieldMapping {
+    /// Deck for notes without a deck column value.
+    pub deck: String,
+    /// Note type of every note.
+    pub model: String,
+    /// Note field for each column.
+    pub fields: HashMap<String, String>,
+    /// Column holding space-separated tags.
+    pub tags_column: Option<String>,
+    /// Column holding the deck name, overriding [`deck`](Self::deck).
+    pub deck_column: Option<String>,
+    /// Tags added to every note.
+    pub tags: Vec<String>,
+}
+
+impl FieldMapping {
+    /// Map records to `model` notes in `deck`.
+    pub fn new(deck: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            deck: deck.into(),
+            model: model.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Import `column` into the note field `field`.
+    pub fn field(mut self, column: impl Into<String>, field: impl Into<String>) -> Self {
+        self.fields.insert(column.into(), field.into());
+        self
+    }
+
+    /// Read tags from `column`.
+    pub fn tags_column(mut self, column: impl Into<String>) -> Self {
+        self.tags_column = Some(column.into());
+        self
+    }
+
+    /// Read the deck from `column`.
+    pub fn deck_column(mut self, column: impl Into<String>) -> Self {
+        self.deck_column = Some(column.into());
+        self
+    }
+
+    /// Add a tag to every note.
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Build a note from a record's columns.
+    fn note(&self, columns: HashMap<String, String>) -> std::result::Result<Note, String> {
+        let special = |column: &String| {
+            self.tags_column.as_ref() == Some(column) || self.deck_column.as_ref() == Some(column)
+        };
+        let mut fields = HashMap::new();
+        if self.fields.is_empty() {
+            for (column, value) in &columns {
+                if !special(column) {
+                    fields.insert(column.clone(), value.clone());
+                }
+            }
+        } else {
+            for (column, field) in &self.fields {
+                let value = columns
+                    .get(column)
+                    .ok_or_else(|| format!("missing column '{}'", column))?;
+                fields.insert(field.clone(), value.clone());
+            }
+        }
+        if fields.values().all(|value| value.trim().is_empty()) {
+            return Err("record has no field values".to_string());
+        }
+
+        let deck = self
+            .deck_column
+            .as_ref()
+            .and_then(|column| columns.get(column))
+            .filter(|deck| !deck.trim().is_empty())
+            .map_or(self.deck.as_str(), |deck| deck.trim());
+        let mut tags = self.tags.clone();
+        if let Some(value) = self.tags_column.as_ref().and_then(|c| columns.get(c)) {
+            tags.extend(value.split_whitespace().map(str::to_string));
+        }
+
+        Ok(Note {
+            deck_name: deck.to_string(),
+            model_name: self.model.clone(),
+            fields,
+            tags,
+            audio: None,
+            video: None,
+            picture: None,
+            options: None,
+        })
+    }
+}
+
+/// Records from CSV or TSV text with a header row.
+///
+/// Quoted values may contain the delimiter, newlines, and doubled quotes
+/// (`""`). Blank lines are skipped.
+#[derive(Debug)]
+pub struct DelimitedSource {
+    records: std::vec::IntoIter<std::result::Result<SourceRecord, RecordError>>,
+}
+
+impl DelimitedSource {
+    /// Read comma-separated text. `name` identifies the source in
+    /// provenance.
+    pub fn csv(name: impl Into<String>, content: &str, mapping: FieldMapping) -> Self {
+        Self::new(name, content, ',', mapping)
+    }
+
+    /// Read tab-separated text.
+    pub fn tsv(name: impl Into<String>, content: &str, mapping: FieldMapping) -> Self {
+        Self::new(name, content, '\t', mapping)
+    }
+
+    /// Read text separated by `delimiter`.
+    pub fn new(
+        name: impl Into<String>,
+        content: &str,
+        delimiter: char,
+        mapping: FieldMapping,
+    ) -> Self {
+        let name = name.into();
+        let mut rows = parse_delimited(content, delimiter).into_iter();
+        let mut records = Vec::new();
+        let header = match rows.next() {
+            Some((_, Ok(header))) => header,
+            Some((line, Err(error))) => {
+                records.push(Err(RecordError {
+                    provenance: Provenance { source: name, line },
+                    error,
+                }));
+                return Self {
+                    records: records.into_iter(),
+                };
+            }
+            None => Vec::new(),
+        };
+        let header: Vec<String> = header.iter().map(|h| h.trim().to_string()).collect();
+
+        for (line, row) in rows {
+            let provenance = Provenance {
+                source: name.clone(),
+                line,
+            };
+            let record = row
+                .and_then(|values| {
+                    if values.len() != header.len() {
+                        return Err(format!(
+                            "expected {} columns, found {}",
+                            header.len(),
+                            values.len()
+                        ));
+                    }
+                    mapping.note(header.iter().cloned().zip(values).collect())
+                })
+                .map(|note| SourceRecord {
+                    note,
+                    provenance: provenance.clone(),
+                })
+                .map_err(|error| RecordError { provenance, error });
+            records.push(record);
+        }
+
+        Self {
+            records: records.into_iter(),
+        }
+    }
+
+    /// Read a file, treating `.tsv` and `.tab` files as tab-separated and
+    /// anything else as comma-separated.
+    pub fn from_path(path: impl AsRef<Path>, mapping: FieldMapping) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        let delimiter = match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("tsv") || ext.eq_ignore_ascii_case("tab") => '\t',
+            _ => ',',
+        };
+        Ok(Self::new(
+            path.display().to_string(),
+            &content,
+            delimiter,
+            mapping,
+        ))
+    }
+}
+
+impl Iterator for DelimitedSource {
+    type Item = std::result::Result<SourceRecord, RecordError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.records.next()
+    }
+}
+
+/// Records from JSON Lines text: one JSON object per line.
+///
+/// Object keys are the columns. Numbers and booleans are imported as text,
+/// and a tags column may be a string or an array of strings. Blank lines
+/// are skipped.
+#[derive(Debug)]
+pub struct JsonlSource {
+    records: std::vec::IntoIter<std::result::Result<SourceRecord, RecordError>>,
+}
+
+impl JsonlSource {
+    /// Read JSON Lines text. `name` identifies the source in provenance.
+    pub fn new(name: impl Into<String>, content: &str, mapping: FieldMapping) -> Self {
+        let name = name.into();
+        let records = content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                let provenance = Provenance {
+                    source: name.clone(),
+                    line: index + 1,
+                };
+                json_columns(line, &mapping)
+                    .and_then(|columns| mapping.note(columns))
+                    .map(|note| SourceRecord {
+                        note,
+                        provenance: provenance.clone(),
+                    })
+                    .map_err(|error| RecordError { provenance, error })
+            })
+            .collect::<Vec<_>>();
+        Self {
+            records: records.into_iter(),
+        }
+    }
+
+    /// Read a JSON Lines file.
+    pub fn from_path(path: impl AsRef<Path>, mapping: FieldMapping) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        Ok(Self::new(path.display().to_string(), &content, mapping))
+    }
+}
+
+impl Iterator for JsonlSource {
+    type Item = std::result::Result<SourceRecord, RecordError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.records.next()
+    }
+}
+
+/// Columns of one JSON Lines record.
+fn json_columns(
+    line: &str,
+    mapping: &FieldMapping,
+) -> std::result::Result<HashMap<String, String>, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(line).map_err(|e| format!("invalid JSON: {}", e))?;
+    let serde_json::Value::Object(object) = value else {
+        return Err("expected a JSON object".to_string());
+    };
+    let mut columns = HashMap::new();
+    for (key, value) in object {
+        let text = match value {
+            serde_json::Value::String(s) => s,
+            serde_json::Value::Null => String::new(),
+            serde_json::Value::Bool(b) => b.to_string(),
+            serde_json::Value::Number(n) => n.to_string(),
+            serde_json::Value::Array(items) if mapping.tags_column.as_ref() == Some(&key) => items
+                .iter()
+                .map(|item| {
+                    item.as_str()
+                        .map(str::to_string)
+                        .unwrap_or(item.to_string())
+                })
+                .collect::<Vec<_>>()
+                .join(" "),
+            _ => return Err(format!("value of '{}' is not text", key)),
+        };
+        columns.insert(key, text);
+    }
+    Ok(columns)
+}
+
+/// Split delimited text into rows, each with the line it starts on.
+fn parse_delimited(
+    content: &str,
+    delimiter: char,
+) -> Vec<(usize, std::result::Result<Vec<String>, String>)> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut value = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    value.push('"');
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    value.push(c);
+                }
+                _ => value.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if value.is_empty() => in_quotes = true,
+            c if c == delimiter => row.push(std::mem::take(&mut value)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                row.push(std::mem::take(&mut value));
+                if !(row.len() == 1 && row[0].trim().is_empty()) {
+                    rows.push((start, Ok(std::mem::take(&mut row))));
+                }
+                row.clear();
+                line += 1;
+                start = line;
+            }
+            _ => value.push(c),
+        }
+    }
+
+    if in_quotes {
+        rows.push((start, Err("unterminated quoted value".to_string())));
+    } else {
+        row.push(value);
+        if !(row.len() == 1 && row[0].trim().is_empty()) {
+            rows.push((start, Ok(row)));
+        }
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_delimited_quotes() {
+        let rows = parse_delimited("a,b\n\"x, y\",\"say \"\"hi\"\"\nthere\"\n\nz,w", ',');
+        let rows: Vec<(usize, Vec<String>)> = rows
+            .into_iter()
+            .map(|(line, row)| (line, row.unwrap()))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                (1, vec!["a".to_string(), "b".to_string()]),
+                (2, vec!["x, y".to_string(), "say \"hi\"\nthere".to_string()]),
+                (5, vec!["z".to_string(), "w".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_delimited_unterminated() {
+        let rows = parse_delimited("a,b\n\"open,x\n", ',');
+        assert!(rows[0].1.is_ok());
+        assert_eq!(rows[1].0, 2);
+        assert!(rows[1].1.is_err());
+    }
+
+    #[test]
+    fn test_delimited_source_records() {
+        let mapping = FieldMapping::new("Default", "Basic")
+            .field("word", "Front")
+            .field("meaning", "Back")
+            .tags_column("tags")
+            .deck_column("deck")
+            .tag("imported");
+        let content = "word\tmeaning\ttags\tdeck\nperro\tdog\tanimal noun\tSpanish\ngato\tcat\n";
+        let records: Vec<_> = DelimitedSource::tsv("vocab.tsv", content, mapping).collect();
+
+        let first = records[0].as_ref().unwrap();
+        assert_eq!(first.note.deck_name, "Spanish");
+        assert_eq!(first.note.fields["Front"], "perro");
+        assert_eq!(first.note.tags, vec!["imported", "animal", "noun"]);
+        assert_eq!(first.provenance.to_string(), "vocab.tsv:2");
+
+        let error = records[1].as_ref().unwrap_err();
+        assert_eq!(error.provenance.line, 3);
+        assert!(error.error.contains("expected 4 columns"));
+    }
+
+    #[test]
+    fn test_jsonl_source_records() {
+        let mapping = FieldMapping::new("Default", "Basic").tags_column("tags");
+        let content =
+            "{\"Front\": \"uno\", \"Back\": 1, \"tags\": [\"a\", \"b\"]}\n\nnot json\n[1]";
+        let records: Vec<_> = JsonlSource::new("data.jsonl", content, mapping).collect();
+
+        let first = records[0].as_ref().unwrap();
+        assert_eq!(first.note.fields["Back"], "1");
+        assert_eq!(first.note.tags, vec!["a", "b"]);
+        assert!(!first.note.fields.contains_key("tags"));
+        assert_eq!(records[1].as_ref().unwrap_err().provenance.line, 3);
+        assert_eq!(
+            records[2].as_ref().unwrap_err().error,
+            "expected a JSON object"
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! Progress reporting for long-running workflows.
+//!
+//! Bulk workflows tell a [`ProgressSink`], set in
+//! [`EngineOptions::progress_sink`](crate::EngineOptions::progress_sink), how
+//! far they have got. Each [`ProgressUpdate`] names the current phase and
+//! counts the items processed so far. Workflows that report progress:
+//!
+//! - `import().notes` (and the imports built on it)
+//! - `migrate().notes`
+//! - `export().deck`
+//! - `deduplicate().remove_duplicates`
+//! - `media().cleanup_orphaned`
+//!
+//! Any `Fn(&ProgressUpdate)` closure is a sink, and so is an unbounded Tokio
+//! channel sender, for streaming updates to another task.
+//!
+//! A sink can also cancel the workflow by returning `true` from
+//! [`ProgressSink::is_cancelled`]. Workflows check between batches, so a
+//! request already sent to Anki always completes, and return
+//! [`Error::Cancelled`](crate::Error::Cancelled) without starting the next
+//! step. Work done before the cancellation is kept; a migration never
+//! deletes its source notes once cancelled.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use ankit_engine::{Engine, EngineOptions, NoteBuilder};
+//! use ankit_engine::import::OnDuplicate;
+//! use ankit_engine::status::ProgressUpdate;
+//!
+//! # async fn example(notes: Vec<ankit_engine::Note>) -> ankit_engine::Result<()> {
+//! let engine = Engine::new().with_options(EngineOptions {
+//!     progress_sink: Some(Arc::new(|update: &ProgressUpdate| {
+//!         eprintln!("{}", update);
+//!     })),
+//!     ..Default::default()
+//! });
+//! engine.import().notes(&notes, OnDuplicate::Skip).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+
+use serde::Serialize;
+
+#[cfg(any(
+    feature = "deduplicate",
+    feature = "export",
+    feature = "import",
+    feature = "media",
+    feature = "migrate"
+))]
+mod tracker;
+
+#[cfg(any(
+    feature = "deduplicate",
+    feature = "export",
+    feature = "import",
+    feature = "media",
+    feature = "migrate"
+))]
+pub(crate) use tracker::Tracker;
+
+/// How far a workflow has got.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProgressUpdate {
+    /// What the workflow is doing, e.g. `"adding notes"`.
+    pub phase: &'static str,
+    /// Items processed so far in this phase.
+    pub processed: u64,
+    /// Items in this phase, if known.
+    pub total: Option<u64>,
+}
+
+impl ProgressUpdate {
+    /// Fraction of the phase completed, from 0.0 to 1.0, if the total is known.
+    pub fn fraction(&self) -> Option<f64> {
+        match self.total {
+            Some(0) => Some(1.0),
+            Some(total) => Some((self.processed as f64 / total as f64).min(1.0)),
+            None => None,
+        }
+    }
+}
+
+impl fmt::Display for ProgressUpdate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.total {
+            Some(total) => write!(f, "{}: {}/{}", self.phase, self.processed, total),
+            None => write!(f, "{}: {}", self.phase, self.processed),
+        }
+    }
+}
+
+/// Receives progress updates from workflows.
+///
+/// Updates may arrive from several tasks at once when workflows run
+/// requests concurrently, so implementations must be thread-safe and should
+/// return quickly.
+pub trait ProgressSink: Send + Sync {
+    /// Handle one update.
+    fn update(&self, update: &ProgressUpdate);
+
+    /// Whether the workflow should stop at the next batch boundary.
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+}
+
+impl<F> ProgressSink for F
+where
+    F: Fn(&ProgressUpdate) + Send + Sync,
+{
+    fn update(&self, update: &ProgressUpdate) {
+        self(update)
+    }
+}
+
+impl ProgressSink for tokio::sync::mpsc::UnboundedSender<ProgressUpdate> {
+    fn update(&self, update: &ProgressUpdate) {
+        // A closed receiver just means nobody is listening any more
+        let _ = self.send(update.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fraction() {
+        let update = |processed, total| ProgressUpdate {
+            phase: "test",
+            processed,
+            total,
+        };
+        assert_eq!(update(1, Some(4)).fraction(), Some(0.25));
+        assert_eq!(update(0, Some(0)).fraction(), Some(1.0));
+        assert_eq!(update(3, None).fraction(), None);
+    }
+}
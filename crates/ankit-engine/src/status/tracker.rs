@@ -0,0 +1,123 @@
+//! Progress tracking for a single workflow phase.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::{ProgressSink, ProgressUpdate};
+use crate::{Error, Result};
+
+/// Counts progress through one phase and reports it to the sink, if any.
+pub(crate) struct Tracker {
+    sink: Option<Arc<dyn ProgressSink>>,
+    phase: &'static str,
+    total: u64,
+    processed: AtomicU64,
+}
+
+impl Tracker {
+    /// Start a phase of `total` items, reporting 0 processed.
+    pub(crate) fn new(
+        sink: Option<&Arc<dyn ProgressSink>>,
+        phase: &'static str,
+        total: usize,
+    ) -> Self {
+        let tracker = Self {
+            sink: sink.cloned(),
+            phase,
+            total: total as u64,
+            processed: AtomicU64::new(0),
+        };
+        tracker.report(0);
+        tracker
+    }
+
+    /// Record `n` more items processed.
+    pub(crate) fn advance(&self, n: usize) {
+        if self.sink.is_some() {
+            let processed = self.processed.fetch_add(n as u64, Ordering::Relaxed) + n as u64;
+            self.report(processed);
+        }
+    }
+
+    /// Whether the sink has asked the workflow to stop.
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.sink.as_ref().is_some_and(|sink| sink.is_cancelled())
+    }
+
+    /// Return [`Error::Cancelled`] if the sink has asked the workflow to stop.
+    pub(crate) fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        Ok(())
+    }
+
+    fn report(&self, processed: u64) {
+        if let Some(sink) = &self.sink {
+            sink.update(&ProgressUpdate {
+                phase: self.phase,
+                processed,
+                total: Some(self.total),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_tracker_reports_to_closure() {
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let sink: Arc<dyn ProgressSink> = {
+            let updates = updates.clone();
+            Arc::new(move |update: &ProgressUpdate| {
+                updates.lock().unwrap().push(update.to_string());
+            })
+        };
+
+        let tracker = Tracker::new(Some(&sink), "adding notes", 3);
+        tracker.advance(2);
+        tracker.advance(1);
+
+        assert_eq!(
+            *updates.lock().unwrap(),
+            [
+                "adding notes: 0/3",
+                "adding notes: 2/3",
+                "adding notes: 3/3"
+            ]
+        );
+    }
+
+    struct Cancelled;
+
+    impl ProgressSink for Cancelled {
+        fn update(&self, _: &ProgressUpdate) {}
+
+        fn is_cancelled(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_tracker_check() {
+        assert!(Tracker::new(None, "adding notes", 1).check().is_ok());
+
+        let sink: Arc<dyn ProgressSink> = Arc::new(Cancelled);
+        let tracker = Tracker::new(Some(&sink), "adding notes", 1);
+        assert!(matches!(tracker.check(), Err(Error::Cancelled)));
+    }
+
+    #[test]
+    fn test_channel_sink() {
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let sink: Arc<dyn ProgressSink> = Arc::new(sender);
+        Tracker::new(Some(&sink), "deleting files", 1).advance(1);
+
+        assert_eq!(receiver.try_recv().unwrap().processed, 0);
+        assert_eq!(receiver.try_recv().unwrap().processed, 1);
+    }
+}
@@ -0,0 +1,205 @@
+//! Text-to-speech providers for audio enrichment.
+//!
+//! [`EnrichEngine::generate_audio`](crate::enrich::EnrichEngine::generate_audio)
+//! turns a text field into audio with a [`TtsProvider`]. Implement the trait
+//! to call an external service, or use [`CommandTts`] to run a local tool
+//! such as `espeak-ng` or `piper`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ankit_engine::Engine;
+//! use ankit_engine::enrich::AudioField;
+//! use ankit_engine::tts::CommandTts;
+//!
+//! # async fn example() -> ankit_engine::Result<()> {
+//! let engine = Engine::new();
+//!
+//! // espeak-ng writes a WAV file to the path given with -w
+//! let espeak = CommandTts::new("espeak-ng", "wav").args(["-v", "es", "-w", "{output}", "{text}"]);
+//!
+//! let field = AudioField::new("Front", "Audio");
+//! let report = engine.enrich().generate_audio("deck:Spanish", &field, &espeak).await?;
+//! println!("Generated {} audio files", report.generated);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::{Error, Result};
+
+/// A source of synthesized speech.
+pub trait TtsProvider {
+    /// File extension of the audio this provider produces, without the dot
+    /// (for example `"mp3"`).
+    fn extension(&self) -> &str;
+
+    /// Synthesize speech for plain text, returning the encoded audio.
+    fn synthesize(&self, text: &str) -> impl Future<Output = Result<Vec<u8>>> + Send;
+}
+
+/// A [`TtsProvider`] that runs a local command.
+///
+/// Arguments may contain two placeholders:
+///
+/// - `{text}` - replaced with the text to speak
+/// - `{output}` - replaced with the path of a temporary file to write to
+///
+/// If no argument contains `{text}`, the text is written to the command's
+/// standard input. If no argument contains `{output}`, the audio is read from
+/// its standard output.
+///
+/// # Example
+///
+/// ```
+/// use ankit_engine::tts::CommandTts;
+///
+/// // piper reads text from stdin
+/// let piper = CommandTts::new("piper", "wav")
+///     .args(["--model", "es_ES-davefx-medium.onnx", "--output_file", "{output}"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CommandTts {
+    program: String,
+    args: Vec<String>,
+    extension: String,
+}
+
+impl CommandTts {
+    /// Create a provider that runs `program` and produces `extension` files.
+    pub fn new(program: impl Into<String>, extension: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            extension: extension.into(),
+        }
+    }
+
+    /// Set the command's arguments.
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// A unique temporary path for one synthesis.
+    fn temp_path(&self) -> PathBuf {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        std::env::temp_dir().join(format!(
+            "ankit-tts-{}-{}.{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed),
+            self.extension
+        ))
+    }
+}
+
+impl TtsProvider for CommandTts {
+    fn extension(&self) -> &str {
+        &self.extension
+    }
+
+    async fn synthesize(&self, text: &str) -> Result<Vec<u8>> {
+        let text_arg = self.args.iter().any(|a| a.contains("{text}"));
+        let output = self
+            .args
+            .iter()
+            .any(|a| a.contains("{output}"))
+            .then(|| self.temp_path());
+        let output_str = output
+            .as_ref()
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let mut command = Command::new(&self.program);
+        command
+            .args(
+                self.args
+                    .iter()
+                    .map(|a| a.replace("{text}", text).replace("{output}", &output_str)),
+            )
+            .stdin(if text_arg {
+                Stdio::null()
+            } else {
+                Stdio::piped()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        let mut child = command
+            .spawn()
+            .map_err(|e| Error::Tts(format!("failed to run '{}': {}", self.program, e)))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes()).await?;
+        }
+        let result = child.wait_with_output().await?;
+
+        if !result.status.success() {
+            if let Some(path) = &output {
+                let _ = std::fs::remove_file(path);
+            }
+            return Err(Error::Tts(format!(
+                "'{}' exited with {}: {}",
+                self.program,
+                result.status,
+                String::from_utf8_lossy(&result.stderr).trim()
+            )));
+        }
+
+        let audio = match &output {
+            Some(path) => {
+                let audio = std::fs::read(path).map_err(|e| {
+                    Error::Tts(format!("'{}' did not write audio: {}", self.program, e))
+                });
+                let _ = std::fs::remove_file(path);
+                audio?
+            }
+            None => result.stdout,
+        };
+        if audio.is_empty() {
+            return Err(Error::Tts(format!("'{}' produced no audio", self.program)));
+        }
+        Ok(audio)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_command_stdout_and_stdin() {
+        // `cat` echoes stdin to stdout
+        let tts = CommandTts::new("cat", "txt");
+        assert_eq!(tts.synthesize("hola").await.unwrap(), b"hola");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_command_output_file() {
+        let tts = CommandTts::new("sh", "txt").args([
+            "-c",
+            "printf '%s' \"$0\" > \"$1\"",
+            "{text}",
+            "{output}",
+        ]);
+        assert_eq!(tts.synthesize("adiós").await.unwrap(), "adiós".as_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_command_failure() {
+        let tts = CommandTts::new("ankit-no-such-tts-command", "wav");
+        assert!(matches!(tts.synthesize("x").await, Err(Error::Tts(_))));
+    }
+}
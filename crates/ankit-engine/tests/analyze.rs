@@ -2,9 +2,16 @@
 
 mod common;
 
-use ankit_engine::analyze::{CompareOptions, PlanOptions, ProblemCriteria};
+use ankit_engine::analyze::{
+    CompareOptions, HeatmapOptions, LeechAction, LeechPolicy, PlanOptions, ProblemCriteria,
+    SlowCardSuggestion,
+};
+use ankit_engine::changes::PlannedChange;
+use ankit_engine::report::WorkflowReport;
 use common::{
-    engine_for_mock, mock_action, mock_action_times, mock_anki_response, setup_mock_server,
+    dry_run_engine_for_mock, engine_for_mock, mock_action, mock_action_times,
+    mock_action_with_params, mock_anki_error, mock_anki_response, mock_deck_config, mock_sequence,
+    setup_mock_server,
 };
 
 #[tokio::test]
@@ -38,6 +45,11 @@ async fn test_study_summary() {
     assert_eq!(summary.unique_cards, 10);
     assert_eq!(summary.daily.len(), 3);
     assert_eq!(summary.daily[0].reviews, 50);
+    assert_eq!(
+        summary.render(),
+        "125 reviews over 3 days (41.7 per day), 10 unique cards\n  \
+         2024-01-15: 50 reviews\n  2024-01-14: 30 reviews\n  2024-01-13: 45 reviews"
+    );
 }
 
 #[tokio::test]
@@ -415,6 +427,8 @@ async fn test_deck_audit() {
     )
     .await;
 
+    mock_deck_config(&server, 8).await;
+
     let engine = engine_for_mock(&server);
     let audit = engine.analyze().deck_audit("Japanese").await.unwrap();
 
@@ -448,6 +462,13 @@ async fn test_deck_audit() {
 
     // Average ease (only cards with ease > 0: 2500, 2000, 2500 = 2333.33)
     assert!(audit.average_ease > 2300.0 && audit.average_ease < 2400.0);
+
+    // Maturity: one young (10 days) and one mature (30 days) review card
+    assert_eq!(audit.maturity.new, 1);
+    assert_eq!(audit.maturity.learning, 1);
+    assert_eq!(audit.maturity.young, 1);
+    assert_eq!(audit.maturity.mature, 1);
+    assert_eq!(audit.maturity.average_interval, 20.0);
 }
 
 #[tokio::test]
@@ -465,6 +486,7 @@ async fn test_deck_audit_empty() {
     assert_eq!(audit.total_notes, 0);
     assert!(audit.cards_by_model.is_empty());
     assert!(audit.tag_distribution.is_empty());
+    assert_eq!(audit.maturity.histogram.len(), 12);
 }
 
 #[tokio::test]
@@ -557,6 +579,8 @@ async fn test_study_report() {
     )
     .await;
 
+    mock_deck_config(&server, 8).await;
+
     let engine = engine_for_mock(&server);
     let report = engine.analyze().study_report("Japanese", 7).await.unwrap();
 
@@ -659,6 +683,8 @@ async fn test_study_report_all_decks() {
     )
     .await;
 
+    mock_deck_config(&server, 8).await;
+
     let engine = engine_for_mock(&server);
     let report = engine.analyze().study_report("*", 7).await.unwrap();
 
@@ -755,6 +781,7 @@ async fn test_compare_decks_exact_matches() {
             CompareOptions {
                 key_field: "Front".to_string(),
                 similarity_threshold: 1.0, // Exact matches only
+                ..Default::default()
             },
         )
         .await
@@ -806,6 +833,7 @@ async fn test_compare_decks_similar_matches() {
             CompareOptions {
                 key_field: "Front".to_string(),
                 similarity_threshold: 0.7,
+                ..Default::default()
             },
         )
         .await
@@ -961,6 +989,74 @@ async fn test_compare_decks_preserves_tags() {
     );
 }
 
+#[tokio::test]
+async fn test_compare_decks_large() {
+    let server = setup_mock_server().await;
+
+    // Every note in A is one letter away from the note in B with the same
+    // number, and at least two away from the rest
+    const COUNT: i64 = 2000;
+    let notes = |first_id: i64, suffix: &str| -> Vec<serde_json::Value> {
+        (0..COUNT)
+            .map(|i| {
+                serde_json::json!({
+                    "noteId": first_id + i,
+                    "modelName": "Basic",
+                    "tags": [],
+                    "fields": {
+                        "Front": {"value": format!("word{:05}{}", i, suffix), "order": 0},
+                        "Back": {"value": "", "order": 1}
+                    }
+                })
+            })
+            .collect()
+    };
+    mock_sequence(
+        &server,
+        "findNotes",
+        vec![
+            mock_anki_response((1..=COUNT).collect::<Vec<_>>()),
+            mock_anki_response((COUNT + 1..=2 * COUNT).collect::<Vec<_>>()),
+        ],
+    )
+    .await;
+    mock_sequence(
+        &server,
+        "notesInfo",
+        vec![
+            mock_anki_response(notes(1, "a")),
+            mock_anki_response(notes(COUNT + 1, "b")),
+        ],
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let comparison = engine
+        .analyze()
+        .compare_decks(
+            "Deck A",
+            "Deck B",
+            CompareOptions {
+                key_field: "Front".to_string(),
+                similarity_threshold: 0.85,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    assert!(comparison.exact_matches.is_empty());
+    assert_eq!(comparison.similar.len(), COUNT as usize);
+    assert!(
+        comparison
+            .similar
+            .iter()
+            .all(|pair| pair.note_b.note_id == pair.note_a.note_id + COUNT)
+    );
+    assert!(comparison.only_in_a.is_empty());
+    assert!(comparison.only_in_b.is_empty());
+}
+
 #[tokio::test]
 async fn test_study_plan_basic() {
     let server = setup_mock_server().await;
@@ -1038,6 +1134,8 @@ async fn test_study_plan_basic() {
     )
     .await;
 
+    mock_deck_config(&server, 8).await;
+
     let engine = engine_for_mock(&server);
     let plan = engine
         .analyze()
@@ -1149,7 +1247,7 @@ async fn test_study_plan_with_leeches() {
             PlanOptions {
                 target_time_minutes: 30,
                 prioritize_leeches: true,
-                leech_threshold: 8,
+                leech_threshold: Some(8),
                 ..PlanOptions::default()
             },
         )
@@ -1203,6 +1301,8 @@ async fn test_study_plan_time_constraint() {
         .collect();
     mock_action(&server, "cardsInfo", mock_anki_response(cards)).await;
 
+    mock_deck_config(&server, 8).await;
+
     let engine = engine_for_mock(&server);
     let plan = engine
         .analyze()
@@ -1342,6 +1442,8 @@ async fn test_study_plan_with_new_cards() {
     )
     .await;
 
+    mock_deck_config(&server, 8).await;
+
     let engine = engine_for_mock(&server);
     let plan = engine
         .analyze()
@@ -1369,3 +1471,818 @@ async fn test_study_plan_with_new_cards() {
             .any(|r| r.contains("new card") || r.contains("Introducing"))
     );
 }
+
+#[tokio::test]
+async fn test_tag_stats() {
+    let server = setup_mock_server().await;
+
+    mock_action(&server, "findNotes", mock_anki_response(vec![1_i64, 2, 3])).await;
+    mock_action(
+        &server,
+        "notesInfo",
+        mock_anki_response(vec![
+            serde_json::json!({"noteId": 1, "modelName": "Basic", "tags": ["lang::jp::verb", "lang::jp"], "fields": {}}),
+            serde_json::json!({"noteId": 2, "modelName": "Basic", "tags": ["Lang::JP::noun"], "fields": {}}),
+            serde_json::json!({"noteId": 3, "modelName": "Basic", "tags": [], "fields": {}}),
+        ]),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let stats = engine.analyze().tag_stats("deck:*").await.unwrap();
+
+    assert_eq!(stats.total_notes, 3);
+    assert_eq!(stats.untagged_notes, 1);
+    assert_eq!(stats.max_depth, 2);
+
+    let jp = stats.tags.iter().find(|t| t.tag == "lang::jp").unwrap();
+    assert_eq!(jp.notes, 1);
+    assert_eq!(jp.total_notes, 2);
+    assert_eq!(jp.children, 2);
+}
+
+/// A review card with the given ID, lapses, and ease factor.
+fn leech_card(card_id: i64, lapses: i64, factor: i64) -> serde_json::Value {
+    serde_json::json!({
+        "cardId": card_id,
+        "noteId": card_id + 100,
+        "deckName": "Japanese",
+        "modelName": "Basic",
+        "question": "",
+        "answer": "",
+        "fields": {},
+        "type": 2,
+        "queue": 2,
+        "due": 0,
+        "interval": 20,
+        "factor": factor,
+        "reps": 30,
+        "lapses": lapses,
+        "left": 0,
+        "mod": 0
+    })
+}
+
+/// Mount findCards, cardsInfo, and one notesInfo per leech.
+async fn mock_leeches(server: &wiremock::MockServer, cards: Vec<serde_json::Value>, leeches: u64) {
+    let ids: Vec<i64> = cards
+        .iter()
+        .map(|c| c["cardId"].as_i64().unwrap())
+        .collect();
+    mock_action(server, "findCards", mock_anki_response(ids)).await;
+    mock_action(server, "cardsInfo", mock_anki_response(cards)).await;
+    mock_action_times(
+        server,
+        "notesInfo",
+        mock_anki_response(vec![serde_json::json!({
+            "noteId": 101_i64,
+            "modelName": "Basic",
+            "tags": [],
+            "fields": {"Front": {"value": "Leech", "order": 0}}
+        })]),
+        leeches,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_remediate_leeches_dry_run() {
+    let server = setup_mock_server().await;
+    mock_leeches(
+        &server,
+        vec![
+            leech_card(1, 9, 1500),
+            leech_card(2, 0, 1800),
+            leech_card(3, 0, 2500),
+        ],
+        2,
+    )
+    .await;
+
+    let policy = LeechPolicy {
+        high_lapses: vec![
+            LeechAction::MoveToDeck {
+                deck: "Leeches".to_string(),
+            },
+            LeechAction::ReduceEase { by: 300 },
+            LeechAction::Suspend,
+        ],
+        ..LeechPolicy::default()
+    };
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine
+        .analyze()
+        .remediate_leeches("deck:Japanese", &policy)
+        .await
+        .unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.cards.len(), 2);
+    assert_eq!(report.cards[0].actions.len(), 3);
+    assert_eq!(
+        report.cards[1].actions,
+        vec![LeechAction::Tag {
+            tag: "leech".to_string()
+        }]
+    );
+
+    // Ease is floored at 130%
+    assert!(report.planned.iter().any(|c| matches!(
+        c,
+        PlannedChange::SetEase { card_ids, ease_factors }
+            if card_ids == &[1] && ease_factors == &[1300]
+    )));
+    assert!(report.planned.iter().any(|c| matches!(
+        c,
+        PlannedChange::MoveCards { deck, .. } if deck == "Leeches"
+    )));
+    assert!(report.planned.iter().any(|c| matches!(
+        c,
+        PlannedChange::AddTags { note_ids, .. } if note_ids == &[102]
+    )));
+    assert!(matches!(
+        report.planned.last(),
+        Some(PlannedChange::SuspendCards { card_ids }) if card_ids == &[1]
+    ));
+}
+
+#[tokio::test]
+async fn test_remediate_leeches() {
+    let server = setup_mock_server().await;
+    mock_leeches(&server, vec![leech_card(1, 9, 2500)], 1).await;
+    mock_action_with_params(
+        &server,
+        "setDueDate",
+        serde_json::json!({"cards": [1], "days": "3"}),
+        mock_anki_response(true),
+    )
+    .await;
+    mock_action(&server, "suspend", mock_anki_response(true)).await;
+
+    let policy = LeechPolicy::all(
+        ProblemCriteria::default(),
+        vec![
+            LeechAction::Suspend,
+            LeechAction::Reschedule {
+                days: "3".to_string(),
+            },
+        ],
+    );
+
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .analyze()
+        .remediate_leeches("deck:Japanese", &policy)
+        .await
+        .unwrap();
+
+    assert!(!report.dry_run);
+    assert_eq!(report.cards.len(), 1);
+    assert_eq!(report.cards[0].card_id, 1);
+    assert!(report.planned.is_empty());
+}
+
+#[tokio::test]
+async fn test_review_heatmap() {
+    let server = setup_mock_server().await;
+    mock_action(&server, "findCards", mock_anki_response(vec![1_i64, 2])).await;
+
+    let review = |card_id: i64, id: i64| {
+        serde_json::json!({
+            "cardId": card_id, "id": id, "ease": 3, "ivl": 4, "lastIvl": 1,
+            "factor": 2500, "time": 6000, "type": 1
+        })
+    };
+    // 2024-01-01 10:00 and 11:00 UTC, then 2024-01-02 10:00 UTC
+    mock_action(
+        &server,
+        "getReviewsOfCards",
+        mock_anki_response(serde_json::json!({
+            "1": [review(1, 1_704_103_200_000), review(1, 1_704_189_600_000)],
+            "2": [review(2, 1_704_106_800_000)]
+        })),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let heatmap = engine
+        .analyze()
+        .review_heatmap("deck:Japanese", HeatmapOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(heatmap.total_reviews, 3);
+    assert_eq!(heatmap.active_days, 2);
+    assert_eq!(heatmap.longest_streak, 2);
+    assert_eq!(heatmap.days[0].date, "2024-01-01");
+    assert_eq!(heatmap.days[0].reviews, 2);
+    assert_eq!(heatmap.days[0].time_ms, 12000);
+    // 2024-01-01 was a Monday
+    assert_eq!(heatmap.by_weekday[0], 2);
+    assert_eq!(heatmap.by_weekday[1], 1);
+    assert_eq!(heatmap.by_hour[10], 2);
+}
+
+fn cohort_card(card_id: i64, factor: i64, lapses: i64) -> serde_json::Value {
+    serde_json::json!({
+        "cardId": card_id, "noteId": card_id + 100, "deckName": "Japanese",
+        "modelName": "Basic", "question": "", "answer": "", "fields": {},
+        "type": 2, "queue": 2, "due": 0, "interval": 10, "factor": factor,
+        "reps": 10, "lapses": lapses, "left": 0, "mod": 0
+    })
+}
+
+fn cohort_reviews(card_id: i64, passed: usize, failed: usize) -> Vec<serde_json::Value> {
+    let answer = |i: usize, ease: i64| {
+        serde_json::json!({
+            "cardId": card_id, "id": 1_704_103_200_000_i64 + i as i64, "ease": ease,
+            "ivl": 4, "lastIvl": 1, "factor": 2500, "time": 6000, "type": 1
+        })
+    };
+    let mut reviews: Vec<_> = (0..passed).map(|i| answer(i, 3)).collect();
+    reviews.extend((passed..passed + failed).map(|i| answer(i, 1)));
+    // Learning steps are left out of retention
+    reviews.push(serde_json::json!({
+        "cardId": card_id, "id": 1_704_000_000_000_i64, "ease": 1,
+        "ivl": -60, "lastIvl": 0, "factor": 0, "time": 6000, "type": 0
+    }));
+    reviews
+}
+
+#[tokio::test]
+async fn test_compare_cohorts() {
+    let server = setup_mock_server().await;
+    mock_action_with_params(
+        &server,
+        "findCards",
+        serde_json::json!({"query": "tag:variant::a"}),
+        mock_anki_response(vec![1_i64, 2]),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "findCards",
+        serde_json::json!({"query": "tag:variant::b"}),
+        mock_anki_response(vec![2_i64, 3, 4]),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "cardsInfo",
+        serde_json::json!({"cards": [1, 2]}),
+        mock_anki_response(vec![cohort_card(1, 2500, 1), cohort_card(2, 2500, 0)]),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "cardsInfo",
+        serde_json::json!({"cards": [2, 3, 4]}),
+        mock_anki_response(vec![
+            cohort_card(2, 2500, 0),
+            cohort_card(3, 2500, 0),
+            cohort_card(4, 2500, 0),
+        ]),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "getReviewsOfCards",
+        serde_json::json!({"cards": [1, 2]}),
+        mock_anki_response(serde_json::json!({
+            "1": cohort_reviews(1, 10, 10),
+            "2": cohort_reviews(2, 10, 10)
+        })),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "getReviewsOfCards",
+        serde_json::json!({"cards": [2, 3, 4]}),
+        mock_anki_response(serde_json::json!({
+            "2": cohort_reviews(2, 20, 0),
+            "3": cohort_reviews(3, 20, 0),
+            "4": cohort_reviews(4, 20, 0)
+        })),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let comparison = engine
+        .analyze()
+        .compare_cohorts("tag:variant::a", "tag:variant::b")
+        .await
+        .unwrap();
+
+    assert_eq!(comparison.a.cards, 2);
+    assert_eq!(comparison.b.cards, 3);
+    assert_eq!(comparison.shared_cards, 1);
+    assert_eq!(comparison.a.reviews, 40);
+    assert!((comparison.a.retention.value - 0.5).abs() < 1e-9);
+    assert!(comparison.a.retention.low < 0.5 && comparison.a.retention.high > 0.5);
+    assert!((comparison.b.retention.value - 1.0).abs() < 1e-9);
+    assert!(comparison.b.retention.high <= 1.0);
+
+    let retention = comparison.retention.as_ref().unwrap();
+    assert!((retention.difference - 0.5).abs() < 1e-9);
+    assert!(retention.low > 0.0 && retention.high > retention.difference);
+    assert!(retention.p_value < 0.001);
+    assert!(retention.significant);
+
+    // Identical ease factors: no difference at all
+    let ease = comparison.ease.as_ref().unwrap();
+    assert_eq!(ease.difference, 0.0);
+    assert_eq!(ease.p_value, 1.0);
+    assert!(!ease.significant);
+
+    let lapse_rate = comparison.lapse_rate.as_ref().unwrap();
+    assert!((lapse_rate.difference + 0.5).abs() < 1e-9);
+    assert!(!lapse_rate.significant);
+}
+
+#[tokio::test]
+async fn test_compare_cohorts_empty_cohort() {
+    let server = setup_mock_server().await;
+    mock_action_with_params(
+        &server,
+        "findCards",
+        serde_json::json!({"query": "tag:variant::a"}),
+        mock_anki_response(vec![1_i64]),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "findCards",
+        serde_json::json!({"query": "tag:variant::b"}),
+        mock_anki_response(Vec::<i64>::new()),
+    )
+    .await;
+    mock_action(
+        &server,
+        "cardsInfo",
+        mock_anki_response(vec![cohort_card(1, 2300, 0)]),
+    )
+    .await;
+    mock_action(
+        &server,
+        "getReviewsOfCards",
+        mock_anki_response(serde_json::json!({"1": cohort_reviews(1, 3, 1)})),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let comparison = engine
+        .analyze()
+        .compare_cohorts("tag:variant::a", "tag:variant::b")
+        .await
+        .unwrap();
+
+    assert_eq!(comparison.a.ease.n, 1);
+    assert!((comparison.a.ease.value - 230.0).abs() < 1e-9);
+    assert_eq!(comparison.b.cards, 0);
+    assert!(comparison.retention.is_none());
+    assert!(comparison.ease.is_none());
+    assert!(comparison.lapse_rate.is_none());
+}
+
+#[tokio::test]
+async fn test_time_of_day() {
+    let server = setup_mock_server().await;
+    mock_action_with_params(
+        &server,
+        "findCards",
+        serde_json::json!({"query": "deck:\"Japanese\""}),
+        mock_anki_response(vec![1_i64]),
+    )
+    .await;
+
+    let answer = |id: i64, ease: i64, review_type: i64, time: i64| {
+        serde_json::json!({
+            "cardId": 1, "id": id, "ease": ease, "ivl": 4, "lastIvl": 1,
+            "factor": 2500, "time": time, "type": review_type
+        })
+    };
+    // Monday 2024-01-01: 25 correct answers at 10:00 UTC, 15 of 25 at 20:00
+    let morning = 1_704_103_200_000_i64;
+    let evening = morning + 10 * 3_600_000;
+    let mut reviews: Vec<_> = (0..25).map(|i| answer(morning + i, 3, 1, 4000)).collect();
+    reviews.extend((0..25).map(|i| answer(evening + i, if i < 10 { 1 } else { 3 }, 1, 8000)));
+    // A failed learning step doesn't count against accuracy
+    reviews.push(answer(morning + 100, 1, 0, 4000));
+    mock_action(
+        &server,
+        "getReviewsOfCards",
+        mock_anki_response(serde_json::json!({ "1": reviews })),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let stats = engine.analyze().time_of_day("Japanese").await.unwrap();
+
+    assert_eq!(stats.total_reviews, 51);
+    let ten = &stats.by_hour[10];
+    assert_eq!(ten.reviews, 26);
+    assert_eq!(ten.review_answers, 25);
+    assert_eq!(ten.accuracy, Some(1.0));
+    assert_eq!(ten.avg_answer_ms, 4000);
+    let twenty = &stats.by_hour[20];
+    assert_eq!(twenty.accuracy, Some(0.6));
+    assert_eq!(twenty.avg_answer_ms, 8000);
+    assert!(stats.by_hour[0].accuracy.is_none());
+    assert_eq!(stats.best_hour, Some(10));
+
+    assert_eq!(stats.by_weekday[0].reviews, 51);
+    assert_eq!(stats.by_weekday[0].correct, 40);
+    assert_eq!(stats.best_weekday, Some(0));
+}
+
+#[tokio::test]
+async fn test_time_of_day_with_offset() {
+    let server = setup_mock_server().await;
+    mock_action(&server, "findCards", mock_anki_response(vec![1_i64])).await;
+    // 2024-01-01 02:00 UTC is Sunday 21:00 in UTC-5
+    mock_action(
+        &server,
+        "getReviewsOfCards",
+        mock_anki_response(serde_json::json!({ "1": [{
+            "cardId": 1, "id": 1_704_074_400_000_i64, "ease": 3, "ivl": 4,
+            "lastIvl": 1, "factor": 2500, "time": 5000, "type": 1
+        }] })),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let options = HeatmapOptions {
+        utc_offset_minutes: -300,
+        ..Default::default()
+    };
+    let stats = engine
+        .analyze()
+        .time_of_day_with("Japanese", options)
+        .await
+        .unwrap();
+
+    assert_eq!(stats.by_hour[21].reviews, 1);
+    assert_eq!(stats.by_weekday[6].reviews, 1);
+    // Too few answers to pick a best time
+    assert_eq!(stats.best_hour, None);
+}
+
+#[tokio::test]
+async fn test_slow_cards() {
+    let server = setup_mock_server().await;
+    mock_action(
+        &server,
+        "findCards",
+        mock_anki_response(vec![1_i64, 2, 3, 4, 5, 6, 7, 8]),
+    )
+    .await;
+
+    let card = |card_id: i64, deck: &str, answer: &str| {
+        serde_json::json!({
+            "cardId": card_id, "noteId": card_id + 100, "deckName": deck,
+            "modelName": "Basic", "question": "", "answer": answer,
+            "fields": {
+                "Back": {"value": "back", "order": 1},
+                "Front": {"value": format!("front {}", card_id), "order": 0}
+            },
+            "type": 2, "queue": 2, "due": 0, "interval": 10, "factor": 2500,
+            "reps": 3, "lapses": 0, "left": 0, "mod": 0
+        })
+    };
+    let list = "Q<hr id=answer><ul><li>one</li><li>two</li><li>three</li></ul>";
+    mock_action(
+        &server,
+        "cardsInfo",
+        mock_anki_response(vec![
+            card(1, "Japanese", "Q<hr id=answer>A"),
+            card(2, "Japanese", "Q<hr id=answer>A"),
+            card(3, "Japanese", "Q<hr id=answer>A"),
+            card(4, "Japanese", "Q<hr id=answer>short answer"),
+            card(5, "Other", "Q<hr id=answer>A"),
+            card(6, "Other", "Q<hr id=answer>A"),
+            card(7, "Other", list),
+            card(8, "Other", "Q<hr id=answer>A"),
+        ]),
+    )
+    .await;
+
+    let timed = |card_id: i64, time: i64, count: i64| -> Vec<serde_json::Value> {
+        (0..count)
+            .map(|i| {
+                serde_json::json!({
+                    "cardId": card_id, "id": 1_704_103_200_000_i64 + card_id * 10 + i,
+                    "ease": 3, "ivl": 4, "lastIvl": 1, "factor": 2500,
+                    "time": time, "type": 1
+                })
+            })
+            .collect()
+    };
+    mock_action(
+        &server,
+        "getReviewsOfCards",
+        mock_anki_response(serde_json::json!({
+            "1": timed(1, 3000, 3),
+            "2": timed(2, 4000, 3),
+            "3": timed(3, 5000, 3),
+            "4": timed(4, 20000, 4),
+            "5": timed(5, 2000, 3),
+            "6": timed(6, 2000, 3),
+            "7": timed(7, 9000, 3),
+            // Too few answers to judge
+            "8": timed(8, 60000, 2)
+        })),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine.analyze().slow_cards("deck:*", 2.0).await.unwrap();
+
+    assert_eq!(report.cards_timed, 7);
+    assert_eq!(report.deck_medians["Japanese"], 4500);
+    assert_eq!(report.deck_medians["Other"], 2000);
+    assert_eq!(report.cards.len(), 2);
+
+    let first = &report.cards[0];
+    assert_eq!(first.card_id, 7);
+    assert_eq!(first.ratio, 4.5);
+    assert_eq!(first.suggestion, SlowCardSuggestion::Split);
+
+    let second = &report.cards[1];
+    assert_eq!(second.card_id, 4);
+    assert_eq!(second.note_id, 104);
+    assert_eq!(second.front, "front 4");
+    assert_eq!(second.reviews, 4);
+    assert_eq!(second.avg_answer_ms, 20000);
+    assert_eq!(second.deck_median_ms, 4500);
+    assert_eq!(second.suggestion, SlowCardSuggestion::Rewrite);
+}
+
+#[tokio::test]
+async fn test_slow_cards_rejects_bad_threshold() {
+    let server = setup_mock_server().await;
+    let engine = engine_for_mock(&server);
+
+    for threshold in [0.0, -1.0, f64::NAN] {
+        let result = engine.analyze().slow_cards("deck:*", threshold).await;
+        assert!(matches!(result, Err(ankit_engine::Error::Validation(_))));
+    }
+}
+
+#[tokio::test]
+async fn test_maturity() {
+    let server = setup_mock_server().await;
+    mock_action_with_params(
+        &server,
+        "findCards",
+        serde_json::json!({"query": "deck:\"Japanese\""}),
+        mock_anki_response(vec![1_i64, 2, 3, 4, 5, 6, 7]),
+    )
+    .await;
+
+    let card = |card_id: i64, card_type: i64, interval: i64| {
+        serde_json::json!({
+            "cardId": card_id, "noteId": card_id + 100, "deckName": "Japanese",
+            "modelName": "Basic", "question": "", "answer": "", "fields": {},
+            "type": card_type, "queue": card_type, "due": 0, "interval": interval,
+            "factor": 2500, "reps": 5, "lapses": 0, "left": 0, "mod": 0
+        })
+    };
+    mock_action(
+        &server,
+        "cardsInfo",
+        mock_anki_response(vec![
+            card(1, 0, 0),
+            card(2, 1, 0),
+            card(3, 3, 2),
+            card(4, 2, 1),
+            card(5, 2, 20),
+            card(6, 2, 21),
+            card(7, 2, 400),
+        ]),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let maturity = engine.analyze().maturity("Japanese").await.unwrap();
+
+    assert_eq!(maturity.total_cards, 7);
+    assert_eq!(maturity.new, 1);
+    assert_eq!(maturity.learning, 2);
+    assert_eq!(maturity.young, 2);
+    assert_eq!(maturity.mature, 1);
+    assert_eq!(maturity.very_mature, 1);
+    assert_eq!(maturity.max_interval, 400);
+    assert_eq!(maturity.average_interval, 110.5);
+
+    let counts: Vec<(&str, usize)> = maturity
+        .histogram
+        .iter()
+        .map(|bin| (bin.label.as_str(), bin.cards))
+        .collect();
+    assert_eq!(
+        counts,
+        vec![
+            ("1d", 1),
+            ("2d", 0),
+            ("3-6d", 0),
+            ("7-13d", 0),
+            ("14-20d", 1),
+            ("21-29d", 1),
+            ("30-59d", 0),
+            ("60-89d", 0),
+            ("90-179d", 0),
+            ("180-364d", 0),
+            ("365-729d", 1),
+            ("730d+", 0),
+        ]
+    );
+    assert_eq!(maturity.histogram[11].max_days, None);
+}
+
+fn audit_card(card_id: i64, note_id: i64, lapses: i64) -> serde_json::Value {
+    serde_json::json!({
+        "cardId": card_id,
+        "noteId": note_id,
+        "deckName": "Japanese",
+        "modelName": "Basic",
+        "question": "",
+        "answer": "",
+        "fields": {},
+        "type": 2,
+        "queue": 2,
+        "due": 0,
+        "interval": 30,
+        "factor": 2500,
+        "reps": 20,
+        "lapses": lapses,
+        "left": 0,
+        "mod": 0
+    })
+}
+
+fn audit_note(note_id: i64, front: &str, tags: &[&str]) -> serde_json::Value {
+    serde_json::json!({
+        "noteId": note_id,
+        "modelName": "Basic",
+        "tags": tags,
+        "fields": {"Front": {"value": front, "order": 0}}
+    })
+}
+
+/// A `multi` response with one wrapped result per action.
+fn multi_response(results: Vec<serde_json::Value>) -> wiremock::ResponseTemplate {
+    mock_anki_response(
+        results
+            .into_iter()
+            .map(|result| serde_json::json!({"result": result, "error": null}))
+            .collect::<Vec<_>>(),
+    )
+}
+
+#[tokio::test]
+async fn test_deck_audit_batched() {
+    let server = setup_mock_server().await;
+
+    // Finds in one request, then card and note info in another
+    mock_sequence(
+        &server,
+        "multi",
+        vec![
+            multi_response(vec![
+                serde_json::json!([1_i64, 2]),
+                serde_json::json!([101_i64, 102]),
+            ]),
+            multi_response(vec![
+                serde_json::json!([audit_card(1, 101, 0), audit_card(2, 102, 9)]),
+                serde_json::json!([audit_note(101, "犬", &["n5"]), audit_note(102, "犬", &[])]),
+            ]),
+        ],
+    )
+    .await;
+
+    mock_deck_config(&server, 8).await;
+
+    let engine = engine_for_mock(&server).with_batching(true);
+    let audit = engine.analyze().deck_audit("Japanese").await.unwrap();
+
+    assert_eq!(audit.total_cards, 2);
+    assert_eq!(audit.total_notes, 2);
+    assert_eq!(audit.leech_count, 1);
+    assert_eq!(audit.untagged_notes, 1);
+    assert_eq!(audit.duplicate_count, 1);
+}
+
+async fn mock_leech_audit(server: &wiremock::MockServer, config: wiremock::ResponseTemplate) {
+    mock_action(server, "findCards", mock_anki_response(vec![1_i64, 2, 3])).await;
+    mock_action(
+        server,
+        "findNotes",
+        mock_anki_response(vec![101_i64, 102, 103]),
+    )
+    .await;
+    mock_action(
+        server,
+        "cardsInfo",
+        mock_anki_response(vec![
+            audit_card(1, 101, 0),
+            audit_card(2, 102, 4),
+            audit_card(3, 103, 9),
+        ]),
+    )
+    .await;
+    mock_action(
+        server,
+        "notesInfo",
+        mock_anki_response(vec![
+            audit_note(101, "犬", &[]),
+            audit_note(102, "猫", &[]),
+            audit_note(103, "鳥", &[]),
+        ]),
+    )
+    .await;
+    // Looked up once for the three cards in the deck
+    mock_action(server, "getDeckConfig", config).await;
+}
+
+#[tokio::test]
+async fn test_deck_audit_uses_deck_leech_threshold() {
+    let server = setup_mock_server().await;
+    mock_leech_audit(
+        &server,
+        mock_anki_response(serde_json::json!({
+            "id": 2,
+            "name": "Hard Decks",
+            "new": {"perDay": 20},
+            "rev": {"perDay": 200},
+            "lapse": {"leechFails": 4}
+        })),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let audit = engine.analyze().deck_audit("Japanese").await.unwrap();
+
+    assert_eq!(audit.leech_count, 2);
+}
+
+#[tokio::test]
+async fn test_deck_audit_leech_threshold_without_deck_config() {
+    let server = setup_mock_server().await;
+    // AnkiConnect answers false when it has no options for the deck
+    mock_leech_audit(&server, mock_anki_response(false)).await;
+
+    let engine = engine_for_mock(&server);
+    let audit = engine.analyze().deck_audit("Japanese").await.unwrap();
+
+    assert_eq!(audit.leech_count, 1);
+}
+
+#[tokio::test]
+async fn test_deck_audit_deck_config_error() {
+    let server = setup_mock_server().await;
+    mock_leech_audit(&server, mock_anki_error("collection is not available")).await;
+
+    let engine = engine_for_mock(&server);
+    let result = engine.analyze().deck_audit("Japanese").await;
+
+    assert!(matches!(
+        result,
+        Err(ankit_engine::Error::Client(
+            ankit::Error::CollectionUnavailable
+        ))
+    ));
+}
+
+#[tokio::test]
+async fn test_find_problems_batched_fetches_notes_once() {
+    let server = setup_mock_server().await;
+
+    mock_action(&server, "findCards", mock_anki_response(vec![1_i64, 2, 3])).await;
+    mock_action(
+        &server,
+        "cardsInfo",
+        mock_anki_response(vec![
+            audit_card(1, 101, 10),
+            audit_card(2, 102, 12),
+            audit_card(3, 103, 0),
+        ]),
+    )
+    .await;
+    mock_action(
+        &server,
+        "notesInfo",
+        mock_anki_response(vec![audit_note(101, "犬", &[]), audit_note(102, "猫", &[])]),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server).with_batching(true);
+    let problems = engine
+        .analyze()
+        .find_problems("deck:Japanese", ProblemCriteria::default())
+        .await
+        .unwrap();
+
+    let fronts: Vec<&str> = problems.iter().map(|p| p.front.as_str()).collect();
+    assert_eq!(fronts, vec!["犬", "猫"]);
+}
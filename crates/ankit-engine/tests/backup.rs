@@ -2,10 +2,69 @@
 
 mod common;
 
+use ankit_engine::backup::RestoreFilter;
 use common::{
-    engine_for_mock, mock_action, mock_action_any, mock_anki_response, setup_mock_server,
+    dry_run_engine_for_mock, engine_for_mock, mock_action, mock_action_any, mock_action_times,
+    mock_anki_error, mock_anki_response, setup_mock_server,
 };
 
+fn card(card_id: i64, note_id: i64) -> serde_json::Value {
+    serde_json::json!({
+        "cardId": card_id,
+        "noteId": note_id,
+        "deckName": "Japanese",
+        "type": 2,
+        "queue": 2,
+        "due": 500,
+        "interval": 30,
+        "factor": 2500,
+        "reps": 5,
+        "lapses": 0,
+        "left": 0,
+        "mod": 1000
+    })
+}
+
+fn note(note_id: i64, front: &str, tags: &[&str], cards: &[i64]) -> serde_json::Value {
+    serde_json::json!({
+        "noteId": note_id,
+        "modelName": "Basic",
+        "tags": tags,
+        "fields": {"Front": {"value": front, "order": 0}},
+        "cards": cards
+    })
+}
+
+/// Take a snapshot of one "Japanese" deck holding notes 1 (tagged verb) and 2.
+async fn take_snapshot(dir: &std::path::Path) -> std::path::PathBuf {
+    let server = setup_mock_server().await;
+    mock_action(&server, "deckNames", mock_anki_response(vec!["Japanese"])).await;
+    mock_action(&server, "findCards", mock_anki_response(vec![10_i64, 20])).await;
+    mock_action(
+        &server,
+        "cardsInfo",
+        mock_anki_response(vec![card(10, 1), card(20, 2)]),
+    )
+    .await;
+    mock_action(
+        &server,
+        "notesInfo",
+        mock_anki_response(vec![
+            note(1, "食べる", &["verb"], &[10]),
+            note(2, "猫", &["noun"], &[20]),
+        ]),
+    )
+    .await;
+    mock_action(&server, "getDeckConfig", mock_anki_error("no config")).await;
+
+    let engine = engine_for_mock(&server);
+    let result = engine.backup().snapshot(dir).await.unwrap();
+    assert_eq!(result.decks, 1);
+    assert_eq!(result.notes, 2);
+    assert_eq!(result.cards, 2);
+    result.path
+}
+
 #[tokio::test]
 async fn test_backup_deck() {
     let server = setup_mock_server().await;
@@ -211,3 +270,118 @@ async fn test_list_backups_recursive() {
     // Should find both files recursively
     assert_eq!(backups.len(), 2);
 }
+
+#[tokio::test]
+async fn test_snapshot_list_and_verify() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = take_snapshot(temp_dir.path()).await;
+
+    assert!(path.join("manifest.json").is_file());
+    assert!(path.join("decks/0001-Japanese.json").is_file());
+
+    let server = setup_mock_server().await;
+    let engine = engine_for_mock(&server);
+    let snapshots = engine
+        .backup()
+        .list_snapshots(temp_dir.path())
+        .await
+        .unwrap();
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(snapshots[0].notes, 2);
+    assert_eq!(snapshots[0].format_version, 1);
+
+    assert!(
+        engine
+            .backup()
+            .verify_snapshot(&path)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    // Tampering with a deck file is caught, and restore refuses to run
+    std::fs::write(path.join("decks/0001-Japanese.json"), b"{}").unwrap();
+    let corrupt = engine.backup().verify_snapshot(&path).await.unwrap();
+    assert_eq!(corrupt, vec!["decks/0001-Japanese.json"]);
+
+    let err = engine
+        .backup()
+        .restore_snapshot(&path, &RestoreFilter::default())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("integrity"));
+}
+
+#[tokio::test]
+async fn test_restore_snapshot_updates_and_recreates() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = take_snapshot(temp_dir.path()).await;
+
+    let server = setup_mock_server().await;
+    // Note 1 still exists; note 2 was deleted
+    mock_action(&server, "findNotes", mock_anki_response(vec![1_i64])).await;
+    mock_action_any(
+        &server,
+        "notesInfo",
+        mock_anki_response(vec![note(1, "changed", &[], &[10])]),
+    )
+    .await;
+    mock_action(
+        &server,
+        "updateNote",
+        mock_anki_response(serde_json::Value::Null),
+    )
+    .await;
+    mock_action(&server, "createDeck", mock_anki_response(1_i64)).await;
+    mock_action(&server, "addNote", mock_anki_response(3_i64)).await;
+    mock_action_times(
+        &server,
+        "setSpecificValueOfCard",
+        mock_anki_response(vec![true; 8]),
+        2,
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .backup()
+        .restore_snapshot(&path, &RestoreFilter::default())
+        .await
+        .unwrap();
+
+    assert_eq!(report.notes_matched, 2);
+    assert_eq!(report.notes_updated, 1);
+    assert_eq!(report.notes_recreated, 1);
+    assert_eq!(report.cards_rescheduled, 2);
+    assert!(report.failures.is_empty(), "{:?}", report.failures);
+}
+
+#[tokio::test]
+async fn test_restore_snapshot_tag_filter_dry_run() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = take_snapshot(temp_dir.path()).await;
+
+    let server = setup_mock_server().await;
+    mock_action(&server, "findNotes", mock_anki_response(vec![1_i64])).await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let filter = RestoreFilter::default().deck("Japanese").tag("VERB");
+    let report = engine
+        .backup()
+        .restore_snapshot(&path, &filter)
+        .await
+        .unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.notes_matched, 1);
+    assert_eq!(report.planned.len(), 1);
+
+    let missing = RestoreFilter::default().deck("French");
+    assert!(
+        engine
+            .backup()
+            .restore_snapshot(&path, &missing)
+            .await
+            .is_err()
+    );
+}
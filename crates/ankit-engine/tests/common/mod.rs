@@ -1,6 +1,6 @@
 //! Common test utilities for ankit-engine workflow tests.
 
-use ankit_engine::Engine;
+use ankit_engine::{Engine, EngineOptions};
 use serde::Serialize;
 use wiremock::matchers::{body_partial_json, method};
 use wiremock::{Mock, MockServer, ResponseTemplate, Times};
@@ -16,6 +16,15 @@ pub fn engine_for_mock(server: &MockServer) -> Engine {
     Engine::from_client(client)
 }
 
+/// Create a dry-run Engine connected to the mock server.
+#[allow(dead_code)]
+pub fn dry_run_engine_for_mock(server: &MockServer) -> Engine {
+    engine_for_mock(server).with_options(EngineOptions {
+        dry_run: true,
+        ..Default::default()
+    })
+}
+
 /// Create a successful AnkiConnect response.
 pub fn mock_anki_response<T: Serialize>(result: T) -> ResponseTemplate {
     ResponseTemplate::new(200).set_body_json(serde_json::json!({
@@ -69,3 +78,50 @@ pub async fn mock_action_any(server: &MockServer, action: &str, response: Respon
         .mount(server)
         .await;
 }
+
+/// Mount a `getDeckConfig` mock for the one deck a leech count reads its
+/// threshold from.
+#[allow(dead_code)]
+pub async fn mock_deck_config(server: &MockServer, leech_fails: i64) {
+    let config = serde_json::json!({
+        "id": 1,
+        "name": "Default",
+        "new": {"perDay": 20},
+        "rev": {"perDay": 200},
+        "lapse": {"leechFails": leech_fails}
+    });
+    mock_action(server, "getDeckConfig", mock_anki_response(config)).await;
+}
+
+/// Mount a mock for an action called with specific parameters.
+#[allow(dead_code)]
+pub async fn mock_action_with_params(
+    server: &MockServer,
+    action: &str,
+    params: serde_json::Value,
+    response: ResponseTemplate,
+) {
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({
+            "action": action,
+            "version": 6,
+            "params": params
+        })))
+        .respond_with(response)
+        .mount(server)
+        .await;
+}
+
+/// Mount responses for an action that are served once each, in order.
+#[allow(dead_code)]
+pub async fn mock_sequence(server: &MockServer, action: &str, responses: Vec<ResponseTemplate>) {
+    for response in responses {
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"action": action})))
+            .respond_with(response)
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+}
@@ -0,0 +1,172 @@
+//! Tests for deduplicate workflow operations.
+
+mod common;
+
+use ankit_engine::KeyNormalization;
+use ankit_engine::changes::PlannedChange;
+use ankit_engine::deduplicate::{DedupeQuery, KeepStrategy, MergeStrategy};
+use common::{
+    dry_run_engine_for_mock, engine_for_mock, mock_action, mock_action_times, mock_anki_response,
+    setup_mock_server,
+};
+
+fn query() -> DedupeQuery {
+    DedupeQuery {
+        search: "deck:Test".to_string(),
+        key_field: "Front".to_string(),
+        keep: KeepStrategy::First,
+        match_mode: Default::default(),
+        normalization: Default::default(),
+    }
+}
+
+/// Two notes with the same key; `notesInfo` is fetched once to find them
+/// and again when merging.
+async fn mock_duplicates(server: &wiremock::MockServer, info_calls: u64) {
+    mock_action(server, "findNotes", mock_anki_response(vec![1_i64, 2])).await;
+    mock_action_times(
+        server,
+        "notesInfo",
+        mock_anki_response(vec![
+            serde_json::json!({
+                "noteId": 1_i64,
+                "modelName": "Basic",
+                "tags": ["verbs"],
+                "fields": {
+                    "Front": {"value": "comer", "order": 0},
+                    "Back": {"value": "", "order": 1}
+                }
+            }),
+            serde_json::json!({
+                "noteId": 2_i64,
+                "modelName": "Basic",
+                "tags": ["verbs", "food"],
+                "fields": {
+                    "Front": {"value": "Comer", "order": 0},
+                    "Back": {"value": "to eat", "order": 1}
+                }
+            }),
+        ]),
+        info_calls,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_remove_duplicates_with_merge() {
+    let server = setup_mock_server().await;
+    mock_duplicates(&server, 2).await;
+    mock_action(&server, "updateNoteFields", mock_anki_response(())).await;
+    mock_action(&server, "addTags", mock_anki_response(())).await;
+    mock_action(&server, "deleteNotes", mock_anki_response(())).await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .deduplicate()
+        .remove_duplicates_with(&query(), &MergeStrategy::all())
+        .await
+        .unwrap();
+
+    assert_eq!(report.deleted, 1);
+    assert_eq!(report.kept, 1);
+    assert_eq!(report.merged, 1);
+}
+
+#[tokio::test]
+async fn test_remove_duplicates_merge_dry_run() {
+    let server = setup_mock_server().await;
+    mock_duplicates(&server, 2).await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine
+        .deduplicate()
+        .remove_duplicates_with(&query(), &MergeStrategy::all())
+        .await
+        .unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.planned.len(), 3);
+    assert!(matches!(
+        &report.planned[0],
+        PlannedChange::UpdateNoteFields { note_id: 1, fields } if fields["Back"] == "to eat"
+    ));
+    assert!(matches!(
+        &report.planned[1],
+        PlannedChange::AddTags { note_ids, tags } if note_ids == &[1] && tags == "food"
+    ));
+    assert!(matches!(
+        &report.planned[2],
+        PlannedChange::DeleteNotes { note_ids } if note_ids == &[2]
+    ));
+}
+
+#[tokio::test]
+async fn test_remove_duplicates_without_merge() {
+    let server = setup_mock_server().await;
+    mock_duplicates(&server, 1).await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine
+        .deduplicate()
+        .remove_duplicates(&query())
+        .await
+        .unwrap();
+
+    assert_eq!(report.merged, 0);
+    assert_eq!(report.planned.len(), 1);
+}
+
+#[tokio::test]
+async fn test_find_duplicates_with_cjk_normalization() {
+    let server = setup_mock_server().await;
+    mock_action_times(
+        &server,
+        "findNotes",
+        mock_anki_response(vec![1_i64, 2, 3]),
+        2,
+    )
+    .await;
+    mock_action_times(
+        &server,
+        "notesInfo",
+        mock_anki_response(vec![
+            serde_json::json!({
+                "noteId": 1_i64,
+                "modelName": "Basic",
+                "tags": [],
+                "fields": {"Front": {"value": "ガッコウ", "order": 0}}
+            }),
+            serde_json::json!({
+                "noteId": 2_i64,
+                "modelName": "Basic",
+                "tags": [],
+                "fields": {"Front": {"value": "ｶﾞｯｺｳ", "order": 0}}
+            }),
+            serde_json::json!({
+                "noteId": 3_i64,
+                "modelName": "Basic",
+                "tags": [],
+                "fields": {"Front": {"value": "ガッコ", "order": 0}}
+            }),
+        ]),
+        2,
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let groups = engine
+        .deduplicate()
+        .find_duplicates(&query())
+        .await
+        .unwrap();
+    assert!(groups.is_empty());
+
+    let cjk = DedupeQuery {
+        normalization: KeyNormalization::cjk(),
+        ..query()
+    };
+    let groups = engine.deduplicate().find_duplicates(&cjk).await.unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].keep_note_id, 1);
+    assert_eq!(groups[0].duplicate_note_ids, vec![2]);
+}
@@ -2,9 +2,14 @@
 
 mod common;
 
-use ankit_engine::enrich::EnrichQuery;
+use ankit_engine::annotate::Annotator;
+use ankit_engine::changes::PlannedChange;
+use ankit_engine::enrich::{AnnotationField, AudioField, EnrichQuery};
+use ankit_engine::normalize::NormalizeOptions;
+use ankit_engine::tts::TtsProvider;
 use common::{
-    engine_for_mock, mock_action, mock_action_times, mock_anki_response, setup_mock_server,
+    dry_run_engine_for_mock, engine_for_mock, mock_action, mock_action_times,
+    mock_action_with_params, mock_anki_error, mock_anki_response, setup_mock_server,
 };
 use std::collections::HashMap;
 
@@ -306,3 +311,470 @@ async fn test_pipeline_merge_updates() {
     let report = pipeline.commit(&engine).await.unwrap();
     assert_eq!(report.updated, 1);
 }
+
+fn messy_notes() -> wiremock::ResponseTemplate {
+    mock_anki_response(vec![
+        serde_json::json!({
+            "noteId": 1_i64,
+            "modelName": "Basic",
+            "tags": [],
+            "fields": {
+                "Front": {"value": "<div><span style=\"color: red\">hola</span>&nbsp;</div>", "order": 0},
+                "Back": {"value": "hello", "order": 1}
+            }
+        }),
+        serde_json::json!({
+            "noteId": 2_i64,
+            "modelName": "Basic",
+            "tags": [],
+            "fields": {
+                "Front": {"value": "adiós", "order": 0},
+                "Back": {"value": "good<div>bye</div>", "order": 1}
+            }
+        }),
+    ])
+}
+
+#[tokio::test]
+async fn test_normalize_fields() {
+    let server = setup_mock_server().await;
+
+    mock_action(&server, "findNotes", mock_anki_response(vec![1_i64, 2])).await;
+    mock_action(&server, "notesInfo", messy_notes()).await;
+    mock_action_times(
+        &server,
+        "updateNoteFields",
+        mock_anki_response(serde_json::Value::Null),
+        2,
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .enrich()
+        .normalize_fields("deck:Test", &NormalizeOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(report.notes_checked, 2);
+    assert_eq!(report.notes_changed, 2);
+    assert_eq!(report.fields_changed, 2);
+    assert!(report.failures.is_empty());
+}
+
+#[tokio::test]
+async fn test_normalize_fields_dry_run() {
+    let server = setup_mock_server().await;
+
+    mock_action(&server, "findNotes", mock_anki_response(vec![1_i64, 2])).await;
+    mock_action(&server, "notesInfo", messy_notes()).await;
+
+    // updateNoteFields should NOT be called in dry-run mode
+
+    let engine = dry_run_engine_for_mock(&server);
+    let options = NormalizeOptions::default().fields(["Front"]);
+    let report = engine
+        .enrich()
+        .normalize_fields("deck:Test", &options)
+        .await
+        .unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.notes_changed, 1);
+    assert!(matches!(
+        &report.planned[..],
+        [PlannedChange::UpdateNoteFields { note_id: 1, fields }] if fields["Front"] == "hola"
+    ));
+}
+
+/// Provider that "speaks" text as its UTF-8 bytes, failing on "error".
+struct EchoTts;
+
+impl TtsProvider for EchoTts {
+    fn extension(&self) -> &str {
+        "mp3"
+    }
+
+    async fn synthesize(&self, text: &str) -> ankit_engine::Result<Vec<u8>> {
+        if text == "error" {
+            return Err(ankit_engine::Error::Tts("voice unavailable".to_string()));
+        }
+        Ok(text.as_bytes().to_vec())
+    }
+}
+
+fn audio_notes() -> wiremock::ResponseTemplate {
+    let note = |id: i64, front: &str, audio: &str| {
+        serde_json::json!({
+            "noteId": id,
+            "modelName": "Basic",
+            "tags": [],
+            "fields": {
+                "Front": {"value": front, "order": 0},
+                "Audio": {"value": audio, "order": 1}
+            }
+        })
+    };
+    mock_anki_response(vec![
+        note(1, "<b>hola</b>", ""),
+        note(2, "hola", ""),
+        note(3, "adiós", "[sound:old.mp3]"),
+        note(4, "", ""),
+        note(5, "error", ""),
+    ])
+}
+
+#[tokio::test]
+async fn test_generate_audio() {
+    let server = setup_mock_server().await;
+
+    mock_action(
+        &server,
+        "findNotes",
+        mock_anki_response(vec![1_i64, 2, 3, 4, 5]),
+    )
+    .await;
+    mock_action(&server, "notesInfo", audio_notes()).await;
+    // Notes 1 and 2 share text, so only one file is stored
+    mock_action_times(
+        &server,
+        "storeMediaFile",
+        mock_anki_response("ankit-tts.mp3"),
+        1,
+    )
+    .await;
+    mock_action_times(
+        &server,
+        "updateNoteFields",
+        mock_anki_response(serde_json::Value::Null),
+        2,
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .enrich()
+        .generate_audio("deck:Spanish", &AudioField::new("Front", "Audio"), &EchoTts)
+        .await
+        .unwrap();
+
+    assert_eq!(report.notes_checked, 5);
+    assert_eq!(report.generated, 1);
+    assert_eq!(report.notes_updated, 2);
+    assert_eq!(report.skipped, 2);
+    assert_eq!(report.failures.len(), 1);
+    assert_eq!(report.failures[0].note_id, 5);
+}
+
+#[tokio::test]
+async fn test_generate_audio_dry_run_overwrite() {
+    let server = setup_mock_server().await;
+
+    mock_action(
+        &server,
+        "findNotes",
+        mock_anki_response(vec![1_i64, 2, 3, 4, 5]),
+    )
+    .await;
+    mock_action(&server, "notesInfo", audio_notes()).await;
+
+    // Neither storeMediaFile nor updateNoteFields should be called in dry-run mode
+
+    let engine = dry_run_engine_for_mock(&server);
+    let field = AudioField::new("Front", "Audio").overwrite(true);
+    let report = engine
+        .enrich()
+        .generate_audio("deck:Spanish", &field, &EchoTts)
+        .await
+        .unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.generated, 0);
+    assert_eq!(report.notes_updated, 4);
+    assert_eq!(report.skipped, 1);
+
+    let stored: Vec<&String> = report
+        .planned
+        .iter()
+        .filter_map(|change| match change {
+            PlannedChange::StoreMedia { filename } => Some(filename),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(stored.len(), 3);
+    assert!(
+        stored
+            .iter()
+            .all(|f| f.starts_with("ankit-tts-") && f.ends_with(".mp3"))
+    );
+
+    let note_3 = report.planned.iter().find_map(|change| match change {
+        PlannedChange::UpdateNoteFields { note_id: 3, fields } => Some(&fields["Audio"]),
+        _ => None,
+    });
+    assert!(note_3.is_some_and(|audio| !audio.contains("old.mp3")));
+}
+
+/// Annotator that spells out a few words, failing on anything else.
+struct TableReadings;
+
+impl Annotator for TableReadings {
+    async fn annotate(&self, text: &str) -> ankit_engine::Result<String> {
+        match text {
+            "日本" => Ok("にほん".to_string()),
+            "猫" => Ok("ねこ".to_string()),
+            _ => Err(ankit_engine::Error::Annotation(format!(
+                "no reading for {text}"
+            ))),
+        }
+    }
+}
+
+fn expression_notes() -> wiremock::ResponseTemplate {
+    let note = |id: i64, expression: &str, reading: &str| {
+        serde_json::json!({
+            "noteId": id,
+            "modelName": "Japanese",
+            "tags": [],
+            "fields": {
+                "Expression": {"value": expression, "order": 0},
+                "Reading": {"value": reading, "order": 1}
+            }
+        })
+    };
+    mock_anki_response(vec![
+        note(1, "<b>日本</b>", ""),
+        note(2, "猫", "ねこ"),
+        note(3, "", ""),
+        note(4, "犬", ""),
+    ])
+}
+
+#[tokio::test]
+async fn test_annotate() {
+    let server = setup_mock_server().await;
+
+    mock_action(
+        &server,
+        "findNotes",
+        mock_anki_response(vec![1_i64, 2, 3, 4]),
+    )
+    .await;
+    mock_action(&server, "notesInfo", expression_notes()).await;
+    mock_action_with_params(
+        &server,
+        "updateNoteFields",
+        serde_json::json!({"note": {"id": 1, "fields": {"Reading": "にほん"}}}),
+        mock_anki_response(serde_json::Value::Null),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let field = AnnotationField::new("Expression", "Reading");
+    let report = engine
+        .enrich()
+        .annotate("deck:Japanese", &field, &TableReadings)
+        .await
+        .unwrap();
+
+    assert_eq!(report.notes_checked, 4);
+    assert_eq!(report.notes_updated, 1);
+    assert_eq!(report.skipped, 2);
+    assert_eq!(report.failures.len(), 1);
+    assert_eq!(report.failures[0].note_id, 4);
+}
+
+#[tokio::test]
+async fn test_annotate_dry_run_overwrite() {
+    let server = setup_mock_server().await;
+
+    mock_action(
+        &server,
+        "findNotes",
+        mock_anki_response(vec![1_i64, 2, 3, 4]),
+    )
+    .await;
+    mock_action(&server, "notesInfo", expression_notes()).await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let field = AnnotationField::new("Expression", "Reading").overwrite(true);
+    let report = engine
+        .enrich()
+        .annotate("deck:Japanese", &field, &TableReadings)
+        .await
+        .unwrap();
+
+    assert!(report.dry_run);
+    // Note 2 already has the same reading, so it is unchanged
+    assert_eq!(report.notes_updated, 1);
+    assert_eq!(report.skipped, 2);
+    assert!(matches!(
+        &report.planned[..],
+        [PlannedChange::UpdateNoteFields { note_id: 1, fields }] if fields["Reading"] == "にほん"
+    ));
+}
+
+fn verb_notes() -> wiremock::ResponseTemplate {
+    mock_anki_response(vec![
+        serde_json::json!({
+            "noteId": 1_i64,
+            "modelName": "Basic",
+            "tags": [],
+            "fields": {
+                "Front": {"value": "correr", "order": 0},
+                "Back": {"value": "to run", "order": 1}
+            }
+        }),
+        serde_json::json!({
+            "noteId": 2_i64,
+            "modelName": "Basic",
+            "tags": [],
+            "fields": {
+                "Front": {"value": "casa", "order": 0},
+                "Back": {"value": "house", "order": 1}
+            }
+        }),
+    ])
+}
+
+#[tokio::test]
+async fn test_find_replace_regex() {
+    let server = setup_mock_server().await;
+
+    mock_action(&server, "findNotes", mock_anki_response(vec![1_i64, 2])).await;
+    mock_action(&server, "notesInfo", verb_notes()).await;
+    mock_action_with_params(
+        &server,
+        "updateNoteFields",
+        serde_json::json!({"note": {"id": 1, "fields": {"Back": "run (v.)"}}}),
+        mock_anki_response(serde_json::Value::Null),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .enrich()
+        .find_replace("deck:Test", "Back", r"^to (\w+)$", "$1 (v.)", true)
+        .await
+        .unwrap();
+
+    assert_eq!(report.notes_checked, 2);
+    assert_eq!(report.notes_changed, 1);
+    assert!(!report.native);
+    assert!(report.failures.is_empty());
+    assert_eq!(report.replacements[0].before, "to run");
+    assert_eq!(report.replacements[0].after, "run (v.)");
+}
+
+#[tokio::test]
+async fn test_find_replace_dry_run_preview() {
+    let server = setup_mock_server().await;
+
+    mock_action(&server, "findNotes", mock_anki_response(vec![1_i64, 2])).await;
+    mock_action(&server, "notesInfo", verb_notes()).await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine
+        .enrich()
+        .find_replace("deck:Test", "Back", "house", "home", false)
+        .await
+        .unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(
+        report.replacements[0].diff(),
+        "note 2 Back\n- house\n+ home"
+    );
+    assert!(matches!(
+        &report.planned[..],
+        [PlannedChange::UpdateNoteFields { note_id: 2, fields }] if fields["Back"] == "home"
+    ));
+}
+
+#[tokio::test]
+async fn test_find_replace_uses_native_action() {
+    let server = setup_mock_server().await;
+
+    mock_action(&server, "findNotes", mock_anki_response(vec![1_i64, 2])).await;
+    mock_action(&server, "notesInfo", verb_notes()).await;
+    mock_action(&server, "version", mock_anki_response(6)).await;
+    mock_action(
+        &server,
+        "apiReflect",
+        mock_anki_response(serde_json::json!({
+            "scopes": ["actions"],
+            "actions": ["findNotes", "notesInfo", "findAndReplaceInModels"]
+        })),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "findAndReplaceInModels",
+        serde_json::json!({
+            "notes": [2],
+            "modelName": "Basic",
+            "fieldName": "Back",
+            "findText": "house",
+            "replaceText": "home"
+        }),
+        mock_anki_response(1),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .enrich()
+        .find_replace("deck:Test", "Back", "house", "home", false)
+        .await
+        .unwrap();
+
+    assert!(report.native);
+    assert_eq!(report.notes_changed, 1);
+}
+
+#[tokio::test]
+async fn test_find_replace_invalid_regex() {
+    let server = setup_mock_server().await;
+    let engine = engine_for_mock(&server);
+
+    let result = engine
+        .enrich()
+        .find_replace("deck:Test", "Back", "(unclosed", "", true)
+        .await;
+    assert!(matches!(result, Err(ankit_engine::Error::Validation(_))));
+}
+
+#[tokio::test]
+async fn test_update_notes_concurrently() {
+    let server = setup_mock_server().await;
+    mock_action_with_params(
+        &server,
+        "updateNoteFields",
+        serde_json::json!({"note": {"id": 3}}),
+        mock_anki_error("note was not found"),
+    )
+    .await;
+    mock_action_times(
+        &server,
+        "updateNoteFields",
+        mock_anki_response(serde_json::Value::Null),
+        4,
+    )
+    .await;
+
+    let engine = engine_for_mock(&server).with_options(ankit_engine::EngineOptions {
+        concurrency: 4,
+        ..Default::default()
+    });
+    let updates: Vec<(i64, HashMap<String, String>)> = (1..=5)
+        .map(|id| {
+            let fields = HashMap::from([("Example".to_string(), format!("Example {}", id))]);
+            (id, fields)
+        })
+        .collect();
+    let report = engine.enrich().update_notes(&updates).await.unwrap();
+
+    assert_eq!(report.updated, 4);
+    assert_eq!(report.failed, 1);
+    assert_eq!(report.failures[0].note_id, 3);
+}
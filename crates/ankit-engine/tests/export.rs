@@ -0,0 +1,531 @@
+//! Tests for export workflow operations.
+
+mod common;
+
+use ankit_engine::export::{
+    ExportCursor, MarkdownLayout, MarkdownOptions, PrintLayout, PrintMedia, PrintableOptions,
+    write_reviews_csv,
+};
+use common::{
+    engine_for_mock, mock_action, mock_action_times, mock_action_with_params, mock_anki_response,
+    setup_mock_server,
+};
+
+fn card(card_id: i64, note_id: i64, modified: i64) -> serde_json::Value {
+    serde_json::json!({
+        "cardId": card_id,
+        "noteId": note_id,
+        "deckName": "Japanese",
+        "type": 2,
+        "queue": 2,
+        "due": 500,
+        "interval": 30,
+        "factor": 2500,
+        "reps": 5,
+        "lapses": 0,
+        "left": 0,
+        "mod": modified
+    })
+}
+
+#[tokio::test]
+async fn test_deck_incremental_fetches_only_changed() {
+    let server = setup_mock_server().await;
+
+    mock_action(&server, "findNotes", mock_anki_response(vec![1_i64, 2, 3])).await;
+    mock_action(
+        &server,
+        "notesModTime",
+        mock_anki_response(vec![
+            serde_json::json!({"noteId": 1_i64, "mod": 900}),
+            serde_json::json!({"noteId": 2_i64, "mod": 1000}),
+            serde_json::json!({"noteId": 3_i64, "mod": 1200}),
+        ]),
+    )
+    .await;
+    mock_action(
+        &server,
+        "notesInfo",
+        mock_anki_response(vec![
+            serde_json::json!({
+                "noteId": 2_i64,
+                "modelName": "Basic",
+                "tags": [],
+                "fields": {"Front": {"value": "犬", "order": 0}}
+            }),
+            serde_json::json!({
+                "noteId": 3_i64,
+                "modelName": "Basic",
+                "tags": ["n5"],
+                "fields": {"Front": {"value": "猫", "order": 0}}
+            }),
+        ]),
+    )
+    .await;
+    mock_action(&server, "findCards", mock_anki_response(vec![10_i64, 20])).await;
+    mock_action(
+        &server,
+        "cardsModTime",
+        mock_anki_response(vec![
+            serde_json::json!({"cardId": 10_i64, "mod": 500}),
+            serde_json::json!({"cardId": 20_i64, "mod": 1500}),
+        ]),
+    )
+    .await;
+    mock_action(
+        &server,
+        "cardsInfo",
+        mock_anki_response(vec![card(20, 2, 1500)]),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let export = engine
+        .export()
+        .deck_incremental("Japanese", 1000)
+        .await
+        .unwrap();
+
+    assert_eq!(export.notes.len(), 2);
+    assert_eq!(export.unchanged_notes, 1);
+    assert_eq!(export.cards.len(), 1);
+    assert_eq!(export.cards[0].card_id, 20);
+    assert_eq!(export.unchanged_cards, 1);
+    assert_eq!(export.cursor.deck_name, "Japanese");
+    assert_eq!(export.cursor.since, 1500);
+}
+
+#[tokio::test]
+async fn test_added_since() {
+    let server = setup_mock_server().await;
+
+    mock_action_with_params(
+        &server,
+        "findCards",
+        serde_json::json!({"query": "added:7"}),
+        mock_anki_response(vec![31_i64, 20, 30]),
+    )
+    .await;
+    let mut spanish = card(20, 2, 1000);
+    spanish["deckName"] = "Spanish".into();
+    mock_action(
+        &server,
+        "cardsInfo",
+        mock_anki_response(vec![spanish, card(30, 3, 1000), card(31, 3, 1000)]),
+    )
+    .await;
+    mock_action(
+        &server,
+        "notesInfo",
+        mock_anki_response(vec![
+            serde_json::json!({
+                "noteId": 3_i64,
+                "modelName": "Basic",
+                "tags": [],
+                "fields": {"Front": {"value": "猫", "order": 0}}
+            }),
+            serde_json::json!({
+                "noteId": 2_i64,
+                "modelName": "Basic",
+                "tags": [],
+                "fields": {"Front": {"value": "perro", "order": 0}}
+            }),
+        ]),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let notes = engine.export().added_since(7).await.unwrap();
+
+    let decks: Vec<(i64, &str)> = notes
+        .iter()
+        .map(|note| (note.note_id, note.deck_name.as_str()))
+        .collect();
+    assert_eq!(decks, vec![(2, "Spanish"), (3, "Japanese")]);
+
+    // Nothing is added in the last zero days
+    assert!(engine.export().added_since(0).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_deck_incremental_nothing_changed() {
+    let server = setup_mock_server().await;
+
+    mock_action(&server, "findNotes", mock_anki_response(vec![1_i64])).await;
+    mock_action(
+        &server,
+        "notesModTime",
+        mock_anki_response(vec![serde_json::json!({"noteId": 1_i64, "mod": 900})]),
+    )
+    .await;
+    mock_action(&server, "findCards", mock_anki_response(vec![10_i64])).await;
+    mock_action(
+        &server,
+        "cardsModTime",
+        mock_anki_response(vec![serde_json::json!({"cardId": 10_i64, "mod": 900})]),
+    )
+    .await;
+
+    // notesInfo and cardsInfo should NOT be called when nothing changed
+
+    let engine = engine_for_mock(&server);
+    let cursor = ExportCursor {
+        deck_name: "Japanese".to_string(),
+        since: 1000,
+        exported_at: 1000,
+    };
+    let export = engine.export().resume(&cursor).await.unwrap();
+
+    assert!(export.notes.is_empty());
+    assert!(export.cards.is_empty());
+    assert_eq!(export.cursor.since, 1000);
+}
+
+#[test]
+fn test_cursor_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cursor.json");
+    let cursor = ExportCursor {
+        deck_name: "Japanese".to_string(),
+        since: 1_700_000_000,
+        exported_at: 1_700_000_100,
+    };
+
+    cursor.save(&path).unwrap();
+    assert_eq!(ExportCursor::load(&path).unwrap(), cursor);
+
+    std::fs::write(&path, "not json").unwrap();
+    assert!(ExportCursor::load(&path).is_err());
+}
+
+#[tokio::test]
+async fn test_reviews_joined() {
+    let server = setup_mock_server().await;
+    mock_action(
+        &server,
+        "findCards",
+        mock_anki_response(vec![10_i64, 11, 20]),
+    )
+    .await;
+    mock_action(
+        &server,
+        "cardsInfo",
+        mock_anki_response(vec![card(10, 1, 0), card(11, 1, 0), card(20, 2, 0)]),
+    )
+    .await;
+    mock_action(
+        &server,
+        "notesInfo",
+        mock_anki_response(vec![
+            serde_json::json!({
+                "noteId": 1_i64,
+                "modelName": "Basic (and reversed card)",
+                "tags": ["n5", "animal"],
+                "fields": {
+                    "Back": {"value": "dog", "order": 1},
+                    "Front": {"value": "犬, いぬ", "order": 0}
+                }
+            }),
+            serde_json::json!({
+                "noteId": 2_i64,
+                "modelName": "Basic",
+                "tags": [],
+                "fields": {"Front": {"value": "猫", "order": 0}}
+            }),
+        ]),
+    )
+    .await;
+
+    let review = |card_id: i64, id: i64| {
+        serde_json::json!({
+            "cardId": card_id, "id": id, "ease": 3, "ivl": 4, "lastIvl": 1,
+            "factor": 2500, "time": 6000, "type": 1
+        })
+    };
+    mock_action(
+        &server,
+        "getReviewsOfCards",
+        mock_anki_response(serde_json::json!({
+            "10": [review(10, 3000)],
+            "11": [review(11, 1000), review(11, 2000)],
+            "20": []
+        })),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let history = engine
+        .export()
+        .reviews_joined("deck:Japanese")
+        .await
+        .unwrap();
+
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].note_id, 1);
+    assert_eq!(history[0].first_field, "犬, いぬ");
+    assert_eq!(history[0].deck_name, "Japanese");
+    let order: Vec<(i64, i64)> = history[0]
+        .reviews
+        .iter()
+        .map(|r| (r.card_id, r.review.timestamp))
+        .collect();
+    assert_eq!(order, vec![(11, 1000), (11, 2000), (10, 3000)]);
+    assert!(history[1].reviews.is_empty());
+
+    let mut csv = Vec::new();
+    write_reviews_csv(&history, &mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].starts_with("note_id,card_id,deck,"));
+    assert_eq!(
+        lines[1],
+        "1,11,Japanese,Basic (and reversed card),\"犬, いぬ\",n5 animal,1000,3,4,1,6000"
+    );
+}
+
+async fn mock_markdown_deck(server: &wiremock::MockServer) {
+    mock_action(server, "findCards", mock_anki_response(vec![10_i64, 20])).await;
+    mock_action(
+        server,
+        "cardsInfo",
+        mock_anki_response(vec![
+            serde_json::json!({"cardId": 10, "noteId": 1, "deckName": "Japanese"}),
+            serde_json::json!({"cardId": 20, "noteId": 2, "deckName": "Japanese::Kanji"}),
+        ]),
+    )
+    .await;
+    mock_action(
+        server,
+        "notesInfo",
+        mock_anki_response(vec![
+            serde_json::json!({
+                "noteId": 1,
+                "modelName": "Basic",
+                "tags": ["lang::ja", "n5"],
+                "fields": {
+                    "Front": {"value": "<b>猫</b>", "order": 0},
+                    "Back": {"value": "cat<br>[sound:neko.mp3]", "order": 1}
+                }
+            }),
+            serde_json::json!({
+                "noteId": 2,
+                "modelName": "Basic",
+                "tags": [],
+                "fields": {
+                    "Front": {"value": "犬 &amp; <i>dog</i>", "order": 0},
+                    "Back": {"value": "<img src=\"inu.png\">", "order": 1}
+                }
+            }),
+        ]),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_to_markdown_file_per_note() {
+    let server = setup_mock_server().await;
+    mock_markdown_deck(&server).await;
+    mock_action_times(&server, "retrieveMediaFile", mock_anki_response("aGk="), 2).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let engine = engine_for_mock(&server);
+    let export = engine
+        .export()
+        .to_markdown("Japanese", dir.path(), &MarkdownOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(export.notes, 2);
+    assert_eq!(export.files.len(), 2);
+    assert_eq!(export.media, vec!["inu.png", "neko.mp3"]);
+
+    let cat = std::fs::read_to_string(dir.path().join("猫.md")).unwrap();
+    assert!(cat.starts_with("---\nnote_id: 1\ndeck: \"Japanese\"\n"));
+    assert!(cat.contains("  - \"lang/ja\"\n"));
+    assert!(cat.contains("## Front\n\n**猫**\n"));
+    assert!(cat.contains("## Back\n\ncat\n![](media/neko.mp3)\n"));
+
+    let dog = std::fs::read_to_string(dir.path().join("犬 & dog.md")).unwrap();
+    assert!(dog.contains("deck: \"Japanese::Kanji\""));
+    assert!(dog.contains("![](media/inu.png)"));
+    assert_eq!(
+        std::fs::read(dir.path().join("media/neko.mp3")).unwrap(),
+        b"hi"
+    );
+}
+
+#[tokio::test]
+async fn test_to_markdown_file_per_deck() {
+    let server = setup_mock_server().await;
+    mock_markdown_deck(&server).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let engine = engine_for_mock(&server);
+    let options = MarkdownOptions {
+        layout: MarkdownLayout::FilePerDeck,
+        copy_media: false,
+        ..Default::default()
+    };
+    let export = engine
+        .export()
+        .to_markdown("Japanese", dir.path(), &options)
+        .await
+        .unwrap();
+
+    assert_eq!(export.files.len(), 2);
+    assert!(export.media.is_empty());
+
+    let deck = std::fs::read_to_string(dir.path().join("Japanese.md")).unwrap();
+    assert!(deck.contains("## **猫**\n\ncat\n![](media/neko.mp3)\n\n#lang/ja #n5 ^anki-1\n"));
+    assert!(dir.path().join("Japanese - Kanji.md").exists());
+}
+
+fn rendered_card(card_id: i64, question: &str, answer: &str) -> serde_json::Value {
+    let mut card = card(card_id, card_id, 0);
+    card["question"] = question.into();
+    card["answer"] = answer.into();
+    card
+}
+
+async fn mock_printable_deck(server: &wiremock::MockServer) {
+    mock_action(
+        server,
+        "findCards",
+        mock_anki_response(vec![10_i64, 20, 30]),
+    )
+    .await;
+    mock_action(
+        server,
+        "cardsInfo",
+        mock_anki_response(vec![
+            rendered_card(
+                20,
+                "<style>.card{color:red}</style>犬[sound:inu.mp3]",
+                "<style>.card{color:red}</style>犬<hr id=answer>dog <img src=\"inu.png\">",
+            ),
+            rendered_card(10, "猫", "猫<hr id=answer><b>cat</b>"),
+            rendered_card(30, "鳥 [[type:Back]]", "鳥<hr id=answer>bird"),
+        ]),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_printable_two_column() {
+    let server = setup_mock_server().await;
+    mock_printable_deck(&server).await;
+
+    let engine = engine_for_mock(&server);
+    let sheet = engine
+        .export()
+        .printable("Japanese", &PrintableOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(sheet.cards, 3);
+    assert!(sheet.html.contains("<title>Japanese</title>"));
+    assert!(
+        sheet
+            .html
+            .contains("<tr><td>猫</td><td><b>cat</b></td></tr>")
+    );
+    assert!(sheet.html.contains("<tr><td>犬</td><td>dog</td></tr>"));
+    assert!(sheet.html.contains("<tr><td>鳥</td><td>bird</td></tr>"));
+    assert!(sheet.html.find("猫").unwrap() < sheet.html.find("犬").unwrap());
+    assert!(!sheet.html.contains("color:red"));
+    assert!(!sheet.html.contains("inu.png"));
+}
+
+#[tokio::test]
+async fn test_printable_grid_mirrors_backs() {
+    let server = setup_mock_server().await;
+    mock_printable_deck(&server).await;
+
+    let engine = engine_for_mock(&server);
+    let options = PrintableOptions {
+        layout: PrintLayout::Grid {
+            columns: 2,
+            rows: 2,
+        },
+        media: PrintMedia::Reference("media".to_string()),
+        title: Some("Vocab".to_string()),
+    };
+    let sheet = engine
+        .export()
+        .printable("Japanese", &options)
+        .await
+        .unwrap();
+
+    assert!(sheet.html.contains("<title>Vocab</title>"));
+    assert_eq!(sheet.html.matches("class=\"page\"").count(), 2);
+    // Fronts in order, backs mirrored within each row
+    let fronts = sheet
+        .html
+        .find("<div class=\"card\">猫</div>\n<div class=\"card\">犬</div>");
+    let backs = sheet.html.find(
+        "<div class=\"card\">dog <img src=\"media/inu.png\"></div>\n<div class=\"card\"><b>cat</b></div>\n<div class=\"card\"></div>\n<div class=\"card\">bird</div>",
+    );
+    assert!(fronts.unwrap() < backs.unwrap());
+}
+
+#[tokio::test]
+async fn test_printable_rejects_empty_grid() {
+    let server = setup_mock_server().await;
+    let engine = engine_for_mock(&server);
+    let options = PrintableOptions {
+        layout: PrintLayout::Grid {
+            columns: 0,
+            rows: 3,
+        },
+        ..Default::default()
+    };
+    let result = engine.export().printable("Japanese", &options).await;
+    assert!(matches!(result, Err(ankit_engine::Error::Validation(_))));
+}
+
+#[cfg(feature = "pdf")]
+fn printable(html: &str) -> ankit_engine::export::PrintableExport {
+    ankit_engine::export::PrintableExport {
+        deck_name: "Japanese".to_string(),
+        cards: 1,
+        html: html.to_string(),
+    }
+}
+
+#[cfg(feature = "pdf")]
+#[tokio::test]
+async fn test_save_pdf_concurrent() {
+    use ankit_engine::export::PdfCommand;
+
+    let dir = tempfile::tempdir().unwrap();
+    // "Convert" by copying the input after a pause, so the two runs overlap
+    let command =
+        PdfCommand::new("sh").args(["-c", "sleep 0.2; cp \"$0\" \"$1\"", "{input}", "{output}"]);
+    let (a, b) = (dir.path().join("a.pdf"), dir.path().join("b.pdf"));
+
+    let (first, second) = (printable("first"), printable("second"));
+    let (first, second) = tokio::join!(first.save_pdf(&a, &command), second.save_pdf(&b, &command));
+    first.unwrap();
+    second.unwrap();
+
+    assert_eq!(std::fs::read_to_string(&a).unwrap(), "first");
+    assert_eq!(std::fs::read_to_string(&b).unwrap(), "second");
+}
+
+#[cfg(feature = "pdf")]
+#[tokio::test]
+async fn test_save_pdf_ignores_stale_output() {
+    use ankit_engine::export::PdfCommand;
+
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("sheet.pdf");
+    std::fs::write(&output, "from an earlier run").unwrap();
+
+    // Exits successfully without writing anything
+    let result = printable("sheet")
+        .save_pdf(&output, &PdfCommand::new("true"))
+        .await;
+
+    assert!(matches!(result, Err(ankit_engine::Error::Pdf(_))));
+}
@@ -3,9 +3,16 @@
 mod common;
 
 use ankit_engine::NoteBuilder;
-use ankit_engine::import::{SmartAddOptions, SmartAddStatus};
+use ankit_engine::changes::PlannedChange;
+use ankit_engine::import::{
+    ImportAction, MarkdownImportOptions, OnDuplicate, SmartAddOptions, SmartAddStatus,
+};
+use ankit_engine::report::WorkflowReport;
+use ankit_engine::rules::{Rule, ValidationRules};
+use ankit_engine::source::{DelimitedSource, FieldMapping};
 use common::{
-    engine_for_mock, mock_action, mock_action_times, mock_anki_response, setup_mock_server,
+    dry_run_engine_for_mock, engine_for_mock, mock_action, mock_action_times,
+    mock_action_with_params, mock_anki_response, setup_mock_server,
 };
 
 #[tokio::test]
@@ -116,6 +123,12 @@ async fn test_smart_add_rejected_duplicate() {
     assert_eq!(result.note_id, None);
     assert_eq!(result.similar_notes, vec![999]);
     assert!(result.suggested_tags.contains(&"existing-tag".to_string()));
+    assert_eq!(result.summary(), "Rejected as a duplicate of note 999");
+    assert!(result.affected().is_empty());
+    assert_eq!(
+        result.to_json().unwrap()["status"]["kind"],
+        "rejected_duplicate"
+    );
 }
 
 #[tokio::test]
@@ -279,3 +292,745 @@ async fn test_smart_add_no_checks() {
     assert_eq!(result.note_id, Some(12347));
     assert!(result.suggested_tags.is_empty());
 }
+
+#[tokio::test]
+async fn test_import_notes_dry_run() {
+    let server = setup_mock_server().await;
+
+    // canAddNotesWithErrorDetail is a read; addNotes must not be called
+    mock_action(
+        &server,
+        "canAddNotesWithErrorDetail",
+        mock_anki_response(vec![
+            serde_json::json!({"canAdd": true}),
+            serde_json::json!({"canAdd": false, "error": "cannot create note because it is a duplicate"}),
+        ]),
+    )
+    .await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let notes = vec![
+        NoteBuilder::new("Japanese", "Basic")
+            .field("Front", "hello")
+            .field("Back", "world")
+            .build(),
+        NoteBuilder::new("Japanese", "Basic")
+            .field("Front", "goodbye")
+            .field("Back", "world")
+            .build(),
+    ];
+
+    let report = engine
+        .import()
+        .notes(&notes, OnDuplicate::Skip)
+        .await
+        .unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.added, 1);
+    assert_eq!(report.skipped, 1);
+    assert_eq!(report.planned.len(), 1);
+    assert!(matches!(
+        &report.planned[0],
+        PlannedChange::AddNote { note } if note.fields["Front"] == "hello"
+    ));
+}
+
+#[tokio::test]
+async fn test_smart_add_dry_run() {
+    let server = setup_mock_server().await;
+
+    mock_action(&server, "modelNames", mock_anki_response(vec!["Basic"])).await;
+    mock_action(&server, "deckNames", mock_anki_response(vec!["Japanese"])).await;
+    mock_action(
+        &server,
+        "modelFieldNames",
+        mock_anki_response(vec!["Front", "Back"]),
+    )
+    .await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let note = NoteBuilder::new("Japanese", "Basic")
+        .field("Front", "hello")
+        .field("Back", "world")
+        .build();
+
+    let options = SmartAddOptions {
+        check_duplicates: false,
+        suggest_tags: false,
+        check_empty_fields: false,
+        ..Default::default()
+    };
+
+    let result = engine.import().smart_add(&note, options).await.unwrap();
+
+    assert!(matches!(result.status, SmartAddStatus::Added));
+    assert_eq!(result.note_id, None);
+}
+
+const VAULT_NOTE: &str = "---
+deck: Geography
+tags: [places/europe]
+---
+# Capitals
+
+```anki
+Q: What is the capital of **France**?
+A: Paris
+```
+^paris1
+
+```anki-cloze
+The capital of ==Italy== is ==Rome==.
+```
+";
+
+#[tokio::test]
+async fn test_markdown_import_adds_blocks_and_writes_ids() {
+    let server = setup_mock_server().await;
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("capitals.md");
+    std::fs::write(&file, VAULT_NOTE).unwrap();
+    // Hidden directories such as .obsidian are skipped
+    std::fs::create_dir(dir.path().join(".obsidian")).unwrap();
+    std::fs::write(
+        dir.path().join(".obsidian/ignored.md"),
+        "```anki\nQ: x\nA: y\n```\n",
+    )
+    .unwrap();
+
+    mock_action(&server, "findNotes", mock_anki_response(Vec::<i64>::new())).await;
+    mock_action_times(&server, "addNote", mock_anki_response(1001_i64), 2).await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .import()
+        .markdown(dir.path(), &MarkdownImportOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(report.files, 1);
+    assert_eq!(report.blocks, 2);
+    assert_eq!(report.added, 2);
+    assert_eq!(report.ids_generated, 1);
+    assert!(report.failures.is_empty());
+
+    // The cloze block gets a generated ID on the line after its fence
+    let written = std::fs::read_to_string(&file).unwrap();
+    let lines: Vec<&str> = written.lines().collect();
+    let fence = lines.iter().rposition(|l| *l == "```").unwrap();
+    assert!(lines[fence + 1].starts_with('^'));
+    assert_eq!(lines[fence + 1].len(), 7);
+    assert!(written.contains("^paris1"));
+}
+
+#[tokio::test]
+async fn test_markdown_import_dry_run_plans_notes() {
+    let server = setup_mock_server().await;
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("capitals.md");
+    std::fs::write(&file, VAULT_NOTE).unwrap();
+
+    mock_action(&server, "findNotes", mock_anki_response(Vec::<i64>::new())).await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine
+        .import()
+        .markdown(dir.path(), &MarkdownImportOptions::default())
+        .await
+        .unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.added, 2);
+    // Dry runs leave the files untouched
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), VAULT_NOTE);
+
+    let notes: Vec<_> = report
+        .planned
+        .iter()
+        .filter_map(|change| match change {
+            PlannedChange::AddNote { note } => Some(note),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(notes.len(), 2);
+
+    let basic = notes.iter().find(|n| n.model_name == "Basic").unwrap();
+    assert_eq!(basic.deck_name, "Geography");
+    assert_eq!(
+        basic.fields["Front"],
+        "What is the capital of <b>France</b>?"
+    );
+    assert_eq!(basic.fields["Back"], "Paris");
+    let tags = &basic.tags;
+    assert!(tags.contains(&"places::europe".to_string()));
+    assert!(tags.contains(&"md-block::paris1".to_string()));
+
+    let cloze = notes.iter().find(|n| n.model_name == "Cloze").unwrap();
+    assert_eq!(
+        cloze.fields["Text"],
+        "The capital of {{c1::Italy}} is {{c2::Rome}}."
+    );
+}
+
+#[tokio::test]
+async fn test_markdown_import_updates_changed_blocks() {
+    let server = setup_mock_server().await;
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("cards.md"),
+        "```anki\nQ: Capital of France?\nA: Paris\n```\n^paris1\n\n```anki\nQ: Capital of Spain?\nA: Madrid\n```\n^madrid\n",
+    )
+    .unwrap();
+
+    mock_action(&server, "findNotes", mock_anki_response(vec![1_i64, 2])).await;
+    mock_action(
+        &server,
+        "notesInfo",
+        mock_anki_response(serde_json::json!([
+            {
+                "noteId": 1,
+                "modelName": "Basic",
+                "tags": ["md-block::paris1"],
+                "fields": {
+                    "Front": {"value": "Capital of France?", "order": 0},
+                    "Back": {"value": "Paris", "order": 1}
+                },
+                "cards": [11]
+            },
+            {
+                "noteId": 2,
+                "modelName": "Basic",
+                "tags": ["md-block::MADRID"],
+                "fields": {
+                    "Front": {"value": "Capital of Spain?", "order": 0},
+                    "Back": {"value": "Barcelona", "order": 1}
+                },
+                "cards": [12]
+            }
+        ])),
+    )
+    .await;
+    mock_action(&server, "updateNoteFields", mock_anki_response(())).await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .import()
+        .markdown(dir.path(), &MarkdownImportOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(report.blocks, 2);
+    assert_eq!(report.unchanged, 1);
+    assert_eq!(report.updated, 1);
+    assert_eq!(report.added, 0);
+    assert_eq!(report.ids_generated, 0);
+}
+
+#[tokio::test]
+async fn test_markdown_import_stores_media_and_reports_duplicate_ids() {
+    let server = setup_mock_server().await;
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("map.png"), b"png").unwrap();
+    std::fs::write(
+        dir.path().join("cards.md"),
+        "```anki\nQ: Which country? ![[map.png]]\nA: France\n```\n^dup\n\n```anki\nQ: Again?\nA: Yes\n```\n^dup\n",
+    )
+    .unwrap();
+
+    mock_action(&server, "findNotes", mock_anki_response(Vec::<i64>::new())).await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine
+        .import()
+        .markdown(dir.path(), &MarkdownImportOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(report.added, 1);
+    assert_eq!(report.failures.len(), 1);
+    assert_eq!(report.failures[0].line, 7);
+    assert!(report.failures[0].error.contains("duplicate"));
+
+    assert!(report.planned.iter().any(|change| matches!(
+        change,
+        PlannedChange::StoreMedia { filename } if filename == "map.png"
+    )));
+    let front = report
+        .planned
+        .iter()
+        .find_map(|change| match change {
+            PlannedChange::AddNote { note } => Some(note.fields["Front"].clone()),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(front, "Which country? <img src=\"map.png\">");
+}
+
+fn keyed_note(key: &str, front: &str) -> ankit_engine::Note {
+    NoteBuilder::new("Imported", "Basic")
+        .field("ExternalID", key)
+        .field("Front", front)
+        .build()
+}
+
+async fn mock_upsert_lookup(server: &wiremock::MockServer) {
+    for (key, ids) in [("a1", vec![10_i64]), ("b2", vec![11]), ("c3", vec![])] {
+        mock_action_with_params(
+            server,
+            "findNotes",
+            serde_json::json!({"query": format!("note:\"Basic\" \"ExternalID:{}\"", key)}),
+            mock_anki_response(ids),
+        )
+        .await;
+    }
+    let existing = |id: i64, key: &str, front: &str| {
+        serde_json::json!({
+            "noteId": id,
+            "modelName": "Basic",
+            "tags": [],
+            "fields": {
+                "ExternalID": {"value": key, "order": 0},
+                "Front": {"value": front, "order": 1}
+            },
+            "cards": []
+        })
+    };
+    mock_action(
+        server,
+        "notesInfo",
+        mock_anki_response(vec![existing(10, "a1", "same"), existing(11, "b2", "old")]),
+    )
+    .await;
+}
+
+fn upsert_notes() -> Vec<ankit_engine::Note> {
+    vec![
+        keyed_note("a1", "same"),
+        keyed_note("b2", "new"),
+        keyed_note("c3", "created"),
+        keyed_note("", "no key"),
+        keyed_note("c3", "repeated"),
+    ]
+}
+
+#[tokio::test]
+async fn test_upsert_by_field() {
+    let server = setup_mock_server().await;
+    mock_upsert_lookup(&server).await;
+    mock_action_with_params(
+        &server,
+        "updateNoteFields",
+        serde_json::json!({"note": {"id": 11, "fields": {"Front": "new"}}}),
+        mock_anki_response(serde_json::Value::Null),
+    )
+    .await;
+    mock_action(&server, "addNote", mock_anki_response(12_i64)).await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .import()
+        .notes(
+            &upsert_notes(),
+            OnDuplicate::UpsertByField("ExternalID".into()),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(report.added, 1);
+    assert_eq!(report.updated, 1);
+    assert_eq!(report.unchanged, 1);
+    assert_eq!(report.failed, 2);
+    let failed: Vec<usize> = report.failures.iter().map(|f| f.index).collect();
+    assert_eq!(failed, vec![3, 4]);
+    let outcomes: Vec<(usize, Option<i64>, ImportAction)> = report
+        .outcomes
+        .iter()
+        .map(|o| (o.index, o.note_id, o.action))
+        .collect();
+    assert_eq!(
+        outcomes,
+        vec![
+            (0, Some(10), ImportAction::Unchanged),
+            (1, Some(11), ImportAction::Updated),
+            (2, Some(12), ImportAction::Created),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_upsert_by_field_dry_run() {
+    let server = setup_mock_server().await;
+    mock_upsert_lookup(&server).await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine
+        .import()
+        .notes(
+            &upsert_notes(),
+            OnDuplicate::UpsertByField("ExternalID".into()),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(report.planned.len(), 2);
+    assert!(matches!(
+        &report.planned[0],
+        PlannedChange::UpdateNoteFields { note_id: 11, .. }
+    ));
+    assert!(matches!(
+        &report.planned[1],
+        PlannedChange::AddNote { note } if note.fields["Front"] == "created"
+    ));
+    assert_eq!(report.outcomes[2].note_id, None);
+}
+
+#[tokio::test]
+async fn test_import_from_source_reports_provenance() {
+    let server = setup_mock_server().await;
+
+    mock_action(
+        &server,
+        "canAddNotesWithErrorDetail",
+        mock_anki_response(vec![
+            serde_json::json!({"canAdd": false, "error": "cannot create note because it is a duplicate"}),
+            serde_json::json!({"canAdd": true}),
+            serde_json::json!({"canAdd": true}),
+        ]),
+    )
+    .await;
+    mock_action(
+        &server,
+        "addNotes",
+        mock_anki_response(vec![Some(1001_i64), None]),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let mapping = FieldMapping::new("Spanish", "Basic")
+        .field("word", "Front")
+        .field("meaning", "Back");
+    let content = "word,meaning\nperro,dog\ngato,cat\nbad row\n\"casa\",\"house, home\"\n";
+    let source = DelimitedSource::csv("vocab.csv", content, mapping);
+
+    let report = engine
+        .import()
+        .from_source(source, OnDuplicate::Skip)
+        .await
+        .unwrap();
+
+    assert_eq!(report.records_read, 4);
+    assert_eq!(report.skipped, 1);
+    assert_eq!(report.added, 1);
+    assert_eq!(report.errors.len(), 2);
+    assert_eq!(report.errors[0].provenance.to_string(), "vocab.csv:4");
+    assert!(report.errors[0].error.contains("expected 2 columns"));
+    assert_eq!(report.errors[1].provenance.to_string(), "vocab.csv:5");
+}
+
+#[tokio::test]
+async fn test_validate_with_rules() {
+    let server = setup_mock_server().await;
+
+    mock_action(&server, "modelNames", mock_anki_response(vec!["Basic"])).await;
+    mock_action(&server, "deckNames", mock_anki_response(vec!["Spanish"])).await;
+    mock_action_times(
+        &server,
+        "modelFieldNames",
+        mock_anki_response(vec!["Front", "Back"]),
+        2,
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let notes = vec![
+        NoteBuilder::new("Spanish", "Basic")
+            .field("Front", "perro")
+            .field("Back", "dog")
+            .build(),
+        NoteBuilder::new("Spanish", "Basic")
+            .field("Front", "")
+            .field("Back", "<b>cat</b>")
+            .build(),
+    ];
+    let rules = ValidationRules::new().required("Front").no_html("Back");
+
+    let results = engine.import().validate_with(&notes, &rules).await.unwrap();
+
+    assert!(results[0].valid);
+    assert!(!results[1].valid);
+    let kinds: Vec<Rule> = results[1].violations.iter().map(|v| v.rule).collect();
+    assert_eq!(kinds, vec![Rule::Html, Rule::Required]);
+    assert_eq!(results[1].errors.len(), 2);
+}
+
+#[tokio::test]
+async fn test_import_notes_reports_progress() {
+    use ankit_engine::EngineOptions;
+    use ankit_engine::status::ProgressUpdate;
+    use std::sync::{Arc, Mutex};
+
+    let server = setup_mock_server().await;
+    mock_action(
+        &server,
+        "canAddNotesWithErrorDetail",
+        mock_anki_response(vec![
+            serde_json::json!({"canAdd": true}),
+            serde_json::json!({"canAdd": true}),
+        ]),
+    )
+    .await;
+    mock_action(
+        &server,
+        "addNotes",
+        mock_anki_response(vec![Some(1001_i64), Some(1002)]),
+    )
+    .await;
+
+    let updates = Arc::new(Mutex::new(Vec::new()));
+    let sink = {
+        let updates = updates.clone();
+        move |update: &ProgressUpdate| updates.lock().unwrap().push(update.clone())
+    };
+    let engine = engine_for_mock(&server).with_options(EngineOptions {
+        progress_sink: Some(Arc::new(sink)),
+        ..Default::default()
+    });
+    let notes = vec![
+        NoteBuilder::new("Japanese", "Basic")
+            .field("Front", "hello")
+            .build(),
+        NoteBuilder::new("Japanese", "Basic")
+            .field("Front", "goodbye")
+            .build(),
+    ];
+
+    let report = engine
+        .import()
+        .notes(&notes, OnDuplicate::Skip)
+        .await
+        .unwrap();
+    assert_eq!(report.added, 2);
+
+    let updates = updates.lock().unwrap();
+    let processed: Vec<_> = updates.iter().map(|u| u.processed).collect();
+    assert_eq!(processed, [0, 2]);
+    assert!(
+        updates
+            .iter()
+            .all(|u| u.phase == "adding notes" && u.total == Some(2))
+    );
+}
+
+#[tokio::test]
+async fn test_import_notes_stops_when_cancelled() {
+    use ankit_engine::status::{ProgressSink, ProgressUpdate};
+    use ankit_engine::{EngineOptions, Error};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// Cancels once the first batch has been added.
+    struct CancelAfterFirstBatch(AtomicBool);
+
+    impl ProgressSink for CancelAfterFirstBatch {
+        fn update(&self, update: &ProgressUpdate) {
+            if update.processed > 0 {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        fn is_cancelled(&self) -> bool {
+            self.0.load(Ordering::SeqCst)
+        }
+    }
+
+    let server = setup_mock_server().await;
+    mock_action(
+        &server,
+        "canAddNotesWithErrorDetail",
+        mock_anki_response(vec![serde_json::json!({"canAdd": true}); 150]),
+    )
+    .await;
+    // Only the first batch of 100 notes is sent
+    mock_action_times(
+        &server,
+        "addNotes",
+        mock_anki_response((1..=100).map(Some).collect::<Vec<Option<i64>>>()),
+        1,
+    )
+    .await;
+
+    let engine = engine_for_mock(&server).with_options(EngineOptions {
+        progress_sink: Some(Arc::new(CancelAfterFirstBatch(AtomicBool::new(false)))),
+        ..Default::default()
+    });
+    let notes: Vec<_> = (0..150)
+        .map(|i| {
+            NoteBuilder::new("Japanese", "Basic")
+                .field("Front", format!("word {}", i))
+                .build()
+        })
+        .collect();
+
+    let result = engine.import().notes(&notes, OnDuplicate::Skip).await;
+    assert!(matches!(result, Err(Error::Cancelled)));
+}
+
+#[tokio::test]
+async fn test_import_notes_detailed_explains_failures() {
+    use ankit_engine::import::AddErrorKind;
+
+    let server = setup_mock_server().await;
+    mock_action(
+        &server,
+        "addNotes",
+        mock_anki_response(vec![Some(1001_i64), None, None]),
+    )
+    .await;
+    // Only the two failed notes are re-checked
+    mock_action(
+        &server,
+        "canAddNotesWithErrorDetail",
+        mock_anki_response(vec![
+            serde_json::json!({"canAdd": false, "error": "cannot create note because it is a duplicate"}),
+            serde_json::json!({"canAdd": false, "error": "model was not found: Vocab"}),
+        ]),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let notes = vec![
+        NoteBuilder::new("Japanese", "Basic")
+            .field("Front", "new")
+            .build(),
+        NoteBuilder::new("Japanese", "Basic")
+            .field("Front", "existing")
+            .build(),
+        NoteBuilder::new("Japanese", "Vocab")
+            .field("Word", "typo")
+            .build(),
+    ];
+
+    let report = engine.import().notes_detailed(&notes).await.unwrap();
+
+    assert_eq!(report.added, 1);
+    assert_eq!(report.note_ids, [Some(1001), None, None]);
+    let errors: Vec<_> = report.errors.iter().map(|e| (e.index, e.kind)).collect();
+    assert_eq!(
+        errors,
+        [
+            (1, AddErrorKind::Duplicate),
+            (2, AddErrorKind::ModelNotFound)
+        ]
+    );
+    let checked = server
+        .received_requests()
+        .await
+        .unwrap()
+        .into_iter()
+        .filter_map(|r| serde_json::from_slice::<serde_json::Value>(&r.body).ok())
+        .find(|body| body["action"] == "canAddNotesWithErrorDetail")
+        .unwrap();
+    assert_eq!(checked["params"]["notes"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_import_notes_detailed_without_error_detail() {
+    use ankit_engine::import::AddErrorKind;
+
+    let server = setup_mock_server().await;
+    mock_action(&server, "addNotes", mock_anki_response(vec![None::<i64>])).await;
+    mock_action(
+        &server,
+        "canAddNotesWithErrorDetail",
+        common::mock_anki_error("unsupported action"),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let notes = vec![
+        NoteBuilder::new("Japanese", "Basic")
+            .field("Front", "hello")
+            .build(),
+    ];
+
+    let report = engine.import().notes_detailed(&notes).await.unwrap();
+
+    assert_eq!(report.added, 0);
+    assert_eq!(report.errors[0].kind, AddErrorKind::Unknown);
+}
+
+#[tokio::test]
+async fn test_import_notes_detailed_with_capability_detection() {
+    use ankit_engine::import::AddErrorKind;
+
+    let server = setup_mock_server().await;
+    mock_action(&server, "version", mock_anki_response(6)).await;
+    mock_action(
+        &server,
+        "apiReflect",
+        mock_anki_response(serde_json::json!({
+            "scopes": ["actions"],
+            "actions": ["version", "apiReflect", "addNotes"]
+        })),
+    )
+    .await;
+    mock_action(&server, "addNotes", mock_anki_response(vec![None::<i64>])).await;
+
+    let client = ankit_engine::ClientBuilder::new()
+        .url(server.uri())
+        .detect_capabilities(true)
+        .build();
+    let engine = ankit_engine::Engine::from_client(client);
+    let notes = vec![
+        NoteBuilder::new("Japanese", "Basic")
+            .field("Front", "hello")
+            .build(),
+    ];
+
+    let report = engine.import().notes_detailed(&notes).await.unwrap();
+
+    assert_eq!(report.added, 0);
+    assert_eq!(report.errors[0].kind, AddErrorKind::Unknown);
+}
+
+#[tokio::test]
+async fn test_import_notes_detailed_classifies_messages() {
+    use ankit_engine::import::AddErrorKind;
+
+    let server = setup_mock_server().await;
+    mock_action(
+        &server,
+        "addNotes",
+        mock_anki_response(vec![None::<i64>, None, None]),
+    )
+    .await;
+    mock_action(
+        &server,
+        "canAddNotesWithErrorDetail",
+        mock_anki_response(vec![
+            serde_json::json!({"canAdd": false, "error": "cannot create note because it is empty"}),
+            serde_json::json!({"canAdd": false, "error": "deck was not found: Japanese"}),
+            serde_json::json!({"canAdd": false, "error": "field is too long"}),
+        ]),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let note = NoteBuilder::new("Japanese", "Basic")
+        .field("Front", "")
+        .build();
+    let notes = vec![note.clone(), note.clone(), note];
+
+    let report = engine.import().notes_detailed(&notes).await.unwrap();
+
+    let kinds: Vec<_> = report.errors.iter().map(|e| e.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            AddErrorKind::MissingField,
+            AddErrorKind::DeckNotFound,
+            AddErrorKind::Other
+        ]
+    );
+}
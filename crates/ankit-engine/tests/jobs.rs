@@ -0,0 +1,95 @@
+//! Tests for scheduled jobs.
+
+mod common;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ankit_engine::Error;
+use ankit_engine::jobs::{JobOutput, JobScheduler, Schedule};
+use ankit_engine::notify::{WebhookFormat, WebhookNotifier};
+use common::{engine_for_mock, mock_action, mock_anki_response, setup_mock_server};
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn scheduler(engine: ankit_engine::Engine, dir: &std::path::Path) -> JobScheduler {
+    JobScheduler::new(engine)
+        .job("decks", Schedule::parse("@hourly").unwrap(), |engine| {
+            Box::pin(async move {
+                let decks = engine.client().decks().names().await?;
+                Ok(JobOutput::new(format!("{} decks", decks.len())))
+            })
+        })
+        .job("broken", Schedule::parse("*/30 * * * *").unwrap(), |_| {
+            Box::pin(async { Err(Error::Validation("boom".to_string())) })
+        })
+        .state_file(dir.join("state.json"))
+        .log_file(dir.join("runs.jsonl"))
+}
+
+#[tokio::test]
+async fn test_jobs_run_when_due_and_persist_state() {
+    let server = setup_mock_server().await;
+    mock_action(
+        &server,
+        "deckNames",
+        mock_anki_response(vec!["Default", "Spanish"]),
+    )
+    .await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let hook = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/hook"))
+        .and(body_partial_json(
+            serde_json::json!({"text": "decks: 2 decks"}),
+        ))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&hook)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/hook"))
+        .and(body_partial_json(
+            serde_json::json!({"text": "broken failed: validation error: boom"}),
+        ))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(1)
+        .mount(&hook)
+        .await;
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let mut jobs = scheduler(engine_for_mock(&server), dir.path())
+        .on_run(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .notifier(
+            WebhookNotifier::new(format!("{}/hook", hook.uri())).format(WebhookFormat::Slack),
+        );
+
+    // Nothing is due when the scheduler first sees its jobs
+    let now = 1_800_000_000;
+    assert!(jobs.run_due(now).await.unwrap().is_empty());
+    assert!(jobs.status().iter().all(|job| job.next_run > Some(now)));
+
+    let runs = jobs.run_due(now + 2 * 3600).await.unwrap();
+    assert_eq!(runs.len(), 2);
+    assert!(runs[0].success);
+    assert_eq!(runs[0].summary, "2 decks");
+    assert!(!runs[1].success);
+    assert_eq!(runs[1].summary, "validation error: boom");
+    // The failed webhook delivery doesn't stop the scheduler
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    let log = std::fs::read_to_string(dir.path().join("runs.jsonl")).unwrap();
+    assert_eq!(log.lines().count(), 2);
+
+    // A new scheduler picks up the last runs from the state file
+    let mut restarted = scheduler(engine_for_mock(&server), dir.path());
+    let run = restarted.run_job("broken").await.unwrap();
+    assert!(!run.success);
+    let status = restarted.status();
+    assert!(status.iter().all(|job| job.last_run.is_some()));
+    assert!(restarted.run_job("missing").await.is_err());
+}
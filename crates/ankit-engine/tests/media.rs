@@ -0,0 +1,531 @@
+//! Tests for media workflow operations.
+
+mod common;
+
+use ankit_engine::changes::PlannedChange;
+use common::{
+    dry_run_engine_for_mock, engine_for_mock, mock_action, mock_action_times,
+    mock_action_with_params, mock_anki_error, mock_anki_response, setup_mock_server,
+};
+use serde_json::json;
+use wiremock::MockServer;
+
+async fn mock_rename_lookup(server: &MockServer) {
+    mock_action_with_params(
+        server,
+        "getMediaFilesNames",
+        json!({"pattern": "old.mp3"}),
+        mock_anki_response(vec!["old.mp3"]),
+    )
+    .await;
+    mock_action_with_params(
+        server,
+        "getMediaFilesNames",
+        json!({"pattern": "perro.mp3"}),
+        mock_anki_response(Vec::<String>::new()),
+    )
+    .await;
+    mock_action(server, "findNotes", mock_anki_response(vec![1_i64, 2])).await;
+    mock_action(
+        server,
+        "notesInfo",
+        mock_anki_response(vec![
+            json!({
+                "noteId": 1,
+                "modelName": "Basic",
+                "tags": [],
+                "fields": {
+                    "Front": {"value": "perro [sound:old.mp3]", "order": 0},
+                    "Back": {"value": "dog", "order": 1}
+                },
+                "cards": []
+            }),
+            // Mentions the name without referencing the file
+            json!({
+                "noteId": 2,
+                "modelName": "Basic",
+                "tags": [],
+                "fields": {
+                    "Front": {"value": "see old.mp3", "order": 0},
+                    "Back": {"value": "", "order": 1}
+                },
+                "cards": []
+            }),
+        ]),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_rename_with_references() {
+    let server = setup_mock_server().await;
+    mock_rename_lookup(&server).await;
+    mock_action(&server, "retrieveMediaFile", mock_anki_response("c291bmQ=")).await;
+    mock_action_with_params(
+        &server,
+        "storeMediaFile",
+        json!({"filename": "perro.mp3", "data": "c291bmQ="}),
+        mock_anki_response("perro.mp3"),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "updateNoteFields",
+        json!({"note": {"id": 1, "fields": {"Front": "perro [sound:perro.mp3]"}}}),
+        mock_anki_response(serde_json::Value::Null),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "deleteMediaFile",
+        json!({"filename": "old.mp3"}),
+        mock_anki_response(serde_json::Value::Null),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .media()
+        .rename_with_references("old.mp3", "perro.mp3")
+        .await
+        .unwrap();
+
+    assert_eq!(report.notes_updated, 1);
+    assert_eq!(report.references_rewritten, 1);
+}
+
+#[tokio::test]
+async fn test_rename_with_references_dry_run() {
+    let server = setup_mock_server().await;
+    mock_rename_lookup(&server).await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine
+        .media()
+        .rename_with_references("old.mp3", "perro.mp3")
+        .await
+        .unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.notes_updated, 1);
+    assert_eq!(report.planned.len(), 3);
+    assert!(matches!(
+        &report.planned[1],
+        PlannedChange::UpdateNoteFields { note_id: 1, fields }
+            if fields["Front"] == "perro [sound:perro.mp3]"
+    ));
+    assert!(matches!(
+        &report.planned[2],
+        PlannedChange::DeleteMedia { filename } if filename == "old.mp3"
+    ));
+}
+
+#[tokio::test]
+async fn test_rename_with_references_rejects_existing_target() {
+    let server = setup_mock_server().await;
+    mock_action_with_params(
+        &server,
+        "getMediaFilesNames",
+        json!({"pattern": "old.mp3"}),
+        mock_anki_response(vec!["old.mp3"]),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "getMediaFilesNames",
+        json!({"pattern": "perro.mp3"}),
+        mock_anki_response(vec!["perro.mp3"]),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let result = engine
+        .media()
+        .rename_with_references("old.mp3", "perro.mp3")
+        .await;
+    assert!(matches!(result, Err(ankit_engine::Error::Validation(_))));
+}
+
+#[tokio::test]
+async fn test_rename_with_references_escapes_glob_characters() {
+    let server = setup_mock_server().await;
+    mock_action_with_params(
+        &server,
+        "getMediaFilesNames",
+        json!({"pattern": "take [[]1][*][?].mp3"}),
+        mock_anki_response(vec!["take [1]*?.mp3"]),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "getMediaFilesNames",
+        json!({"pattern": "take 1.mp3"}),
+        mock_anki_response(vec!["take 1.mp3"]),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let result = engine
+        .media()
+        .rename_with_references("take [1]*?.mp3", "take 1.mp3")
+        .await;
+    // The old file was found, so only the existing target is rejected
+    assert!(matches!(
+        result,
+        Err(ankit_engine::Error::Validation(message)) if message.contains("already exists")
+    ));
+}
+
+#[tokio::test]
+async fn test_rename_with_references_rejects_sanitized_name() {
+    let server = setup_mock_server().await;
+    mock_rename_lookup(&server).await;
+    mock_action(&server, "retrieveMediaFile", mock_anki_response("c291bmQ=")).await;
+    mock_action(&server, "storeMediaFile", mock_anki_response("perro_.mp3")).await;
+    mock_action_times(
+        &server,
+        "updateNoteFields",
+        mock_anki_response(serde_json::Value::Null),
+        0,
+    )
+    .await;
+    // The copy is removed and the original kept
+    mock_action_with_params(
+        &server,
+        "deleteMediaFile",
+        json!({"filename": "perro_.mp3"}),
+        mock_anki_response(serde_json::Value::Null),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let result = engine
+        .media()
+        .rename_with_references("old.mp3", "perro.mp3")
+        .await;
+    assert!(matches!(result, Err(ankit_engine::Error::Validation(_))));
+}
+
+#[tokio::test]
+async fn test_audit_sizes() {
+    let server = setup_mock_server().await;
+    mock_action(
+        &server,
+        "getMediaFilesNames",
+        mock_anki_response(vec!["big.png", "small.mp3", "stray.jpg"]),
+    )
+    .await;
+    // Not on this machine, so sizes come from the file contents
+    mock_action(
+        &server,
+        "getMediaDirPath",
+        mock_anki_response("/nonexistent/ankit-media"),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "retrieveMediaFile",
+        json!({"filename": "big.png"}),
+        mock_anki_response("AAAAAAAAAAA="),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "retrieveMediaFile",
+        json!({"filename": "small.mp3"}),
+        mock_anki_response("AAAA"),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "retrieveMediaFile",
+        json!({"filename": "stray.jpg"}),
+        mock_anki_response("AAAAAA=="),
+    )
+    .await;
+    mock_action(&server, "findCards", mock_anki_response(vec![1_i64, 2])).await;
+    let card = |id: i64, deck: &str, front: &str| {
+        json!({
+            "cardId": id,
+            "noteId": id + 100,
+            "deckName": deck,
+            "modelName": "Basic",
+            "question": "",
+            "answer": "",
+            "fields": {"Front": {"value": front, "order": 0}},
+            "type": 2,
+            "queue": 2,
+            "due": 0,
+            "interval": 1,
+            "factor": 2500,
+            "reps": 1,
+            "lapses": 0,
+            "left": 0,
+            "mod": 0
+        })
+    };
+    mock_action(
+        &server,
+        "cardsInfo",
+        mock_anki_response(vec![
+            card(1, "Spanish", "<img src=\"big.png\"> [sound:small.mp3]"),
+            card(2, "French", "<img src=\"big.png\">"),
+        ]),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine.media().audit_sizes().await.unwrap();
+
+    assert_eq!(report.total_files, 3);
+    assert_eq!(report.total_bytes, 8 + 3 + 4);
+    assert_eq!(report.unreferenced_bytes, 4);
+    assert_eq!(report.files[0].filename, "big.png");
+    assert_eq!(report.files[0].bytes, 8);
+    assert_eq!(report.files[0].decks, vec!["French", "Spanish"]);
+    assert_eq!(report.files[1].filename, "stray.jpg");
+    assert!(report.files[1].decks.is_empty());
+    assert_eq!(report.by_deck[0].deck, "Spanish");
+    assert_eq!(report.by_deck[0].files, 2);
+    assert_eq!(report.by_deck[0].bytes, 11);
+    assert_eq!(report.by_deck[1].bytes, 8);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_compress_replaces_smaller_files() {
+    use ankit_engine::media::CompressOptions;
+    use std::os::unix::fs::PermissionsExt;
+
+    // Stand-in encoder that writes a single byte to its output path
+    let dir = tempfile::tempdir().unwrap();
+    let ffmpeg = dir.path().join("ffmpeg");
+    std::fs::write(
+        &ffmpeg,
+        "#!/bin/sh\nfor out; do :; done\nprintf x > \"$out\"\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&ffmpeg, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let server = setup_mock_server().await;
+    mock_action(
+        &server,
+        "getMediaFilesNames",
+        mock_anki_response(vec!["big.png", "notes.txt"]),
+    )
+    .await;
+    mock_action(
+        &server,
+        "getMediaDirPath",
+        mock_anki_response("/nonexistent/ankit-media"),
+    )
+    .await;
+    mock_action_times(
+        &server,
+        "retrieveMediaFile",
+        mock_anki_response("AAAAAAAAAAA="),
+        2,
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "storeMediaFile",
+        json!({"filename": "big.png", "data": "eA=="}),
+        mock_anki_response("big.png"),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let options = CompressOptions {
+        min_bytes: 1,
+        ffmpeg: ffmpeg.to_string_lossy().into_owned(),
+        ..Default::default()
+    };
+    let report = engine.media().compress(&options).await.unwrap();
+
+    assert_eq!(report.files_checked, 1);
+    assert!(report.failed.is_empty(), "{:?}", report.failed);
+    assert_eq!(report.files.len(), 1);
+    assert_eq!(report.files[0].compressed, "big.png");
+    assert_eq!(report.files[0].bytes_before, 8);
+    assert_eq!(report.files[0].bytes_after, 1);
+    assert_eq!(report.bytes_saved, 7);
+    assert_eq!(report.notes_updated, 0);
+}
+
+async fn mock_remote_notes(server: &MockServer) {
+    mock_action(server, "findNotes", mock_anki_response(vec![1_i64, 2])).await;
+    mock_action(
+        server,
+        "notesInfo",
+        mock_anki_response(vec![
+            json!({
+                "noteId": 1,
+                "modelName": "Basic",
+                "tags": [],
+                "fields": {
+                    "Front": {
+                        "value": "<img src=\"https://example.com/img/cat.jpg?s=1&amp;v=2\">",
+                        "order": 0
+                    },
+                    "Back": {"value": "[sound:https://example.com/meow.mp3]", "order": 1}
+                },
+                "cards": []
+            }),
+            json!({
+                "noteId": 2,
+                "modelName": "Basic",
+                "tags": [],
+                "fields": {
+                    "Front": {"value": "<img src=\"local.png\">", "order": 0},
+                    "Back": {"value": "https://example.com in text", "order": 1}
+                },
+                "cards": []
+            }),
+        ]),
+    )
+    .await;
+    mock_action(
+        server,
+        "getMediaFilesNames",
+        mock_anki_response(Vec::<String>::new()),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_localize_remote() {
+    let server = setup_mock_server().await;
+    mock_remote_notes(&server).await;
+    mock_action_with_params(
+        &server,
+        "storeMediaFile",
+        json!({"url": "https://example.com/img/cat.jpg?s=1&v=2"}),
+        mock_anki_response("cat-local.jpg"),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "storeMediaFile",
+        json!({"url": "https://example.com/meow.mp3"}),
+        mock_anki_error("download failed"),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "updateNoteFields",
+        json!({"note": {"id": 1, "fields": {"Front": "<img src=\"cat-local.jpg\">"}}}),
+        mock_anki_response(serde_json::Value::Null),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .media()
+        .localize_remote("deck:Imported")
+        .await
+        .unwrap();
+
+    assert_eq!(report.notes_checked, 2);
+    assert_eq!(report.notes_updated, 1);
+    assert_eq!(report.references_rewritten, 1);
+    assert_eq!(report.downloaded.len(), 1);
+    assert_eq!(report.downloaded[0].filename, "cat-local.jpg");
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].url, "https://example.com/meow.mp3");
+}
+
+#[tokio::test]
+async fn test_localize_remote_dry_run() {
+    let server = setup_mock_server().await;
+    mock_remote_notes(&server).await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine
+        .media()
+        .localize_remote("deck:Imported")
+        .await
+        .unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.notes_updated, 1);
+    assert_eq!(report.references_rewritten, 2);
+    assert_eq!(report.downloaded.len(), 2);
+    let cat = &report.downloaded[0].filename;
+    assert!(cat.starts_with("cat-") && cat.ends_with(".jpg"), "{}", cat);
+    let stores = report
+        .planned
+        .iter()
+        .filter(|c| matches!(c, PlannedChange::StoreMedia { .. }))
+        .count();
+    assert_eq!(stores, 2);
+    let Some(PlannedChange::UpdateNoteFields { note_id, fields }) = report.planned.last() else {
+        panic!("expected a field update");
+    };
+    assert_eq!(*note_id, 1);
+    assert_eq!(fields["Front"], format!("<img src=\"{}\">", cat));
+}
+
+#[tokio::test]
+async fn test_verify() {
+    let server = setup_mock_server().await;
+    mock_action(
+        &server,
+        "getMediaFilesNames",
+        mock_anki_response(vec!["Cat.jpg", "a.mp3", "b.mp3", "empty.png"]),
+    )
+    .await;
+    mock_action(
+        &server,
+        "getMediaDirPath",
+        mock_anki_response("/nonexistent/ankit-media"),
+    )
+    .await;
+    for (filename, data) in [
+        ("Cat.jpg", "bWVvdw=="),
+        ("a.mp3", "c291bmQ="),
+        ("b.mp3", "c291bmQ="),
+        ("empty.png", ""),
+    ] {
+        mock_action_with_params(
+            &server,
+            "retrieveMediaFile",
+            json!({ "filename": filename }),
+            mock_anki_response(data),
+        )
+        .await;
+    }
+    mock_action(&server, "findNotes", mock_anki_response(vec![1_i64])).await;
+    mock_action(
+        &server,
+        "notesInfo",
+        mock_anki_response(vec![json!({
+            "noteId": 1,
+            "modelName": "Basic",
+            "tags": [],
+            "fields": {
+                "Front": {"value": "<img src=\"cat.jpg\"> [sound:a.mp3]", "order": 0},
+                "Back": {"value": "<img src=\"gone.png\">", "order": 1}
+            },
+            "cards": []
+        })]),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine.media().verify().await.unwrap();
+
+    assert!(!report.is_healthy());
+    assert_eq!(report.files_checked, 4);
+    assert_eq!(report.references_checked, 3);
+    assert_eq!(report.missing.len(), 1);
+    assert_eq!(report.missing[0].filename, "gone.png");
+    assert_eq!(report.case_mismatches.len(), 1);
+    assert_eq!(report.case_mismatches[0].referenced, "cat.jpg");
+    assert_eq!(report.case_mismatches[0].actual, "Cat.jpg");
+    assert_eq!(report.empty_files, vec!["empty.png"]);
+    assert_eq!(report.duplicates.len(), 1);
+    assert_eq!(report.duplicates[0].files, vec!["a.mp3", "b.mp3"]);
+    assert_eq!(report.duplicates[0].bytes, 5);
+    assert_eq!(report.checksums.len(), 4);
+}
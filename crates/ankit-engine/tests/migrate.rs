@@ -0,0 +1,368 @@
+//! Tests for note type migration workflows.
+
+mod common;
+
+use std::collections::HashMap;
+
+use ankit_engine::changes::PlannedChange;
+use ankit_engine::migrate::{FieldOperation, MigrationConfig};
+use common::{
+    dry_run_engine_for_mock, engine_for_mock, mock_action, mock_action_times,
+    mock_action_with_params, mock_anki_response, mock_sequence, setup_mock_server,
+};
+use serde_json::json;
+use wiremock::MockServer;
+
+fn note(id: i64, model: &str, fields: &[(&str, &str)]) -> serde_json::Value {
+    let fields: serde_json::Map<String, serde_json::Value> = fields
+        .iter()
+        .enumerate()
+        .map(|(order, (name, value))| (name.to_string(), json!({"value": value, "order": order})))
+        .collect();
+    json!({
+        "noteId": id,
+        "modelName": model,
+        "tags": [],
+        "fields": fields,
+        "cards": []
+    })
+}
+
+async fn mock_models(server: &MockServer) {
+    mock_action(
+        server,
+        "modelNames",
+        mock_anki_response(vec!["Basic", "Vocab"]),
+    )
+    .await;
+    mock_action_with_params(
+        server,
+        "modelFieldNames",
+        json!({"modelName": "Basic"}),
+        mock_anki_response(vec!["Front", "Back", "Picture"]),
+    )
+    .await;
+    mock_action_with_params(
+        server,
+        "modelFieldNames",
+        json!({"modelName": "Vocab"}),
+        mock_anki_response(vec!["Word", "Meaning", "Image", "Notes"]),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_suggest_mapping() {
+    let server = setup_mock_server().await;
+    mock_models(&server).await;
+
+    mock_action_with_params(
+        &server,
+        "findNotes",
+        json!({"query": "note:\"Basic\""}),
+        mock_anki_response(vec![1_i64]),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "findNotes",
+        json!({"query": "note:\"Vocab\""}),
+        mock_anki_response(vec![2_i64]),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "notesInfo",
+        json!({"notes": [1]}),
+        mock_anki_response(vec![note(
+            1,
+            "Basic",
+            &[
+                ("Front", "perro"),
+                ("Back", "dog"),
+                ("Picture", "<img src=\"dog.jpg\">"),
+            ],
+        )]),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "notesInfo",
+        json!({"notes": [2]}),
+        mock_anki_response(vec![note(
+            2,
+            "Vocab",
+            &[
+                ("Word", "gato"),
+                ("Meaning", "cat"),
+                ("Image", "<img src=\"cat.jpg\">"),
+                ("Notes", ""),
+            ],
+        )]),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let suggestion = engine
+        .migrate()
+        .suggest_mapping("Basic", "Vocab")
+        .await
+        .unwrap();
+
+    let mapping = suggestion.field_mapping();
+    assert_eq!(mapping["Front"], "Word");
+    assert_eq!(mapping["Back"], "Meaning");
+    assert_eq!(mapping["Picture"], "Image");
+    assert!(suggestion.unmapped_source.is_empty());
+    assert_eq!(suggestion.unmapped_target, vec!["Notes"]);
+}
+
+#[tokio::test]
+async fn test_suggest_mapping_unknown_model() {
+    let server = setup_mock_server().await;
+    mock_action(&server, "modelNames", mock_anki_response(vec!["Basic"])).await;
+
+    let engine = engine_for_mock(&server);
+    let result = engine.migrate().suggest_mapping("Basic", "Missing").await;
+
+    assert!(matches!(
+        result,
+        Err(ankit_engine::Error::ModelNotFound(model)) if model == "Missing"
+    ));
+}
+
+#[tokio::test]
+async fn test_preview_note_values() {
+    let server = setup_mock_server().await;
+    mock_models(&server).await;
+
+    mock_action(&server, "findNotes", mock_anki_response(vec![1_i64])).await;
+    mock_action(
+        &server,
+        "notesInfo",
+        mock_anki_response(vec![note(
+            1,
+            "Basic",
+            &[
+                ("Front", "perro"),
+                ("Back", "dog"),
+                ("Picture", "<img src=\"dog.jpg\">"),
+            ],
+        )]),
+    )
+    .await;
+
+    let config = MigrationConfig {
+        source_model: "Basic".to_string(),
+        target_model: "Vocab".to_string(),
+        field_mapping: HashMap::from([
+            ("Front".to_string(), "Word".to_string()),
+            ("Back".to_string(), "Meaning".to_string()),
+        ]),
+        target_deck: None,
+        delete_source: false,
+        add_tags: Vec::new(),
+    };
+
+    let engine = engine_for_mock(&server);
+    let preview = engine.migrate().preview(&config, None).await.unwrap();
+
+    assert_eq!(preview.notes_to_migrate, 1);
+    assert!(preview.mapping_issues.is_empty());
+
+    let note = &preview.notes[0];
+    assert_eq!(note.before["Front"], "perro");
+    assert_eq!(note.after["Word"], "perro");
+    assert_eq!(note.after["Meaning"], "dog");
+    assert_eq!(note.after["Image"], "");
+    assert_eq!(note.dropped, vec!["Picture"]);
+}
+
+fn restructure_plan() -> Vec<FieldOperation> {
+    vec![
+        FieldOperation::Rename {
+            from: "Back".into(),
+            to: "Meaning".into(),
+        },
+        FieldOperation::Add {
+            name: "Reading".into(),
+            index: Some(1),
+        },
+        FieldOperation::Remove {
+            name: "Extra".into(),
+        },
+    ]
+}
+
+async fn mock_restructure(server: &MockServer, meaning_after: &str) {
+    mock_action(server, "modelNames", mock_anki_response(vec!["Vocab"])).await;
+    mock_sequence(
+        server,
+        "modelFieldNames",
+        vec![
+            mock_anki_response(vec!["Front", "Back", "Extra"]),
+            mock_anki_response(vec!["Front", "Reading", "Meaning"]),
+        ],
+    )
+    .await;
+    mock_action(server, "findNotes", mock_anki_response(vec![1_i64])).await;
+    mock_sequence(
+        server,
+        "notesInfo",
+        vec![
+            mock_anki_response(vec![note(
+                1,
+                "Vocab",
+                &[("Front", "perro"), ("Back", "dog"), ("Extra", "noun")],
+            )]),
+            mock_anki_response(vec![note(
+                1,
+                "Vocab",
+                &[
+                    ("Front", "perro"),
+                    ("Reading", ""),
+                    ("Meaning", meaning_after),
+                ],
+            )]),
+        ],
+    )
+    .await;
+    mock_action_with_params(
+        server,
+        "modelFieldRename",
+        json!({"modelName": "Vocab", "oldFieldName": "Back", "newFieldName": "Meaning"}),
+        mock_anki_response(serde_json::Value::Null),
+    )
+    .await;
+    mock_action_with_params(
+        server,
+        "modelFieldAdd",
+        json!({"modelName": "Vocab", "fieldName": "Reading", "index": 1}),
+        mock_anki_response(serde_json::Value::Null),
+    )
+    .await;
+    mock_action_with_params(
+        server,
+        "modelFieldRemove",
+        json!({"modelName": "Vocab", "fieldName": "Extra"}),
+        mock_anki_response(serde_json::Value::Null),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_restructure_model() {
+    let server = setup_mock_server().await;
+    mock_restructure(&server, "dog").await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .migrate()
+        .restructure_model("Vocab", &restructure_plan())
+        .await
+        .unwrap();
+
+    assert_eq!(report.operations_applied, 3);
+    assert_eq!(report.fields_after, vec!["Front", "Reading", "Meaning"]);
+    assert_eq!(report.notes_checked, 1);
+    assert_eq!(report.values_removed, 1);
+    assert!(report.verified());
+}
+
+#[tokio::test]
+async fn test_restructure_model_reports_lost_content() {
+    let server = setup_mock_server().await;
+    mock_restructure(&server, "").await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .migrate()
+        .restructure_model("Vocab", &restructure_plan())
+        .await
+        .unwrap();
+
+    assert!(!report.verified());
+    assert_eq!(report.mismatches.len(), 1);
+    assert_eq!(report.mismatches[0].field, "Meaning");
+    assert_eq!(report.mismatches[0].expected, "dog");
+    assert_eq!(report.mismatches[0].actual.as_deref(), Some(""));
+}
+
+#[tokio::test]
+async fn test_restructure_model_dry_run() {
+    let server = setup_mock_server().await;
+    mock_action(&server, "modelNames", mock_anki_response(vec!["Vocab"])).await;
+    mock_action(
+        &server,
+        "modelFieldNames",
+        mock_anki_response(vec!["Front", "Back", "Extra"]),
+    )
+    .await;
+    mock_action(&server, "findNotes", mock_anki_response(vec![1_i64])).await;
+    mock_action(
+        &server,
+        "notesInfo",
+        mock_anki_response(vec![note(
+            1,
+            "Vocab",
+            &[("Front", "perro"), ("Back", "dog"), ("Extra", "")],
+        )]),
+    )
+    .await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine
+        .migrate()
+        .restructure_model("Vocab", &restructure_plan())
+        .await
+        .unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.fields_after, vec!["Front", "Reading", "Meaning"]);
+    assert_eq!(report.values_removed, 0);
+    assert_eq!(report.planned.len(), 3);
+    assert!(matches!(
+        &report.planned[0],
+        PlannedChange::RenameField { old, new, .. } if old == "Back" && new == "Meaning"
+    ));
+}
+
+#[tokio::test]
+async fn test_restructure_model_validates_plan() {
+    let server = setup_mock_server().await;
+    mock_action_times(&server, "modelNames", mock_anki_response(vec!["Vocab"]), 2).await;
+    mock_action_times(
+        &server,
+        "modelFieldNames",
+        mock_anki_response(vec!["Front", "Back"]),
+        2,
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let missing = engine
+        .migrate()
+        .restructure_model(
+            "Vocab",
+            &[FieldOperation::Remove {
+                name: "Extra".into(),
+            }],
+        )
+        .await;
+    assert!(matches!(
+        missing,
+        Err(ankit_engine::Error::MissingField { field, .. }) if field == "Extra"
+    ));
+
+    let duplicate = engine
+        .migrate()
+        .restructure_model(
+            "Vocab",
+            &[FieldOperation::Rename {
+                from: "Back".into(),
+                to: "Front".into(),
+            }],
+        )
+        .await;
+    assert!(matches!(duplicate, Err(ankit_engine::Error::Validation(_))));
+}
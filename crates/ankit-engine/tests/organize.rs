@@ -2,8 +2,11 @@
 
 mod common;
 
+use ankit_engine::changes::PlannedChange;
+use ankit_engine::organize::OrphanAction;
 use common::{
-    engine_for_mock, mock_action, mock_action_times, mock_anki_response, setup_mock_server,
+    dry_run_engine_for_mock, engine_for_mock, mock_action, mock_action_times,
+    mock_action_with_params, mock_anki_response, mock_sequence, setup_mock_server,
 };
 
 #[tokio::test]
@@ -139,6 +142,39 @@ async fn test_merge_decks() {
     assert_eq!(report.destination, "Merged Deck");
 }
 
+#[tokio::test]
+async fn test_merge_decks_dry_run() {
+    let server = setup_mock_server().await;
+
+    // Only reads are mocked; createDeck and changeDeck must not be called
+    mock_action_times(
+        &server,
+        "findCards",
+        mock_anki_response(vec![1_i64, 2, 3]),
+        2,
+    )
+    .await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine
+        .organize()
+        .merge_decks(&["Deck A", "Deck B"], "Merged Deck")
+        .await
+        .unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.cards_moved, 6);
+    assert_eq!(report.planned.len(), 3);
+    assert!(matches!(
+        &report.planned[0],
+        PlannedChange::CreateDeck { deck } if deck == "Merged Deck"
+    ));
+    assert!(matches!(
+        &report.planned[1],
+        PlannedChange::MoveCards { card_ids, .. } if card_ids == &[1, 2, 3]
+    ));
+}
+
 #[tokio::test]
 async fn test_move_by_tag() {
     let server = setup_mock_server().await;
@@ -193,3 +229,665 @@ async fn test_move_by_tag_no_matches() {
 
     assert_eq!(count, 0);
 }
+
+#[tokio::test]
+async fn test_merge_deck_trees_keeps_structure() {
+    let server = setup_mock_server().await;
+
+    mock_action(
+        &server,
+        "deckNames",
+        mock_anki_response(vec!["Spanish 1", "Spanish 1::Verbs", "Spanish 2"]),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "findCards",
+        serde_json::json!({"query": "deck:\"Spanish 1\" -deck:\"Spanish 1::*\""}),
+        mock_anki_response(vec![1_i64]),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "findCards",
+        serde_json::json!({"query": "deck:\"Spanish 1::Verbs\" -deck:\"Spanish 1::Verbs::*\""}),
+        mock_anki_response(vec![2_i64, 3]),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "findCards",
+        serde_json::json!({"query": "deck:\"Spanish 2\" -deck:\"Spanish 2::*\""}),
+        mock_anki_response(vec![4_i64]),
+    )
+    .await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine
+        .organize()
+        .merge_deck_trees(&["Spanish 1", "Spanish 2"], "Spanish")
+        .await
+        .unwrap();
+
+    assert_eq!(report.cards_moved, 4);
+    let moves: Vec<(&str, usize)> = report
+        .planned
+        .iter()
+        .filter_map(|change| match change {
+            PlannedChange::MoveCards { card_ids, deck } => Some((deck.as_str(), card_ids.len())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        moves,
+        vec![("Spanish", 1), ("Spanish::Verbs", 2), ("Spanish", 1)]
+    );
+}
+
+#[tokio::test]
+async fn test_move_deck_tree() {
+    let server = setup_mock_server().await;
+
+    mock_action(
+        &server,
+        "deckNames",
+        mock_anki_response(vec!["Japanese", "Japanese::Vocab", "Japanese::Vocab::N5"]),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "findCards",
+        serde_json::json!({"query": "deck:\"Japanese::Vocab\" -deck:\"Japanese::Vocab::*\""}),
+        mock_anki_response(Vec::<i64>::new()),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "findCards",
+        serde_json::json!({"query": "deck:\"Japanese::Vocab::N5\" -deck:\"Japanese::Vocab::N5::*\""}),
+        mock_anki_response(vec![7_i64, 8]),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "findCards",
+        serde_json::json!({"query": "deck:\"Japanese::Vocab\""}),
+        mock_anki_response(Vec::<i64>::new()),
+    )
+    .await;
+    mock_action_times(&server, "createDeck", mock_anki_response(1_i64), 2).await;
+    mock_action(
+        &server,
+        "changeDeck",
+        mock_anki_response(serde_json::Value::Null),
+    )
+    .await;
+    mock_action(
+        &server,
+        "deleteDecks",
+        mock_anki_response(serde_json::Value::Null),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .organize()
+        .move_deck_tree("Japanese::Vocab", Some("Archive"))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        report.renamed,
+        vec![
+            ("Japanese::Vocab".to_string(), "Archive::Vocab".to_string()),
+            (
+                "Japanese::Vocab::N5".to_string(),
+                "Archive::Vocab::N5".to_string()
+            ),
+        ]
+    );
+    assert_eq!(report.cards_moved, 2);
+    assert!(report.source_removed);
+}
+
+#[tokio::test]
+async fn test_move_deck_tree_under_itself() {
+    let server = setup_mock_server().await;
+
+    mock_action(
+        &server,
+        "deckNames",
+        mock_anki_response(vec!["Japanese", "Japanese::Vocab"]),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let result = engine
+        .organize()
+        .move_deck_tree("Japanese", Some("Japanese::Vocab"))
+        .await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_rename_tag_tree() {
+    let server = setup_mock_server().await;
+
+    mock_action(&server, "findNotes", mock_anki_response(vec![1_i64, 2])).await;
+    mock_action(
+        &server,
+        "notesInfo",
+        mock_anki_response(vec![
+            serde_json::json!({"noteId": 1, "modelName": "Basic", "tags": ["lang::jp::verb", "n5"], "fields": {}}),
+            serde_json::json!({"noteId": 2, "modelName": "Basic", "tags": ["Lang::JP"], "fields": {}}),
+        ]),
+    )
+    .await;
+    mock_action_times(
+        &server,
+        "addTags",
+        mock_anki_response(serde_json::Value::Null),
+        2,
+    )
+    .await;
+    mock_action_times(
+        &server,
+        "removeTags",
+        mock_anki_response(serde_json::Value::Null),
+        2,
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .organize()
+        .rename_tag_tree("lang::jp", "japanese")
+        .await
+        .unwrap();
+
+    assert_eq!(report.notes_updated, 2);
+    assert_eq!(
+        report.renamed,
+        vec![
+            ("Lang::JP".to_string(), "japanese".to_string()),
+            ("lang::jp::verb".to_string(), "japanese::verb".to_string()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_rename_tag_tree_under_itself() {
+    let server = setup_mock_server().await;
+    let engine = engine_for_mock(&server);
+
+    let result = engine.organize().rename_tag_tree("lang", "lang::old").await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_tag_tree_to_decks_dry_run() {
+    let server = setup_mock_server().await;
+
+    mock_action(&server, "findNotes", mock_anki_response(vec![1_i64, 2])).await;
+    mock_action(
+        &server,
+        "notesInfo",
+        mock_anki_response(vec![
+            serde_json::json!({"noteId": 1, "modelName": "Basic", "tags": ["lang::jp", "lang::jp::verb"], "fields": {}, "cards": [10, 11]}),
+            serde_json::json!({"noteId": 2, "modelName": "Basic", "tags": ["lang::jp"], "fields": {}, "cards": [20]}),
+        ]),
+    )
+    .await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine
+        .organize()
+        .tag_tree_to_decks("lang::jp", Some("Japanese"))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        report.moved,
+        vec![
+            ("lang::jp".to_string(), "Japanese".to_string(), 1),
+            (
+                "lang::jp::verb".to_string(),
+                "Japanese::verb".to_string(),
+                2
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_deck_tree_to_tags_dry_run() {
+    let server = setup_mock_server().await;
+
+    mock_action(
+        &server,
+        "deckNames",
+        mock_anki_response(vec!["Japanese", "Japanese::Core Vocab"]),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "findNotes",
+        serde_json::json!({"query": "deck:\"Japanese\" -deck:\"Japanese::*\""}),
+        mock_anki_response(Vec::<i64>::new()),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "findNotes",
+        serde_json::json!({"query": "deck:\"Japanese::Core Vocab\" -deck:\"Japanese::Core Vocab::*\""}),
+        mock_anki_response(vec![1_i64, 2]),
+    )
+    .await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine
+        .organize()
+        .deck_tree_to_tags("Japanese", Some("jp"))
+        .await
+        .unwrap();
+
+    assert_eq!(report.notes_tagged, 2);
+    assert!(matches!(
+        &report.planned[0],
+        PlannedChange::AddTags { tags, .. } if tags == "jp::Core_Vocab"
+    ));
+}
+
+fn card_with_fields(
+    card_id: i64,
+    note_id: i64,
+    model: &str,
+    ord: i32,
+    fields: &[(&str, &str)],
+) -> serde_json::Value {
+    let fields: serde_json::Map<String, serde_json::Value> = fields
+        .iter()
+        .enumerate()
+        .map(|(order, (name, value))| {
+            (
+                name.to_string(),
+                serde_json::json!({"value": value, "order": order}),
+            )
+        })
+        .collect();
+    serde_json::json!({
+        "cardId": card_id,
+        "noteId": note_id,
+        "deckName": "Japanese",
+        "modelName": model,
+        "ord": ord,
+        "fields": fields,
+        "type": 0,
+        "queue": 0,
+        "due": 1,
+        "interval": 0,
+        "factor": 0,
+        "reps": 0,
+        "lapses": 0,
+        "left": 0
+    })
+}
+
+async fn mock_empty_cards(server: &wiremock::MockServer) {
+    mock_action(
+        server,
+        "findCards",
+        mock_anki_response(vec![1_i64, 2, 3, 4, 5]),
+    )
+    .await;
+    let optional = [("Front", "perro"), ("Back", "dog"), ("Add Reverse", "")];
+    let reversed = [("Front", "gato"), ("Back", "cat"), ("Add Reverse", "y")];
+    mock_action(
+        server,
+        "cardsInfo",
+        mock_anki_response(vec![
+            card_with_fields(1, 10, "Basic (optional reversed card)", 0, &optional),
+            card_with_fields(2, 10, "Basic (optional reversed card)", 1, &optional),
+            card_with_fields(3, 20, "Basic (optional reversed card)", 1, &reversed),
+            card_with_fields(4, 30, "Cloze", 0, &[("Text", "{{c1::Tokyo}} is big")]),
+            card_with_fields(5, 30, "Cloze", 1, &[("Text", "{{c1::Tokyo}} is big")]),
+        ]),
+    )
+    .await;
+    mock_action(
+        server,
+        "findModelsByName",
+        mock_anki_response(vec![
+            serde_json::json!({
+                "name": "Basic (optional reversed card)",
+                "type": 0,
+                "tmpls": [
+                    {"name": "Card 1", "ord": 0, "qfmt": "{{Front}}", "afmt": "{{Back}}"},
+                    {
+                        "name": "Card 2",
+                        "ord": 1,
+                        "qfmt": "{{#Add Reverse}}{{Back}}{{/Add Reverse}}",
+                        "afmt": "{{Front}}"
+                    }
+                ]
+            }),
+            serde_json::json!({
+                "name": "Cloze",
+                "type": 1,
+                "tmpls": [{"name": "Cloze", "ord": 0, "qfmt": "{{cloze:Text}}", "afmt": ""}]
+            }),
+        ]),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_find_empty_cards() {
+    let server = setup_mock_server().await;
+    mock_empty_cards(&server).await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .organize()
+        .find_empty_cards("Japanese")
+        .await
+        .unwrap();
+
+    assert_eq!(report.cards_checked, 5);
+    let empty: Vec<(i64, &str)> = report
+        .empty_cards
+        .iter()
+        .map(|card| (card.card_id, card.template.as_str()))
+        .collect();
+    assert_eq!(empty, vec![(2, "Card 2"), (5, "Cloze 2")]);
+}
+
+#[tokio::test]
+async fn test_remove_empty_cards_dry_run() {
+    let server = setup_mock_server().await;
+    mock_empty_cards(&server).await;
+    mock_action(
+        &server,
+        "notesInfo",
+        mock_anki_response(vec![
+            serde_json::json!({"noteId": 10, "modelName": "Basic (optional reversed card)",
+                "tags": [], "fields": {}, "cards": [1, 2]}),
+            serde_json::json!({"noteId": 30, "modelName": "Cloze",
+                "tags": [], "fields": {}, "cards": [4, 5]}),
+        ]),
+    )
+    .await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine
+        .organize()
+        .remove_empty_cards("Japanese")
+        .await
+        .unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.notes_deleted, 0);
+    assert_eq!(report.cards_suspended, 2);
+    assert!(matches!(
+        &report.planned[..],
+        [PlannedChange::SuspendCards { card_ids }] if card_ids == &[2, 5]
+    ));
+}
+
+#[tokio::test]
+async fn test_remove_empty_cards_deletes_fully_empty_notes() {
+    let server = setup_mock_server().await;
+    mock_action(&server, "findCards", mock_anki_response(vec![1_i64])).await;
+    mock_action(
+        &server,
+        "cardsInfo",
+        mock_anki_response(vec![card_with_fields(
+            1,
+            10,
+            "Basic",
+            0,
+            &[("Front", ""), ("Back", "dog")],
+        )]),
+    )
+    .await;
+    mock_action(
+        &server,
+        "findModelsByName",
+        mock_anki_response(vec![serde_json::json!({
+            "name": "Basic",
+            "type": 0,
+            "tmpls": [{"name": "Card 1", "ord": 0, "qfmt": "Q: {{Front}}", "afmt": "{{Back}}"}]
+        })]),
+    )
+    .await;
+    mock_action(
+        &server,
+        "notesInfo",
+        mock_anki_response(vec![serde_json::json!({"noteId": 10, "modelName": "Basic",
+            "tags": [], "fields": {}, "cards": [1]})]),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "deleteNotes",
+        serde_json::json!({"notes": [10]}),
+        mock_anki_response(serde_json::Value::Null),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .organize()
+        .remove_empty_cards("Japanese")
+        .await
+        .unwrap();
+
+    assert_eq!(report.notes_deleted, 1);
+    assert_eq!(report.cards_suspended, 0);
+}
+
+async fn mock_orphans(server: &wiremock::MockServer) {
+    mock_action(server, "findNotes", mock_anki_response(vec![10_i64, 20])).await;
+    mock_action(
+        server,
+        "notesInfo",
+        mock_anki_response(vec![
+            serde_json::json!({"noteId": 10, "modelName": "Basic", "tags": ["verbs"],
+                "fields": {"Front": {"value": "comer", "order": 0}}, "cards": []}),
+            serde_json::json!({"noteId": 20, "modelName": "Basic", "tags": [],
+                "fields": {"Front": {"value": "beber", "order": 0}}, "cards": [200]}),
+        ]),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_find_orphaned_notes() {
+    let server = setup_mock_server().await;
+    mock_orphans(&server).await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .organize()
+        .find_orphaned_notes("note:Basic")
+        .await
+        .unwrap();
+
+    assert_eq!(report.notes_checked, 2);
+    assert_eq!(report.orphaned.len(), 1);
+    assert_eq!(report.orphaned[0].note_id, 10);
+    assert_eq!(report.orphaned[0].fields["Front"], "comer");
+}
+
+#[tokio::test]
+async fn test_resolve_orphaned_notes_delete_dry_run() {
+    let server = setup_mock_server().await;
+    mock_orphans(&server).await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine
+        .organize()
+        .resolve_orphaned_notes("note:Basic", &OrphanAction::Delete)
+        .await
+        .unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.notes_deleted, 1);
+    assert!(matches!(
+        &report.planned[..],
+        [PlannedChange::DeleteNotes { note_ids }] if note_ids == &[10]
+    ));
+}
+
+#[tokio::test]
+async fn test_resolve_orphaned_notes_regenerate() {
+    let server = setup_mock_server().await;
+    mock_action(&server, "findNotes", mock_anki_response(vec![10_i64])).await;
+    mock_sequence(
+        &server,
+        "notesInfo",
+        vec![
+            mock_anki_response(vec![serde_json::json!({"noteId": 10, "modelName": "Basic",
+                "tags": ["verbs"], "fields": {"Front": {"value": "comer", "order": 0}},
+                "cards": []})]),
+            mock_anki_response(vec![serde_json::json!({"noteId": 11, "modelName": "Basic",
+                "tags": ["verbs"], "fields": {"Front": {"value": "comer", "order": 0}},
+                "cards": [110]})]),
+        ],
+    )
+    .await;
+    mock_action(&server, "createDeck", mock_anki_response(1_i64)).await;
+    mock_action(&server, "addNote", mock_anki_response(11_i64)).await;
+    mock_action_with_params(
+        &server,
+        "deleteNotes",
+        serde_json::json!({"notes": [10]}),
+        mock_anki_response(serde_json::Value::Null),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let action = OrphanAction::Regenerate {
+        deck: "Recovered".to_string(),
+    };
+    let report = engine
+        .organize()
+        .resolve_orphaned_notes("note:Basic", &action)
+        .await
+        .unwrap();
+
+    assert_eq!(report.notes_regenerated, 1);
+    assert_eq!(report.notes_failed, 0);
+    assert_eq!(report.notes_deleted, 0);
+}
+
+async fn mock_profile_copy(server: &wiremock::MockServer) {
+    mock_action(
+        server,
+        "getProfiles",
+        mock_anki_response(vec!["Personal", "Work"]),
+    )
+    .await;
+    mock_action(server, "getActiveProfile", mock_anki_response("Personal")).await;
+    mock_action(server, "deckNames", mock_anki_response(vec!["Japanese"])).await;
+}
+
+#[tokio::test]
+async fn test_copy_to_profile() {
+    let server = setup_mock_server().await;
+    mock_profile_copy(&server).await;
+    mock_sequence(
+        &server,
+        "findNotes",
+        vec![
+            mock_anki_response(vec![1_i64, 2, 3]),
+            // Target profile before and after the import
+            mock_anki_response(vec![10_i64]),
+            mock_anki_response(vec![10_i64, 11, 12]),
+        ],
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "exportPackage",
+        serde_json::json!({"deck": "Japanese"}),
+        mock_anki_response(true),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "loadProfile",
+        serde_json::json!({"name": "Work"}),
+        mock_anki_response(true),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "loadProfile",
+        serde_json::json!({"name": "Personal"}),
+        mock_anki_response(true),
+    )
+    .await;
+    mock_sequence(
+        &server,
+        "modelNames",
+        vec![
+            mock_anki_response(vec!["Basic"]),
+            mock_anki_response(vec!["Basic", "Japanese Vocab"]),
+        ],
+    )
+    .await;
+    mock_action(&server, "importPackage", mock_anki_response(true)).await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .organize()
+        .copy_to_profile("Japanese", "Work")
+        .await
+        .unwrap();
+
+    assert_eq!(report.source_profile, "Personal");
+    assert_eq!(report.notes_exported, 3);
+    assert_eq!(report.notes_imported, 2);
+    assert_eq!(report.models_created, vec!["Japanese Vocab"]);
+}
+
+#[tokio::test]
+async fn test_copy_to_profile_dry_run() {
+    let server = setup_mock_server().await;
+    mock_profile_copy(&server).await;
+    mock_action(&server, "findNotes", mock_anki_response(vec![1_i64, 2])).await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine
+        .organize()
+        .copy_to_profile("Japanese", "Work")
+        .await
+        .unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.notes_imported, 0);
+    assert!(matches!(
+        &report.planned[..],
+        [PlannedChange::CopyDeckToProfile { notes: 2, .. }]
+    ));
+}
+
+#[tokio::test]
+async fn test_copy_to_profile_rejects_current_profile() {
+    let server = setup_mock_server().await;
+    mock_action(
+        &server,
+        "getProfiles",
+        mock_anki_response(vec!["Personal", "Work"]),
+    )
+    .await;
+    mock_action(&server, "getActiveProfile", mock_anki_response("Work")).await;
+
+    let engine = engine_for_mock(&server);
+    let result = engine.organize().copy_to_profile("Japanese", "Work").await;
+
+    assert!(matches!(result, Err(ankit_engine::Error::Validation(_))));
+}
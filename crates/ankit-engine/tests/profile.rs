@@ -0,0 +1,77 @@
+//! Tests for running workflows in another profile.
+
+mod common;
+
+use ankit_engine::Error;
+use common::{engine_for_mock, mock_action, mock_anki_response, setup_mock_server};
+use wiremock::matchers::{body_partial_json, method};
+use wiremock::{Mock, MockServer};
+
+async fn mock_profiles(server: &MockServer) {
+    mock_action(
+        server,
+        "getProfiles",
+        mock_anki_response(vec!["Personal", "Work"]),
+    )
+    .await;
+    mock_action(server, "getActiveProfile", mock_anki_response("Personal")).await;
+}
+
+async fn expect_load(server: &MockServer, profile: &str, times: u64) {
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({
+            "action": "loadProfile",
+            "params": {"name": profile}
+        })))
+        .respond_with(mock_anki_response(true))
+        .expect(times)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_with_profile_switches_back() {
+    let server = setup_mock_server().await;
+    mock_profiles(&server).await;
+    expect_load(&server, "Work", 1).await;
+    expect_load(&server, "Personal", 1).await;
+    mock_action(&server, "deckNames", mock_anki_response(vec!["Projects"])).await;
+
+    let engine = engine_for_mock(&server);
+    let decks = engine
+        .with_profile("Work")
+        .run(async |engine| Ok(engine.client().decks().names().await?))
+        .await
+        .unwrap();
+
+    assert_eq!(decks, vec!["Projects"]);
+}
+
+#[tokio::test]
+async fn test_with_profile_keeps_profile_on_error() {
+    let server = setup_mock_server().await;
+    mock_profiles(&server).await;
+    expect_load(&server, "Work", 1).await;
+    expect_load(&server, "Personal", 0).await;
+
+    let engine = engine_for_mock(&server);
+    let result: ankit_engine::Result<()> = engine
+        .with_profile("Work")
+        .switch_back(false)
+        .run(async |_| Err(Error::Cancelled))
+        .await;
+
+    assert!(matches!(result, Err(Error::Cancelled)));
+}
+
+#[tokio::test]
+async fn test_with_unknown_profile() {
+    let server = setup_mock_server().await;
+    mock_action(&server, "getProfiles", mock_anki_response(vec!["Personal"])).await;
+    expect_load(&server, "Work", 0).await;
+
+    let engine = engine_for_mock(&server);
+    let result = engine.with_profile("Work").run(async |_| Ok(())).await;
+
+    assert!(matches!(result, Err(Error::ProfileNotFound(name)) if name == "Work"));
+}
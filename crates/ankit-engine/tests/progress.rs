@@ -2,11 +2,16 @@
 
 mod common;
 
+use ankit_engine::changes::PlannedChange;
+use ankit_engine::leech::LeechThresholdSource;
 use ankit_engine::progress::{
-    KeepStrategy, PerformanceCriteria, SimilarityCriteria, SuspendCriteria, TagOperation,
+    CramOptions, FrequencyList, KeepStrategy, PerformanceCriteria, ReviewDecision,
+    SimilarityCriteria, SuspendCriteria, TagOperation,
 };
+use ankit_engine::{Ease, EngineOptions};
 use common::{
-    engine_for_mock, mock_action, mock_action_times, mock_anki_response, setup_mock_server,
+    dry_run_engine_for_mock, engine_for_mock, mock_action, mock_action_times,
+    mock_action_with_params, mock_anki_response, mock_deck_config, setup_mock_server,
 };
 
 #[tokio::test]
@@ -36,6 +41,101 @@ async fn test_reset_deck_with_cards() {
     assert_eq!(report.deck, "Test Deck");
 }
 
+#[tokio::test]
+async fn test_reset_deck_dry_run() {
+    let server = setup_mock_server().await;
+
+    mock_action(&server, "findCards", mock_anki_response(vec![1_i64, 2, 3])).await;
+
+    // forgetCards should NOT be called in dry-run mode
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine.progress().reset_deck("Test Deck").await.unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.cards_reset, 3);
+    assert_eq!(report.planned.len(), 1);
+    assert!(matches!(
+        &report.planned[0],
+        PlannedChange::ForgetCards { card_ids } if card_ids == &[1, 2, 3]
+    ));
+}
+
+#[tokio::test]
+async fn test_bulk_tag_dry_run() {
+    let server = setup_mock_server().await;
+
+    mock_action(&server, "findNotes", mock_anki_response(vec![10_i64, 20])).await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine
+        .progress()
+        .bulk_tag("deck:Test", TagOperation::Remove("old".to_string()))
+        .await
+        .unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.notes_affected, 2);
+    assert!(matches!(
+        &report.planned[0],
+        PlannedChange::RemoveTags { note_ids, tags } if note_ids == &[10, 20] && tags == "old"
+    ));
+}
+
+#[tokio::test]
+async fn test_reset_deck_journal_and_rollback() {
+    let server = setup_mock_server().await;
+    let dir = tempfile::tempdir().unwrap();
+
+    mock_action(&server, "findCards", mock_anki_response(vec![1_i64])).await;
+    mock_action(
+        &server,
+        "cardsInfo",
+        mock_anki_response(vec![serde_json::json!({
+            "cardId": 1_i64,
+            "noteId": 100_i64,
+            "deckName": "Test Deck",
+            "type": 2,
+            "queue": 2,
+            "due": 500,
+            "interval": 30,
+            "factor": 2300,
+            "reps": 12,
+            "lapses": 1,
+            "left": 0
+        })]),
+    )
+    .await;
+    mock_action(
+        &server,
+        "forgetCards",
+        mock_anki_response(serde_json::Value::Null),
+    )
+    .await;
+    mock_action(
+        &server,
+        "setSpecificValueOfCard",
+        mock_anki_response(vec![true; 8]),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server).with_options(EngineOptions {
+        journal_dir: Some(dir.path().to_path_buf()),
+        ..Default::default()
+    });
+    let report = engine.progress().reset_deck("Test Deck").await.unwrap();
+
+    let journal = report.journal.expect("journal should be written");
+    assert!(journal.starts_with(dir.path()));
+
+    let contents = std::fs::read_to_string(&journal).unwrap();
+    assert!(contents.contains("\"ease_factor\": 2300"));
+
+    let rollback = engine.rollback(&journal).await.unwrap();
+    assert_eq!(rollback.cards_rescheduled, 1);
+    assert!(rollback.failures.is_empty());
+}
+
 #[tokio::test]
 async fn test_reset_deck_empty() {
     let server = setup_mock_server().await;
@@ -349,6 +449,8 @@ async fn test_deck_health_report() {
     )
     .await;
 
+    mock_deck_config(&server, 8).await;
+
     let engine = engine_for_mock(&server);
     let report = engine.progress().deck_health("Test").await.unwrap();
 
@@ -363,6 +465,49 @@ async fn test_deck_health_report() {
     assert_eq!(report.total_lapses, 9); // 0+1+8+0
 }
 
+#[tokio::test]
+async fn test_deck_health_fixed_leech_threshold() {
+    let server = setup_mock_server().await;
+
+    mock_action(&server, "findCards", mock_anki_response(vec![1_i64, 2, 3])).await;
+    let card = |card_id: i64, lapses: i64| {
+        serde_json::json!({
+            "cardId": card_id,
+            "noteId": card_id + 100,
+            "deckName": "Test",
+            "modelName": "Basic",
+            "question": "",
+            "answer": "",
+            "fields": {},
+            "type": 2,
+            "queue": 2,
+            "due": 0,
+            "interval": 10,
+            "factor": 2500,
+            "reps": 10,
+            "lapses": lapses,
+            "left": 0,
+            "mod": 0
+        })
+    };
+    mock_action(
+        &server,
+        "cardsInfo",
+        mock_anki_response(vec![card(1, 2), card(2, 3), card(3, 5)]),
+    )
+    .await;
+    // The override skips the deck options lookup
+    mock_action_times(&server, "getDeckConfig", mock_anki_response(false), 0).await;
+
+    let engine = engine_for_mock(&server).with_options(EngineOptions {
+        leech: LeechThresholdSource::Fixed(3),
+        ..Default::default()
+    });
+    let report = engine.progress().deck_health("Test").await.unwrap();
+
+    assert_eq!(report.leech_count, 2);
+}
+
 #[tokio::test]
 async fn test_bulk_tag_add() {
     let server = setup_mock_server().await;
@@ -571,6 +716,7 @@ async fn test_smart_suspend_finds_similar() {
                 field: "Front".to_string(),
                 keep_strategy: KeepStrategy::MostMature,
                 dry_run: false,
+                ..SimilarityCriteria::default()
             },
         )
         .await
@@ -677,6 +823,7 @@ async fn test_smart_suspend_dry_run() {
                 field: "Front".to_string(),
                 keep_strategy: KeepStrategy::MostMature,
                 dry_run: true, // Dry run!
+                ..SimilarityCriteria::default()
             },
         )
         .await
@@ -850,3 +997,786 @@ async fn test_smart_suspend_keep_strategies() {
         .unwrap();
     assert_eq!(report.groups[0].keep, 2);
 }
+
+#[tokio::test]
+async fn test_replay_reviews() {
+    let server = setup_mock_server().await;
+
+    mock_action(
+        &server,
+        "multi",
+        mock_anki_response(vec![
+            serde_json::json!({"result": [true], "error": null}),
+            serde_json::json!({"result": null, "error": "card was not found"}),
+            serde_json::json!({"result": [true], "error": null}),
+        ]),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .progress()
+        .replay_reviews(&[
+            ReviewDecision::new(1, Ease::Good, 3_000),
+            ReviewDecision::new(2, Ease::Again, 1_000),
+            ReviewDecision::new(3, Ease::Easy, 2_000),
+        ])
+        .await
+        .unwrap();
+
+    assert_eq!(report.submitted, 3);
+    assert_eq!(report.answered, 2);
+    // Sorted by timestamp: 2, 3, 1
+    assert_eq!(report.failed, vec![3]);
+}
+
+#[tokio::test]
+async fn test_replay_reviews_dry_run() {
+    let server = setup_mock_server().await;
+
+    // multi should NOT be called in dry-run mode
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine
+        .progress()
+        .replay_reviews(&[
+            ReviewDecision::new(1, Ease::Good, 2_000),
+            ReviewDecision::new(2, Ease::Hard, 1_000),
+        ])
+        .await
+        .unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.answered, 0);
+    let PlannedChange::AnswerCards { answers } = &report.planned[0] else {
+        panic!("expected AnswerCards, got {:?}", report.planned);
+    };
+    let order: Vec<i64> = answers.iter().map(|a| a.card_id).collect();
+    assert_eq!(order, vec![2, 1]);
+}
+
+fn new_vocab_cards() -> wiremock::ResponseTemplate {
+    let card = |id: i64, word: &str, due: i64| {
+        serde_json::json!({
+            "cardId": id,
+            "noteId": id * 10,
+            "deckName": "Spanish",
+            "modelName": "Basic",
+            "fields": {"Front": {"value": word, "order": 0}},
+            "type": 0,
+            "queue": 0,
+            "due": due
+        })
+    };
+    mock_anki_response(vec![
+        card(1, "perro", 10),
+        card(2, "<b>la</b>", 11),
+        card(3, "xilófono", 12),
+        card(4, "casa", 13),
+    ])
+}
+
+#[tokio::test]
+async fn test_prioritize_by_frequency() {
+    let server = setup_mock_server().await;
+
+    mock_action(
+        &server,
+        "findCards",
+        mock_anki_response(vec![1_i64, 2, 3, 4]),
+    )
+    .await;
+    mock_action(&server, "cardsInfo", new_vocab_cards()).await;
+    mock_action(
+        &server,
+        "multi",
+        mock_anki_response(vec![
+            serde_json::json!({"result": [true], "error": null}),
+            serde_json::json!({"result": [true], "error": null}),
+            serde_json::json!({"result": null, "error": "card was not found"}),
+            serde_json::json!({"result": [true], "error": null}),
+        ]),
+    )
+    .await;
+
+    let frequency = FrequencyList::parse("la\t300\ncasa\t120\nperro\t40\n");
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .progress()
+        .prioritize_by_frequency("deck:Spanish", &frequency, "Front")
+        .await
+        .unwrap();
+
+    assert_eq!(report.cards_checked, 4);
+    assert_eq!(report.cards_matched, 3);
+    assert_eq!(report.cards_repositioned, 3);
+    assert_eq!(report.failed.len(), 1);
+}
+
+#[tokio::test]
+async fn test_prioritize_by_frequency_dry_run() {
+    let server = setup_mock_server().await;
+
+    mock_action(
+        &server,
+        "findCards",
+        mock_anki_response(vec![1_i64, 2, 3, 4]),
+    )
+    .await;
+    mock_action(&server, "cardsInfo", new_vocab_cards()).await;
+
+    // multi should NOT be called in dry-run mode
+
+    let frequency = FrequencyList::from_words(["la", "casa", "perro"]);
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine
+        .progress()
+        .prioritize_by_frequency("deck:Spanish", &frequency, "Front")
+        .await
+        .unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.cards_repositioned, 4);
+    let PlannedChange::RepositionCards {
+        card_ids,
+        positions,
+    } = &report.planned[0]
+    else {
+        panic!("expected RepositionCards, got {:?}", report.planned);
+    };
+    // la, casa, perro, then the unlisted xilófono
+    assert_eq!(card_ids, &vec![2, 4, 1, 3]);
+    assert_eq!(positions, &vec![10, 11, 12, 13]);
+}
+
+#[test]
+fn test_frequency_list_formats() {
+    let ranked = FrequencyList::parse("# rank word\n1 the\n2 of\n\n3 and\n");
+    assert_eq!(ranked.len(), 3);
+    assert_eq!(ranked.rank("of"), Some(1));
+
+    // Counts are only used when every line has one
+    let mixed = FrequencyList::parse("rare,5\ncommon\n");
+    assert_eq!(mixed.rank("rare"), Some(0));
+}
+
+#[tokio::test]
+async fn test_bury_cards() {
+    let server = setup_mock_server().await;
+
+    mock_action(
+        &server,
+        "multi",
+        mock_anki_response(vec![
+            serde_json::json!({"result": [true], "error": null}),
+            serde_json::json!({"result": null, "error": "card was not found"}),
+        ]),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine.progress().bury_cards(&[1, 2]).await.unwrap();
+
+    assert_eq!(report.cards_changed, 1);
+    assert_eq!(report.failed, vec![2]);
+}
+
+#[tokio::test]
+async fn test_unbury() {
+    let server = setup_mock_server().await;
+
+    mock_action(&server, "findCards", mock_anki_response(vec![1_i64, 2])).await;
+    mock_action(
+        &server,
+        "cardsInfo",
+        mock_anki_response(vec![
+            serde_json::json!({"cardId": 1, "type": 0, "queue": -2, "due": 3}),
+            serde_json::json!({"cardId": 2, "type": 2, "queue": -3, "due": 900}),
+        ]),
+    )
+    .await;
+    mock_action(
+        &server,
+        "multi",
+        mock_anki_response(vec![
+            serde_json::json!({"result": [true], "error": null}),
+            serde_json::json!({"result": [true], "error": null}),
+        ]),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine.progress().unbury("Japanese").await.unwrap();
+
+    assert_eq!(report.cards_changed, 2);
+    assert!(report.failed.is_empty());
+}
+
+#[tokio::test]
+async fn test_unbury_dry_run() {
+    let server = setup_mock_server().await;
+
+    mock_action(&server, "findCards", mock_anki_response(vec![1_i64, 2])).await;
+
+    // Neither cardsInfo nor multi should be called in dry-run mode
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine.progress().unbury("Japanese").await.unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.cards_changed, 2);
+    assert!(matches!(
+        &report.planned[..],
+        [PlannedChange::UnburyCards { card_ids }] if card_ids == &vec![1, 2]
+    ));
+}
+
+#[tokio::test]
+async fn test_cram_with_review() {
+    let server = setup_mock_server().await;
+
+    mock_action(&server, "createFilteredDeck", mock_anki_response(42_i64)).await;
+    mock_action(&server, "findCards", mock_anki_response(vec![1_i64, 2, 3])).await;
+    mock_action(&server, "guiDeckReview", mock_anki_response(true)).await;
+
+    let engine = engine_for_mock(&server);
+    let options = CramOptions {
+        deck: "Cram tonight".to_string(),
+        limit: 20,
+        start_review: true,
+        ..Default::default()
+    };
+    let report = engine
+        .progress()
+        .cram_with("deck:Japanese is:due", &options)
+        .await
+        .unwrap();
+
+    assert_eq!(report.deck, "Cram tonight");
+    assert_eq!(report.deck_id, Some(42));
+    assert_eq!(report.cards, 3);
+    assert!(report.review_started);
+}
+
+#[tokio::test]
+async fn test_cram_dry_run() {
+    let server = setup_mock_server().await;
+
+    mock_action(&server, "findCards", mock_anki_response(vec![1_i64, 2, 3])).await;
+
+    // createFilteredDeck should NOT be called in dry-run mode
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine.progress().cram("deck:Japanese", 2).await.unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.deck_id, None);
+    assert_eq!(report.cards, 2);
+    assert!(matches!(
+        &report.planned[..],
+        [PlannedChange::CreateFilteredDeck { deck, limit: 2, .. }] if deck == "Cram"
+    ));
+}
+
+#[tokio::test]
+async fn test_cram_zero_limit() {
+    let server = setup_mock_server().await;
+
+    let engine = engine_for_mock(&server);
+    let result = engine.progress().cram("deck:Japanese", 0).await;
+
+    assert!(matches!(result, Err(ankit_engine::Error::Validation(_))));
+}
+
+async fn mock_due_days(server: &wiremock::MockServer, days: &[Vec<i64>]) {
+    let base = "deck:\"Japanese\" is:review -is:learn -is:suspended -is:buried";
+    for (day, card_ids) in days.iter().enumerate() {
+        let filter = if day == 0 {
+            "prop:due<=0".to_string()
+        } else {
+            format!("prop:due={}", day)
+        };
+        mock_action_with_params(
+            server,
+            "findCards",
+            serde_json::json!({"query": format!("{} {}", base, filter)}),
+            mock_anki_response(card_ids.clone()),
+        )
+        .await;
+    }
+}
+
+#[tokio::test]
+async fn test_smooth_due() {
+    let server = setup_mock_server().await;
+    mock_due_days(&server, &[vec![5, 4, 3, 2, 1], vec![6], vec![]]).await;
+    mock_action_with_params(
+        &server,
+        "setDueDate",
+        serde_json::json!({"cards": [3, 4], "days": "1"}),
+        mock_anki_response(true),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "setDueDate",
+        serde_json::json!({"cards": [5, 6], "days": "2"}),
+        mock_anki_response(true),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .progress()
+        .smooth_due("Japanese", 3, 2)
+        .await
+        .unwrap();
+
+    assert_eq!(report.before, vec![5, 1, 0]);
+    assert_eq!(report.after, vec![2, 2, 2]);
+    assert_eq!(report.cards_moved, 4);
+    assert_eq!(report.unplaced, 0);
+    assert!(report.failed.is_empty());
+}
+
+#[tokio::test]
+async fn test_smooth_due_dry_run_with_overflow() {
+    let server = setup_mock_server().await;
+    mock_due_days(&server, &[vec![1, 2, 3], vec![]]).await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine
+        .progress()
+        .smooth_due("Japanese", 2, 1)
+        .await
+        .unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.before, vec![3, 0]);
+    // One card fits nowhere and stays due today
+    assert_eq!(report.after, vec![2, 1]);
+    assert_eq!(report.unplaced, 1);
+    assert_eq!(report.cards_moved, 1);
+    assert_eq!(report.planned.len(), 1);
+    assert!(matches!(
+        &report.planned[0],
+        PlannedChange::SetDueDate { card_ids, days } if card_ids == &[2] && days == "1"
+    ));
+}
+
+#[tokio::test]
+async fn test_smooth_due_rejects_zero_limits() {
+    let server = setup_mock_server().await;
+    let engine = engine_for_mock(&server);
+
+    for (horizon, max) in [(0, 10), (7, 0)] {
+        let result = engine.progress().smooth_due("Japanese", horizon, max).await;
+        assert!(matches!(result, Err(ankit_engine::Error::Validation(_))));
+    }
+}
+
+fn scheduled_cards() -> wiremock::ResponseTemplate {
+    let card = |card_id: i64, card_type: i32, due: i64, interval: i64| {
+        serde_json::json!({
+            "cardId": card_id,
+            "noteId": card_id * 100,
+            "deckName": "Japanese",
+            "type": card_type,
+            "queue": card_type,
+            "due": due,
+            "interval": interval,
+            "factor": 2500,
+            "reps": 5,
+            "lapses": 0,
+            "left": 0
+        })
+    };
+    mock_anki_response(vec![
+        card(1, 2, 500, 30),
+        card(2, 2, 502, 10),
+        card(3, 0, 7, 0),
+    ])
+}
+
+#[tokio::test]
+async fn test_postpone() {
+    let server = setup_mock_server().await;
+    mock_action(&server, "findCards", mock_anki_response(vec![1_i64, 2, 3])).await;
+    mock_action(&server, "cardsInfo", scheduled_cards()).await;
+    mock_action_with_params(
+        &server,
+        "setSpecificValueOfCard",
+        serde_json::json!({"card": 1, "keys": ["due", "ivl"], "newValues": ["514", "44"]}),
+        mock_anki_response(vec![true, true]),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "setSpecificValueOfCard",
+        serde_json::json!({"card": 2, "keys": ["due", "ivl"], "newValues": ["516", "24"]}),
+        mock_anki_response(vec![true, true]),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .progress()
+        .postpone("deck:Japanese", 14, false)
+        .await
+        .unwrap();
+
+    assert_eq!(report.cards_shifted, 2);
+    assert_eq!(report.skipped_new, 1);
+    assert!(report.failed.is_empty());
+}
+
+#[tokio::test]
+async fn test_postpone_dry_run() {
+    let server = setup_mock_server().await;
+    mock_action(&server, "findCards", mock_anki_response(vec![1_i64, 2, 3])).await;
+    mock_action(&server, "cardsInfo", scheduled_cards()).await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine
+        .progress()
+        .postpone("deck:Japanese", 7, true)
+        .await
+        .unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.cards_shifted, 2);
+    assert_eq!(report.planned.len(), 1);
+    assert!(matches!(
+        &report.planned[0],
+        PlannedChange::ShiftDue { card_ids, days: 7, preserve_interval: true } if card_ids == &[1, 2]
+    ));
+}
+
+#[tokio::test]
+async fn test_resume_clamps_to_today() {
+    let server = setup_mock_server().await;
+    mock_action_with_params(
+        &server,
+        "findCards",
+        serde_json::json!({"query": "deck:Japanese"}),
+        mock_anki_response(vec![1_i64, 2, 3]),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "findCards",
+        serde_json::json!({"query": "(deck:Japanese) is:review prop:due<=4"}),
+        mock_anki_response(vec![2_i64]),
+    )
+    .await;
+    mock_action(&server, "cardsInfo", scheduled_cards()).await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine
+        .progress()
+        .resume("deck:Japanese", 4, true)
+        .await
+        .unwrap();
+
+    assert_eq!(report.cards_shifted, 1);
+    assert_eq!(report.cards_due_today, 1);
+    assert_eq!(report.planned.len(), 2);
+    assert!(matches!(
+        &report.planned[0],
+        PlannedChange::ShiftDue { card_ids, days: -4, .. } if card_ids == &[1]
+    ));
+    assert!(matches!(
+        &report.planned[1],
+        PlannedChange::SetDueDate { card_ids, days } if card_ids == &[2] && days == "0"
+    ));
+}
+
+#[tokio::test]
+async fn test_postpone_and_resume_skip_learning() {
+    use ankit_engine::progress::ShiftOptions;
+
+    let server = setup_mock_server().await;
+    mock_action_with_params(
+        &server,
+        "findCards",
+        serde_json::json!({"query": "deck:Japanese"}),
+        mock_anki_response(vec![1_i64, 2]),
+    )
+    .await;
+    // The relearning card also matches is:review, but must stay put
+    mock_action_with_params(
+        &server,
+        "findCards",
+        serde_json::json!({"query": "(deck:Japanese) is:review prop:due<=4"}),
+        mock_anki_response(vec![2_i64]),
+    )
+    .await;
+    mock_action_times(
+        &server,
+        "cardsInfo",
+        mock_anki_response(vec![
+            serde_json::json!({
+                "cardId": 1, "noteId": 100, "deckName": "Japanese",
+                "type": 2, "queue": 2, "due": 500, "interval": 30,
+                "factor": 2500, "reps": 5, "lapses": 0, "left": 0
+            }),
+            serde_json::json!({
+                "cardId": 2, "noteId": 200, "deckName": "Japanese",
+                "type": 3, "queue": 3, "due": 502, "interval": 1,
+                "factor": 2300, "reps": 6, "lapses": 1, "left": 1
+            }),
+        ]),
+        2,
+    )
+    .await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let options = ShiftOptions {
+        preserve_interval: true,
+        skip_learning: true,
+    };
+
+    let postponed = engine
+        .progress()
+        .postpone_with("deck:Japanese", 14, &options)
+        .await
+        .unwrap();
+    let resumed = engine
+        .progress()
+        .resume_with("deck:Japanese", 4, &options)
+        .await
+        .unwrap();
+
+    for report in [&postponed, &resumed] {
+        assert_eq!(report.cards_shifted, 1);
+        assert_eq!(report.cards_due_today, 0);
+        assert_eq!(report.skipped_learning, 1);
+        assert!(matches!(
+            &report.planned[..],
+            [PlannedChange::ShiftDue { card_ids, .. }] if card_ids == &[1]
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_postpone_rejects_zero_days() {
+    let server = setup_mock_server().await;
+    let engine = engine_for_mock(&server);
+
+    let result = engine.progress().postpone("deck:Japanese", 0, true).await;
+    assert!(matches!(result, Err(ankit_engine::Error::Validation(_))));
+}
+
+fn eased_cards() -> wiremock::ResponseTemplate {
+    let card = |card_id: i64, card_type: i32, interval: i64, factor: i64| {
+        serde_json::json!({
+            "cardId": card_id,
+            "noteId": card_id * 100,
+            "deckName": "Japanese",
+            "type": card_type,
+            "queue": card_type,
+            "due": 500,
+            "interval": interval,
+            "factor": factor,
+            "reps": 12,
+            "lapses": 3,
+            "left": 0
+        })
+    };
+    mock_anki_response(vec![
+        card(1, 2, 10, 1300),
+        card(2, 2, 20, 2500),
+        card(3, 0, 0, 0),
+        card(4, 2, 4, 2000),
+    ])
+}
+
+#[tokio::test]
+async fn test_reset_ease() {
+    let server = setup_mock_server().await;
+    mock_action(
+        &server,
+        "findCards",
+        mock_anki_response(vec![1_i64, 2, 3, 4]),
+    )
+    .await;
+    mock_action(&server, "cardsInfo", eased_cards()).await;
+    mock_action_with_params(
+        &server,
+        "setEaseFactors",
+        serde_json::json!({"cards": [1, 4], "easeFactors": [2500, 2500]}),
+        mock_anki_response(vec![true, false]),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine
+        .progress()
+        .reset_ease("deck:Japanese", 2500, false)
+        .await
+        .unwrap();
+
+    assert_eq!(report.cards_checked, 3);
+    assert_eq!(report.already_at_target, 1);
+    assert_eq!(report.cards_reset, 1);
+    assert_eq!(report.intervals_adjusted, 0);
+    assert_eq!(report.failed, vec![4]);
+}
+
+#[tokio::test]
+async fn test_reset_ease_dry_run_scales_intervals() {
+    let server = setup_mock_server().await;
+    mock_action(
+        &server,
+        "findCards",
+        mock_anki_response(vec![1_i64, 2, 3, 4]),
+    )
+    .await;
+    mock_action(&server, "cardsInfo", eased_cards()).await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine
+        .progress()
+        .reset_ease("deck:Japanese", 2500, true)
+        .await
+        .unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.cards_reset, 2);
+    assert_eq!(report.intervals_adjusted, 2);
+    assert_eq!(report.planned.len(), 2);
+    assert!(matches!(
+        &report.planned[0],
+        PlannedChange::SetEase { card_ids, ease_factors }
+            if card_ids == &[1, 4] && ease_factors == &[2500, 2500]
+    ));
+    // 10 days at 130% and 4 days at 200% keep their next interval at 250%
+    assert!(matches!(
+        &report.planned[1],
+        PlannedChange::SetIntervals { card_ids, intervals }
+            if card_ids == &[1, 4] && intervals == &[5, 3]
+    ));
+}
+
+#[tokio::test]
+async fn test_reset_ease_rejects_low_target() {
+    let server = setup_mock_server().await;
+    let engine = engine_for_mock(&server);
+
+    let result = engine
+        .progress()
+        .reset_ease("deck:Japanese", 1000, true)
+        .await;
+    assert!(matches!(result, Err(ankit_engine::Error::Validation(_))));
+}
+
+/// Note 10 has two cards: card 1 (ord 0) active and card 2 (ord 1)
+/// already suspended as a leech.
+fn tagged_cards(first_queue: i32) -> wiremock::ResponseTemplate {
+    mock_anki_response(vec![
+        serde_json::json!({"cardId": 1, "noteId": 10, "ord": 0, "queue": first_queue}),
+        serde_json::json!({"cardId": 2, "noteId": 10, "ord": 1, "queue": -1}),
+    ])
+}
+
+#[tokio::test]
+async fn test_suspend_by_tag_remembers_prior_state() {
+    let server = setup_mock_server().await;
+    mock_action(&server, "findCards", mock_anki_response(vec![1_i64, 2])).await;
+    mock_action(&server, "cardsInfo", tagged_cards(2)).await;
+    mock_action_with_params(
+        &server,
+        "addTags",
+        serde_json::json!({"notes": [10], "tags": "ankit-suspended::exam::ord0"}),
+        mock_anki_response(()),
+    )
+    .await;
+    mock_action_with_params(
+        &server,
+        "suspend",
+        serde_json::json!({"cards": [1]}),
+        mock_anki_response(true),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let report = engine.progress().suspend_by_tag("exam").await.unwrap();
+
+    assert_eq!(report.changed, vec![1]);
+    assert_eq!(report.kept_suspended, vec![2]);
+}
+
+#[tokio::test]
+async fn test_unsuspend_by_tag_restores_prior_state() {
+    let server = setup_mock_server().await;
+    mock_action(&server, "findCards", mock_anki_response(vec![1_i64, 2])).await;
+    mock_action(&server, "cardsInfo", tagged_cards(-1)).await;
+    mock_action(
+        &server,
+        "notesInfo",
+        mock_anki_response(vec![serde_json::json!({
+            "noteId": 10,
+            "modelName": "Basic (and reversed card)",
+            "tags": ["exam", "ankit-suspended::exam::ord0"],
+            "fields": {}
+        })]),
+    )
+    .await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine.progress().unsuspend_by_tag("exam").await.unwrap();
+
+    assert_eq!(report.changed, vec![1]);
+    assert_eq!(report.kept_suspended, vec![2]);
+    assert!(matches!(
+        &report.planned[..],
+        [
+            PlannedChange::UnsuspendCards { card_ids },
+            PlannedChange::RemoveTags { note_ids, tags },
+        ] if card_ids == &vec![1] && note_ids == &vec![10] && tags == "ankit-suspended::exam::ord0"
+    ));
+}
+
+#[tokio::test]
+async fn test_unsuspend_by_tag_keeps_child_tag_markers() {
+    let server = setup_mock_server().await;
+    mock_action(&server, "findCards", mock_anki_response(vec![1_i64, 2])).await;
+    mock_action(
+        &server,
+        "cardsInfo",
+        mock_anki_response(vec![
+            serde_json::json!({"cardId": 1, "noteId": 10, "ord": 0, "queue": -1}),
+            serde_json::json!({"cardId": 2, "noteId": 10, "ord": 1, "queue": -1}),
+        ]),
+    )
+    .await;
+    // Card 1 was suspended by the child tag, card 2 by the parent
+    mock_action(
+        &server,
+        "notesInfo",
+        mock_anki_response(vec![serde_json::json!({
+            "noteId": 10,
+            "modelName": "Basic (and reversed card)",
+            "tags": [
+                "exam::ch1",
+                "ankit-suspended::exam::ch1::ord0",
+                "ankit-suspended::exam::ord1"
+            ],
+            "fields": {}
+        })]),
+    )
+    .await;
+
+    let engine = dry_run_engine_for_mock(&server);
+    let report = engine.progress().unsuspend_by_tag("exam").await.unwrap();
+
+    assert_eq!(report.changed, vec![2]);
+    assert_eq!(report.kept_suspended, vec![1]);
+    assert!(matches!(
+        &report.planned[..],
+        [
+            PlannedChange::UnsuspendCards { card_ids },
+            PlannedChange::RemoveTags { note_ids, tags },
+        ] if card_ids == &vec![2] && note_ids == &vec![10] && tags == "ankit-suspended::exam::ord1"
+    ));
+}
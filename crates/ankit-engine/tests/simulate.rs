@@ -0,0 +1,57 @@
+//! Tests for review simulation.
+
+mod common;
+
+use ankit_engine::simulate::SimulationOptions;
+use common::{
+    engine_for_mock, mock_action, mock_action_with_params, mock_anki_response, setup_mock_server,
+};
+
+#[tokio::test]
+async fn test_simulate_deck() {
+    let server = setup_mock_server().await;
+    let base = "deck:\"Japanese\" -is:suspended";
+    for (query, card_ids) in [
+        (base.to_string(), vec![1_i64, 2, 3]),
+        (
+            format!("{} (is:learn OR (is:review prop:due<=0))", base),
+            vec![2],
+        ),
+        (format!("{} is:review -is:learn prop:due=1", base), vec![3]),
+    ] {
+        mock_action_with_params(
+            &server,
+            "findCards",
+            serde_json::json!({"query": query}),
+            mock_anki_response(card_ids),
+        )
+        .await;
+    }
+    mock_action(
+        &server,
+        "cardsInfo",
+        mock_anki_response(vec![
+            serde_json::json!({"cardId": 1, "type": 0, "queue": 0, "interval": 0, "factor": 0}),
+            serde_json::json!({"cardId": 2, "type": 2, "queue": 2, "interval": 10, "factor": 2500}),
+            serde_json::json!({"cardId": 3, "type": 2, "queue": 2, "interval": 30, "factor": 2500}),
+        ]),
+    )
+    .await;
+
+    let engine = engine_for_mock(&server);
+    let options = SimulationOptions {
+        days: 2,
+        new_per_day: 1,
+        retention: 1.0,
+        ..Default::default()
+    };
+    let report = engine.simulate().deck("Japanese", &options).await.unwrap();
+
+    assert_eq!(report.deck.as_deref(), Some("Japanese"));
+    let reviews: Vec<usize> = report.days.iter().map(|d| d.reviews).collect();
+    // Card 2 today; card 3 and yesterday's new card tomorrow
+    assert_eq!(reviews, vec![1, 2]);
+    assert_eq!(report.days[0].new_cards, 1);
+    assert_eq!(report.days[1].unseen, 0);
+    assert_eq!(report.final_mature, 2);
+}
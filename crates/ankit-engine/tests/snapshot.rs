@@ -0,0 +1,108 @@
+//! Integration tests for note snapshot workflows.
+
+mod common;
+
+use ankit_engine::report::WorkflowReport;
+use common::{engine_for_mock, mock_action, mock_anki_response, setup_mock_server};
+use wiremock::matchers::body_partial_json;
+use wiremock::{Mock, MockServer};
+
+fn note(note_id: i64, back: &str, tags: &[&str]) -> serde_json::Value {
+    serde_json::json!({
+        "noteId": note_id,
+        "modelName": "Basic",
+        "tags": tags,
+        "fields": {
+            "Front": {"value": format!("word {}", note_id), "order": 0},
+            "Back": {"value": back, "order": 1}
+        },
+        "cards": [note_id * 10]
+    })
+}
+
+async fn mock_find(server: &MockServer, query: &str, note_ids: Vec<i64>) {
+    Mock::given(body_partial_json(serde_json::json!({
+        "action": "findNotes",
+        "params": {"query": query}
+    })))
+    .respond_with(mock_anki_response(note_ids))
+    .expect(1)
+    .mount(server)
+    .await;
+}
+
+#[tokio::test]
+async fn test_capture_and_diff_snapshot() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("snapshots/japanese.json");
+
+    let server = setup_mock_server().await;
+    mock_find(&server, "deck:Japanese", vec![2, 1, 3]).await;
+    mock_action(
+        &server,
+        "notesInfo",
+        mock_anki_response(vec![
+            note(1, "neko", &["noun"]),
+            note(2, "taberu", &["verb"]),
+            note(3, "inu", &["noun"]),
+        ]),
+    )
+    .await;
+
+    let report = engine_for_mock(&server)
+        .snapshot()
+        .capture("deck:Japanese", &path)
+        .await
+        .unwrap();
+    assert_eq!(report.notes_captured, 3);
+    assert!(path.exists());
+
+    // Later: note 1's Back and tags changed, note 3 was deleted, note 4 added
+    let server = setup_mock_server().await;
+    mock_find(&server, "nid:1,2,3", vec![1, 2]).await;
+    mock_find(&server, "deck:Japanese", vec![1, 2, 4]).await;
+    mock_action(
+        &server,
+        "notesInfo",
+        mock_anki_response(vec![
+            note(1, "猫", &["noun", "kanji"]),
+            note(2, "taberu", &["verb"]),
+        ]),
+    )
+    .await;
+
+    let diff = engine_for_mock(&server)
+        .snapshot()
+        .diff_snapshot(&path)
+        .await
+        .unwrap();
+    assert_eq!(diff.unchanged, 1);
+    assert_eq!(diff.added, vec![4]);
+    assert_eq!(diff.deleted, vec![3]);
+    assert_eq!(diff.changed.len(), 1);
+    let change = &diff.changed[0];
+    assert_eq!(change.note_id, 1);
+    assert_eq!(change.fields[0].field, "Back");
+    assert_eq!(change.fields[0].before.as_deref(), Some("neko"));
+    assert_eq!(change.fields[0].after.as_deref(), Some("猫"));
+    assert_eq!(change.tags_added, vec!["kanji"]);
+    assert_eq!(diff.affected().note_ids, vec![1, 3, 4]);
+    assert_eq!(
+        diff.summary(),
+        "Since snapshot of 'deck:Japanese': 1 notes changed, 1 added, 1 deleted, 1 unchanged"
+    );
+}
+
+#[tokio::test]
+async fn test_diff_snapshot_rejects_invalid_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("broken.json");
+    std::fs::write(&path, "not json").unwrap();
+
+    let server = setup_mock_server().await;
+    let result = engine_for_mock(&server)
+        .snapshot()
+        .diff_snapshot(&path)
+        .await;
+    assert!(matches!(result, Err(ankit_engine::Error::Snapshot(_))));
+}
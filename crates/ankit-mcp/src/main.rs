@@ -3,17 +3,25 @@
 //! This server exposes ankit-engine workflows and key raw API operations
 //! as tools for LLM assistants like Claude.
 
+mod policy;
+mod prompts;
 mod state;
 mod tools;
 
+use std::path::PathBuf;
 use std::sync::Arc;
 
+use ankit::ClientConfig;
+use ankit_engine::notify::{WebhookFormat, WebhookNotifier};
 use clap::Parser;
-use tower_mcp::{HttpTransport, McpRouter, StdioTransport};
+use tower_mcp::filter::DenialBehavior;
+use tower_mcp::{CapabilityFilter, HttpTransport, McpRouter, StdioTransport, Tool};
 use tracing::info;
 
+use crate::policy::Policy;
+use crate::prompts::all_prompts;
 use crate::state::AnkiState;
-use crate::tools::all_tools;
+use crate::tools::tool_groups;
 
 // ============================================================================
 // CLI Arguments
@@ -24,18 +32,22 @@ use crate::tools::all_tools;
 #[command(name = "ankit-mcp")]
 #[command(version, about, long_about = None)]
 struct Args {
-    /// AnkiConnect host address
-    #[arg(long, default_value = "127.0.0.1")]
-    host: String,
+    /// AnkiConnect host address [default: 127.0.0.1, or from ANKICONNECT_URL]
+    #[arg(long)]
+    host: Option<String>,
 
-    /// AnkiConnect port
-    #[arg(long, default_value_t = 8765)]
-    port: u16,
+    /// AnkiConnect port [default: 8765, or from ANKICONNECT_URL]
+    #[arg(long)]
+    port: Option<u16>,
 
     /// Read-only mode (disables write operations)
     #[arg(long, default_value_t = false)]
     read_only: bool,
 
+    /// Tool permission policy file (TOML or JSON) with allow/deny lists
+    #[arg(long)]
+    policy: Option<PathBuf>,
+
     /// Enable verbose logging (use multiple times for more verbosity)
     #[arg(short, long, action = clap::ArgAction::Count)]
     verbose: u8,
@@ -51,6 +63,14 @@ struct Args {
     /// HTTP server bind address (only used with --transport http)
     #[arg(long, default_value = "127.0.0.1")]
     http_host: String,
+
+    /// Webhook URL to notify when backups, imports, and duplicate removal finish
+    #[arg(long)]
+    notify_webhook: Option<String>,
+
+    /// Webhook payload format: json (default), slack, or discord
+    #[arg(long, default_value = "json")]
+    notify_format: NotifyFormat,
 }
 
 /// Transport mode for the MCP server.
@@ -75,6 +95,26 @@ impl std::str::FromStr for Transport {
     }
 }
 
+/// Payload format for `--notify-webhook`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct NotifyFormat(WebhookFormat);
+
+impl std::str::FromStr for NotifyFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(NotifyFormat(WebhookFormat::Json)),
+            "slack" => Ok(NotifyFormat(WebhookFormat::Slack)),
+            "discord" => Ok(NotifyFormat(WebhookFormat::Discord)),
+            _ => Err(format!(
+                "Invalid notify format: {}. Use 'json', 'slack', or 'discord'",
+                s
+            )),
+        }
+    }
+}
+
 // ============================================================================
 // Main
 // ============================================================================
@@ -96,16 +136,42 @@ async fn main() -> Result<(), tower_mcp::BoxError> {
         .with_writer(std::io::stderr)
         .init();
 
-    let url = format!("http://{}:{}", args.host, args.port);
+    // Settings come from ANKICONNECT_* variables and the config file, with
+    // --host and --port taking precedence
+    let mut config = ClientConfig::load()?;
+    if args.host.is_some() || args.port.is_some() {
+        config.url = Some(format!(
+            "http://{}:{}",
+            args.host.as_deref().unwrap_or("127.0.0.1"),
+            args.port.unwrap_or(8765)
+        ));
+    }
     info!(
-        anki_url = %url,
+        anki_url = config.url.as_deref().unwrap_or("http://127.0.0.1:8765"),
         read_only = args.read_only,
+        policy = ?args.policy,
         transport = ?args.transport,
         "Starting ankit-mcp server"
     );
 
     // Create shared state
-    let state = Arc::new(AnkiState::new(&url, args.read_only));
+    let mut state = AnkiState::new(config);
+    if let Some(webhook) = &args.notify_webhook {
+        state = state.with_notifier(WebhookNotifier::new(webhook).format(args.notify_format.0));
+    }
+    let state = Arc::new(state);
+
+    // Resolve which tools this server exposes
+    let mut policy = match &args.policy {
+        Some(path) => Policy::load(path)?,
+        None => Policy::default(),
+    };
+    if args.read_only {
+        policy = policy.deny_writes();
+    }
+    let groups = tool_groups(state);
+    let allowed = Arc::new(policy.resolve(&groups)?);
+    info!(allowed = allowed.len(), "Resolved tool policy");
 
     // Build instructions text
     let mode = if args.read_only { " (read-only)" } else { "" };
@@ -118,16 +184,31 @@ async fn main() -> Result<(), tower_mcp::BoxError> {
          - Offer to preview changes before applying them (preview_deduplicate, plan_sync_toml)\n\
          - When in doubt, use read operations first to show what would be affected\n\n\
          Key tools: add_note, find_notes, backup_deck, backup_collection, list_decks, \
-         study_summary, find_problems, import_notes, remove_duplicates, and more.",
+         study_summary, find_problems, import_notes, remove_duplicates, and more.\n\n\
+         Prompts offer guided workflows: review_struggling_cards, summarize_deck_health, \
+         and generate_cloze_cards.",
         mode
     );
 
-    // Build router with all tools
-    let tools = all_tools(state);
+    // Build router with all tools and prompts; the policy is enforced by the
+    // tool filter, which hides denied tools and rejects calls to them
+    let tools: Vec<Tool> = groups.into_iter().flat_map(|(_, tools)| tools).collect();
+    let filter = {
+        let allowed = allowed.clone();
+        CapabilityFilter::new(move |_session, tool: &Tool| allowed.contains(&tool.name))
+    }
+    .denial_behavior(DenialBehavior::custom(|name| {
+        tower_mcp::Error::tool(format!(
+            "Tool '{}' is not allowed by the server policy",
+            name
+        ))
+    }));
     let router = McpRouter::new()
         .server_info("ankit-mcp", env!("CARGO_PKG_VERSION"))
         .instructions(instructions)
-        .tools(tools);
+        .tools(tools)
+        .tool_filter(filter)
+        .prompts(all_prompts(&allowed));
 
     // Run on the appropriate transport
     match args.transport {
@@ -0,0 +1,128 @@
+//! Tool permission policy.
+//!
+//! A policy decides which tools the server exposes. It is loaded from a TOML
+//! or JSON file at startup and enforced centrally by the router's tool
+//! filter: denied tools are hidden from `tools/list` and calls to them are
+//! rejected.
+//!
+//! Entries are tool names or groups:
+//!
+//! - `group:<name>` - every tool in a domain group (`notes`, `cards`, `decks`, ...)
+//! - `group:read` - every read-only tool
+//! - `group:write` - every tool that can modify the collection or files
+//! - `*` - every tool
+//!
+//! If `allow` is non-empty, only the tools it lists are available. `deny`
+//! always wins over `allow`.
+//!
+//! # Example
+//!
+//! ```toml
+//! # Allow reading everything and editing notes, but never deleting
+//! allow = ["group:read", "add_note", "update_note", "group:tags"]
+//! deny = ["delete_notes", "delete_deck", "group:deduplicate"]
+//! ```
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde::Deserialize;
+use tower_mcp::Tool;
+
+/// Which tools the server exposes.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Policy {
+    /// Tools or groups to allow. Empty means all tools.
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Tools or groups to deny.
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+impl Policy {
+    /// Load a policy from a `.json` or `.toml` file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read policy '{}': {}", path.display(), e))?;
+
+        let parsed = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&contents).map_err(|e| e.to_string())
+        } else {
+            toml::from_str(&contents).map_err(|e| e.to_string())
+        };
+        parsed.map_err(|e| format!("Invalid policy '{}': {}", path.display(), e))
+    }
+
+    /// Additionally deny every write tool.
+    pub fn deny_writes(mut self) -> Self {
+        self.deny.push("group:write".to_string());
+        self
+    }
+
+    /// Resolve the policy against the server's tools, returning the names of
+    /// the tools that are allowed.
+    ///
+    /// Fails on entries that name no known tool or group, so that a typo
+    /// cannot silently leave a tool enabled.
+    pub fn resolve(&self, groups: &[(&str, Vec<Tool>)]) -> Result<HashSet<String>, String> {
+        let mut allowed = if self.allow.is_empty() {
+            expand("*", groups)?
+        } else {
+            let mut allowed = HashSet::new();
+            for entry in &self.allow {
+                allowed.extend(expand(entry, groups)?);
+            }
+            allowed
+        };
+
+        for entry in &self.deny {
+            for name in expand(entry, groups)? {
+                allowed.remove(&name);
+            }
+        }
+
+        Ok(allowed)
+    }
+}
+
+/// Expand a policy entry into tool names.
+fn expand(entry: &str, groups: &[(&str, Vec<Tool>)]) -> Result<HashSet<String>, String> {
+    let tools = groups.iter().flat_map(|(_, tools)| tools);
+    let names: HashSet<String> = match entry {
+        "*" => tools.map(|t| t.name.clone()).collect(),
+        "group:read" => tools
+            .filter(|t| is_read_only(t))
+            .map(|t| t.name.clone())
+            .collect(),
+        "group:write" => tools
+            .filter(|t| !is_read_only(t))
+            .map(|t| t.name.clone())
+            .collect(),
+        _ => match entry.strip_prefix("group:") {
+            Some(group) => groups
+                .iter()
+                .find(|(name, _)| *name == group)
+                .ok_or_else(|| format!("Unknown tool group in policy: {}", group))?
+                .1
+                .iter()
+                .map(|t| t.name.clone())
+                .collect(),
+            None => tools
+                .filter(|t| t.name == entry)
+                .map(|t| t.name.clone())
+                .collect(),
+        },
+    };
+
+    if names.is_empty() && !entry.starts_with("group:") && entry != "*" {
+        return Err(format!("Unknown tool in policy: {}", entry));
+    }
+    Ok(names)
+}
+
+/// Whether a tool is annotated as read-only.
+fn is_read_only(tool: &Tool) -> bool {
+    tool.annotations.as_ref().is_some_and(|a| a.read_only_hint)
+}
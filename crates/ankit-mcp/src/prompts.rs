@@ -0,0 +1,143 @@
+//! Prompt templates for common study workflows.
+//!
+//! Each prompt expands into a user message that walks the assistant through
+//! a sequence of tool calls with arguments already filled in, so users can
+//! pick a guided workflow instead of composing raw tool calls.
+
+use std::collections::{HashMap, HashSet};
+
+use tower_mcp::error::JsonRpcError;
+use tower_mcp::{Error, GetPromptResult, Prompt, PromptBuilder};
+
+/// Create the prompts whose workflows the tool policy allows.
+///
+/// Each prompt lists the tools its workflow can't do without, and is only
+/// offered when every one of them is in `allowed`.
+pub fn all_prompts(allowed: &HashSet<String>) -> Vec<Prompt> {
+    let prompts: [(Prompt, &[&str]); 3] = [
+        (
+            review_struggling_cards(),
+            &["find_problems", "get_notes_info"],
+        ),
+        (
+            summarize_deck_health(),
+            &[
+                "deck_health_report",
+                "study_summary",
+                "retention_stats",
+                "find_problems",
+            ],
+        ),
+        (
+            generate_cloze_cards(),
+            &["get_model_fields", "validate_notes", "import_notes"],
+        ),
+    ];
+    prompts
+        .into_iter()
+        .filter(|(_, tools)| tools.iter().all(|tool| allowed.contains(*tool)))
+        .map(|(prompt, _)| prompt)
+        .collect()
+}
+
+/// Get a required prompt argument.
+fn required<'a>(args: &'a HashMap<String, String>, name: &str) -> Result<&'a str, Error> {
+    args.get(name)
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| {
+            Error::JsonRpc(JsonRpcError::invalid_params(format!(
+                "Missing required argument: {}",
+                name
+            )))
+        })
+}
+
+/// Walk through finding and dealing with struggling cards in a deck.
+pub fn review_struggling_cards() -> Prompt {
+    PromptBuilder::new("review_struggling_cards")
+        .title("Review struggling cards")
+        .description("Find leeches and low-ease cards in a deck and decide what to do with them.")
+        .required_arg("deck", "Deck name")
+        .optional_arg("min_lapses", "Minimum lapse count to flag (default: 5)")
+        .handler(|args| async move {
+            let deck = required(&args, "deck")?;
+            let min_lapses = args
+                .get("min_lapses")
+                .and_then(|s| s.trim().parse::<i64>().ok())
+                .unwrap_or(5);
+
+            Ok(GetPromptResult::user_message(format!(
+                "Help me deal with the cards I'm struggling with in the deck \"{deck}\".\n\n\
+                 1. Call `find_problems` with query `deck:\"{deck}\"` and min_lapses {min_lapses}.\n\
+                 2. For the worst cards, call `get_notes_info` on their note IDs so you can \
+                 see the content.\n\
+                 3. For each card, suggest one of: rewording the note (`update_note`), \
+                 splitting it into smaller notes, or suspending it (`suspend_cards`).\n\
+                 4. Ask me to confirm before changing or suspending anything, and offer to \
+                 `backup_deck` first.\n\n\
+                 Finish with a short summary of what was changed."
+            )))
+        })
+}
+
+/// Summarize the overall health of a deck.
+pub fn summarize_deck_health() -> Prompt {
+    PromptBuilder::new("summarize_deck_health")
+        .title("Summarize deck health")
+        .description("Summarize review activity, retention, and problem areas for a deck.")
+        .required_arg("deck", "Deck name")
+        .optional_arg(
+            "days",
+            "Number of days of review history to include (default: 30)",
+        )
+        .handler(|args| async move {
+            let deck = required(&args, "deck")?;
+            let days = args
+                .get("days")
+                .and_then(|s| s.trim().parse::<u32>().ok())
+                .unwrap_or(30);
+
+            Ok(GetPromptResult::user_message(format!(
+                "Give me a health check of my deck \"{deck}\".\n\n\
+                 1. Call `deck_health_report` with deck \"{deck}\".\n\
+                 2. Call `study_summary` with deck \"{deck}\" and days {days}.\n\
+                 3. Call `retention_stats` with deck \"{deck}\".\n\
+                 4. Call `find_problems` with query `deck:\"{deck}\"`.\n\n\
+                 Summarize the results in a few short paragraphs: how much I've been \
+                 studying, how well I'm retaining cards, and the main problem areas. \
+                 End with up to three concrete suggestions. Don't change anything."
+            )))
+        })
+}
+
+/// Turn a passage of text into cloze deletion notes.
+pub fn generate_cloze_cards() -> Prompt {
+    PromptBuilder::new("generate_cloze_cards")
+        .title("Generate cloze cards from text")
+        .description("Turn a passage of text into cloze deletion notes and add them to a deck.")
+        .required_arg("text", "Source text to create cards from")
+        .required_arg("deck", "Deck to add the notes to")
+        .optional_arg("model", "Cloze note type to use (default: Cloze)")
+        .handler(|args| async move {
+            let text = required(&args, "text")?;
+            let deck = required(&args, "deck")?;
+            let model = args
+                .get("model")
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .unwrap_or("Cloze");
+
+            Ok(GetPromptResult::user_message(format!(
+                "Create cloze deletion cards from the text below and add them to the deck \
+                 \"{deck}\".\n\n\
+                 1. Pick out the key facts. Write one note per fact, using Anki cloze syntax \
+                 (`{{{{c1::answer}}}}`, adding `c2`, `c3` only for closely related facts).\n\
+                 2. Call `get_model_fields` with model \"{model}\" to check the field names.\n\
+                 3. Show me the proposed notes and wait for my approval.\n\
+                 4. Call `validate_notes`, then `import_notes` with deck \"{deck}\", model \
+                 \"{model}\", and on_duplicate \"skip\".\n\n\
+                 Text:\n\n{text}"
+            )))
+        })
+}
@@ -2,42 +2,85 @@
 
 use std::sync::Arc;
 
-use ankit_engine::Engine;
-use tower_mcp::Error;
+use ankit::ClientConfig;
+use ankit_engine::notify::{Notification, Notifier};
+use ankit_engine::status::{ProgressSink, ProgressUpdate};
+use ankit_engine::{Engine, EngineOptions};
+use tower_mcp::RequestContext;
 use tracing::warn;
 
-/// Shared state containing the Anki engine and configuration.
+/// Shared state containing the Anki engine.
+///
+/// Which tools may run is decided by the server [`Policy`](crate::policy::Policy),
+/// not by the tools themselves.
 #[derive(Clone)]
 pub struct AnkiState {
     /// The Anki engine for API operations.
     pub engine: Arc<Engine>,
-    /// Whether the server is in read-only mode.
-    pub read_only: bool,
+    /// Where to report the results of long-running workflows, if anywhere.
+    pub notifier: Option<Arc<dyn Notifier>>,
 }
 
 impl AnkiState {
-    /// Create a new AnkiState.
-    pub fn new(url: &str, read_only: bool) -> Self {
-        let client = ankit_engine::ClientBuilder::new().url(url).build();
+    /// Create a new AnkiState with a client configured by `config`.
+    pub fn new(config: ClientConfig) -> Self {
+        // Log every AnkiConnect call at debug level (-vv)
+        let client = config
+            .apply(ankit_engine::ClientBuilder::new())
+            .observer(ankit::TracingObserver)
+            .build();
         let engine = Engine::from_client(client);
         Self {
             engine: Arc::new(engine),
-            read_only,
+            notifier: None,
         }
     }
 
-    /// Check if a write operation is allowed.
+    /// Report workflow results to `notifier`.
+    pub fn with_notifier(mut self, notifier: impl Notifier + 'static) -> Self {
+        self.notifier = Some(Arc::new(notifier));
+        self
+    }
+
+    /// An engine for one tool call that reports progress to the client and
+    /// stops at the next batch when the client cancels the request.
+    pub fn engine_for(&self, ctx: &RequestContext) -> Engine {
+        let options = EngineOptions {
+            progress_sink: Some(Arc::new(RequestProgress(ctx.clone()))),
+            ..self.engine.options().clone()
+        };
+        self.engine.as_ref().clone().with_options(options)
+    }
+
+    /// Send a notification in the background, if a notifier is configured.
     ///
-    /// Returns an error if the server is in read-only mode.
-    pub fn check_write(&self, operation: &str) -> Result<(), Error> {
-        if self.read_only {
-            warn!("Blocked write operation in read-only mode: {}", operation);
-            Err(Error::tool(format!(
-                "Write operation '{}' is not allowed in read-only mode",
-                operation
-            )))
-        } else {
-            Ok(())
-        }
+    /// Delivery failures are logged and otherwise ignored so that a slow or
+    /// unreachable webhook never delays or fails a tool call.
+    pub fn notify(&self, notification: Notification) {
+        let Some(notifier) = self.notifier.clone() else {
+            return;
+        };
+        tokio::spawn(async move {
+            if let Err(e) = notifier.notify(&notification).await {
+                warn!(source = %notification.source, error = %e, "Notification failed");
+            }
+        });
+    }
+}
+
+/// Forwards engine progress to MCP progress notifications.
+struct RequestProgress(RequestContext);
+
+impl ProgressSink for RequestProgress {
+    fn update(&self, update: &ProgressUpdate) {
+        self.0.report_progress_sync(
+            update.processed as f64,
+            update.total.map(|total| total as f64),
+            Some(&update.to_string()),
+        );
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.0.is_cancelled()
     }
 }
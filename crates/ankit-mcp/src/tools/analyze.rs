@@ -2,11 +2,12 @@
 
 use std::sync::Arc;
 
-use ankit_engine::analyze::ProblemCriteria;
+use ankit_engine::analyze::{HeatmapOptions, LeechAction, LeechPolicy, ProblemCriteria};
+use ankit_engine::report::WorkflowReport;
 use schemars::JsonSchema;
 use serde::Deserialize;
 use tower_mcp::{CallToolResult, Tool, ToolBuilder};
-use tracing::debug;
+use tracing::{debug, info};
 
 use crate::state::AnkiState;
 
@@ -31,6 +32,45 @@ fn default_min_lapses() -> i64 {
     5
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RemediateLeechesParams {
+    /// Anki search query
+    pub query: String,
+    /// Minimum lapse count to flag (default: 5)
+    #[serde(default = "default_min_lapses")]
+    pub min_lapses: i64,
+    /// Reset leeches to new
+    #[serde(default)]
+    pub reset: bool,
+    /// Lower ease by this amount (e.g., 200 = 20 percentage points)
+    pub reduce_ease: Option<i64>,
+    /// New due date, e.g. "0" (today) or "1-7" (random range)
+    pub reschedule: Option<String>,
+    /// Deck to move leeches into, e.g. "Leeches"
+    pub move_to_deck: Option<String>,
+    /// Tag to add to leech notes
+    pub tag: Option<String>,
+    /// Suspend leeches
+    #[serde(default)]
+    pub suspend: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ReviewHeatmapParams {
+    /// Anki search query selecting cards (e.g., "deck:Japanese")
+    pub query: String,
+    /// Offset of local time from UTC in minutes (e.g., -300 for UTC-5; default: 0)
+    #[serde(default)]
+    pub utc_offset_minutes: i32,
+    /// Local hour at which a new study day starts (default: 4)
+    #[serde(default = "default_rollover_hour")]
+    pub rollover_hour: u8,
+}
+
+fn default_rollover_hour() -> u8 {
+    4
+}
+
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct RetentionStatsParams {
     /// Deck name
@@ -47,16 +87,14 @@ pub fn study_summary(state: Arc<AnkiState>) -> Tool {
             |state: Arc<AnkiState>, params: StudySummaryParams| async move {
                 debug!(deck = %params.deck, days = params.days, "Getting study summary");
 
-                let stats = state
+                let summary = state
                     .engine
                     .analyze()
                     .study_summary(&params.deck, params.days)
                     .await
                     .map_err(|e| tower_mcp::Error::tool(e.to_string()))?;
 
-                Ok(CallToolResult::text(
-                    serde_json::to_string_pretty(&stats).unwrap(),
-                ))
+                Ok(CallToolResult::text(summary.render()))
             },
         )
         .build()
@@ -99,6 +137,89 @@ pub fn find_problems(state: Arc<AnkiState>) -> Tool {
         .expect("valid tool")
 }
 
+/// Find leeches and act on them: reset, reduce ease, reschedule, move, tag, or suspend.
+pub fn remediate_leeches(state: Arc<AnkiState>) -> Tool {
+    ToolBuilder::new("remediate_leeches")
+        .description("Find problem cards (leeches) and act on them. Choose any of: reset, reduce_ease, reschedule, move_to_deck, tag, suspend. Returns the actions taken per card.")
+        .handler_with_state(
+            state,
+            |state: Arc<AnkiState>, params: RemediateLeechesParams| async move {
+                debug!(query = %params.query, min_lapses = params.min_lapses, "Remediating leeches");
+
+                let mut actions = Vec::new();
+                if params.reset {
+                    actions.push(LeechAction::Reset);
+                }
+                if let Some(by) = params.reduce_ease {
+                    actions.push(LeechAction::ReduceEase { by });
+                }
+                if let Some(days) = params.reschedule {
+                    actions.push(LeechAction::Reschedule { days });
+                }
+                if let Some(deck) = params.move_to_deck {
+                    actions.push(LeechAction::MoveToDeck { deck });
+                }
+                if let Some(tag) = params.tag {
+                    actions.push(LeechAction::Tag { tag });
+                }
+                if params.suspend {
+                    actions.push(LeechAction::Suspend);
+                }
+                if actions.is_empty() {
+                    return Err(tower_mcp::Error::tool(
+                        "Choose at least one action: reset, reduce_ease, reschedule, move_to_deck, tag, or suspend",
+                    ));
+                }
+
+                let criteria = ProblemCriteria {
+                    min_lapses: params.min_lapses,
+                    ..Default::default()
+                };
+                let report = state
+                    .engine
+                    .analyze()
+                    .remediate_leeches(&params.query, &LeechPolicy::all(criteria, actions))
+                    .await
+                    .map_err(|e| tower_mcp::Error::tool(e.to_string()))?;
+
+                info!(leeches = report.cards.len(), "Leeches remediated");
+                Ok(CallToolResult::text(report.render()))
+            },
+        )
+        .build()
+        .expect("valid tool")
+}
+
+/// Aggregate review history into per-day counts, streaks, and weekday/hour distributions.
+pub fn review_heatmap(state: Arc<AnkiState>) -> Tool {
+    ToolBuilder::new("review_heatmap")
+        .description("Aggregate review history into per-day review counts, current and longest streaks, and per-weekday and per-hour distributions, for rendering a study heatmap.")
+        .read_only()
+        .handler_with_state(
+            state,
+            |state: Arc<AnkiState>, params: ReviewHeatmapParams| async move {
+                debug!(query = %params.query, utc_offset = params.utc_offset_minutes, "Building review heatmap");
+
+                let options = HeatmapOptions {
+                    utc_offset_minutes: params.utc_offset_minutes,
+                    rollover_hour: params.rollover_hour,
+                };
+                let heatmap = state
+                    .engine
+                    .analyze()
+                    .review_heatmap(&params.query, options)
+                    .await
+                    .map_err(|e| tower_mcp::Error::tool(e.to_string()))?;
+
+                Ok(CallToolResult::text(
+                    serde_json::to_string_pretty(&heatmap).unwrap(),
+                ))
+            },
+        )
+        .build()
+        .expect("valid tool")
+}
+
 /// Get retention statistics for a deck including average ease and retention rate.
 pub fn retention_stats(state: Arc<AnkiState>) -> Tool {
     ToolBuilder::new("retention_stats")
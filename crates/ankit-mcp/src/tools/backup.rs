@@ -7,6 +7,9 @@ use serde::Deserialize;
 use tower_mcp::{CallToolResult, Tool, ToolBuilder};
 use tracing::{debug, info};
 
+use ankit_engine::backup::RestoreFilter;
+use ankit_engine::notify::Notification;
+
 use crate::state::AnkiState;
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -35,6 +38,43 @@ pub struct ListBackupsParams {
     pub backup_dir: String,
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SnapshotCollectionParams {
+    /// Directory to save the snapshot in
+    pub snapshot_dir: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListSnapshotsParams {
+    /// Directory to scan for snapshots
+    pub snapshot_dir: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RestoreSnapshotParams {
+    /// Path to the snapshot directory
+    pub snapshot_path: String,
+    /// Decks to restore, including sub-decks (default: all decks)
+    #[serde(default)]
+    pub decks: Vec<String>,
+    /// Only restore notes with this tag
+    #[serde(default)]
+    pub tag: Option<String>,
+    /// Only restore notes currently matching this Anki search query
+    #[serde(default)]
+    pub query: Option<String>,
+    /// Restore card scheduling (default: true)
+    #[serde(default = "default_true")]
+    pub scheduling: bool,
+    /// Restore deck options groups (default: false)
+    #[serde(default)]
+    pub deck_config: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
 /// Backup a deck to an .apkg file.
 pub fn backup_deck(state: Arc<AnkiState>) -> Tool {
     ToolBuilder::new("backup_deck")
@@ -43,7 +83,6 @@ pub fn backup_deck(state: Arc<AnkiState>) -> Tool {
             state,
             |state: Arc<AnkiState>, params: BackupDeckParams| async move {
                 // Backup is a write operation because it creates files
-                state.check_write("backup_deck")?;
                 debug!(deck = %params.deck, backup_dir = %params.backup_dir, "Backing up deck");
 
                 let result = state
@@ -59,6 +98,7 @@ pub fn backup_deck(state: Arc<AnkiState>) -> Tool {
                     size = result.size_bytes,
                     "Deck backed up"
                 );
+                state.notify(Notification::from_report("backup_deck", &result));
 
                 Ok(CallToolResult::text(format!(
                     "Backed up deck '{}' to {} ({} bytes)",
@@ -79,7 +119,6 @@ pub fn backup_collection(state: Arc<AnkiState>) -> Tool {
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: BackupCollectionParams| async move {
-                state.check_write("backup_collection")?;
                 debug!(backup_dir = %params.backup_dir, "Backing up collection");
 
                 let result = state
@@ -95,6 +134,7 @@ pub fn backup_collection(state: Arc<AnkiState>) -> Tool {
                     dir = %result.backup_dir.display(),
                     "Collection backed up"
                 );
+                state.notify(Notification::from_report("backup_collection", &result));
 
                 let mut msg = format!(
                     "Backed up {} decks to {}",
@@ -123,7 +163,6 @@ pub fn restore_deck(state: Arc<AnkiState>) -> Tool {
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: RestoreDeckParams| async move {
-                state.check_write("restore_deck")?;
                 debug!(backup_path = %params.backup_path, "Restoring deck");
 
                 let result = state
@@ -195,3 +234,109 @@ pub fn list_backups(state: Arc<AnkiState>) -> Tool {
         .build()
         .expect("valid tool")
 }
+
+/// Snapshot every deck in the collection.
+pub fn snapshot_collection(state: Arc<AnkiState>) -> Tool {
+    ToolBuilder::new("snapshot_collection")
+        .description("Snapshot all decks (notes, tags, scheduling, deck options) to a checksummed directory. Unlike .apkg backups, snapshots can be restored selectively by deck, tag, or query.")
+        .handler_with_state(
+            state,
+            |state: Arc<AnkiState>, params: SnapshotCollectionParams| async move {
+                debug!(snapshot_dir = %params.snapshot_dir, "Snapshotting collection");
+
+                let result = state
+                    .engine
+                    .backup()
+                    .snapshot(&params.snapshot_dir)
+                    .await
+                    .map_err(|e| tower_mcp::Error::tool(e.to_string()))?;
+
+                info!(
+                    decks = result.decks,
+                    notes = result.notes,
+                    path = %result.path.display(),
+                    "Collection snapshot taken"
+                );
+
+                Ok(CallToolResult::text(format!(
+                    "Snapshot of {} notes ({} cards) in {} decks saved to {}",
+                    result.notes,
+                    result.cards,
+                    result.decks,
+                    result.path.display()
+                )))
+            },
+        )
+        .build()
+        .expect("valid tool")
+}
+
+/// List collection snapshots in a directory.
+pub fn list_snapshots(state: Arc<AnkiState>) -> Tool {
+    ToolBuilder::new("list_snapshots")
+        .description("List collection snapshots in a directory, newest first.")
+        .read_only()
+        .handler_with_state(
+            state,
+            |state: Arc<AnkiState>, params: ListSnapshotsParams| async move {
+                debug!(snapshot_dir = %params.snapshot_dir, "Listing snapshots");
+
+                let snapshots = state
+                    .engine
+                    .backup()
+                    .list_snapshots(&params.snapshot_dir)
+                    .await
+                    .map_err(|e| tower_mcp::Error::tool(e.to_string()))?;
+
+                info!(count = snapshots.len(), "Listed snapshots");
+
+                if snapshots.is_empty() {
+                    return Ok(CallToolResult::text("No snapshots found"));
+                }
+
+                Ok(CallToolResult::text(
+                    serde_json::to_string_pretty(&snapshots).unwrap(),
+                ))
+            },
+        )
+        .build()
+        .expect("valid tool")
+}
+
+/// Restore notes from a collection snapshot.
+pub fn restore_snapshot(state: Arc<AnkiState>) -> Tool {
+    ToolBuilder::new("restore_snapshot")
+        .description("Restore notes from a collection snapshot, optionally limited to decks, a tag, or a search query. Existing notes get their fields, tags, and scheduling written back; deleted notes are re-created. Checksums are verified first.")
+        .handler_with_state(
+            state,
+            |state: Arc<AnkiState>, params: RestoreSnapshotParams| async move {
+                debug!(snapshot_path = %params.snapshot_path, "Restoring snapshot");
+
+                let filter = RestoreFilter {
+                    decks: params.decks,
+                    tag: params.tag,
+                    query: params.query,
+                    scheduling: params.scheduling,
+                    deck_config: params.deck_config,
+                };
+                let report = state
+                    .engine
+                    .backup()
+                    .restore_snapshot(&params.snapshot_path, &filter)
+                    .await
+                    .map_err(|e| tower_mcp::Error::tool(e.to_string()))?;
+
+                info!(
+                    updated = report.notes_updated,
+                    recreated = report.notes_recreated,
+                    "Snapshot restored"
+                );
+
+                Ok(CallToolResult::text(
+                    serde_json::to_string_pretty(&report).unwrap(),
+                ))
+            },
+        )
+        .build()
+        .expect("valid tool")
+}
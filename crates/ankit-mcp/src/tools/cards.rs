@@ -2,6 +2,7 @@
 
 use std::sync::Arc;
 
+use ankit::Flag;
 use schemars::JsonSchema;
 use serde::Deserialize;
 use tower_mcp::{CallToolResult, Tool, ToolBuilder};
@@ -55,6 +56,14 @@ pub struct SetDueDateParams {
     pub days: String,
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SetFlagParams {
+    /// Card IDs to flag
+    pub card_ids: Vec<i64>,
+    /// Flag number: 1 (red), 2 (orange), 3 (green), 4 (blue), 5 (pink), 6 (turquoise), 7 (purple), or 0 to remove the flag
+    pub flag: u8,
+}
+
 /// Search for cards using Anki query syntax. Returns card IDs.
 pub fn find_cards(state: Arc<AnkiState>) -> Tool {
     ToolBuilder::new("find_cards")
@@ -121,7 +130,6 @@ pub fn suspend_cards(state: Arc<AnkiState>) -> Tool {
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: SuspendCardsParams| async move {
-                state.check_write("suspend_cards")?;
                 debug!(count = params.card_ids.len(), "Suspending cards");
 
                 state
@@ -150,7 +158,6 @@ pub fn unsuspend_cards(state: Arc<AnkiState>) -> Tool {
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: UnsuspendCardsParams| async move {
-                state.check_write("unsuspend_cards")?;
                 debug!(count = params.card_ids.len(), "Unsuspending cards");
 
                 state
@@ -179,7 +186,6 @@ pub fn forget_cards(state: Arc<AnkiState>) -> Tool {
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: ForgetCardsParams| async move {
-                state.check_write("forget_cards")?;
                 debug!(count = params.card_ids.len(), "Forgetting cards");
 
                 state
@@ -208,7 +214,6 @@ pub fn set_ease(state: Arc<AnkiState>) -> Tool {
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: SetEaseParams| async move {
-                state.check_write("set_ease")?;
                 debug!(count = params.card_ids.len(), "Setting ease factors");
 
                 let results = state
@@ -239,7 +244,6 @@ pub fn set_due_date(state: Arc<AnkiState>) -> Tool {
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: SetDueDateParams| async move {
-                state.check_write("set_due_date")?;
                 debug!(count = params.card_ids.len(), days = %params.days, "Setting due date");
 
                 state
@@ -261,3 +265,41 @@ pub fn set_due_date(state: Arc<AnkiState>) -> Tool {
         .build()
         .expect("valid tool")
 }
+
+/// Set or clear the flag on cards.
+pub fn set_flag(state: Arc<AnkiState>) -> Tool {
+    ToolBuilder::new("set_flag")
+        .description("Set a flag on cards: 1 (red), 2 (orange), 3 (green), 4 (blue), 5 (pink), 6 (turquoise), 7 (purple). Use 0 to remove the flag.")
+        .handler_with_state(
+            state,
+            |state: Arc<AnkiState>, params: SetFlagParams| async move {
+                debug!(count = params.card_ids.len(), flag = params.flag, "Setting flag");
+
+                let cards = state.engine.client().cards();
+                let results = match params.flag {
+                    0 => cards.clear_flag(&params.card_ids).await,
+                    number => {
+                        let flag = Flag::from_number(number).ok_or_else(|| {
+                            tower_mcp::Error::tool(format!(
+                                "Invalid flag {}: expected 0-7",
+                                number
+                            ))
+                        })?;
+                        cards.set_flag(&params.card_ids, flag).await
+                    }
+                }
+                .map_err(|e| tower_mcp::Error::tool(e.to_string()))?;
+
+                let success_count = results.iter().filter(|&&r| r).count();
+                info!(success_count, flag = params.flag, "Flag set");
+                Ok(CallToolResult::text(format!(
+                    "Set flag {} on {} of {} cards",
+                    params.flag,
+                    success_count,
+                    params.card_ids.len()
+                )))
+            },
+        )
+        .build()
+        .expect("valid tool")
+}
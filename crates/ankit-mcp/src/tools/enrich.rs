@@ -83,7 +83,6 @@ pub fn enrich_note(state: Arc<AnkiState>) -> Tool {
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: EnrichNoteParams| async move {
-                state.check_write("enrich_note")?;
                 debug!(note_id = params.note_id, "Enriching note");
 
                 state
@@ -113,7 +112,6 @@ pub fn enrich_notes(state: Arc<AnkiState>) -> Tool {
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: EnrichNotesParams| async move {
-                state.check_write("enrich_notes")?;
                 debug!(count = params.updates.len(), "Enriching notes");
 
                 let updates: Vec<(i64, HashMap<String, String>)> = params
@@ -4,7 +4,7 @@ use std::sync::Arc;
 
 use schemars::JsonSchema;
 use serde::Deserialize;
-use tower_mcp::{CallToolResult, Tool, ToolBuilder};
+use tower_mcp::{CallToolResult, RequestContext, Tool, ToolBuilder};
 use tracing::debug;
 
 use crate::state::AnkiState;
@@ -26,13 +26,13 @@ pub fn export_deck(state: Arc<AnkiState>) -> Tool {
     ToolBuilder::new("export_deck")
         .description("Export all notes and cards from a deck as JSON.")
         .read_only()
-        .handler_with_state(
+        .handler_with_state_and_context(
             state,
-            |state: Arc<AnkiState>, params: ExportDeckParams| async move {
+            |state: Arc<AnkiState>, ctx: RequestContext, params: ExportDeckParams| async move {
                 debug!(deck = %params.deck, "Exporting deck");
 
                 let export = state
-                    .engine
+                    .engine_for(&ctx)
                     .export()
                     .deck(&params.deck)
                     .await
@@ -3,10 +3,11 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use ankit_engine::{NoteBuilder, import::OnDuplicate};
+use ankit_engine::notify::Notification;
+use ankit_engine::{NoteBuilder, import::OnDuplicate, rules::ValidationRules};
 use schemars::JsonSchema;
 use serde::Deserialize;
-use tower_mcp::{CallToolResult, Tool, ToolBuilder};
+use tower_mcp::{CallToolResult, RequestContext, Tool, ToolBuilder};
 use tracing::{debug, info};
 
 use crate::state::AnkiState;
@@ -31,6 +32,11 @@ pub struct ImportNotesParams {
     /// How to handle duplicates: "skip", "update", or "allow"
     #[serde(default = "default_on_duplicate")]
     pub on_duplicate: String,
+    /// Field holding a unique key (e.g., "ExternalID"). When set, notes
+    /// with a matching key are updated and the rest created, and
+    /// on_duplicate is ignored.
+    #[serde(default)]
+    pub key_field: Option<String>,
 }
 
 fn default_on_duplicate() -> String {
@@ -41,26 +47,31 @@ fn default_on_duplicate() -> String {
 pub struct ValidateNotesParams {
     /// Notes to validate
     pub notes: Vec<ImportNote>,
+    /// Optional validation rules in TOML: `allowed_tags = [...]` and
+    /// `[fields.<name>]` tables with `required`, `pattern`, `max_length`,
+    /// and `html`
+    #[serde(default)]
+    pub rules: Option<String>,
 }
 
 /// Import multiple notes with duplicate handling.
 pub fn import_notes(state: Arc<AnkiState>) -> Tool {
     ToolBuilder::new("import_notes")
-        .description("Import multiple notes with duplicate handling. on_duplicate can be 'skip', 'update', or 'allow'.")
-        .handler_with_state(
+        .description("Import multiple notes with duplicate handling. on_duplicate can be 'skip', 'update', or 'allow'. Set key_field to update or create notes by a unique key field, so repeated imports are safe.")
+        .handler_with_state_and_context(
             state,
-            |state: Arc<AnkiState>, params: ImportNotesParams| async move {
-                state.check_write("import_notes")?;
+            |state: Arc<AnkiState>, ctx: RequestContext, params: ImportNotesParams| async move {
                 debug!(
                     count = params.notes.len(),
                     on_duplicate = %params.on_duplicate,
                     "Importing notes"
                 );
 
-                let on_duplicate = match params.on_duplicate.as_str() {
-                    "update" => OnDuplicate::Update,
-                    "allow" => OnDuplicate::Allow,
-                    _ => OnDuplicate::Skip,
+                let on_duplicate = match (&params.key_field, params.on_duplicate.as_str()) {
+                    (Some(field), _) => OnDuplicate::UpsertByField(field.clone()),
+                    (None, "update") => OnDuplicate::Update,
+                    (None, "allow") => OnDuplicate::Allow,
+                    (None, _) => OnDuplicate::Skip,
                 };
 
                 let notes: Vec<_> = params
@@ -76,7 +87,7 @@ pub fn import_notes(state: Arc<AnkiState>) -> Tool {
                     .collect();
 
                 let report = state
-                    .engine
+                    .engine_for(&ctx)
                     .import()
                     .notes(&notes, on_duplicate)
                     .await
@@ -86,12 +97,14 @@ pub fn import_notes(state: Arc<AnkiState>) -> Tool {
                     added = report.added,
                     skipped = report.skipped,
                     updated = report.updated,
+                    unchanged = report.unchanged,
                     failed = report.failed,
                     "Import completed"
                 );
+                state.notify(Notification::from_report("import_notes", &report));
                 Ok(CallToolResult::text(format!(
-                    "Import complete: {} added, {} skipped, {} updated, {} failed",
-                    report.added, report.skipped, report.updated, report.failed
+                    "Import complete: {} added, {} skipped, {} updated, {} unchanged, {} failed",
+                    report.added, report.skipped, report.updated, report.unchanged, report.failed
                 )))
             },
         )
@@ -102,7 +115,7 @@ pub fn import_notes(state: Arc<AnkiState>) -> Tool {
 /// Validate notes before importing. Checks if decks and models exist.
 pub fn validate_notes(state: Arc<AnkiState>) -> Tool {
     ToolBuilder::new("validate_notes")
-        .description("Validate notes before importing. Checks if decks and models exist.")
+        .description("Validate notes before importing. Checks if decks and models exist, and optionally checks TOML rules (required fields, patterns, max lengths, plain-text fields, allowed tags).")
         .read_only()
         .handler_with_state(
             state,
@@ -121,12 +134,16 @@ pub fn validate_notes(state: Arc<AnkiState>) -> Tool {
                     })
                     .collect();
 
-                let results = state
-                    .engine
-                    .import()
-                    .validate(&notes)
-                    .await
-                    .map_err(|e| tower_mcp::Error::tool(e.to_string()))?;
+                let import = state.engine.import();
+                let results = match &params.rules {
+                    Some(rules) => {
+                        let rules = ValidationRules::from_toml(rules)
+                            .map_err(|e| tower_mcp::Error::tool(e.to_string()))?;
+                        import.validate_with(&notes, &rules).await
+                    }
+                    None => import.validate(&notes).await,
+                }
+                .map_err(|e| tower_mcp::Error::tool(e.to_string()))?;
 
                 let valid_count = results.iter().filter(|r| r.valid).count();
                 let invalid: Vec<_> = results
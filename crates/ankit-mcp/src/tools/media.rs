@@ -4,7 +4,7 @@ use std::sync::Arc;
 
 use schemars::JsonSchema;
 use serde::Deserialize;
-use tower_mcp::{CallToolResult, Tool, ToolBuilder};
+use tower_mcp::{CallToolResult, RequestContext, Tool, ToolBuilder};
 use tracing::{debug, info};
 
 use crate::state::AnkiState;
@@ -37,20 +37,45 @@ pub fn audit_media(state: Arc<AnkiState>) -> Tool {
         .expect("valid tool")
 }
 
+/// Preview which orphaned media files cleanup would delete.
+///
+/// A read-only counterpart to `cleanup_media`'s dry run, so previews stay
+/// available when the policy denies write tools.
+pub fn preview_media_cleanup(state: Arc<AnkiState>) -> Tool {
+    ToolBuilder::new("preview_media_cleanup")
+        .description(
+            "Count the orphaned media files cleanup_media would delete, without deleting them.",
+        )
+        .read_only()
+        .handler_no_params_with_state(state, |state: Arc<AnkiState>| async move {
+            debug!("Previewing media cleanup");
+
+            let report = state
+                .engine
+                .media()
+                .cleanup_orphaned(true)
+                .await
+                .map_err(|e| tower_mcp::Error::tool(e.to_string()))?;
+
+            Ok(CallToolResult::text(format!(
+                "Would delete {} files",
+                report.files_deleted
+            )))
+        })
+        .expect("valid tool")
+}
+
 /// Clean up orphaned media files. Set dry_run=true to preview without deleting.
 pub fn cleanup_media(state: Arc<AnkiState>) -> Tool {
     ToolBuilder::new("cleanup_media")
         .description("Clean up orphaned media files. Set dry_run=true to preview without deleting.")
-        .handler_with_state(
+        .handler_with_state_and_context(
             state,
-            |state: Arc<AnkiState>, params: CleanupMediaParams| async move {
-                if !params.dry_run {
-                    state.check_write("cleanup_media")?;
-                }
+            |state: Arc<AnkiState>, ctx: RequestContext, params: CleanupMediaParams| async move {
                 debug!(dry_run = params.dry_run, "Cleaning up media");
 
                 let report = state
-                    .engine
+                    .engine_for(&ctx)
                     .media()
                     .cleanup_orphaned(params.dry_run)
                     .await
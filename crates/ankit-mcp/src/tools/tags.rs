@@ -33,6 +33,28 @@ pub struct ReplaceTagsAllParams {
     pub new_tag: String,
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RenameTagTreeParams {
+    /// Tag to rename, along with every tag below it (e.g., "lang::jp")
+    pub from: String,
+    /// New name for the tag (e.g., "japanese")
+    pub to: String,
+    /// Only retag notes matching this Anki search query (default: all notes)
+    #[serde(default)]
+    pub query: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct TagStatsParams {
+    /// Anki search query selecting notes (default: "deck:*")
+    #[serde(default = "default_all_query")]
+    pub query: String,
+}
+
+fn default_all_query() -> String {
+    "deck:*".to_string()
+}
+
 /// Add tags to notes. Tags are space-separated.
 pub fn add_tags(state: Arc<AnkiState>) -> Tool {
     ToolBuilder::new("add_tags")
@@ -40,7 +62,6 @@ pub fn add_tags(state: Arc<AnkiState>) -> Tool {
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: AddTagsParams| async move {
-                state.check_write("add_tags")?;
                 debug!(count = params.note_ids.len(), tags = %params.tags, "Adding tags");
 
                 state
@@ -70,7 +91,6 @@ pub fn remove_tags(state: Arc<AnkiState>) -> Tool {
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: RemoveTagsParams| async move {
-                state.check_write("remove_tags")?;
                 debug!(count = params.note_ids.len(), tags = %params.tags, "Removing tags");
 
                 state
@@ -100,7 +120,6 @@ pub fn replace_tags_all(state: Arc<AnkiState>) -> Tool {
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: ReplaceTagsAllParams| async move {
-                state.check_write("replace_tags_all")?;
                 debug!(old = %params.old_tag, new = %params.new_tag, "Replacing tag globally");
 
                 state
@@ -127,7 +146,6 @@ pub fn clear_unused_tags(state: Arc<AnkiState>) -> Tool {
     ToolBuilder::new("clear_unused_tags")
         .description("Remove all tags that are not used by any notes.")
         .handler_no_params_with_state(state, |state: Arc<AnkiState>| async move {
-            state.check_write("clear_unused_tags")?;
             debug!("Clearing unused tags");
 
             state
@@ -143,3 +161,71 @@ pub fn clear_unused_tags(state: Arc<AnkiState>) -> Tool {
         })
         .expect("valid tool")
 }
+
+/// Rename a tag branch, or move matching notes to another branch.
+pub fn rename_tag_tree(state: Arc<AnkiState>) -> Tool {
+    ToolBuilder::new("rename_tag_tree")
+        .description("Rename a hierarchical tag and every tag below it (e.g., 'lang::jp' -> 'japanese' turns 'lang::jp::verb' into 'japanese::verb'). With a query, only matching notes are moved to the new branch.")
+        .handler_with_state(
+            state,
+            |state: Arc<AnkiState>, params: RenameTagTreeParams| async move {
+                debug!(from = %params.from, to = %params.to, query = ?params.query, "Renaming tag tree");
+
+                let organize = state.engine.organize();
+                let report = match &params.query {
+                    Some(query) => {
+                        organize
+                            .move_tag_branch(query, &params.from, &params.to)
+                            .await
+                    }
+                    None => organize.rename_tag_tree(&params.from, &params.to).await,
+                }
+                .map_err(|e| tower_mcp::Error::tool(e.to_string()))?;
+
+                info!(
+                    notes_updated = report.notes_updated,
+                    tags = report.renamed.len(),
+                    "Tag tree renamed"
+                );
+
+                let renamed: Vec<String> = report
+                    .renamed
+                    .iter()
+                    .map(|(old, new)| format!("{} -> {}", old, new))
+                    .collect();
+                Ok(CallToolResult::text(format!(
+                    "Retagged {} notes:\n{}",
+                    report.notes_updated,
+                    renamed.join("\n")
+                )))
+            },
+        )
+        .build()
+        .expect("valid tool")
+}
+
+/// Report note counts over the tag hierarchy.
+pub fn tag_stats(state: Arc<AnkiState>) -> Tool {
+    ToolBuilder::new("tag_stats")
+        .description("Report how notes are spread over the tag hierarchy: notes per tag, notes per branch including child tags, and untagged notes.")
+        .read_only()
+        .handler_with_state(
+            state,
+            |state: Arc<AnkiState>, params: TagStatsParams| async move {
+                debug!(query = %params.query, "Getting tag stats");
+
+                let stats = state
+                    .engine
+                    .analyze()
+                    .tag_stats(&params.query)
+                    .await
+                    .map_err(|e| tower_mcp::Error::tool(e.to_string()))?;
+
+                Ok(CallToolResult::text(
+                    serde_json::to_string_pretty(&stats).unwrap(),
+                ))
+            },
+        )
+        .build()
+        .expect("valid tool")
+}
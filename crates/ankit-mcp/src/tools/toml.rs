@@ -208,7 +208,6 @@ pub fn sync_deck_toml(state: Arc<AnkiState>) -> Tool {
         .handler_with_state(
             state,
             |state: Arc<AnkiState>, params: SyncDeckTomlParams| async move {
-                state.check_write("sync_deck_toml")?;
                 debug!(strategy = %params.strategy, "Syncing TOML with Anki");
 
                 let toml_content = resolve_toml_content(params.toml_content, params.toml_path)?;
@@ -226,9 +225,7 @@ pub fn sync_deck_toml(state: Arc<AnkiState>) -> Tool {
                     "pull_only" => ankit_builder::SyncStrategy::pull_only(),
                     "bidirectional" => ankit_builder::SyncStrategy {
                         conflict_resolution,
-                        pull_new_notes: true,
-                        push_new_notes: true,
-                        update_tags: true,
+                        ..ankit_builder::SyncStrategy::bidirectional()
                     },
                     _ => ankit_builder::SyncStrategy::push_only(),
                 };
@@ -277,8 +274,7 @@ pub fn import_deck_toml(state: Arc<AnkiState>) -> Tool {
         .description("Import a TOML deck definition into Anki. Creates decks and adds notes.")
         .handler_with_state(
             state,
-            |state: Arc<AnkiState>, params: ImportDeckTomlParams| async move {
-                state.check_write("import_deck_toml")?;
+            |_state: Arc<AnkiState>, params: ImportDeckTomlParams| async move {
                 debug!("Importing TOML to Anki");
 
                 let toml_content = resolve_toml_content(params.toml_content, params.toml_path)?;
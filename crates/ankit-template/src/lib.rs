@@ -0,0 +1,478 @@
+//! Parsing of Anki card template syntax.
+//!
+//! Card templates mix HTML with `{{...}}` tags. This crate parses them into
+//! a tree of [`Node`]s, each carrying the byte range it was parsed from, so
+//! renderers, linters, and editors can share one reading of the syntax:
+//!
+//! - Field replacements: `{{Front}}`
+//! - Conditionals: `{{#Field}}...{{/Field}}` and `{{^Field}}...{{/Field}}`
+//! - Filters, applied from the one nearest the field outwards:
+//!   `{{cloze:Text}}`, `{{hint:Field}}`, `{{type:Field}}`,
+//!   `{{furigana:text:Field}}`
+//!
+//! [`parse`] builds the tree and fails on the first structural error, as
+//! Anki does. [`tags`] lists the `{{...}}` tags without checking that
+//! sections are balanced, for tools that want to keep going past mistakes.
+//!
+//! It has no dependencies, so offline tools can lint templates without the
+//! AnkiConnect client. The client re-exports it as `ankit::template`.
+//!
+//! # Example
+//!
+//! ```
+//! use ankit_template::{Filter, Node, parse};
+//!
+//! let template = "{{#Hint}}{{hint:Hint}}{{/Hint}} {{cloze:Text}}";
+//! let nodes = parse(template).unwrap();
+//!
+//! let Node::Conditional { field, children, span, .. } = &nodes[0] else {
+//!     unreachable!()
+//! };
+//! assert_eq!(field, "Hint");
+//! assert_eq!(&template[span.clone()], "{{#Hint}}{{hint:Hint}}{{/Hint}}");
+//! assert!(matches!(
+//!     &children[0],
+//!     Node::Replacement { filters, .. } if filters == &[Filter::Hint]
+//! ));
+//!
+//! let error = parse("{{#Front}}{{Front}}").unwrap_err();
+//! assert_eq!(error.to_string(), "Missing '{{/Front}}' for '{{#Front}}'");
+//! assert_eq!(error.span, 0..10);
+//! ```
+
+use std::fmt;
+use std::ops::Range;
+
+/// Fields Anki provides to every template, in addition to the note's own.
+pub const SPECIAL_FIELDS: &[&str] = &[
+    "FrontSide",
+    "Tags",
+    "Type",
+    "Deck",
+    "Subdeck",
+    "Card",
+    "CardFlag",
+    "CardID",
+];
+
+/// A filter applied to a field replacement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    /// `text`: strip HTML.
+    Text,
+    /// `cloze`: show the card's cloze deletions.
+    Cloze,
+    /// `cloze-only`: only the text of the card's cloze deletions.
+    ClozeOnly,
+    /// `hint`: hide the field behind a link.
+    Hint,
+    /// `type`: a type-in-the-answer box.
+    Type,
+    /// `furigana`: ruby text from `漢字[かんじ]` readings.
+    Furigana,
+    /// `kana`: only the readings.
+    Kana,
+    /// `kanji`: only the base text.
+    Kanji,
+    /// Any other filter, such as an add-on's or `tts`.
+    Other(String),
+}
+
+impl Filter {
+    /// The filter for a name as written in a template.
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "text" => Filter::Text,
+            "cloze" => Filter::Cloze,
+            "cloze-only" => Filter::ClozeOnly,
+            "hint" => Filter::Hint,
+            "type" => Filter::Type,
+            "furigana" => Filter::Furigana,
+            "kana" => Filter::Kana,
+            "kanji" => Filter::Kanji,
+            other => Filter::Other(other.to_string()),
+        }
+    }
+
+    /// The filter's name as written in a template.
+    pub fn name(&self) -> &str {
+        match self {
+            Filter::Text => "text",
+            Filter::Cloze => "cloze",
+            Filter::ClozeOnly => "cloze-only",
+            Filter::Hint => "hint",
+            Filter::Type => "type",
+            Filter::Furigana => "furigana",
+            Filter::Kana => "kana",
+            Filter::Kanji => "kanji",
+            Filter::Other(name) => name,
+        }
+    }
+}
+
+/// A node of a parsed template.
+///
+/// Spans are byte ranges into the template string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node {
+    /// Literal text and HTML between tags.
+    Text {
+        /// The text.
+        text: String,
+        /// Where the text appears.
+        span: Range<usize>,
+    },
+    /// A `{{filter:...:Field}}` replacement.
+    Replacement {
+        /// The field name. Empty for Anki's literal `{{}}`.
+        field: String,
+        /// Filters in the order written, outermost first.
+        filters: Vec<Filter>,
+        /// The whole tag.
+        span: Range<usize>,
+    },
+    /// A `{{#Field}}` or `{{^Field}}` section.
+    Conditional {
+        /// The field tested.
+        field: String,
+        /// Whether the section shows when the field is empty (`^`).
+        negated: bool,
+        /// Nodes inside the section.
+        children: Vec<Node>,
+        /// From the opening tag through the closing tag.
+        span: Range<usize>,
+    },
+}
+
+impl Node {
+    /// Where the node appears in the template.
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            Node::Text { span, .. }
+            | Node::Replacement { span, .. }
+            | Node::Conditional { span, .. } => span.clone(),
+        }
+    }
+}
+
+/// What kind of `{{...}}` tag a [`Tag`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagKind {
+    /// A field replacement.
+    Replacement,
+    /// `{{#Field}}`, or `{{^Field}}` when `negated`.
+    Open {
+        /// Whether this is a `^` section.
+        negated: bool,
+    },
+    /// `{{/Field}}`.
+    Close,
+}
+
+/// A single `{{...}}` tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tag {
+    /// What kind of tag this is.
+    pub kind: TagKind,
+    /// The field name, without filters or section markers.
+    pub field: String,
+    /// Filters in the order written. Always empty for section tags.
+    pub filters: Vec<Filter>,
+    /// The whole tag, including braces.
+    pub span: Range<usize>,
+}
+
+impl Tag {
+    /// Whether the tag applies the given filter.
+    pub fn has_filter(&self, filter: &Filter) -> bool {
+        self.filters.contains(filter)
+    }
+}
+
+/// A structural error in a template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateError {
+    /// What went wrong.
+    pub kind: TemplateErrorKind,
+    /// The tag or text the error points at.
+    pub span: Range<usize>,
+}
+
+/// The kind of a [`TemplateError`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateErrorKind {
+    /// A `{{` without a matching `}}`. Holds the rest of the template.
+    UnclosedTag(String),
+    /// A `{{/Field}}` that closes a different section than the one open.
+    MismatchedClose {
+        /// The field in the closing tag.
+        found: String,
+        /// The field of the open section.
+        expected: String,
+    },
+    /// A `{{/Field}}` with no section open.
+    UnopenedClose(String),
+    /// A section that is never closed.
+    UnclosedSection {
+        /// The field of the section.
+        field: String,
+        /// Whether this is a `^` section.
+        negated: bool,
+    },
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            TemplateErrorKind::UnclosedTag(rest) => write!(f, "Unclosed tag: '{}'", rest),
+            TemplateErrorKind::MismatchedClose { found, expected } => write!(
+                f,
+                "Found '{{{{/{}}}}}', but expected '{{{{/{}}}}}'",
+                found, expected
+            ),
+            TemplateErrorKind::UnopenedClose(found) => {
+                write!(f, "Found '{{{{/{}}}}}', but it was not opened", found)
+            }
+            TemplateErrorKind::UnclosedSection { field, negated } => write!(
+                f,
+                "Missing '{{{{/{}}}}}' for '{{{{{}{}}}}}'",
+                field,
+                if *negated { '^' } else { '#' },
+                field
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// List the `{{...}}` tags in a template.
+///
+/// Unlike [`parse`], this does not check that sections are balanced. It stops
+/// at a `{{` with no closing `}}`.
+pub fn tags(template: &str) -> Vec<Tag> {
+    Scanner::new(template)
+        .map_while(|token| match token {
+            Token::Tag(tag) => Some(Some(tag)),
+            Token::Text(_) => Some(None),
+            Token::Unclosed(_) => None,
+        })
+        .flatten()
+        .collect()
+}
+
+/// Parse a template into a tree of nodes.
+pub fn parse(template: &str) -> Result<Vec<Node>, TemplateError> {
+    // Stack of open sections: (opening tag, nodes before the section)
+    let mut stack: Vec<(Tag, Vec<Node>)> = Vec::new();
+    let mut nodes = Vec::new();
+
+    for token in Scanner::new(template) {
+        match token {
+            Token::Text(span) => nodes.push(Node::Text {
+                text: template[span.clone()].to_string(),
+                span,
+            }),
+            Token::Unclosed(start) => {
+                return Err(TemplateError {
+                    kind: TemplateErrorKind::UnclosedTag(template[start..].to_string()),
+                    span: start..template.len(),
+                });
+            }
+            Token::Tag(tag) => match tag.kind {
+                TagKind::Replacement => nodes.push(Node::Replacement {
+                    field: tag.field,
+                    filters: tag.filters,
+                    span: tag.span,
+                }),
+                TagKind::Open { .. } => stack.push((tag, std::mem::take(&mut nodes))),
+                TagKind::Close => match stack.pop() {
+                    Some((open, parent)) if open.field == tag.field => {
+                        let children = std::mem::replace(&mut nodes, parent);
+                        nodes.push(Node::Conditional {
+                            field: open.field,
+                            negated: open.kind == TagKind::Open { negated: true },
+                            children,
+                            span: open.span.start..tag.span.end,
+                        });
+                    }
+                    Some((open, _)) => {
+                        return Err(TemplateError {
+                            kind: TemplateErrorKind::MismatchedClose {
+                                found: tag.field,
+                                expected: open.field,
+                            },
+                            span: tag.span,
+                        });
+                    }
+                    None => {
+                        return Err(TemplateError {
+                            kind: TemplateErrorKind::UnopenedClose(tag.field),
+                            span: tag.span,
+                        });
+                    }
+                },
+            },
+        }
+    }
+
+    if let Some((open, _)) = stack.pop() {
+        return Err(TemplateError {
+            kind: TemplateErrorKind::UnclosedSection {
+                field: open.field,
+                negated: open.kind == TagKind::Open { negated: true },
+            },
+            span: open.span,
+        });
+    }
+    Ok(nodes)
+}
+
+enum Token {
+    Text(Range<usize>),
+    Tag(Tag),
+    /// A `{{` at this offset that is never closed.
+    Unclosed(usize),
+}
+
+/// Splits a template into text and tags.
+struct Scanner<'a> {
+    template: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(template: &'a str) -> Self {
+        Self { template, pos: 0 }
+    }
+}
+
+impl Iterator for Scanner<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        let rest = &self.template[self.pos..];
+        if rest.is_empty() {
+            return None;
+        }
+        let start = self.pos;
+        let Some(offset) = rest.find("{{") else {
+            self.pos = self.template.len();
+            return Some(Token::Text(start..self.pos));
+        };
+        if offset > 0 {
+            self.pos += offset;
+            return Some(Token::Text(start..self.pos));
+        }
+        let Some(len) = rest[2..].find("}}") else {
+            self.pos = self.template.len();
+            return Some(Token::Unclosed(start));
+        };
+        self.pos += len + 4;
+
+        let content = rest[2..2 + len].trim();
+        let (kind, body) = if let Some(body) = content.strip_prefix('#') {
+            (TagKind::Open { negated: false }, body)
+        } else if let Some(body) = content.strip_prefix('^') {
+            (TagKind::Open { negated: true }, body)
+        } else if let Some(body) = content.strip_prefix('/') {
+            (TagKind::Close, body)
+        } else {
+            (TagKind::Replacement, content)
+        };
+
+        let (field, filters) = if kind == TagKind::Replacement {
+            let mut parts: Vec<&str> = body.split(':').map(str::trim).collect();
+            let field = parts.pop().unwrap_or_default().to_string();
+            (field, parts.into_iter().map(Filter::from_name).collect())
+        } else {
+            (body.trim().to_string(), Vec::new())
+        };
+
+        Some(Token::Tag(Tag {
+            kind,
+            field,
+            filters,
+            span: start..self.pos,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_spans() {
+        let template = "Q: {{text:Front}}{{^Back}}-{{/Back}}";
+        let nodes = parse(template).unwrap();
+
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(&template[nodes[0].span()], "Q: ");
+        assert_eq!(
+            nodes[1],
+            Node::Replacement {
+                field: "Front".to_string(),
+                filters: vec![Filter::Text],
+                span: 3..17,
+            }
+        );
+        let Node::Conditional {
+            field,
+            negated,
+            children,
+            span,
+        } = &nodes[2]
+        else {
+            panic!("expected a conditional");
+        };
+        assert_eq!(field, "Back");
+        assert!(negated);
+        assert_eq!(&template[span.clone()], "{{^Back}}-{{/Back}}");
+        assert_eq!(&template[children[0].span()], "-");
+    }
+
+    #[test]
+    fn test_filters_in_written_order() {
+        let tags = tags("{{ furigana : text : Reading }}{{type:cloze:Text}}{{tts en_US:Front}}");
+        assert_eq!(tags[0].field, "Reading");
+        assert_eq!(tags[0].filters, vec![Filter::Furigana, Filter::Text]);
+        assert_eq!(tags[1].filters, vec![Filter::Type, Filter::Cloze]);
+        assert!(tags[1].has_filter(&Filter::Cloze));
+        assert_eq!(
+            tags[2].filters,
+            vec![Filter::Other("tts en_US".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_tags_ignore_balance() {
+        let tags = tags("{{/A}}{{#B}}{{C}}{{D");
+        let kinds: Vec<TagKind> = tags.iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TagKind::Close,
+                TagKind::Open { negated: false },
+                TagKind::Replacement
+            ]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = parse("{{#A}}x{{/B}}").unwrap_err();
+        assert_eq!(error.to_string(), "Found '{{/B}}', but expected '{{/A}}'");
+        assert_eq!(error.span, 7..13);
+
+        let error = parse("x{{/A}}").unwrap_err();
+        assert_eq!(
+            error.kind,
+            TemplateErrorKind::UnopenedClose("A".to_string())
+        );
+
+        let error = parse("ok {{Front").unwrap_err();
+        assert_eq!(error.to_string(), "Unclosed tag: '{{Front'");
+        assert_eq!(error.span, 3..10);
+    }
+}
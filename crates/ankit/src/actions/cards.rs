@@ -26,11 +26,18 @@
 //! # }
 //! ```
 
+use std::collections::HashMap;
+
 use serde::Serialize;
 
+use crate::actions::MultiAction;
 use crate::client::AnkiClient;
 use crate::error::Result;
-use crate::types::{CardAnswer, CardInfo, CardModTime};
+use crate::types::{CardAnswer, CardInfo, CardModTime, DueSpec, Flag};
+
+/// Maximum number of answers sent in one `multi` request by
+/// [`CardActions::answer_batch()`].
+const ANSWER_BATCH_SIZE: usize = 500;
 
 /// Provides access to card-related AnkiConnect operations.
 ///
@@ -329,38 +336,89 @@ impl<'a> CardActions<'a> {
             .await
     }
 
+    /// Answer a large number of cards, batching requests with `multi`.
+    ///
+    /// Each answer is sent as its own `answerCards` action, so a card that
+    /// cannot be answered does not prevent the others from being recorded.
+    /// Answers are submitted in order, in requests of up to 500 answers.
+    ///
+    /// Returns one entry per answer: `true` if the card was answered.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ankit::{AnkiClient, CardAnswer, Ease};
+    ///
+    /// # async fn example() -> ankit::Result<()> {
+    /// let client = AnkiClient::new();
+    ///
+    /// let answers: Vec<_> = (0..1000)
+    ///     .map(|i| CardAnswer::new(1234567890 + i, Ease::Good))
+    ///     .collect();
+    ///
+    /// let results = client.cards().answer_batch(&answers).await?;
+    /// let answered = results.iter().filter(|ok| **ok).count();
+    /// println!("Answered {} of {} cards", answered, answers.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn answer_batch(&self, answers: &[CardAnswer]) -> Result<Vec<bool>> {
+        let mut results = Vec::with_capacity(answers.len());
+
+        for chunk in answers.chunks(ANSWER_BATCH_SIZE) {
+            let actions = chunk
+                .iter()
+                .map(|answer| {
+                    let params = serde_json::to_value(AnswerCardsParams {
+                        answers: std::slice::from_ref(answer),
+                    })?;
+                    Ok(MultiAction::with_params("answerCards", params))
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            let start = results.len();
+            let responses = self.client.misc().multi(&actions).await?;
+            results.extend(responses.iter().map(succeeded));
+            // Treat answers missing from a short response as not answered
+            results.resize(start + chunk.len(), false);
+        }
+
+        Ok(results)
+    }
+
     /// Set the due date for cards.
     ///
-    /// The `days` parameter can be:
+    /// `days` is a [`DueSpec`], or the raw string AnkiConnect expects:
     /// - A number like `"0"` (due today), `"1"` (due tomorrow), `"-1"` (due yesterday)
     /// - A range like `"0-3"` (randomly between today and 3 days from now)
-    /// - An exclamation mark suffix like `"1!"` to set as review cards instead of new
+    /// - An exclamation mark suffix like `"1!"` to also set the review interval
     ///
     /// # Example
     ///
     /// ```no_run
-    /// # use ankit::AnkiClient;
+    /// # use ankit::{AnkiClient, DueSpec};
     /// # async fn example() -> ankit::Result<()> {
     /// let client = AnkiClient::new();
     ///
     /// // Make cards due today
-    /// client.cards().set_due_date(&[1234567890], "0").await?;
+    /// client.cards().set_due_date(&[1234567890], DueSpec::today()).await?;
     ///
     /// // Make cards due in 7 days
-    /// client.cards().set_due_date(&[1234567890], "7").await?;
+    /// client.cards().set_due_date(&[1234567890], DueSpec::in_days(7)).await?;
     ///
     /// // Make cards due randomly between 1-7 days
     /// client.cards().set_due_date(&[1234567890], "1-7").await?;
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn set_due_date(&self, card_ids: &[i64], days: &str) -> Result<bool> {
+    pub async fn set_due_date(&self, card_ids: &[i64], days: impl Into<DueSpec>) -> Result<bool> {
+        let days = days.into();
         self.client
             .invoke(
                 "setDueDate",
                 SetDueDateParams {
                     cards: card_ids,
-                    days,
+                    days: days.as_str(),
                 },
             )
             .await
@@ -414,4 +472,207 @@ impl<'a> CardActions<'a> {
             )
             .await
     }
+
+    /// Set a flag on cards, replacing any flag they already have.
+    ///
+    /// AnkiConnect has no bulk flag action, so each card is updated with
+    /// `setSpecificValueOfCard` inside a single `multi` request. Returns
+    /// whether each card was updated, in the same order as `card_ids`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit::{AnkiClient, Flag};
+    /// # async fn example() -> ankit::Result<()> {
+    /// let client = AnkiClient::new();
+    ///
+    /// let leeches = client.cards().find("tag:leech").await?;
+    /// client.cards().set_flag(&leeches, Flag::Red).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_flag(&self, card_ids: &[i64], flag: Flag) -> Result<Vec<bool>> {
+        self.write_flags(card_ids, flag.number()).await
+    }
+
+    /// Remove the flag from cards.
+    ///
+    /// Returns whether each card was updated, in the same order as `card_ids`.
+    pub async fn clear_flag(&self, card_ids: &[i64]) -> Result<Vec<bool>> {
+        self.write_flags(card_ids, 0).await
+    }
+
+    /// Bury cards until the next day.
+    ///
+    /// AnkiConnect has no bury action, so each card is moved to the manually
+    /// buried queue with `setSpecificValueOfCard` inside a single `multi`
+    /// request. Returns whether each card was updated, in the same order as
+    /// `card_ids`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit::AnkiClient;
+    /// # async fn example() -> ankit::Result<()> {
+    /// let client = AnkiClient::new();
+    /// let cards = client.cards().find("deck:Japanese tag:later").await?;
+    /// client.cards().bury(&cards).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn bury(&self, card_ids: &[i64]) -> Result<Vec<bool>> {
+        let values: Vec<(i64, String)> = card_ids
+            .iter()
+            .map(|&card| (card, QUEUE_MANUALLY_BURIED.to_string()))
+            .collect();
+        self.write_values("queue", &values).await
+    }
+
+    /// Unbury cards, returning them to the queue for their card type.
+    ///
+    /// Both manually buried cards and siblings buried by the scheduler are
+    /// restored. Returns whether each card was unburied, in the same order as
+    /// `card_ids`; cards that were not buried are left alone and reported as
+    /// `false`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit::AnkiClient;
+    /// # async fn example() -> ankit::Result<()> {
+    /// let client = AnkiClient::new();
+    /// let buried = client.cards().find("deck:Japanese is:buried").await?;
+    /// client.cards().unbury(&buried).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn unbury(&self, card_ids: &[i64]) -> Result<Vec<bool>> {
+        if card_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let values: Vec<(i64, String)> = self
+            .info(card_ids)
+            .await?
+            .iter()
+            .filter(|card| is_buried(card.queue))
+            .map(|card| (card.card_id, unburied_queue(card).to_string()))
+            .collect();
+        let written: HashMap<i64, bool> = values
+            .iter()
+            .map(|(card, _)| *card)
+            .zip(self.write_values("queue", &values).await?)
+            .collect();
+
+        Ok(card_ids
+            .iter()
+            .map(|card| written.get(card).copied().unwrap_or(false))
+            .collect())
+    }
+
+    /// Move new cards to positions in the new card queue.
+    ///
+    /// Each entry pairs a card ID with its new position; cards with lower
+    /// positions are introduced first. AnkiConnect has no reposition action,
+    /// so the `due` value of each card is written with
+    /// `setSpecificValueOfCard` inside a single `multi` request. Only use this
+    /// on new cards, whose `due` is their queue position.
+    ///
+    /// Returns whether each card was updated, in the same order as `positions`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit::AnkiClient;
+    /// # async fn example() -> ankit::Result<()> {
+    /// let client = AnkiClient::new();
+    ///
+    /// // Introduce card 1234567890 before card 1234567891
+    /// client.cards().reposition(&[(1234567890, 1), (1234567891, 2)]).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn reposition(&self, positions: &[(i64, i64)]) -> Result<Vec<bool>> {
+        let values: Vec<(i64, String)> = positions
+            .iter()
+            .map(|&(card, position)| (card, position.to_string()))
+            .collect();
+        self.write_values("due", &values).await
+    }
+
+    async fn write_flags(&self, card_ids: &[i64], flag: u8) -> Result<Vec<bool>> {
+        let value = flag.to_string();
+        let values: Vec<(i64, String)> =
+            card_ids.iter().map(|&card| (card, value.clone())).collect();
+        self.write_values("flags", &values).await
+    }
+
+    /// Write one low-level field on each card with a single `multi` request.
+    async fn write_values(&self, key: &str, values: &[(i64, String)]) -> Result<Vec<bool>> {
+        if values.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let actions = values
+            .iter()
+            .map(|(card, value)| {
+                let params = serde_json::to_value(SetSpecificValueParams {
+                    card: *card,
+                    keys: &[key],
+                    new_values: &[value.as_str()],
+                    // Acknowledge the warning for low-level card fields
+                    warning_check: true,
+                })?;
+                Ok(MultiAction::with_params("setSpecificValueOfCard", params))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let responses = self.client.misc().multi(&actions).await?;
+        let mut results: Vec<bool> = responses.iter().map(succeeded).collect();
+        results.resize(values.len(), false);
+        Ok(results)
+    }
+}
+
+/// Queue of cards buried by the scheduler because a sibling was studied.
+const QUEUE_SIBLING_BURIED: i32 = -2;
+
+/// Queue of cards buried by the user.
+const QUEUE_MANUALLY_BURIED: i32 = -3;
+
+fn is_buried(queue: i32) -> bool {
+    queue == QUEUE_SIBLING_BURIED || queue == QUEUE_MANUALLY_BURIED
+}
+
+/// The queue a buried card returns to.
+fn unburied_queue(card: &CardInfo) -> i32 {
+    match card.card_type {
+        // Learning and relearning cards due within the day have a timestamp
+        // due, cards due on a later day have a day number
+        1 | 3 if card.due < 1_000_000_000 => 3,
+        1 | 3 => 1,
+        card_type => card_type,
+    }
+}
+
+/// Whether a `multi` response entry reports success for its card.
+///
+/// AnkiConnect wraps each result as `{"result": ..., "error": ...}`; older
+/// versions return the bare result.
+fn succeeded(response: &serde_json::Value) -> bool {
+    let result = match response.as_object() {
+        Some(wrapped) if wrapped.contains_key("result") || wrapped.contains_key("error") => {
+            if !wrapped.get("error").is_none_or(|e| e.is_null()) {
+                return false;
+            }
+            wrapped.get("result").unwrap_or(&serde_json::Value::Null)
+        }
+        _ => response,
+    };
+
+    match result {
+        serde_json::Value::Array(values) => values.first().and_then(|v| v.as_bool()) == Some(true),
+        serde_json::Value::Bool(ok) => *ok,
+        _ => false,
+    }
 }
@@ -25,9 +25,10 @@ use std::collections::HashMap;
 
 use serde::Serialize;
 
+use crate::cache::MetadataKey;
 use crate::client::AnkiClient;
-use crate::error::Result;
-use crate::types::{DeckConfig, DeckStats};
+use crate::error::{Error, Result};
+use crate::types::{BurySettings, DeckConfig, DeckStats, DeckTree};
 
 /// Provides access to deck-related AnkiConnect operations.
 ///
@@ -91,6 +92,15 @@ struct RemoveDeckConfigParams {
     config_id: i64,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateFilteredDeckParams<'a> {
+    new_deck_name: &'a str,
+    search_query: &'a str,
+    gather_count: usize,
+    reschedule: bool,
+}
+
 #[derive(Serialize)]
 struct GetDeckStatsParams<'a> {
     decks: &'a [&'a str],
@@ -113,7 +123,31 @@ impl<'a> DeckActions<'a> {
     /// # }
     /// ```
     pub async fn names(&self) -> Result<Vec<String>> {
-        self.client.invoke_without_params("deckNames").await
+        self.client
+            .cached_metadata(
+                MetadataKey::Decks,
+                self.client.invoke_without_params("deckNames"),
+            )
+            .await
+    }
+
+    /// Get the deck hierarchy.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit::AnkiClient;
+    /// # async fn example() -> ankit::Result<()> {
+    /// let client = AnkiClient::new();
+    /// let tree = client.decks().tree().await?;
+    /// for deck in tree.subtree("Japanese") {
+    ///     println!("{}", deck);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn tree(&self) -> Result<DeckTree> {
+        Ok(DeckTree::from_names(self.names().await?))
     }
 
     /// Get all deck names with their IDs.
@@ -180,6 +214,49 @@ impl<'a> DeckActions<'a> {
             .await
     }
 
+    /// Create a filtered deck from a search.
+    ///
+    /// Gathers up to `limit` cards matching `search` into a new filtered deck
+    /// and returns its ID. When `reschedule` is false, reviews in the deck do
+    /// not affect the cards' scheduling, which suits cramming. Cards return
+    /// to their home decks when the filtered deck is emptied or deleted.
+    ///
+    /// Requires an AnkiConnect version with the `createFilteredDeck` action.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit::AnkiClient;
+    /// # async fn example() -> ankit::Result<()> {
+    /// let client = AnkiClient::new();
+    /// let deck_id = client
+    ///     .decks()
+    ///     .create_filtered("Cram tonight", "deck:Japanese prop:ease<2.1", 100, false)
+    ///     .await?;
+    /// println!("Created filtered deck with ID: {}", deck_id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_filtered(
+        &self,
+        name: &str,
+        search: &str,
+        limit: usize,
+        reschedule: bool,
+    ) -> Result<i64> {
+        self.client
+            .invoke(
+                "createFilteredDeck",
+                CreateFilteredDeckParams {
+                    new_deck_name: name,
+                    search_query: search,
+                    gather_count: limit,
+                    reschedule,
+                },
+            )
+            .await
+    }
+
     /// Move cards to a different deck.
     ///
     /// # Example
@@ -238,9 +315,66 @@ impl<'a> DeckActions<'a> {
     /// # }
     /// ```
     pub async fn config(&self, deck: &str) -> Result<DeckConfig> {
-        self.client
+        self.config_if_exists(deck)
+            .await?
+            .ok_or_else(|| Error::DeckNotFound(deck.to_string()))
+    }
+
+    /// Get the configuration for a deck, or None if the deck doesn't exist.
+    ///
+    /// AnkiConnect answers `false` rather than an error for a missing deck;
+    /// [`config`](Self::config) reports that as [`Error::DeckNotFound`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit::AnkiClient;
+    /// # async fn example() -> ankit::Result<()> {
+    /// let client = AnkiClient::new();
+    /// match client.decks().config_if_exists("Japanese").await? {
+    ///     Some(config) => println!("Leech threshold: {}", config.lapse.leech_fails),
+    ///     None => println!("No such deck"),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn config_if_exists(&self, deck: &str) -> Result<Option<DeckConfig>> {
+        let config: serde_json::Value = self
+            .client
             .invoke("getDeckConfig", GetDeckConfigParams { deck })
-            .await
+            .await?;
+        if config == serde_json::Value::Bool(false) {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_value(config)?))
+    }
+
+    /// Find a deck configuration (options group) by name.
+    ///
+    /// AnkiConnect can only look up the configuration of a deck, so this
+    /// checks each deck in turn and returns None if no deck uses a
+    /// configuration with this name.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit::AnkiClient;
+    /// # async fn example() -> ankit::Result<()> {
+    /// let client = AnkiClient::new();
+    /// if let Some(config) = client.decks().config_by_name("Language Decks").await? {
+    ///     client.decks().set_config_id(&["Spanish"], config.id).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn config_by_name(&self, name: &str) -> Result<Option<DeckConfig>> {
+        for deck in self.names().await? {
+            let config = self.config(&deck).await?;
+            if config.name == name {
+                return Ok(Some(config));
+            }
+        }
+        Ok(None)
     }
 
     /// Save a deck configuration.
@@ -265,6 +399,45 @@ impl<'a> DeckActions<'a> {
             .await
     }
 
+    /// Get the sibling burying settings of a deck's configuration.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit::AnkiClient;
+    /// # async fn example() -> ankit::Result<()> {
+    /// let client = AnkiClient::new();
+    /// let bury = client.decks().bury_settings("Japanese").await?;
+    /// println!("Bury new siblings: {}", bury.new);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn bury_settings(&self, deck: &str) -> Result<BurySettings> {
+        Ok(self.config(deck).await?.bury_settings())
+    }
+
+    /// Change the sibling burying settings of a deck's configuration.
+    ///
+    /// The configuration is shared by every deck that uses the same options
+    /// preset, so the change applies to all of them. Returns true if
+    /// successful.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit::{AnkiClient, BurySettings};
+    /// # async fn example() -> ankit::Result<()> {
+    /// let client = AnkiClient::new();
+    /// client.decks().set_bury_settings("Japanese", BurySettings::all()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_bury_settings(&self, deck: &str, settings: BurySettings) -> Result<bool> {
+        let mut config = self.config(deck).await?;
+        config.set_bury_settings(settings);
+        self.save_config(&config).await
+    }
+
     /// Assign a configuration to multiple decks.
     ///
     /// Returns true if successful.
@@ -18,9 +18,34 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! # Review Before Saving
+//!
+//! A capture tool can open the Add Cards dialog prefilled with scraped
+//! content, leaving the user to edit and save it:
+//!
+//! ```no_run
+//! use ankit::{AnkiClient, NoteBuilder};
+//!
+//! # async fn example() -> ankit::Result<()> {
+//! let client = AnkiClient::new();
+//! let note = NoteBuilder::new("Inbox", "Basic")
+//!     .field("Front", "What does <code>Vec::drain</code> return?")
+//!     .field("Back", "An iterator over the removed elements")
+//!     .tag("captured")
+//!     .close_after_adding(true)
+//!     .build();
+//! client.gui().add_cards(note).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! To drive the reviewer card by card, see
+//! [`review_session`](GuiActions::review_session).
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
 
 use crate::client::AnkiClient;
 use crate::error::Result;
@@ -35,12 +60,27 @@ pub struct GuiActions<'a> {
 }
 
 #[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
 struct BrowseParams<'a> {
     query: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reorder_cards: Option<ReorderCards<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReorderCards<'a> {
+    order: SortOrder,
+    column_id: &'a str,
 }
 
 #[derive(Serialize)]
-struct EditNoteParams {
+struct AddCardsParams {
+    note: Note,
+}
+
+#[derive(Serialize)]
+struct NoteParams {
     note: i64,
 }
 
@@ -75,6 +115,16 @@ struct AddNoteSetDataParams<'a> {
     tags: Option<&'a [&'a str]>,
 }
 
+/// Sort direction for the card browser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    /// Smallest or oldest first.
+    Ascending,
+    /// Largest or newest first.
+    Descending,
+}
+
 /// Result of getting the current card.
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -133,7 +183,61 @@ impl<'a> GuiActions<'a> {
     /// ```
     pub async fn browse(&self, query: &str) -> Result<Vec<i64>> {
         self.client
-            .invoke("guiBrowse", BrowseParams { query })
+            .invoke(
+                "guiBrowse",
+                BrowseParams {
+                    query,
+                    reorder_cards: None,
+                },
+            )
+            .await
+    }
+
+    /// Open the card browser with a search query, sorted by a column.
+    ///
+    /// `column_id` is the browser's internal column name, such as `noteCrt`
+    /// (creation date), `cardDue`, `cardIvl`, `cardEase`, or `noteFld` (sort
+    /// field). Returns the IDs of cards matching the query.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ankit::{AnkiClient, QueryBuilder, SortOrder};
+    ///
+    /// # async fn example() -> ankit::Result<()> {
+    /// let client = AnkiClient::new();
+    /// // A search kept by a dashboard, newest notes first
+    /// let leeches = QueryBuilder::new().deck("Japanese").tag("leech").build();
+    /// client
+    ///     .gui()
+    ///     .browse_sorted(&leeches, "noteCrt", SortOrder::Descending)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn browse_sorted(
+        &self,
+        query: &str,
+        column_id: &str,
+        order: SortOrder,
+    ) -> Result<Vec<i64>> {
+        self.client
+            .invoke(
+                "guiBrowse",
+                BrowseParams {
+                    query,
+                    reorder_cards: Some(ReorderCards { order, column_id }),
+                },
+            )
+            .await
+    }
+
+    /// Select a note in the open card browser.
+    ///
+    /// Returns false if the browser isn't open.
+    pub async fn select_note(&self, note_id: i64) -> Result<bool> {
+        self.client
+            .invoke("guiSelectNote", NoteParams { note: note_id })
             .await
     }
 
@@ -142,17 +246,24 @@ impl<'a> GuiActions<'a> {
         self.client.invoke_without_params("guiSelectedNotes").await
     }
 
-    /// Open the Add Cards dialog with a note.
+    /// Open the Add Cards dialog prefilled with a note's deck, model,
+    /// fields, tags, and media.
     ///
-    /// Returns the ID of the added note, or None if cancelled.
+    /// The note isn't saved until the user clicks Add. Calling this again
+    /// replaces the open dialog's contents. Returns the ID AnkiConnect
+    /// reports for the pending note, if any. Set
+    /// [`NoteBuilder::close_after_adding`](crate::NoteBuilder::close_after_adding)
+    /// to close the dialog once the user saves.
     pub async fn add_cards(&self, note: Note) -> Result<Option<i64>> {
-        self.client.invoke("guiAddCards", note).await
+        self.client
+            .invoke("guiAddCards", AddCardsParams { note })
+            .await
     }
 
     /// Open the note editor for a specific note.
     pub async fn edit_note(&self, note_id: i64) -> Result<()> {
         self.client
-            .invoke_void("guiEditNote", EditNoteParams { note: note_id })
+            .invoke_void("guiEditNote", NoteParams { note: note_id })
             .await
     }
 
@@ -165,6 +276,56 @@ impl<'a> GuiActions<'a> {
             .await
     }
 
+    /// Poll the reviewer until it shows a card other than `previous`.
+    ///
+    /// Checks [`current_card`](Self::current_card) every `interval` and
+    /// returns the new card, or None if nothing changed within `timeout`.
+    /// Pass `None` as `previous` to wait for any card to be shown.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit::AnkiClient;
+    /// # use std::time::Duration;
+    /// # async fn example() -> ankit::Result<()> {
+    /// let client = AnkiClient::new();
+    /// let mut shown = None;
+    /// while let Some(card) = client
+    ///     .gui()
+    ///     .wait_for_card_change(shown, Duration::from_millis(500), Duration::from_secs(60))
+    ///     .await?
+    /// {
+    ///     println!("Now reviewing {}", card.question);
+    ///     shown = Some(card.card_id);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn wait_for_card_change(
+        &self,
+        previous: Option<i64>,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<Option<CurrentCard>> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            if let Some(card) = self.current_card().await? {
+                if Some(card.card_id) != previous {
+                    return Ok(Some(card));
+                }
+            }
+            if tokio::time::Instant::now() + interval > deadline {
+                return Ok(None);
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// Check whether the reviewer is showing a card.
+    pub async fn review_active(&self) -> Result<bool> {
+        self.client.invoke_without_params("guiReviewActive").await
+    }
+
     /// Start the card timer.
     ///
     /// This resets the timer used to track how long the user takes to answer.
@@ -228,6 +389,9 @@ impl<'a> GuiActions<'a> {
     }
 
     /// Exit Anki.
+    ///
+    /// Anki closes gracefully after responding, so this returns before it
+    /// has exited.
     pub async fn exit_anki(&self) -> Result<()> {
         self.client.invoke_void("guiExitAnki", ()).await
     }
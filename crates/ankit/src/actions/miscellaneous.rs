@@ -194,6 +194,23 @@ impl<'a> MiscActions<'a> {
             .await
     }
 
+    /// Get the name of the profile that is currently loaded.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit::AnkiClient;
+    /// # async fn example() -> ankit::Result<()> {
+    /// let client = AnkiClient::new();
+    /// let profile = client.misc().active_profile().await?;
+    /// println!("Working in profile: {}", profile);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn active_profile(&self) -> Result<String> {
+        self.client.invoke_without_params("getActiveProfile").await
+    }
+
     /// Export a deck to an .apkg file.
     ///
     /// # Example
@@ -9,13 +9,15 @@ mod media;
 mod miscellaneous;
 mod models;
 mod notes;
+mod review_session;
 mod statistics;
 
 pub use cards::CardActions;
 pub use decks::DeckActions;
-pub use graphical::{CurrentCard, GuiActions, ImportResult};
+pub use graphical::{CurrentCard, GuiActions, ImportResult, SortOrder};
 pub use media::MediaActions;
 pub use miscellaneous::{ApiReflectResult, MiscActions, MultiAction, PermissionResult};
 pub use models::ModelActions;
 pub use notes::NoteActions;
+pub use review_session::{AnswerShown, AnsweredCard, QuestionShown, ReviewSession, ReviewStep};
 pub use statistics::{ReviewEntry, StatisticsActions};
@@ -23,8 +23,10 @@
 use serde::Serialize;
 use std::collections::HashMap;
 
+use crate::cache::MetadataKey;
 use crate::client::AnkiClient;
-use crate::error::Result;
+use crate::error::{Error, Result};
+use crate::render::{self, RenderContext, RenderedCard};
 use crate::types::{
     CardTemplate, CreateModelParams, FieldFont, FieldsOnTemplates, FindReplaceParams, ModelStyling,
 };
@@ -202,7 +204,12 @@ impl<'a> ModelActions<'a> {
     /// # }
     /// ```
     pub async fn names(&self) -> Result<Vec<String>> {
-        self.client.invoke_without_params("modelNames").await
+        self.client
+            .cached_metadata(
+                MetadataKey::Models,
+                self.client.invoke_without_params("modelNames"),
+            )
+            .await
     }
 
     /// Get all model names and their IDs.
@@ -227,7 +234,11 @@ impl<'a> ModelActions<'a> {
     /// ```
     pub async fn field_names(&self, model_name: &str) -> Result<Vec<String>> {
         self.client
-            .invoke("modelFieldNames", ModelNameParams { model_name })
+            .cached_metadata(
+                MetadataKey::ModelFields(model_name.to_string()),
+                self.client
+                    .invoke("modelFieldNames", ModelNameParams { model_name }),
+            )
             .await
     }
 
@@ -292,6 +303,46 @@ impl<'a> ModelActions<'a> {
             .await
     }
 
+    /// Render a card locally from a model's templates and styling.
+    ///
+    /// Fetches the model's templates and CSS and renders them with
+    /// [`render::render_card()`], without going through Anki's GUI. The
+    /// context's note type and card name are filled in from the arguments.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ankit::AnkiClient;
+    /// use ankit::render::RenderContext;
+    ///
+    /// # async fn example() -> ankit::Result<()> {
+    /// let client = AnkiClient::new();
+    ///
+    /// let context = RenderContext::new([("Front", "hola"), ("Back", "hello")]);
+    /// let card = client.models().render("Basic", "Card 1", context).await?;
+    /// println!("{}", card.answer_page());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn render(
+        &self,
+        model_name: &str,
+        template_name: &str,
+        context: RenderContext,
+    ) -> Result<RenderedCard> {
+        let templates = self.templates(model_name).await?;
+        let template = templates.get(template_name).ok_or_else(|| {
+            Error::AnkiConnect(format!(
+                "template '{}' was not found in model '{}'",
+                template_name, model_name
+            ))
+        })?;
+        let styling = self.styling(model_name).await?;
+
+        let context = context.note_type(model_name).card(template_name);
+        Ok(render::render_card(template, &styling.css, &context))
+    }
+
     /// Update card templates for a model.
     ///
     /// # Example
@@ -33,7 +33,7 @@ use serde::Serialize;
 
 use crate::client::AnkiClient;
 use crate::error::Result;
-use crate::types::{CanAddResult, Note, NoteInfo, NoteModTime};
+use crate::types::{CanAddResult, Note, NoteInfo, NoteModTime, TagTree};
 
 /// Provides access to note-related AnkiConnect operations.
 ///
@@ -624,4 +624,24 @@ impl<'a> NoteActions<'a> {
     pub async fn all_tags(&self) -> Result<Vec<String>> {
         self.client.invoke("getTags", serde_json::json!({})).await
     }
+
+    /// Get the tag hierarchy of the collection.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ankit::AnkiClient;
+    /// # async fn example() -> ankit::Result<()> {
+    /// let client = AnkiClient::new();
+    ///
+    /// let tree = client.notes().tag_tree().await?;
+    /// for tag in tree.children("lang") {
+    ///     println!("{}", tag);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn tag_tree(&self) -> Result<TagTree> {
+        Ok(TagTree::from_names(self.all_tags().await?))
+    }
 }
@@ -0,0 +1,172 @@
+//! A typed review loop over the GUI reviewer.
+//!
+//! Driving the reviewer with [`GuiActions`] means calling `guiShowAnswer`
+//! and `guiAnswerCard` in the right order for whichever card is showing.
+//! [`ReviewSession`] tracks that order in its type: a card on its question
+//! side can only be flipped, and a card on its answer side can only be
+//! answered.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ankit::{AnkiClient, Ease};
+//!
+//! # async fn example() -> ankit::Result<()> {
+//! let client = AnkiClient::new();
+//! client.gui().deck_review("Japanese").await?;
+//!
+//! let mut session = client.gui().review_session().await?;
+//! while let Some(question) = session {
+//!     println!("Q: {}", question.card().question);
+//!     let answer = question.show_answer().await?;
+//!     println!("A: {}", answer.card().answer);
+//!
+//!     let step = answer.answer(Ease::Good).await?;
+//!     println!("Answered in {:?}", step.answered.total_time());
+//!     session = step.next;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use super::graphical::{CurrentCard, GuiActions};
+use crate::client::AnkiClient;
+use crate::error::{Error, Result};
+use crate::types::Ease;
+
+/// Review state: the reviewer is showing the question.
+#[derive(Debug)]
+pub struct QuestionShown;
+
+/// Review state: the reviewer is showing the answer.
+#[derive(Debug)]
+pub struct AnswerShown {
+    /// When the answer was shown.
+    at: Instant,
+}
+
+/// The card in Anki's reviewer, in state `S`.
+///
+/// Obtained via [`GuiActions::review_session()`]. Each transition consumes
+/// the session and returns it in the next state.
+#[derive(Debug)]
+pub struct ReviewSession<'a, S> {
+    client: &'a AnkiClient,
+    card: CurrentCard,
+    shown_at: Instant,
+    state: S,
+}
+
+/// A card answered in a [`ReviewSession`].
+#[derive(Debug, Clone)]
+pub struct AnsweredCard {
+    /// The card ID.
+    pub card_id: i64,
+    /// The ease it was answered with.
+    pub ease: Ease,
+    /// Time from showing the question to showing the answer.
+    pub question_time: Duration,
+    /// Time from showing the answer to answering.
+    pub answer_time: Duration,
+}
+
+impl AnsweredCard {
+    /// Total time spent on the card.
+    pub fn total_time(&self) -> Duration {
+        self.question_time + self.answer_time
+    }
+}
+
+/// Result of answering a card in a [`ReviewSession`].
+#[derive(Debug)]
+pub struct ReviewStep<'a> {
+    /// The card that was answered.
+    pub answered: AnsweredCard,
+    /// The next card, or None if the reviewer has nothing left to show.
+    pub next: Option<ReviewSession<'a, QuestionShown>>,
+}
+
+impl<'a> GuiActions<'a> {
+    /// Start a typed review loop on the card the reviewer is showing.
+    ///
+    /// Shows the card's question and restarts Anki's card timer. Returns
+    /// None if the reviewer isn't showing a card; open one with
+    /// [`deck_review`](Self::deck_review) first.
+    pub async fn review_session(&self) -> Result<Option<ReviewSession<'a, QuestionShown>>> {
+        ReviewSession::start(self.client).await
+    }
+}
+
+impl<'a, S> ReviewSession<'a, S> {
+    /// The card being reviewed, as it was when its question was shown.
+    pub fn card(&self) -> &CurrentCard {
+        &self.card
+    }
+
+    /// Time since the question was shown.
+    pub fn elapsed(&self) -> Duration {
+        self.shown_at.elapsed()
+    }
+}
+
+impl<'a> ReviewSession<'a, QuestionShown> {
+    async fn start(client: &'a AnkiClient) -> Result<Option<Self>> {
+        let Some(card) = client.gui().current_card().await? else {
+            return Ok(None);
+        };
+        client.gui().show_question().await?;
+        client.gui().start_timer().await?;
+        Ok(Some(Self {
+            client,
+            card,
+            shown_at: Instant::now(),
+            state: QuestionShown,
+        }))
+    }
+
+    /// Flip the card to its answer side.
+    ///
+    /// Fails if the reviewer is no longer showing a card.
+    pub async fn show_answer(self) -> Result<ReviewSession<'a, AnswerShown>> {
+        if !self.client.gui().show_answer().await? {
+            return Err(Error::ReviewerClosed("show the answer".to_string()));
+        }
+        Ok(ReviewSession {
+            client: self.client,
+            card: self.card,
+            shown_at: self.shown_at,
+            state: AnswerShown { at: Instant::now() },
+        })
+    }
+}
+
+impl<'a> ReviewSession<'a, AnswerShown> {
+    /// Time from showing the question to showing the answer.
+    pub fn question_time(&self) -> Duration {
+        self.state.at - self.shown_at
+    }
+
+    /// Answer the card and move on to the next one.
+    ///
+    /// Fails if the reviewer is no longer showing a card or rejects the
+    /// ease.
+    pub async fn answer(self, ease: Ease) -> Result<ReviewStep<'a>> {
+        if !self.client.gui().answer_card(ease).await? {
+            return Err(Error::ReviewerClosed("answer the card".to_string()));
+        }
+        let answered = AnsweredCard {
+            card_id: self.card.card_id,
+            ease,
+            question_time: self.question_time(),
+            answer_time: self.state.at.elapsed(),
+        };
+        Ok(ReviewStep {
+            answered,
+            next: ReviewSession::start(self.client).await?,
+        })
+    }
+}
@@ -0,0 +1,120 @@
+//! Memoized deck and model metadata.
+//!
+//! Workflows ask for `deckNames`, `modelNames`, and `modelFieldNames` over and
+//! over, and the answers rarely change. With caching enabled (see
+//! [`ClientBuilder::metadata_cache`](crate::ClientBuilder::metadata_cache)),
+//! answers are reused until they are older than the TTL, or until the client
+//! sends an action that may change them.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Actions after which cached metadata may be stale.
+///
+/// `changeDeck` and the imports create decks and models that don't exist
+/// yet, and `guiUndo` may undo any change.
+const INVALIDATING_ACTIONS: &[&str] = &[
+    "createDeck",
+    "createFilteredDeck",
+    "changeDeck",
+    "deleteDecks",
+    "createModel",
+    "modelFieldAdd",
+    "modelFieldRemove",
+    "modelFieldRename",
+    "modelFieldReposition",
+    "importPackage",
+    "guiImportFile",
+    "guiUndo",
+    "loadProfile",
+    "reloadCollection",
+    "sync",
+    "multi",
+];
+
+/// A cached metadata request.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum MetadataKey {
+    /// `deckNames`
+    Decks,
+    /// `modelNames`
+    Models,
+    /// `modelFieldNames` for one model
+    ModelFields(String),
+}
+
+/// Cached metadata shared by a client and its clones.
+#[derive(Debug)]
+pub(crate) struct MetadataCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<MetadataKey, (Instant, Vec<String>)>>,
+}
+
+impl MetadataCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The cached value for `key`, if it hasn't expired.
+    pub fn get(&self, key: &MetadataKey) -> Option<Vec<String>> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(key)
+            .filter(|(fetched, _)| fetched.elapsed() < self.ttl)
+            .map(|(_, value)| value.clone())
+    }
+
+    pub fn insert(&self, key: MetadataKey, value: Vec<String>) {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(key, (Instant::now(), value));
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Clear the cache if `action` may change decks or models.
+    pub fn observe(&self, action: &str) {
+        if INVALIDATING_ACTIONS.contains(&action) {
+            self.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_respects_ttl() {
+        let cache = MetadataCache::new(Duration::from_secs(60));
+        cache.insert(MetadataKey::Decks, vec!["Default".to_string()]);
+        assert_eq!(
+            cache.get(&MetadataKey::Decks),
+            Some(vec!["Default".to_string()])
+        );
+
+        let expired = MetadataCache::new(Duration::ZERO);
+        expired.insert(MetadataKey::Decks, vec!["Default".to_string()]);
+        assert_eq!(expired.get(&MetadataKey::Decks), None);
+    }
+
+    #[test]
+    fn test_observe_clears_on_writes() {
+        let cache = MetadataCache::new(Duration::from_secs(60));
+        let key = MetadataKey::ModelFields("Basic".to_string());
+        cache.insert(key.clone(), vec!["Front".to_string()]);
+
+        cache.observe("addNote");
+        assert!(cache.get(&key).is_some());
+
+        cache.observe("modelFieldAdd");
+        assert!(cache.get(&key).is_none());
+    }
+}
@@ -0,0 +1,248 @@
+//! Client settings from the environment and a config file.
+//!
+//! [`ClientConfig`] collects the settings every program talking to Anki
+//! needs, so the CLI, the MCP server, and user scripts configure the client
+//! the same way. Settings are read from, in increasing precedence:
+//!
+//! 1. the config file (requires the `config` feature): `$ANKIT_CONFIG`, or
+//!    `ankit/config.toml` in `$XDG_CONFIG_HOME` (default `~/.config`),
+//! 2. environment variables:
+//!    - `ANKICONNECT_URL`, e.g. `http://127.0.0.1:8765`
+//!    - `ANKICONNECT_API_KEY`
+//!    - `ANKICONNECT_TIMEOUT`, in seconds
+//!
+//! A config file has the same three settings:
+//!
+//! ```toml
+//! url = "http://192.168.1.20:8765"
+//! api_key = "secret"
+//! timeout = 60
+//! ```
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ankit::AnkiClient;
+//!
+//! # async fn example() -> ankit::Result<()> {
+//! let client = AnkiClient::from_env()?;
+//! let version = client.misc().version().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::client::ClientBuilder;
+use crate::error::{Error, Result};
+
+/// Environment variable with the AnkiConnect URL.
+pub const URL_VAR: &str = "ANKICONNECT_URL";
+
+/// Environment variable with the AnkiConnect API key.
+pub const API_KEY_VAR: &str = "ANKICONNECT_API_KEY";
+
+/// Environment variable with the request timeout in seconds.
+pub const TIMEOUT_VAR: &str = "ANKICONNECT_TIMEOUT";
+
+/// Environment variable overriding the config file path.
+pub const CONFIG_VAR: &str = "ANKIT_CONFIG";
+
+/// Client settings; unset values keep the builder's defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize))]
+#[cfg_attr(feature = "config", serde(deny_unknown_fields))]
+pub struct ClientConfig {
+    /// AnkiConnect URL.
+    pub url: Option<String>,
+    /// AnkiConnect API key.
+    pub api_key: Option<String>,
+    /// Request timeout.
+    #[cfg_attr(feature = "config", serde(default, with = "timeout_secs"))]
+    pub timeout: Option<Duration>,
+}
+
+impl ClientConfig {
+    /// Load settings from the config file, if any, and the environment.
+    ///
+    /// Returns [`Error::Config`] if the file can't be parsed or a variable
+    /// has an invalid value. A missing default config file is not an error.
+    pub fn load() -> Result<Self> {
+        let mut config = Self::default();
+        #[cfg(feature = "config")]
+        {
+            let explicit = std::env::var_os(CONFIG_VAR).map(PathBuf::from);
+            let path = explicit.clone().or_else(Self::default_path);
+            if let Some(path) = path.filter(|p| explicit.is_some() || p.exists()) {
+                config = Self::from_file(&path)?;
+            }
+        }
+        config.merge(Self::from_env()?);
+        Ok(config)
+    }
+
+    /// Settings from environment variables only.
+    pub fn from_env() -> Result<Self> {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// Settings from a TOML config file.
+    #[cfg(feature = "config")]
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("cannot read {}: {}", path.display(), e)))?;
+        toml::from_str(&content)
+            .map_err(|e| Error::Config(format!("invalid {}: {}", path.display(), e)))
+    }
+
+    /// The default config file path, `ankit/config.toml` in the user's
+    /// config directory.
+    pub fn default_path() -> Option<PathBuf> {
+        let var = |name| std::env::var_os(name).filter(|v| !v.is_empty());
+        let dir = match var("XDG_CONFIG_HOME") {
+            Some(dir) => PathBuf::from(dir),
+            None if cfg!(windows) => PathBuf::from(var("APPDATA")?),
+            None => Path::new(&var("HOME")?).join(".config"),
+        };
+        Some(dir.join("ankit").join("config.toml"))
+    }
+
+    /// Override these settings with those set in `other`.
+    pub fn merge(&mut self, other: ClientConfig) {
+        if other.url.is_some() {
+            self.url = other.url;
+        }
+        if other.api_key.is_some() {
+            self.api_key = other.api_key;
+        }
+        if other.timeout.is_some() {
+            self.timeout = other.timeout;
+        }
+    }
+
+    /// Apply these settings to a builder.
+    pub fn apply(self, mut builder: ClientBuilder) -> ClientBuilder {
+        if let Some(url) = self.url {
+            builder = builder.url(url);
+        }
+        if let Some(key) = self.api_key {
+            builder = builder.api_key(key);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        builder
+    }
+
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let var = |name| lookup(name).filter(|value| !value.trim().is_empty());
+        let timeout = match var(TIMEOUT_VAR) {
+            Some(value) => Some(parse_timeout(&value).ok_or_else(|| {
+                Error::Config(format!(
+                    "{} must be a number of seconds, got '{}'",
+                    TIMEOUT_VAR, value
+                ))
+            })?),
+            None => None,
+        };
+        Ok(Self {
+            url: var(URL_VAR),
+            api_key: var(API_KEY_VAR),
+            timeout,
+        })
+    }
+}
+
+fn parse_timeout(value: &str) -> Option<Duration> {
+    let secs: f64 = value.trim().parse().ok()?;
+    Duration::try_from_secs_f64(secs).ok()
+}
+
+#[cfg(feature = "config")]
+mod timeout_secs {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Duration>, D::Error> {
+        let secs = Option::<f64>::deserialize(d)?;
+        secs.map(|s| Duration::try_from_secs_f64(s).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn test_from_lookup() {
+        let config = ClientConfig::from_lookup(lookup(&[
+            (URL_VAR, "http://10.0.0.2:8765"),
+            (API_KEY_VAR, "secret"),
+            (TIMEOUT_VAR, "2.5"),
+        ]))
+        .unwrap();
+
+        assert_eq!(config.url.as_deref(), Some("http://10.0.0.2:8765"));
+        assert_eq!(config.api_key.as_deref(), Some("secret"));
+        assert_eq!(config.timeout, Some(Duration::from_millis(2500)));
+    }
+
+    #[test]
+    fn test_from_lookup_ignores_empty_values() {
+        let config =
+            ClientConfig::from_lookup(lookup(&[(URL_VAR, ""), (TIMEOUT_VAR, " ")])).unwrap();
+        assert_eq!(config, ClientConfig::default());
+    }
+
+    #[test]
+    fn test_from_lookup_invalid_timeout() {
+        let err = ClientConfig::from_lookup(lookup(&[(TIMEOUT_VAR, "soon")])).unwrap_err();
+        assert!(matches!(err, Error::Config(_)));
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn test_from_file() {
+        let path = std::env::temp_dir().join(format!("ankit-config-{}.toml", std::process::id()));
+        std::fs::write(&path, "url = \"http://10.0.0.2:8765\"\ntimeout = 60\n").unwrap();
+        let config = ClientConfig::from_file(&path);
+        std::fs::write(&path, "port = 8765\n").unwrap();
+        let unknown = ClientConfig::from_file(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let config = config.unwrap();
+        assert_eq!(config.url.as_deref(), Some("http://10.0.0.2:8765"));
+        assert_eq!(config.api_key, None);
+        assert_eq!(config.timeout, Some(Duration::from_secs(60)));
+        assert!(matches!(unknown, Err(Error::Config(_))));
+    }
+
+    #[test]
+    fn test_merge_prefers_other() {
+        let mut config = ClientConfig {
+            url: Some("http://file:8765".to_string()),
+            api_key: Some("file-key".to_string()),
+            timeout: None,
+        };
+        config.merge(ClientConfig {
+            url: Some("http://env:8765".to_string()),
+            ..Default::default()
+        });
+
+        assert_eq!(config.url.as_deref(), Some("http://env:8765"));
+        assert_eq!(config.api_key.as_deref(), Some("file-key"));
+    }
+}
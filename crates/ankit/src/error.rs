@@ -7,9 +7,16 @@
 //! The most common errors you'll encounter are:
 //!
 //! - [`Error::ConnectionRefused`]: Anki is not running or AnkiConnect is not installed
-//! - [`Error::AnkiConnect`]: The operation failed (e.g., deck not found, invalid query)
+//! - [`Error::DeckNotFound`], [`Error::ModelNotFound`], [`Error::NoteNotFound`],
+//!   [`Error::CardNotFound`]: The operation named something that doesn't exist
+//! - [`Error::DuplicateNote`]: A note with the same first field already exists
+//! - [`Error::AnkiConnect`]: Any other failure (e.g., an invalid query)
 //! - [`Error::PermissionDenied`]: API key required or request needs approval
 //!
+//! AnkiConnect reports every failure as a message string. Messages the
+//! client recognizes are turned into the typed variants above; the rest keep
+//! their message in [`Error::AnkiConnect`].
+//!
 //! # Example
 //!
 //! ```no_run
@@ -71,7 +78,7 @@ use thiserror::Error;
 ///
 /// match client.notes().add(note).await {
 ///     Ok(id) => println!("Created note {}", id),
-///     Err(Error::AnkiConnect(msg)) if msg.contains("duplicate") => {
+///     Err(Error::DuplicateNote) => {
 ///         println!("Note already exists");
 ///     }
 ///     Err(e) => return Err(e),
@@ -90,14 +97,39 @@ pub enum Error {
 
     /// AnkiConnect returned an error message.
     ///
-    /// The message string contains details about what went wrong.
-    /// Common messages include:
-    /// - "cannot create note because it is a duplicate"
-    /// - "deck was not found"
-    /// - "model was not found"
+    /// The message string contains details about what went wrong. Messages
+    /// with a more specific variant, such as a missing deck, are never
+    /// reported this way.
     #[error("AnkiConnect error: {0}")]
     AnkiConnect(String),
 
+    /// A deck with this name does not exist.
+    #[error("Deck not found: {0}")]
+    DeckNotFound(String),
+
+    /// A model (note type) with this name does not exist.
+    #[error("Model not found: {0}")]
+    ModelNotFound(String),
+
+    /// A note with this ID does not exist.
+    #[error("Note not found: {0}")]
+    NoteNotFound(String),
+
+    /// A card with this ID does not exist.
+    #[error("Card not found: {0}")]
+    CardNotFound(String),
+
+    /// The note duplicates an existing note's first field.
+    ///
+    /// Allow duplicates with [`NoteOptions`](crate::NoteOptions) to add it anyway.
+    #[error("Cannot create note because it is a duplicate")]
+    DuplicateNote,
+
+    /// No collection is open, usually because Anki is showing the profile
+    /// chooser or is syncing.
+    #[error("Anki collection is not available")]
+    CollectionUnavailable,
+
     /// Response was empty (no result or error).
     ///
     /// This is unexpected and may indicate an AnkiConnect bug.
@@ -135,12 +167,124 @@ pub enum Error {
     #[error("Note validation failed: {0}")]
     NoteValidation(String),
 
+    /// The reviewer stopped showing a card during a review session.
+    ///
+    /// Returned by [`ReviewSession`](crate::ReviewSession) when the user
+    /// closed the reviewer or Anki moved on. Holds the step that failed,
+    /// such as "show the answer".
+    #[error("Could not {0}: the reviewer is not showing a card")]
+    ReviewerClosed(String),
+
     /// Invalid configuration.
     ///
     /// A configuration value was invalid or inconsistent.
     #[error("Invalid configuration: {0}")]
     Config(String),
+
+    /// The installed AnkiConnect doesn't support an action.
+    ///
+    /// Only returned when capability detection is enabled with
+    /// [`ClientBuilder::detect_capabilities`](crate::ClientBuilder::detect_capabilities).
+    /// Updating the AnkiConnect add-on usually adds the action.
+    #[error("AnkiConnect v{version} does not support action '{action}'")]
+    UnsupportedAction {
+        /// The action that was called.
+        action: String,
+        /// The AnkiConnect API version.
+        version: u8,
+    },
+}
+
+impl Error {
+    /// Turn an AnkiConnect error message into the most specific variant.
+    ///
+    /// The client does this for every failed request. Use it for messages
+    /// AnkiConnect reports in results instead, such as the per-note errors
+    /// from `canAddNotesWithErrorDetail`.
+    pub fn from_message(message: impl Into<String>) -> Self {
+        let message = message.into();
+        let lower = message.to_lowercase();
+        // Names and IDs follow the prefix, as in "deck was not found: Spanish"
+        let subject = |prefix: &str| -> Option<String> {
+            if !lower.starts_with(prefix) {
+                return None;
+            }
+            let rest = message.get(prefix.len()..).unwrap_or_default();
+            Some(rest.trim_start_matches(':').trim().to_string())
+        };
+
+        if lower.contains("permission") {
+            Error::PermissionDenied
+        } else if let Some(deck) = subject("deck was not found") {
+            Error::DeckNotFound(deck)
+        } else if let Some(model) = subject("model was not found") {
+            Error::ModelNotFound(model)
+        } else if let Some(note) = subject("note was not found") {
+            Error::NoteNotFound(note)
+        } else if let Some(card) = subject("card was not found") {
+            Error::CardNotFound(card)
+        } else if lower.contains("cannot create note because it is a duplicate") {
+            Error::DuplicateNote
+        } else if lower.contains("cannot create note because it is empty") {
+            Error::NoteValidation(message)
+        } else if lower.contains("collection is not available") {
+            Error::CollectionUnavailable
+        } else {
+            Error::AnkiConnect(message)
+        }
+    }
+
+    /// Whether the error means a deck, model, note, or card doesn't exist.
+    pub fn is_not_found(&self) -> bool {
+        matches!(
+            self,
+            Error::DeckNotFound(_)
+                | Error::ModelNotFound(_)
+                | Error::NoteNotFound(_)
+                | Error::CardNotFound(_)
+        )
+    }
 }
 
 /// A specialized Result type for AnkiConnect operations.
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_message() {
+        let from = |m: &str| Error::from_message(m.to_string());
+
+        assert!(
+            matches!(from("deck was not found: Spanish::Verbs"), Error::DeckNotFound(d) if d == "Spanish::Verbs")
+        );
+        assert!(
+            matches!(from("model was not found: Cloze+"), Error::ModelNotFound(m) if m == "Cloze+")
+        );
+        assert!(matches!(from("Note was not found: 1234"), Error::NoteNotFound(n) if n == "1234"));
+        assert!(matches!(from("Card was not found: 42"), Error::CardNotFound(c) if c == "42"));
+        assert!(matches!(
+            from("cannot create note because it is a duplicate"),
+            Error::DuplicateNote
+        ));
+        assert!(matches!(
+            from("cannot create note because it is empty"),
+            Error::NoteValidation(_)
+        ));
+        assert!(matches!(
+            from("collection is not available"),
+            Error::CollectionUnavailable
+        ));
+        assert!(matches!(
+            from("valid api key must be provided; permission denied"),
+            Error::PermissionDenied
+        ));
+        assert!(
+            matches!(from("unsupported action"), Error::AnkiConnect(m) if m == "unsupported action")
+        );
+        assert!(from("deck was not found: X").is_not_found());
+        assert!(!Error::DuplicateNote.is_not_found());
+    }
+}
@@ -0,0 +1,346 @@
+//! Normalization of note keys for duplicate detection and matching.
+//!
+//! Deduplication, deck comparison, and TOML diffs decide whether two notes
+//! are "the same" by comparing a key field. By default keys are compared
+//! without HTML, with whitespace collapsed, and case-insensitively, which
+//! is not enough for every language: `Ｔｏｋｙｏ` and `Tokyo`, `ｶﾞｯｺｳ` and
+//! `ガッコウ`, or `café` typed with a combining accent and `café` typed
+//! with a precomposed one all look identical but differ byte for byte.
+//! [`KeyNormalization`] adds the folding needed to match them.
+//!
+//! Unicode normalization uses built-in tables covering Latin, Greek,
+//! Cyrillic, Vietnamese, and kana, and compatibility forms in Latin-1,
+//! General Punctuation, and Halfwidth and Fullwidth Forms. Characters
+//! outside these ranges are left unchanged.
+//!
+//! # Example
+//!
+//! ```
+//! use ankit::key::KeyNormalization;
+//!
+//! let cjk = KeyNormalization::cjk();
+//! assert_eq!(cjk.apply("ｶﾞｯｺｳ"), cjk.apply("ガッコウ"));
+//! assert_eq!(cjk.apply("<b>Ｔｏｋｙｏ</b>"), "tokyo");
+//!
+//! let accents = KeyNormalization::accent_insensitive();
+//! assert_eq!(accents.apply("Café!"), "cafe");
+//! ```
+
+mod tables;
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use tables::{COMPATIBILITY, COMPOSITIONS};
+
+/// Unicode normalization form applied to keys.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnicodeForm {
+    /// Compare code points as they are.
+    #[default]
+    None,
+    /// Canonical composition: precomposed and combining-mark spellings of
+    /// the same letter compare equal.
+    Nfc,
+    /// Compatibility composition: like [`Nfc`](Self::Nfc), and also folds
+    /// full-width and half-width forms, ligatures, and special spaces.
+    Nfkc,
+}
+
+/// How to normalize key values before comparing them.
+///
+/// The default strips HTML, collapses whitespace, and lowercases, which is
+/// what deduplication and diffs have always done.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyNormalization {
+    /// Remove HTML tags.
+    pub strip_html: bool,
+    /// Trim and collapse runs of whitespace to one space.
+    pub collapse_whitespace: bool,
+    /// Compare case-insensitively.
+    pub case_fold: bool,
+    /// Unicode normalization form.
+    pub unicode: UnicodeForm,
+    /// Fold full-width ASCII and the ideographic space to ASCII, and
+    /// half-width katakana to full-width.
+    pub fold_width: bool,
+    /// Remove accents and other combining marks from letters (`é` → `e`).
+    ///
+    /// Kana voicing marks are kept, since `が` and `か` are different
+    /// syllables.
+    pub strip_diacritics: bool,
+    /// Remove punctuation, such as `.`, `!`, `¿`, `“`, and `。`.
+    pub strip_punctuation: bool,
+}
+
+impl Default for KeyNormalization {
+    fn default() -> Self {
+        Self {
+            strip_html: true,
+            collapse_whitespace: true,
+            case_fold: true,
+            unicode: UnicodeForm::None,
+            fold_width: false,
+            strip_diacritics: false,
+            strip_punctuation: false,
+        }
+    }
+}
+
+impl KeyNormalization {
+    /// Compare keys exactly as stored.
+    pub fn none() -> Self {
+        Self {
+            strip_html: false,
+            collapse_whitespace: false,
+            case_fold: false,
+            ..Default::default()
+        }
+    }
+
+    /// The defaults plus NFKC and width folding, for Chinese, Japanese, and
+    /// Korean decks.
+    pub fn cjk() -> Self {
+        Self {
+            unicode: UnicodeForm::Nfkc,
+            fold_width: true,
+            ..Default::default()
+        }
+    }
+
+    /// The defaults plus NFC, diacritic stripping, and punctuation removal,
+    /// for decks where accents are typed inconsistently.
+    pub fn accent_insensitive() -> Self {
+        Self {
+            unicode: UnicodeForm::Nfc,
+            strip_diacritics: true,
+            strip_punctuation: true,
+            ..Default::default()
+        }
+    }
+
+    /// Normalize a key value.
+    pub fn apply(&self, value: &str) -> String {
+        let mut text = if self.strip_html {
+            strip_tags(value)
+        } else {
+            value.to_string()
+        };
+
+        if self.unicode == UnicodeForm::Nfkc {
+            text = text.chars().flat_map(compatibility).collect();
+        } else if self.fold_width {
+            text = text
+                .chars()
+                .flat_map(|c| {
+                    if is_width_variant(c) {
+                        compatibility(c)
+                    } else {
+                        Decomposed::One(c)
+                    }
+                })
+                .collect();
+        }
+        if self.unicode != UnicodeForm::None || self.fold_width || self.strip_diacritics {
+            let mut decomposed = String::with_capacity(text.len());
+            for c in text.chars() {
+                decompose(c, &mut decomposed);
+            }
+            if self.strip_diacritics {
+                decomposed.retain(|c| !is_diacritic(c));
+            }
+            text = compose(&decomposed);
+        }
+
+        if self.strip_punctuation {
+            text.retain(|c| !is_punctuation(c));
+        }
+        if self.case_fold {
+            text = text.to_lowercase();
+        }
+        if self.collapse_whitespace {
+            text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        }
+        text
+    }
+}
+
+/// Remove HTML tags, keeping the text between them.
+fn strip_tags(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut in_tag = false;
+    for c in value.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => result.push(c),
+            _ => {}
+        }
+    }
+    result
+}
+
+/// A character's compatibility decomposition: itself, or a replacement.
+enum Decomposed {
+    One(char),
+    Many(std::str::Chars<'static>),
+}
+
+impl Iterator for Decomposed {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        match self {
+            Decomposed::One(c) => {
+                let c = *c;
+                *self = Decomposed::Many("".chars());
+                Some(c)
+            }
+            Decomposed::Many(chars) => chars.next(),
+        }
+    }
+}
+
+fn compatibility(c: char) -> Decomposed {
+    match COMPATIBILITY.binary_search_by_key(&c, |&(from, _)| from) {
+        Ok(i) => Decomposed::Many(COMPATIBILITY[i].1.chars()),
+        Err(_) => Decomposed::One(c),
+    }
+}
+
+/// Full-width ASCII, the ideographic space, and half-width kana.
+fn is_width_variant(c: char) -> bool {
+    matches!(c, '\u{3000}' | '\u{FF01}'..='\u{FFEE}')
+}
+
+/// Append the canonical decomposition of `c`.
+fn decompose(c: char, out: &mut String) {
+    match COMPOSITIONS.binary_search_by_key(&c, |&(composed, _, _)| composed) {
+        Ok(i) => {
+            let (_, base, mark) = COMPOSITIONS[i];
+            decompose(base, out);
+            out.push(mark);
+        }
+        Err(_) => out.push(c),
+    }
+}
+
+/// Recombine base characters with the combining marks that follow them.
+fn compose(text: &str) -> String {
+    static PAIRS: OnceLock<HashMap<(char, char), char>> = OnceLock::new();
+    let pairs = PAIRS.get_or_init(|| {
+        COMPOSITIONS
+            .iter()
+            .map(|&(composed, base, mark)| ((base, mark), composed))
+            .collect()
+    });
+
+    let mut result = String::with_capacity(text.len());
+    let mut pending: Option<char> = None;
+    for c in text.chars() {
+        if let Some(base) = pending {
+            if let Some(&composed) = pairs.get(&(base, c)) {
+                pending = Some(composed);
+                continue;
+            }
+            result.push(base);
+        }
+        pending = Some(c);
+    }
+    result.extend(pending);
+    result
+}
+
+/// Combining diacritical marks, excluding kana voicing marks.
+fn is_diacritic(c: char) -> bool {
+    matches!(c, '\u{0300}'..='\u{036F}' | '\u{1AB0}'..='\u{1AFF}' | '\u{1DC0}'..='\u{1DFF}')
+}
+
+fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation()
+        || matches!(
+            c,
+            '¡' | '«' | '·' | '»' | '¿'
+                | '\u{2010}'..='\u{2027}'
+                | '\u{2030}'..='\u{205E}'
+                | '\u{3001}'..='\u{3003}'
+                | '\u{3008}'..='\u{3011}'
+                | '\u{3014}'..='\u{301F}'
+                | '\u{30FB}'
+                | '\u{FF01}'..='\u{FF0F}'
+                | '\u{FF1A}'..='\u{FF20}'
+                | '\u{FF3B}'..='\u{FF40}'
+                | '\u{FF5B}'..='\u{FF65}'
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_matches_previous_behavior() {
+        let key = KeyNormalization::default();
+        assert_eq!(key.apply("  <b>Hello</b>   World "), "hello world");
+        assert_eq!(key.apply("Café"), "café");
+    }
+
+    #[test]
+    fn test_nfc_composes_combining_marks() {
+        let key = KeyNormalization {
+            unicode: UnicodeForm::Nfc,
+            ..Default::default()
+        };
+        assert_eq!(key.apply("cafe\u{301}"), "café");
+        // Two marks on one letter, as in Vietnamese
+        assert_eq!(key.apply("e\u{323}\u{302}"), "ệ");
+        assert_eq!(key.apply("か\u{3099}"), "が");
+    }
+
+    #[test]
+    fn test_width_folding() {
+        let key = KeyNormalization {
+            fold_width: true,
+            ..Default::default()
+        };
+        assert_eq!(key.apply("Ａｎｋｉ\u{3000}１２３"), "anki 123");
+        assert_eq!(key.apply("ｶﾞｯｺｳ"), "ガッコウ");
+        // Only width variants are touched
+        assert_eq!(key.apply("ﬁ"), "ﬁ");
+    }
+
+    #[test]
+    fn test_nfkc_folds_compatibility_forms() {
+        let key = KeyNormalization::cjk();
+        assert_eq!(key.apply("ﬁsh"), "fish");
+        assert_eq!(key.apply("x²"), "x2");
+        assert_eq!(key.apply("ﾊﾟﾝ"), key.apply("パン"));
+    }
+
+    #[test]
+    fn test_strip_diacritics_keeps_kana_voicing() {
+        let key = KeyNormalization {
+            strip_diacritics: true,
+            ..Default::default()
+        };
+        assert_eq!(key.apply("Ñandú"), "nandu");
+        assert_eq!(key.apply("Tiếng Việt"), "tieng viet");
+        assert_eq!(key.apply("がっこう"), "がっこう");
+    }
+
+    #[test]
+    fn test_strip_punctuation() {
+        let key = KeyNormalization {
+            strip_punctuation: true,
+            ..Default::default()
+        };
+        assert_eq!(key.apply("¿Qué tal?"), "qué tal");
+        assert_eq!(key.apply("「学校」。"), "学校");
+        assert_eq!(key.apply("rock’n’roll"), "rocknroll");
+    }
+
+    #[test]
+    fn test_none_keeps_value() {
+        assert_eq!(KeyNormalization::none().apply(" <b>A</b> "), " <b>A</b> ");
+    }
+}
@@ -34,6 +34,10 @@
 //!     .build();
 //! ```
 //!
+//! Or read the same settings from `ANKICONNECT_*` environment variables
+//! (and, with the `config` feature, a config file) with
+//! [`AnkiClient::from_env()`]. See the [`config`] module.
+//!
 //! # Action Groups
 //!
 //! Operations are organized into groups accessible from the client:
@@ -68,23 +72,38 @@
 //! ```
 
 pub mod actions;
+mod cache;
 pub mod client;
+pub mod config;
 pub mod error;
+pub mod key;
 pub mod query;
+pub mod render;
 mod request;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod transport;
 pub mod types;
 
-pub use client::{AnkiClient, ClientBuilder};
+pub use ankit_template as template;
+pub use client::{AnkiClient, Capabilities, ClientBuilder};
+pub use config::ClientConfig;
 pub use error::{Error, Result};
+#[cfg(feature = "tracing")]
+pub use request::TracingObserver;
+pub use request::{PrometheusMetrics, RequestEvent, RequestObserver};
+#[cfg(unix)]
+pub use transport::UnixSocketTransport;
+pub use transport::{MockTransport, Transport};
 pub use types::{
-    CanAddResult, CardAnswer, CardInfo, CardModTime, CardTemplate, CreateModelParams, DeckConfig,
-    DeckStats, DuplicateScope, Ease, FieldFont, FindReplaceParams, LapseConfig, MediaAttachment,
-    ModelField, ModelStyling, NewCardConfig, Note, NoteBuilder, NoteField, NoteInfo, NoteModTime,
-    NoteOptions, ReviewConfig, StoreMediaParams,
+    BurySettings, CanAddResult, CardAnswer, CardInfo, CardModTime, CardTemplate, CreateModelParams,
+    DeckConfig, DeckStats, DeckTree, DueSpec, DuplicateScope, Ease, FieldFont, FindReplaceParams,
+    Flag, LapseConfig, MediaAttachment, ModelField, ModelStyling, NewCardConfig, Note, NoteBuilder,
+    NoteField, NoteInfo, NoteModTime, NoteOptions, ReviewConfig, StoreMediaParams, TagTree,
 };
 
 // Re-export types from actions module
-pub use actions::{MultiAction, ReviewEntry};
+pub use actions::{MultiAction, ReviewEntry, ReviewSession, SortOrder};
 
 // Re-export query builder
-pub use query::{OrBuilder, QueryBuilder};
+pub use query::{CardQueue, OrBuilder, QueryBuilder};
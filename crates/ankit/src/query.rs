@@ -42,6 +42,58 @@
 //!     .or(|q| q.tag("verb").tag("noun"))
 //!     .build();
 //! ```
+//!
+//! # Card Properties
+//!
+//! Interval, due, and ease filters take ranges and compile to `prop:` searches:
+//!
+//! ```
+//! use ankit::{CardQueue, Flag, QueryBuilder};
+//!
+//! let query = QueryBuilder::new()
+//!     .queue(CardQueue::Review)
+//!     .interval(7..=30)
+//!     .due(..=0)
+//!     .flagged(Flag::Red)
+//!     .build();
+//!
+//! assert_eq!(query, "is:review prop:ivl>=7 prop:ivl<=30 prop:due<=0 flag:1");
+//! ```
+
+use std::ops::{Bound, RangeBounds};
+
+use crate::types::{Ease, Flag};
+
+/// Card states matched by `is:` searches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CardQueue {
+    /// New cards that have never been studied (`is:new`).
+    New,
+    /// Cards in learning or relearning (`is:learn`).
+    Learn,
+    /// Review cards, including ones being relearned (`is:review`).
+    Review,
+    /// Review and learning cards waiting to be studied (`is:due`).
+    Due,
+    /// Suspended cards (`is:suspended`).
+    Suspended,
+    /// Manually or automatically buried cards (`is:buried`).
+    Buried,
+}
+
+impl CardQueue {
+    /// The `is:` search term for this state.
+    pub fn as_search(self) -> &'static str {
+        match self {
+            CardQueue::New => "is:new",
+            CardQueue::Learn => "is:learn",
+            CardQueue::Review => "is:review",
+            CardQueue::Due => "is:due",
+            CardQueue::Suspended => "is:suspended",
+            CardQueue::Buried => "is:buried",
+        }
+    }
+}
 
 /// A builder for constructing Anki search queries.
 ///
@@ -169,6 +221,14 @@ impl QueryBuilder {
         self
     }
 
+    /// Filter for cards in a state.
+    ///
+    /// Use [`not`](Self::not) to exclude a state.
+    pub fn queue(mut self, queue: CardQueue) -> Self {
+        self.parts.push(queue.as_search().to_string());
+        self
+    }
+
     // ========================================================================
     // Tags
     // ========================================================================
@@ -282,6 +342,67 @@ impl QueryBuilder {
         self
     }
 
+    /// Filter for cards whose interval in days is in a range.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ankit::QueryBuilder;
+    ///
+    /// let q = QueryBuilder::new().interval(7..30).build();
+    /// assert_eq!(q, "prop:ivl>=7 prop:ivl<30");
+    ///
+    /// let q = QueryBuilder::new().interval(21..).build();
+    /// assert_eq!(q, "prop:ivl>=21");
+    /// ```
+    pub fn interval(mut self, days: impl RangeBounds<u32>) -> Self {
+        self.parts
+            .extend(prop_range("ivl", days, |days| days.to_string()));
+        self
+    }
+
+    /// Filter for cards due within a range of days relative to today.
+    ///
+    /// 0 is today, 1 is tomorrow, and negative offsets are overdue. Only
+    /// review and learning cards have a due offset.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ankit::QueryBuilder;
+    ///
+    /// // Overdue by a week or more
+    /// let q = QueryBuilder::new().due(..=-7).build();
+    /// assert_eq!(q, "prop:due<=-7");
+    ///
+    /// // Due in the next three days
+    /// let q = QueryBuilder::new().due(1..=3).build();
+    /// assert_eq!(q, "prop:due>=1 prop:due<=3");
+    /// ```
+    pub fn due(mut self, days: impl RangeBounds<i32>) -> Self {
+        self.parts
+            .extend(prop_range("due", days, |days| days.to_string()));
+        self
+    }
+
+    /// Filter for cards whose ease factor is in a range.
+    ///
+    /// Ease is expressed as a decimal (e.g., 2.5 = 250%).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ankit::QueryBuilder;
+    ///
+    /// let q = QueryBuilder::new().ease(1.3..2.0).build();
+    /// assert_eq!(q, "prop:ease>=1.30 prop:ease<2.00");
+    /// ```
+    pub fn ease(mut self, ease: impl RangeBounds<f32>) -> Self {
+        self.parts
+            .extend(prop_range("ease", ease, |ease| format!("{:.2}", ease)));
+        self
+    }
+
     // ========================================================================
     // Time-based
     // ========================================================================
@@ -310,6 +431,76 @@ impl QueryBuilder {
         self
     }
 
+    /// Filter for cards added within a range of days ago.
+    ///
+    /// 0 is today and 1 is yesterday, so `..7` is the last seven days
+    /// including today. An unbounded end matches cards added at any time
+    /// before the start.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ankit::QueryBuilder;
+    ///
+    /// // This week
+    /// let q = QueryBuilder::new().added(..7).build();
+    /// assert_eq!(q, "added:7");
+    ///
+    /// // Last week, but not this week
+    /// let q = QueryBuilder::new().added(7..14).build();
+    /// assert_eq!(q, "added:14 -added:7");
+    ///
+    /// // More than a month ago
+    /// let q = QueryBuilder::new().added(30..).build();
+    /// assert_eq!(q, "-added:30");
+    /// ```
+    pub fn added(mut self, days_ago: impl RangeBounds<u32>) -> Self {
+        self.parts.extend(days_ago_range("added", days_ago));
+        self
+    }
+
+    /// Filter for notes edited within a range of days ago.
+    ///
+    /// Works like [`added`](Self::added).
+    pub fn edited(mut self, days_ago: impl RangeBounds<u32>) -> Self {
+        self.parts.extend(days_ago_range("edited", days_ago));
+        self
+    }
+
+    /// Filter for cards answered within a range of days ago.
+    ///
+    /// Works like [`added`](Self::added). Anki only searches review
+    /// history up to 365 days back.
+    pub fn rated(mut self, days_ago: impl RangeBounds<u32>) -> Self {
+        self.parts.extend(days_ago_range("rated", days_ago));
+        self
+    }
+
+    /// Filter for cards first answered within a range of days ago.
+    ///
+    /// Works like [`added`](Self::added).
+    pub fn introduced(mut self, days_ago: impl RangeBounds<u32>) -> Self {
+        self.parts.extend(days_ago_range("introduced", days_ago));
+        self
+    }
+
+    /// Filter for cards answered with `ease` within the last N days.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ankit::{Ease, QueryBuilder};
+    ///
+    /// // Failed today
+    /// let q = QueryBuilder::new().rated_as(1, Ease::Again).build();
+    /// assert_eq!(q, "rated:1:1");
+    /// ```
+    pub fn rated_as(mut self, days: u32, ease: Ease) -> Self {
+        self.parts
+            .push(format!("rated:{}:{}", days, i32::from(ease)));
+        self
+    }
+
     // ========================================================================
     // Content Search
     // ========================================================================
@@ -429,6 +620,21 @@ impl QueryBuilder {
         self
     }
 
+    /// Filter for cards with a specific flag.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ankit::{Flag, QueryBuilder};
+    ///
+    /// let q = QueryBuilder::new().flagged(Flag::Blue).build();
+    /// assert_eq!(q, "flag:4");
+    /// ```
+    pub fn flagged(mut self, flag: Flag) -> Self {
+        self.parts.push(format!("flag:{}", flag.number()));
+        self
+    }
+
     // ========================================================================
     // Combinators
     // ========================================================================
@@ -600,6 +806,67 @@ fn quote_if_needed(s: &str) -> String {
     }
 }
 
+/// Compile a range into `prop:` comparisons.
+///
+/// A range covering a single value becomes an equality, and an unbounded
+/// range produces no terms.
+fn prop_range<T: Copy + PartialEq>(
+    prop: &str,
+    range: impl RangeBounds<T>,
+    format: impl Fn(T) -> String,
+) -> Vec<String> {
+    if let (Bound::Included(start), Bound::Included(end)) = (range.start_bound(), range.end_bound())
+    {
+        if start == end {
+            return vec![format!("prop:{}={}", prop, format(*start))];
+        }
+    }
+
+    let mut terms = Vec::new();
+    match range.start_bound() {
+        Bound::Included(start) => terms.push(format!("prop:{}>={}", prop, format(*start))),
+        Bound::Excluded(start) => terms.push(format!("prop:{}>{}", prop, format(*start))),
+        Bound::Unbounded => {}
+    }
+    match range.end_bound() {
+        Bound::Included(end) => terms.push(format!("prop:{}<={}", prop, format(*end))),
+        Bound::Excluded(end) => terms.push(format!("prop:{}<{}", prop, format(*end))),
+        Bound::Unbounded => {}
+    }
+    terms
+}
+
+/// Compile a range of days ago into `atom:N` terms, which match the last N
+/// days including today.
+///
+/// Anki reads `atom:0` as `atom:1`, so a range ending at 0 becomes a pair of
+/// terms that matches nothing.
+fn days_ago_range(atom: &str, range: impl RangeBounds<u32>) -> Vec<String> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => Some(end.saturating_add(1)),
+        Bound::Excluded(&end) => Some(end),
+        Bound::Unbounded => None,
+    };
+
+    if end == Some(0) {
+        return vec![format!("{}:1", atom), format!("-{}:1", atom)];
+    }
+
+    let mut terms = Vec::new();
+    if let Some(end) = end {
+        terms.push(format!("{}:{}", atom, end));
+    }
+    if start > 0 {
+        terms.push(format!("-{}:{}", atom, start));
+    }
+    terms
+}
+
 /// Escape double quotes in a string.
 fn escape_quotes(s: &str) -> String {
     s.replace('"', "\\\"")
@@ -666,6 +933,32 @@ mod tests {
         assert_eq!(q, "prop:lapses>=5 prop:ease<2.10 prop:ivl>30");
     }
 
+    #[test]
+    fn test_property_ranges() {
+        let q = QueryBuilder::new().interval(5..=5).build();
+        assert_eq!(q, "prop:ivl=5");
+
+        let q = QueryBuilder::new().interval(..).build();
+        assert_eq!(q, "");
+
+        let q = QueryBuilder::new().due(-3..0).ease(..=1.3).build();
+        assert_eq!(q, "prop:due>=-3 prop:due<0 prop:ease<=1.30");
+    }
+
+    #[test]
+    fn test_queue_and_flags() {
+        let q = QueryBuilder::new()
+            .queue(CardQueue::Learn)
+            .not(|q| q.queue(CardQueue::Suspended))
+            .flagged(Flag::Purple)
+            .build();
+        assert_eq!(q, "is:learn -is:suspended flag:7");
+
+        assert_eq!(Flag::from_number(3), Some(Flag::Green));
+        assert_eq!(Flag::from_number(0), None);
+        assert_eq!(Flag::from_number(8), None);
+    }
+
     #[test]
     fn test_time_filters() {
         let q = QueryBuilder::new()
@@ -675,6 +968,38 @@ mod tests {
         assert_eq!(q, "added:7 rated:1");
     }
 
+    #[test]
+    fn test_time_ranges() {
+        assert_eq!(QueryBuilder::new().edited(..=6).build(), "edited:7");
+        assert_eq!(
+            QueryBuilder::new().introduced(1..=1).build(),
+            "introduced:2 -introduced:1"
+        );
+        assert_eq!(QueryBuilder::new().rated(..).build(), "");
+        assert_eq!(QueryBuilder::new().added(..0).build(), "added:1 -added:1");
+        assert_eq!(
+            QueryBuilder::new().edited(..=u32::MAX).build(),
+            format!("edited:{}", u32::MAX)
+        );
+        assert_eq!(
+            QueryBuilder::new().added(u32::MAX..).build(),
+            format!("-added:{}", u32::MAX)
+        );
+        assert_eq!(
+            QueryBuilder::new()
+                .rated((Bound::Excluded(u32::MAX), Bound::Unbounded))
+                .build(),
+            format!("-rated:{}", u32::MAX)
+        );
+        assert_eq!(
+            QueryBuilder::new()
+                .added(..7)
+                .rated_as(7, Ease::Hard)
+                .build(),
+            "added:7 rated:7:2"
+        );
+    }
+
     #[test]
     fn test_content_search() {
         let q = QueryBuilder::new().contains("to eat").build();
@@ -0,0 +1,590 @@
+//! Local rendering of Anki card templates.
+//!
+//! This module renders a note's fields through a model's card templates the
+//! way Anki does, so tools can preview a card without opening Anki. It
+//! supports the template syntax used by most note types:
+//!
+//! - Field replacements: `{{Front}}`
+//! - Conditionals: `{{#Field}}...{{/Field}}` and `{{^Field}}...{{/Field}}`
+//! - Filters, including chains: `{{text:Field}}`, `{{cloze:Text}}`,
+//!   `{{hint:Field}}`, `{{type:Field}}`, `{{furigana:Field}}`,
+//!   `{{kana:Field}}`, `{{kanji:Field}}`
+//! - Special fields: `{{FrontSide}}`, `{{Tags}}`, `{{Type}}`, `{{Deck}}`,
+//!   `{{Subdeck}}`, `{{Card}}`
+//!
+//! Unknown filters are ignored. Template errors, such as an unclosed
+//! conditional, are rendered into the card text as Anki does, rather than
+//! returned as errors. Use [`template::parse`](crate::template::parse) to
+//! get the errors, or the parsed template, directly.
+//!
+//! # Example
+//!
+//! ```
+//! use ankit::CardTemplate;
+//! use ankit::render::{RenderContext, render_card};
+//!
+//! let template = CardTemplate {
+//!     front: "{{Front}}".to_string(),
+//!     back: "{{FrontSide}}<hr id=answer>{{Back}}".to_string(),
+//! };
+//! let context = RenderContext::new([("Front", "hola"), ("Back", "hello")]);
+//!
+//! let card = render_card(&template, ".card { color: black; }", &context);
+//! assert_eq!(card.question, "hola");
+//! assert_eq!(card.answer, "hola<hr id=answer>hello");
+//! ```
+
+use std::collections::HashMap;
+
+use crate::template::{self, Filter, Node};
+use crate::types::{CardTemplate, NoteInfo};
+
+/// Note data and card details used to render a template.
+#[derive(Debug, Clone)]
+pub struct RenderContext {
+    /// Field values by field name (HTML).
+    pub fields: HashMap<String, String>,
+    /// Note tags.
+    pub tags: Vec<String>,
+    /// Note type name, for `{{Type}}`.
+    pub note_type: String,
+    /// Full deck name, for `{{Deck}}` and `{{Subdeck}}`.
+    pub deck: String,
+    /// Card template name, for `{{Card}}`.
+    pub card: String,
+    /// Cloze number to render (1-based). Ignored by non-cloze templates.
+    pub cloze: u32,
+}
+
+impl Default for RenderContext {
+    fn default() -> Self {
+        Self {
+            fields: HashMap::new(),
+            tags: Vec::new(),
+            note_type: String::new(),
+            deck: String::new(),
+            card: String::new(),
+            cloze: 1,
+        }
+    }
+}
+
+impl RenderContext {
+    /// Create a context from field name/value pairs.
+    pub fn new<K, V>(fields: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        Self {
+            fields: fields
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Create a context from an existing note's fields, tags, and note type.
+    pub fn from_note(note: &NoteInfo) -> Self {
+        Self::new(
+            note.fields
+                .iter()
+                .map(|(name, field)| (name.clone(), field.value.clone())),
+        )
+        .tags(note.tags.iter().cloned())
+        .note_type(note.model_name.clone())
+    }
+
+    /// Set the note tags.
+    pub fn tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.tags = tags.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the note type name.
+    pub fn note_type(mut self, name: impl Into<String>) -> Self {
+        self.note_type = name.into();
+        self
+    }
+
+    /// Set the deck name.
+    pub fn deck(mut self, name: impl Into<String>) -> Self {
+        self.deck = name.into();
+        self
+    }
+
+    /// Set the card template name.
+    pub fn card(mut self, name: impl Into<String>) -> Self {
+        self.card = name.into();
+        self
+    }
+
+    /// Set the cloze number to render (1-based).
+    pub fn cloze(mut self, number: u32) -> Self {
+        self.cloze = number;
+        self
+    }
+}
+
+/// A rendered card.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedCard {
+    /// Question side HTML.
+    pub question: String,
+    /// Answer side HTML.
+    pub answer: String,
+    /// The model's CSS.
+    pub css: String,
+}
+
+impl RenderedCard {
+    /// A standalone HTML page showing the question side.
+    pub fn question_page(&self) -> String {
+        self.page(&self.question)
+    }
+
+    /// A standalone HTML page showing the answer side.
+    pub fn answer_page(&self) -> String {
+        self.page(&self.answer)
+    }
+
+    fn page(&self, body: &str) -> String {
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<style>\n{}\n</style>\n</head>\n<body>\n<div class=\"card\">\n{}\n</div>\n</body>\n</html>\n",
+            self.css, body
+        )
+    }
+}
+
+/// Render both sides of a card.
+pub fn render_card(template: &CardTemplate, css: &str, context: &RenderContext) -> RenderedCard {
+    let question = render_question(&template.front, context);
+    let answer = render_answer(&template.back, &question, context);
+    RenderedCard {
+        question,
+        answer,
+        css: css.to_string(),
+    }
+}
+
+/// Render the question side of a card.
+pub fn render_question(template: &str, context: &RenderContext) -> String {
+    render(template, context, Side::Question)
+}
+
+/// Render the answer side of a card, given the rendered question for
+/// `{{FrontSide}}`.
+pub fn render_answer(template: &str, question: &str, context: &RenderContext) -> String {
+    render(template, context, Side::Answer { question })
+}
+
+#[derive(Clone, Copy)]
+enum Side<'a> {
+    Question,
+    Answer { question: &'a str },
+}
+
+fn render(template: &str, context: &RenderContext, side: Side<'_>) -> String {
+    match template::parse(template) {
+        Ok(nodes) => {
+            let mut output = String::new();
+            render_nodes(&nodes, context, side, &mut output);
+            output
+        }
+        Err(error) => format!(
+            "<div class=\"template-error\">{}</div>",
+            escape(&error.to_string())
+        ),
+    }
+}
+
+fn render_nodes(nodes: &[Node], context: &RenderContext, side: Side<'_>, output: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text { text, .. } => output.push_str(text),
+            Node::Replacement { field, filters, .. } => {
+                output.push_str(&replacement(field, filters, context, side));
+            }
+            Node::Conditional {
+                field,
+                negated,
+                children,
+                ..
+            } => {
+                let non_empty =
+                    field_value(field, context, side).is_some_and(|value| !field_is_empty(&value));
+                if non_empty != *negated {
+                    render_nodes(children, context, side, output);
+                }
+            }
+        }
+    }
+}
+
+/// The value of a field or special field.
+fn field_value(key: &str, context: &RenderContext, side: Side<'_>) -> Option<String> {
+    if let Some(value) = context.fields.get(key) {
+        return Some(value.clone());
+    }
+    match key {
+        "FrontSide" => Some(match side {
+            Side::Question => String::new(),
+            Side::Answer { question } => question.to_string(),
+        }),
+        "Tags" => Some(context.tags.join(" ")),
+        "Type" => Some(context.note_type.clone()),
+        "Deck" => Some(context.deck.clone()),
+        "Subdeck" => Some(
+            context
+                .deck
+                .rsplit("::")
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+        ),
+        "Card" => Some(context.card.clone()),
+        _ => None,
+    }
+}
+
+/// Render a `{{filter:...:Field}}` replacement. Filters apply from the one
+/// nearest the field outwards.
+fn replacement(key: &str, filters: &[Filter], context: &RenderContext, side: Side<'_>) -> String {
+    // An empty key is how Anki spells a literal `{{}}`
+    if key.is_empty() && filters.is_empty() {
+        return "{{}}".to_string();
+    }
+    let Some(mut value) = field_value(key, context, side) else {
+        return format!("{{unknown field {}}}", escape(key));
+    };
+
+    for filter in filters.iter().rev() {
+        value = match filter {
+            Filter::Text => strip_html(&value),
+            Filter::Cloze => cloze(&value, context.cloze, side),
+            Filter::ClozeOnly => cloze_only(&value, context.cloze),
+            Filter::Hint => hint(key, &value),
+            Filter::Type => type_answer(&value, side),
+            Filter::Furigana => ruby(&value, Ruby::Furigana),
+            Filter::Kana => ruby(&value, Ruby::Kana),
+            Filter::Kanji => ruby(&value, Ruby::Kanji),
+            Filter::Other(_) => value,
+        };
+    }
+    value
+}
+
+/// Whether a field counts as empty for conditionals.
+fn field_is_empty(value: &str) -> bool {
+    let mut rest = value;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '\u{a0}');
+        let next = ["<br>", "<br/>", "<br />", "<div>", "</div>", "&nbsp;"]
+            .iter()
+            .find_map(|tag| rest.strip_prefix(tag));
+        match next {
+            Some(after) => rest = after,
+            None => return rest.is_empty(),
+        }
+    }
+}
+
+/// A parsed `{{cN::text::hint}}` deletion.
+struct Deletion<'a> {
+    ordinal: u32,
+    text: &'a str,
+    hint: Option<&'a str>,
+}
+
+/// Split text into plain segments and cloze deletions, calling `f` with
+/// each deletion to produce its replacement.
+fn replace_clozes(text: &str, f: &mut dyn FnMut(Deletion<'_>) -> String) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{c") {
+        let after = &rest[start + 3..];
+        let digits = after.chars().take_while(char::is_ascii_digit).count();
+        let body = &after[digits..];
+        if digits == 0 || !body.starts_with("::") {
+            output.push_str(&rest[..start + 3]);
+            rest = after;
+            continue;
+        }
+        let Some(end) = matching_close(&body[2..]) else {
+            break;
+        };
+
+        let ordinal = after[..digits].parse().unwrap_or(0);
+        let inner = &body[2..2 + end];
+        let (text, hint) = match inner.rfind("::") {
+            Some(i) if !inner[i..].contains("}}") => (&inner[..i], Some(&inner[i + 2..])),
+            _ => (inner, None),
+        };
+
+        output.push_str(&rest[..start]);
+        let text = replace_clozes(text, f);
+        output.push_str(&f(Deletion {
+            ordinal,
+            text: &text,
+            hint,
+        }));
+        rest = &body[2 + end + 2..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Position of the `}}` that closes a cloze, allowing nested clozes.
+fn matching_close(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = 0;
+    while i + 1 < text.len() {
+        match &text.as_bytes()[i..i + 2] {
+            b"{{" => {
+                depth += 1;
+                i += 2;
+            }
+            b"}}" if depth == 0 => return Some(i),
+            b"}}" => {
+                depth -= 1;
+                i += 2;
+            }
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// The `cloze` filter.
+fn cloze(text: &str, ordinal: u32, side: Side<'_>) -> String {
+    replace_clozes(text, &mut |deletion| {
+        if deletion.ordinal != ordinal {
+            return format!(
+                "<span class=\"cloze-inactive\" data-ordinal=\"{}\">{}</span>",
+                deletion.ordinal, deletion.text
+            );
+        }
+        let shown = match side {
+            Side::Question => format!("[{}]", deletion.hint.unwrap_or("...")),
+            Side::Answer { .. } => deletion.text.to_string(),
+        };
+        format!(
+            "<span class=\"cloze\" data-ordinal=\"{}\">{}</span>",
+            deletion.ordinal, shown
+        )
+    })
+}
+
+/// The `cloze-only` filter: the text of the active deletions.
+fn cloze_only(text: &str, ordinal: u32) -> String {
+    let mut active = Vec::new();
+    replace_clozes(text, &mut |deletion| {
+        if deletion.ordinal == ordinal {
+            active.push(deletion.text.to_string());
+        }
+        String::new()
+    });
+    active.join(", ")
+}
+
+/// The `hint` filter: a link that reveals the field.
+fn hint(key: &str, value: &str) -> String {
+    if field_is_empty(value) {
+        return String::new();
+    }
+    format!(
+        "<a class=\"hint\" href=\"#\" onclick=\"this.style.display='none';\
+         this.nextElementSibling.style.display='block';return false;\">{}</a>\
+         <div class=\"hint\" style=\"display: none\">{}</div>",
+        escape(key),
+        value
+    )
+}
+
+/// The `type` filter: an input box on the question, the expected answer on
+/// the answer side.
+fn type_answer(value: &str, side: Side<'_>) -> String {
+    match side {
+        Side::Question => "<input type=\"text\" id=\"typeans\">".to_string(),
+        Side::Answer { .. } => format!("<code id=\"typeans\">{}</code>", strip_html(value)),
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Ruby {
+    Furigana,
+    Kana,
+    Kanji,
+}
+
+/// The `furigana`, `kana`, and `kanji` filters for `漢字[かんじ]` readings.
+fn ruby(text: &str, mode: Ruby) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(open) = rest.find('[') {
+        let Some(len) = rest[open..].find(']') else {
+            break;
+        };
+        let reading = &rest[open + 1..open + len];
+        if reading.starts_with("sound:") {
+            output.push_str(&rest[..open + len + 1]);
+            rest = &rest[open + len + 1..];
+            continue;
+        }
+
+        // The base text runs back to the previous space or tag; a separating
+        // space is dropped
+        let before = &rest[..open];
+        let base_start = before.rfind([' ', '>']).map_or(0, |i| i + 1);
+        let base = &before[base_start..];
+        let prefix = &before[..base_start];
+        output.push_str(prefix.strip_suffix(' ').unwrap_or(prefix));
+
+        match mode {
+            Ruby::Furigana => {
+                output.push_str(&format!(
+                    "<ruby><rb>{}</rb><rt>{}</rt></ruby>",
+                    base, reading
+                ));
+            }
+            Ruby::Kana => output.push_str(reading),
+            Ruby::Kanji => output.push_str(base),
+        }
+        rest = &rest[open + len + 1..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Strip HTML tags and decode common entities.
+fn strip_html(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+}
+
+/// Escape text for inclusion in HTML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(fields: &[(&str, &str)]) -> RenderContext {
+        RenderContext::new(fields.iter().copied())
+    }
+
+    #[test]
+    fn test_conditionals() {
+        let ctx = context(&[("Front", "a"), ("Extra", "<br>")]);
+        let template = "{{Front}}{{#Extra}} [{{Extra}}]{{/Extra}}{{^Extra}} (none){{/Extra}}";
+
+        assert_eq!(render_question(template, &ctx), "a (none)");
+    }
+
+    #[test]
+    fn test_front_side_and_special_fields() {
+        let ctx = context(&[("Front", "q")])
+            .tags(["verb", "es"])
+            .deck("Spanish::Verbs")
+            .note_type("Basic");
+
+        assert_eq!(
+            render_answer("{{FrontSide}}|{{Tags}}", "Q", &ctx),
+            "Q|verb es"
+        );
+        assert_eq!(render_question("{{Subdeck}} {{Type}}", &ctx), "Verbs Basic");
+        assert_eq!(render_question("[{{FrontSide}}]", &ctx), "[]");
+    }
+
+    #[test]
+    fn test_cloze() {
+        let ctx = context(&[("Text", "{{c1::Paris}} is in {{c2::France::country}}")]);
+        let template = CardTemplate {
+            front: "{{cloze:Text}}".to_string(),
+            back: "{{cloze:Text}}".to_string(),
+        };
+
+        let card1 = render_card(&template, "", &ctx);
+        assert_eq!(
+            card1.question,
+            "<span class=\"cloze\" data-ordinal=\"1\">[...]</span> is in \
+             <span class=\"cloze-inactive\" data-ordinal=\"2\">France</span>"
+        );
+        assert!(
+            card1
+                .answer
+                .starts_with("<span class=\"cloze\" data-ordinal=\"1\">Paris</span>")
+        );
+
+        let card2 = render_card(&template, "", &ctx.clone().cloze(2));
+        assert!(
+            card2
+                .question
+                .ends_with("<span class=\"cloze\" data-ordinal=\"2\">[country]</span>")
+        );
+    }
+
+    #[test]
+    fn test_nested_cloze() {
+        let ctx = context(&[("Text", "{{c1::a {{c2::b}} c}}")]);
+
+        assert_eq!(
+            render_answer("{{cloze:Text}}", "", &ctx),
+            "<span class=\"cloze\" data-ordinal=\"1\">a \
+             <span class=\"cloze-inactive\" data-ordinal=\"2\">b</span> c</span>"
+        );
+    }
+
+    #[test]
+    fn test_filters() {
+        let ctx = context(&[("Word", "<b>日本[にほん]</b> 語[ご]")]);
+
+        assert_eq!(
+            render_question("{{text:Word}}", &ctx),
+            "日本[にほん] 語[ご]"
+        );
+        assert_eq!(render_question("{{kana:text:Word}}", &ctx), "にほんご");
+        assert_eq!(
+            render_question("{{furigana:text:Word}}", &ctx),
+            "<ruby><rb>日本</rb><rt>にほん</rt></ruby><ruby><rb>語</rb><rt>ご</rt></ruby>"
+        );
+    }
+
+    #[test]
+    fn test_template_errors() {
+        let ctx = context(&[("Front", "a")]);
+
+        assert!(render_question("{{#Front}}x", &ctx).contains("Missing"));
+        assert!(render_question("{{#Front}}x{{/Back}}", &ctx).contains("expected"));
+        assert_eq!(
+            render_question("{{Missing}}", &ctx),
+            "{unknown field Missing}"
+        );
+    }
+}
@@ -1,6 +1,14 @@
-//! Internal request and response types for the AnkiConnect protocol.
+//! Request and response types for the AnkiConnect protocol, and hooks for
+//! observing every request the client sends.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
 
 /// The request format expected by AnkiConnect.
 #[derive(Debug, Serialize)]
@@ -47,3 +55,352 @@ pub(crate) struct AnkiResponse<T> {
     /// The error message, if the action failed.
     pub error: Option<String>,
 }
+
+/// Client-side throttle applied to every outgoing request.
+///
+/// Limits how fast requests are started (`max_per_second`) and how many may be
+/// in flight at once (`max_concurrent`). Clones of a client share the same
+/// throttle, so the limits hold across every action group.
+#[derive(Debug)]
+pub(crate) struct Throttle {
+    /// Minimum spacing between request starts.
+    interval: Option<Duration>,
+    /// The earliest instant the next request may start.
+    next_slot: Mutex<Option<Instant>>,
+    /// Caps the number of in-flight requests.
+    semaphore: Option<Arc<Semaphore>>,
+}
+
+impl Throttle {
+    /// Create a throttle, or `None` if neither limit is set.
+    pub fn new(max_per_second: Option<u32>, max_concurrent: Option<usize>) -> Option<Self> {
+        let interval = max_per_second
+            .filter(|&n| n > 0)
+            .map(|n| Duration::from_secs(1) / n);
+        let semaphore = max_concurrent
+            .filter(|&n| n > 0)
+            .map(|n| Arc::new(Semaphore::new(n)));
+
+        if interval.is_none() && semaphore.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            interval,
+            next_slot: Mutex::new(None),
+            semaphore,
+        })
+    }
+
+    /// Wait until a request may be sent.
+    ///
+    /// The returned permit (if any) must be held until the response has been read.
+    pub async fn acquire(&self) -> Option<OwnedSemaphorePermit> {
+        let permit = match &self.semaphore {
+            Some(semaphore) => Some(
+                semaphore
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .expect("throttle semaphore is never closed"),
+            ),
+            None => None,
+        };
+
+        if let Some(interval) = self.interval {
+            let start = {
+                let mut next = self.next_slot.lock().expect("throttle lock poisoned");
+                let now = Instant::now();
+                let start = next.map_or(now, |slot| slot.max(now));
+                *next = Some(start + interval);
+                start
+            };
+            tokio::time::sleep_until(start).await;
+        }
+
+        permit
+    }
+}
+
+/// What happened during one AnkiConnect request.
+///
+/// Passed to every [`RequestObserver`] after the response has been read.
+#[derive(Debug, Clone)]
+pub struct RequestEvent<'a> {
+    /// The action that was called, such as `"deckNames"`.
+    pub action: &'a str,
+    /// Size of the JSON request body in bytes.
+    pub request_bytes: usize,
+    /// Size of the response body in bytes (0 if no response arrived).
+    pub response_bytes: usize,
+    /// Time from sending the request to reading the response. Time spent
+    /// waiting on the client's throttle is not included.
+    pub duration: Duration,
+    /// The error AnkiConnect returned, or the transport failure, if the
+    /// request failed.
+    pub error: Option<&'a str>,
+}
+
+impl RequestEvent<'_> {
+    /// Whether the request succeeded.
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// A hook called for every AnkiConnect request.
+///
+/// Register observers with
+/// [`ClientBuilder::observer`](crate::ClientBuilder::observer). They are
+/// called synchronously on the request path, so they should be quick.
+///
+/// # Example
+///
+/// ```
+/// use ankit::{RequestEvent, RequestObserver};
+///
+/// struct SlowRequests;
+///
+/// impl RequestObserver for SlowRequests {
+///     fn on_request(&self, event: &RequestEvent<'_>) {
+///         if event.duration.as_secs() >= 1 {
+///             eprintln!("{} took {:?}", event.action, event.duration);
+///         }
+///     }
+/// }
+/// ```
+pub trait RequestObserver: Send + Sync {
+    /// Called once a request has completed or failed.
+    fn on_request(&self, event: &RequestEvent<'_>);
+}
+
+impl<T: RequestObserver + ?Sized> RequestObserver for Arc<T> {
+    fn on_request(&self, event: &RequestEvent<'_>) {
+        (**self).on_request(event);
+    }
+}
+
+/// The observers registered on a client.
+#[derive(Clone, Default)]
+pub(crate) struct Observers(Vec<Arc<dyn RequestObserver>>);
+
+impl Observers {
+    pub fn push(&mut self, observer: Arc<dyn RequestObserver>) {
+        self.0.push(observer);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn notify(&self, event: &RequestEvent<'_>) {
+        for observer in &self.0 {
+            observer.on_request(event);
+        }
+    }
+}
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Observers({})", self.0.len())
+    }
+}
+
+/// A [`RequestObserver`] that emits a `tracing` event per request.
+///
+/// Successful requests are logged at `DEBUG` and failures at `WARN`, under
+/// the `ankit::request` target.
+///
+/// # Example
+///
+/// ```
+/// use ankit::{AnkiClient, TracingObserver};
+///
+/// let client = AnkiClient::builder().observer(TracingObserver).build();
+/// ```
+#[cfg(feature = "tracing")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TracingObserver;
+
+#[cfg(feature = "tracing")]
+impl RequestObserver for TracingObserver {
+    fn on_request(&self, event: &RequestEvent<'_>) {
+        let duration_ms = event.duration.as_secs_f64() * 1000.0;
+        match event.error {
+            None => tracing::debug!(
+                target: "ankit::request",
+                action = event.action,
+                request_bytes = event.request_bytes,
+                response_bytes = event.response_bytes,
+                duration_ms,
+                "AnkiConnect request"
+            ),
+            Some(error) => tracing::warn!(
+                target: "ankit::request",
+                action = event.action,
+                request_bytes = event.request_bytes,
+                response_bytes = event.response_bytes,
+                duration_ms,
+                error,
+                "AnkiConnect request failed"
+            ),
+        }
+    }
+}
+
+/// A [`RequestObserver`] that keeps per-action counters and renders them in
+/// the Prometheus text exposition format.
+///
+/// Serve [`render`](Self::render) from a `/metrics` endpoint to scrape it.
+/// The exported metrics are:
+///
+/// - `ankit_requests_total{action, outcome}` - requests by outcome
+///   (`success` or `error`)
+/// - `ankit_request_duration_seconds{action}` - a summary of request time
+/// - `ankit_request_bytes_total{action, direction}` - bytes `sent` and
+///   `received`
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use ankit::{AnkiClient, PrometheusMetrics};
+///
+/// let metrics = Arc::new(PrometheusMetrics::new());
+/// let client = AnkiClient::builder().observer(metrics.clone()).build();
+///
+/// // Later, in the metrics handler
+/// let body = metrics.render();
+/// ```
+#[derive(Debug, Default)]
+pub struct PrometheusMetrics {
+    actions: Mutex<BTreeMap<String, ActionMetrics>>,
+}
+
+/// Counters for one action.
+#[derive(Debug, Default, Clone, Copy)]
+struct ActionMetrics {
+    successes: u64,
+    errors: u64,
+    duration_seconds: f64,
+    bytes_sent: u64,
+    bytes_received: u64,
+}
+
+impl PrometheusMetrics {
+    /// Create an empty set of metrics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Render the metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let actions = self.actions.lock().expect("metrics lock poisoned");
+        let mut out = String::new();
+
+        out.push_str("# HELP ankit_requests_total AnkiConnect requests by action and outcome.\n");
+        out.push_str("# TYPE ankit_requests_total counter\n");
+        for (action, m) in actions.iter() {
+            let action = escape_label(action);
+            out.push_str(&format!(
+                "ankit_requests_total{{action=\"{}\",outcome=\"success\"}} {}\n",
+                action, m.successes
+            ));
+            out.push_str(&format!(
+                "ankit_requests_total{{action=\"{}\",outcome=\"error\"}} {}\n",
+                action, m.errors
+            ));
+        }
+
+        out.push_str("# HELP ankit_request_duration_seconds Time spent on AnkiConnect requests.\n");
+        out.push_str("# TYPE ankit_request_duration_seconds summary\n");
+        for (action, m) in actions.iter() {
+            let action = escape_label(action);
+            out.push_str(&format!(
+                "ankit_request_duration_seconds_sum{{action=\"{}\"}} {}\n",
+                action, m.duration_seconds
+            ));
+            out.push_str(&format!(
+                "ankit_request_duration_seconds_count{{action=\"{}\"}} {}\n",
+                action,
+                m.successes + m.errors
+            ));
+        }
+
+        out.push_str("# HELP ankit_request_bytes_total Bytes exchanged with AnkiConnect.\n");
+        out.push_str("# TYPE ankit_request_bytes_total counter\n");
+        for (action, m) in actions.iter() {
+            let action = escape_label(action);
+            out.push_str(&format!(
+                "ankit_request_bytes_total{{action=\"{}\",direction=\"sent\"}} {}\n",
+                action, m.bytes_sent
+            ));
+            out.push_str(&format!(
+                "ankit_request_bytes_total{{action=\"{}\",direction=\"received\"}} {}\n",
+                action, m.bytes_received
+            ));
+        }
+
+        out
+    }
+}
+
+impl RequestObserver for PrometheusMetrics {
+    fn on_request(&self, event: &RequestEvent<'_>) {
+        let mut actions = self.actions.lock().expect("metrics lock poisoned");
+        let m = actions.entry(event.action.to_string()).or_default();
+        if event.is_success() {
+            m.successes += 1;
+        } else {
+            m.errors += 1;
+        }
+        m.duration_seconds += event.duration.as_secs_f64();
+        m.bytes_sent += event.request_bytes as u64;
+        m.bytes_received += event.response_bytes as u64;
+    }
+}
+
+/// Escape a Prometheus label value.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prometheus_render() {
+        let metrics = PrometheusMetrics::new();
+        let event = |error| RequestEvent {
+            action: "deckNames",
+            request_bytes: 40,
+            response_bytes: 60,
+            duration: Duration::from_millis(250),
+            error,
+        };
+        metrics.on_request(&event(None));
+        metrics.on_request(&event(Some("boom")));
+
+        let text = metrics.render();
+        assert!(
+            text.contains("ankit_requests_total{action=\"deckNames\",outcome=\"success\"} 1\n")
+        );
+        assert!(text.contains("ankit_requests_total{action=\"deckNames\",outcome=\"error\"} 1\n"));
+        assert!(text.contains("ankit_request_duration_seconds_sum{action=\"deckNames\"} 0.5\n"));
+        assert!(text.contains("ankit_request_duration_seconds_count{action=\"deckNames\"} 2\n"));
+        assert!(
+            text.contains(
+                "ankit_request_bytes_total{action=\"deckNames\",direction=\"sent\"} 80\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_escape_label() {
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
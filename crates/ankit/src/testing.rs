@@ -0,0 +1,308 @@
+//! A fake AnkiConnect server for tests (requires the `testing` feature).
+//!
+//! [`MockAnkiServer`] listens on a local port and answers AnkiConnect
+//! requests over HTTP, so code under test talks to it exactly as it would to
+//! Anki. Each action is answered, in order of precedence, by:
+//!
+//! 1. a handler registered with [`MockAnkiServer::handle`],
+//! 2. a canned result or error from [`respond`](MockAnkiServer::respond) or
+//!    [`error`](MockAnkiServer::error),
+//! 3. the built-in deck simulation (`deckNames`, `deckNamesAndIds`,
+//!    `createDeck`, `deleteDecks`), plus `version` and `multi`.
+//!
+//! Anything else fails with `unsupported action`, as AnkiConnect does.
+//!
+//! # Example
+//!
+//! ```
+//! use ankit::testing::MockAnkiServer;
+//!
+//! # async fn example() -> ankit::Result<()> {
+//! let server = MockAnkiServer::start().await;
+//! server.respond("getProfiles", serde_json::json!(["User 1"]));
+//!
+//! let client = server.client();
+//! client.decks().create("Japanese").await?;
+//! assert!(client.decks().names().await?.contains(&"Japanese".to_string()));
+//! assert_eq!(client.misc().profiles().await?, vec!["User 1"]);
+//! assert_eq!(server.requests_for("createDeck").len(), 1);
+//! # Ok(())
+//! # }
+//! # tokio::runtime::Runtime::new().unwrap().block_on(example()).unwrap();
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use serde_json::{Value, json};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+use crate::AnkiClient;
+
+/// A handler for one action: receives the request `params` (or `null`) and
+/// returns the result or an AnkiConnect error message.
+type Handler = Arc<dyn Fn(&Value) -> std::result::Result<Value, String> + Send + Sync>;
+
+/// A programmable fake AnkiConnect server.
+///
+/// The server runs on the current Tokio runtime until dropped.
+pub struct MockAnkiServer {
+    addr: SocketAddr,
+    state: Arc<State>,
+    task: JoinHandle<()>,
+}
+
+#[derive(Default)]
+struct State {
+    handlers: Mutex<HashMap<String, Handler>>,
+    answers: Mutex<HashMap<String, std::result::Result<Value, String>>>,
+    decks: Mutex<BTreeMap<String, i64>>,
+    requests: Mutex<Vec<Value>>,
+}
+
+impl MockAnkiServer {
+    /// Start a server on a free local port with only the `Default` deck.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no local port can be bound.
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to bind mock AnkiConnect server");
+        let addr = listener.local_addr().expect("listener has no address");
+        let state = Arc::new(State::default());
+        state.lock_decks().insert("Default".to_string(), 1);
+
+        let task = tokio::spawn({
+            let state = state.clone();
+            async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    tokio::spawn(serve(stream, state.clone()));
+                }
+            }
+        });
+        Self { addr, state, task }
+    }
+
+    /// The server's base URL, e.g. `http://127.0.0.1:49152`.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// A client connected to this server.
+    pub fn client(&self) -> AnkiClient {
+        AnkiClient::builder().url(self.url()).build()
+    }
+
+    /// Answer `action` with `result`.
+    pub fn respond(&self, action: impl Into<String>, result: Value) -> &Self {
+        self.state.lock_answers().insert(action.into(), Ok(result));
+        self
+    }
+
+    /// Answer `action` with an AnkiConnect error message.
+    pub fn error(&self, action: impl Into<String>, message: impl Into<String>) -> &Self {
+        self.state
+            .lock_answers()
+            .insert(action.into(), Err(message.into()));
+        self
+    }
+
+    /// Answer `action` by calling `handler` with the request params.
+    ///
+    /// Handlers can capture shared state to simulate actions the server
+    /// doesn't model itself.
+    pub fn handle<F>(&self, action: impl Into<String>, handler: F) -> &Self
+    where
+        F: Fn(&Value) -> std::result::Result<Value, String> + Send + Sync + 'static,
+    {
+        self.state
+            .handlers
+            .lock()
+            .expect("mock lock poisoned")
+            .insert(action.into(), Arc::new(handler));
+        self
+    }
+
+    /// Add decks to the simulated collection.
+    pub fn with_decks<I, S>(&self, names: I) -> &Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        for name in names {
+            self.state.create_deck(name.into());
+        }
+        self
+    }
+
+    /// Names of the decks in the simulated collection.
+    pub fn decks(&self) -> Vec<String> {
+        self.state.lock_decks().keys().cloned().collect()
+    }
+
+    /// Every request received so far, as JSON (`action`, `version`, `params`).
+    pub fn requests(&self) -> Vec<Value> {
+        self.state
+            .requests
+            .lock()
+            .expect("mock lock poisoned")
+            .clone()
+    }
+
+    /// Requests received so far for `action`.
+    pub fn requests_for(&self, action: &str) -> Vec<Value> {
+        self.requests()
+            .into_iter()
+            .filter(|request| request["action"] == action)
+            .collect()
+    }
+}
+
+impl Drop for MockAnkiServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl std::fmt::Debug for MockAnkiServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockAnkiServer")
+            .field("addr", &self.addr)
+            .finish_non_exhaustive()
+    }
+}
+
+impl State {
+    fn lock_answers(
+        &self,
+    ) -> std::sync::MutexGuard<'_, HashMap<String, std::result::Result<Value, String>>> {
+        self.answers.lock().expect("mock lock poisoned")
+    }
+
+    fn lock_decks(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, i64>> {
+        self.decks.lock().expect("mock lock poisoned")
+    }
+
+    fn create_deck(&self, name: String) -> i64 {
+        let mut decks = self.lock_decks();
+        let next = decks.values().max().copied().unwrap_or(0) + 1;
+        *decks.entry(name).or_insert(next)
+    }
+
+    /// Record a request and build its response envelope.
+    fn answer(&self, request: Value) -> Value {
+        self.requests
+            .lock()
+            .expect("mock lock poisoned")
+            .push(request.clone());
+        envelope(self.dispatch(&request))
+    }
+
+    fn dispatch(&self, request: &Value) -> std::result::Result<Value, String> {
+        let action = request["action"].as_str().unwrap_or_default();
+        let params = &request["params"];
+
+        let handler = self
+            .handlers
+            .lock()
+            .expect("mock lock poisoned")
+            .get(action)
+            .cloned();
+        if let Some(handler) = handler {
+            return handler(params);
+        }
+        if let Some(answer) = self.lock_answers().get(action) {
+            return answer.clone();
+        }
+
+        match action {
+            "version" => Ok(json!(6)),
+            "deckNames" => Ok(json!(self.lock_decks().keys().collect::<Vec<_>>())),
+            "deckNamesAndIds" => Ok(json!(*self.lock_decks())),
+            "createDeck" => {
+                let name = params["deck"].as_str().ok_or("missing deck name")?;
+                Ok(json!(self.create_deck(name.to_string())))
+            }
+            "deleteDecks" => {
+                let mut decks = self.lock_decks();
+                for name in params["decks"].as_array().into_iter().flatten() {
+                    if let Some(name) = name.as_str() {
+                        decks.remove(name);
+                    }
+                }
+                Ok(Value::Null)
+            }
+            "multi" => Ok(params["actions"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|action| envelope(self.dispatch(action)))
+                .collect()),
+            _ => Err("unsupported action".to_string()),
+        }
+    }
+}
+
+fn envelope(result: std::result::Result<Value, String>) -> Value {
+    match result {
+        Ok(result) => json!({ "result": result, "error": null }),
+        Err(error) => json!({ "result": null, "error": error }),
+    }
+}
+
+/// Serve HTTP/1.1 requests on one connection until the client closes it.
+async fn serve(mut stream: TcpStream, state: Arc<State>) {
+    let mut buffer = Vec::new();
+    loop {
+        let Some(body) = read_request(&mut stream, &mut buffer).await else {
+            return;
+        };
+        let response = match serde_json::from_slice::<Value>(&body) {
+            Ok(request) => state.answer(request),
+            Err(e) => envelope(Err(format!("invalid request: {}", e))),
+        };
+        let body = response.to_string();
+        let head = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+            body.len()
+        );
+        if stream.write_all(head.as_bytes()).await.is_err()
+            || stream.write_all(body.as_bytes()).await.is_err()
+        {
+            return;
+        }
+    }
+}
+
+/// Read one request from the connection and return its body.
+///
+/// Bytes past the end of the request stay in `buffer` for the next call.
+async fn read_request(stream: &mut TcpStream, buffer: &mut Vec<u8>) -> Option<Vec<u8>> {
+    let mut chunk = [0u8; 8192];
+    loop {
+        if let Some(split) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            let head = String::from_utf8_lossy(&buffer[..split]).to_ascii_lowercase();
+            let length = head
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .and_then(|value| value.trim().parse::<usize>().ok())
+                .unwrap_or(0);
+            let end = split + 4 + length;
+            if buffer.len() >= end {
+                let body = buffer[split + 4..end].to_vec();
+                buffer.drain(..end);
+                return Some(body);
+            }
+        }
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+    }
+}
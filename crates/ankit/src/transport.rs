@@ -0,0 +1,292 @@
+//! Pluggable transports for AnkiConnect requests.
+//!
+//! The client serializes each request to JSON and hands the body to a
+//! [`Transport`], which returns the raw JSON response. By default requests
+//! are posted over HTTP(S) to the configured URL; set another transport with
+//! [`ClientBuilder::transport`](crate::ClientBuilder::transport):
+//!
+//! - [`UnixSocketTransport`] speaks HTTP over a Unix domain socket, for
+//!   AnkiConnect behind a local proxy (Unix only).
+//! - [`MockTransport`] answers in-process with canned results, for tests
+//!   that don't need a mock HTTP server.
+//!
+//! Throttling, observers, and capability detection apply to every transport.
+//!
+//! # Example
+//!
+//! ```
+//! use ankit::{AnkiClient, MockTransport};
+//!
+//! # async fn example() -> ankit::Result<()> {
+//! let mock = MockTransport::new()
+//!     .respond("deckNames", serde_json::json!(["Default", "Japanese"]))
+//!     .error("deleteDecks", "decks are read-only in this test");
+//! let client = AnkiClient::builder().transport(mock.clone()).build();
+//!
+//! assert_eq!(client.decks().names().await?, vec!["Default", "Japanese"]);
+//! assert_eq!(mock.requests()[0]["action"], "deckNames");
+//! # Ok(())
+//! # }
+//! # tokio::runtime::Runtime::new().unwrap().block_on(example()).unwrap();
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use reqwest::Client;
+use reqwest::header::CONTENT_TYPE;
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+
+/// Future returned by [`Transport::send`].
+pub type TransportFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<u8>>> + Send + 'a>>;
+
+/// Delivers serialized AnkiConnect requests.
+pub trait Transport: Send + Sync {
+    /// Send a JSON request body and return the JSON response body.
+    ///
+    /// Return [`Error::ConnectionRefused`] when AnkiConnect can't be reached,
+    /// so callers can tell that apart from other failures.
+    fn send(&self, body: Vec<u8>) -> TransportFuture<'_>;
+}
+
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    fn send(&self, body: Vec<u8>) -> TransportFuture<'_> {
+        (**self).send(body)
+    }
+}
+
+/// A transport shared by a builder and the clients it builds.
+#[derive(Clone)]
+pub(crate) struct SharedTransport(pub Arc<dyn Transport>);
+
+impl fmt::Debug for SharedTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Transport")
+    }
+}
+
+/// The default transport: HTTP POST to the AnkiConnect URL.
+pub(crate) struct HttpTransport {
+    pub client: Client,
+    pub url: String,
+}
+
+impl Transport for HttpTransport {
+    fn send(&self, body: Vec<u8>) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let response = self
+                .client
+                .post(&self.url)
+                .header(CONTENT_TYPE, "application/json")
+                .body(body)
+                .send()
+                .await
+                .map_err(|e| {
+                    if e.is_connect() {
+                        Error::ConnectionRefused
+                    } else {
+                        Error::Http(e)
+                    }
+                })?;
+            Ok(response.bytes().await?.to_vec())
+        })
+    }
+}
+
+/// An in-process transport that answers with canned results.
+///
+/// Actions without a canned answer fail with an AnkiConnect error, as an
+/// unknown action would. Every request is recorded, and clones share the
+/// same answers and recorded requests, so keep a clone to inspect them.
+#[derive(Debug, Clone, Default)]
+pub struct MockTransport {
+    answers: Arc<Mutex<HashMap<String, Value>>>,
+    requests: Arc<Mutex<Vec<Value>>>,
+}
+
+impl MockTransport {
+    /// Create a transport with no canned answers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer `action` with `result`.
+    pub fn respond(self, action: impl Into<String>, result: Value) -> Self {
+        self.answer(
+            action,
+            serde_json::json!({ "result": result, "error": null }),
+        )
+    }
+
+    /// Answer `action` with an AnkiConnect error message.
+    pub fn error(self, action: impl Into<String>, message: impl Into<String>) -> Self {
+        let message = message.into();
+        self.answer(
+            action,
+            serde_json::json!({ "result": null, "error": message }),
+        )
+    }
+
+    /// Every request sent so far, as JSON (`action`, `version`, `params`).
+    pub fn requests(&self) -> Vec<Value> {
+        self.requests.lock().expect("mock lock poisoned").clone()
+    }
+
+    /// Requests sent so far for `action`.
+    pub fn requests_for(&self, action: &str) -> Vec<Value> {
+        self.requests()
+            .into_iter()
+            .filter(|request| request["action"] == action)
+            .collect()
+    }
+
+    fn answer(self, action: impl Into<String>, response: Value) -> Self {
+        self.answers
+            .lock()
+            .expect("mock lock poisoned")
+            .insert(action.into(), response);
+        self
+    }
+}
+
+impl Transport for MockTransport {
+    fn send(&self, body: Vec<u8>) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let request: Value = serde_json::from_slice(&body)?;
+            let action = request["action"].as_str().unwrap_or_default().to_string();
+            self.requests
+                .lock()
+                .expect("mock lock poisoned")
+                .push(request);
+            let response = self
+                .answers
+                .lock()
+                .expect("mock lock poisoned")
+                .get(&action)
+                .cloned()
+                .unwrap_or_else(
+                    || serde_json::json!({ "result": null, "error": "unsupported action" }),
+                );
+            Ok(serde_json::to_vec(&response)?)
+        })
+    }
+}
+
+/// HTTP over a Unix domain socket.
+///
+/// Sends each request as an HTTP/1.1 `POST` on a new connection to the
+/// socket, as a local proxy in front of AnkiConnect expects.
+#[cfg(unix)]
+#[derive(Debug, Clone)]
+pub struct UnixSocketTransport {
+    path: std::path::PathBuf,
+}
+
+#[cfg(unix)]
+impl UnixSocketTransport {
+    /// Connect to the socket at `path` for each request.
+    pub fn new(path: impl Into<std::path::PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[cfg(unix)]
+impl Transport for UnixSocketTransport {
+    fn send(&self, body: Vec<u8>) -> TransportFuture<'_> {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        Box::pin(async move {
+            let mut stream = tokio::net::UnixStream::connect(&self.path)
+                .await
+                .map_err(|_| Error::ConnectionRefused)?;
+            let head = format!(
+                "POST / HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            let mut response = Vec::new();
+            async {
+                stream.write_all(head.as_bytes()).await?;
+                stream.write_all(&body).await?;
+                stream.read_to_end(&mut response).await
+            }
+            .await
+            .map_err(|e| Error::Config(format!("unix socket request failed: {}", e)))?;
+            parse_http_response(&response)
+        })
+    }
+}
+
+/// Extract the body of an HTTP/1.1 response, decoding chunked transfer
+/// encoding.
+#[cfg(unix)]
+fn parse_http_response(response: &[u8]) -> Result<Vec<u8>> {
+    let invalid = |what: &str| Error::Config(format!("invalid HTTP response: {}", what));
+    let split = response
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .ok_or_else(|| invalid("no header terminator"))?;
+    let head = std::str::from_utf8(&response[..split]).map_err(|_| invalid("non-UTF-8 headers"))?;
+    let body = &response[split + 4..];
+
+    let mut lines = head.split("\r\n");
+    let status = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .ok_or_else(|| invalid("missing status line"))?;
+    if !status.starts_with('2') {
+        return Err(Error::Config(format!(
+            "AnkiConnect proxy returned HTTP {}",
+            status
+        )));
+    }
+    let chunked = lines.any(|line| {
+        let lower = line.to_ascii_lowercase();
+        lower.starts_with("transfer-encoding:") && lower.contains("chunked")
+    });
+    if !chunked {
+        return Ok(body.to_vec());
+    }
+
+    let mut decoded = Vec::new();
+    let mut rest = body;
+    loop {
+        let end = rest
+            .windows(2)
+            .position(|w| w == b"\r\n")
+            .ok_or_else(|| invalid("truncated chunk"))?;
+        let size = std::str::from_utf8(&rest[..end])
+            .ok()
+            .and_then(|line| usize::from_str_radix(line.split(';').next()?.trim(), 16).ok())
+            .ok_or_else(|| invalid("bad chunk size"))?;
+        rest = &rest[end + 2..];
+        if size == 0 {
+            return Ok(decoded);
+        }
+        let chunk = rest.get(..size).ok_or_else(|| invalid("truncated chunk"))?;
+        decoded.extend_from_slice(chunk);
+        rest = rest.get(size + 2..).unwrap_or_default();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_http_response() {
+        let plain = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}";
+        assert_eq!(parse_http_response(plain).unwrap(), b"{}");
+
+        let chunked = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\n{\"a\r\n4\r\n\":1}\r\n0\r\n\r\n";
+        assert_eq!(parse_http_response(chunked).unwrap(), b"{\"a\":1}");
+
+        let error = b"HTTP/1.1 502 Bad Gateway\r\n\r\n";
+        assert!(matches!(parse_http_response(error), Err(Error::Config(m)) if m.contains("502")));
+    }
+}
@@ -1,6 +1,7 @@
 //! Card-related types.
 
 use std::collections::HashMap;
+use std::fmt;
 
 use serde::{Deserialize, Serialize};
 
@@ -24,6 +25,10 @@ pub struct CardInfo {
     /// The note type (model) name.
     #[serde(default)]
     pub model_name: String,
+    /// Template ordinal (0-based); for cloze note types, the cloze number
+    /// minus one.
+    #[serde(default)]
+    pub ord: i32,
     /// The card's question side (HTML).
     #[serde(default)]
     pub question: String,
@@ -114,3 +119,140 @@ impl From<Ease> for i32 {
         ease as i32
     }
 }
+
+/// A card flag color.
+///
+/// Anki stores flags as numbers 1-7; 0 means no flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum Flag {
+    /// Red flag (1).
+    Red = 1,
+    /// Orange flag (2).
+    Orange = 2,
+    /// Green flag (3).
+    Green = 3,
+    /// Blue flag (4).
+    Blue = 4,
+    /// Pink flag (5).
+    Pink = 5,
+    /// Turquoise flag (6).
+    Turquoise = 6,
+    /// Purple flag (7).
+    Purple = 7,
+}
+
+impl Flag {
+    /// Every flag, in numeric order.
+    pub const ALL: [Flag; 7] = [
+        Flag::Red,
+        Flag::Orange,
+        Flag::Green,
+        Flag::Blue,
+        Flag::Pink,
+        Flag::Turquoise,
+        Flag::Purple,
+    ];
+
+    /// The number Anki uses for this flag.
+    pub fn number(self) -> u8 {
+        self as u8
+    }
+
+    /// The flag with the given number, or `None` for 0 and out-of-range values.
+    pub fn from_number(number: u8) -> Option<Self> {
+        Self::ALL.get(usize::from(number).checked_sub(1)?).copied()
+    }
+}
+
+impl From<Flag> for i32 {
+    fn from(flag: Flag) -> i32 {
+        flag as i32
+    }
+}
+
+/// When cards passed to [`set_due_date`](crate::actions::CardActions::set_due_date)
+/// become due.
+///
+/// Renders the day strings `setDueDate` expects: `"0"` for today, `"1-7"`
+/// for a random day in a range, and a trailing `!` to also set the review
+/// interval to the new due date. Days are counted forward from today. Raw
+/// strings still work through `From<&str>`.
+///
+/// # Example
+///
+/// ```
+/// use ankit::DueSpec;
+///
+/// assert_eq!(DueSpec::today().to_string(), "0");
+/// assert_eq!(DueSpec::in_days(3).to_string(), "3");
+/// assert_eq!(DueSpec::random_range(1, 7).to_string(), "1-7");
+/// assert_eq!(DueSpec::reset_interval_today().to_string(), "0!");
+/// assert_eq!(DueSpec::in_days(7).reset_interval().to_string(), "7!");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DueSpec(String);
+
+impl DueSpec {
+    /// Due today.
+    pub fn today() -> Self {
+        Self::in_days(0)
+    }
+
+    /// Due `days` from today.
+    ///
+    /// Anki only accepts days from today onward, so there is no way to set
+    /// a due date in the past.
+    pub fn in_days(days: u32) -> Self {
+        Self(days.to_string())
+    }
+
+    /// Due on a random day from `min` to `max` days from today, inclusive.
+    ///
+    /// The bounds are swapped if `min` is greater than `max`.
+    pub fn random_range(min: u32, max: u32) -> Self {
+        Self(format!("{}-{}", min.min(max), min.max(max)))
+    }
+
+    /// Due today, with the review interval reset to match.
+    pub fn reset_interval_today() -> Self {
+        Self::today().reset_interval()
+    }
+
+    /// Also set the card's review interval to the new due date.
+    pub fn reset_interval(mut self) -> Self {
+        if !self.0.ends_with('!') {
+            self.0.push('!');
+        }
+        self
+    }
+
+    /// The string sent to AnkiConnect.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for DueSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for DueSpec {
+    fn from(days: &str) -> Self {
+        Self(days.to_string())
+    }
+}
+
+impl From<&String> for DueSpec {
+    fn from(days: &String) -> Self {
+        Self(days.clone())
+    }
+}
+
+impl From<String> for DueSpec {
+    fn from(days: String) -> Self {
+        Self(days)
+    }
+}
@@ -1,5 +1,7 @@
 //! Deck-related types.
 
+use std::collections::BTreeSet;
+
 use serde::{Deserialize, Serialize};
 
 /// Statistics for a deck.
@@ -47,6 +49,9 @@ pub struct DeckConfig {
     /// Timer setting.
     #[serde(default)]
     pub timer: i64,
+    /// Whether to bury interday learning siblings.
+    #[serde(default)]
+    pub bury_interday_learning: bool,
     /// New card settings.
     pub new: NewCardConfig,
     /// Review settings.
@@ -55,6 +60,55 @@ pub struct DeckConfig {
     pub lapse: LapseConfig,
 }
 
+impl DeckConfig {
+    /// The sibling burying settings of this configuration.
+    pub fn bury_settings(&self) -> BurySettings {
+        BurySettings {
+            new: self.new.bury,
+            reviews: self.rev.bury,
+            interday_learning: self.bury_interday_learning,
+        }
+    }
+
+    /// Replace the sibling burying settings of this configuration.
+    pub fn set_bury_settings(&mut self, settings: BurySettings) {
+        self.new.bury = settings.new;
+        self.rev.bury = settings.reviews;
+        self.bury_interday_learning = settings.interday_learning;
+    }
+}
+
+/// Sibling burying settings of a deck configuration.
+///
+/// When a card is studied, Anki can hide its siblings (other cards of the
+/// same note) until the next day. Each kind of sibling is controlled
+/// separately.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BurySettings {
+    /// Bury new siblings.
+    pub new: bool,
+    /// Bury review siblings.
+    pub reviews: bool,
+    /// Bury interday learning siblings.
+    pub interday_learning: bool,
+}
+
+impl BurySettings {
+    /// Bury every kind of sibling.
+    pub fn all() -> Self {
+        Self {
+            new: true,
+            reviews: true,
+            interday_learning: true,
+        }
+    }
+
+    /// Bury no siblings.
+    pub fn none() -> Self {
+        Self::default()
+    }
+}
+
 /// Configuration for new cards.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -77,6 +131,9 @@ pub struct NewCardConfig {
     /// Maximum new cards per day.
     #[serde(default)]
     pub per_day: i64,
+    /// Whether to bury new siblings.
+    #[serde(default)]
+    pub bury: bool,
 }
 
 /// Configuration for reviews.
@@ -126,3 +183,188 @@ pub struct LapseConfig {
     #[serde(default)]
     pub mult: f64,
 }
+
+/// Separator between levels of a deck name (`Japanese::Vocab::N5`).
+pub const DECK_SEPARATOR: &str = "::";
+
+/// The deck hierarchy, built from flat deck names.
+///
+/// Anki stores sub-decks as `::`-separated names. `DeckTree` adds
+/// navigation over those names: parents, children, and whole sub-trees.
+/// Missing intermediate decks are filled in, so a tree built from
+/// `["A::B::C"]` also contains `A` and `A::B`.
+///
+/// Obtained via [`DeckActions::tree()`](crate::actions::DeckActions::tree),
+/// or built directly from names.
+///
+/// # Example
+///
+/// ```
+/// use ankit::DeckTree;
+///
+/// let tree = DeckTree::from_names(["Japanese::Vocab::N5", "Japanese::Grammar", "Default"]);
+/// assert_eq!(tree.roots(), vec!["Default", "Japanese"]);
+/// assert_eq!(tree.children("Japanese"), vec!["Japanese::Grammar", "Japanese::Vocab"]);
+/// assert_eq!(tree.parent("Japanese::Vocab::N5"), Some("Japanese::Vocab"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeckTree {
+    names: BTreeSet<String>,
+}
+
+impl DeckTree {
+    /// Build a tree from deck names, adding any missing parent decks.
+    pub fn from_names<I, S>(names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut tree = Self::default();
+        for name in names {
+            let name = name.into();
+            let mut end = 0;
+            while let Some(offset) = name[end..].find(DECK_SEPARATOR) {
+                end += offset;
+                tree.names.insert(name[..end].to_string());
+                end += DECK_SEPARATOR.len();
+            }
+            tree.names.insert(name);
+        }
+        tree
+    }
+
+    /// Whether the tree contains a deck.
+    pub fn contains(&self, deck: &str) -> bool {
+        self.names.contains(deck)
+    }
+
+    /// Number of decks in the tree.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Whether the tree has no decks.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// All deck names, sorted so that every parent comes before its children.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(String::as_str)
+    }
+
+    /// Top-level decks.
+    pub fn roots(&self) -> Vec<&str> {
+        self.iter().filter(|name| Self::depth(name) == 0).collect()
+    }
+
+    /// The parent of a deck, or `None` for a top-level deck.
+    ///
+    /// This only looks at the name, so it works for decks not in the tree.
+    pub fn parent<'n>(&self, deck: &'n str) -> Option<&'n str> {
+        deck.rsplit_once(DECK_SEPARATOR).map(|(parent, _)| parent)
+    }
+
+    /// Direct children of a deck.
+    pub fn children(&self, deck: &str) -> Vec<&str> {
+        self.descendants(deck)
+            .into_iter()
+            .filter(|name| !name[deck.len() + DECK_SEPARATOR.len()..].contains(DECK_SEPARATOR))
+            .collect()
+    }
+
+    /// All decks below a deck, at any depth, parents before children.
+    pub fn descendants(&self, deck: &str) -> Vec<&str> {
+        let prefix = format!("{}{}", deck, DECK_SEPARATOR);
+        self.names
+            .range(prefix.clone()..)
+            .take_while(|name| name.starts_with(&prefix))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// A deck followed by all its descendants; empty if the deck isn't in
+    /// the tree.
+    pub fn subtree(&self, deck: &str) -> Vec<&str> {
+        match self.names.get(deck) {
+            Some(name) => std::iter::once(name.as_str())
+                .chain(self.descendants(deck))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Depth of a deck name: 0 for top-level decks.
+    pub fn depth(deck: &str) -> usize {
+        deck.matches(DECK_SEPARATOR).count()
+    }
+
+    /// The last component of a deck name (`N5` for `Japanese::Vocab::N5`).
+    pub fn leaf_name(deck: &str) -> &str {
+        deck.rsplit_once(DECK_SEPARATOR)
+            .map_or(deck, |(_, leaf)| leaf)
+    }
+
+    /// Rename `deck` as if the sub-tree rooted at `from` were moved to `to`.
+    ///
+    /// Returns `None` if `deck` is not `from` or one of its descendants.
+    ///
+    /// ```
+    /// use ankit::DeckTree;
+    ///
+    /// assert_eq!(
+    ///     DeckTree::rebase("Japanese::Vocab::N5", "Japanese", "Archive::Japanese"),
+    ///     Some("Archive::Japanese::Vocab::N5".to_string())
+    /// );
+    /// assert_eq!(DeckTree::rebase("Japanese2", "Japanese", "Other"), None);
+    /// ```
+    pub fn rebase(deck: &str, from: &str, to: &str) -> Option<String> {
+        if deck == from {
+            return Some(to.to_string());
+        }
+        deck.strip_prefix(from)
+            .filter(|rest| rest.starts_with(DECK_SEPARATOR))
+            .map(|rest| format!("{}{}", to, rest))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree_fills_missing_parents() {
+        let tree = DeckTree::from_names(["A::B::C"]);
+        assert_eq!(
+            tree.iter().collect::<Vec<_>>(),
+            vec!["A", "A::B", "A::B::C"]
+        );
+        assert_eq!(tree.roots(), vec!["A"]);
+    }
+
+    #[test]
+    fn test_children_and_descendants() {
+        let tree = DeckTree::from_names(["A::B::C", "A::D", "AB", "A::B::C::E"]);
+        assert_eq!(tree.children("A"), vec!["A::B", "A::D"]);
+        assert_eq!(
+            tree.descendants("A"),
+            vec!["A::B", "A::B::C", "A::B::C::E", "A::D"]
+        );
+        assert_eq!(tree.subtree("A::B::C"), vec!["A::B::C", "A::B::C::E"]);
+        assert!(tree.subtree("Missing").is_empty());
+        assert!(tree.children("AB").is_empty());
+    }
+
+    #[test]
+    fn test_name_helpers() {
+        let tree = DeckTree::default();
+        assert_eq!(tree.parent("A::B"), Some("A"));
+        assert_eq!(tree.parent("A"), None);
+        assert_eq!(DeckTree::depth("A::B::C"), 2);
+        assert_eq!(DeckTree::leaf_name("A::B::C"), "C");
+        assert_eq!(
+            DeckTree::rebase("A::B", "A", "X::Y"),
+            Some("X::Y::B".to_string())
+        );
+    }
+}
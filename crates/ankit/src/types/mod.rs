@@ -8,9 +8,13 @@ mod deck;
 mod media;
 mod model;
 mod note;
+mod tag;
 
-pub use card::{CardAnswer, CardInfo, CardModTime, Ease};
-pub use deck::{DeckConfig, DeckStats, LapseConfig, NewCardConfig, ReviewConfig};
+pub use card::{CardAnswer, CardInfo, CardModTime, DueSpec, Ease, Flag};
+pub use deck::{
+    BurySettings, DECK_SEPARATOR, DeckConfig, DeckStats, DeckTree, LapseConfig, NewCardConfig,
+    ReviewConfig,
+};
 pub use media::{MediaData, StoreMediaParams};
 pub use model::{
     CardTemplate, CreateModelParams, FieldFont, FieldsOnTemplates, FindReplaceParams, ModelField,
@@ -20,3 +24,4 @@ pub use note::{
     CanAddResult, DuplicateScope, DuplicateScopeOptions, MediaAttachment, Note, NoteBuilder,
     NoteField, NoteInfo, NoteModTime, NoteOptions,
 };
+pub use tag::{TAG_SEPARATOR, TagTree};
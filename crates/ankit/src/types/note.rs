@@ -73,6 +73,9 @@ pub struct NoteOptions {
     /// Additional options for duplicate scope.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub duplicate_scope_options: Option<DuplicateScopeOptions>,
+    /// Close the Add Cards dialog once the note is added (`guiAddCards` only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub close_after_adding: Option<bool>,
 }
 
 /// Scope for duplicate note checking.
@@ -236,6 +239,16 @@ impl NoteBuilder {
         self
     }
 
+    /// Close the Add Cards dialog after the user adds the note.
+    ///
+    /// Only used by [`GuiActions::add_cards`](crate::actions::GuiActions::add_cards).
+    pub fn close_after_adding(mut self, close: bool) -> Self {
+        self.options
+            .get_or_insert_with(NoteOptions::default)
+            .close_after_adding = Some(close);
+        self
+    }
+
     /// Set the duplicate checking scope.
     pub fn duplicate_scope(mut self, scope: DuplicateScope) -> Self {
         self.options
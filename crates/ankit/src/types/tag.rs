@@ -0,0 +1,199 @@
+//! Tag-related types.
+
+use std::collections::BTreeMap;
+
+/// Separator between levels of a hierarchical tag (`lang::jp::verb`).
+pub const TAG_SEPARATOR: &str = "::";
+
+/// The tag hierarchy, built from flat tag names.
+///
+/// Anki treats `::` in tags as hierarchy, and compares tags without regard
+/// to case. `TagTree` does the same: lookups are case-insensitive, and names
+/// are returned with the casing they were built from. Missing intermediate
+/// tags are filled in, so a tree built from `["lang::jp::verb"]` also
+/// contains `lang` and `lang::jp`.
+///
+/// Obtained via [`NoteActions::tag_tree()`](crate::actions::NoteActions::tag_tree),
+/// or built directly from names.
+///
+/// # Example
+///
+/// ```
+/// use ankit::TagTree;
+///
+/// let tree = TagTree::from_names(["lang::jp::verb", "lang::jp::noun", "leech"]);
+/// assert_eq!(tree.roots(), vec!["lang", "leech"]);
+/// assert_eq!(tree.children("LANG::JP"), vec!["lang::jp::noun", "lang::jp::verb"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagTree {
+    /// Lowercased tag to tag as first seen.
+    tags: BTreeMap<String, String>,
+}
+
+impl TagTree {
+    /// Build a tree from tag names, adding any missing parent tags.
+    pub fn from_names<I, S>(names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut tree = Self::default();
+        for name in names {
+            let name = name.into();
+            let mut end = 0;
+            while let Some(offset) = name[end..].find(TAG_SEPARATOR) {
+                end += offset;
+                tree.insert(&name[..end]);
+                end += TAG_SEPARATOR.len();
+            }
+            tree.insert(&name);
+        }
+        tree
+    }
+
+    fn insert(&mut self, tag: &str) {
+        self.tags
+            .entry(tag.to_lowercase())
+            .or_insert_with(|| tag.to_string());
+    }
+
+    /// Whether the tree contains a tag (case-insensitive).
+    pub fn contains(&self, tag: &str) -> bool {
+        self.tags.contains_key(&tag.to_lowercase())
+    }
+
+    /// The tag as stored in the tree, for a case-insensitive lookup.
+    pub fn get(&self, tag: &str) -> Option<&str> {
+        self.tags.get(&tag.to_lowercase()).map(String::as_str)
+    }
+
+    /// Number of tags in the tree.
+    pub fn len(&self) -> usize {
+        self.tags.len()
+    }
+
+    /// Whether the tree has no tags.
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+
+    /// All tags, sorted so that every parent comes before its children.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.tags.values().map(String::as_str)
+    }
+
+    /// Top-level tags.
+    pub fn roots(&self) -> Vec<&str> {
+        self.iter().filter(|tag| Self::depth(tag) == 0).collect()
+    }
+
+    /// The parent of a tag, or `None` for a top-level tag.
+    ///
+    /// This only looks at the name, so it works for tags not in the tree.
+    pub fn parent<'n>(&self, tag: &'n str) -> Option<&'n str> {
+        tag.rsplit_once(TAG_SEPARATOR).map(|(parent, _)| parent)
+    }
+
+    /// Direct children of a tag.
+    pub fn children(&self, tag: &str) -> Vec<&str> {
+        let depth = Self::depth(tag) + 1;
+        self.descendants(tag)
+            .into_iter()
+            .filter(|child| Self::depth(child) == depth)
+            .collect()
+    }
+
+    /// All tags below a tag, at any depth, parents before children.
+    pub fn descendants(&self, tag: &str) -> Vec<&str> {
+        let prefix = format!("{}{}", tag.to_lowercase(), TAG_SEPARATOR);
+        self.tags
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .map(|(_, tag)| tag.as_str())
+            .collect()
+    }
+
+    /// A tag followed by all its descendants; empty if the tag isn't in the
+    /// tree.
+    pub fn subtree(&self, tag: &str) -> Vec<&str> {
+        match self.get(tag) {
+            Some(found) => std::iter::once(found)
+                .chain(self.descendants(tag))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Depth of a tag: 0 for top-level tags.
+    pub fn depth(tag: &str) -> usize {
+        tag.matches(TAG_SEPARATOR).count()
+    }
+
+    /// The last component of a tag (`verb` for `lang::jp::verb`).
+    pub fn leaf_name(tag: &str) -> &str {
+        tag.rsplit_once(TAG_SEPARATOR).map_or(tag, |(_, leaf)| leaf)
+    }
+
+    /// Rename `tag` as if the branch rooted at `from` were moved to `to`.
+    ///
+    /// Matching is case-insensitive, like Anki's. Returns `None` if `tag` is
+    /// not `from` or one of its descendants.
+    ///
+    /// ```
+    /// use ankit::TagTree;
+    ///
+    /// assert_eq!(
+    ///     TagTree::rebase("Lang::JP::verb", "lang::jp", "japanese"),
+    ///     Some("japanese::verb".to_string())
+    /// );
+    /// assert_eq!(TagTree::rebase("language", "lang", "x"), None);
+    /// ```
+    pub fn rebase(tag: &str, from: &str, to: &str) -> Option<String> {
+        if tag.len() < from.len() || !tag.is_char_boundary(from.len()) {
+            return None;
+        }
+        let (head, rest) = tag.split_at(from.len());
+        if head.to_lowercase() != from.to_lowercase() {
+            return None;
+        }
+        if rest.is_empty() {
+            Some(to.to_string())
+        } else if rest.starts_with(TAG_SEPARATOR) {
+            Some(format!("{}{}", to, rest))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree_is_case_insensitive() {
+        let tree = TagTree::from_names(["Lang::JP::verb", "lang::jp::noun"]);
+        assert_eq!(
+            tree.iter().collect::<Vec<_>>(),
+            vec!["Lang", "Lang::JP", "lang::jp::noun", "Lang::JP::verb"]
+        );
+        assert!(tree.contains("LANG::jp"));
+        assert_eq!(tree.get("lang::jp"), Some("Lang::JP"));
+    }
+
+    #[test]
+    fn test_children_and_subtree() {
+        let tree = TagTree::from_names(["a::b::c", "a::d", "ab"]);
+        assert_eq!(tree.children("a"), vec!["a::b", "a::d"]);
+        assert_eq!(tree.subtree("A::B"), vec!["a::b", "a::b::c"]);
+        assert!(tree.subtree("missing").is_empty());
+    }
+
+    #[test]
+    fn test_rebase() {
+        assert_eq!(TagTree::rebase("a::b", "A", "x"), Some("x::b".to_string()));
+        assert_eq!(TagTree::rebase("a", "a", "x"), Some("x".to_string()));
+        assert_eq!(TagTree::rebase("ab", "a", "x"), None);
+    }
+}
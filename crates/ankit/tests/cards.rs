@@ -2,8 +2,10 @@
 
 mod common;
 
-use ankit::AnkiClient;
+use ankit::{AnkiClient, DueSpec, Flag};
 use common::{mock_action, mock_anki_response, setup_mock_server};
+use wiremock::Mock;
+use wiremock::matchers::{body_partial_json, method};
 
 #[tokio::test]
 async fn test_find_cards() {
@@ -287,6 +289,41 @@ async fn test_answer_cards() {
     assert_eq!(result, vec![true, true]);
 }
 
+#[tokio::test]
+async fn test_answer_batch() {
+    let server = setup_mock_server().await;
+    mock_action(
+        &server,
+        "multi",
+        mock_anki_response(vec![
+            serde_json::json!({"result": [true], "error": null}),
+            serde_json::json!({"result": null, "error": "card was not found"}),
+            serde_json::json!([true]),
+        ]),
+    )
+    .await;
+
+    let client = AnkiClient::builder().url(server.uri()).build();
+    let answers = vec![
+        ankit::CardAnswer::new(1, ankit::Ease::Good),
+        ankit::CardAnswer::new(2, ankit::Ease::Again),
+        ankit::CardAnswer::new(3, ankit::Ease::Easy),
+    ];
+    let result = client.cards().answer_batch(&answers).await.unwrap();
+
+    assert_eq!(result, vec![true, false, true]);
+}
+
+#[tokio::test]
+async fn test_answer_batch_empty() {
+    let server = setup_mock_server().await;
+
+    let client = AnkiClient::builder().url(server.uri()).build();
+    let result = client.cards().answer_batch(&[]).await.unwrap();
+
+    assert!(result.is_empty());
+}
+
 #[tokio::test]
 async fn test_set_due_date() {
     let server = setup_mock_server().await;
@@ -313,6 +350,24 @@ async fn test_set_due_date_range() {
     assert!(result);
 }
 
+#[tokio::test]
+async fn test_set_due_date_spec() {
+    let server = setup_mock_server().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({
+            "action": "setDueDate",
+            "params": {"cards": [1], "days": "3-7!"}
+        })))
+        .respond_with(mock_anki_response(true))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = AnkiClient::builder().url(server.uri()).build();
+    let days = DueSpec::random_range(7, 3).reset_interval();
+    assert!(client.cards().set_due_date(&[1], days).await.unwrap());
+}
+
 #[tokio::test]
 async fn test_set_specific_value() {
     let server = setup_mock_server().await;
@@ -332,3 +387,123 @@ async fn test_set_specific_value() {
 
     assert_eq!(result, vec![true]);
 }
+
+#[tokio::test]
+async fn test_set_flag() {
+    let server = setup_mock_server().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({
+            "action": "multi",
+            "params": {"actions": [
+                {"action": "setSpecificValueOfCard", "params": {"card": 1, "keys": ["flags"], "newValues": ["1"]}},
+                {"action": "setSpecificValueOfCard", "params": {"card": 2, "keys": ["flags"], "newValues": ["1"]}}
+            ]}
+        })))
+        .respond_with(mock_anki_response(vec![
+            serde_json::json!({"result": [true], "error": null}),
+            serde_json::json!({"result": null, "error": "card was not found: 2"}),
+        ]))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = AnkiClient::builder().url(server.uri()).build();
+    let result = client.cards().set_flag(&[1, 2], Flag::Red).await.unwrap();
+
+    assert_eq!(result, vec![true, false]);
+}
+
+#[tokio::test]
+async fn test_clear_flag_empty() {
+    let server = setup_mock_server().await;
+
+    let client = AnkiClient::builder().url(server.uri()).build();
+    let result = client.cards().clear_flag(&[]).await.unwrap();
+
+    assert!(result.is_empty());
+}
+
+#[tokio::test]
+async fn test_reposition() {
+    let server = setup_mock_server().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({
+            "action": "multi",
+            "params": {"actions": [
+                {"action": "setSpecificValueOfCard", "params": {"card": 1, "keys": ["due"], "newValues": ["5"], "warningCheck": true}},
+                {"action": "setSpecificValueOfCard", "params": {"card": 2, "keys": ["due"], "newValues": ["3"], "warningCheck": true}}
+            ]}
+        })))
+        .respond_with(mock_anki_response(vec![
+            serde_json::json!({"result": [true], "error": null}),
+            serde_json::json!({"result": [true], "error": null}),
+        ]))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = AnkiClient::builder().url(server.uri()).build();
+    let result = client.cards().reposition(&[(1, 5), (2, 3)]).await.unwrap();
+
+    assert_eq!(result, vec![true, true]);
+}
+
+#[tokio::test]
+async fn test_bury() {
+    let server = setup_mock_server().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({
+            "action": "multi",
+            "params": {"actions": [
+                {"action": "setSpecificValueOfCard", "params": {"card": 1, "keys": ["queue"], "newValues": ["-3"]}}
+            ]}
+        })))
+        .respond_with(mock_anki_response(vec![serde_json::json!({
+            "result": [true],
+            "error": null
+        })]))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = AnkiClient::builder().url(server.uri()).build();
+    let result = client.cards().bury(&[1]).await.unwrap();
+
+    assert_eq!(result, vec![true]);
+}
+
+#[tokio::test]
+async fn test_unbury() {
+    let server = setup_mock_server().await;
+    mock_action(
+        &server,
+        "cardsInfo",
+        mock_anki_response(vec![
+            serde_json::json!({"cardId": 1, "type": 2, "queue": -3, "due": 500}),
+            serde_json::json!({"cardId": 2, "type": 2, "queue": 2, "due": 500}),
+            serde_json::json!({"cardId": 3, "type": 1, "queue": -2, "due": 1_700_000_000}),
+        ]),
+    )
+    .await;
+    // Card 2 is not buried, so only cards 1 and 3 are written
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({
+            "action": "multi",
+            "params": {"actions": [
+                {"action": "setSpecificValueOfCard", "params": {"card": 1, "keys": ["queue"], "newValues": ["2"]}},
+                {"action": "setSpecificValueOfCard", "params": {"card": 3, "keys": ["queue"], "newValues": ["1"]}}
+            ]}
+        })))
+        .respond_with(mock_anki_response(vec![
+            serde_json::json!({"result": [true], "error": null}),
+            serde_json::json!({"result": [true], "error": null}),
+        ]))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = AnkiClient::builder().url(server.uri()).build();
+    let result = client.cards().unbury(&[1, 2, 3]).await.unwrap();
+
+    assert_eq!(result, vec![true, false, true]);
+}
@@ -0,0 +1,393 @@
+//! Tests for client configuration.
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use ankit::{AnkiClient, Error, MockTransport, PrometheusMetrics, RequestEvent, RequestObserver};
+use common::{mock_anki_error, mock_anki_response, setup_mock_server};
+use wiremock::Mock;
+use wiremock::matchers::{body_partial_json, method};
+
+#[tokio::test]
+async fn test_max_requests_per_second() {
+    let server = setup_mock_server().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({"action": "version"})))
+        .respond_with(mock_anki_response(6))
+        .expect(5)
+        .mount(&server)
+        .await;
+
+    let client = AnkiClient::builder()
+        .url(server.uri())
+        .max_requests_per_second(20)
+        .build();
+
+    let start = Instant::now();
+    for _ in 0..5 {
+        client.misc().version().await.unwrap();
+    }
+
+    // First request goes immediately, the remaining four are spaced 50ms apart
+    assert!(start.elapsed() >= Duration::from_millis(200));
+}
+
+#[tokio::test]
+async fn test_max_concurrent_requests() {
+    let server = setup_mock_server().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({"action": "version"})))
+        .respond_with(mock_anki_response(6).set_delay(Duration::from_millis(100)))
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    let client = AnkiClient::builder()
+        .url(server.uri())
+        .max_concurrent_requests(1)
+        .build();
+
+    let misc = client.misc();
+    let start = Instant::now();
+    let (a, b, c) = tokio::join!(misc.version(), misc.version(), misc.version());
+    a.unwrap();
+    b.unwrap();
+    c.unwrap();
+
+    // Requests are serialized, so each 100ms response waits on the previous one
+    assert!(start.elapsed() >= Duration::from_millis(300));
+}
+
+#[tokio::test]
+async fn test_throttle_shared_across_clones() {
+    let server = setup_mock_server().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({"action": "version"})))
+        .respond_with(mock_anki_response(6).set_delay(Duration::from_millis(100)))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let client = AnkiClient::builder()
+        .url(server.uri())
+        .max_concurrent_requests(1)
+        .build();
+    let clone = client.clone();
+
+    let (misc, clone_misc) = (client.misc(), clone.misc());
+    let start = Instant::now();
+    let (a, b) = tokio::join!(misc.version(), clone_misc.version());
+    a.unwrap();
+    b.unwrap();
+
+    assert!(start.elapsed() >= Duration::from_millis(200));
+}
+
+#[tokio::test]
+async fn test_detect_capabilities() {
+    let server = setup_mock_server().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({"action": "version"})))
+        .respond_with(mock_anki_response(6))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({"action": "apiReflect"}),
+        ))
+        .respond_with(mock_anki_response(serde_json::json!({
+            "scopes": ["actions"],
+            "actions": ["version", "apiReflect", "deckNames"]
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({"action": "deckNames"}),
+        ))
+        .respond_with(mock_anki_response(vec!["Default"]))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let client = AnkiClient::builder()
+        .url(server.uri())
+        .detect_capabilities(true)
+        .build();
+
+    assert_eq!(client.decks().names().await.unwrap(), vec!["Default"]);
+    assert_eq!(client.decks().names().await.unwrap(), vec!["Default"]);
+
+    match client.models().names().await {
+        Err(Error::UnsupportedAction { action, version }) => {
+            assert_eq!(action, "modelNames");
+            assert_eq!(version, 6);
+        }
+        other => panic!("expected UnsupportedAction, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_detect_capabilities_without_api_reflect() {
+    let server = setup_mock_server().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({"action": "version"})))
+        .respond_with(mock_anki_response(5))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({"action": "apiReflect"}),
+        ))
+        .respond_with(mock_anki_error("unsupported action"))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({"action": "deckNames"}),
+        ))
+        .respond_with(mock_anki_response(vec!["Default"]))
+        .mount(&server)
+        .await;
+
+    let client = AnkiClient::builder()
+        .url(server.uri())
+        .detect_capabilities(true)
+        .build();
+
+    // Without an action list, every action is let through
+    assert!(client.decks().names().await.is_ok());
+    let capabilities = client.capabilities().await.unwrap();
+    assert_eq!(capabilities.version, 5);
+    assert!(capabilities.actions.is_none());
+}
+
+/// Records the action and error of each request.
+#[derive(Default)]
+struct Recorder(Mutex<Vec<(String, Option<String>, usize)>>);
+
+impl RequestObserver for Recorder {
+    fn on_request(&self, event: &RequestEvent<'_>) {
+        self.0.lock().unwrap().push((
+            event.action.to_string(),
+            event.error.map(str::to_string),
+            event.request_bytes,
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_request_observers() {
+    let server = setup_mock_server().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({"action": "version"})))
+        .respond_with(mock_anki_response(6))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({"action": "deckNames"}),
+        ))
+        .respond_with(mock_anki_error("collection is not available"))
+        .mount(&server)
+        .await;
+
+    let recorder = Arc::new(Recorder::default());
+    let metrics = Arc::new(PrometheusMetrics::new());
+    let client = AnkiClient::builder()
+        .url(server.uri())
+        .observer(recorder.clone())
+        .observer(metrics.clone())
+        .build();
+
+    client.misc().version().await.unwrap();
+    assert!(client.decks().names().await.is_err());
+
+    let events = recorder.0.lock().unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].0, "version");
+    assert!(events[0].1.is_none());
+    assert!(events[0].2 > 0);
+    assert_eq!(events[1].1.as_deref(), Some("collection is not available"));
+
+    let text = metrics.render();
+    assert!(text.contains(r#"ankit_requests_total{action="deckNames",outcome="error"} 1"#));
+    assert!(text.contains(r#"ankit_requests_total{action="version",outcome="success"} 1"#));
+}
+
+// Self-signed certificate and key for localhost, used only by these tests
+const TEST_CERT: &[u8] = include_bytes!("fixtures/localhost-cert.pem");
+const TEST_KEY: &[u8] = include_bytes!("fixtures/localhost-key.pem");
+
+#[test]
+fn test_tls_options() {
+    let client = AnkiClient::builder()
+        .url("https://localhost:8765")
+        .root_certificate_pem(TEST_CERT)
+        .client_identity_pem(TEST_CERT, TEST_KEY)
+        .try_build();
+    assert!(client.is_ok());
+
+    let client = AnkiClient::builder()
+        .danger_accept_invalid_certs(true)
+        .try_build();
+    assert!(client.is_ok());
+}
+
+#[test]
+fn test_tls_invalid_certificates() {
+    let result = AnkiClient::builder()
+        .root_certificate_pem("not a certificate")
+        .try_build();
+    assert!(matches!(result, Err(Error::Config(msg)) if msg.contains("root certificate")));
+
+    let result = AnkiClient::builder()
+        .client_identity_pem(TEST_CERT, "not a key")
+        .try_build();
+    assert!(matches!(result, Err(Error::Config(msg)) if msg.contains("client identity")));
+}
+
+#[tokio::test]
+async fn test_mock_transport() {
+    let mock = MockTransport::new()
+        .respond("deckNames", serde_json::json!(["Default"]))
+        .error("deleteDecks", "read-only");
+    let client = AnkiClient::builder().transport(mock.clone()).build();
+
+    assert_eq!(client.decks().names().await.unwrap(), vec!["Default"]);
+    assert!(matches!(
+        client.decks().delete(&["Default"], true).await,
+        Err(Error::AnkiConnect(msg)) if msg == "read-only"
+    ));
+    assert!(client.misc().version().await.is_err());
+
+    assert_eq!(mock.requests().len(), 3);
+    let deletes = mock.requests_for("deleteDecks");
+    assert_eq!(
+        deletes[0]["params"]["decks"],
+        serde_json::json!(["Default"])
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_unix_socket_transport() {
+    use ankit::UnixSocketTransport;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let path = std::env::temp_dir().join(format!("ankit-test-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let listener = tokio::net::UnixListener::bind(&path).unwrap();
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 1024];
+        while !request.ends_with(b"}") {
+            let n = stream.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+        }
+        let body = br#"{"result": 6, "error": null}"#;
+        let head = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", body.len());
+        stream.write_all(head.as_bytes()).await.unwrap();
+        stream.write_all(body).await.unwrap();
+    });
+
+    let client = AnkiClient::builder()
+        .transport(UnixSocketTransport::new(&path))
+        .build();
+    assert_eq!(client.misc().version().await.unwrap(), 6);
+
+    let missing = AnkiClient::builder()
+        .transport(UnixSocketTransport::new(path.with_extension("missing")))
+        .build();
+    assert!(matches!(
+        missing.misc().version().await,
+        Err(Error::ConnectionRefused)
+    ));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn test_metadata_cache() {
+    let server = setup_mock_server().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({"action": "deckNames"}),
+        ))
+        .respond_with(mock_anki_response(vec!["Default"]))
+        .expect(3)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({"action": "createDeck"}),
+        ))
+        .respond_with(mock_anki_response(1_i64))
+        .mount(&server)
+        .await;
+
+    let client = AnkiClient::builder()
+        .url(server.uri())
+        .metadata_cache(Duration::from_secs(60))
+        .build();
+
+    // Repeated lookups are answered from the cache, including by clones
+    client.decks().names().await.unwrap();
+    client.decks().names().await.unwrap();
+    client.clone().decks().names().await.unwrap();
+
+    // Creating a deck clears the cache
+    client.decks().create("Spanish").await.unwrap();
+    client.decks().names().await.unwrap();
+
+    client.invalidate_metadata();
+    client.decks().names().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_metadata_cache_cleared_by_moving_cards() {
+    let server = setup_mock_server().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({"action": "deckNames"}),
+        ))
+        .respond_with(mock_anki_response(vec!["Default"]))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({"action": "deckNames"}),
+        ))
+        .respond_with(mock_anki_response(vec!["Default", "New Deck"]))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({"action": "changeDeck"}),
+        ))
+        .respond_with(mock_anki_response(serde_json::Value::Null))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = AnkiClient::builder()
+        .url(server.uri())
+        .metadata_cache(Duration::from_secs(60))
+        .build();
+
+    assert_eq!(client.decks().names().await.unwrap(), vec!["Default"]);
+
+    // changeDeck creates the target deck if it is missing
+    client.decks().move_cards(&[1], "New Deck").await.unwrap();
+    assert_eq!(
+        client.decks().names().await.unwrap(),
+        vec!["Default", "New Deck"]
+    );
+}
@@ -27,6 +27,7 @@ pub fn mock_anki_error(error: &str) -> ResponseTemplate {
 }
 
 /// Mount a mock for a specific action.
+#[allow(dead_code)] // Not all test files use this
 pub async fn mock_action(server: &MockServer, action: &str, response: ResponseTemplate) {
     Mock::given(method("POST"))
         .and(body_partial_json(serde_json::json!({
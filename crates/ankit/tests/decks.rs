@@ -2,8 +2,10 @@
 
 mod common;
 
-use ankit::AnkiClient;
+use ankit::{AnkiClient, BurySettings};
 use common::{mock_action, mock_anki_error, mock_anki_response, setup_mock_server};
+use wiremock::Mock;
+use wiremock::matchers::{body_partial_json, method};
 
 #[tokio::test]
 async fn test_deck_names() {
@@ -21,6 +23,23 @@ async fn test_deck_names() {
     assert_eq!(decks, vec!["Default", "Japanese"]);
 }
 
+#[tokio::test]
+async fn test_deck_tree() {
+    let server = setup_mock_server().await;
+    mock_action(
+        &server,
+        "deckNames",
+        mock_anki_response(vec!["Default", "Japanese::Vocab::N5"]),
+    )
+    .await;
+
+    let client = AnkiClient::builder().url(server.uri()).build();
+    let tree = client.decks().tree().await.unwrap();
+
+    assert_eq!(tree.len(), 4);
+    assert_eq!(tree.children("Japanese"), vec!["Japanese::Vocab"]);
+}
+
 #[tokio::test]
 async fn test_deck_names_and_ids() {
     let server = setup_mock_server().await;
@@ -52,6 +71,34 @@ async fn test_create_deck() {
     assert_eq!(deck_id, 1234567890);
 }
 
+#[tokio::test]
+async fn test_create_filtered_deck() {
+    let server = setup_mock_server().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({
+            "action": "createFilteredDeck",
+            "params": {
+                "newDeckName": "Cram",
+                "searchQuery": "deck:Japanese is:due",
+                "gatherCount": 50,
+                "reschedule": false
+            }
+        })))
+        .respond_with(mock_anki_response(1234567891_i64))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = AnkiClient::builder().url(server.uri()).build();
+    let deck_id = client
+        .decks()
+        .create_filtered("Cram", "deck:Japanese is:due", 50, false)
+        .await
+        .unwrap();
+
+    assert_eq!(deck_id, 1234567891);
+}
+
 #[tokio::test]
 async fn test_delete_deck() {
     let server = setup_mock_server().await;
@@ -158,6 +205,81 @@ async fn test_deck_config() {
     assert_eq!(config.id, 1);
     assert_eq!(config.name, "Default");
     assert_eq!(config.new.per_day, 20);
+    assert_eq!(
+        config.bury_settings(),
+        BurySettings {
+            new: true,
+            reviews: true,
+            interday_learning: false,
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_deck_config_missing_deck() {
+    let server = setup_mock_server().await;
+    // AnkiConnect answers false for a deck that doesn't exist
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({"action": "getDeckConfig"}),
+        ))
+        .respond_with(mock_anki_response(false))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let client = AnkiClient::builder().url(server.uri()).build();
+    assert!(
+        client
+            .decks()
+            .config_if_exists("Missing")
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(matches!(
+        client.decks().config("Missing").await,
+        Err(ankit::Error::DeckNotFound(deck)) if deck == "Missing"
+    ));
+}
+
+#[tokio::test]
+async fn test_set_bury_settings() {
+    let server = setup_mock_server().await;
+    mock_action(
+        &server,
+        "getDeckConfig",
+        mock_anki_response(serde_json::json!({
+            "id": 1,
+            "name": "Default",
+            "new": {"perDay": 20, "bury": true},
+            "rev": {"perDay": 200, "bury": true},
+            "lapse": {"leechFails": 8}
+        })),
+    )
+    .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({
+            "action": "saveDeckConfig",
+            "params": {"config": {
+                "buryInterdayLearning": false,
+                "new": {"perDay": 20, "bury": false},
+                "rev": {"bury": false}
+            }}
+        })))
+        .respond_with(mock_anki_response(true))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = AnkiClient::builder().url(server.uri()).build();
+    let saved = client
+        .decks()
+        .set_bury_settings("Default", BurySettings::none())
+        .await
+        .unwrap();
+
+    assert!(saved);
 }
 
 #[tokio::test]
@@ -175,6 +297,7 @@ async fn test_save_deck_config() {
         replayq: true,
         autoplay: true,
         timer: 0,
+        bury_interday_learning: false,
         new: ankit::NewCardConfig {
             delays: vec![1.0, 10.0],
             order: 1,
@@ -182,6 +305,7 @@ async fn test_save_deck_config() {
             separate: true,
             ints: vec![1, 4],
             per_day: 50,
+            bury: false,
         },
         rev: ankit::ReviewConfig {
             per_day: 200,
@@ -206,6 +330,33 @@ async fn test_save_deck_config() {
     assert!(result);
 }
 
+#[tokio::test]
+async fn test_config_by_name() {
+    let server = ankit::testing::MockAnkiServer::start().await;
+    server.with_decks(["Default", "Spanish"]);
+    server.handle("getDeckConfig", |params| {
+        let (id, name) = match params["deck"].as_str() {
+            Some("Spanish") => (2, "Languages"),
+            _ => (1, "Default"),
+        };
+        Ok(serde_json::json!({
+            "id": id, "name": name, "new": {}, "rev": {}, "lapse": {}
+        }))
+    });
+    let client = server.client();
+
+    let config = client.decks().config_by_name("Languages").await.unwrap();
+    assert_eq!(config.unwrap().id, 2);
+    assert!(
+        client
+            .decks()
+            .config_by_name("Missing")
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
 #[tokio::test]
 async fn test_set_config_id() {
     let server = setup_mock_server().await;
@@ -2,7 +2,7 @@
 
 mod common;
 
-use ankit::AnkiClient;
+use ankit::{AnkiClient, SortOrder};
 use common::{mock_action, mock_anki_response, setup_mock_server};
 
 #[tokio::test]
@@ -95,11 +95,23 @@ async fn test_gui_add_cards() {
     let server = setup_mock_server().await;
     let client = AnkiClient::builder().url(server.uri()).build();
 
-    mock_action(&server, "guiAddCards", mock_anki_response(1234567890_i64)).await;
+    wiremock::Mock::given(wiremock::matchers::body_partial_json(serde_json::json!({
+        "action": "guiAddCards",
+        "params": {"note": {
+            "deckName": "Default",
+            "modelName": "Basic",
+            "options": {"closeAfterAdding": true}
+        }}
+    })))
+    .respond_with(mock_anki_response(1234567890_i64))
+    .expect(1)
+    .mount(&server)
+    .await;
 
     let note = ankit::NoteBuilder::new("Default", "Basic")
         .field("Front", "Question")
         .field("Back", "Answer")
+        .close_after_adding(true)
         .build();
 
     let result = client.gui().add_cards(note).await.unwrap();
@@ -341,3 +353,180 @@ async fn test_gui_active_profile() {
     let result = client.gui().active_profile().await.unwrap();
     assert_eq!(result, "User 1");
 }
+
+#[tokio::test]
+async fn test_gui_browse_sorted() {
+    let server = setup_mock_server().await;
+    let client = AnkiClient::builder().url(server.uri()).build();
+
+    wiremock::Mock::given(wiremock::matchers::method("POST"))
+        .and(wiremock::matchers::body_partial_json(serde_json::json!({
+            "action": "guiBrowse",
+            "params": {
+                "query": "tag:leech",
+                "reorderCards": {"order": "descending", "columnId": "noteCrt"}
+            }
+        })))
+        .respond_with(mock_anki_response(vec![1234567890_i64]))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let result = client
+        .gui()
+        .browse_sorted("tag:leech", "noteCrt", SortOrder::Descending)
+        .await
+        .unwrap();
+    assert_eq!(result, vec![1234567890]);
+}
+
+#[tokio::test]
+async fn test_gui_select_note() {
+    let server = setup_mock_server().await;
+    let client = AnkiClient::builder().url(server.uri()).build();
+
+    mock_action(&server, "guiSelectNote", mock_anki_response(true)).await;
+
+    let result = client.gui().select_note(1234567890).await.unwrap();
+    assert!(result);
+}
+
+#[tokio::test]
+async fn test_gui_review_active() {
+    let server = setup_mock_server().await;
+    let client = AnkiClient::builder().url(server.uri()).build();
+
+    mock_action(&server, "guiReviewActive", mock_anki_response(false)).await;
+
+    let result = client.gui().review_active().await.unwrap();
+    assert!(!result);
+}
+
+#[tokio::test]
+async fn test_gui_wait_for_card_change() {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    let server = ankit::testing::MockAnkiServer::start().await;
+    let polls = Arc::new(AtomicUsize::new(0));
+    server.handle("guiCurrentCard", {
+        let polls = polls.clone();
+        move |_| {
+            // The first card stays up for two polls, then the next one shows
+            let card_id = if polls.fetch_add(1, Ordering::SeqCst) < 2 {
+                1
+            } else {
+                2
+            };
+            Ok(serde_json::json!({
+                "cardId": card_id, "noteId": 10, "deckId": 1, "modelId": 100,
+                "fields": {}, "question": "Q", "answer": "A",
+                "deckName": "Default", "modelName": "Basic", "templateName": "Card 1",
+                "buttons": [1, 2, 3, 4], "nextReviews": ["<1m", "<10m", "1d", "4d"]
+            }))
+        }
+    });
+    let client = server.client();
+    let gui = client.gui();
+    let interval = Duration::from_millis(10);
+
+    let card = gui
+        .wait_for_card_change(None, interval, Duration::from_secs(5))
+        .await;
+    assert_eq!(card.unwrap().unwrap().card_id, 1);
+
+    let card = gui
+        .wait_for_card_change(Some(1), interval, Duration::from_secs(5))
+        .await;
+    assert_eq!(card.unwrap().unwrap().card_id, 2);
+    assert_eq!(polls.load(Ordering::SeqCst), 3);
+
+    let card = gui
+        .wait_for_card_change(Some(2), interval, Duration::from_millis(50))
+        .await;
+    assert!(card.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_gui_review_session() {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicI64, Ordering};
+
+    let server = ankit::testing::MockAnkiServer::start().await;
+    // Cards 1 and 2 are due; answering shows the next one
+    let current = Arc::new(AtomicI64::new(1));
+    server.handle("guiCurrentCard", {
+        let current = current.clone();
+        move |_| {
+            let card_id = current.load(Ordering::SeqCst);
+            if card_id > 2 {
+                return Ok(serde_json::Value::Null);
+            }
+            Ok(serde_json::json!({
+                "cardId": card_id, "noteId": 10, "deckId": 1, "modelId": 100,
+                "fields": {}, "question": "Q", "answer": "A",
+                "deckName": "Default", "modelName": "Basic", "templateName": "Card 1",
+                "buttons": [1, 2, 3, 4], "nextReviews": ["<1m", "<10m", "1d", "4d"]
+            }))
+        }
+    });
+    server.handle("guiAnswerCard", {
+        let current = current.clone();
+        move |_| {
+            current.fetch_add(1, Ordering::SeqCst);
+            Ok(serde_json::json!(true))
+        }
+    });
+    server
+        .respond("guiShowQuestion", serde_json::json!(true))
+        .respond("guiShowAnswer", serde_json::json!(true))
+        .respond("guiStartCardTimer", serde_json::json!(true));
+    let client = server.client();
+
+    let mut answered = Vec::new();
+    let mut session = client.gui().review_session().await.unwrap();
+    while let Some(question) = session {
+        let answer = question.show_answer().await.unwrap();
+        assert!(answer.question_time() <= answer.elapsed());
+        let step = answer.answer(ankit::Ease::Good).await.unwrap();
+        assert!(step.answered.total_time() >= step.answered.question_time);
+        answered.push(step.answered.card_id);
+        session = step.next;
+    }
+
+    assert_eq!(answered, vec![1, 2]);
+    let answers = server.requests_for("guiAnswerCard");
+    assert_eq!(answers.len(), 2);
+    assert_eq!(answers[0]["params"]["ease"], 3);
+    assert_eq!(server.requests_for("guiStartCardTimer").len(), 2);
+}
+
+#[tokio::test]
+async fn test_gui_review_session_closed() {
+    let server = ankit::testing::MockAnkiServer::start().await;
+    server.respond("guiCurrentCard", serde_json::Value::Null);
+    let client = server.client();
+    assert!(client.gui().review_session().await.unwrap().is_none());
+
+    server
+        .respond(
+            "guiCurrentCard",
+            serde_json::json!({
+                "cardId": 1, "noteId": 10, "deckId": 1, "modelId": 100,
+                "fields": {}, "question": "Q", "answer": "A",
+                "deckName": "Default", "modelName": "Basic", "templateName": "Card 1",
+                "buttons": [1, 2, 3, 4], "nextReviews": ["<1m", "<10m", "1d", "4d"]
+            }),
+        )
+        .respond("guiShowQuestion", serde_json::json!(true))
+        .respond("guiStartCardTimer", serde_json::json!(true))
+        // The user closed the reviewer
+        .respond("guiShowAnswer", serde_json::json!(false));
+    let question = client.gui().review_session().await.unwrap().unwrap();
+    assert_eq!(question.card().card_id, 1);
+    assert!(matches!(
+        question.show_answer().await,
+        Err(ankit::Error::ReviewerClosed(step)) if step == "show the answer"
+    ));
+}
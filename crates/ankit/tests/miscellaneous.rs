@@ -73,6 +73,17 @@ async fn test_load_profile() {
     assert!(result);
 }
 
+#[tokio::test]
+async fn test_active_profile() {
+    let server = setup_mock_server().await;
+    let client = AnkiClient::builder().url(server.uri()).build();
+
+    mock_action(&server, "getActiveProfile", mock_anki_response("Work")).await;
+
+    let result = client.misc().active_profile().await.unwrap();
+    assert_eq!(result, "Work");
+}
+
 #[tokio::test]
 async fn test_request_permission() {
     let server = setup_mock_server().await;
@@ -179,6 +179,61 @@ async fn test_model_templates() {
     assert_eq!(template.front, "{{Front}}");
 }
 
+#[tokio::test]
+async fn test_render_card() {
+    let server = setup_mock_server().await;
+    let client = AnkiClient::builder().url(server.uri()).build();
+
+    mock_action(
+        &server,
+        "modelTemplates",
+        mock_anki_response(serde_json::json!({
+            "Card 1": {
+                "Front": "{{Front}}<br>{{Card}}",
+                "Back": "{{FrontSide}}<hr>{{Back}}{{#Extra}} ({{Extra}}){{/Extra}}"
+            }
+        })),
+    )
+    .await;
+    mock_action(
+        &server,
+        "modelStyling",
+        mock_anki_response(serde_json::json!({ "css": ".card { color: black; }" })),
+    )
+    .await;
+
+    let context = ankit::render::RenderContext::new([("Front", "hola"), ("Back", "hello")]);
+    let card = client
+        .models()
+        .render("Basic", "Card 1", context)
+        .await
+        .unwrap();
+
+    assert_eq!(card.question, "hola<br>Card 1");
+    assert_eq!(card.answer, "hola<br>Card 1<hr>hello");
+    assert!(card.question_page().contains(".card { color: black; }"));
+}
+
+#[tokio::test]
+async fn test_render_card_unknown_template() {
+    let server = setup_mock_server().await;
+    let client = AnkiClient::builder().url(server.uri()).build();
+
+    mock_action(
+        &server,
+        "modelTemplates",
+        mock_anki_response(serde_json::json!({
+            "Card 1": { "Front": "{{Front}}", "Back": "{{Back}}" }
+        })),
+    )
+    .await;
+
+    let context = ankit::render::RenderContext::default();
+    let result = client.models().render("Basic", "Card 2", context).await;
+
+    assert!(matches!(result, Err(ankit::Error::AnkiConnect(_))));
+}
+
 #[tokio::test]
 async fn test_update_styling() {
     let server = setup_mock_server().await;
@@ -103,8 +103,7 @@ async fn test_duplicate_note_error() {
 
     let result = client.notes().add(note).await;
 
-    assert!(result.is_err());
-    assert!(result.unwrap_err().to_string().contains("duplicate"));
+    assert!(matches!(result, Err(ankit::Error::DuplicateNote)));
 }
 
 #[test]
@@ -522,3 +521,20 @@ async fn test_all_tags() {
     assert!(tags.contains(&"vocabulary".to_string()));
     assert!(tags.contains(&"grammar".to_string()));
 }
+
+#[tokio::test]
+async fn test_tag_tree() {
+    let server = setup_mock_server().await;
+    mock_action(
+        &server,
+        "getTags",
+        mock_anki_response(vec!["lang::jp::verb", "lang::es", "leech"]),
+    )
+    .await;
+
+    let client = AnkiClient::builder().url(server.uri()).build();
+    let tree = client.notes().tag_tree().await.unwrap();
+
+    assert_eq!(tree.roots(), vec!["lang", "leech"]);
+    assert_eq!(tree.children("lang"), vec!["lang::es", "lang::jp"]);
+}
@@ -0,0 +1,70 @@
+//! Tests for the fake AnkiConnect server in `ankit::testing`.
+
+use std::sync::{Arc, Mutex};
+
+use ankit::testing::MockAnkiServer;
+use ankit::{Error, MultiAction};
+
+#[tokio::test]
+async fn test_decks_are_stateful() {
+    let server = MockAnkiServer::start().await;
+    server.with_decks(["Spanish"]);
+    let client = server.client();
+
+    let id = client.decks().create("Japanese").await.unwrap();
+    assert_eq!(
+        client.decks().names().await.unwrap(),
+        vec!["Default", "Japanese", "Spanish"]
+    );
+    assert_eq!(
+        client.decks().names_and_ids().await.unwrap()["Japanese"],
+        id
+    );
+
+    client.decks().delete(&["Spanish"], true).await.unwrap();
+    assert_eq!(server.decks(), vec!["Default", "Japanese"]);
+    assert_eq!(
+        server.requests_for("deleteDecks")[0]["params"]["decks"],
+        serde_json::json!(["Spanish"])
+    );
+}
+
+#[tokio::test]
+async fn test_canned_responses_and_errors() {
+    let server = MockAnkiServer::start().await;
+    server
+        .respond("getProfiles", serde_json::json!(["User 1"]))
+        .error("deckNames", "collection is not available");
+    let client = server.client();
+
+    assert_eq!(client.misc().profiles().await.unwrap(), vec!["User 1"]);
+    assert!(client.decks().names().await.is_err());
+    assert!(matches!(
+        client.misc().active_profile().await,
+        Err(Error::AnkiConnect(msg)) if msg == "unsupported action"
+    ));
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn test_handlers_and_multi() {
+    let server = MockAnkiServer::start().await;
+    let synced = Arc::new(Mutex::new(0));
+    server.handle("sync", {
+        let synced = synced.clone();
+        move |_| {
+            *synced.lock().unwrap() += 1;
+            Ok(serde_json::Value::Null)
+        }
+    });
+    let client = server.client();
+
+    client.misc().sync().await.unwrap();
+    let results = client
+        .misc()
+        .multi(&[MultiAction::new("version"), MultiAction::new("sync")])
+        .await
+        .unwrap();
+    assert_eq!(results[0]["result"], 6);
+    assert_eq!(*synced.lock().unwrap(), 2);
+}